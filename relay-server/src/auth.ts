@@ -0,0 +1,79 @@
+import { createHmac, timingSafeEqual } from 'crypto';
+
+/**
+ * Host authentication. The relay operator can hand out either:
+ * - API keys, configured as `RELAY_HOST_KEYS=account=key,other=key2`
+ * - signed tokens `<account>.<expires unix secs>.<hex HMAC-SHA256>`, checked
+ *   against `RELAY_TOKEN_SECRET` (see `npm run issue-token`)
+ * With neither configured the relay is open and hosts need no credentials.
+ */
+
+/** Account names are the first field of a signed token, so they can't contain dots */
+export function validAccountName(account: string): boolean {
+  return account.length > 0 && !account.includes('.');
+}
+
+const hostKeys = new Map<string, string>(
+  (process.env.RELAY_HOST_KEYS || '')
+    .split(',')
+    .map(entry => entry.trim())
+    .filter(entry => entry.includes('='))
+    .map(entry => {
+      // Split on the first '=' only: base64 keys end in '=' padding
+      const separator = entry.indexOf('=');
+      const account = entry.slice(0, separator).trim();
+      const key = entry.slice(separator + 1).trim();
+      return [key, account] as [string, string];
+    })
+    .filter(([key, account]) => {
+      if (!key || !account) return false;
+      if (!validAccountName(account)) {
+        console.warn(`Ignoring RELAY_HOST_KEYS entry for account "${account}": account names may not contain dots`);
+        return false;
+      }
+      return true;
+    })
+);
+
+const tokenSecret = process.env.RELAY_TOKEN_SECRET || '';
+
+export function authRequired(): boolean {
+  return hostKeys.size > 0 || tokenSecret.length > 0;
+}
+
+function sign(payload: string, secret: string): string {
+  return createHmac('sha256', secret).update(payload).digest('hex');
+}
+
+function constantTimeEquals(a: string, b: string): boolean {
+  const left = Buffer.from(a);
+  const right = Buffer.from(b);
+  return left.length === right.length && timingSafeEqual(left, right);
+}
+
+export function issueHostToken(account: string, expiresAt: number, secret: string): string {
+  if (!validAccountName(account)) {
+    throw new Error(`Invalid account name "${account}": account names may not contain dots`);
+  }
+  const payload = `${account}.${expiresAt}`;
+  return `${payload}.${sign(payload, secret)}`;
+}
+
+/** Account a host credential belongs to, or null if it isn't valid */
+export function authenticateHost(token: unknown): string | null {
+  if (typeof token !== 'string' || !token) return null;
+
+  for (const [key, account] of hostKeys) {
+    if (constantTimeEquals(token, key)) return account;
+  }
+
+  if (!tokenSecret) return null;
+  const parts = token.split('.');
+  if (parts.length !== 3) return null;
+  const [account, expires, signature] = parts;
+  if (!account || !constantTimeEquals(signature, sign(`${account}.${expires}`, tokenSecret))) {
+    return null;
+  }
+  if (!(Number(expires) > Date.now() / 1000)) return null;
+  return account;
+}
@@ -0,0 +1,14 @@
+import { issueHostToken, validAccountName } from './auth';
+
+// Usage: RELAY_TOKEN_SECRET=... npm run issue-token -- <account> [days valid, default 365]
+const [account, days = '365'] = process.argv.slice(2);
+const secret = process.env.RELAY_TOKEN_SECRET;
+
+if (!account || !secret || !validAccountName(account) || !(Number(days) > 0)) {
+  console.error('Usage: RELAY_TOKEN_SECRET=<secret> npm run issue-token -- <account> [days]');
+  console.error('Account names may not contain dots.');
+  process.exit(1);
+}
+
+const expiresAt = Math.floor(Date.now() / 1000) + Math.round(Number(days) * 24 * 60 * 60);
+console.log(issueHostToken(account, expiresAt, secret));
@@ -1,15 +1,29 @@
 import express from 'express';
-import { createServer } from 'http';
+import { createServer, IncomingMessage } from 'http';
 import { WebSocketServer, WebSocket } from 'ws';
 import path from 'path';
 import { v4 as uuidv4 } from 'uuid';
+import { authenticateHost, authRequired } from './auth';
 
 const app = express();
 const server = createServer(app);
-const wss = new WebSocketServer({ server });
+/** Largest frame accepted from anyone; hosts publish the web client's files through it */
+const MAX_PAYLOAD_BYTES = 16 * 1024 * 1024;
+/** Largest message a participant may send; matches MAX_CLIENT_MESSAGE_BYTES in the desktop app */
+const MAX_PARTICIPANT_MESSAGE_BYTES = 16 * 1024;
+/** Deepest nesting of arrays and objects accepted; matches MAX_JSON_DEPTH in the desktop app */
+const MAX_JSON_DEPTH = 32;
+const wss = new WebSocketServer({ server, maxPayload: MAX_PAYLOAD_BYTES });
 
 const PORT = process.env.PORT || 8070;
 const RELAY_URL = process.env.RELAY_URL || 'https://scrum-poker-hydra.ngrok.dev';
+/** Version of the host <-> relay protocol; matches RELAY_PROTOCOL_VERSION in the desktop app */
+const PROTOCOL_VERSION = 1;
+const MAX_ROOMS_PER_HOST = Number(process.env.MAX_ROOMS_PER_HOST) || 50;
+/** Matches DEFAULT_DISCONNECT_GRACE_SECS in the desktop app */
+const DEFAULT_DISCONNECT_GRACE_SECS = 60;
+/** How far ahead of the reveal broadcast clients are told to flip the cards */
+const REVEAL_DELAY_MS = 500;
 
 // Types
 interface Room {
@@ -18,15 +32,83 @@ interface Room {
   invite_code: string;
   participants: Participant[];
   votes_revealed: boolean;
+  /** When clients should flip the cards over (Unix ms), shortly after the reveal */
+  reveal_at: number | null;
+  /** Participants whose votes have been shown so far in a one-by-one reveal */
+  reveal_progress: string[] | null;
+  /** Votes handed to someone else for this round: delegator ID -> delegate ID */
+  delegations: Record<string, string>;
+  /** Concerns flagged about the current ticket this round, keyed by participant ID */
+  signals: Record<string, TicketSignal[]>;
   current_ticket: JiraTicket | null;
+  /** Tickets voted on together in a triage round; empty otherwise */
+  batch: JiraTicket[];
+  /** Results of a re-estimation campaign, without the old estimates; null outside one */
+  campaign: unknown;
+  /** ticket_hash last sent to participants; undefined until the first room_update */
+  sent_ticket_hash?: string | null;
+  deck: string[];
+  /** Cards for the second (risk/uncertainty) vote; null when the room only votes on effort */
+  risk_deck: string[] | null;
+  /** Set by the host; no new participants may join */
+  locked: boolean;
+  /** The story is being discussed; votes are turned away until the host opens voting */
+  discussing: boolean;
+  /** When voting opens after a discussion (Unix ms), for the countdown */
+  voting_opens_at: number | null;
+  /** Participants don't see who has voted until everyone has or the votes are revealed */
+  hide_voters: boolean;
+  /** How full the sprint is with the session's estimates, computed by the host; null when not tracked */
+  sprint_fill: unknown;
+  /** Announced start of voting (Unix seconds), for the join page countdown */
+  starts_at: number | null;
+  /** Zone the host shows the room's times in */
+  time_zone: RoomTimeZone | null;
+  /** Seconds a dropped participant is kept (greyed out, with their vote) before removal */
+  disconnect_grace_secs: number;
+  /** Participant ID per client identity token, so someone rejoining gets their place back. Never sent out. */
+  identities: Map<string, string>;
+  /** Bumped on every room_update so clients and the host can spot missed updates */
+  revision: number;
   host_ws: WebSocket | null;
+  /** Account of the host that owns the room, when the relay requires authentication */
+  account: string | null;
 }
 
+/** Same as TicketSignal in the desktop app */
+const TICKET_SIGNALS = ['unclear_requirements', 'needs_design', 'security_review'] as const;
+type TicketSignal = typeof TICKET_SIGNALS[number];
+
+// Matches the desktop app's default deck: STORY_POINTS plus the abstain card
+const DEFAULT_DECK = ['?', '☕', '0', '0.5', '1', '2', '3', '5', '8', '13', '20', '40', '100', 'abstain'];
+
 interface Participant {
   id: string;
   name: string;
   vote: string | null;
+  risk_vote?: string | null;
   is_host: boolean;
+  color?: string;
+  /** Coarse client details, only if the participant opted in to sharing them */
+  client?: ClientInfo;
+  /** Unix seconds when the participant's connection dropped */
+  disconnected_at?: number;
+  /** Left out of the voting until they're back */
+  sitting_out?: boolean;
+  /** Joined to watch; never votes and isn't waited on */
+  is_observer?: boolean;
+  /** Unix ms of the last change to the vote, risk vote or sitting out; the host merges by it */
+  updated_at?: number;
+}
+
+interface RoomTimeZone {
+  /** IANA name, e.g. "Europe/Berlin" */
+  name: string;
+}
+
+interface ClientInfo {
+  kind: 'browser' | 'desktop' | 'tui' | 'other';
+  user_agent_family: string | null;
 }
 
 interface JiraTicket {
@@ -36,6 +118,10 @@ interface JiraTicket {
   issue_type: string | null;
   status: string | null;
   url: string;
+  /** Size hints computed by the desktop app; passed through as-is */
+  complexity?: unknown;
+  /** Pull request size, for GitHub items; passed through as-is */
+  diff_stats?: unknown;
 }
 
 interface ClientConnection {
@@ -43,12 +129,42 @@ interface ClientConnection {
   type: 'host' | 'participant';
   roomId: string | null;
   participantId: string | null;
+  // Participants speaking the host protocol are bridged straight through to the host
+  bridged: boolean;
+  connectionId: string | null;
+  userAgent: string | null;
+  /** Account a host authenticated as */
+  account: string | null;
+  /** Web client this host published, keyed by path; only served for the host's own rooms */
+  publishedClient: Map<string, PublishedFile> | null;
+  /** Room (ID or invite code) from a `/ws/<room>` URL; participants can't join any other */
+  boundRoom: string | null;
+}
+
+interface PublishedFile {
+  content_type: string;
+  data: Buffer;
 }
 
 // State
 const rooms = new Map<string, Room>();
 const connections = new Map<WebSocket, ClientConnection>();
 const inviteCodeToRoom = new Map<string, string>();
+/**
+ * Files a host may publish: the page and the bundle Vite builds next to it.
+ * Content types come from here, not from the host.
+ */
+const PUBLISHABLE_FILE = /^(index\.html|[\w-]+\.(svg|png|ico)|assets\/[\w.-]+\.(js|css|svg|png|woff2?))$/;
+const PUBLISHED_CONTENT_TYPES: Record<string, string> = {
+  html: 'text/html; charset=utf-8',
+  js: 'text/javascript; charset=utf-8',
+  css: 'text/css; charset=utf-8',
+  svg: 'image/svg+xml',
+  png: 'image/png',
+  ico: 'image/x-icon',
+  woff: 'font/woff',
+  woff2: 'font/woff2',
+};
 
 // Generate invite code (three random words style)
 function generateInviteCode(): string {
@@ -65,21 +181,107 @@ function generateInviteCode(): string {
 }
 
 // Broadcast room update to all participants in a room
-function broadcastRoomUpdate(roomId: string) {
-  const room = rooms.get(roomId);
-  if (!room) return;
+/** 32-bit FNV-1a, matching fnv1a in the desktop app's room.rs */
+function fnv1a(text: string): number {
+  let hash = 0x811c9dc5;
+  for (const byte of Buffer.from(text, 'utf8')) {
+    hash = Math.imul(hash ^ byte, 0x01000193) >>> 0;
+  }
+  return hash;
+}
+
+/** Same hash as JiraTicket::content_hash in the desktop app */
+function ticketHash(ticket: JiraTicket | null): string | null {
+  if (!ticket) return null;
+  const fields = [ticket.key, ticket.summary, ticket.description, ticket.issue_type, ticket.status, ticket.url];
+  return fnv1a(fields.map(field => field ?? '').join('\u0001')).toString(16).padStart(8, '0');
+}
+
+/** Same hash as Room::checksum in the desktop app, so the two copies can be compared */
+function roomChecksum(room: Room): string {
+  const participants = [...room.participants].sort((a, b) => (a.id < b.id ? -1 : a.id > b.id ? 1 : 0));
+  let canonical = '';
+  for (const p of participants) {
+    canonical += `${p.id}\u0001${p.name}\u0001${p.vote ?? ''}\u0001${p.risk_vote ?? ''}\u0002`;
+  }
+  canonical += `${room.votes_revealed ? '1' : '0'}\u0002${ticketHash(room.current_ticket) ?? ''}`;
+  return fnv1a(canonical).toString(16).padStart(8, '0');
+}
 
-  const message = JSON.stringify({
+/** Same rule as Room::voters_hidden in the desktop app */
+function votersHidden(room: Room): boolean {
+  if (!room.hide_voters || room.votes_revealed || room.reveal_progress !== null) return false;
+  const voted = (p: Participant) =>
+    p.vote !== null || room.participants.some(d => d.id === room.delegations[p.id] && d.vote !== null);
+  return !room.participants.filter(p => !p.sitting_out && !p.is_observer).every(voted);
+}
+
+/**
+ * Participants as `viewerId` may see them, like Room::view_for in the desktop app: while voters
+ * are hidden, everyone else's votes are left out. Nobody sees when others last changed their
+ * vote; that's only for the host's merge, and would show who is voting.
+ */
+function visibleParticipants(room: Room, viewerId: string | null): Participant[] {
+  const hidden = votersHidden(room);
+  return room.participants.map(p => {
+    if (p.id === viewerId) return p;
+    return hidden ? { ...p, vote: null, risk_vote: null, updated_at: undefined } : { ...p, updated_at: undefined };
+  });
+}
+
+/** Room update for a participant, or for the host (who sees everything) when `viewerId` is left out */
+function roomUpdateMessage(room: Room, viewerId?: string | null): string {
+  const participants = viewerId === undefined ? room.participants : visibleParticipants(room, viewerId);
+  return JSON.stringify({
     type: 'room_update',
+    checksum: roomChecksum({ ...room, participants }),
     room: {
       id: room.id,
       name: room.name,
       invite_code: room.invite_code,
-      participants: room.participants,
+      participants,
       votes_revealed: room.votes_revealed,
-      current_ticket: room.current_ticket,
-    }
+      reveal_at: room.reveal_at,
+      reveal_progress: room.reveal_progress,
+      delegations: room.delegations,
+      // Only the host sees who raised what; participants see their own
+      signals: viewerId === undefined
+        ? room.signals
+        : Object.fromEntries(Object.entries(room.signals).filter(([id]) => id === viewerId)),
+      // Ticket details go out in ticket_changed; updates only carry the hash
+      ticket_hash: ticketHash(room.current_ticket),
+      batch: room.batch,
+      campaign: room.campaign,
+      deck: room.deck,
+      risk_deck: room.risk_deck,
+      sprint_fill: room.sprint_fill,
+      discussing: room.discussing,
+      voting_opens_at: room.voting_opens_at,
+      revision: room.revision,
+    },
+    // Timestamps in the room are on this relay's clock
+    server_time: Date.now(),
+  });
+}
+
+function ticketChangedMessage(room: Room): string {
+  return JSON.stringify({
+    type: 'ticket_changed',
+    ticket: room.current_ticket,
+    ticket_hash: ticketHash(room.current_ticket),
   });
+}
+
+function broadcastRoomUpdate(roomId: string) {
+  const room = rooms.get(roomId);
+  if (!room) return;
+
+  room.revision += 1;
+  const message = roomUpdateMessage(room);
+  const perViewer = votersHidden(room) || Object.keys(room.signals).length > 0;
+  const hash = ticketHash(room.current_ticket);
+  const ticketChanged = (room.sent_ticket_hash ?? null) !== hash;
+  room.sent_ticket_hash = hash;
 
   // Send to host
   if (room.host_ws && room.host_ws.readyState === WebSocket.OPEN) {
@@ -89,13 +291,14 @@ function broadcastRoomUpdate(roomId: string) {
   // Send to all participants
   connections.forEach((conn, ws) => {
     if (conn.roomId === roomId && conn.type === 'participant' && ws.readyState === WebSocket.OPEN) {
-      ws.send(message);
+      if (ticketChanged) ws.send(ticketChangedMessage(room));
+      ws.send(perViewer ? roomUpdateMessage(room, conn.participantId) : message);
     }
   });
 }
 
 // WebSocket handling
-wss.on('connection', (ws: WebSocket) => {
+wss.on('connection', (ws: WebSocket, req: IncomingMessage) => {
   console.log('New WebSocket connection');
   
   const conn: ClientConnection = {
@@ -103,12 +306,25 @@ wss.on('connection', (ws: WebSocket) => {
     type: 'participant',
     roomId: null,
     participantId: null,
+    bridged: false,
+    connectionId: null,
+    userAgent: req.headers['user-agent'] ?? null,
+    account: null,
+    publishedClient: null,
+    boundRoom: boundRoomFromUrl(req.url),
   };
   connections.set(ws, conn);
 
   ws.on('message', (data: Buffer) => {
+    const text = data.toString();
+    const tooLarge = conn.type !== 'host' && data.length > MAX_PARTICIPANT_MESSAGE_BYTES;
+    if (tooLarge || exceedsJsonDepth(text, MAX_JSON_DEPTH)) {
+      console.warn(`Closing ${conn.type} connection: message too large or too deeply nested`);
+      ws.close(1009, 'Message too large');
+      return;
+    }
     try {
-      const message = JSON.parse(data.toString());
+      const message = JSON.parse(text);
       handleMessage(ws, conn, message);
     } catch (e) {
       console.error('Failed to parse message:', e);
@@ -126,13 +342,56 @@ wss.on('connection', (ws: WebSocket) => {
   });
 });
 
+/**
+ * Room a participant connection is bound to, from its `/ws/<room id or invite code>` URL.
+ * Hosts connect to the root URL and aren't bound to a room.
+ */
+function boundRoomFromUrl(url: string | undefined): string | null {
+  const match = (url ?? '/').match(/^\/ws\/([^/?#]+)/);
+  if (!match) return null;
+  try {
+    return decodeURIComponent(match[1]);
+  } catch {
+    return null;
+  }
+}
+
+function hostRoomCount(ws: WebSocket): number {
+  let count = 0;
+  rooms.forEach(room => {
+    if (room.host_ws === ws) count++;
+  });
+  return count;
+}
+
+/** Report a rejected host request; codes match `RelayErrorCode` in the desktop app */
+function sendHostError(ws: WebSocket, code: string, message: string) {
+  ws.send(JSON.stringify({ type: 'error', code, message }));
+}
+
 function handleMessage(ws: WebSocket, conn: ClientConnection, message: any) {
   console.log('Received message:', message.type);
 
+  // Bridged participants: everything goes to the host untouched
+  if (conn.bridged) {
+    forwardToHost(conn, message);
+    return;
+  }
+  if (message.type === 'Join') {
+    handleBridgedJoin(ws, conn, message);
+    return;
+  }
+
+  if (typeof message.type === 'string' && message.type.startsWith('host_')
+      && message.type !== 'host_register' && conn.type !== 'host') {
+    sendHostError(ws, 'unauthorized', 'Register as a host before sending host messages');
+    return;
+  }
+
   switch (message.type) {
     // Host messages
     case 'host_register':
-      handleHostRegister(ws, conn);
+      handleHostRegister(ws, conn, message);
       break;
 
     case 'host_create_room':
@@ -155,6 +414,10 @@ function handleMessage(ws: WebSocket, conn: ClientConnection, message: any) {
       handleHostRevealVotes(conn, message.room_id, false);
       break;
 
+    case 'host_reveal_vote':
+      handleHostRevealVote(conn, message.room_id, message.participant_id);
+      break;
+
     case 'host_reset_votes':
       handleHostResetVotes(conn, message.room_id);
       break;
@@ -163,14 +426,30 @@ function handleMessage(ws: WebSocket, conn: ClientConnection, message: any) {
       handleHostKickParticipant(conn, message.room_id, message.participant_id);
       break;
 
+    case 'host_set_sitting_out':
+      handleHostSetSittingOut(conn, message.room_id, message.participant_id, !!message.sitting_out);
+      break;
+
     case 'host_set_ticket':
-      handleHostSetTicket(conn, message.room_id, message.ticket);
+      handleHostSetTicket(conn, message.room_id, message.ticket, message.deck);
       break;
 
     case 'host_clear_ticket':
       handleHostClearTicket(conn, message.room_id);
       break;
 
+    case 'host_resync_room':
+      handleResync(conn, message.room_id);
+      break;
+
+    case 'host_participant_message':
+      handleHostParticipantMessage(conn, message.connection_id, message.message);
+      break;
+
+    case 'host_publish_client':
+      handleHostPublishClient(conn, message.files);
+      break;
+
     // Participant messages
     case 'join':
       handleParticipantJoin(ws, conn, message);
@@ -180,47 +459,198 @@ function handleMessage(ws: WebSocket, conn: ClientConnection, message: any) {
       handleParticipantVote(conn, message.vote);
       break;
 
+    case 'delegate':
+      handleParticipantDelegate(conn, message.to ?? null);
+      break;
+
+    case 'sit_out':
+      if (conn.roomId && conn.participantId) {
+        setSittingOut(conn.roomId, conn.participantId, !!message.sitting_out);
+      }
+      break;
+
+    case 'risk_vote':
+      handleParticipantRiskVote(conn, message.vote);
+      break;
+
+    case 'signal':
+      handleParticipantSignal(conn, message.signal, !!message.raised);
+      break;
+
+    case 'resync':
+      handleResync(conn, conn.roomId);
+      break;
+
+    case 'time_sync':
+      if (typeof message.client_time === 'number') {
+        ws.send(JSON.stringify({ type: 'time_sync_reply', client_time: message.client_time, server_time: Date.now() }));
+      }
+      break;
+
     case 'ping':
       ws.send(JSON.stringify({ type: 'pong' }));
       break;
   }
 }
 
-function handleHostRegister(ws: WebSocket, conn: ClientConnection) {
+/** Whether JSON text nests arrays and objects deeper than maxDepth; same as the desktop app's check */
+function exceedsJsonDepth(text: string, maxDepth: number): boolean {
+  let depth = 0;
+  let inString = false;
+  let escaped = false;
+  for (const ch of text) {
+    if (inString) {
+      if (escaped) escaped = false;
+      else if (ch === '\\') escaped = true;
+      else if (ch === '"') inString = false;
+      continue;
+    }
+    if (ch === '"') inString = true;
+    else if (ch === '[' || ch === '{') {
+      if (++depth > maxDepth) return true;
+    } else if (ch === ']' || ch === '}') depth = Math.max(0, depth - 1);
+  }
+  return false;
+}
+
+/** Resend the full room state to a host or participant that noticed a desync */
+function handleResync(conn: ClientConnection, roomId: string | null) {
+  const room = roomId ? rooms.get(roomId) : undefined;
+  if (!room) return;
+  const allowed = conn.type === 'host' ? room.host_ws === conn.ws : conn.roomId === room.id;
+  if (!allowed) return;
+
+  console.warn(`Resync of room ${room.name} requested by ${conn.type} at revision ${room.revision}`);
+  if (conn.type === 'participant') conn.ws.send(ticketChangedMessage(room));
+  conn.ws.send(conn.type === 'participant' ? roomUpdateMessage(room, conn.participantId) : roomUpdateMessage(room));
+}
+
+function handleHostRegister(ws: WebSocket, conn: ClientConnection, message: any) {
+  if (conn.type === 'host') {
+    sendHostError(ws, 'host_already_registered', 'This connection is already registered as a host');
+    return;
+  }
+  // Hosts that predate versioning don't send one; they speak version 1
+  const version = message.protocol_version ?? 1;
+  if (version !== PROTOCOL_VERSION) {
+    sendHostError(ws, 'protocol_version_mismatch',
+      `Relay speaks protocol version ${PROTOCOL_VERSION}, host sent ${version}`);
+    return;
+  }
+
+  if (authRequired()) {
+    const account = authenticateHost(message.token);
+    if (!account) {
+      sendHostError(ws, 'unauthorized', message.token ? 'Invalid or expired host token' : 'This relay requires a host token');
+      return;
+    }
+    conn.account = account;
+  }
+
   conn.type = 'host';
-  console.log('Host registered');
+  console.log(conn.account ? `Host registered (account ${conn.account})` : 'Host registered');
   
-  // Send current rooms to host
+  // Send current rooms to host, including the ones it had before losing its
+  // connection, so it can merge what happened here while it was away
+  const roomIds: string[] = Array.isArray(message.room_ids) ? message.room_ids : [];
   const hostRooms = Array.from(rooms.values())
-    .filter(r => r.host_ws === ws)
+    .filter(r => r.host_ws === ws || (roomIds.includes(r.id) && canTakeOver(r, ws, conn)))
     .map(r => ({
       id: r.id,
       name: r.name,
       invite_code: r.invite_code,
       participants: r.participants,
       votes_revealed: r.votes_revealed,
+      delegations: r.delegations,
       current_ticket: r.current_ticket,
+      deck: r.deck,
+      risk_deck: r.risk_deck,
+      revision: r.revision,
     }));
 
   ws.send(JSON.stringify({
     type: 'host_registered',
     rooms: hostRooms,
     relay_url: RELAY_URL,
+    join_urls: Object.fromEntries(hostRooms.map(r => [r.id, joinUrl(r)])),
   }));
 }
 
+/** Link that takes participants straight into a room's join page on this relay */
+function joinUrl(room: { id: string }): string {
+  return `${RELAY_URL.replace(/\/$/, '')}/r/${encodeURIComponent(room.id)}/`;
+}
+
+/**
+ * Whether a host connection may take a room over. Without accounts, a room can't be
+ * taken over while its host is still connected. With them, the same account may take
+ * over, e.g. after a half-open disconnect.
+ */
+function canTakeOver(room: Room, ws: WebSocket, conn: ClientConnection): boolean {
+  if (room.account) return room.account === conn.account;
+  return !room.host_ws || room.host_ws === ws || room.host_ws.readyState !== WebSocket.OPEN;
+}
+
+/**
+ * Move a timestamp from the host's clock onto this relay's, which is the one
+ * participants sync with. `hostSentAt` is the host's clock when it sent the
+ * room; the shift includes the time in transit, so countdowns end a moment
+ * late rather than early. Older hosts don't send it and are taken as they are.
+ */
+function fromHostClock(timestamp: number | null, hostSentAt: unknown): number | null {
+  if (timestamp === null || typeof hostSentAt !== 'number') return timestamp;
+  return timestamp + (Date.now() - hostSentAt);
+}
+
 function handleHostSyncRoom(ws: WebSocket, conn: ClientConnection, message: any) {
   // Host is syncing an existing room - use the provided ID and invite code
   const existingRoom = rooms.get(message.room.id);
+  if (existingRoom?.account && existingRoom.account !== conn.account) {
+    sendHostError(ws, 'unauthorized', `Room ${existingRoom.name} belongs to another account`);
+    return;
+  }
+  if (existingRoom && !canTakeOver(existingRoom, ws, conn)) {
+    sendHostError(ws, 'unauthorized', `Room ${existingRoom.name} is hosted by another connection`);
+    return;
+  }
+  if (!existingRoom && hostRoomCount(ws) >= MAX_ROOMS_PER_HOST) {
+    sendHostError(ws, 'room_limit_reached', `A host may have at most ${MAX_ROOMS_PER_HOST} rooms on this relay`);
+    return;
+  }
   
   const room: Room = {
     id: message.room.id,
     name: message.room.name,
     invite_code: message.room.invite_code,
-    participants: message.room.participants || [],
+    // When votes last changed is compared with the relay's own times in the host's merge
+    participants: (message.room.participants || []).map((p: Participant) => ({
+      ...p,
+      updated_at: p.updated_at ? fromHostClock(p.updated_at, message.sent_at) ?? undefined : undefined,
+    })),
     votes_revealed: message.room.votes_revealed || false,
+    reveal_at: message.room.votes_revealed ? existingRoom?.reveal_at ?? null : null,
+    reveal_progress: message.room.reveal_progress ?? null,
+    delegations: message.room.delegations ?? {},
+    signals: message.room.signals ?? {},
     current_ticket: message.room.current_ticket || null,
+    batch: message.room.batch || [],
+    // Old estimates stay with the host so they can't anchor anyone's vote
+    campaign: message.room.campaign ? { ...message.room.campaign, previous: {} } : null,
+    sent_ticket_hash: existingRoom?.sent_ticket_hash,
+    deck: message.room.deck || DEFAULT_DECK,
+    risk_deck: message.room.risk_deck || null,
+    locked: message.room.locked || false,
+    discussing: message.room.discussing || false,
+    voting_opens_at: fromHostClock(message.room.voting_opens_at ?? null, message.sent_at),
+    hide_voters: message.room.hide_voters || false,
+    sprint_fill: message.room.sprint_fill ?? null,
+    starts_at: message.room.starts_at ?? null,
+    time_zone: message.room.time_zone ?? null,
+    disconnect_grace_secs: message.room.disconnect_grace_secs ?? DEFAULT_DISCONNECT_GRACE_SECS,
+    identities: existingRoom?.identities ?? new Map(),
+    revision: existingRoom?.revision ?? 0,
     host_ws: ws,
+    account: conn.account,
   };
 
   // Clean up old invite code mapping if room existed
@@ -243,11 +673,19 @@ function handleHostSyncRoom(ws: WebSocket, conn: ClientConnection, message: any)
       participants: room.participants,
       votes_revealed: room.votes_revealed,
       current_ticket: room.current_ticket,
-    }
+      deck: room.deck,
+      risk_deck: room.risk_deck,
+    },
+    join_url: joinUrl(room),
   }));
 }
 
 function handleHostCreateRoom(ws: WebSocket, conn: ClientConnection, message: any) {
+  if (hostRoomCount(ws) >= MAX_ROOMS_PER_HOST) {
+    sendHostError(ws, 'room_limit_reached', `A host may have at most ${MAX_ROOMS_PER_HOST} rooms on this relay`);
+    return;
+  }
+
   const roomId = uuidv4();
   const inviteCode = generateInviteCode();
   
@@ -257,8 +695,27 @@ function handleHostCreateRoom(ws: WebSocket, conn: ClientConnection, message: an
     invite_code: inviteCode,
     participants: [],
     votes_revealed: false,
+    reveal_at: null,
+    reveal_progress: null,
+    delegations: {},
+    signals: {},
     current_ticket: null,
+    batch: [],
+    campaign: null,
+    deck: DEFAULT_DECK,
+    risk_deck: null,
+    locked: false,
+    discussing: false,
+    voting_opens_at: null,
+    hide_voters: false,
+    sprint_fill: null,
+    starts_at: null,
+    time_zone: null,
+    disconnect_grace_secs: DEFAULT_DISCONNECT_GRACE_SECS,
+    identities: new Map(),
+    revision: 0,
     host_ws: ws,
+    account: conn.account,
   };
 
   rooms.set(roomId, room);
@@ -275,7 +732,10 @@ function handleHostCreateRoom(ws: WebSocket, conn: ClientConnection, message: an
       participants: room.participants,
       votes_revealed: room.votes_revealed,
       current_ticket: room.current_ticket,
-    }
+      deck: room.deck,
+      risk_deck: room.risk_deck,
+    },
+    join_url: joinUrl(room),
   }));
 }
 
@@ -306,15 +766,63 @@ function handleHostRevealVotes(conn: ClientConnection, roomId: string, revealed:
   if (!room || room.host_ws !== conn.ws) return;
 
   room.votes_revealed = revealed;
+  room.reveal_at = revealed ? Date.now() + REVEAL_DELAY_MS : null;
+  room.reveal_progress = null;
   broadcastRoomUpdate(roomId);
 }
 
+/** Show one more card of a one-by-one reveal; the host decides the order and when it's done */
+function handleHostRevealVote(conn: ClientConnection, roomId: string, participantId: string) {
+  const room = rooms.get(roomId);
+  if (!room || room.host_ws !== conn.ws || typeof participantId !== 'string') return;
+
+  room.reveal_progress = [...(room.reveal_progress ?? []), participantId];
+  const vote = room.participants.find(p => p.id === participantId)?.vote ?? null;
+  const message = JSON.stringify({ type: 'vote_revealed', participant_id: participantId, vote });
+  connections.forEach((c, ws) => {
+    if (c.roomId === roomId && c.type === 'participant' && ws.readyState === WebSocket.OPEN) {
+      ws.send(message);
+    }
+  });
+}
+
 function handleHostResetVotes(conn: ClientConnection, roomId: string) {
   const room = rooms.get(roomId);
   if (!room || room.host_ws !== conn.ws) return;
 
-  room.participants.forEach(p => p.vote = null);
+  const now = Date.now();
+  room.participants.forEach(p => {
+    p.vote = null;
+    p.risk_vote = null;
+    p.updated_at = now;
+  });
   room.votes_revealed = false;
+  room.reveal_at = null;
+  room.reveal_progress = null;
+  room.delegations = {};
+  room.signals = {};
+  broadcastRoomUpdate(roomId);
+}
+
+function handleHostSetSittingOut(conn: ClientConnection, roomId: string, participantId: string, sittingOut: boolean) {
+  const room = rooms.get(roomId);
+  if (!room || room.host_ws !== conn.ws) return;
+  setSittingOut(roomId, participantId, sittingOut);
+}
+
+/** Same as Room::set_sitting_out in the desktop app */
+function setSittingOut(roomId: string, participantId: string, sittingOut: boolean) {
+  const room = rooms.get(roomId);
+  const participant = room?.participants.find(p => p.id === participantId);
+  if (!room || !participant) return;
+
+  participant.sitting_out = sittingOut || undefined;
+  participant.updated_at = Date.now();
+  if (sittingOut) {
+    participant.vote = null;
+    participant.risk_vote = null;
+    dropDelegations(room, participantId);
+  }
   broadcastRoomUpdate(roomId);
 }
 
@@ -332,14 +840,23 @@ function handleHostKickParticipant(conn: ClientConnection, roomId: string, parti
   });
 
   room.participants = room.participants.filter(p => p.id !== participantId);
+  dropDelegations(room, participantId);
   broadcastRoomUpdate(roomId);
 }
 
-function handleHostSetTicket(conn: ClientConnection, roomId: string, ticket: JiraTicket) {
+function handleHostSetTicket(conn: ClientConnection, roomId: string, ticket: JiraTicket, deck: unknown) {
   const room = rooms.get(roomId);
   if (!room || room.host_ws !== conn.ws) return;
 
+  // Signals were about the ticket that's going away
+  if (room.current_ticket?.key !== ticket?.key) room.signals = {};
   room.current_ticket = ticket;
+  // Showing a ticket ends any batch round, as on the host
+  room.batch = [];
+  // The host may switch decks by issue type; older hosts don't send one
+  if (Array.isArray(deck) && deck.length > 0 && deck.every(card => typeof card === 'string')) {
+    room.deck = deck;
+  }
   broadcastRoomUpdate(roomId);
 }
 
@@ -348,40 +865,125 @@ function handleHostClearTicket(conn: ClientConnection, roomId: string) {
   if (!room || room.host_ws !== conn.ws) return;
 
   room.current_ticket = null;
+  room.signals = {};
   broadcastRoomUpdate(roomId);
 }
 
+// Matches the limits in the desktop app's moderation module
+const MAX_NAME_LENGTH = 40;
+/** Matches MAX_IDENTITY_LENGTH in the desktop app */
+const MAX_IDENTITY_LENGTH = 64;
+
+/** Collapse whitespace and reject empty, oversized or control-character text */
+function sanitizeText(text: unknown, maxLength: number): string | null {
+  if (typeof text !== 'string') return null;
+  const collapsed = text.split(/\s+/).filter(Boolean).join(' ');
+  if (!collapsed || [...collapsed].length > maxLength || /\p{Cc}/u.test(collapsed)) {
+    return null;
+  }
+  return collapsed;
+}
+
+// Same families and detection order as user_agent_family in the desktop app's room.rs
+const USER_AGENT_FAMILIES = ['Edge', 'Opera', 'Firefox', 'Chrome', 'Safari', 'Other'];
+const CLIENT_KINDS = ['browser', 'desktop', 'tui', 'other'];
+
+function userAgentFamily(userAgent: string): string {
+  if (userAgent.includes('Edg/')) return 'Edge';
+  if (userAgent.includes('OPR/')) return 'Opera';
+  if (userAgent.includes('Firefox/')) return 'Firefox';
+  if (userAgent.includes('Chrome/')) return 'Chrome';
+  if (userAgent.includes('Safari/')) return 'Safari';
+  return 'Other';
+}
+
+/** Keep only known values from opted-in client info, so nothing free-form reaches the host */
+function sanitizeClientInfo(client: any, userAgent: string | null): ClientInfo | undefined {
+  if (!client || typeof client !== 'object') return undefined;
+  const kind = CLIENT_KINDS.includes(client.kind) ? client.kind : 'other';
+  const claimed = typeof client.user_agent_family === 'string'
+    ? USER_AGENT_FAMILIES.find(f => f.toLowerCase() === client.user_agent_family.toLowerCase())
+    : undefined;
+  return {
+    kind,
+    user_agent_family: claimed ?? (userAgent ? userAgentFamily(userAgent) : null),
+  };
+}
+
+// Same palette as PARTICIPANT_COLORS in the desktop app
+const PARTICIPANT_COLORS = [
+  '#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4', '#42d4f4',
+  '#f032e6', '#9a6324', '#469990', '#808000', '#000075', '#e6beff',
+];
+
+function leastUsedColor(room: Room): string {
+  const usage = (color: string) => room.participants.filter(p => p.color === color).length;
+  return PARTICIPANT_COLORS.reduce((best, color) => (usage(color) < usage(best) ? color : best));
+}
+
 function handleParticipantJoin(ws: WebSocket, conn: ClientConnection, message: any) {
-  // Find room by ID or invite code
-  let room = rooms.get(message.room_id);
-  if (!room) {
-    // Try invite code
-    const normalizedCode = message.room_id.toLowerCase().replace(/-/g, ' ');
-    const roomId = inviteCodeToRoom.get(normalizedCode);
-    if (roomId) {
-      room = rooms.get(roomId);
-    }
+  // Find room by ID or invite code; the room in a /ws/<room> URL wins over the message's
+  const requested = conn.boundRoom ?? message.room_id;
+  const room = typeof requested === 'string' ? findRoom(requested) : undefined;
+  if (room && conn.roomId && conn.roomId !== room.id) {
+    ws.send(JSON.stringify({ type: 'error', code: 'other', message: 'Already joined a room on this connection' }));
+    return;
   }
 
   if (!room) {
-    ws.send(JSON.stringify({ type: 'error', message: 'Room not found' }));
+    ws.send(JSON.stringify({ type: 'error', code: 'room_not_found', message: 'Room not found' }));
     return;
   }
 
-  const participantId = uuidv4();
-  const participant: Participant = {
-    id: participantId,
-    name: message.name,
-    vote: null,
-    is_host: false,
-  };
+  const identity = typeof message.identity === 'string' && message.identity.length > 0
+    && message.identity.length <= MAX_IDENTITY_LENGTH ? message.identity : null;
+  const returning = identity
+    ? room.participants.find(p => p.id === room!.identities.get(identity))
+    : undefined;
+
+  if (room.locked && !returning) {
+    ws.send(JSON.stringify({ type: 'error', code: 'room_locked', message: 'This room is locked to new participants; ask the host to unlock it' }));
+    return;
+  }
+
+  const name = sanitizeText(message.name, MAX_NAME_LENGTH);
+  if (!name) {
+    ws.send(JSON.stringify({ type: 'error', code: 'invalid_name', message: `Name must be 1-${MAX_NAME_LENGTH} characters` }));
+    return;
+  }
+
+  let participantId: string;
+  if (returning) {
+    // Rejoining after a refresh or dropped connection: same place, vote and role
+    participantId = returning.id;
+    returning.name = name;
+    returning.client = sanitizeClientInfo(message.client, conn.userAgent);
+    delete returning.disconnected_at;
+    connections.forEach((other, otherWs) => {
+      if (other !== conn && other.roomId === room!.id && other.participantId === participantId) {
+        other.participantId = null;
+        otherWs.close();
+      }
+    });
+  } else {
+    participantId = uuidv4();
+    room.participants.push({
+      id: participantId,
+      name,
+      vote: null,
+      is_host: false,
+      color: leastUsedColor(room),
+      client: sanitizeClientInfo(message.client, conn.userAgent),
+      is_observer: message.observer === true || undefined,
+    });
+    if (identity) room.identities.set(identity, participantId);
+  }
 
-  room.participants.push(participant);
   conn.type = 'participant';
   conn.roomId = room.id;
   conn.participantId = participantId;
 
-  console.log(`Participant ${message.name} joined room ${room.name}`);
+  console.log(`Participant ${name} joined room ${room.name}`);
 
   // Send join confirmation
   ws.send(JSON.stringify({
@@ -391,29 +993,234 @@ function handleParticipantJoin(ws: WebSocket, conn: ClientConnection, message: a
       id: room.id,
       name: room.name,
       invite_code: room.invite_code,
-      participants: room.participants,
+      participants: visibleParticipants(room, participantId),
       votes_revealed: room.votes_revealed,
       current_ticket: room.current_ticket,
-    }
+      ticket_hash: ticketHash(room.current_ticket),
+      batch: room.batch,
+      campaign: room.campaign,
+      deck: room.deck,
+      risk_deck: room.risk_deck,
+      sprint_fill: room.sprint_fill,
+      discussing: room.discussing,
+      voting_opens_at: room.voting_opens_at,
+    },
+    server_time: Date.now(),
   }));
 
   broadcastRoomUpdate(room.id);
 }
 
+/** Same rule as Room::voting_open in the desktop app */
+function votingOpen(room: Room): boolean {
+  return !room.discussing && (room.voting_opens_at === null || Date.now() >= room.voting_opens_at);
+}
+
+function isObserver(room: Room, participantId: string): boolean {
+  return room.participants.some(p => p.id === participantId && p.is_observer);
+}
+
+/** Same message as the desktop app's OBSERVER_VOTE_MESSAGE */
+function rejectObserverVote(conn: ClientConnection, code: string) {
+  conn.ws.send(JSON.stringify({ type: 'error', code, message: "You joined as an observer, so you can't vote" }));
+}
+
+function rejectClosedVoting(conn: ClientConnection) {
+  conn.ws.send(JSON.stringify({
+    type: 'error',
+    code: 'voting_closed',
+    message: "Voting hasn't opened yet; the story is still being discussed",
+  }));
+}
+
 function handleParticipantVote(conn: ClientConnection, vote: string | null) {
   if (!conn.roomId || !conn.participantId) return;
 
   const room = rooms.get(conn.roomId);
   if (!room) return;
+  if (!votingOpen(room)) return rejectClosedVoting(conn);
+
+  if (isObserver(room, conn.participantId)) return rejectObserverVote(conn, 'invalid_vote');
+  if (vote !== null && !room.deck.includes(vote)) {
+    conn.ws.send(JSON.stringify({ type: 'error', code: 'invalid_vote', message: 'Invalid vote' }));
+    return;
+  }
 
   const participant = room.participants.find(p => p.id === conn.participantId);
   if (participant) {
     participant.vote = vote;
+    participant.updated_at = Date.now();
+    // Voting yourself takes back a delegated vote, and means you're back in
+    if (vote !== null) {
+      delete room.delegations[participant.id];
+      participant.sitting_out = undefined;
+    }
     broadcastRoomUpdate(conn.roomId);
   }
 }
 
+/** Same rules as Room::delegate_vote in the desktop app */
+function handleParticipantDelegate(conn: ClientConnection, to: string | null) {
+  if (!conn.roomId || !conn.participantId) return;
+
+  const room = rooms.get(conn.roomId);
+  if (!room) return;
+  if (!votingOpen(room)) return rejectClosedVoting(conn);
+  const from = conn.participantId;
+  const reject = (message: string) =>
+    conn.ws.send(JSON.stringify({ type: 'error', code: 'invalid_delegation', message }));
+
+  if (to === null) {
+    delete room.delegations[from];
+    broadcastRoomUpdate(room.id);
+    return;
+  }
+  const delegate = room.participants.find(p => p.id === to);
+  if (isObserver(room, from)) return rejectObserverVote(conn, 'invalid_delegation');
+  if (to === from) return reject("You can't hand your vote to yourself");
+  if (!delegate) return reject('Participant not found');
+  if (delegate.is_observer) return reject(`${delegate.name} is observing and doesn't vote`);
+  if (room.delegations[to]) return reject(`${delegate.name} has handed their own vote on`);
+  if (Object.values(room.delegations).includes(from)) {
+    return reject("Someone has handed you their vote, so you can't pass it on");
+  }
+
+  room.delegations[from] = to;
+  const participant = room.participants.find(p => p.id === from);
+  if (participant) {
+    participant.vote = null;
+    participant.risk_vote = null;
+    participant.updated_at = Date.now();
+  }
+  broadcastRoomUpdate(room.id);
+}
+
+function handleParticipantRiskVote(conn: ClientConnection, vote: string | null) {
+  if (!conn.roomId || !conn.participantId) return;
+
+  const room = rooms.get(conn.roomId);
+  if (!room) return;
+  if (!votingOpen(room)) return rejectClosedVoting(conn);
+
+  if (isObserver(room, conn.participantId)) return rejectObserverVote(conn, 'invalid_vote');
+  if (vote !== null && !room.risk_deck?.includes(vote)) {
+    conn.ws.send(JSON.stringify({ type: 'error', code: 'invalid_vote', message: 'Invalid risk vote' }));
+    return;
+  }
+
+  const participant = room.participants.find(p => p.id === conn.participantId);
+  if (participant) {
+    participant.risk_vote = vote;
+    participant.updated_at = Date.now();
+    broadcastRoomUpdate(conn.roomId);
+  }
+}
+
+/** Same as Room::set_signal in the desktop app */
+function handleParticipantSignal(conn: ClientConnection, signal: unknown, raised: boolean) {
+  if (!conn.roomId || !conn.participantId) return;
+  const room = rooms.get(conn.roomId);
+  if (!room || !TICKET_SIGNALS.includes(signal as TicketSignal)) return;
+
+  const signals = (room.signals[conn.participantId] ?? []).filter(s => s !== signal);
+  if (raised) signals.push(signal as TicketSignal);
+  if (signals.length > 0) {
+    room.signals[conn.participantId] = signals;
+  } else {
+    delete room.signals[conn.participantId];
+  }
+  broadcastRoomUpdate(conn.roomId);
+}
+
+function findRoom(roomIdOrInvite: string): Room | undefined {
+  const room = rooms.get(roomIdOrInvite);
+  if (room) return room;
+  const normalizedCode = roomIdOrInvite.toLowerCase().replace(/-/g, ' ');
+  const roomId = inviteCodeToRoom.get(normalizedCode);
+  return roomId ? rooms.get(roomId) : undefined;
+}
+
+function handleBridgedJoin(ws: WebSocket, conn: ClientConnection, message: any) {
+  const room = findRoom(conn.boundRoom ?? message.payload?.room_id ?? '');
+  if (!room || !room.host_ws || room.host_ws.readyState !== WebSocket.OPEN) {
+    ws.send(JSON.stringify({ type: 'Error', payload: { code: 'RoomNotFound', message: 'Room not found' } }));
+    return;
+  }
+
+  conn.bridged = true;
+  conn.roomId = room.id;
+  conn.connectionId = uuidv4();
+
+  room.host_ws.send(JSON.stringify({
+    type: 'participant_connected',
+    connection_id: conn.connectionId,
+  }));
+
+  // The participant may have used an invite code; the host only knows room IDs
+  forwardToHost(conn, { ...message, payload: { ...message.payload, room_id: room.id } });
+}
+
+function forwardToHost(conn: ClientConnection, message: any) {
+  const room = conn.roomId ? rooms.get(conn.roomId) : undefined;
+  if (!room || !room.host_ws || room.host_ws.readyState !== WebSocket.OPEN) return;
+
+  room.host_ws.send(JSON.stringify({
+    type: 'participant_message',
+    connection_id: conn.connectionId,
+    message,
+  }));
+}
+
+function handleHostParticipantMessage(conn: ClientConnection, connectionId: string, message: any) {
+  connections.forEach((c, ws) => {
+    if (c.bridged && c.connectionId === connectionId) {
+      const room = c.roomId ? rooms.get(c.roomId) : undefined;
+      if (room && room.host_ws === conn.ws && ws.readyState === WebSocket.OPEN) {
+        ws.send(JSON.stringify(message));
+      }
+    }
+  });
+}
+
+/**
+ * Keep the web client a host published, to serve under /r/<room id>/ for that
+ * host's rooms only. Hosts can't touch the relay's own copy or each other's.
+ */
+function handleHostPublishClient(conn: ClientConnection, files: any[]) {
+  if (conn.type !== 'host' || !Array.isArray(files)) return;
+
+  const published = new Map<string, PublishedFile>();
+  for (const file of files) {
+    if (typeof file?.path !== 'string' || typeof file.data !== 'string' || !PUBLISHABLE_FILE.test(file.path)) {
+      console.warn(`Ignoring published file outside the web client bundle: ${String(file?.path).slice(0, 100)}`);
+      continue;
+    }
+    const extension = file.path.slice(file.path.lastIndexOf('.') + 1);
+    published.set(file.path, {
+      content_type: PUBLISHED_CONTENT_TYPES[extension],
+      data: Buffer.from(file.data, 'base64'),
+    });
+  }
+  if (!published.has('index.html')) {
+    console.warn('Ignoring a published web client without an index.html');
+    return;
+  }
+  conn.publishedClient = published;
+  console.log(`Host published web client (${published.size} files)`);
+}
+
 function handleDisconnect(ws: WebSocket, conn: ClientConnection) {
+  if (conn.bridged) {
+    const room = conn.roomId ? rooms.get(conn.roomId) : undefined;
+    if (room && room.host_ws && room.host_ws.readyState === WebSocket.OPEN) {
+      room.host_ws.send(JSON.stringify({
+        type: 'participant_disconnected',
+        connection_id: conn.connectionId,
+      }));
+    }
+    return;
+  }
+
   if (conn.type === 'host') {
     // Mark all rooms hosted by this connection as having no host
     rooms.forEach((room, roomId) => {
@@ -426,14 +1233,65 @@ function handleDisconnect(ws: WebSocket, conn: ClientConnection) {
     });
   } else if (conn.type === 'participant' && conn.roomId && conn.participantId) {
     const room = rooms.get(conn.roomId);
-    if (room) {
-      room.participants = room.participants.filter(p => p.id !== conn.participantId);
-      console.log(`Participant left room ${room.name}`);
-      broadcastRoomUpdate(conn.roomId);
+    const participant = room?.participants.find(p => p.id === conn.participantId);
+    if (room && participant) {
+      if (room.disconnect_grace_secs > 0) {
+        // Keep them greyed out in case they're only refreshing the page
+        participant.disconnected_at = Math.floor(Date.now() / 1000);
+        const roomId = room.id;
+        setTimeout(() => removeIfStillDisconnected(roomId, participant.id), room.disconnect_grace_secs * 1000);
+      } else {
+        room.participants = room.participants.filter(p => p.id !== participant.id);
+        dropDelegations(room, participant.id);
+        console.log(`Participant left room ${room.name}`);
+      }
+      broadcastRoomUpdate(room.id);
     }
   }
 }
 
+/** Forget any delegation to or from a participant who has left, and their signals */
+function dropDelegations(room: Room, participantId: string) {
+  for (const [from, to] of Object.entries(room.delegations)) {
+    if (from === participantId || to === participantId) delete room.delegations[from];
+  }
+  delete room.signals[participantId];
+}
+
+function removeIfStillDisconnected(roomId: string, participantId: string) {
+  const room = rooms.get(roomId);
+  const participant = room?.participants.find(p => p.id === participantId);
+  if (!room || !participant?.disconnected_at) return;
+  room.participants = room.participants.filter(p => p.id !== participantId);
+  dropDelegations(room, participantId);
+  console.log(`Participant didn't come back to room ${room.name}`);
+  broadcastRoomUpdate(roomId);
+}
+
+/** Web client the host of a room published, if it's connected and did */
+function publishedClientFor(roomId: string): Map<string, PublishedFile> | null {
+  const room = rooms.get(roomId);
+  const host = room?.host_ws ? connections.get(room.host_ws) : undefined;
+  return host?.publishedClient ?? null;
+}
+
+// A room's own page: the web client its host published, falling back to the bundled copy.
+// The page's bundle links are pointed at /r/<room id>/assets/, so they come from the same copy.
+app.get('/r/:roomId/', (req, res) => {
+  const published = publishedClientFor(req.params.roomId);
+  const page = published?.get('index.html');
+  if (!page) return res.sendFile(path.join(__dirname, '../public/index.html'));
+  const prefix = `/r/${encodeURIComponent(req.params.roomId)}/`;
+  const html = page.data.toString('utf8').replace(/(src|href)="\/(?!\/)/g, `$1="${prefix}`);
+  res.type(page.content_type).send(html);
+});
+
+app.get('/r/:roomId/*', (req, res, next) => {
+  const file = publishedClientFor(req.params.roomId)?.get((req.params as Record<string, string>)[0]);
+  if (!file) return next();
+  res.type(file.content_type).send(file.data);
+});
+
 // Serve static files (web client)
 app.use(express.static(path.join(__dirname, '../public')));
 
@@ -442,6 +1300,30 @@ app.get('/api/health', (req, res) => {
   res.json({ status: 'ok', rooms: rooms.size });
 });
 
+/** Seconds until a room's announced start, like Room::starts_in_secs in the desktop app */
+function startsInSecs(room: Room): number | undefined {
+  const now = Math.floor(Date.now() / 1000);
+  return room.starts_at !== null && room.starts_at > now ? room.starts_at - now : undefined;
+}
+
+/** The announced start in the room's time zone, like the desktop app's RoomTimeZone::format */
+function startsAtLocal(room: Room): string | undefined {
+  if (startsInSecs(room) === undefined || room.starts_at === null || !room.time_zone) return undefined;
+  try {
+    // The zone's offset at the start time itself, so a DST change in between doesn't shift it
+    const parts = new Intl.DateTimeFormat('en-CA', {
+      timeZone: room.time_zone.name,
+      year: 'numeric', month: '2-digit', day: '2-digit',
+      hour: '2-digit', minute: '2-digit', hourCycle: 'h23',
+    }).formatToParts(new Date(room.starts_at * 1000));
+    const part = (type: Intl.DateTimeFormatPartTypes) => parts.find(p => p.type === type)?.value ?? '';
+    return `${part('year')}-${part('month')}-${part('day')} ${part('hour')}:${part('minute')} ${room.time_zone.name}`;
+  } catch {
+    // A zone this relay doesn't know
+    return undefined;
+  }
+}
+
 app.get('/api/room/:roomId', (req, res) => {
   const room = rooms.get(req.params.roomId);
   if (!room) {
@@ -455,9 +1337,13 @@ app.get('/api/room/:roomId', (req, res) => {
           id: foundRoom.id,
           name: foundRoom.name,
           invite_code: foundRoom.invite_code,
-          participants: foundRoom.participants,
+          participants: visibleParticipants(foundRoom, null),
           votes_revealed: foundRoom.votes_revealed,
           current_ticket: foundRoom.current_ticket,
+          deck: foundRoom.deck,
+          risk_deck: foundRoom.risk_deck,
+          starts_in_secs: startsInSecs(foundRoom),
+          starts_at_local: startsAtLocal(foundRoom),
         });
       }
     }
@@ -467,12 +1353,25 @@ app.get('/api/room/:roomId', (req, res) => {
     id: room.id,
     name: room.name,
     invite_code: room.invite_code,
-    participants: room.participants,
+    participants: visibleParticipants(room, null),
     votes_revealed: room.votes_revealed,
     current_ticket: room.current_ticket,
+    deck: room.deck,
+    risk_deck: room.risk_deck,
+    starts_in_secs: startsInSecs(room),
+    starts_at_local: startsAtLocal(room),
   });
 });
 
+// Clients fetch this when a room_update carries a ticket_hash they haven't seen
+app.get('/api/room/:roomId/ticket', (req, res) => {
+  const room = rooms.get(req.params.roomId);
+  if (!room) {
+    return res.status(404).json({ error: 'Room not found' });
+  }
+  res.json({ ticket: room.current_ticket, ticket_hash: ticketHash(room.current_ticket) });
+});
+
 app.get('/api/story-points', (req, res) => {
   res.json(['0', '0.5', '1', '2', '3', '5', '8', '13', '21', '?', '☕']);
 });
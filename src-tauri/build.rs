@@ -1,3 +1,11 @@
 fn main() {
+    // The gRPC API's messages and service trait, generated from its .proto with
+    // a bundled protoc so no system install is needed
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("protoc is bundled for this platform");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/host.proto").expect("Failed to generate the gRPC service");
+    }
     tauri_build::build()
 }
@@ -0,0 +1,202 @@
+use crate::credentials::get_data_dir;
+use crate::room::{HistoryEvent, Room};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+const ACTUALS_FILE: &str = "ticket_actuals.json";
+
+/// How a ticket's actual effort was measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActualSource {
+    /// Work logged on the issue
+    LoggedWork,
+    /// Wall-clock time from the first status change until it was resolved
+    TimeInProgress,
+}
+
+/// How long a completed ticket actually took, according to Jira
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketActual {
+    pub ticket_key: String,
+    pub issue_type: Option<String>,
+    pub hours: f64,
+    pub source: ActualSource,
+}
+
+/// Estimate against actual effort for one ticket
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketAccuracy {
+    pub ticket_key: String,
+    pub estimate: f64,
+    pub hours: f64,
+    pub source: ActualSource,
+    /// Hours per point compared to the issue type as a whole: 1.0 is on par,
+    /// 2.0 took twice as long as its estimate suggested
+    pub relative_effort: f64,
+}
+
+/// How well estimates tracked actual effort for one issue type
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueTypeAccuracy {
+    pub issue_type: String,
+    pub average_estimate: f64,
+    pub average_hours: f64,
+    pub hours_per_point: f64,
+    /// Spread of hours per point relative to its mean (coefficient of variation);
+    /// the lower it is, the better the estimates predict effort
+    pub variation: f64,
+    pub tickets: Vec<TicketAccuracy>,
+}
+
+/// Final team estimate per ticket: the average of its most recently revealed
+/// round, or the host's override of it
+pub fn final_estimates<'a>(rooms: impl IntoIterator<Item = &'a Room>) -> HashMap<String, f64> {
+    let mut latest: HashMap<String, (u64, f64)> = HashMap::new();
+    for room in rooms {
+        for entry in &room.history {
+            let (key, estimate) = match &entry.event {
+                HistoryEvent::VotesRevealed { ticket_key: Some(key), average: Some(average), .. } => (key, *average),
+                HistoryEvent::EstimateOverridden { ticket_key: Some(key), value, .. } => match value.parse::<f64>() {
+                    Ok(value) => (key, value),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            let newer = latest.get(key).is_none_or(|(timestamp, _)| entry.timestamp >= *timestamp);
+            if newer && estimate > 0.0 {
+                latest.insert(key.clone(), (entry.timestamp, estimate));
+            }
+        }
+    }
+    latest.into_iter().map(|(key, (_, average))| (key, average)).collect()
+}
+
+/// Compare estimates with actual effort, grouped by issue type
+pub fn accuracy_report(estimates: &HashMap<String, f64>, actuals: &[TicketActual]) -> Vec<IssueTypeAccuracy> {
+    let mut by_type: HashMap<String, Vec<(&TicketActual, f64)>> = HashMap::new();
+    for actual in actuals {
+        if let Some(estimate) = estimates.get(&actual.ticket_key) {
+            let issue_type = actual.issue_type.clone().unwrap_or_else(|| "Other".to_string());
+            by_type.entry(issue_type).or_default().push((actual, *estimate));
+        }
+    }
+
+    let mut report: Vec<IssueTypeAccuracy> = by_type
+        .into_iter()
+        .map(|(issue_type, tickets)| {
+            let count = tickets.len() as f64;
+            let total_points: f64 = tickets.iter().map(|(_, estimate)| estimate).sum();
+            let total_hours: f64 = tickets.iter().map(|(actual, _)| actual.hours).sum();
+            let hours_per_point = total_hours / total_points;
+
+            let ratios: Vec<f64> = tickets.iter().map(|(actual, estimate)| actual.hours / estimate).collect();
+            let mean_ratio = ratios.iter().sum::<f64>() / count;
+            let variance = ratios.iter().map(|r| (r - mean_ratio).powi(2)).sum::<f64>() / count;
+            let variation = if mean_ratio > 0.0 { variance.sqrt() / mean_ratio } else { 0.0 };
+
+            let mut tickets: Vec<TicketAccuracy> = tickets
+                .into_iter()
+                .map(|(actual, estimate)| TicketAccuracy {
+                    ticket_key: actual.ticket_key.clone(),
+                    estimate,
+                    hours: actual.hours,
+                    source: actual.source,
+                    relative_effort: if hours_per_point > 0.0 { actual.hours / estimate / hours_per_point } else { 0.0 },
+                })
+                .collect();
+            tickets.sort_by(|a, b| b.relative_effort.total_cmp(&a.relative_effort));
+
+            IssueTypeAccuracy {
+                issue_type,
+                average_estimate: total_points / count,
+                average_hours: total_hours / count,
+                hours_per_point,
+                variation,
+                tickets,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| a.issue_type.cmp(&b.issue_type));
+    report
+}
+
+/// Parse a Jira timestamp such as `2024-01-15T10:30:00.000+0100` into Unix seconds
+pub fn parse_jira_timestamp(text: &str) -> Option<u64> {
+    let number = |range: std::ops::Range<usize>| text.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+
+    // The offset follows the optional fraction of a second
+    let offset_at = text.get(19..)?.find(['+', '-']).map(|i| i + 19);
+    let offset_secs = match offset_at {
+        Some(at) => {
+            let sign = if &text[at..at + 1] == "-" { -1 } else { 1 };
+            sign * (number(at + 1..at + 3)? * 3600 + number(at + 3..at + 5)? * 60)
+        }
+        None => 0,
+    };
+
+    // Days since the epoch for a proleptic Gregorian date
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    u64::try_from(days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs).ok()
+}
+
+pub fn load_actuals() -> Result<Vec<TicketActual>, String> {
+    let path = get_data_dir()?.join(ACTUALS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read ticket actuals: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse ticket actuals: {}", e))
+}
+
+pub fn save_actuals(actuals: &[TicketActual]) -> Result<(), String> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(actuals).map_err(|e| format!("Failed to serialize ticket actuals: {}", e))?;
+    fs::write(data_dir.join(ACTUALS_FILE), json).map_err(|e| format!("Failed to write ticket actuals: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actual(key: &str, issue_type: &str, hours: f64) -> TicketActual {
+        TicketActual {
+            ticket_key: key.to_string(),
+            issue_type: Some(issue_type.to_string()),
+            hours,
+            source: ActualSource::LoggedWork,
+        }
+    }
+
+    #[test]
+    fn jira_timestamps_honour_their_offset() {
+        assert_eq!(parse_jira_timestamp("2024-01-01T00:00:00.000+0000"), Some(1_704_067_200));
+        assert_eq!(parse_jira_timestamp("2024-01-01T01:30:00.000+0130"), Some(1_704_067_200));
+        assert_eq!(parse_jira_timestamp("2023-12-31T19:00:00-0500"), Some(1_704_067_200));
+        assert_eq!(parse_jira_timestamp("not a date"), None);
+    }
+
+    #[test]
+    fn accuracy_is_reported_per_issue_type() {
+        let estimates = HashMap::from([("A-1".into(), 2.0), ("A-2".into(), 4.0), ("A-3".into(), 3.0)]);
+        let actuals = [actual("A-1", "Story", 4.0), actual("A-2", "Story", 16.0), actual("A-3", "Bug", 6.0)];
+
+        let report = accuracy_report(&estimates, &actuals);
+        assert_eq!(report.iter().map(|r| r.issue_type.as_str()).collect::<Vec<_>>(), ["Bug", "Story"]);
+        let stories = &report[1];
+        assert_eq!(stories.hours_per_point, 20.0 / 6.0);
+        assert_eq!(stories.tickets[0].ticket_key, "A-2", "worst overrun first");
+        assert!(stories.variation > 0.0);
+        assert_eq!(report[0].variation, 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::Deserialize;
+
+/// A Jira description in ADF (Atlassian Document Format)
+#[derive(Debug, Deserialize)]
+pub struct AdfDocument {
+    content: Option<Vec<AdfNode>>,
+}
+
+/// ADF content node - can contain text or nested content
+#[derive(Debug, Deserialize)]
+struct AdfNode {
+    #[serde(rename = "type")]
+    node_type: Option<String>,
+    text: Option<String>,
+    content: Option<Vec<AdfNode>>,
+    #[serde(default)]
+    attrs: AdfAttrs,
+    /// Formatting on a text node; only links are kept
+    #[serde(default)]
+    marks: Vec<AdfMark>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AdfAttrs {
+    /// Account ID of a mentioned user
+    id: Option<String>,
+    /// Text Jira stored with a mention, e.g. "@Jane Doe"; can be stale or missing
+    text: Option<String>,
+    /// Target of an inline, block or embed card
+    url: Option<String>,
+    /// Target of a link mark
+    href: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdfMark {
+    #[serde(rename = "type")]
+    mark_type: String,
+    #[serde(default)]
+    attrs: AdfAttrs,
+}
+
+impl AdfDocument {
+    /// Account IDs of the users mentioned, without duplicates
+    pub fn mentioned_users(&self) -> Vec<String> {
+        fn collect<'a>(nodes: &'a [AdfNode], ids: &mut Vec<&'a str>) {
+            for node in nodes {
+                if node.node_type.as_deref() == Some("mention") {
+                    if let Some(id) = node.attrs.id.as_deref().filter(|id| !ids.contains(id)) {
+                        ids.push(id);
+                    }
+                }
+                collect(node.content.as_deref().unwrap_or_default(), ids);
+            }
+        }
+        let mut ids = Vec::new();
+        collect(self.content.as_deref().unwrap_or_default(), &mut ids);
+        ids.into_iter().map(str::to_string).collect()
+    }
+
+    /// The description as plain text. Mentions read "@Display Name", using
+    /// `user_name` to look up account IDs; links keep their URL next to their
+    /// text, so clients can make them clickable. None if there's no text.
+    pub fn to_text(&self, user_name: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let text: String = self.content.iter().flatten().map(|node| node.extract_text(&user_name)).collect();
+        Some(text.trim().to_string()).filter(|s| !s.is_empty())
+    }
+}
+
+impl AdfNode {
+    /// Recursively extract all text from this node and its children
+    fn extract_text(&self, user_name: &impl Fn(&str) -> Option<String>) -> String {
+        let mut result = String::new();
+
+        match self.node_type.as_deref() {
+            // Fence code blocks so they can be told apart from prose
+            Some("codeBlock") => {
+                let code: String = self.content.iter().flatten().map(|child| child.extract_text(user_name)).collect();
+                return format!("```\n{}\n```\n", code);
+            }
+            Some("mention") => {
+                let name = self.attrs.id.as_deref().and_then(user_name);
+                return match (name, &self.attrs.text) {
+                    (Some(name), _) => format!("@{}", name),
+                    (None, Some(text)) if text.starts_with('@') => text.clone(),
+                    (None, Some(text)) => format!("@{}", text),
+                    (None, None) => "@unknown user".to_string(),
+                };
+            }
+            Some("inlineCard") => return self.attrs.url.clone().unwrap_or_default(),
+            Some("blockCard" | "embedCard") => {
+                return self.attrs.url.as_ref().map(|url| format!("{}\n", url)).unwrap_or_default();
+            }
+            _ => {}
+        }
+
+        // If this node has direct text, add it, with the target of any link
+        if let Some(ref text) = self.text {
+            let href = self
+                .marks
+                .iter()
+                .find(|mark| mark.mark_type == "link")
+                .and_then(|mark| mark.attrs.href.as_deref());
+            match href {
+                Some(href) if href != text => {
+                    result.push_str(text);
+                    result.push_str(" (");
+                    result.push_str(href);
+                    result.push(')');
+                }
+                _ => result.push_str(text),
+            }
+        }
+
+        // Recursively process child content
+        if let Some(ref children) = self.content {
+            for child in children {
+                result.push_str(&child.extract_text(user_name));
+            }
+        }
+
+        // Add newline after paragraph nodes
+        if let Some(ref node_type) = self.node_type {
+            if node_type == "paragraph" || node_type == "heading" {
+                result.push('\n');
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_and_links_are_kept() {
+        let doc: AdfDocument = serde_json::from_value(serde_json::json!({
+            "type": "doc",
+            "content": [{
+                "type": "paragraph",
+                "content": [
+                    { "type": "mention", "attrs": { "id": "abc", "text": "@Old Name" } },
+                    { "type": "text", "text": " and " },
+                    { "type": "mention", "attrs": { "id": "def", "text": "@Bob" } },
+                    { "type": "text", "text": ", see " },
+                    { "type": "text", "text": "the spec", "marks": [{ "type": "link", "attrs": { "href": "https://example.com/spec" } }] },
+                    { "type": "text", "text": " and " },
+                    { "type": "inlineCard", "attrs": { "url": "https://example.atlassian.net/browse/ABC-1" } }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(doc.mentioned_users(), ["abc", "def"]);
+        let names = |id: &str| (id == "abc").then(|| "Alice Smith".to_string());
+        assert_eq!(
+            doc.to_text(names).unwrap(),
+            "@Alice Smith and @Bob, see the spec (https://example.com/spec) and https://example.atlassian.net/browse/ABC-1"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::recap::escape_html;
+use crate::state::AppState;
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct AdminQuery {
+    token: Option<String>,
+}
+
+/// Read-only diagnostics page for when the desktop window itself is misbehaving.
+/// Served only on the loopback admin listener, to clients holding the token from
+/// the desktop app.
+pub async fn admin_page(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<AdminQuery>,
+) -> Response {
+    if !addr.ip().to_canonical().is_loopback() {
+        return (StatusCode::FORBIDDEN, "The admin page is only available on this machine").into_response();
+    }
+    if query.token.as_deref() != Some(state.admin_token.as_str()) {
+        return (StatusCode::UNAUTHORIZED, "Invalid admin token").into_response();
+    }
+
+    let relay = match state.get_relay_client().await {
+        Some(client) => format!(
+            "{} ({})",
+            if client.is_connected().await { "connected" } else { "disconnected" },
+            client.get_relay_url().await
+        ),
+        None => "not configured".to_string(),
+    };
+
+    Html(render_admin_page(&state, &relay)).into_response()
+}
+
+fn render_admin_page(state: &AppState, relay: &str) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<html><head><title>Scrum Poker admin</title>\
+         <meta http-equiv=\"refresh\" content=\"5\"></head>\
+         <body style=\"font-family: sans-serif\">\
+         <h1>Scrum Poker admin</h1>\
+         <p>Server: {} &middot; Relay: {}</p>",
+        escape_html(&state.get_server_url()),
+        escape_html(relay)
+    );
+
+    html.push_str(
+        "<h2>Rooms</h2><table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+         <tr><th>Name</th><th>ID</th><th>Participants</th><th>Connected</th>\
+         <th>Revealed</th><th>Ticket</th></tr>",
+    );
+    for room in state.get_rooms() {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&room.name),
+            escape_html(&room.id),
+            room.participants.len(),
+            state.connection_count(&room.id),
+            room.votes_revealed,
+            room.current_ticket.as_ref().map(|t| escape_html(&t.key)).unwrap_or_default()
+        );
+    }
+    html.push_str("</table>");
+
+    html.push_str(
+        "<h2>Connections</h2><table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+         <tr><th>Participant</th><th>Name</th><th>Room</th></tr>",
+    );
+    for connections in state.connections.iter() {
+        let room_id = connections.key();
+        let room = state.get_room(room_id);
+        for participant_id in connections.keys() {
+            let name = room
+                .as_ref()
+                .and_then(|room| room.participants.iter().find(|p| &p.id == participant_id).map(|p| p.name.clone()))
+                .unwrap_or_default();
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(participant_id),
+                escape_html(&name),
+                escape_html(room_id)
+            );
+        }
+    }
+    html.push_str("</table>");
+
+    html.push_str(
+        "<h2>Recent warnings and errors</h2><table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+         <tr><th>Time</th><th>Level</th><th>Source</th><th>Message</th></tr>",
+    );
+    for entry in state.error_log.recent() {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            entry.timestamp,
+            entry.level,
+            escape_html(&entry.target),
+            escape_html(&entry.message)
+        );
+    }
+    html.push_str("</table></body></html>");
+
+    html
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+/// Descriptions longer than this many words count towards complexity
+const LONG_DESCRIPTION_WORDS: usize = 150;
+
+/// Rough size of a ticket, as a hint before anyone votes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplexityLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// What the ticket's description and links say about its size. Purely local
+/// heuristics; meant to prompt discussion, not to replace the estimate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComplexityHints {
+    pub level: ComplexityLevel,
+    pub word_count: usize,
+    pub acceptance_criteria: usize,
+    pub linked_issues: usize,
+    pub has_code_blocks: bool,
+}
+
+/// Analyze a ticket's description (plain text, with code blocks fenced by ```)
+/// and the number of issues linked to it
+pub fn analyze_ticket(description: Option<&str>, linked_issues: usize) -> ComplexityHints {
+    let text = description.unwrap_or("");
+    let word_count = text.split_whitespace().count();
+    let acceptance_criteria = count_acceptance_criteria(text);
+    let has_code_blocks = text.contains("```") || text.contains("{code") || text.contains("{noformat");
+
+    let score = word_count / LONG_DESCRIPTION_WORDS
+        + acceptance_criteria / 3
+        + linked_issues
+        + if has_code_blocks { 2 } else { 0 };
+    let level = match score {
+        0..=2 => ComplexityLevel::Low,
+        3..=5 => ComplexityLevel::Medium,
+        _ => ComplexityLevel::High,
+    };
+
+    ComplexityHints {
+        level,
+        word_count,
+        acceptance_criteria,
+        linked_issues,
+        has_code_blocks,
+    }
+}
+
+/// Lines under an "Acceptance criteria" heading, up to the next heading-like
+/// line (one ending in a colon). Without such a heading, Gherkin-style
+/// "Given ..." scenarios are counted instead.
+fn count_acceptance_criteria(text: &str) -> usize {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let heading = lines.by_ref().find(|line| line.to_lowercase().contains("acceptance criteria"));
+    if heading.is_some() {
+        return lines.take_while(|line| !line.ends_with(':')).count();
+    }
+    text.lines()
+        .filter(|line| line.trim_start().to_lowercase().starts_with("given "))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acceptance_criteria_and_code_raise_the_level() {
+        let description = "Export the report.\n\
+                           Acceptance criteria:\n\
+                           - CSV download works\n\
+                           - Columns match the UI\n\
+                           - Empty reports show a message\n\
+                           Notes:\n\
+                           ```\nSELECT * FROM reports\n```";
+        let hints = analyze_ticket(Some(description), 1);
+        assert_eq!(hints.acceptance_criteria, 3);
+        assert!(hints.has_code_blocks);
+        assert_eq!(hints.level, ComplexityLevel::Medium);
+
+        let trivial = analyze_ticket(Some("Fix the typo in the footer"), 0);
+        assert_eq!(trivial.level, ComplexityLevel::Low);
+        assert_eq!(trivial.word_count, 6);
+    }
+}
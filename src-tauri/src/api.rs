@@ -1,25 +1,52 @@
-use crate::room::{Participant, Room, WsMessage, STORY_POINTS};
+use crate::basic_client;
+use crate::message_limits::{self, MAX_CLIENT_MESSAGE_BYTES};
+use crate::outbox::{self, ClientSender};
+use crate::recap::{format_round_summary, SummaryFormat};
+use crate::relay::{ParticipantEvent, ParticipantMessage, PublishedFile, RelayClient};
+use crate::room::{ClientError, ClientInfo, ErrorCode, JiraTicket, Participant, Room, WsMessage, STORY_POINTS};
+use crate::host_actions::{self, HostAction};
 use crate::state::AppState;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        ConnectInfo, Path, Query, Request, State,
     },
-    http::{header, Method, StatusCode},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    middleware::{self, Next},
     response::{Html, IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use base64::{Engine as _, engine::general_purpose};
 use futures_util::{SinkExt, StreamExt};
+use rust_embed::RustEmbed;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tauri::AppHandle;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tracing::Instrument;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 
-/// Start the API server
-pub async fn start_server(state: Arc<AppState>, _app_handle: AppHandle) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// Ports the API server tries, in order
+pub const DEFAULT_PORTS: RangeInclusive<u16> = 3030..=3050;
+
+/// Numbers HTTP requests and WebSocket connections so their log lines can be told apart
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Responses that may be cached but must be revalidated (with their ETag) every time
+const REVALIDATE: &str = "no-cache";
+/// Vite's build output under /assets has content hashes in the file names
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Start the API server on the first free port in `ports`
+/// (a range of `0..=0` lets the OS pick an ephemeral port)
+pub async fn start_server(state: Arc<AppState>, ports: RangeInclusive<u16>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Try to get local IP, fallback to localhost
     let local_ip = local_ip_address::local_ip()
         .map(|ip| ip.to_string())
@@ -31,66 +58,314 @@ pub async fn start_server(state: Arc<AppState>, _app_handle: AppHandle) -> Resul
         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
         .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
-    // Get the path to web-client/dist relative to the executable
-    let web_client_path = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        .map(|p| {
-            // In dev mode, go up from target/debug to project root
-            if p.ends_with("target\\debug") || p.ends_with("target/debug") {
-                p.parent().unwrap().parent().unwrap().join("web-client").join("dist")
-            } else {
-                p.join("web-client").join("dist")
-            }
-        })
-        .unwrap_or_else(|| std::path::PathBuf::from("web-client/dist"));
-    
-    tracing::info!("Serving web client from: {:?}", web_client_path);
+    let branding_path = crate::branding::branding_dir()
+        .unwrap_or_else(|_| std::path::PathBuf::from("branding"));
 
     let app = Router::new()
         // API routes
         .route("/api/room/:room_id", get(get_room))
+        .route("/api/room/:room_id/ticket", get(get_room_ticket))
+        .route("/api/room/:room_id/summary", get(get_round_summary))
+        .route("/api/room/:room_id/history", get(get_room_history))
         .route("/api/room/invite/:invite_code", get(get_room_by_invite))
         .route("/api/room/:room_id/join", post(join_room))
         .route("/api/story-points", get(get_story_points))
-        // WebSocket
-        .route("/ws", get(ws_handler))
+        .route("/api/host/:token/:action", get(controller_action).post(controller_action))
+        .route("/api/inbound/:provider", post(crate::sms::inbound))
+        .route("/api/ballot/:token", get(crate::ballots::get_ballot).post(crate::ballots::cast_vote))
+        .route("/ballot/:token", get(crate::ballots::ballot_page).post(crate::ballots::ballot_form))
+        // WebSocket, one per room
+        .route("/ws/:room_id", get(ws_handler))
         // Serve the web client HTML
         .route("/join/:room_id", get(serve_web_client))
+        // No-JavaScript fallback for screen readers and locked-down browsers
+        .route("/join/:room_id/basic", get(basic_client::room_page).post(basic_client::join))
+        .route("/join/:room_id/basic/vote", post(basic_client::vote))
+        .route("/join/:room_id/basic/leave", post(basic_client::leave))
         .route("/", get(serve_web_client_root))
-        // Serve static assets from web-client/dist
-        .nest_service("/assets", ServeDir::new(web_client_path.join("assets")))
+        // Serve uploaded room logos
+        .nest_service("/branding", ServeDir::new(branding_path))
+        // Serve static assets embedded from web-client/dist
+        .fallback(serve_web_client_asset)
+        .layer(middleware::from_fn_with_state(state.clone(), enforce_allowlist))
+        .layer(middleware::from_fn(request_span))
         .layer(cors)
         .with_state(state.clone());
 
-    // Try ports starting from 3030
-    let mut port = 3030;
-    let listener = loop {
-        match tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await {
-            Ok(l) => break l,
-            Err(_) => {
-                port += 1;
-                if port > 3050 {
-                    return Err("Could not find available port".into());
+    let mut listener = None;
+    for port in ports {
+        if let Ok(l) = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await {
+            listener = Some(l);
+            break;
+        }
+    }
+    let listener = listener.ok_or("Could not find available port")?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(serve_ipv6(app.clone(), port));
+
+    tracing::info!("API server running on http://{}:{}", local_ip, port);
+    state.set_server_info(local_ip, port);
+    tokio::spawn(basic_client::expire_sessions(state.clone()));
+    tokio::spawn(crate::state::enforce_ticket_deadlines(state.clone()));
+    tokio::spawn(crate::state::expire_disconnected_participants(state.clone()));
+    tokio::spawn(crate::state::advance_timed_reveals(state.clone()));
+    let admin_state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = start_admin_server(admin_state).await {
+            tracing::error!("Admin server failed: {}", e);
+        }
+    });
+    #[cfg(feature = "grpc")]
+    {
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::grpc::start_server(grpc_state).await {
+                tracing::error!("gRPC server failed: {}", e);
+            }
+        });
+    }
+
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+    Ok(())
+}
+
+/// Serve the same app over IPv6 on the same port, so participants can come in
+/// through a native or Teredo/6to4 address when IPv4 port forwarding fails.
+/// Where the IPv6 wildcard clashes with the IPv4 listener (dual-stack sockets),
+/// only the machine's routable IPv6 address is bound.
+async fn serve_ipv6(app: Router, port: u16) {
+    let listener = match tokio::net::TcpListener::bind((Ipv6Addr::UNSPECIFIED, port)).await {
+        Ok(listener) => listener,
+        Err(_) => {
+            let Some((address, _)) = crate::ipv6::detect() else {
+                return;
+            };
+            match tokio::net::TcpListener::bind((address, port)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::info!("Not serving over IPv6: {}", e);
+                    return;
                 }
             }
         }
     };
+    tracing::info!("API server also listening on {}", listener.local_addr().map(|a| a.to_string()).unwrap_or_default());
+    if let Err(e) = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await {
+        tracing::warn!("IPv6 listener stopped: {}", e);
+    }
+}
 
-    tracing::info!("API server running on http://{}:{}", local_ip, port);
-    state.set_server_info(local_ip, port);
+/// Serve the host-only pages on their own listener, bound to loopback on an
+/// ephemeral port, so nothing privileged is routed through the public port
+async fn start_admin_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let app = Router::new()
+        .route("/admin", get(crate::admin::admin_page))
+        .with_state(state.clone());
 
-    axum::serve(listener, app).await?;
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let port = listener.local_addr()?.port();
+    tracing::info!("Admin server running on http://127.0.0.1:{}", port);
+    *state.admin_port.write().unwrap() = port;
+
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
     Ok(())
 }
 
+/// Reject clients outside the configured network allowlist
+async fn enforce_allowlist(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.get_settings().is_client_allowed(addr.ip()) {
+        tracing::warn!("Rejected request from {} (not in allowlist)", addr.ip());
+        return (StatusCode::FORBIDDEN, "Access denied").into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether an address belongs to a private/local network
+fn is_lan_address(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_loopback() || v6.is_unique_local() || v6.is_unicast_link_local(),
+    }
+}
+
+/// Whether a client at `ip` may access a room, honoring the room's LAN-only flag.
+/// Clients bridged through the relay have no direct address and never count as LAN.
+pub(crate) fn can_access_room(room: &Room, ip: Option<IpAddr>) -> bool {
+    !room.lan_only || ip.is_some_and(is_lan_address)
+}
+
+pub(crate) const LAN_ONLY_MESSAGE: &str = "This room is only available on the local network";
+pub(crate) const SESSION_ENDED_MESSAGE: &str = "This session has ended";
+pub(crate) const ROOM_LOCKED_MESSAGE: &str = "This room is locked to new participants; ask the host to unlock it";
+
+/// Run a host action for an external controller such as a Stream Deck "website"
+/// button: `/api/host/:token/reveal`, `/hide`, `/reset` or `/next`. GET works as
+/// well as POST since that's all some controllers can send.
+async fn controller_action(
+    State(state): State<Arc<AppState>>,
+    Path((token, action)): Path<(String, String)>,
+) -> Response {
+    let Some(room_id) = state.room_for_controller(&token) else {
+        return (StatusCode::UNAUTHORIZED, "Invalid controller token").into_response();
+    };
+    let Some(action) = HostAction::from_path(&action) else {
+        return (StatusCode::NOT_FOUND, "Unknown action").into_response();
+    };
+    match host_actions::perform(&state, &room_id, action).await {
+        Ok(()) => (StatusCode::OK, "OK").into_response(),
+        Err(e) => (StatusCode::CONFLICT, e).into_response(),
+    }
+}
+
+/// A room as returned to the join page, with the countdown to its announced
+/// start worked out on the host's clock so a client's skewed clock doesn't matter
+#[derive(Debug, Serialize)]
+struct RoomResponse {
+    #[serde(flatten)]
+    room: Room,
+    /// Seconds until voting starts; absent if no start was announced or it has passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    starts_in_secs: Option<u64>,
+    /// The start time in the room's time zone, e.g. "2024-03-05 14:30 Europe/Berlin"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    starts_at_local: Option<String>,
+}
+
+impl From<Room> for RoomResponse {
+    /// Anyone can fetch a room, so it's the view of someone who hasn't voted
+    fn from(room: Room) -> Self {
+        let room = room.view_for("");
+        let starts_in_secs = room.starts_in_secs();
+        let starts_at_local = starts_in_secs.and(room.starts_at).map(|at| room.time_zone.format(at));
+        Self { room, starts_in_secs, starts_at_local }
+    }
+}
+
+impl RoomResponse {
+    /// The room's revision, plus the countdown while one runs, since that
+    /// changes without the room itself changing
+    fn etag(&self) -> String {
+        match self.starts_in_secs {
+            Some(secs) => format!("\"{}-{}\"", self.room.revision, secs),
+            None => format!("\"{}\"", self.room.revision),
+        }
+    }
+}
+
+/// Whether the client's copy (If-None-Match) is already this version
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag || tag == "*")
+}
+
+/// 304 Not Modified if the client has this version already, otherwise the full
+/// response; either way tagged with the ETag and Cache-Control
+fn cached_response(headers: &HeaderMap, etag: String, cache_control: &'static str, response: impl FnOnce() -> Response) -> Response {
+    let mut response = if etag_matches(headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        response()
+    };
+    let response_headers = response.headers_mut();
+    if let Ok(etag) = header::HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    response_headers.insert(header::CACHE_CONTROL, header::HeaderValue::from_static(cache_control));
+    response
+}
+
 /// Get a room by ID
 async fn get_room(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Path(room_id): Path<String>,
+    headers: HeaderMap,
 ) -> Response {
     match state.get_room(&room_id) {
-        Some(room) => Json(room).into_response(),
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) => {
+            let room = RoomResponse::from(room);
+            cached_response(&headers, room.etag(), REVALIDATE, || Json(room).into_response())
+        }
+        None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
+    }
+}
+
+/// Current ticket of a room, `ticket_hash` included
+#[derive(Debug, Serialize)]
+struct TicketResponse {
+    ticket: Option<JiraTicket>,
+    ticket_hash: Option<String>,
+}
+
+/// Get a room's current ticket; clients fetch this when a room update carries a
+/// ticket hash they haven't seen
+async fn get_room_ticket(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+) -> Response {
+    match state.get_room(&room_id) {
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) => Json(TicketResponse {
+            ticket: room.current_ticket,
+            ticket_hash: room.ticket_hash,
+        })
+        .into_response(),
+        None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
+    }
+}
+
+/// Rounds voted in the room so far; votes of rounds reset before the reveal aren't included
+async fn get_room_history(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+) -> Response {
+    match state.get_room(&room_id) {
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) => Json(room.voting_rounds()).into_response(),
+        None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryQuery {
+    #[serde(default)]
+    format: SummaryFormat,
+}
+
+/// Summary of the room's last reveal, formatted here so every client copies the same text
+async fn get_round_summary(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+    Query(query): Query<SummaryQuery>,
+) -> Response {
+    match state.get_room(&room_id) {
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) => match format_round_summary(&room, query.format) {
+            Ok(text) => text.into_response(),
+            Err(e) => (StatusCode::NOT_FOUND, e).into_response(),
+        },
         None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
     }
 }
@@ -98,13 +373,18 @@ async fn get_room(
 /// Get a room by invite code
 async fn get_room_by_invite(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Path(invite_code): Path<String>,
 ) -> Response {
     // Normalize invite code (remove spaces, handle URL encoding)
     let normalized = invite_code.replace("%20", " ").replace("-", " ");
     
     match state.get_room_by_invite(&normalized) {
-        Some(room) => Json(room).into_response(),
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) if room.locked => (StatusCode::LOCKED, ROOM_LOCKED_MESSAGE).into_response(),
+        Some(room) => Json(RoomResponse::from(room)).into_response(),
         None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
     }
 }
@@ -112,6 +392,14 @@ async fn get_room_by_invite(
 #[derive(Debug, Deserialize)]
 struct JoinRequest {
     name: String,
+    #[serde(default)]
+    identity: Option<String>,
+    /// Client details, sent only if the participant opted in
+    #[serde(default)]
+    client: Option<ClientInfo>,
+    /// Watch the room without voting
+    #[serde(default)]
+    observer: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -123,17 +411,44 @@ struct JoinResponse {
 /// Join a room as a participant
 async fn join_room(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Path(room_id): Path<String>,
+    headers: HeaderMap,
     Json(req): Json<JoinRequest>,
 ) -> Response {
-    let participant = Participant::new(req.name, false);
-    let participant_id = participant.id.clone();
-    
-    if state.add_participant(&room_id, participant).is_some() {
+    if !state.join_throttle.try_join(addr.ip()) {
+        return (StatusCode::TOO_MANY_REQUESTS, JOIN_THROTTLED_MESSAGE).into_response();
+    }
+
+    if let Some(room) = state.get_room(&room_id) {
+        if !can_access_room(&room, Some(addr.ip())) {
+            return (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response();
+        }
+        if room.is_ended() {
+            return (StatusCode::GONE, SESSION_ENDED_MESSAGE).into_response();
+        }
+        if !room.admits(req.identity.as_deref()) {
+            return (StatusCode::LOCKED, ROOM_LOCKED_MESSAGE).into_response();
+        }
+    }
+    if let Err(denied) = state.check_seat(&room_id, req.identity.as_deref()) {
+        return (StatusCode::CONFLICT, Json(denied)).into_response();
+    }
+
+    let name = match state.validate_participant_name(&req.name) {
+        Ok(name) => name,
+        Err(error) => return (StatusCode::BAD_REQUEST, Json(error)).into_response(),
+    };
+
+    let mut participant = Participant::new(name, false).with_identity(req.identity).observing(req.observer);
+    let user_agent = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok());
+    participant.client = req.client.map(|info| info.sanitized(user_agent));
+    if let Some(participant_id) = state.add_participant(&room_id, participant) {
         // Broadcast the update to all connected clients
         state.broadcast_room_update(&room_id).await;
         
         if let Some(room) = state.get_room(&room_id) {
+            let room = room.view_for(&participant_id);
             return Json(JoinResponse { participant_id, room }).into_response();
         }
     }
@@ -142,98 +457,524 @@ async fn join_room(
 }
 
 /// Get available story point values
-async fn get_story_points() -> Json<Vec<&'static str>> {
-    Json(STORY_POINTS.to_vec())
+#[derive(Debug, Deserialize)]
+struct StoryPointsQuery {
+    /// Room ID
+    room: Option<String>,
 }
 
-/// WebSocket upgrade handler
+/// Cards to vote with: the room's deck if one is asked for, else the default story points
+async fn get_story_points(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<StoryPointsQuery>,
+) -> Response {
+    let Some(room_id) = query.room else {
+        return Json(STORY_POINTS.to_vec()).into_response();
+    };
+    match state.get_room(&room_id) {
+        Some(room) if !can_access_room(&room, Some(addr.ip())) => {
+            (StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE).into_response()
+        }
+        Some(room) => Json(room.deck).into_response(),
+        None => (StatusCode::NOT_FOUND, "Room not found").into_response(),
+    }
+}
+
+/// WebSocket upgrade handler. The room (ID or invite code) is part of the URL
+/// and the connection stays bound to it. Browsers may only connect from pages
+/// this app served or from origins in the allowlist, so other sites can't drive
+/// a visitor's browser into a room.
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(socket, state))
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let origin = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok());
+    let host = headers.get(header::HOST).and_then(|v| v.to_str().ok());
+    if !state.get_settings().is_origin_allowed(origin, host) {
+        tracing::warn!("Rejected WebSocket from {} with origin {:?}", addr.ip(), origin);
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    let Some(room) = state
+        .get_room(&room_id)
+        .or_else(|| state.get_room_by_invite(&room_id.replace('-', " ")))
+    else {
+        return (StatusCode::NOT_FOUND, "Room not found").into_response();
+    };
+
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    // Frames past the limit are refused before they're buffered, dropping the connection
+    ws.max_message_size(MAX_CLIENT_MESSAGE_BYTES)
+        .max_frame_size(MAX_CLIENT_MESSAGE_BYTES)
+        .on_upgrade(move |socket| handle_websocket(socket, state, addr, room.id, user_agent))
+}
+
+/// Longest a single message may take to reach a client before it's considered stalled
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) const JOIN_THROTTLED_MESSAGE: &str = "Too many join attempts, try again in a minute";
+
+/// A participant connection, independent of whether it arrives directly or through the relay
+struct ClientSession {
+    /// Client address for direct connections; None when bridged through the relay
+    addr: Option<SocketAddr>,
+    tx: ClientSender,
+    participant_id: Option<String>,
+    room_id: Option<String>,
+    /// Set when the client misbehaved and the connection should be dropped
+    closing: bool,
+    /// User-Agent of the upgrade request, for direct connections
+    user_agent: Option<String>,
+    /// Room chosen when the connection was opened; direct connections can't join any other
+    bound_room: Option<String>,
+    /// Span for everything logged on behalf of this connection; gets the
+    /// participant ID once they've joined
+    span: tracing::Span,
+}
+
+impl ClientSession {
+    fn new(addr: Option<SocketAddr>, tx: ClientSender, span: tracing::Span) -> Self {
+        Self {
+            addr,
+            tx,
+            participant_id: None,
+            room_id: None,
+            closing: false,
+            user_agent: None,
+            bound_room: None,
+            span,
+        }
+    }
+}
+
+/// Log everything done for an HTTP request under a span with its own ID. A
+/// client-supplied `X-Request-Id` is used if present, and the ID is echoed back.
+async fn request_span(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 64)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("r{}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)));
+    let span = tracing::info_span!("request", id = %request_id, method = %request.method(), path = %request.uri().path());
+
+    let mut response = next.run(request).instrument(span).await;
+    if let Ok(value) = header::HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert("x-request-id", value);
+    }
+    response
+}
+
+/// Handle a single message from a participant
+async fn handle_client_message(state: &Arc<AppState>, session: &mut ClientSession, msg: WsMessage) {
+    let tx = session.tx.clone();
+    if session.participant_id.as_deref().is_some_and(|id| state.is_participant_traced(id)) {
+        tracing::info!(message = ?msg, "Traced participant sent a message");
+    }
+
+    match msg {
+        WsMessage::Join { room_id: rid, name, identity, client, observer } => {
+            // One participant per connection; otherwise a socket could be used to probe other rooms
+            if session.participant_id.is_some() {
+                let _ = tx.send(ClientError::new(ErrorCode::Other, "Already joined a room on this connection").into());
+                return;
+            }
+            let rid = session.bound_room.clone().unwrap_or(rid);
+            let ip = session.addr.map(|addr| addr.ip());
+            if ip.is_some_and(|ip| !state.join_throttle.try_join(ip)) {
+                tracing::warn!("Throttled join attempts from {:?}; closing connection", ip);
+                let _ = tx.send(ClientError::new(ErrorCode::Throttled, JOIN_THROTTLED_MESSAGE).into());
+                session.closing = true;
+                return;
+            }
+            if state.get_room(&rid).is_some_and(|room| !can_access_room(&room, ip)) {
+                let _ = tx.send(ClientError::new(ErrorCode::LanOnly, LAN_ONLY_MESSAGE).into());
+                return;
+            }
+            if state.get_room(&rid).is_some_and(|room| room.is_ended()) {
+                let _ = tx.send(ClientError::new(ErrorCode::SessionEnded, SESSION_ENDED_MESSAGE).into());
+                return;
+            }
+            if state.get_room(&rid).is_some_and(|room| !room.admits(identity.as_deref())) {
+                let _ = tx.send(ClientError::new(ErrorCode::RoomLocked, ROOM_LOCKED_MESSAGE).into());
+                return;
+            }
+            if let Err(denied) = state.check_seat(&rid, identity.as_deref()) {
+                let _ = tx.send(ClientError::from(denied).into());
+                return;
+            }
+
+            let name = match state.validate_participant_name(&name) {
+                Ok(name) => name,
+                Err(error) => {
+                    let _ = tx.send(ClientError::from(error).into());
+                    return;
+                }
+            };
+
+            // Create participant and join room
+            let mut participant = Participant::new(name, false).with_identity(identity).observing(observer);
+            participant.client = client.map(|info| info.sanitized(session.user_agent.as_deref()));
+
+            // Someone rejoining with their identity token gets their old place back
+            if let Some(pid) = state.add_participant(&rid, participant) {
+                session.participant_id = Some(pid.clone());
+                session.room_id = Some(rid.clone());
+                session.span.record("participant", pid.as_str());
+                
+                // Register connection
+                state.register_connection(pid.clone(), rid.clone(), tx.clone());
+                
+                // Send room update to all
+                state.broadcast_room_update(&rid).await;
+
+                // Bring the joiner up to speed on where the session is
+                if let Some(room) = state.get_room(&rid) {
+                    state.send_to_participant(&rid, &pid, WsMessage::CatchUp(room.catch_up(&pid)));
+                }
+            } else {
+                let _ = tx.send(ClientError::new(ErrorCode::RoomNotFound, "Room not found").into());
+            }
+        }
+        WsMessage::Vote { vote } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.set_vote(rid, pid, vote) {
+                    Ok(()) => {
+                        state.auto_reveal_if_ready(rid).await;
+                        state.broadcast_room_update(rid).await
+                    }
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, error.into());
+                    }
+                }
+            }
+        }
+        WsMessage::Delegate { to } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.delegate_vote(rid, pid, to.as_deref()) {
+                    Ok(()) => {
+                        state.auto_reveal_if_ready(rid).await;
+                        state.broadcast_room_update(rid).await
+                    }
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, error.into());
+                    }
+                }
+            }
+        }
+        WsMessage::SitOut { sitting_out } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.set_sitting_out(rid, pid, sitting_out) {
+                    Ok(()) => {
+                        state.auto_reveal_if_ready(rid).await;
+                        state.broadcast_room_update(rid).await
+                    }
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, error.into());
+                    }
+                }
+            }
+        }
+        WsMessage::Signal { signal, raised } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.set_signal(rid, pid, signal, raised) {
+                    Ok(()) => state.broadcast_room_update(rid).await,
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, error.into());
+                    }
+                }
+            }
+        }
+        WsMessage::RiskVote { vote } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.set_risk_vote(rid, pid, vote) {
+                    Ok(()) => state.broadcast_room_update(rid).await,
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, error.into());
+                    }
+                }
+            }
+        }
+        WsMessage::Rename { name } => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                match state.validate_participant_name(&name) {
+                    Ok(name) => {
+                        if state.rename_participant(rid, pid, name) {
+                            state.broadcast_room_update(rid).await;
+                        }
+                    }
+                    Err(error) => {
+                        state.send_to_participant(rid, pid, ClientError::from(error).into());
+                    }
+                }
+            }
+        }
+        WsMessage::Resync => {
+            if let (Some(pid), Some(rid)) = (&session.participant_id, &session.room_id) {
+                if let Some(room) = state.get_room(rid) {
+                    tracing::warn!(
+                        "Participant {} requested a resync of room {} at revision {}",
+                        pid,
+                        room.id,
+                        room.revision
+                    );
+                    state.send_to_participant(rid, pid, room.ticket_changed_message());
+                    state.send_to_participant(rid, pid, room.update_message(pid));
+                }
+            }
+        }
+        WsMessage::TimeSync { client_time } => {
+            let _ = tx.send(WsMessage::TimeSyncReply { client_time, server_time: state.env.now_millis() });
+        }
+        WsMessage::Ping => {
+            let _ = tx.send(WsMessage::Pong);
+        }
+        _ => {}
+    }
+}
+
+/// Clean up after a participant disconnects. They're kept, greyed out, for the
+/// room's grace period in case they're only refreshing the page.
+async fn end_client_session(state: &Arc<AppState>, session: ClientSession) {
+    if let (Some(pid), Some(rid)) = (session.participant_id, session.room_id) {
+        state.participant_disconnected(&rid, &pid, &session.tx);
+        state.broadcast_room_update(&rid).await;
+    }
 }
 
 /// Handle WebSocket connection
-async fn handle_websocket(socket: WebSocket, state: Arc<AppState>) {
+async fn handle_websocket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    addr: SocketAddr,
+    room_id: String,
+    user_agent: Option<String>,
+) {
     let (mut sender, mut receiver) = socket.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<WsMessage>();
-    
-    let mut participant_id: Option<String> = None;
-    let mut room_id: Option<String> = None;
+    let (tx, mut rx) = outbox::channel();
+
+    let span = tracing::info_span!(
+        "connection",
+        id = %format!("c{}", NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed)),
+        room = %room_id,
+        %addr,
+        participant = tracing::field::Empty,
+    );
+    let mut session = ClientSession::new(Some(addr), tx, span.clone());
+    session.user_agent = user_agent;
+    session.bound_room = Some(room_id);
 
-    // Spawn task to forward messages from channel to websocket
-    let send_task = tokio::spawn(async move {
+    // Spawn task to forward messages from the outbox to the websocket. It ends
+    // when the client stops keeping up, which drops the connection.
+    let mut send_task = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
             if let Ok(text) = serde_json::to_string(&msg) {
-                if sender.send(Message::Text(text.into())).await.is_err() {
-                    break;
+                match tokio::time::timeout(SEND_TIMEOUT, sender.send(Message::Text(text.into()))).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(_)) => break,
+                    Err(_) => {
+                        tracing::warn!("Client {} stopped reading for {:?}; disconnecting", addr, SEND_TIMEOUT);
+                        break;
+                    }
                 }
             }
         }
-    });
+    }.instrument(span.clone()));
 
     // Handle incoming messages
-    while let Some(result) = receiver.next().await {
-        match result {
-            Ok(Message::Text(text)) => {
-                if let Ok(msg) = serde_json::from_str::<WsMessage>(&text) {
-                    match msg {
-                        WsMessage::Join { room_id: rid, name } => {
-                            // Create participant and join room
-                            let participant = Participant::new(name, false);
-                            let pid = participant.id.clone();
-                            
-                            if state.add_participant(&rid, participant).is_some() {
-                                participant_id = Some(pid.clone());
-                                room_id = Some(rid.clone());
-                                
-                                // Register connection
-                                state.register_connection(pid, rid.clone(), tx.clone());
-                                
-                                // Send room update to all
-                                state.broadcast_room_update(&rid).await;
-                            } else {
-                                let _ = tx.send(WsMessage::Error {
-                                    message: "Room not found".to_string(),
-                                });
-                            }
-                        }
-                        WsMessage::Vote { vote } => {
-                            if let (Some(pid), Some(rid)) = (&participant_id, &room_id) {
-                                state.set_vote(rid, pid, vote);
-                                state.broadcast_room_update(rid).await;
-                            }
-                        }
-                        WsMessage::Ping => {
-                            let _ = tx.send(WsMessage::Pong);
+    loop {
+        tokio::select! {
+            result = receiver.next() => match result {
+                Some(Ok(Message::Text(text))) => {
+                    if let Err(reason) = message_limits::check_inbound(&text, MAX_CLIENT_MESSAGE_BYTES) {
+                        tracing::warn!("Dropping connection from {}: {}", addr, reason);
+                        break;
+                    }
+                    if let Ok(msg) = serde_json::from_str::<WsMessage>(&text) {
+                        let span = session.span.clone();
+                        handle_client_message(&state, &mut session, msg).instrument(span).await;
+                        if session.closing {
+                            break;
                         }
-                        _ => {}
                     }
                 }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+            _ = &mut send_task => {
+                tracing::warn!("Dropping connection from {}: it fell behind or the participant rejoined elsewhere", addr);
+                break;
             }
-            Ok(Message::Close(_)) => break,
-            Err(_) => break,
-            _ => {}
         }
     }
 
     // Cleanup on disconnect
-    if let (Some(pid), Some(rid)) = (participant_id, room_id) {
-        state.unregister_connection(&pid);
-        state.remove_participant(&rid, &pid);
-        state.broadcast_room_update(&rid).await;
-    }
+    let span = session.span.clone();
+    end_client_session(&state, session).instrument(span).await;
 
     send_task.abort();
 }
 
-/// Serve the web client HTML (embedded or redirect to dev server)
-async fn serve_web_client_root() -> Html<&'static str> {
-    serve_web_client(Path(String::new())).await
+/// Bridge participants connected to the relay into local room state.
+/// Each relay connection gets a session just like a direct WebSocket, with replies
+/// wrapped in a `ParticipantMessage` envelope and sent back through the relay.
+/// Holds the relay client weakly so dropping it ends the bridge.
+pub async fn bridge_relay_participants(
+    state: Arc<AppState>,
+    relay_client: Weak<RelayClient>,
+    mut events: mpsc::UnboundedReceiver<ParticipantEvent>,
+) {
+    let mut sessions: HashMap<String, ClientSession> = HashMap::new();
+
+    while let Some(event) = events.recv().await {
+        match event {
+            ParticipantEvent::Connected(connection_id) => {
+                let (tx, mut rx) = outbox::channel();
+                let relay = relay_client.clone();
+                let id = connection_id.clone();
+                tokio::spawn(async move {
+                    while let Some(message) = rx.recv().await {
+                        let Some(relay) = relay.upgrade() else { break };
+                        let envelope = ParticipantMessage {
+                            connection_id: id.clone(),
+                            message,
+                        };
+                        if relay.send_participant_message(envelope).is_err() {
+                            break;
+                        }
+                    }
+                });
+                let span = tracing::info_span!("relay_connection", id = %connection_id, participant = tracing::field::Empty);
+                sessions.insert(connection_id, ClientSession::new(None, tx, span));
+            }
+            ParticipantEvent::Message(envelope) => {
+                match sessions.get_mut(&envelope.connection_id) {
+                    Some(session) => {
+                        let span = session.span.clone();
+                        handle_client_message(&state, session, envelope.message).instrument(span).await
+                    }
+                    None => tracing::warn!("Message for unknown relay connection {}", envelope.connection_id),
+                }
+            }
+            ParticipantEvent::Disconnected(connection_id) => {
+                if let Some(session) = sessions.remove(&connection_id) {
+                    let span = session.span.clone();
+                    end_client_session(&state, session).instrument(span).await;
+                }
+            }
+        }
+    }
+
+    // Relay connection is gone; drop everyone who came through it
+    for (_, session) in sessions {
+        end_client_session(&state, session).await;
+    }
+}
+
+/// Web client files to publish to the relay so remote participants can load the client from it
+pub fn web_client_files() -> Vec<PublishedFile> {
+    WebClientAssets::iter()
+        .filter_map(|path| {
+            WebClientAssets::get(&path).map(|file| PublishedFile {
+                path: path.to_string(),
+                content_type: file.metadata.mimetype().to_string(),
+                data: general_purpose::STANDARD.encode(&file.data),
+            })
+        })
+        .collect()
+}
+
+/// The built web client, embedded into the executable
+#[derive(RustEmbed)]
+#[folder = "../web-client/dist"]
+struct WebClientAssets;
+
+/// Serve the web client HTML
+async fn serve_web_client_root(state: State<Arc<AppState>>, uri: Uri, headers: HeaderMap) -> Response {
+    serve_web_client(state, Path(String::new()), uri, headers).await
+}
+
+async fn serve_web_client(
+    State(state): State<Arc<AppState>>,
+    Path(_room_id): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(dev_url) = state.get_settings().web_client_dev_url {
+        return proxy_to_dev_server(&dev_url, &uri).await;
+    }
+
+    match WebClientAssets::get("index.html") {
+        // The page names the current bundles, so browsers have to check it's still current
+        Some(file) => cached_response(&headers, asset_etag(&file), REVALIDATE, || Html(file.data).into_response()),
+        None => (StatusCode::NOT_FOUND, "Web client not built").into_response(),
+    }
 }
 
-async fn serve_web_client(Path(_room_id): Path<String>) -> Html<&'static str> {
-    Html(include_str!("../../web-client/dist/index.html"))
+/// ETag of an embedded file, from the content hash computed at build time
+fn asset_etag(file: &rust_embed::EmbeddedFile) -> String {
+    let hash: String = file.metadata.sha256_hash()[..16].iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("\"{}\"", hash)
 }
 
+/// Serve any other embedded web client file (JS/CSS bundles, icons)
+async fn serve_web_client_asset(State(state): State<Arc<AppState>>, uri: Uri, headers: HeaderMap) -> Response {
+    // Vite serves modules from many paths (/src, /@vite, /node_modules), so forward everything
+    if let Some(dev_url) = state.get_settings().web_client_dev_url {
+        return proxy_to_dev_server(&dev_url, &uri).await;
+    }
+
+    let path = uri.path().trim_start_matches('/');
+
+    match WebClientAssets::get(path) {
+        Some(file) => {
+            let cache_control = if path.starts_with("assets/") { IMMUTABLE } else { REVALIDATE };
+            cached_response(&headers, asset_etag(&file), cache_control, || {
+                ([(header::CONTENT_TYPE, file.metadata.mimetype().to_string())], file.data).into_response()
+            })
+        }
+        None => (StatusCode::NOT_FOUND, "Not found").into_response(),
+    }
+}
+
+
+/// Forward a GET request to the Vite dev server
+async fn proxy_to_dev_server(dev_url: &str, uri: &Uri) -> Response {
+    let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let url = format!("{}{}", dev_url.trim_end_matches('/'), path_and_query);
+
+    // The dev server is local, so never route it through a proxy
+    let client = match reqwest::Client::builder().no_proxy().build() {
+        Ok(client) => client,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let response = match client.get(&url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("Dev server request to {} failed: {}", url, e);
+            return (StatusCode::BAD_GATEWAY, format!("Web client dev server unavailable: {}", e)).into_response();
+        }
+    };
+
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    match response.bytes().await {
+        Ok(body) => (status, [(header::CONTENT_TYPE, content_type)], body).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,308 @@
+use crate::basic_client::page;
+use crate::credentials::get_data_dir;
+use crate::recap::escape_html;
+use crate::room::{JiraTicket, Room};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    Form, Json,
+};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+const BALLOTS_FILE: &str = "async_ballots.json";
+
+/// Someone to collect estimates from without a live session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncVoter {
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// One voter's personal ballot for a room's tickets, reached through the
+/// unguessable token in their voting link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ballot {
+    pub token: String,
+    pub room_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Tickets to estimate, as they were when the ballots were issued
+    pub tickets: Vec<JiraTicket>,
+    /// Votes cast so far, keyed by ticket key
+    #[serde(default)]
+    pub votes: HashMap<String, String>,
+    pub created_at: u64,
+}
+
+/// What a voter sees: their tickets, the room's cards and their votes so far
+#[derive(Debug, Serialize)]
+pub struct BallotView {
+    pub room_name: String,
+    pub name: String,
+    pub deck: Vec<String>,
+    pub tickets: Vec<JiraTicket>,
+    pub votes: HashMap<String, String>,
+}
+
+/// Everyone's async votes on one ticket
+#[derive(Debug, Clone, Serialize)]
+pub struct AsyncTicketResult {
+    pub ticket_key: String,
+    pub summary: String,
+    /// Voter name and card, in the order the ballots were issued
+    pub votes: Vec<(String, String)>,
+    /// Voters who haven't estimated this ticket yet
+    pub waiting_for: Vec<String>,
+}
+
+/// Ballots for each voter covering the room's current ticket and its queue
+pub fn issue_ballots(room: &Room, voters: &[AsyncVoter], state: &AppState) -> Result<Vec<Ballot>, String> {
+    let tickets: Vec<JiraTicket> = room.current_ticket.iter().chain(&room.ticket_queue).cloned().collect();
+    if tickets.is_empty() {
+        return Err("Queue some tickets before sending out ballots".into());
+    }
+    voters
+        .iter()
+        .map(|voter| {
+            let name = state.validate_participant_name(&voter.name).map_err(|e| e.message)?;
+            Ok(Ballot {
+                token: uuid::Uuid::new_v4().simple().to_string(),
+                room_id: room.id.clone(),
+                name,
+                email: voter.email.clone().filter(|email| !email.trim().is_empty()),
+                tickets: tickets.clone(),
+                votes: HashMap::new(),
+                created_at: state.env.now_secs(),
+            })
+        })
+        .collect()
+}
+
+/// Results per ticket from a room's ballots
+pub fn tally<'a>(ballots: impl Iterator<Item = &'a Ballot> + Clone) -> Vec<AsyncTicketResult> {
+    let Some(first) = ballots.clone().next() else {
+        return Vec::new();
+    };
+    first
+        .tickets
+        .iter()
+        .map(|ticket| {
+            let (voted, waiting): (Vec<&Ballot>, Vec<&Ballot>) =
+                ballots.clone().partition(|ballot| ballot.votes.contains_key(&ticket.key));
+            AsyncTicketResult {
+                ticket_key: ticket.key.clone(),
+                summary: ticket.summary.clone(),
+                votes: voted.iter().map(|b| (b.name.clone(), b.votes[&ticket.key].clone())).collect(),
+                waiting_for: waiting.iter().map(|b| b.name.clone()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Write the digest for a batch of ballots into `dir`: a CSV of everyone's voting
+/// link, plus an unsent email (.eml) per voter with an address. Returns the CSV path.
+pub fn write_digest(room: &Room, ballots: &[Ballot], base_url: &str, dir: &FsPath) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create digest dir: {}", e))?;
+    let safe_name: String = room.name.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+
+    let mut csv = String::from("name,email,link\n");
+    for ballot in ballots {
+        let link = format!("{}/ballot/{}", base_url, ballot.token);
+        let _ = writeln!(
+            csv,
+            "{},{},{}",
+            csv_field(&ballot.name),
+            csv_field(ballot.email.as_deref().unwrap_or("")),
+            link
+        );
+        if let Some(email) = &ballot.email {
+            let file = dir.join(format!("ballot_{}_{}.eml", safe_name, &ballot.token[..8]));
+            fs::write(&file, build_ballot_email(room, ballot, email, &link))
+                .map_err(|e| format!("Failed to write ballot email: {}", e))?;
+        }
+    }
+
+    let path = dir.join(format!("ballots_{}_{}.csv", safe_name, ballots.first().map_or(0, |b| b.created_at)));
+    fs::write(&path, csv).map_err(|e| format!("Failed to write ballot digest: {}", e))?;
+    Ok(path)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Unsent email inviting one voter to estimate, in the same form as the session recap
+fn build_ballot_email(room: &Room, ballot: &Ballot, to: &str, link: &str) -> String {
+    let mut html = format!(
+        "<html><body style=\"font-family: sans-serif\"><p>Hi {},</p>\
+         <p>Please estimate these tickets for <strong>{}</strong> when you have a moment:</p><ul>",
+        escape_html(&ballot.name),
+        escape_html(&room.name)
+    );
+    for ticket in &ballot.tickets {
+        let _ = write!(html, "<li>{}: {}</li>", escape_html(&ticket.key), escape_html(&ticket.summary));
+    }
+    let _ = write!(
+        html,
+        "</ul><p><a href=\"{0}\">{0}</a></p><p>The link is personal to you.</p></body></html>",
+        escape_html(link)
+    );
+
+    let mut email = format!(
+        "To: {}\r\nSubject: =?UTF-8?B?{}?=\r\nX-Unsent: 1\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/html; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+        to,
+        general_purpose::STANDARD.encode(format!("Estimates needed: {}", room.name))
+    );
+    for chunk in general_purpose::STANDARD.encode(html).as_bytes().chunks(76) {
+        email.push_str(&String::from_utf8_lossy(chunk));
+        email.push_str("\r\n");
+    }
+    email
+}
+
+pub fn load_ballots() -> Result<Vec<Ballot>, String> {
+    let path = get_data_dir()?.join(BALLOTS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read ballots: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse ballots: {}", e))
+}
+
+pub fn save_ballots(ballots: &[Ballot]) -> Result<(), String> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(ballots).map_err(|e| format!("Failed to serialize ballots: {}", e))?;
+    fs::write(data_dir.join(BALLOTS_FILE), json).map_err(|e| format!("Failed to write ballots: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BallotVote {
+    ticket_key: String,
+    /// A card, or None (or an empty string from the HTML form) to take the vote back
+    vote: Option<String>,
+}
+
+/// `GET /api/ballot/:token`
+pub async fn get_ballot(State(state): State<Arc<AppState>>, Path(token): Path<String>) -> Response {
+    match state.ballot_view(&token) {
+        Some(view) => Json(view).into_response(),
+        None => (StatusCode::NOT_FOUND, "Ballot not found").into_response(),
+    }
+}
+
+/// `POST /api/ballot/:token` with `{"ticket_key": ..., "vote": ...}`
+pub async fn cast_vote(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    Json(vote): Json<BallotVote>,
+) -> Response {
+    match record_vote(&state, &token, vote) {
+        Ok(view) => Json(view).into_response(),
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+/// Cast a vote from either endpoint and save the ballots
+fn record_vote(state: &AppState, token: &str, vote: BallotVote) -> Result<BallotView, (StatusCode, String)> {
+    if state.ballot_view(token).is_none() {
+        return Err((StatusCode::NOT_FOUND, "Ballot not found".into()));
+    }
+    let card = vote.vote.filter(|card| !card.is_empty());
+    let view = state
+        .cast_ballot_vote(token, &vote.ticket_key, card)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    if let Err(e) = save_ballots(&state.all_ballots()) {
+        tracing::warn!("Failed to save ballots: {}", e);
+    }
+    Ok(view)
+}
+
+/// The voting link from the email: a plain form, one ticket per row
+pub async fn ballot_page(State(state): State<Arc<AppState>>, Path(token): Path<String>) -> Response {
+    match state.ballot_view(&token) {
+        Some(view) => Html(render_ballot_page(&view, &token, None)).into_response(),
+        None => (StatusCode::NOT_FOUND, Html(page("Scrum Poker", None, "<p role=\"alert\">Ballot not found</p>")))
+            .into_response(),
+    }
+}
+
+/// Form posts from the ballot page go through the same checks as the REST endpoint
+pub async fn ballot_form(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    Form(vote): Form<BallotVote>,
+) -> Response {
+    match record_vote(&state, &token, vote) {
+        Ok(_) => Redirect::to(&format!("/ballot/{}", token)).into_response(),
+        Err((status, message)) => match state.ballot_view(&token) {
+            Some(view) => (status, Html(render_ballot_page(&view, &token, Some(&message)))).into_response(),
+            None => (status, message).into_response(),
+        },
+    }
+}
+
+fn render_ballot_page(view: &BallotView, token: &str, notice: Option<&str>) -> String {
+    let mut body = format!("<h1>{}</h1><p>Estimates from {}</p>", escape_html(&view.room_name), escape_html(&view.name));
+    if let Some(notice) = notice {
+        let _ = write!(body, "<p role=\"alert\">{}</p>", escape_html(notice));
+    }
+    for ticket in &view.tickets {
+        let current = view.votes.get(&ticket.key);
+        let _ = write!(
+            body,
+            "<form method=\"post\" action=\"/ballot/{}\"><h2>{}: {}</h2>\
+             <input type=\"hidden\" name=\"ticket_key\" value=\"{}\">\
+             <label>Your estimate <select name=\"vote\"><option value=\"\">Not yet</option>",
+            escape_html(token),
+            escape_html(&ticket.key),
+            escape_html(&ticket.summary),
+            escape_html(&ticket.key)
+        );
+        for card in &view.deck {
+            let selected = if current == Some(card) { " selected" } else { "" };
+            let _ = write!(body, "<option{}>{}</option>", selected, escape_html(card));
+        }
+        body.push_str("</select></label> <button type=\"submit\">Save</button></form>");
+    }
+    page(&format!("{} - Scrum Poker", view.room_name), None, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+
+    #[test]
+    fn ballots_only_take_cards_for_their_own_tickets() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Async".into(), None, None).unwrap();
+        state.set_current_ticket(&room.id, Some(JiraTicket { key: "POKER-1".into(), ..Default::default() }));
+        let voters = [AsyncVoter { name: "Ada".into(), email: None }, AsyncVoter { name: "Bob".into(), email: None }];
+        let ballots = state.issue_async_ballots(&room.id, &voters).unwrap();
+
+        assert!(state.cast_ballot_vote(&ballots[0].token, "POKER-1", Some("5".into())).is_ok());
+        assert!(state.cast_ballot_vote(&ballots[0].token, "POKER-1", Some("banana".into())).is_err());
+        assert!(state.cast_ballot_vote(&ballots[0].token, "POKER-2", Some("5".into())).is_err());
+        assert!(state.cast_ballot_vote("not-a-token", "POKER-1", Some("5".into())).is_err());
+
+        let results = state.async_results(&room.id);
+        assert_eq!(results[0].votes, [("Ada".to_string(), "5".to_string())]);
+        assert_eq!(results[0].waiting_for, ["Bob"]);
+    }
+}
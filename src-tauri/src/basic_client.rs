@@ -0,0 +1,393 @@
+use crate::api::{can_access_room, JOIN_THROTTLED_MESSAGE, LAN_ONLY_MESSAGE, ROOM_LOCKED_MESSAGE, SESSION_ENDED_MESSAGE};
+use crate::recap::escape_html;
+use crate::room::{Participant, Room};
+use crate::state::AppState;
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Seconds between automatic page refreshes
+const REFRESH_SECS: u64 = 10;
+/// A participant who hasn't loaded the page for this long has closed it
+const SESSION_TIMEOUT_SECS: u64 = 60;
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Someone taking part through the plain HTML page. There's no connection to
+/// notice them leaving, so they're dropped once they stop refreshing.
+#[derive(Debug, Clone)]
+pub struct BasicSession {
+    pub room_id: String,
+    pub participant_id: String,
+    pub last_seen: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    session: Option<String>,
+    /// `auto=0` turns off the automatic refresh, which some screen readers find disruptive
+    auto: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinForm {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VoteForm {
+    session: String,
+    vote: Option<String>,
+    risk_vote: Option<String>,
+    /// "vote" or "risk" to take back a vote
+    clear: Option<String>,
+    auto: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaveForm {
+    session: String,
+}
+
+/// The room page, or the join form if the visitor hasn't joined yet
+pub async fn room_page(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> Response {
+    let room = match find_room(&state, &room_id, addr) {
+        Ok(room) => room,
+        Err((status, message)) => return message_page(status, message),
+    };
+    let auto_refresh = query.auto != Some(0);
+
+    let Some(token) = query.session else {
+        return Html(render_join_page(&room, None)).into_response();
+    };
+    let Some(participant_id) = touch_session(&state, &token, &room.id) else {
+        return Html(render_join_page(&room, Some("Your session has expired. Join again to keep voting."))).into_response();
+    };
+    if !room.participants.iter().any(|p| p.id == participant_id) {
+        state.basic_sessions.remove(&token);
+        return Html(render_join_page(&room, Some("You were removed from the room."))).into_response();
+    }
+
+    let room = room.view_for(&participant_id);
+    Html(render_room_page(&room, &participant_id, &token, auto_refresh)).into_response()
+}
+
+pub async fn join(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(room_id): Path<String>,
+    Form(form): Form<JoinForm>,
+) -> Response {
+    let room = match find_room(&state, &room_id, addr) {
+        Ok(room) => room,
+        Err((status, message)) => return message_page(status, message),
+    };
+    if !state.join_throttle.try_join(addr.ip()) {
+        return message_page(StatusCode::TOO_MANY_REQUESTS, JOIN_THROTTLED_MESSAGE);
+    }
+    if !room.admits(None) {
+        return message_page(StatusCode::LOCKED, ROOM_LOCKED_MESSAGE);
+    }
+    if let Err(denied) = state.check_seat(&room.id, None) {
+        return message_page(StatusCode::CONFLICT, &denied.message);
+    }
+
+    let name = match state.validate_participant_name(&form.name) {
+        Ok(name) => name,
+        Err(error) => {
+            return (StatusCode::BAD_REQUEST, Html(render_join_page(&room, Some(&error.message)))).into_response();
+        }
+    };
+
+    let participant = Participant::new(name, false);
+    let participant_id = participant.id.clone();
+    if state.add_participant(&room.id, participant).is_none() {
+        return message_page(StatusCode::NOT_FOUND, "Room not found");
+    }
+    state.broadcast_room_update(&room.id).await;
+
+    let token = state.env.new_id();
+    state.basic_sessions.insert(
+        token.clone(),
+        BasicSession {
+            room_id: room.id.clone(),
+            participant_id,
+            last_seen: state.env.now_secs(),
+        },
+    );
+    Redirect::to(&page_url(&room.id, &token, true)).into_response()
+}
+
+pub async fn vote(
+    State(state): State<Arc<AppState>>,
+    Path(room_id): Path<String>,
+    Form(form): Form<VoteForm>,
+) -> Response {
+    let auto_refresh = form.auto != Some(0);
+    let Some(room) = state.get_room(&room_id) else {
+        return message_page(StatusCode::NOT_FOUND, "Room not found");
+    };
+    let Some(participant_id) = touch_session(&state, &form.session, &room.id) else {
+        return Redirect::to(&format!("/join/{}/basic", room.id)).into_response();
+    };
+
+    let result = match (form.clear.as_deref(), form.vote, form.risk_vote) {
+        (Some("vote"), _, _) => state.set_vote(&room.id, &participant_id, None),
+        (Some("risk"), _, _) => state.set_risk_vote(&room.id, &participant_id, None),
+        (_, Some(vote), _) => state.set_vote(&room.id, &participant_id, Some(vote)),
+        (_, _, Some(risk_vote)) => state.set_risk_vote(&room.id, &participant_id, Some(risk_vote)),
+        _ => Ok(()),
+    };
+    if let Err(error) = result {
+        return message_page(StatusCode::BAD_REQUEST, &error.message);
+    }
+    state.auto_reveal_if_ready(&room.id).await;
+    state.broadcast_room_update(&room.id).await;
+
+    Redirect::to(&page_url(&room.id, &form.session, auto_refresh)).into_response()
+}
+
+pub async fn leave(
+    State(state): State<Arc<AppState>>,
+    Path(room_id): Path<String>,
+    Form(form): Form<LeaveForm>,
+) -> Response {
+    if let Some((_, session)) = state.basic_sessions.remove(&form.session) {
+        state.remove_participant(&session.room_id, &session.participant_id);
+        state.broadcast_room_update(&session.room_id).await;
+    }
+    let Some(room) = state.get_room(&room_id) else {
+        return message_page(StatusCode::NOT_FOUND, "Room not found");
+    };
+    Redirect::to(&format!("/join/{}/basic", room.id)).into_response()
+}
+
+/// Periodically drop participants whose page has stopped refreshing
+pub async fn expire_sessions(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let cutoff = state.env.now_secs().saturating_sub(SESSION_TIMEOUT_SECS);
+        let expired: Vec<String> = state
+            .basic_sessions
+            .iter()
+            .filter(|session| session.last_seen < cutoff)
+            .map(|session| session.key().clone())
+            .collect();
+
+        for token in expired {
+            if let Some((_, session)) = state.basic_sessions.remove(&token) {
+                tracing::info!("Basic client {} stopped refreshing; removing them", session.participant_id);
+                state.remove_participant(&session.room_id, &session.participant_id);
+                state.broadcast_room_update(&session.room_id).await;
+            }
+        }
+    }
+}
+
+/// Look up a room by ID or invite code, applying the same checks as other joins
+fn find_room(state: &AppState, room_id: &str, addr: SocketAddr) -> Result<Room, (StatusCode, &'static str)> {
+    let room = state
+        .get_room(room_id)
+        .or_else(|| state.get_room_by_invite(&room_id.replace('-', " ")))
+        .ok_or((StatusCode::NOT_FOUND, "Room not found"))?;
+    if !can_access_room(&room, Some(addr.ip())) {
+        return Err((StatusCode::FORBIDDEN, LAN_ONLY_MESSAGE));
+    }
+    if room.is_ended() {
+        return Err((StatusCode::GONE, SESSION_ENDED_MESSAGE));
+    }
+    Ok(room)
+}
+
+/// Participant ID for a session token, marking the session as still active
+fn touch_session(state: &AppState, token: &str, room_id: &str) -> Option<String> {
+    let mut session = state.basic_sessions.get_mut(token)?;
+    if session.room_id != room_id {
+        return None;
+    }
+    session.last_seen = state.env.now_secs();
+    Some(session.participant_id.clone())
+}
+
+fn page_url(room_id: &str, token: &str, auto_refresh: bool) -> String {
+    let mut url = format!("/join/{}/basic?session={}", room_id, token);
+    if !auto_refresh {
+        url.push_str("&auto=0");
+    }
+    url
+}
+
+fn message_page(status: StatusCode, message: &str) -> Response {
+    (status, Html(page("Scrum Poker", None, &format!("<p role=\"alert\">{}</p>", escape_html(message))))).into_response()
+}
+
+pub(crate) fn page(title: &str, refresh: Option<&str>, body: &str) -> String {
+    let refresh = refresh
+        .map(|url| format!("<meta http-equiv=\"refresh\" content=\"{}; url={}\">", REFRESH_SECS, escape_html(url)))
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{}</title>{}</head>\
+         <body style=\"font-family: sans-serif; max-width: 40em; margin: 1em auto; padding: 0 1em; line-height: 1.5\">\
+         <main>{}</main></body></html>",
+        escape_html(title),
+        refresh,
+        body
+    )
+}
+
+fn render_join_page(room: &Room, notice: Option<&str>) -> String {
+    let mut body = String::new();
+    let _ = write!(body, "<h1>Join {}</h1>", escape_html(&room.name));
+    if let Some(notice) = notice {
+        let _ = write!(body, "<p role=\"alert\">{}</p>", escape_html(notice));
+    }
+    let _ = write!(
+        body,
+        "<form method=\"post\" action=\"/join/{}/basic\">\
+         <p><label for=\"name\">Your name</label><br>\
+         <input id=\"name\" name=\"name\" required autocomplete=\"name\"></p>\
+         <p><button type=\"submit\">Join room</button></p></form>",
+        escape_html(&room.id)
+    );
+    page(&format!("Join {} - Scrum Poker", room.name), None, &body)
+}
+
+fn render_room_page(room: &Room, participant_id: &str, token: &str, auto_refresh: bool) -> String {
+    let me = room.participants.iter().find(|p| p.id == participant_id);
+    let room_id = escape_html(&room.id);
+    let session_field = escape_html(token);
+    let auto_field = if auto_refresh { "" } else { "<input type=\"hidden\" name=\"auto\" value=\"0\">" };
+    let mut body = String::new();
+
+    let _ = write!(body, "<h1>{}</h1>", escape_html(&room.name));
+    match &room.current_ticket {
+        Some(ticket) => {
+            let _ = write!(
+                body,
+                "<h2>Estimating {}</h2><p>{}</p>",
+                escape_html(&ticket.key),
+                escape_html(&ticket.summary)
+            );
+        }
+        None => body.push_str("<h2>No ticket selected</h2>"),
+    }
+
+    let summary = room.get_vote_summary();
+    // The room is already this participant's view, so only their own vote is left
+    let voters_hidden = room.voters_hidden();
+    body.push_str("<section aria-labelledby=\"votes\"><h2 id=\"votes\">Votes</h2>");
+    if room.votes_revealed {
+        let _ = write!(
+            body,
+            "<p>Votes are revealed. Average: {}. Suggested estimate: {}.</p>",
+            summary.average.map(|a| format!("{:.1}", a)).unwrap_or_else(|| "none".into()),
+            summary.suggested.as_deref().map(escape_html).unwrap_or_else(|| "none".into())
+        );
+    } else if voters_hidden {
+        body.push_str("<p>Votes are hidden, and so is who has voted until everyone has.</p>");
+    } else {
+        let _ = write!(
+            body,
+            "<p>Votes are hidden. {} of {} have voted.</p>",
+            summary.voted_count, summary.total_voters
+        );
+    }
+    body.push_str("<table><caption>Participants</caption><tr><th scope=\"col\">Name</th><th scope=\"col\">Vote</th></tr>");
+    for participant in &room.participants {
+        let vote = match (&participant.vote, room.votes_revealed) {
+            _ if voters_hidden && participant.id != participant_id => "hidden".to_string(),
+            (Some(vote), true) => escape_html(vote),
+            (Some(_), false) => "voted".to_string(),
+            (None, _) => "not voted".to_string(),
+        };
+        let you = if participant.id == participant_id { " (you)" } else { "" };
+        let _ = write!(
+            body,
+            "<tr><td>{}{}</td><td>{}</td></tr>",
+            escape_html(&participant.name),
+            you,
+            vote
+        );
+    }
+    body.push_str("</table></section>");
+
+    let _ = write!(
+        body,
+        "<form method=\"post\" action=\"/join/{}/basic/vote\">\
+         <input type=\"hidden\" name=\"session\" value=\"{}\">{}",
+        room_id, session_field, auto_field
+    );
+    let my_vote = me.and_then(|p| p.vote.as_deref());
+    let _ = write!(
+        body,
+        "<fieldset><legend>Your estimate (currently {})</legend>",
+        my_vote.map(escape_html).unwrap_or_else(|| "none".into())
+    );
+    write_card_buttons(&mut body, "vote", &room.deck, my_vote);
+    if my_vote.is_some() {
+        body.push_str(" <button type=\"submit\" name=\"clear\" value=\"vote\">Clear my estimate</button>");
+    }
+    body.push_str("</fieldset>");
+
+    if let Some(risk_deck) = &room.risk_deck {
+        let my_risk = me.and_then(|p| p.risk_vote.as_deref());
+        let _ = write!(
+            body,
+            "<fieldset><legend>Risk, from 1 (low) to 5 (high) (currently {})</legend>",
+            my_risk.map(escape_html).unwrap_or_else(|| "none".into())
+        );
+        write_card_buttons(&mut body, "risk_vote", risk_deck, my_risk);
+        if my_risk.is_some() {
+            body.push_str(" <button type=\"submit\" name=\"clear\" value=\"risk\">Clear my risk vote</button>");
+        }
+        body.push_str("</fieldset>");
+    }
+    body.push_str("</form>");
+
+    let page_link = page_url(&room.id, token, auto_refresh);
+    let toggle_link = page_url(&room.id, token, !auto_refresh);
+    let _ = write!(
+        body,
+        "<p><a href=\"{}\">Refresh now</a> &middot; <a href=\"{}\">{}</a></p>\
+         <form method=\"post\" action=\"/join/{}/basic/leave\">\
+         <input type=\"hidden\" name=\"session\" value=\"{}\">\
+         <button type=\"submit\">Leave room</button></form>",
+        escape_html(&page_link),
+        escape_html(&toggle_link),
+        if auto_refresh { "Stop automatic refresh" } else { "Refresh automatically" },
+        room_id,
+        session_field
+    );
+
+    let refresh = auto_refresh.then_some(page_link);
+    page(&format!("{} - Scrum Poker", room.name), refresh.as_deref(), &body)
+}
+
+fn write_card_buttons(body: &mut String, field: &str, deck: &[String], selected: Option<&str>) {
+    for card in deck {
+        let pressed = if selected == Some(card.as_str()) { "true" } else { "false" };
+        let _ = write!(
+            body,
+            "<button type=\"submit\" name=\"{}\" value=\"{}\" aria-pressed=\"{}\">{}</button> ",
+            field,
+            escape_html(card),
+            pressed,
+            escape_html(card)
+        );
+    }
+}
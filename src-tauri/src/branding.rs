@@ -0,0 +1,60 @@
+use crate::credentials::get_data_dir;
+use std::fs;
+use std::path::PathBuf;
+
+const BRANDING_DIR: &str = "branding";
+/// Maximum logo size in bytes
+const MAX_LOGO_SIZE: usize = 1024 * 1024;
+
+/// Directory that room logos are stored in and served from (at `/branding`)
+pub fn branding_dir() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(BRANDING_DIR))
+}
+
+/// Check that a color is a hex color like "#1a2b3c" or "#abc"
+pub fn validate_accent_color(color: &str) -> Result<(), String> {
+    let hex = color
+        .strip_prefix('#')
+        .ok_or_else(|| "Accent color must start with '#'".to_string())?;
+
+    if (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid accent color: {}", color))
+    }
+}
+
+/// Store a room's logo and return the URL path it is served at.
+/// SVG is deliberately not accepted since it can carry scripts.
+pub fn save_logo(room_id: &str, content_type: &str, data: &[u8]) -> Result<String, String> {
+    let extension = match content_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => return Err(format!("Unsupported logo type: {}", content_type)),
+    };
+    if data.len() > MAX_LOGO_SIZE {
+        return Err(format!("Logo must be smaller than {} KB", MAX_LOGO_SIZE / 1024));
+    }
+
+    let dir = branding_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create branding dir: {}", e))?;
+
+    // Drop any previous logo, which may have had a different extension
+    delete_logo(room_id);
+
+    let file_name = format!("{}.{}", room_id, extension);
+    fs::write(dir.join(&file_name), data).map_err(|e| format!("Failed to save logo: {}", e))?;
+
+    // Version the URL so clients don't keep showing a cached older logo
+    Ok(format!("/branding/{}?v={}", file_name, crate::room::now_secs()))
+}
+
+/// Remove a room's logo, if it has one
+pub fn delete_logo(room_id: &str) {
+    let Ok(dir) = branding_dir() else { return };
+    for extension in ["png", "jpg", "gif", "webp"] {
+        let _ = fs::remove_file(dir.join(format!("{}.{}", room_id, extension)));
+    }
+}
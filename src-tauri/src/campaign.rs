@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Most issues loaded into one re-estimation campaign
+pub const MAX_CAMPAIGN_ISSUES: usize = 100;
+
+/// How far a re-estimated ticket moved from its old estimate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Drift {
+    pub ticket_key: String,
+    pub previous: f64,
+    pub estimate: f64,
+    /// `estimate - previous`; positive when the ticket got bigger
+    pub drift: f64,
+    /// Whether the new estimate has been written back to Jira
+    #[serde(default)]
+    pub written: bool,
+}
+
+/// Re-estimation of a backlog after its old estimates stopped holding, e.g.
+/// after a major architectural change
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Campaign {
+    /// Story points field the old estimates came from, and new ones go back to
+    pub field: String,
+    /// Estimate each ticket had when the campaign started, keyed by ticket key.
+    /// Only the host sees these, so they can't anchor the votes.
+    #[serde(default)]
+    pub previous: HashMap<String, f64>,
+    /// Tickets re-estimated so far, in order
+    #[serde(default)]
+    pub results: Vec<Drift>,
+}
+
+impl Campaign {
+    pub fn new(field: String, previous: HashMap<String, f64>) -> Self {
+        Self { field, previous, results: Vec::new() }
+    }
+
+    /// Record a ticket's new estimate, to one decimal; estimating it again
+    /// replaces the result. Tickets that weren't part of the campaign are ignored.
+    pub fn record(&mut self, ticket_key: &str, estimate: f64) {
+        let Some(&previous) = self.previous.get(ticket_key) else {
+            return;
+        };
+        let estimate = (estimate * 10.0).round() / 10.0;
+        let drift = Drift {
+            ticket_key: ticket_key.to_string(),
+            previous,
+            estimate,
+            drift: estimate - previous,
+            written: false,
+        };
+        match self.results.iter_mut().find(|d| d.ticket_key == ticket_key) {
+            Some(existing) => *existing = drift,
+            None => self.results.push(drift),
+        }
+    }
+
+    /// Results whose estimate changed and hasn't been written back yet
+    pub fn unwritten_changes(&self) -> Vec<Drift> {
+        self.results
+            .iter()
+            .filter(|d| !d.written && d.drift != 0.0)
+            .cloned()
+            .collect()
+    }
+
+    pub fn mark_written(&mut self, ticket_key: &str) {
+        if let Some(drift) = self.results.iter_mut().find(|d| d.ticket_key == ticket_key) {
+            drift.written = true;
+        }
+    }
+}
+
+/// JQL for a project's issues that were estimated but haven't been started
+pub fn backlog_jql(project_key: &str, field: &str) -> String {
+    format!(
+        "project = \"{}\" AND statusCategory = \"To Do\" AND \"{}\" is not EMPTY ORDER BY Rank ASC",
+        project_key.replace('"', ""),
+        field.replace('"', "")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_estimates_are_written_back() {
+        let previous = HashMap::from([("POKER-1".to_string(), 3.0), ("POKER-2".to_string(), 5.0)]);
+        let mut campaign = Campaign::new("Story Points".into(), previous);
+
+        campaign.record("POKER-1", 5.0);
+        campaign.record("POKER-2", 5.0);
+        campaign.record("POKER-9", 8.0);
+        campaign.record("POKER-1", 8.0);
+
+        assert_eq!(campaign.results.len(), 2);
+        let changes = campaign.unwritten_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].ticket_key.as_str(), changes[0].drift), ("POKER-1", 5.0));
+
+        campaign.mark_written("POKER-1");
+        assert!(campaign.unwritten_changes().is_empty());
+    }
+}
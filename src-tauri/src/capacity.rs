@@ -0,0 +1,63 @@
+use crate::accuracy;
+use crate::room::Room;
+use serde::{Deserialize, Serialize};
+
+/// How much of the sprint the tickets estimated in a session would take up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SprintFill {
+    /// Points the team can take on this sprint, as entered by the host
+    pub capacity: f64,
+    /// Sum of the final estimates of the tickets estimated so far
+    pub committed: f64,
+    pub tickets: usize,
+    /// Committed points over capacity, e.g. 0.75 when three quarters full
+    pub fraction: f64,
+    pub overcommitted: bool,
+}
+
+pub fn validate_capacity(capacity: f64) -> Result<(), String> {
+    if !capacity.is_finite() || capacity <= 0.0 {
+        return Err("Sprint capacity must be a positive number of points".into());
+    }
+    Ok(())
+}
+
+/// Fill of a sprint with `capacity` points, from the final estimate of each
+/// ticket revealed in the room (re-estimated tickets count once)
+pub fn sprint_fill(capacity: f64, room: &Room) -> SprintFill {
+    let estimates = accuracy::final_estimates(std::iter::once(room));
+    let committed: f64 = estimates.values().sum();
+    SprintFill {
+        capacity,
+        committed,
+        tickets: estimates.len(),
+        fraction: committed / capacity,
+        overcommitted: committed > capacity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room::JiraTicket;
+    use crate::test_support::{room_with, FakeEnvironment};
+
+    #[test]
+    fn re_estimated_tickets_count_once() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice"]);
+        for (key, vote) in [("POKER-1", "5"), ("POKER-2", "8"), ("POKER-1", "3")] {
+            room.set_current_ticket(Some(JiraTicket { key: key.into(), ..Default::default() }));
+            room.set_vote("alice", Some(vote.into()));
+            room.votes_revealed = true;
+            room.complete_round();
+            room.reset_votes();
+            env.advance(60);
+        }
+
+        let fill = sprint_fill(10.0, &room);
+        assert_eq!((fill.committed, fill.tickets), (11.0, 2));
+        assert!(fill.overcommitted);
+        assert!(!sprint_fill(20.0, &room).overcommitted);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::credentials::LlmCredentials;
+use crate::recap::describe_event;
+use crate::room::{HistoryEvent, Room};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+const SYSTEM_PROMPT: &str = "You write short summaries of scrum poker estimation sessions for a sprint \
+planning document. Use plain Markdown: a one-paragraph overview, then a bullet per ticket with its \
+estimate, and call out rounds where the team disagreed and anything left unresolved. Only use the \
+facts given; don't invent discussion that isn't in the log.";
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    temperature: f32,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatReply,
+}
+
+#[derive(Deserialize)]
+struct ChatReply {
+    content: String,
+}
+
+/// The session's structured history written out as plain text for the model
+pub fn session_facts(room: &Room) -> String {
+    let mut facts = format!("Room: {}\nStarted: {}\n", room.name, room.time_zone.format(room.created_at));
+    if let Some(summary) = &room.session_summary {
+        let _ = writeln!(
+            facts,
+            "Duration: {} min, {} rounds, {:.1} points in total",
+            summary.duration_secs / 60,
+            summary.rounds,
+            summary.total_points
+        );
+        let _ = writeln!(facts, "Attendance: {}", summary.attendance.join(", "));
+    } else {
+        let names: Vec<&str> = room.participants.iter().map(|p| p.name.as_str()).collect();
+        let _ = writeln!(facts, "Participants: {}", names.join(", "));
+    }
+
+    facts.push_str("\nSession log:\n");
+    for entry in &room.history {
+        let _ = writeln!(
+            facts,
+            "- {} {}",
+            room.time_zone.format_time(entry.timestamp),
+            describe_event(&entry.event)
+        );
+        if let HistoryEvent::VotesRevealed { votes, .. } = &entry.event {
+            if !votes.is_empty() {
+                let cast: Vec<String> = votes.iter().map(|v| format!("{} {}", v.name, v.vote)).collect();
+                let _ = writeln!(facts, "  Votes: {}", cast.join(", "));
+            }
+        }
+    }
+
+    if !room.ticket_queue.is_empty() {
+        facts.push_str("\nNot estimated yet:\n");
+        for ticket in &room.ticket_queue {
+            let _ = writeln!(facts, "- {}: {}", ticket.key, ticket.summary);
+        }
+    }
+    facts
+}
+
+/// Ask the configured model to draft a summary of the session
+pub async fn draft_session_summary(
+    client: &reqwest::Client,
+    credentials: &LlmCredentials,
+    room: &Room,
+) -> Result<String, String> {
+    let facts = session_facts(room);
+    let request = ChatRequest {
+        model: &credentials.model,
+        messages: [
+            ChatMessage { role: "system", content: SYSTEM_PROMPT },
+            ChatMessage { role: "user", content: &facts },
+        ],
+        temperature: 0.3,
+    };
+
+    let url = format!("{}/chat/completions", credentials.endpoint.trim_end_matches('/'));
+    let response = client
+        .post(&url)
+        .bearer_auth(&credentials.api_key)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the language model: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Language model error {}: {}", status, body));
+    }
+
+    let reply: ChatResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse the language model's reply: {}", e))?;
+    reply
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content.trim().to_string())
+        .filter(|content| !content.is_empty())
+        .ok_or_else(|| "The language model returned an empty summary".to_string())
+}
@@ -10,15 +10,82 @@ use std::fs;
 use std::path::PathBuf;
 
 const CREDENTIALS_FILE: &str = "jira_credentials.enc";
+const LLM_CREDENTIALS_FILE: &str = "llm_credentials.enc";
+const PROXY_CREDENTIALS_FILE: &str = "proxy_credentials.enc";
+const GITHUB_TOKEN_FILE: &str = "github_token.enc";
+const SMS_AUTH_TOKEN_FILE: &str = "sms_auth_token.enc";
+const RELAY_TOKEN_FILE: &str = "relay_token.enc";
 const SALT_FILE: &str = "jira_salt.key";
 
-/// Encrypted credentials stored on disk
+/// Encrypted data stored on disk (credentials, and optionally rooms)
 #[derive(Debug, Serialize, Deserialize)]
-struct EncryptedCredentials {
+pub struct EncryptedData {
     nonce: String,       // Base64 encoded
     ciphertext: String,  // Base64 encoded
 }
 
+impl EncryptedData {
+    /// Recognize encrypted data in an already-parsed JSON value
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        if value.get("nonce").is_some() && value.get("ciphertext").is_some() {
+            serde_json::from_value(value.clone()).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// AES-256-GCM key derived from the master password
+#[derive(Clone)]
+pub struct MasterKey([u8; 32]);
+
+impl MasterKey {
+    /// Derive the key from the master password, using the stored salt
+    pub fn derive(password: &str) -> Result<Self, String> {
+        let salt = get_or_create_salt()?;
+        derive_key(password, &salt).map(Self)
+    }
+
+    pub fn encrypt(&self, plain_text: &[u8]) -> Result<EncryptedData, String> {
+        // Generate random nonce
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+        
+        let cipher = Aes256Gcm::new_from_slice(&self.0)
+            .map_err(|e| format!("Failed to create cipher: {}", e))?;
+        
+        let ciphertext = cipher
+            .encrypt(nonce, plain_text)
+            .map_err(|e| format!("Encryption failed: {}", e))?;
+        
+        Ok(EncryptedData {
+            nonce: general_purpose::STANDARD.encode(nonce_bytes),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt data; fails if the password was wrong or the data was tampered with
+    pub fn decrypt(&self, encrypted: &EncryptedData) -> Result<Vec<u8>, String> {
+        // Decode base64
+        let nonce_bytes = general_purpose::STANDARD
+            .decode(&encrypted.nonce)
+            .map_err(|e| format!("Failed to decode nonce: {}", e))?;
+        
+        let ciphertext = general_purpose::STANDARD
+            .decode(&encrypted.ciphertext)
+            .map_err(|e| format!("Failed to decode ciphertext: {}", e))?;
+        
+        let cipher = Aes256Gcm::new_from_slice(&self.0)
+            .map_err(|e| format!("Failed to create cipher: {}", e))?;
+        
+        let nonce = Nonce::from_slice(&nonce_bytes);
+        cipher
+            .decrypt(nonce, ciphertext.as_ref())
+            .map_err(|_| "Invalid password or corrupted data".to_string())
+    }
+}
+
 /// Plain credentials before encryption
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JiraCredentials {
@@ -27,11 +94,9 @@ pub struct JiraCredentials {
     pub api_token: String,
 }
 
-/// Get the app data directory
-fn get_data_dir() -> Result<PathBuf, String> {
-    directories::ProjectDirs::from("com", "scrumpoker", "ScrumPoker")
-        .map(|dirs| dirs.data_dir().to_path_buf())
-        .ok_or_else(|| "Could not determine data directory".to_string())
+/// Get the data directory of the active profile
+pub fn get_data_dir() -> Result<PathBuf, String> {
+    crate::profiles::profile_dir(&crate::profiles::active_profile())
 }
 
 /// Derive an encryption key from password using Argon2
@@ -74,86 +139,161 @@ pub fn has_stored_credentials() -> bool {
 
 /// Save encrypted credentials
 pub fn save_credentials(password: &str, credentials: &JiraCredentials) -> Result<(), String> {
+    save_encrypted(CREDENTIALS_FILE, password, credentials)
+}
+
+/// Load and decrypt credentials
+pub fn load_credentials(password: &str) -> Result<JiraCredentials, String> {
+    load_encrypted(CREDENTIALS_FILE, password)
+}
+
+/// Encrypt a value with the password and write it to the data directory
+fn save_encrypted<T: Serialize>(file_name: &str, password: &str, value: &T) -> Result<(), String> {
     let data_dir = get_data_dir()?;
     fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
     
-    let salt = get_or_create_salt()?;
-    let key = derive_key(password, &salt)?;
+    let key = MasterKey::derive(password)?;
     
     // Serialize credentials to JSON
-    let plain_text = serde_json::to_string(credentials)
+    let plain_text = serde_json::to_string(value)
         .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
     
-    // Generate random nonce
-    let mut nonce_bytes = [0u8; 12];
-    OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
-    
-    // Encrypt
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to create cipher: {}", e))?;
-    
-    let ciphertext = cipher
-        .encrypt(nonce, plain_text.as_bytes())
-        .map_err(|e| format!("Encryption failed: {}", e))?;
+    let encrypted = key.encrypt(plain_text.as_bytes())?;
     
     // Save to file
-    let encrypted = EncryptedCredentials {
-        nonce: general_purpose::STANDARD.encode(nonce_bytes),
-        ciphertext: general_purpose::STANDARD.encode(ciphertext),
-    };
-    
     let json = serde_json::to_string_pretty(&encrypted)
         .map_err(|e| format!("Failed to serialize encrypted data: {}", e))?;
     
-    fs::write(data_dir.join(CREDENTIALS_FILE), json)
+    fs::write(data_dir.join(file_name), json)
         .map_err(|e| format!("Failed to write credentials file: {}", e))?;
     
     Ok(())
 }
 
-/// Load and decrypt credentials
-pub fn load_credentials(password: &str) -> Result<JiraCredentials, String> {
+/// Read a value written by `save_encrypted` and decrypt it with the password
+fn load_encrypted<T: for<'de> Deserialize<'de>>(file_name: &str, password: &str) -> Result<T, String> {
     let data_dir = get_data_dir()?;
-    let cred_path = data_dir.join(CREDENTIALS_FILE);
+    let cred_path = data_dir.join(file_name);
     
     if !cred_path.exists() {
         return Err("No stored credentials found".to_string());
     }
     
-    let salt = get_or_create_salt()?;
-    let key = derive_key(password, &salt)?;
+    let key = MasterKey::derive(password)?;
     
     // Read encrypted file
     let json = fs::read_to_string(&cred_path)
         .map_err(|e| format!("Failed to read credentials file: {}", e))?;
     
-    let encrypted: EncryptedCredentials = serde_json::from_str(&json)
+    let encrypted: EncryptedData = serde_json::from_str(&json)
         .map_err(|e| format!("Failed to parse credentials file: {}", e))?;
     
-    // Decode base64
-    let nonce_bytes = general_purpose::STANDARD
-        .decode(&encrypted.nonce)
-        .map_err(|e| format!("Failed to decode nonce: {}", e))?;
-    
-    let ciphertext = general_purpose::STANDARD
-        .decode(&encrypted.ciphertext)
-        .map_err(|e| format!("Failed to decode ciphertext: {}", e))?;
-    
-    // Decrypt
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to create cipher: {}", e))?;
-    
-    let nonce = Nonce::from_slice(&nonce_bytes);
-    let plain_text = cipher
-        .decrypt(nonce, ciphertext.as_ref())
+    let plain_text = key
+        .decrypt(&encrypted)
         .map_err(|_| "Invalid password or corrupted credentials".to_string())?;
     
     // Parse JSON
-    let credentials: JiraCredentials = serde_json::from_slice(&plain_text)
-        .map_err(|e| format!("Failed to parse decrypted credentials: {}", e))?;
-    
-    Ok(credentials)
+    serde_json::from_slice(&plain_text)
+        .map_err(|e| format!("Failed to parse decrypted credentials: {}", e))
+}
+
+/// Language model endpoint used to draft session summaries. Any service with an
+/// OpenAI-compatible chat completions API works.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmCredentials {
+    /// Base URL such as `https://api.openai.com/v1`
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: String,
+}
+
+pub fn has_stored_llm_credentials() -> bool {
+    get_data_dir().is_ok_and(|dir| dir.join(LLM_CREDENTIALS_FILE).exists())
+}
+
+/// Save the language model settings, encrypted with the same password as the Jira credentials
+pub fn save_llm_credentials(password: &str, credentials: &LlmCredentials) -> Result<(), String> {
+    save_encrypted(LLM_CREDENTIALS_FILE, password, credentials)
+}
+
+pub fn load_llm_credentials(password: &str) -> Result<LlmCredentials, String> {
+    load_encrypted(LLM_CREDENTIALS_FILE, password)
+}
+
+pub fn delete_llm_credentials() -> Result<(), String> {
+    let path = get_data_dir()?.join(LLM_CREDENTIALS_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete credentials: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Save the proxy password, encrypted with the same password as the Jira credentials
+pub fn save_proxy_password(password: &str, proxy_password: &str) -> Result<(), String> {
+    save_encrypted(PROXY_CREDENTIALS_FILE, password, &proxy_password)
+}
+
+pub fn load_proxy_password(password: &str) -> Result<String, String> {
+    load_encrypted(PROXY_CREDENTIALS_FILE, password)
+}
+
+pub fn delete_proxy_password() -> Result<(), String> {
+    let path = get_data_dir()?.join(PROXY_CREDENTIALS_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete credentials: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Save the GitHub token, encrypted with the same password as the Jira credentials
+pub fn save_github_token(password: &str, token: &str) -> Result<(), String> {
+    save_encrypted(GITHUB_TOKEN_FILE, password, &token)
+}
+
+pub fn load_github_token(password: &str) -> Result<String, String> {
+    load_encrypted(GITHUB_TOKEN_FILE, password)
+}
+
+pub fn delete_github_token() -> Result<(), String> {
+    let path = get_data_dir()?.join(GITHUB_TOKEN_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete credentials: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Save the SMS provider's auth token, encrypted with the same password as the Jira credentials
+pub fn save_sms_auth_token(password: &str, token: &str) -> Result<(), String> {
+    save_encrypted(SMS_AUTH_TOKEN_FILE, password, &token)
+}
+
+pub fn load_sms_auth_token(password: &str) -> Result<String, String> {
+    load_encrypted(SMS_AUTH_TOKEN_FILE, password)
+}
+
+pub fn delete_sms_auth_token() -> Result<(), String> {
+    let path = get_data_dir()?.join(SMS_AUTH_TOKEN_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete credentials: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Save the relay host token, encrypted with the same password as the Jira credentials
+pub fn save_relay_token(password: &str, token: &str) -> Result<(), String> {
+    save_encrypted(RELAY_TOKEN_FILE, password, &token)
+}
+
+pub fn load_relay_token(password: &str) -> Result<String, String> {
+    load_encrypted(RELAY_TOKEN_FILE, password)
+}
+
+pub fn delete_relay_token() -> Result<(), String> {
+    let path = get_data_dir()?.join(RELAY_TOKEN_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete credentials: {}", e))?;
+    }
+    Ok(())
 }
 
 /// Delete stored credentials
@@ -0,0 +1,72 @@
+use crate::moderation::{validate_text, ValidationError};
+use crate::room::{ABSTAIN, STORY_POINTS};
+use serde::{Deserialize, Serialize};
+
+/// Longest card value; longer ones don't fit on a card
+const MAX_CARD_LENGTH: usize = 8;
+/// Most cards a deck may have
+const MAX_CARDS: usize = 24;
+
+const T_SHIRT_SIZES: &[&str] = &["?", "☕", "XS", "S", "M", "L", "XL", "XXL"];
+const POWERS_OF_TWO: &[&str] = &["?", "☕", "0", "1", "2", "4", "8", "16", "32", "64"];
+
+/// The scale a room votes with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeckDefinition {
+    /// The usual story points, `STORY_POINTS`
+    Fibonacci,
+    TShirt,
+    PowersOfTwo,
+    /// The team's own cards, in order
+    Custom { cards: Vec<String> },
+}
+
+impl DeckDefinition {
+    /// The deck's cards, checked and normalized. The presets offer the abstain
+    /// card; a custom deck has exactly the cards given.
+    pub fn cards(&self) -> Result<Vec<String>, ValidationError> {
+        let preset = match self {
+            DeckDefinition::Fibonacci => STORY_POINTS,
+            DeckDefinition::TShirt => T_SHIRT_SIZES,
+            DeckDefinition::PowersOfTwo => POWERS_OF_TWO,
+            DeckDefinition::Custom { cards } => return validate_cards(cards),
+        };
+        Ok(preset.iter().chain([&ABSTAIN]).map(|card| card.to_string()).collect())
+    }
+}
+
+/// Normalize custom cards and check there's a sensible number of distinct ones
+fn validate_cards(cards: &[String]) -> Result<Vec<String>, ValidationError> {
+    let mut deck: Vec<String> = Vec::with_capacity(cards.len());
+    for card in cards {
+        let card = validate_text("deck", "A card", card, MAX_CARD_LENGTH)?;
+        if deck.contains(&card) {
+            return Err(ValidationError::new("deck", format!("The card {} is in the deck twice", card)));
+        }
+        deck.push(card);
+    }
+    if deck.len() < 2 {
+        return Err(ValidationError::new("deck", "A deck needs at least two cards"));
+    }
+    if deck.len() > MAX_CARDS {
+        return Err(ValidationError::new("deck", format!("A deck can have at most {} cards", MAX_CARDS)));
+    }
+    Ok(deck)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_decks_are_trimmed_and_checked() {
+        let custom = |cards: &[&str]| DeckDefinition::Custom { cards: cards.iter().map(|c| c.to_string()).collect() };
+
+        assert_eq!(custom(&[" 1 ", "2", "3"]).cards().unwrap(), ["1", "2", "3"]);
+        assert!(custom(&["1", "1"]).cards().is_err());
+        assert!(custom(&["1"]).cards().is_err());
+        assert!(custom(&["1", "much too long"]).cards().is_err());
+        assert_eq!(DeckDefinition::TShirt.cards().unwrap().last().map(String::as_str), Some(ABSTAIN));
+    }
+}
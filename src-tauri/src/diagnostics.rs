@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of log entries kept for the admin page
+const MAX_ENTRIES: usize = 100;
+
+/// A warning or error that was logged
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Ring buffer of the most recent warnings and errors
+#[derive(Default)]
+pub struct ErrorLog {
+    entries: Mutex<VecDeque<LogEntry>>,
+}
+
+impl ErrorLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recent entries, newest first
+    pub fn recent(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+}
+
+/// Tracing layer feeding warnings and errors into an `ErrorLog`
+pub struct ErrorLogLayer {
+    log: std::sync::Arc<ErrorLog>,
+}
+
+impl ErrorLogLayer {
+    pub fn new(log: std::sync::Arc<ErrorLog>) -> Self {
+        Self { log }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ErrorLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.log.push(LogEntry {
+            timestamp: crate::room::now_secs(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// Something to do to a ticket in Jira once its estimate comes in at or above
+/// a threshold, e.g. label anything of 13 points or more "needs-breakdown".
+/// Set per room, or for every Jira room in the settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EstimateRule {
+    /// Estimates at or above this trigger the rule
+    pub threshold: f64,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Add a label to the issue
+    Label { label: String },
+    /// Flag the issue, as "Add flag" on a Jira board does
+    Flag,
+}
+
+/// A revealed round that triggered a rule, waiting to be carried out in Jira
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleHit {
+    pub room_id: String,
+    pub ticket_key: String,
+    pub estimate: f64,
+    pub action: RuleAction,
+}
+
+impl EstimateRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.threshold.is_finite() || self.threshold <= 0.0 {
+            return Err("The threshold must be a positive number".into());
+        }
+        if let RuleAction::Label { label } = &self.action {
+            if label.is_empty() || label.chars().any(char::is_whitespace) {
+                return Err("Jira labels can't be empty or contain spaces".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// The hit for a round estimated at `estimate`, if that's high enough
+    pub fn check(&self, room_id: &str, ticket_key: &str, estimate: f64) -> Option<RuleHit> {
+        (estimate >= self.threshold).then(|| RuleHit {
+            room_id: room_id.to_string(),
+            ticket_key: ticket_key.to_string(),
+            estimate,
+            action: self.action.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_fire_at_or_above_the_threshold() {
+        let rule = EstimateRule {
+            threshold: 13.0,
+            action: RuleAction::Label { label: "needs-breakdown".into() },
+        };
+        assert!(rule.validate().is_ok());
+        assert_eq!(rule.check("room", "POKER-1", 8.0), None);
+        let hit = rule.check("room", "POKER-1", 13.0).unwrap();
+        assert_eq!(hit.action, RuleAction::Label { label: "needs-breakdown".into() });
+
+        let spaced = EstimateRule {
+            threshold: 13.0,
+            action: RuleAction::Label { label: "needs breakdown".into() },
+        };
+        assert!(spaced.validate().is_err());
+    }
+}
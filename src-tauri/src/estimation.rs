@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+/// Computes a room's suggested estimate from the revealed votes
+pub trait Estimator {
+    /// `votes` holds the numeric votes (non-numeric cards like "?" are already
+    /// dropped); `deck` is the room's card values. Returns the suggested card,
+    /// or None if there's no sensible suggestion.
+    fn suggest(&self, votes: &[f64], deck: &[String]) -> Option<String>;
+}
+
+/// Estimation formula used by a room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimationMethod {
+    /// Average of all votes, rounded to the nearest card
+    #[default]
+    MeanSnap,
+    /// Middle vote, rounded to the nearest card
+    Median,
+    /// Average after dropping the highest and lowest votes
+    TrimmedMean,
+    /// Only suggests once votes have converged onto neighbouring cards;
+    /// until then the team should discuss and vote again
+    Delphi,
+}
+
+impl EstimationMethod {
+    pub fn estimator(self) -> &'static dyn Estimator {
+        match self {
+            EstimationMethod::MeanSnap => &MeanSnap,
+            EstimationMethod::Median => &Median,
+            EstimationMethod::TrimmedMean => &TrimmedMean,
+            EstimationMethod::Delphi => &Delphi,
+        }
+    }
+}
+
+pub struct MeanSnap;
+
+impl Estimator for MeanSnap {
+    fn suggest(&self, votes: &[f64], deck: &[String]) -> Option<String> {
+        snap_to_deck(mean(votes)?, deck)
+    }
+}
+
+pub struct Median;
+
+impl Estimator for Median {
+    fn suggest(&self, votes: &[f64], deck: &[String]) -> Option<String> {
+        snap_to_deck(median(votes)?, deck)
+    }
+}
+
+pub struct TrimmedMean;
+
+impl Estimator for TrimmedMean {
+    fn suggest(&self, votes: &[f64], deck: &[String]) -> Option<String> {
+        // Too few votes to trim; fall back to the plain mean
+        if votes.len() < 3 {
+            return snap_to_deck(mean(votes)?, deck);
+        }
+        let sorted = sorted(votes);
+        snap_to_deck(mean(&sorted[1..sorted.len() - 1])?, deck)
+    }
+}
+
+pub struct Delphi;
+
+impl Estimator for Delphi {
+    fn suggest(&self, votes: &[f64], deck: &[String]) -> Option<String> {
+        let cards = numeric_cards(deck);
+        let position = |vote: f64| cards.iter().position(|(value, _)| *value == vote);
+
+        // Votes have converged when they all sit on the same or adjacent cards
+        let positions: Vec<usize> = votes.iter().filter_map(|v| position(*v)).collect();
+        let (min, max) = (positions.iter().min()?, positions.iter().max()?);
+        if max - min > 1 {
+            return None;
+        }
+        snap_to_deck(median(votes)?, deck)
+    }
+}
+
+fn mean(votes: &[f64]) -> Option<f64> {
+    if votes.is_empty() {
+        None
+    } else {
+        Some(votes.iter().sum::<f64>() / votes.len() as f64)
+    }
+}
+
+fn median(votes: &[f64]) -> Option<f64> {
+    if votes.is_empty() {
+        return None;
+    }
+    let sorted = sorted(votes);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+fn sorted(votes: &[f64]) -> Vec<f64> {
+    let mut sorted = votes.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// Numeric cards in the deck with their values, in ascending order
+fn numeric_cards(deck: &[String]) -> Vec<(f64, &str)> {
+    let mut cards: Vec<(f64, &str)> = deck
+        .iter()
+        .filter_map(|card| card.parse::<f64>().ok().map(|value| (value, card.as_str())))
+        .collect();
+    cards.sort_by(|a, b| a.0.total_cmp(&b.0));
+    cards
+}
+
+/// Nearest numeric card to a value, preferring the higher card on a tie
+fn snap_to_deck(value: f64, deck: &[String]) -> Option<String> {
+    numeric_cards(deck)
+        .into_iter()
+        .min_by(|a, b| {
+            (a.0 - value)
+                .abs()
+                .total_cmp(&(b.0 - value).abs())
+                .then(b.0.total_cmp(&a.0))
+        })
+        .map(|(_, card)| card.to_string())
+}
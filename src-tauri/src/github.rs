@@ -0,0 +1,159 @@
+use crate::analysis;
+use crate::room::{DiffStats, JiraTicket};
+use serde::Deserialize;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Most open pull requests loaded from a repository at once
+const MAX_PULL_REQUESTS: usize = 20;
+
+/// A pull request to load: "owner/repo#123" or its github.com URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequestRef {
+    pub repo: String,
+    pub number: u64,
+}
+
+/// What the host asked to load: one pull request, or a repository's open ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PullRequestSource {
+    One(PullRequestRef),
+    OpenIn(String),
+}
+
+impl PullRequestSource {
+    /// Parse "owner/repo#123", "https://github.com/owner/repo/pull/123" or "owner/repo"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_end_matches('/');
+        let path = text
+            .strip_prefix("https://github.com/")
+            .or_else(|| text.strip_prefix("github.com/"))
+            .unwrap_or(text);
+        let invalid = || format!("Expected owner/repo or owner/repo#123, got \"{}\"", text);
+
+        let (repo, number) = match path.split_once("/pull/") {
+            Some((repo, number)) => (repo, Some(number)),
+            None => match path.split_once('#') {
+                Some((repo, number)) => (repo, Some(number)),
+                None => (path, None),
+            },
+        };
+        let mut parts = repo.split('/');
+        let valid_repo = matches!((parts.next(), parts.next(), parts.next()), (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty());
+        if !valid_repo {
+            return Err(invalid());
+        }
+        match number {
+            Some(number) => {
+                let number = number.parse().map_err(|_| invalid())?;
+                Ok(PullRequestSource::One(PullRequestRef { repo: repo.to_string(), number }))
+            }
+            None => Ok(PullRequestSource::OpenIn(repo.to_string())),
+        }
+    }
+}
+
+/// Whether a ticket key names a pull request ("owner/repo#123") rather than a Jira issue
+pub fn is_pull_request_key(key: &str) -> bool {
+    key.contains('/') && key.contains('#')
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    additions: u64,
+    deletions: u64,
+    changed_files: u64,
+    commits: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestSummary {
+    number: u64,
+}
+
+async fn get<T: for<'de> Deserialize<'de>>(client: &reqwest::Client, url: &str, token: Option<&str>) -> Result<T, String> {
+    let mut request = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "scrum-poker");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("Failed to reach GitHub: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("GitHub API error ({}): {}", status, body));
+    }
+    response.json().await.map_err(|e| format!("Failed to parse GitHub response: {}", e))
+}
+
+/// Load one pull request as a votable ticket, with its diff stats
+pub async fn fetch_pull_request(client: &reqwest::Client, pr: &PullRequestRef, token: Option<&str>) -> Result<JiraTicket, String> {
+    let url = format!("{}/repos/{}/pulls/{}", GITHUB_API, pr.repo, pr.number);
+    let pull: PullRequest = get(client, &url, token).await?;
+    let status = if pull.draft { "draft".to_string() } else { pull.state };
+    Ok(JiraTicket {
+        key: format!("{}#{}", pr.repo, pull.number),
+        summary: pull.title,
+        complexity: Some(analysis::analyze_ticket(pull.body.as_deref(), 0)),
+        description: pull.body,
+        issue_type: Some("Pull request".into()),
+        status: Some(status),
+        url: pull.html_url,
+        diff_stats: Some(DiffStats {
+            additions: pull.additions,
+            deletions: pull.deletions,
+            changed_files: pull.changed_files,
+            commits: pull.commits,
+        }),
+    })
+}
+
+/// Load what the host asked for: one pull request, or up to `MAX_PULL_REQUESTS`
+/// of a repository's open ones, oldest first so the queue follows review order
+pub async fn load(client: &reqwest::Client, source: &PullRequestSource, token: Option<&str>) -> Result<Vec<JiraTicket>, String> {
+    let refs = match source {
+        PullRequestSource::One(pr) => vec![pr.clone()],
+        PullRequestSource::OpenIn(repo) => {
+            let url = format!(
+                "{}/repos/{}/pulls?state=open&sort=created&direction=asc&per_page={}",
+                GITHUB_API, repo, MAX_PULL_REQUESTS
+            );
+            let open: Vec<PullRequestSummary> = get(client, &url, token).await?;
+            open.into_iter()
+                .map(|pr| PullRequestRef { repo: repo.clone(), number: pr.number })
+                .collect()
+        }
+    };
+    // The list endpoint leaves out diff stats, so each one is fetched in full
+    let mut tickets = Vec::with_capacity(refs.len());
+    for pr in &refs {
+        tickets.push(fetch_pull_request(client, pr, token).await?);
+    }
+    Ok(tickets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_requests_can_be_named_several_ways() {
+        let one = PullRequestSource::One(PullRequestRef { repo: "acme/app".into(), number: 42 });
+        assert_eq!(PullRequestSource::parse("acme/app#42").unwrap(), one);
+        assert_eq!(PullRequestSource::parse("https://github.com/acme/app/pull/42/").unwrap(), one);
+        assert_eq!(PullRequestSource::parse("acme/app").unwrap(), PullRequestSource::OpenIn("acme/app".into()));
+        assert!(PullRequestSource::parse("acme").is_err());
+        assert!(PullRequestSource::parse("acme/app#latest").is_err());
+        assert!(is_pull_request_key("acme/app#42"));
+        assert!(!is_pull_request_key("POKER-42"));
+    }
+}
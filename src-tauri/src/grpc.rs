@@ -0,0 +1,219 @@
+//! gRPC control API for internal tools that would rather make typed calls than
+//! use the JSON HTTP API: the `Host` service in proto/host.proto, served over
+//! HTTP/2 without TLS. Its messages and service trait are generated from the
+//! .proto by tonic-build (see build.rs). It goes through the same host actions
+//! as the app and the REST handlers.
+
+// Calls fail with tonic's `Status`, which is large but what the generated traits return
+#![allow(clippy::result_large_err)]
+
+use crate::host_actions;
+use crate::room::{JiraTicket, Participant, Room};
+use crate::state::{AppState, RoomEvent};
+use axum::Router;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Request, Response, Status};
+
+/// Types and service traits generated from proto/host.proto
+#[allow(clippy::large_enum_variant)]
+pub mod proto {
+    tonic::include_proto!("scrumpoker.v1");
+}
+
+use proto::host_server::{Host, HostServer};
+use proto::room_event::Event;
+
+/// Callers must send `authorization: Bearer <token>`; the API is off without one
+pub const GRPC_TOKEN_VAR: &str = "SCRUM_POKER_GRPC_TOKEN";
+/// Address to listen on, e.g. 0.0.0.0:50051 to take calls from other machines
+pub const GRPC_ADDR_VAR: &str = "SCRUM_POKER_GRPC_ADDR";
+const DEFAULT_ADDR: &str = "127.0.0.1:50051";
+
+/// Serve the API if a token is configured
+pub async fn start_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(token) = std::env::var(GRPC_TOKEN_VAR).ok().filter(|token| !token.is_empty()) else {
+        tracing::info!("gRPC API is off; set {} to turn it on", GRPC_TOKEN_VAR);
+        return Ok(());
+    };
+    let address = std::env::var(GRPC_ADDR_VAR).unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    tracing::info!("gRPC API listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(state, token)).await?;
+    Ok(())
+}
+
+/// The service, turning away calls without the token; unknown methods are unimplemented
+pub fn router(state: Arc<AppState>, token: String) -> Router {
+    let expected = format!("Bearer {}", token);
+    let check_token = move |request: Request<()>| {
+        let bearer = request.metadata().get("authorization").and_then(|value| value.to_str().ok());
+        if bearer != Some(expected.as_str()) {
+            return Err(Status::unauthenticated("Missing or invalid token"));
+        }
+        Ok(request)
+    };
+    let service = HostServer::with_interceptor(HostService { state }, check_token);
+    tonic::service::Routes::new(service).into_axum_router()
+}
+
+#[derive(Clone)]
+struct HostService {
+    state: Arc<AppState>,
+}
+
+impl HostService {
+    fn existing_room(&self, room_id: &str) -> Result<Room, Status> {
+        self.state.get_room(room_id).ok_or_else(|| Status::not_found("Room not found"))
+    }
+
+    fn room_message(&self, room: &Room) -> proto::Room {
+        let server_url = self.state.get_server_url();
+        let join_url = if server_url.is_empty() { String::new() } else { format!("{}/join/{}", server_url, room.id) };
+        proto::Room {
+            id: room.id.clone(),
+            name: room.name.clone(),
+            invite_code: room.invite_code.clone(),
+            current_ticket: room.current_ticket.as_ref().map(ticket_message),
+            votes_revealed: room.votes_revealed,
+            participants: room.participants.iter().map(|p| participant_message(p, room.votes_revealed)).collect(),
+            deck: room.deck.clone(),
+            revision: room.revision,
+            join_url,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Host for HostService {
+    async fn create_room(&self, request: Request<proto::CreateRoomRequest>) -> Result<Response<proto::Room>, Status> {
+        let request = request.into_inner();
+        let host_name = Some(request.host_name).filter(|name| !name.is_empty());
+        let room = host_actions::create_room(&self.state, request.name, None, host_name)
+            .await
+            .map_err(Status::invalid_argument)?;
+        Ok(Response::new(self.room_message(&room)))
+    }
+
+    async fn set_ticket(&self, request: Request<proto::SetTicketRequest>) -> Result<Response<proto::Room>, Status> {
+        let request = request.into_inner();
+        let ticket = request.ticket.map(jira_ticket).transpose()?;
+        self.existing_room(&request.room_id)?;
+        host_actions::set_ticket(&self.state, &request.room_id, ticket)
+            .await
+            .map_err(Status::failed_precondition)?;
+        Ok(Response::new(self.room_message(&self.existing_room(&request.room_id)?)))
+    }
+
+    async fn reveal(&self, request: Request<proto::RoomRequest>) -> Result<Response<proto::Room>, Status> {
+        let room_id = request.into_inner().room_id;
+        self.existing_room(&room_id)?;
+        host_actions::reveal_votes(&self.state, &room_id)
+            .await
+            .map_err(Status::failed_precondition)?;
+        Ok(Response::new(self.room_message(&self.existing_room(&room_id)?)))
+    }
+
+    type StreamRoomEventsStream = BoxStream<'static, Result<proto::RoomEvent, Status>>;
+
+    /// The room as it is, then each update, until the room is deleted or the caller hangs up
+    async fn stream_room_events(
+        &self,
+        request: Request<proto::RoomRequest>,
+    ) -> Result<Response<Self::StreamRoomEventsStream>, Status> {
+        let room = self.existing_room(&request.into_inner().room_id)?;
+        // Watching before the first event is sent, so nothing falls in between
+        let events = self.state.watch_rooms();
+        let first = updated(self.room_message(&room));
+        let service = self.clone();
+        let updates = stream::unfold(Some((events, service, room.id)), |watch| async move {
+            let (mut events, service, room_id) = watch?;
+            loop {
+                let event = match events.recv().await {
+                    Ok(RoomEvent::Updated(room)) if room.id == room_id => updated(service.room_message(&room)),
+                    Ok(RoomEvent::Deleted(deleted)) if deleted == room_id => {
+                        let event = proto::RoomEvent { event: Some(Event::Deleted(proto::RoomDeleted { room_id: deleted })) };
+                        return Some((Ok(event), None));
+                    }
+                    Ok(_) => continue,
+                    // Missed updates; the room as it is now covers them
+                    Err(RecvError::Lagged(_)) => match service.state.get_room(&room_id) {
+                        Some(room) => updated(service.room_message(&room)),
+                        None => continue,
+                    },
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), Some((events, service, room_id))));
+            }
+        });
+        Ok(Response::new(stream::once(async { Ok(first) }).chain(updates).boxed()))
+    }
+}
+
+fn updated(room: proto::Room) -> proto::RoomEvent {
+    proto::RoomEvent { event: Some(Event::Updated(room)) }
+}
+
+fn ticket_message(ticket: &JiraTicket) -> proto::Ticket {
+    proto::Ticket {
+        key: ticket.key.clone(),
+        summary: ticket.summary.clone(),
+        description: ticket.description.clone().unwrap_or_default(),
+        issue_type: ticket.issue_type.clone().unwrap_or_default(),
+        url: ticket.url.clone(),
+    }
+}
+
+fn jira_ticket(ticket: proto::Ticket) -> Result<JiraTicket, Status> {
+    if ticket.key.is_empty() {
+        return Err(Status::invalid_argument("The ticket needs a key"));
+    }
+    Ok(JiraTicket {
+        key: ticket.key,
+        summary: ticket.summary,
+        description: Some(ticket.description).filter(|text| !text.is_empty()),
+        issue_type: Some(ticket.issue_type).filter(|text| !text.is_empty()),
+        url: ticket.url,
+        ..Default::default()
+    })
+}
+
+fn participant_message(participant: &Participant, revealed: bool) -> proto::Participant {
+    proto::Participant {
+        id: participant.id.clone(),
+        name: participant.name.clone(),
+        is_host: participant.is_host,
+        has_voted: participant.vote.is_some(),
+        vote: participant.vote.clone().filter(|_| revealed).unwrap_or_default(),
+        connected: participant.disconnected_at.is_none(),
+        is_observer: participant.is_observer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::participant;
+
+    #[test]
+    fn votes_stay_hidden_until_revealed() {
+        let mut alice = participant("alice", "Alice");
+        alice.vote = Some("5".into());
+
+        let hidden = participant_message(&alice, false);
+        assert!(hidden.has_voted);
+        assert_eq!(hidden.vote, "");
+        assert_eq!(participant_message(&alice, true).vote, "5");
+    }
+
+    #[test]
+    fn tickets_need_a_key() {
+        let ticket = proto::Ticket { key: "PROJ-7".into(), summary: "Add login".into(), ..Default::default() };
+        let ticket = jira_ticket(ticket).unwrap();
+        assert_eq!((ticket.key.as_str(), ticket.description), ("PROJ-7", None));
+
+        let keyless = proto::Ticket { summary: "No key".into(), ..Default::default() };
+        assert_eq!(jira_ticket(keyless).unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
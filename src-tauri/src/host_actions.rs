@@ -0,0 +1,142 @@
+use crate::decks::DeckDefinition;
+use crate::room::{JiraTicket, Room};
+use crate::state::AppState;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Host actions that can be bound to a keyboard shortcut or a button deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostAction {
+    Reveal,
+    Hide,
+    Reset,
+    NextTicket,
+}
+
+impl HostAction {
+    /// Action named by the last segment of a controller URL
+    pub fn from_path(name: &str) -> Option<Self> {
+        match name {
+            "reveal" => Some(Self::Reveal),
+            "hide" => Some(Self::Hide),
+            "reset" => Some(Self::Reset),
+            "next" => Some(Self::NextTicket),
+            _ => None,
+        }
+    }
+}
+
+/// Run one of the common host actions on a room. Buttons, shortcuts and external
+/// controllers all go through here, so they behave the same and each use is
+/// logged the same way.
+pub async fn perform(state: &Arc<AppState>, room_id: &str, action: HostAction) -> Result<(), String> {
+    tracing::info!(target: "host_action", room_id = %room_id, ?action, "Host action");
+    let result = match action {
+        HostAction::Reveal => reveal_votes(state, room_id).await,
+        HostAction::Hide => hide_votes(state, room_id).await,
+        HostAction::Reset => reset_votes(state, room_id).await,
+        HostAction::NextTicket => next_ticket(state, room_id).await.map(|_| ()),
+    };
+    if let Err(e) = &result {
+        tracing::warn!(target: "host_action", room_id = %room_id, ?action, "Host action failed: {}", e);
+    }
+    result
+}
+
+/// Open a room, putting it on the relay too if there is one
+pub async fn create_room(
+    state: &Arc<AppState>,
+    name: String,
+    deck: Option<DeckDefinition>,
+    host_name: Option<String>,
+) -> Result<Room, String> {
+    let room = state.create_room(name, deck, host_name)?;
+
+    // If relay is connected, sync the room
+    if let Some(relay_client) = state.get_relay_client().await {
+        if let Err(e) = relay_client.sync_room(room.clone()) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    Ok(room)
+}
+
+/// Show a ticket to the room, or clear the current one with None
+pub async fn set_ticket(state: &AppState, room_id: &str, ticket: Option<JiraTicket>) -> Result<(), String> {
+    if state.get_room(room_id).is_none() {
+        return Err("Room not found".into());
+    }
+    state.set_current_ticket(room_id, ticket.clone());
+    state.broadcast_room_update(room_id).await;
+
+    // Notify relay
+    if let Some(relay_client) = state.get_relay_client().await {
+        let _ = match (ticket, state.get_room(room_id)) {
+            (Some(ticket), Some(room)) => relay_client.set_ticket(room_id.to_string(), ticket, room.deck),
+            _ => relay_client.clear_ticket(room_id.to_string()),
+        };
+    }
+    Ok(())
+}
+
+pub async fn reveal_votes(state: &Arc<AppState>, room_id: &str) -> Result<(), String> {
+    // Rooms that reveal one by one show their first card right away
+    if state.start_stepwise_reveal(room_id) {
+        state.broadcast_room_update(room_id).await;
+        state.reveal_next_vote(room_id).await;
+        return Ok(());
+    }
+    state.set_votes_revealed(room_id, true);
+    state.broadcast_room_update(room_id).await;
+
+    // Notify relay
+    if let Some(relay_client) = state.get_relay_client().await {
+        let _ = relay_client.reveal_votes(room_id.to_string());
+        // The sprint fill meter moved with the new estimate
+        if let Some(room) = state.get_room(room_id).filter(|room| room.sprint_capacity.is_some()) {
+            let _ = relay_client.sync_room(room);
+        }
+    }
+    Ok(())
+}
+
+pub async fn hide_votes(state: &Arc<AppState>, room_id: &str) -> Result<(), String> {
+    state.set_votes_revealed(room_id, false);
+    state.broadcast_room_update(room_id).await;
+
+    // Notify relay
+    if let Some(relay_client) = state.get_relay_client().await {
+        let _ = relay_client.hide_votes(room_id.to_string());
+    }
+    Ok(())
+}
+
+pub async fn reset_votes(state: &Arc<AppState>, room_id: &str) -> Result<(), String> {
+    state.reset_votes(room_id);
+    state.broadcast_room_update(room_id).await;
+
+    // Notify relay
+    if let Some(relay_client) = state.get_relay_client().await {
+        let _ = relay_client.reset_votes(room_id.to_string());
+    }
+    Ok(())
+}
+
+/// Move on to the next queued ticket
+pub async fn next_ticket(state: &Arc<AppState>, room_id: &str) -> Result<Option<JiraTicket>, String> {
+    if state.get_room(room_id).is_none() {
+        return Err("Room not found".into());
+    }
+    let Some(ticket) = state.advance_ticket(room_id) else {
+        return Ok(None);
+    };
+    state.broadcast_room_update(room_id).await;
+
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(room_id)) {
+        let _ = relay_client.set_ticket(room_id.to_string(), ticket.clone(), room.deck);
+    }
+
+    crate::prefetch_queued_tickets(state.clone(), room_id.to_string());
+    Ok(Some(ticket))
+}
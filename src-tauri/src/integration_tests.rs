@@ -0,0 +1,571 @@
+//! End-to-end tests that start the real API server on an ephemeral port and
+//! talk to it over WebSockets. Run with `cargo test --features integration-tests`.
+
+use crate::api;
+use crate::room::{ErrorCode, JiraTicket, Room, WsMessage};
+use crate::state::AppState;
+use crate::test_support::{test_state, FakeEnvironment, START_TIME};
+use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// How long to wait for a message before failing the test
+const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A running API server backed by its own state
+struct TestServer {
+    state: Arc<AppState>,
+    env: Arc<FakeEnvironment>,
+    port: u16,
+}
+
+impl TestServer {
+    async fn start() -> Self {
+        let (state, env) = test_state();
+        let state = Arc::new(state);
+        tokio::spawn(api::start_server(state.clone(), 0..=0));
+
+        // The port is published once the listener is bound
+        let port = tokio::time::timeout(RECV_TIMEOUT, async {
+            loop {
+                let port = *state.server_port.read().unwrap();
+                if port != 0 {
+                    return port;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("server didn't start");
+
+        Self { state, env, port }
+    }
+
+    fn create_room(&self, name: &str) -> Room {
+        self.state.create_room(name.to_string(), None, None).expect("valid room name")
+    }
+
+    async fn connect(&self, room_id: &str) -> TestClient {
+        let (ws, _) = connect_async(format!("ws://127.0.0.1:{}/ws/{}", self.port, room_id))
+            .await
+            .expect("WebSocket connect failed");
+        TestClient { ws }
+    }
+
+    /// Connect and join a room, returning the client and its participant ID
+    async fn join(&self, room_id: &str, name: &str) -> (TestClient, String) {
+        let mut client = self.connect(room_id).await;
+        client
+            .send(WsMessage::Join {
+                room_id: room_id.to_string(),
+                name: name.to_string(),
+                identity: None,
+                client: None,
+                observer: false,
+            })
+            .await;
+        let participant_id = match client.recv_matching(|m| matches!(m, WsMessage::CatchUp(_))).await {
+            WsMessage::CatchUp(catch_up) => catch_up.participant_id,
+            _ => unreachable!(),
+        };
+        (client, participant_id)
+    }
+}
+
+/// A participant speaking the native WebSocket protocol
+struct TestClient {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl TestClient {
+    async fn send(&mut self, message: WsMessage) {
+        let text = serde_json::to_string(&message).unwrap();
+        self.ws.send(Message::Text(text)).await.expect("send failed");
+    }
+
+    /// Next protocol message from the server
+    async fn recv(&mut self) -> WsMessage {
+        loop {
+            let frame = tokio::time::timeout(RECV_TIMEOUT, self.ws.next())
+                .await
+                .expect("timed out waiting for a message")
+                .expect("connection closed")
+                .expect("WebSocket error");
+            if let Message::Text(text) = frame {
+                return serde_json::from_str(&text).unwrap_or_else(|e| panic!("bad message {}: {}", text, e));
+            }
+        }
+    }
+
+    /// Skip messages until one matches
+    async fn recv_matching(&mut self, matches: impl Fn(&WsMessage) -> bool) -> WsMessage {
+        loop {
+            let message = self.recv().await;
+            if matches(&message) {
+                return message;
+            }
+        }
+    }
+
+    /// Next room update, skipping anything else
+    async fn recv_room_update(&mut self) -> (Room, String) {
+        match self.recv_matching(|m| matches!(m, WsMessage::RoomUpdate { .. })).await {
+            WsMessage::RoomUpdate { room, checksum, .. } => (room, checksum),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn join_broadcasts_update_then_sends_catch_up() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+
+    let mut alice = server.connect(&room.id).await;
+    alice
+        .send(WsMessage::Join { room_id: room.id.clone(), name: "Alice".into(), identity: None, client: None, observer: false })
+        .await;
+
+    let (update, checksum) = alice.recv_room_update().await;
+    assert_eq!(update.participants.len(), 1);
+    assert_eq!(checksum, update.checksum());
+
+    match alice.recv().await {
+        WsMessage::CatchUp(catch_up) => {
+            assert_eq!(catch_up.participant_id, update.participants[0].id);
+            assert!(catch_up.voting_open);
+        }
+        other => panic!("expected catch-up, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn votes_are_broadcast_with_increasing_revisions() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, alice_id) = server.join(&room.id, "Alice").await;
+    let (mut bob, _) = server.join(&room.id, "Bob").await;
+
+    // Alice sees Bob join
+    let (joined, _) = alice.recv_room_update().await;
+    assert_eq!(joined.participants.len(), 2);
+
+    server.env.advance(12);
+    alice.send(WsMessage::Vote { vote: Some("5".into()) }).await;
+
+    let (seen_by_bob, _) = bob.recv_room_update().await;
+    let (seen_by_alice, _) = alice.recv_room_update().await;
+    assert_eq!(seen_by_bob.revision, joined.revision + 1);
+    assert_eq!(seen_by_alice.revision, seen_by_bob.revision);
+    let alice_vote = seen_by_bob.participants.iter().find(|p| p.id == alice_id).unwrap();
+    assert_eq!(alice_vote.vote.as_deref(), Some("5"));
+    assert_eq!(seen_by_bob.round_first_votes.get(&alice_id), Some(&12));
+}
+
+#[tokio::test]
+async fn joining_over_http_only_returns_what_the_joiner_may_see() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    server.state.set_room_hide_voters(&room.id, true);
+    let (mut alice, alice_id) = server.join(&room.id, "Alice").await;
+    alice.send(WsMessage::Vote { vote: Some("5".into()) }).await;
+    alice.recv_room_update().await;
+
+    let joined: serde_json::Value = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/api/room/{}/join", server.port, room.id))
+        .json(&serde_json::json!({ "name": "Bob" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let seen: Room = serde_json::from_value(joined["room"].clone()).unwrap();
+    let alice = seen.participants.iter().find(|p| p.id == alice_id).unwrap();
+    assert_eq!(alice.vote, None);
+    assert!(!seen.round_first_votes.contains_key(&alice_id));
+    assert!(server.state.get_room(&room.id).unwrap().participants.iter().any(|p| p.vote.as_deref() == Some("5")));
+}
+
+#[tokio::test]
+async fn invalid_vote_is_rejected_only_for_the_sender() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+    let (mut bob, _) = server.join(&room.id, "Bob").await;
+    alice.recv_room_update().await;
+
+    alice.send(WsMessage::Vote { vote: Some("7".into()) }).await;
+    match alice.recv().await {
+        WsMessage::Error { code, .. } => assert_eq!(code, ErrorCode::InvalidVote),
+        other => panic!("expected error, got {:?}", other),
+    }
+
+    // Bob's next message is the update for his own vote, not anything from Alice's
+    bob.send(WsMessage::Vote { vote: Some("3".into()) }).await;
+    let (update, _) = bob.recv_room_update().await;
+    assert!(update.participants.iter().all(|p| p.vote.as_deref() != Some("7")));
+}
+
+#[tokio::test]
+async fn ending_the_session_notifies_everyone_and_blocks_joins() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+
+    server.env.advance(300);
+    server.state.end_session(&room.id).unwrap();
+
+    match alice.recv_matching(|m| matches!(m, WsMessage::SessionEnded { .. })).await {
+        WsMessage::SessionEnded { summary } => assert_eq!(summary.duration_secs, 300),
+        _ => unreachable!(),
+    }
+
+    let mut late = server.connect(&room.id).await;
+    late.send(WsMessage::Join { room_id: room.id.clone(), name: "Late".into(), identity: None, client: None, observer: false })
+        .await;
+    match late.recv().await {
+        WsMessage::Error { code, .. } => assert_eq!(code, ErrorCode::SessionEnded),
+        other => panic!("expected error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn resync_resends_current_state() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+
+    alice.send(WsMessage::Resync).await;
+    let (update, checksum) = alice.recv_room_update().await;
+    assert_eq!(update.id, room.id);
+    assert_eq!(checksum, update.checksum());
+}
+
+#[tokio::test]
+async fn ticket_details_are_sent_only_when_the_ticket_changes() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+
+    let ticket = JiraTicket { key: "POKER-1".into(), description: Some("long text".into()), ..Default::default() };
+    server.state.set_current_ticket(&room.id, Some(ticket));
+    server.state.broadcast_room_update(&room.id).await;
+    let hash = match alice.recv_matching(|m| matches!(m, WsMessage::TicketChanged { .. })).await {
+        WsMessage::TicketChanged { ticket, ticket_hash } => {
+            assert_eq!(ticket.unwrap().key, "POKER-1");
+            ticket_hash
+        }
+        _ => unreachable!(),
+    };
+    let (update, checksum) = alice.recv_room_update().await;
+    assert!(update.current_ticket.is_none());
+    assert_eq!(update.ticket_hash, hash);
+    assert_eq!(checksum, update.checksum());
+
+    // Unrelated updates carry the same hash and no ticket
+    alice.send(WsMessage::Vote { vote: Some("5".into()) }).await;
+    assert!(matches!(alice.recv().await, WsMessage::RoomUpdate { room, .. } if room.ticket_hash == hash));
+}
+
+#[tokio::test]
+async fn join_page_sees_a_countdown_to_the_announced_start() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let url = format!("http://127.0.0.1:{}/api/room/{}", server.port, room.id);
+    let starts_in = |body: serde_json::Value| body.get("starts_in_secs").and_then(|v| v.as_u64());
+
+    let body: serde_json::Value = reqwest::get(&url).await.unwrap().json().await.unwrap();
+    assert_eq!(starts_in(body), None);
+
+    server.state.set_scheduled_start(&room.id, Some(START_TIME + 240)).unwrap();
+    server.env.advance(60);
+    let body: serde_json::Value = reqwest::get(&url).await.unwrap().json().await.unwrap();
+    assert_eq!(body["name"], "Sprint 1");
+    assert_eq!(starts_in(body), Some(180));
+
+    server.env.advance(180);
+    let body: serde_json::Value = reqwest::get(&url).await.unwrap().json().await.unwrap();
+    assert_eq!(starts_in(body), None);
+}
+
+#[tokio::test]
+async fn unchanged_rooms_are_not_sent_again() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let url = format!("http://127.0.0.1:{}/api/room/{}", server.port, room.id);
+    let client = reqwest::Client::new();
+
+    let first = client.get(&url).send().await.unwrap();
+    let etag = first.headers()["etag"].to_str().unwrap().to_string();
+    let again = client.get(&url).header("If-None-Match", &etag).send().await.unwrap();
+    assert_eq!(again.status(), reqwest::StatusCode::NOT_MODIFIED);
+
+    server.state.broadcast_room_update(&room.id).await;
+    let changed = client.get(&url).header("If-None-Match", &etag).send().await.unwrap();
+    assert_eq!(changed.status(), reqwest::StatusCode::OK);
+    assert_ne!(changed.headers()["etag"], etag.as_str());
+}
+
+#[tokio::test]
+async fn request_ids_are_echoed_or_assigned() {
+    let server = TestServer::start().await;
+    let url = format!("http://127.0.0.1:{}/api/story-points", server.port);
+    let client = reqwest::Client::new();
+
+    let given = client.get(&url).header("x-request-id", "abc-123").send().await.unwrap();
+    assert_eq!(given.headers()["x-request-id"], "abc-123");
+    let assigned = client.get(&url).send().await.unwrap();
+    assert!(!assigned.headers()["x-request-id"].is_empty());
+}
+
+#[tokio::test]
+async fn controller_urls_run_host_actions_with_a_valid_token() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+    alice.send(WsMessage::Vote { vote: Some("5".into()) }).await;
+    alice.recv_room_update().await;
+
+    let token = server.state.controller_token(&room.id).unwrap();
+    let base = format!("http://127.0.0.1:{}/api/host", server.port);
+    let reveal = reqwest::get(format!("{}/{}/reveal", base, token)).await.unwrap();
+    assert_eq!(reveal.status(), reqwest::StatusCode::OK);
+    let (update, _) = alice.recv_room_update().await;
+    assert!(update.votes_revealed);
+
+    let forged = reqwest::Client::new().post(format!("{}/not-a-token/reset", base)).send().await.unwrap();
+    assert_eq!(forged.status(), reqwest::StatusCode::UNAUTHORIZED);
+    assert!(server.state.get_room(&room.id).unwrap().votes_revealed);
+}
+
+#[tokio::test]
+async fn connections_are_bound_to_the_room_in_the_url() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let other = server.create_room("Sprint 2");
+
+    let unknown = connect_async(format!("ws://127.0.0.1:{}/ws/no-such-room", server.port)).await;
+    assert!(unknown.is_err(), "upgrade should be refused for an unknown room");
+
+    // The room in the Join message can't override the URL
+    let mut alice = server.connect(&room.id).await;
+    alice
+        .send(WsMessage::Join { room_id: other.id.clone(), name: "Alice".into(), identity: None, client: None, observer: false })
+        .await;
+    let (update, _) = alice.recv_room_update().await;
+    assert_eq!(update.id, room.id);
+
+    // Nor can a second Join move the connection elsewhere
+    alice
+        .send(WsMessage::Join { room_id: other.id.clone(), name: "Alice".into(), identity: None, client: None, observer: false })
+        .await;
+    match alice.recv_matching(|m| matches!(m, WsMessage::Error { .. })).await {
+        WsMessage::Error { code, .. } => assert_eq!(code, ErrorCode::Other),
+        _ => unreachable!(),
+    }
+    assert!(server.state.get_room(&other.id).unwrap().participants.is_empty());
+}
+
+/// The web client's WebSocket URL template, as written in its source
+const WEB_CLIENT_WS_URL: &str = "`${protocol}//${window.location.host}/ws/${encodeURIComponent(roomId)}`";
+
+/// The WebSocket URL the web client opens from a join page, built the way App.tsx builds it:
+/// the room is the path segment after `/join/` (or `/r/` on the relay)
+fn web_client_ws_url(page_url: &str) -> String {
+    let page = url::Url::parse(page_url).unwrap();
+    let mut segments = page.path_segments().unwrap();
+    let room_id = segments
+        .by_ref()
+        .skip_while(|segment| *segment != "join" && *segment != "r")
+        .nth(1)
+        .expect("no room in the join page URL");
+    let encoded: String = room_id
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("ws://{}:{}/ws/{}", page.host_str().unwrap(), page.port().unwrap(), encoded)
+}
+
+#[tokio::test]
+async fn web_client_connects_from_its_join_page() {
+    let client_source = include_str!("../../web-client/src/App.tsx");
+    assert!(client_source.contains(WEB_CLIENT_WS_URL), "the web client builds its WebSocket URL differently");
+
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+
+    // Share links name the room by ID, or by its invite code with dashes for spaces
+    let invite = room.invite_code.replace(' ', "-");
+    for (name, room_in_link) in [("Alice", room.id.as_str()), ("Bob", invite.as_str())] {
+        let ws_url = web_client_ws_url(&format!("http://127.0.0.1:{}/join/{}", server.port, room_in_link));
+        let (ws, _) = connect_async(ws_url).await.expect("WebSocket connect failed");
+        let mut client = TestClient { ws };
+        client
+            .send(WsMessage::Join { room_id: room_in_link.to_string(), name: name.into(), identity: None, client: None, observer: false })
+            .await;
+        match client.recv_matching(|m| matches!(m, WsMessage::CatchUp(_))).await {
+            WsMessage::CatchUp(catch_up) => assert_eq!(catch_up.room.id, room.id),
+            _ => unreachable!(),
+        }
+    }
+    assert_eq!(server.state.get_room(&room.id).unwrap().participants.len(), 2);
+}
+
+#[tokio::test]
+async fn admin_page_is_only_served_on_the_loopback_listener() {
+    let server = TestServer::start().await;
+    let admin_port = tokio::time::timeout(RECV_TIMEOUT, async {
+        loop {
+            let port = *server.state.admin_port.read().unwrap();
+            if port != 0 {
+                return port;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("admin server didn't start");
+    assert_ne!(admin_port, server.port);
+
+    let token = &server.state.admin_token;
+    let admin = reqwest::get(format!("http://127.0.0.1:{}/admin?token={}", admin_port, token)).await.unwrap();
+    assert!(admin.text().await.unwrap().contains("Scrum Poker admin"));
+
+    let public = reqwest::get(format!("http://127.0.0.1:{}/admin?token={}", server.port, token)).await.unwrap();
+    assert!(!public.text().await.unwrap().contains("Scrum Poker admin"));
+}
+
+#[tokio::test]
+async fn basic_client_can_join_and_vote_without_javascript() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+    let base = format!("http://127.0.0.1:{}/join/{}/basic", server.port, room.id);
+    let http = reqwest::Client::new();
+
+    // Joining redirects to the room page for the new session
+    let joined = http.post(&base).form(&[("name", "Bob")]).send().await.unwrap();
+    let session = joined
+        .url()
+        .query_pairs()
+        .find(|(key, _)| key == "session")
+        .map(|(_, value)| value.into_owned())
+        .expect("redirected to a session");
+    assert!(joined.text().await.unwrap().contains("Bob (you)"));
+    let (update, _) = alice.recv_room_update().await;
+    assert_eq!(update.participants.len(), 2);
+
+    http.post(format!("{}/vote", base))
+        .form(&[("session", session.as_str()), ("vote", "8")])
+        .send()
+        .await
+        .unwrap();
+    let (update, _) = alice.recv_room_update().await;
+    let bob = update.participants.iter().find(|p| p.name == "Bob").unwrap();
+    assert_eq!(bob.vote.as_deref(), Some("8"));
+}
+
+#[tokio::test]
+async fn basic_client_leave_only_redirects_to_existing_rooms() {
+    let server = TestServer::start().await;
+    let url = format!("http://127.0.0.1:{}/join/%0A/basic/leave", server.port);
+    let left = reqwest::Client::new().post(url).form(&[("session", "none")]).send().await.unwrap();
+    assert_eq!(left.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deeply_nested_messages_close_the_connection() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+
+    let nested = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    alice.ws.send(Message::Text(nested)).await.expect("send failed");
+
+    let closed = tokio::time::timeout(RECV_TIMEOUT, async {
+        loop {
+            match alice.ws.next().await {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            }
+        }
+    })
+    .await;
+    assert!(closed.is_ok(), "connection stayed open");
+}
+
+#[tokio::test]
+async fn time_sync_echoes_the_client_clock_with_the_servers() {
+    let server = TestServer::start().await;
+    let room = server.create_room("Sprint 1");
+    let (mut alice, _) = server.join(&room.id, "Alice").await;
+
+    alice.send(WsMessage::TimeSync { client_time: 42 }).await;
+    let reply = alice.recv_matching(|m| matches!(m, WsMessage::TimeSyncReply { .. })).await;
+    assert!(matches!(
+        reply,
+        WsMessage::TimeSyncReply { client_time: 42, server_time } if server_time == START_TIME * 1000
+    ));
+
+    // Room updates carry the server's clock too
+    server.env.advance(5);
+    alice.send(WsMessage::Resync).await;
+    let update = alice.recv_matching(|m| matches!(m, WsMessage::RoomUpdate { .. })).await;
+    assert!(matches!(update, WsMessage::RoomUpdate { server_time, .. } if server_time == (START_TIME + 5) * 1000));
+}
+
+/// A gRPC request carrying `token`
+#[cfg(feature = "grpc")]
+fn grpc_request<T>(message: T, token: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    request
+}
+
+#[cfg(feature = "grpc")]
+#[tokio::test]
+async fn grpc_host_calls_share_the_app_host_actions() {
+    use crate::grpc::proto::{host_client::HostClient, room_event::Event, CreateRoomRequest, RoomRequest, SetTicketRequest, Ticket};
+
+    let (state, _env) = test_state();
+    let state = Arc::new(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server_state = state.clone();
+    tokio::spawn(async move { axum::serve(listener, crate::grpc::router(server_state, "secret".into())).await });
+    let mut client = HostClient::connect(url).await.unwrap();
+
+    let create = CreateRoomRequest { name: "Sprint 1".into(), host_name: String::new() };
+    let created = client.create_room(grpc_request(create, "secret")).await.unwrap().into_inner();
+    assert_eq!(created.name, "Sprint 1");
+    let room_id = created.id;
+
+    let watch = RoomRequest { room_id: room_id.clone() };
+    let mut events = client.stream_room_events(grpc_request(watch, "secret")).await.unwrap().into_inner();
+    let current = tokio::time::timeout(RECV_TIMEOUT, events.message()).await.unwrap().unwrap().unwrap();
+    assert!(matches!(current.event, Some(Event::Updated(room)) if room.name == "Sprint 1"));
+
+    let ticket = Ticket { key: "PROJ-7".into(), summary: "Add login".into(), ..Default::default() };
+    let set_ticket = SetTicketRequest { room_id: room_id.clone(), ticket: Some(ticket) };
+    let set = client.set_ticket(grpc_request(set_ticket, "secret")).await.unwrap().into_inner();
+    assert_eq!(set.current_ticket.unwrap().key, "PROJ-7");
+    let update = tokio::time::timeout(RECV_TIMEOUT, events.message()).await.unwrap().unwrap().unwrap();
+    assert!(matches!(update.event, Some(Event::Updated(room)) if room.current_ticket.as_ref().is_some_and(|t| t.key == "PROJ-7")));
+
+    client.reveal(grpc_request(RoomRequest { room_id: room_id.clone() }, "secret")).await.unwrap();
+    assert!(state.get_room(&room_id).unwrap().votes_revealed);
+    assert_eq!(state.get_room(&room_id).unwrap().current_ticket.unwrap().key, "PROJ-7");
+
+    let missing = client.reveal(grpc_request(RoomRequest { room_id: "no-such-room".into() }, "secret")).await;
+    assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+    let forged = client.reveal(grpc_request(RoomRequest { room_id }, "not-the-token")).await;
+    assert_eq!(forged.unwrap_err().code(), tonic::Code::Unauthenticated);
+}
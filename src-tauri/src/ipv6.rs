@@ -0,0 +1,100 @@
+use serde::Serialize;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
+
+/// How long the reachability test waits for the server to answer
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Where a globally routable IPv6 address comes from. Transition addresses
+/// (Teredo, 6to4) tunnel through IPv4 NAT, so they can work when port
+/// forwarding doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ipv6Kind {
+    /// Assigned by the ISP; preferred
+    Native,
+    /// 6to4 (2002::/16)
+    SixToFour,
+    /// Teredo (2001:0::/32), Windows' built-in tunnel
+    Teredo,
+}
+
+/// An IPv6 address participants could use instead of the IPv4 one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ipv6Route {
+    pub address: Ipv6Addr,
+    pub kind: Ipv6Kind,
+    /// Whether the server answered on this address when last tested
+    pub reachable: bool,
+}
+
+impl Ipv6Route {
+    pub fn url(&self, port: u16) -> String {
+        format!("http://[{}]:{}", self.address, port)
+    }
+}
+
+/// The kind of a globally routable address; None for loopback, link-local,
+/// unique-local and other addresses nobody outside can reach
+pub fn classify(address: &Ipv6Addr) -> Option<Ipv6Kind> {
+    let segments = address.segments();
+    // Global unicast is 2000::/3
+    if segments[0] & 0xe000 != 0x2000 {
+        return None;
+    }
+    Some(match segments {
+        [0x2001, 0x0000, ..] => Ipv6Kind::Teredo,
+        [0x2002, ..] => Ipv6Kind::SixToFour,
+        _ => Ipv6Kind::Native,
+    })
+}
+
+/// The best globally routable IPv6 address among the machine's addresses:
+/// native over 6to4 over Teredo
+pub fn best_address(addresses: impl IntoIterator<Item = IpAddr>) -> Option<(Ipv6Addr, Ipv6Kind)> {
+    addresses
+        .into_iter()
+        .filter_map(|address| match address {
+            IpAddr::V6(v6) => classify(&v6).map(|kind| (v6, kind)),
+            IpAddr::V4(_) => None,
+        })
+        .min_by_key(|(_, kind)| *kind)
+}
+
+/// This machine's best IPv6 address, if it has a routable one
+pub fn detect() -> Option<(Ipv6Addr, Ipv6Kind)> {
+    let interfaces = local_ip_address::list_afinet_netifas().ok()?;
+    best_address(interfaces.into_iter().map(|(_, address)| address))
+}
+
+/// Detect an IPv6 address and check the server answers on it. This only
+/// proves the server is listening there; a firewall between here and the
+/// participant can still block the way in.
+pub async fn probe(port: u16) -> Option<Ipv6Route> {
+    let (address, kind) = detect()?;
+    let url = format!("http://[{}]:{}/api/story-points", address, port);
+    let reachable = match reqwest::Client::builder().no_proxy().timeout(REACHABILITY_TIMEOUT).build() {
+        Ok(client) => client.get(&url).send().await.is_ok_and(|response| response.status().is_success()),
+        Err(_) => false,
+    };
+    Some(Ipv6Route { address, kind, reachable })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_addresses_are_preferred_over_tunnels() {
+        let teredo: IpAddr = "2001:0:4137:9e76:2c5a:1a2b:3c4d:5e6f".parse().unwrap();
+        let six_to_four: IpAddr = "2002:c000:0204::1".parse().unwrap();
+        let native: IpAddr = "2a02:8070:1234::42".parse().unwrap();
+        let link_local: IpAddr = "fe80::1".parse().unwrap();
+        let unique_local: IpAddr = "fd12:3456::1".parse().unwrap();
+
+        assert_eq!(best_address([link_local, unique_local]), None);
+        assert_eq!(best_address([link_local, teredo]).map(|(_, kind)| kind), Some(Ipv6Kind::Teredo));
+        assert_eq!(best_address([teredo, six_to_four]).map(|(_, kind)| kind), Some(Ipv6Kind::SixToFour));
+        assert_eq!(best_address([teredo, native, six_to_four]).map(|(address, _)| IpAddr::V6(address)), Some(native));
+    }
+}
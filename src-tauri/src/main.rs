@@ -1,39 +1,107 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod accuracy;
+mod adf;
+mod admin;
+mod analysis;
 mod api;
+mod ballots;
+mod basic_client;
+mod branding;
+mod campaign;
+mod capacity;
+mod copilot;
 mod credentials;
+mod decks;
+mod diagnostics;
+mod estimate_rules;
+mod estimation;
+mod github;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod host_actions;
+#[cfg(all(test, feature = "integration-tests"))]
+mod integration_tests;
+mod ipv6;
+mod merge;
+mod message_limits;
+mod migrations;
+mod moderation;
+mod onboarding;
+mod outbox;
+mod persistence;
+mod port_forward;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod profiles;
+mod recap;
+mod recovery;
 mod relay;
 mod room;
+mod seats;
+mod session_plan;
+mod session_search;
+mod settings;
+mod sms;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod state;
-
+mod storage;
+#[cfg(test)]
+mod test_support;
+mod throttle;
+mod timezone;
+mod updates;
+
+use host_actions::HostAction;
 use room::JiraTicket;
-use state::AppState;
+use state::{AppState, PendingJiraRequest};
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use dashmap::DashMap;
+use futures_util::StreamExt;
 use base64::{Engine as _, engine::general_purpose};
 use serde::{Deserialize, Serialize};
+use tauri::Emitter;
 
 fn main() {
+    let app_state = Arc::new(AppState::new());
+
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer())
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
         ))
+        .with(diagnostics::ErrorLogLayer::new(app_state.error_log.clone()))
         .init();
 
-    let app_state = Arc::new(AppState::new());
+    if let Some(profile) = profiles::requested_profile() {
+        if let Err(e) = profiles::set_active_profile(&profile) {
+            tracing::error!("Can't use profile {}: {}", profile, e);
+        }
+    }
+    load_profile(&app_state);
+    recovery::install_panic_hook(app_state.clone());
 
     tauri::Builder::default()
         .manage(app_state.clone())
         .setup(move |app| {
             let state = app_state.clone();
-            let app_handle = app.handle().clone();
+
+            // Ask the host to unlock or re-enter the credentials when Jira rejects them
+            let handle = app.handle().clone();
+            state.set_jira_auth_callback(move || {
+                let _ = handle.emit("jira-auth-failed", ());
+            });
             
             // Start the API server in a background thread
             std::thread::spawn(move || {
                 let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
                 rt.block_on(async {
-                    if let Err(e) = api::start_server(state, app_handle).await {
+                    tokio::spawn(collect_ticket_actuals(state.clone()));
+                    tokio::spawn(apply_estimate_rules(state.clone()));
+                    tokio::spawn(apply_shared_changes(state.clone()));
+                    if let Err(e) = api::start_server(state, api::DEFAULT_PORTS).await {
                         tracing::error!("API server error: {}", e);
                     }
                 });
@@ -43,54 +111,215 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             create_room,
+            host_vote,
+            host_clear_vote,
             get_rooms,
             get_room,
             delete_room,
             reveal_votes,
             hide_votes,
+            tag_round,
+            override_estimate,
+            get_room_history,
+            set_estimate_rule,
+            reveal_next_vote,
+            set_reveal_mode,
             reset_votes,
+            host_action,
             get_server_url,
             kick_participant,
+            set_participant_sitting_out,
+            set_disconnect_grace,
+            set_scheduled_start,
+            set_room_time_zone,
+            get_participant_stats,
+            get_estimation_accuracy,
+            generate_session_recap,
+            format_round_summary,
+            issue_async_ballots,
+            get_async_results,
+            draft_session_summary,
+            search_sessions,
+            end_session,
+            set_room_lan_only,
+            set_estimation_method,
+            set_risk_voting,
+            set_hide_voters,
+            set_abstain_offered,
+            set_sprint_capacity,
+            trace_participant,
+            revert_deck_switch,
+            start_batch,
+            push_batch_estimate,
+            push_estimate_to_jira,
+            start_reestimation_campaign,
+            write_back_campaign,
+            end_reestimation_campaign,
+            set_reveal_rules,
+            lock_room,
+            transfer_ticket,
+            unlock_room,
+            set_discussion_phase,
+            import_session_plan,
+            next_ticket,
+            set_ticket_deadline,
+            requeue_parked_ticket,
+            set_room_branding,
+            set_room_logo,
+            clear_room_logo,
             set_jira_config,
             has_jira_config,
             fetch_jira_ticket,
             clear_current_ticket,
+            split_ticket,
+            create_jira_issue,
+            load_github_pull_requests,
             list_jira_projects,
             list_jira_boards,
             list_board_issues,
             has_stored_credentials,
             unlock_credentials,
+            unlock_room_storage,
+            set_room_encryption,
+            set_storage_backend,
             save_jira_credentials,
             logout_jira,
+            save_llm_credentials,
+            has_llm_credentials,
+            delete_llm_credentials,
+            save_proxy_password,
+            save_github_token,
+            save_sms_auth_token,
+            save_relay_token,
             get_public_ip,
             get_network_info,
             open_firewall_port,
             open_upnp_port,
+            get_port_forward_instructions,
             get_share_url,
             connect_relay,
             disconnect_relay,
             is_relay_connected,
             get_relay_url,
+            get_relay_share_url,
+            get_dashboard,
+            get_admin_url,
+            get_controller_url,
+            check_data_integrity,
+            list_profiles,
+            get_active_profile,
+            select_profile,
+            get_settings,
+            get_usage,
+            check_for_updates,
+            save_settings,
+            get_recovery_info,
+            recover_last_session,
+            discard_recovery,
+            get_onboarding_state,
+            test_server_started,
+            test_network_reachable,
+            test_jira_connection,
+            test_relay_connection,
+            skip_onboarding_step,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Only a crash should leave a recovery file behind
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = recovery::discard() {
+                    tracing::warn!("{}", e);
+                }
+            }
+        });
+}
+
+/// Load the active profile's settings and rooms
+fn load_profile(state: &AppState) {
+    match settings::load_settings() {
+        Ok(settings) => state.set_settings(settings),
+        Err(e) => tracing::warn!("Failed to load settings, using defaults: {}", e),
+    }
+    match accuracy::load_actuals() {
+        Ok(actuals) => {
+            for actual in actuals {
+                state.ticket_actuals.insert(actual.ticket_key.clone(), actual);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load ticket actuals: {}", e),
+    }
+    match ballots::load_ballots() {
+        Ok(loaded) => {
+            for ballot in loaded {
+                state.async_ballots.insert(ballot.token.clone(), ballot);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load ballots: {}", e),
+    }
+    if state.get_settings().encrypt_rooms {
+        tracing::info!("Room storage is encrypted; rooms are restored once the master password is entered");
+    } else if let Err(e) = restore_rooms(state, None) {
+        tracing::error!("Room persistence unavailable: {}", e);
+    }
+}
+
+/// Recover rooms persisted by a previous run and start persisting changes.
+/// Rooms created while the storage was still locked are kept.
+fn restore_rooms(state: &AppState, key: Option<credentials::MasterKey>) -> Result<(), String> {
+    let backend = state.get_settings().storage_backend;
+    let rooms = backend.load(key.as_ref())?;
+    tracing::info!("Recovered {} rooms from {:?} storage", rooms.len(), backend);
+    state.restore_rooms(rooms, backend.is_shared());
+
+    let storage = backend.open(state.get_rooms(), key.clone())?;
+    state.set_storage(storage, key);
+    Ok(())
+}
+
+/// Derive the storage key, checking the password against the stored
+/// credentials when there are any
+fn derive_master_key(password: &str) -> Result<credentials::MasterKey, String> {
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(password)?;
+    }
+    credentials::MasterKey::derive(password)
 }
 
 #[tauri::command]
 async fn create_room(
     state: tauri::State<'_, Arc<AppState>>,
     name: String,
+    deck: Option<decks::DeckDefinition>,
+    host_name: Option<String>,
 ) -> Result<room::Room, String> {
-    let room = state.create_room(name);
-    
-    // If relay is connected, sync the room
-    if let Some(relay_client) = state.get_relay_client().await {
-        if let Err(e) = relay_client.sync_room(room.clone()) {
+    host_actions::create_room(state.inner(), name, deck, host_name).await
+}
+
+/// Cast the host's vote, in a room created with the host taking part
+#[tauri::command]
+async fn host_vote(state: tauri::State<'_, Arc<AppState>>, room_id: String, vote: String) -> Result<(), String> {
+    state.set_host_vote(&room_id, Some(vote)).map_err(|e| e.message)?;
+    host_vote_changed(&state, &room_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn host_clear_vote(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
+    state.set_host_vote(&room_id, None).map_err(|e| e.message)?;
+    host_vote_changed(&state, &room_id).await;
+    Ok(())
+}
+
+/// Pass a change to the host's vote on the way participants' votes go, and to the relay
+async fn host_vote_changed(state: &AppState, room_id: &str) {
+    state.auto_reveal_if_ready(room_id).await;
+    state.broadcast_room_update(room_id).await;
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
             tracing::warn!("Failed to sync room to relay: {}", e);
         }
     }
-    
-    Ok(room)
 }
 
 #[tauri::command]
@@ -110,8 +339,10 @@ async fn get_room(
 async fn delete_room(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<bool, String> {
     let deleted = state.delete_room(&room_id);
     
-    // If relay is connected, notify about deletion
     if deleted {
+        branding::delete_logo(&room_id);
+
+        // If relay is connected, notify about deletion
         if let Some(relay_client) = state.get_relay_client().await {
             if let Err(e) = relay_client.delete_room(room_id) {
                 tracing::warn!("Failed to delete room from relay: {}", e);
@@ -124,40 +355,99 @@ async fn delete_room(state: tauri::State<'_, Arc<AppState>>, room_id: String) ->
 
 #[tauri::command]
 async fn reveal_votes(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
-    state.set_votes_revealed(&room_id, true);
-    state.broadcast_room_update(&room_id).await;
-    
-    // Notify relay
-    if let Some(relay_client) = state.get_relay_client().await {
-        let _ = relay_client.reveal_votes(room_id);
+    host_actions::reveal_votes(state.inner(), &room_id).await
+}
+
+/// Show the next card of a one-by-one reveal
+#[tauri::command]
+async fn reveal_next_vote(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
+    if !state.reveal_next_vote(&room_id).await {
+        return Err("No reveal in progress".into());
     }
     Ok(())
 }
 
 #[tauri::command]
-async fn hide_votes(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
-    state.set_votes_revealed(&room_id, false);
+async fn set_reveal_mode(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    mode: room::RevealMode,
+) -> Result<(), String> {
+    state.set_reveal_mode(&room_id, mode)?;
     state.broadcast_room_update(&room_id).await;
-    
-    // Notify relay
-    if let Some(relay_client) = state.get_relay_client().await {
-        let _ = relay_client.hide_votes(room_id);
-    }
     Ok(())
 }
 
+#[tauri::command]
+async fn hide_votes(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
+    host_actions::hide_votes(state.inner(), &room_id).await
+}
+
 #[tauri::command]
 async fn reset_votes(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
-    state.reset_votes(&room_id);
+    host_actions::reset_votes(state.inner(), &room_id).await
+}
+
+/// Tag the room's revealed round ("needs spike", "split", "accepted", ...).
+/// With `label_in_jira`, the tag is also added as a label on the round's ticket.
+#[tauri::command]
+async fn tag_round(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    tag: String,
+    label_in_jira: Option<bool>,
+) -> Result<(), String> {
+    let ticket_key = state.tag_round(&room_id, &tag)?;
     state.broadcast_room_update(&room_id).await;
-    
-    // Notify relay
-    if let Some(relay_client) = state.get_relay_client().await {
-        let _ = relay_client.reset_votes(room_id);
+
+    let Some(ticket_key) = ticket_key.filter(|_| label_in_jira.unwrap_or(false)) else {
+        return Ok(());
+    };
+    if !state.has_jira_config() {
+        return Err("The round was tagged, but Jira is not configured.".into());
     }
+    let client = state.http_client()?;
+    add_issue_label(&client, &state.get_jira_config(), &ticket_key, &tag)
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+    tracing::info!("Labelled {} with {:?}", ticket_key, tag.trim());
+    Ok(())
+}
+
+/// Rounds voted in a room so far, with their votes and final estimates
+#[tauri::command]
+async fn get_room_history(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Vec<room::VotingRound>, String> {
+    state.get_voting_rounds(&room_id).ok_or_else(|| "Room not found".into())
+}
+
+/// Set the revealed round's estimate by the host's call, outside the team's
+/// consensus. The reason is required and kept in the session history.
+#[tauri::command]
+async fn override_estimate(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    value: String,
+    reason: String,
+) -> Result<(), String> {
+    state.override_estimate(&room_id, &value, &reason)?;
+    tracing::info!("Estimate in room {} overridden to {}", room_id, value.trim());
+    state.broadcast_room_update(&room_id).await;
     Ok(())
 }
 
+/// Run one of the common host actions on a room, for keyboard shortcuts
+#[tauri::command]
+async fn host_action(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    action: HostAction,
+) -> Result<(), String> {
+    host_actions::perform(state.inner(), &room_id, action).await
+}
+
 #[tauri::command]
 async fn get_server_url(state: tauri::State<'_, Arc<AppState>>) -> Result<String, String> {
     Ok(state.get_server_url())
@@ -168,6 +458,18 @@ async fn get_share_url(state: tauri::State<'_, Arc<AppState>>) -> Result<String,
     Ok(state.get_share_url())
 }
 
+/// How long participants whose connection dropped are kept before being removed
+#[tauri::command]
+async fn set_disconnect_grace(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    secs: u64,
+) -> Result<(), String> {
+    state.set_disconnect_grace(&room_id, secs)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
 #[tauri::command]
 async fn kick_participant(
     state: tauri::State<'_, Arc<AppState>>,
@@ -184,214 +486,1800 @@ async fn kick_participant(
     Ok(())
 }
 
+/// Mark a participant as sitting out (or back in), leaving them out of the quorum
 #[tauri::command]
-async fn set_jira_config(
+async fn set_participant_sitting_out(
     state: tauri::State<'_, Arc<AppState>>,
-    base_url: String,
-    email: String,
-    api_token: String,
+    room_id: String,
+    participant_id: String,
+    sitting_out: bool,
 ) -> Result<(), String> {
-    state.set_jira_config(base_url, email, api_token);
+    state
+        .set_sitting_out(&room_id, &participant_id, sitting_out)
+        .map_err(|e| e.message)?;
+    state.auto_reveal_if_ready(&room_id).await;
+    state.broadcast_room_update(&room_id).await;
+
+    if let Some(relay_client) = state.get_relay_client().await {
+        let _ = relay_client.set_sitting_out(room_id, participant_id, sitting_out);
+    }
     Ok(())
 }
 
 #[tauri::command]
-async fn has_jira_config(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
-    Ok(state.has_jira_config())
+async fn get_participant_stats(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Vec<room::ParticipantStatsSummary>, String> {
+    state
+        .get_participant_stats(&room_id)
+        .ok_or_else(|| "Room not found".to_string())
 }
 
-/// Jira API response structures
-#[derive(Debug, Deserialize)]
-struct JiraIssueResponse {
-    key: String,
-    fields: JiraFields,
+/// How estimates compared with the actual effort logged in Jira, per issue type
+#[tauri::command]
+async fn get_estimation_accuracy(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<accuracy::IssueTypeAccuracy>, String> {
+    Ok(state.estimation_accuracy())
 }
 
-#[derive(Debug, Deserialize)]
-struct JiraFields {
-    summary: String,
-    description: Option<JiraDescriptionValue>,
-    issuetype: Option<JiraIssueType>,
-    status: Option<JiraStatus>,
-}
+/// Write the session recap as an unsent email (.eml) addressed to the configured
+/// distribution list, returning the file path so the UI can open it in the mail client
+#[tauri::command]
+async fn generate_session_recap(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    directory: Option<String>,
+) -> Result<String, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    let recipients = state.get_settings().recap_recipients;
 
-/// Description can be either a plain string or ADF (Atlassian Document Format)
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
-enum JiraDescriptionValue {
-    PlainString(String),
-    Adf(JiraDescription),
-}
+    let dir = match directory {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => credentials::get_data_dir()?.join("recaps"),
+    };
 
-#[derive(Debug, Deserialize)]
-struct JiraDescription {
-    content: Option<Vec<JiraContentNode>>,
+    let path = recap::write_recap_email(&room, &recipients, &dir)?;
+    Ok(path.display().to_string())
 }
 
-/// ADF content node - can contain text or nested content
-#[derive(Debug, Deserialize)]
-struct JiraContentNode {
-    #[serde(rename = "type")]
-    node_type: Option<String>,
-    text: Option<String>,
-    content: Option<Vec<JiraContentNode>>,
+/// Summarize the room's last reveal as Markdown, Jira wiki markup or plain text
+/// for the host to copy
+#[tauri::command]
+async fn format_round_summary(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    format: Option<recap::SummaryFormat>,
+) -> Result<String, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    recap::format_round_summary(&room, format.unwrap_or_default())
 }
 
-impl JiraContentNode {
-    /// Recursively extract all text from this node and its children
-    fn extract_text(&self) -> String {
-        let mut result = String::new();
-        
-        // If this node has direct text, add it
-        if let Some(ref text) = self.text {
-            result.push_str(text);
-        }
-        
-        // Recursively process child content
-        if let Some(ref children) = self.content {
-            for child in children {
-                result.push_str(&child.extract_text());
-            }
-        }
-        
-        // Add newline after paragraph nodes
-        if let Some(ref node_type) = self.node_type {
-            if node_type == "paragraph" || node_type == "heading" {
-                result.push('\n');
-            }
-        }
-        
-        result
+/// Collect estimates without a live session: issue each voter a personal voting
+/// link for the room's current and queued tickets, and write a digest (a CSV of
+/// the links plus a draft email per voter with an address). Returns the CSV path.
+#[tauri::command]
+async fn issue_async_ballots(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    voters: Vec<ballots::AsyncVoter>,
+    directory: Option<String>,
+) -> Result<String, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    let base_url = state.get_share_url();
+    if base_url.is_empty() {
+        return Err("Server is not running yet".into());
     }
-}
+    let issued = state.issue_async_ballots(&room_id, &voters)?;
+    ballots::save_ballots(&state.all_ballots())?;
 
-#[derive(Debug, Deserialize)]
-struct JiraIssueType {
-    name: String,
+    let dir = match directory {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => credentials::get_data_dir()?.join("ballots"),
+    };
+    let path = ballots::write_digest(&room, &issued, &base_url, &dir)?;
+    Ok(path.display().to_string())
 }
 
-#[derive(Debug, Deserialize)]
-struct JiraStatus {
-    name: String,
+/// Async votes collected so far for a room, per ticket
+#[tauri::command]
+async fn get_async_results(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Vec<ballots::AsyncTicketResult>, String> {
+    Ok(state.async_results(&room_id))
 }
 
+/// Draft a plain-language summary of the session with the configured language
+/// model, for pasting into the sprint doc
 #[tauri::command]
-async fn fetch_jira_ticket(
+async fn draft_session_summary(
     state: tauri::State<'_, Arc<AppState>>,
     room_id: String,
-    ticket_key: String,
-) -> Result<JiraTicket, String> {
-    let config = state.get_jira_config();
-    
-    if config.base_url.is_empty() || config.email.is_empty() || config.api_token.is_empty() {
-        return Err("Jira is not configured. Please set up Jira credentials first.".into());
-    }
-
-    let url = format!("{}/rest/api/3/issue/{}", config.base_url, ticket_key);
-    let auth = format!("{}:{}", config.email, config.api_token);
-    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", auth_header)
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch ticket: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Jira API error ({}): {}", status, body));
-    }
+) -> Result<String, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    let llm = state
+        .llm_credentials()
+        .ok_or("No language model configured; add one in the settings first")?;
+    copilot::draft_session_summary(&state.http_client()?, &llm, &room).await
+}
 
-    let issue: JiraIssueResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Jira response: {}", e))?;
+/// Find sessions by ticket key, room name or participant, e.g. to look up when a
+/// ticket was estimated and what everyone voted
+#[tauri::command]
+async fn search_sessions(
+    state: tauri::State<'_, Arc<AppState>>,
+    query: String,
+    date_range: Option<session_search::DateRange>,
+) -> Result<Vec<session_search::SessionResult>, String> {
+    Ok(state.search_sessions(&query, date_range.unwrap_or_default()))
+}
 
-    // Extract full description text - handles both plain string and ADF format
-    let description = issue.fields.description.and_then(|d| match d {
-        JiraDescriptionValue::PlainString(s) => Some(s),
-        JiraDescriptionValue::Adf(adf) => {
-            adf.content.map(|contents| {
-                contents.iter()
-                    .map(|node| node.extract_text())
-                    .collect::<String>()
-                    .trim()
-                    .to_string()
-            }).filter(|s| !s.is_empty())
+/// End a room's session: freeze voting, tell participants, and export the summary
+/// to `sessions/` in the data directory. A recap email is drafted when a
+/// distribution list is configured.
+#[tauri::command]
+async fn end_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<room::SessionSummary, String> {
+    let summary = state.end_session(&room_id).ok_or("Room not found")?;
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    let data_dir = credentials::get_data_dir()?;
+
+    let sessions_dir = data_dir.join("sessions");
+    std::fs::create_dir_all(&sessions_dir)
+        .map_err(|e| format!("Failed to create sessions dir: {}", e))?;
+    let json = serde_json::to_string_pretty(&summary)
+        .map_err(|e| format!("Failed to serialize session summary: {}", e))?;
+    std::fs::write(sessions_dir.join(format!("{}_{}.json", room_id, summary.ended_at)), json)
+        .map_err(|e| format!("Failed to write session summary: {}", e))?;
+
+    let recipients = state.get_settings().recap_recipients;
+    if !recipients.is_empty() {
+        if let Err(e) = recap::write_recap_email(&room, &recipients, &data_dir.join("recaps")) {
+            tracing::warn!("Failed to write session recap: {}", e);
         }
-    });
-
-    let ticket = JiraTicket {
-        key: issue.key.clone(),
-        summary: issue.fields.summary,
-        description,
-        issue_type: issue.fields.issuetype.map(|t| t.name),
-        status: issue.fields.status.map(|s| s.name),
-        url: format!("{}/browse/{}", config.base_url, issue.key),
-    };
+    }
 
-    // Update the room with the ticket
-    state.set_current_ticket(&room_id, Some(ticket.clone()));
-    state.broadcast_room_update(&room_id).await;
-    
-    // Notify relay
     if let Some(relay_client) = state.get_relay_client().await {
-        let _ = relay_client.set_ticket(room_id, ticket.clone());
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync ended room to relay: {}", e);
+        }
     }
 
-    Ok(ticket)
+    Ok(summary)
 }
 
+/// Restrict a room to clients on the local network
 #[tauri::command]
-async fn clear_current_ticket(
+async fn set_room_lan_only(
     state: tauri::State<'_, Arc<AppState>>,
     room_id: String,
+    lan_only: bool,
 ) -> Result<(), String> {
-    state.set_current_ticket(&room_id, None);
-    state.broadcast_room_update(&room_id).await;
-    
-    // Notify relay
-    if let Some(relay_client) = state.get_relay_client().await {
-        let _ = relay_client.clear_ticket(room_id);
+    if !state.set_room_lan_only(&room_id, lan_only) {
+        return Err("Room not found".into());
     }
+    state.broadcast_room_update(&room_id).await;
     Ok(())
 }
 
-// ============ Jira Project/Board Browsing ============
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JiraProject {
-    pub id: String,
-    pub key: String,
-    pub name: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct JiraProjectResponse {
-    id: String,
-    key: String,
-    name: String,
+/// Stop new participants joining a room, e.g. once estimation has started
+#[tauri::command]
+async fn lock_room(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<(), String> {
+    set_room_locked(&state, room_id, true).await
 }
 
 #[tauri::command]
-async fn list_jira_projects(
+async fn unlock_room(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<JiraProject>, String> {
-    let config = state.get_jira_config();
-    
-    if !state.has_jira_config() {
-        return Err("Jira is not configured.".into());
-    }
+    room_id: String,
+) -> Result<(), String> {
+    set_room_locked(&state, room_id, false).await
+}
+
+async fn set_room_locked(state: &AppState, room_id: String, locked: bool) -> Result<(), String> {
+    if !state.set_room_locked(&room_id, locked) {
+        return Err("Room not found".into());
+    }
+    // The relay turns away its own joiners, so it needs to know too
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Start a discussion phase, when nobody can vote, or end it and open voting
+/// after a countdown of `countdown_secs` (none opens it straight away)
+#[tauri::command]
+async fn set_discussion_phase(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    discussing: bool,
+    countdown_secs: Option<u32>,
+) -> Result<(), String> {
+    state.set_discussion_phase(&room_id, discussing, countdown_secs.unwrap_or(0))?;
+    // The relay turns away its own participants' votes, so it needs to know too
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Announce when voting starts (Unix seconds), so early joiners see a countdown;
+/// None clears it
+#[tauri::command]
+async fn set_scheduled_start(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    starts_at: Option<u64>,
+) -> Result<(), String> {
+    state.set_scheduled_start(&room_id, starts_at)?;
+    // People joining through the relay see the countdown too
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Show a room's times in the given time zone (IANA name)
+#[tauri::command]
+async fn set_room_time_zone(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    name: String,
+) -> Result<(), String> {
+    state.set_room_time_zone(&room_id, timezone::RoomTimeZone::new(&name)?)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Hide who has voted from participants until everyone has or the votes are revealed
+#[tauri::command]
+async fn set_hide_voters(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    hidden: bool,
+) -> Result<(), String> {
+    if !state.set_room_hide_voters(&room_id, hidden) {
+        return Err("Room not found".into());
+    }
+    // The relay hides them from its own participants
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Turn the second (risk/uncertainty) vote on or off for a room
+#[tauri::command]
+async fn set_risk_voting(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    if !state.set_room_risk_voting(&room_id, enabled) {
+        return Err("Room not found".into());
+    }
+    // Relay participants need the risk deck too
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Offer the abstain card in a room's deck, or take it out
+#[tauri::command]
+async fn set_abstain_offered(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    offered: bool,
+) -> Result<(), String> {
+    if !state.set_room_abstain_offered(&room_id, offered) {
+        return Err("Room not found".into());
+    }
+    // Relay participants vote from the room's deck as well
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Go back to the room's own deck when it was switched for the ticket's issue type
+#[tauri::command]
+async fn revert_deck_switch(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<(), String> {
+    state.revert_deck_switch(&room_id)?;
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Track how much of the sprint the session's estimates fill, or stop tracking it
+#[tauri::command]
+async fn set_sprint_capacity(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    points: Option<f64>,
+) -> Result<(), String> {
+    if let Some(points) = points {
+        capacity::validate_capacity(points)?;
+    }
+    if !state.set_room_sprint_capacity(&room_id, points) {
+        return Err("Room not found".into());
+    }
+    state.broadcast_room_update(&room_id).await;
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Turn verbose logging of one participant's connection on or off
+#[tauri::command]
+async fn trace_participant(
+    state: tauri::State<'_, Arc<AppState>>,
+    participant_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.trace_participant(&participant_id, enabled);
+    tracing::info!(participant = %participant_id, enabled, "Participant tracing toggled");
+    Ok(())
+}
+
+/// Configure when a room's votes may be revealed, and whether that happens automatically
+#[tauri::command]
+async fn set_reveal_rules(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    auto_reveal: bool,
+    quorum: room::QuorumRules,
+) -> Result<(), String> {
+    state.set_reveal_rules(&room_id, auto_reveal, quorum)?;
+    // The room may already meet the new rules
+    state.auto_reveal_if_ready(&room_id).await;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Choose the formula used for a room's suggested estimate
+#[tauri::command]
+async fn set_estimation_method(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    method: estimation::EstimationMethod,
+) -> Result<(), String> {
+    if !state.set_room_estimation_method(&room_id, method) {
+        return Err("Room not found".into());
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Set a room's accent color and title (pass None to clear)
+#[tauri::command]
+async fn set_room_branding(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    accent_color: Option<String>,
+    title: Option<String>,
+) -> Result<(), String> {
+    let accent_color = accent_color.filter(|c| !c.trim().is_empty());
+    if let Some(color) = &accent_color {
+        branding::validate_accent_color(color)?;
+    }
+    let title = match title.filter(|t| !t.trim().is_empty()) {
+        Some(title) => Some(moderation::validate_text("title", "Title", &title, moderation::MAX_ROOM_NAME_LENGTH)?),
+        None => None,
+    };
+
+    let updated = state.update_room_branding(&room_id, |branding| {
+        branding.accent_color = accent_color;
+        branding.title = title;
+    });
+    if !updated {
+        return Err("Room not found".into());
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Upload a room logo (base64-encoded image data)
+#[tauri::command]
+async fn set_room_logo(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    content_type: String,
+    data: String,
+) -> Result<String, String> {
+    if state.get_room(&room_id).is_none() {
+        return Err("Room not found".into());
+    }
+
+    let bytes = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid logo data: {}", e))?;
+    let logo_url = branding::save_logo(&room_id, &content_type, &bytes)?;
+
+    state.update_room_branding(&room_id, |branding| {
+        branding.logo_url = Some(logo_url.clone());
+    });
+    state.broadcast_room_update(&room_id).await;
+    Ok(logo_url)
+}
+
+#[tauri::command]
+async fn clear_room_logo(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<(), String> {
+    branding::delete_logo(&room_id);
+    if state.update_room_branding(&room_id, |branding| branding.logo_url = None) {
+        state.broadcast_room_update(&room_id).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_jira_config(
+    state: tauri::State<'_, Arc<AppState>>,
+    base_url: String,
+    email: String,
+    api_token: String,
+) -> Result<(), String> {
+    state.set_jira_config(base_url, email, api_token);
+    retry_pending_jira_requests(state.inner().clone());
+    Ok(())
+}
+
+#[tauri::command]
+async fn has_jira_config(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.has_jira_config())
+}
+
+/// Jira API response structures
+#[derive(Debug, Deserialize)]
+struct JiraIssueResponse {
+    key: String,
+    fields: JiraFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraFields {
+    summary: String,
+    description: Option<JiraDescriptionValue>,
+    issuetype: Option<JiraIssueType>,
+    status: Option<JiraStatus>,
+    #[serde(default)]
+    issuelinks: Vec<serde_json::Value>,
+}
+
+/// Description can be either a plain string or ADF (Atlassian Document Format)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JiraDescriptionValue {
+    PlainString(String),
+    Adf(adf::AdfDocument),
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraIssueType {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraStatus {
+    name: String,
+}
+
+#[tauri::command]
+async fn fetch_jira_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    ticket_key: String,
+) -> Result<JiraTicket, String> {
+    load_current_ticket(&state, room_id, ticket_key).await
+}
+
+/// Fetch a ticket from Jira and make it the room's current ticket
+async fn load_current_ticket(state: &AppState, room_id: String, ticket_key: String) -> Result<JiraTicket, String> {
+    let config = state.get_jira_config();
+    
+    if config.base_url.is_empty() || config.email.is_empty() || config.api_token.is_empty() {
+        return Err("Jira is not configured. Please set up Jira credentials first.".into());
+    }
+    let retry = || PendingJiraRequest::FetchTicket { room_id: room_id.clone(), ticket_key: ticket_key.clone() };
+    if config.rejected {
+        return Err(jira_failure(state, JIRA_AUTH_FAILED.into(), Some(retry())));
+    }
+
+    let client = state.http_client()?;
+    let ticket = fetch_ticket(&client, &config, &state.jira_user_names, &ticket_key)
+        .await
+        .map_err(|e| jira_failure(state, e, Some(retry())))?;
+
+    host_actions::set_ticket(state, &room_id, Some(ticket.clone())).await?;
+    Ok(ticket)
+}
+
+/// Shown instead of Jira's own response body when it rejects the credentials
+const JIRA_AUTH_FAILED: &str = "Jira rejected the credentials. Unlock or re-enter them to continue.";
+
+/// Error for an unsuccessful Jira response. A 401 or 403 always gives
+/// `JIRA_AUTH_FAILED`, which `jira_failure` looks for.
+async fn jira_error(response: reqwest::Response) -> String {
+    let status = response.status();
+    if matches!(status, reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) {
+        return JIRA_AUTH_FAILED.into();
+    }
+    let body = response.text().await.unwrap_or_default();
+    format!("Jira API error ({}): {}", status, body)
+}
+
+/// Pass on the error from a Jira call. If the credentials were rejected they're
+/// marked invalid, which prompts the host to re-enter them, and `retry` runs
+/// once they have been.
+fn jira_failure(state: &AppState, error: String, retry: Option<PendingJiraRequest>) -> String {
+    if error == JIRA_AUTH_FAILED {
+        state.reject_jira_config(retry);
+    }
+    error
+}
+
+/// Retry the Jira requests that failed while the credentials were rejected
+fn retry_pending_jira_requests(state: Arc<AppState>) {
+    let pending = state.take_pending_jira_requests();
+    if pending.is_empty() {
+        return;
+    }
+    tracing::info!("Retrying {} Jira request(s) with the new credentials", pending.len());
+    tokio::spawn(async move {
+        for request in pending {
+            match request {
+                PendingJiraRequest::FetchTicket { room_id, ticket_key } => {
+                    if let Err(e) = load_current_ticket(&state, room_id, ticket_key.clone()).await {
+                        tracing::warn!("Failed to load {} again: {}", ticket_key, e);
+                    }
+                }
+                PendingJiraRequest::Prefetch { room_id } => prefetch_queued_tickets(state.clone(), room_id),
+                PendingJiraRequest::EstimateRule(hit) => state.estimate_rule_hits.lock().unwrap().push(hit),
+            }
+        }
+    });
+}
+
+/// Fetch a single issue from Jira
+#[tracing::instrument(name = "jira", skip(client, config, user_names))]
+async fn fetch_ticket(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    user_names: &DashMap<String, String>,
+    ticket_key: &str,
+) -> Result<JiraTicket, String> {
+    let url = format!("{}/rest/api/3/issue/{}", config.base_url, ticket_key);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+
+    let response = client
+        .get(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch ticket: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+
+    let issue: JiraIssueResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Jira response: {}", e))?;
+    resolve_mentions(client, config, user_names, std::slice::from_ref(&issue)).await;
+    Ok(ticket_from_issue(issue, &config.base_url, user_names))
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraUser {
+    #[serde(rename = "displayName")]
+    display_name: String,
+}
+
+/// Look up the display names of users mentioned in the issues' descriptions
+/// that aren't in `user_names` yet. Lookups that fail are left out, and the
+/// mention shows the text Jira stored with it instead.
+async fn resolve_mentions(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    user_names: &DashMap<String, String>,
+    issues: &[JiraIssueResponse],
+) {
+    let mut unknown: Vec<String> = issues
+        .iter()
+        .filter_map(|issue| match &issue.fields.description {
+            Some(JiraDescriptionValue::Adf(adf)) => Some(adf.mentioned_users()),
+            _ => None,
+        })
+        .flatten()
+        .filter(|id| !user_names.contains_key(id))
+        .collect();
+    unknown.sort();
+    unknown.dedup();
+
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+    let url = format!("{}/rest/api/3/user", config.base_url);
+    let lookups = futures_util::stream::iter(unknown)
+        .map(|account_id| {
+            let request = client
+                .get(&url)
+                .query(&[("accountId", account_id.as_str())])
+                .header("Authorization", &auth_header)
+                .header("Accept", "application/json");
+            async move {
+                let user: JiraUser = request.send().await.ok()?.error_for_status().ok()?.json().await.ok()?;
+                Some((account_id, user.display_name))
+            }
+        })
+        .buffer_unordered(TICKET_FETCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for (account_id, name) in lookups.into_iter().flatten() {
+        user_names.insert(account_id, name);
+    }
+}
+
+/// Turn an issue from the Jira API into a votable ticket
+fn ticket_from_issue(issue: JiraIssueResponse, base_url: &str, user_names: &DashMap<String, String>) -> JiraTicket {
+    // Extract full description text - handles both plain string and ADF format
+    let description = issue.fields.description.and_then(|d| match d {
+        JiraDescriptionValue::PlainString(s) => Some(s),
+        JiraDescriptionValue::Adf(adf) => adf.to_text(|id| user_names.get(id).map(|name| name.clone())),
+    });
+
+    let complexity = analysis::analyze_ticket(description.as_deref(), issue.fields.issuelinks.len());
+    JiraTicket {
+        key: issue.key.clone(),
+        summary: issue.fields.summary,
+        description,
+        issue_type: issue.fields.issuetype.map(|t| t.name),
+        status: issue.fields.status.map(|s| s.name),
+        url: format!("{}/browse/{}", base_url, issue.key),
+        complexity: Some(complexity),
+        diff_stats: None,
+    }
+}
+
+/// Issues matching a JQL query, each with the number in `points_field` (a field ID)
+#[tracing::instrument(name = "jira", skip(client, config, user_names))]
+async fn search_issues(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    user_names: &DashMap<String, String>,
+    jql: &str,
+    points_field: &str,
+) -> Result<Vec<(JiraTicket, Option<f64>)>, String> {
+    #[derive(Deserialize)]
+    struct SearchResponse {
+        issues: Vec<serde_json::Value>,
+    }
+
+    let url = format!("{}/rest/api/3/search", config.base_url);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+    let fields = format!("summary,description,issuetype,status,issuelinks,{}", points_field);
+    let max_results = campaign::MAX_CAMPAIGN_ISSUES.to_string();
+
+    let response = client
+        .get(&url)
+        .query(&[("jql", jql), ("fields", fields.as_str()), ("maxResults", max_results.as_str())])
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search issues: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+
+    let found: SearchResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse issues: {}", e))?;
+    let (issues, points): (Vec<JiraIssueResponse>, Vec<Option<f64>>) = found
+        .issues
+        .into_iter()
+        .map(|issue| {
+            let points = issue["fields"][points_field].as_f64();
+            let issue = serde_json::from_value(issue).map_err(|e| format!("Failed to parse issue: {}", e))?;
+            Ok::<_, String>((issue, points))
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    resolve_mentions(client, config, user_names, &issues).await;
+    Ok(issues
+        .into_iter()
+        .map(|issue| ticket_from_issue(issue, &config.base_url, user_names))
+        .zip(points)
+        .collect())
+}
+
+/// Fetch several issues at once, at most `TICKET_FETCH_CONCURRENCY` in flight.
+/// Results come back in the same order as `keys`.
+async fn fetch_tickets(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    user_names: &DashMap<String, String>,
+    keys: Vec<String>,
+) -> Vec<(String, Result<JiraTicket, String>)> {
+    futures_util::stream::iter(keys)
+        .map(|key| async move {
+            let result = fetch_ticket(client, config, user_names, &key).await;
+            (key, result)
+        })
+        .buffered(TICKET_FETCH_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Queued tickets whose details are fetched ahead of time
+const PREFETCH_AHEAD: usize = 3;
+/// Maximum concurrent Jira requests when fetching several tickets
+const TICKET_FETCH_CONCURRENCY: usize = 3;
+
+/// Refresh the next few queued tickets in the background, so advancing to
+/// the next ticket shows up-to-date details without waiting on Jira
+fn prefetch_queued_tickets(state: Arc<AppState>, room_id: String) {
+    if state.get_jira_config().rejected {
+        state.reject_jira_config(Some(PendingJiraRequest::Prefetch { room_id }));
+        return;
+    }
+    if !state.has_jira_config() {
+        return;
+    }
+    let keys = state.tickets_to_prefetch(&room_id, PREFETCH_AHEAD);
+    if keys.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let config = state.get_jira_config();
+        let client = match state.http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Failed to prefetch queued tickets: {}", e);
+                return;
+            }
+        };
+        for (key, result) in fetch_tickets(&client, &config, &state.jira_user_names, keys).await {
+            match result {
+                Ok(ticket) => state.update_queued_ticket(&room_id, ticket),
+                Err(e) => {
+                    let e = jira_failure(&state, e, Some(PendingJiraRequest::Prefetch { room_id: room_id.clone() }));
+                    tracing::warn!("Failed to prefetch {}: {}", key, e);
+                }
+            }
+        }
+    });
+}
+
+/// Issue fields needed to tell how long a completed ticket took
+#[derive(Debug, Deserialize)]
+struct JiraActualsResponse {
+    fields: JiraActualsFields,
+    changelog: Option<JiraChangelog>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraActualsFields {
+    issuetype: Option<JiraIssueType>,
+    status: Option<JiraCategorizedStatus>,
+    /// Seconds of work logged
+    timespent: Option<u64>,
+    resolutiondate: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraCategorizedStatus {
+    #[serde(rename = "statusCategory")]
+    status_category: Option<JiraStatusCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraStatusCategory {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraChangelog {
+    histories: Vec<JiraChangeHistory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraChangeHistory {
+    created: String,
+    items: Vec<JiraChangeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraChangeItem {
+    field: String,
+}
+
+/// How often estimated tickets are checked for their actual effort
+const ACTUALS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+/// Fetch how long a ticket actually took: logged work if there is any, otherwise
+/// the time from its first status change to its resolution. None while the
+/// ticket isn't done yet.
+#[tracing::instrument(name = "jira", skip(client, config))]
+async fn fetch_ticket_actual(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    ticket_key: &str,
+) -> Result<Option<accuracy::TicketActual>, String> {
+    let url = format!(
+        "{}/rest/api/3/issue/{}?fields=issuetype,status,timespent,resolutiondate&expand=changelog",
+        config.base_url, ticket_key
+    );
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+
+    let response = client
+        .get(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch ticket: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+
+    let issue: JiraActualsResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Jira response: {}", e))?;
+
+    let fields = issue.fields;
+    let done = fields
+        .status
+        .and_then(|status| status.status_category)
+        .is_some_and(|category| category.key == "done");
+    if !done {
+        return Ok(None);
+    }
+    let issue_type = fields.issuetype.map(|t| t.name);
+
+    if let Some(seconds) = fields.timespent.filter(|s| *s > 0) {
+        return Ok(Some(accuracy::TicketActual {
+            ticket_key: ticket_key.to_string(),
+            issue_type,
+            hours: seconds as f64 / 3600.0,
+            source: accuracy::ActualSource::LoggedWork,
+        }));
+    }
+
+    let started = issue
+        .changelog
+        .iter()
+        .flat_map(|changelog| &changelog.histories)
+        .filter(|history| history.items.iter().any(|item| item.field == "status"))
+        .filter_map(|history| accuracy::parse_jira_timestamp(&history.created))
+        .min();
+    let resolved = fields.resolutiondate.as_deref().and_then(accuracy::parse_jira_timestamp);
+    Ok(match (started, resolved) {
+        (Some(started), Some(resolved)) if resolved > started => Some(accuracy::TicketActual {
+            ticket_key: ticket_key.to_string(),
+            issue_type,
+            hours: (resolved - started) as f64 / 3600.0,
+            source: accuracy::ActualSource::TimeInProgress,
+        }),
+        _ => None,
+    })
+}
+
+/// Background job: pass on the changes other instances sharing the room storage
+/// make, to the participants connected here
+async fn apply_shared_changes(state: Arc<AppState>) {
+    let Some(mut changes) = state.take_shared_changes() else {
+        return;
+    };
+    while let Some(change) = changes.recv().await {
+        state.apply_shared_change(change);
+    }
+}
+
+/// Background job: for estimated tickets that have since been completed in Jira,
+/// fetch how long they actually took so estimates can be compared with reality
+async fn collect_ticket_actuals(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(ACTUALS_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if !state.has_jira_config() {
+            continue;
+        }
+        let keys = state.tickets_missing_actuals();
+        if keys.is_empty() {
+            continue;
+        }
+        let client = match state.http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Failed to check ticket actuals: {}", e);
+                continue;
+            }
+        };
+        let config = state.get_jira_config();
+
+        let results: Vec<_> = futures_util::stream::iter(keys)
+            .map(|key| {
+                let (client, config) = (&client, &config);
+                async move {
+                    let result = fetch_ticket_actual(client, config, &key).await;
+                    (key, result)
+                }
+            })
+            .buffered(TICKET_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+        let mut actuals = Vec::new();
+        for (key, result) in results {
+            match result {
+                Ok(Some(actual)) => actuals.push(actual),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to fetch actuals for {}: {}", key, jira_failure(&state, e, None)),
+            }
+        }
+        if actuals.is_empty() {
+            continue;
+        }
+        tracing::info!("Recorded actual effort for {} tickets", actuals.len());
+        if let Err(e) = state.record_ticket_actuals(actuals) {
+            tracing::warn!("Failed to save ticket actuals: {}", e);
+        }
+    }
+}
+
+/// How often estimate rules triggered by revealed rounds are carried out
+const ESTIMATE_RULE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Background job: label or flag tickets whose estimate triggered a rule
+async fn apply_estimate_rules(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(ESTIMATE_RULE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let hits = state.take_estimate_rule_hits();
+        if hits.is_empty() {
+            continue;
+        }
+        // Held until the credentials are re-entered
+        if state.get_jira_config().rejected {
+            for hit in hits {
+                state.reject_jira_config(Some(PendingJiraRequest::EstimateRule(hit)));
+            }
+            continue;
+        }
+        if !state.has_jira_config() {
+            tracing::warn!("Skipping {} estimate rule(s): Jira is not configured", hits.len());
+            continue;
+        }
+        let client = match state.http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Failed to apply estimate rules: {}", e);
+                continue;
+            }
+        };
+        let config = state.get_jira_config();
+
+        for hit in hits {
+            let result = match &hit.action {
+                estimate_rules::RuleAction::Label { label } => {
+                    add_issue_label(&client, &config, &hit.ticket_key, label).await
+                }
+                estimate_rules::RuleAction::Flag => flag_issue(&client, &config, &hit.ticket_key).await,
+            };
+            match result {
+                Ok(()) => tracing::info!(
+                    "Estimate of {} for {} in room {} triggered {:?}",
+                    hit.estimate, hit.ticket_key, hit.room_id, hit.action
+                ),
+                Err(e) => {
+                    let e = jira_failure(&state, e, Some(PendingJiraRequest::EstimateRule(hit.clone())));
+                    tracing::warn!("Failed to apply estimate rule to {}: {}", hit.ticket_key, e);
+                }
+            }
+        }
+    }
+}
+
+/// Set the rule applied to a room's large estimates, or clear it to fall back
+/// on the rule in the settings
+#[tauri::command]
+async fn set_estimate_rule(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    rule: Option<estimate_rules::EstimateRule>,
+) -> Result<(), String> {
+    state.set_room_estimate_rule(&room_id, rule)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Move on to the next queued ticket
+#[tauri::command]
+async fn next_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Option<JiraTicket>, String> {
+    host_actions::next_ticket(state.inner(), &room_id).await
+}
+
+/// Give a ticket a time limit in minutes (or lift it with None); if its votes
+/// aren't revealed in time it's parked and the room moves on
+#[tauri::command]
+async fn set_ticket_deadline(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    ticket_key: String,
+    minutes: Option<u32>,
+) -> Result<(), String> {
+    state.set_ticket_deadline(&room_id, &ticket_key, minutes)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Put a parked ticket back at the end of the queue
+#[tauri::command]
+async fn requeue_parked_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    ticket_key: String,
+) -> Result<(), String> {
+    state.requeue_parked_ticket(&room_id, &ticket_key)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// Triage mode: vote on several queued tickets at once, for obvious small ones
+#[tauri::command]
+async fn start_batch(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    ticket_keys: Vec<String>,
+) -> Result<(), String> {
+    state.start_batch(&room_id, &ticket_keys)?;
+    state.broadcast_room_update(&room_id).await;
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Write one estimate to every ticket of the room's revealed batch round.
+/// Returns the keys that were updated; tickets that failed are reported in the error.
+#[tauri::command]
+async fn push_batch_estimate(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    points: f64,
+    story_points_field: Option<String>,
+) -> Result<Vec<String>, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    if room.batch.is_empty() || !room.votes_revealed {
+        return Err("There's no revealed batch round to push".into());
+    }
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    let client = state.http_client()?;
+    let config = state.get_jira_config();
+    let field_name = story_points_field
+        .as_deref()
+        .filter(|f| !f.trim().is_empty())
+        .unwrap_or(DEFAULT_STORY_POINTS_FIELD);
+    let field_id = find_field_id(&client, &config, &[field_name])
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+
+    let mut updated = Vec::new();
+    let mut failures = Vec::new();
+    for ticket in &room.batch {
+        match set_issue_field(&client, &config, &ticket.key, &field_id, serde_json::json!(points)).await {
+            Ok(()) => updated.push(ticket.key.clone()),
+            Err(e) => failures.push(format!("{}: {}", ticket.key, jira_failure(&state, e, None))),
+        }
+    }
+    tracing::info!("Pushed {} points to {} batch tickets", points, updated.len());
+    if !failures.is_empty() {
+        return Err(format!("Updated {}, but some failed: {}", updated.join(", "), failures.join("; ")));
+    }
+    Ok(updated)
+}
+
+/// Write the agreed estimate of the room's last revealed round to its ticket's
+/// story points field, or `points` if given. Without a field name, the field is
+/// found under either of the names Jira uses for it. Returns the points written.
+#[tauri::command]
+async fn push_estimate_to_jira(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    points: Option<f64>,
+    story_points_field: Option<String>,
+) -> Result<f64, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    if !room.batch.is_empty() {
+        return Err("This is a batch round; push its estimate to every ticket in the batch instead".into());
+    }
+    let round = room
+        .voting_rounds()
+        .into_iter()
+        .rev()
+        .find(|round| round.revealed)
+        .ok_or("No votes have been revealed in this room yet")?;
+    let ticket_key = round.ticket_key.ok_or("The last round wasn't for a Jira ticket")?;
+    let points = match points {
+        Some(points) => points,
+        None => round
+            .final_estimate
+            .as_deref()
+            .and_then(|estimate| estimate.parse().ok())
+            .ok_or("The round has no numeric estimate; choose the points to write")?,
+    };
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    let client = state.http_client()?;
+    let config = state.get_jira_config();
+    let field_names = match story_points_field.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        Some(name) => vec![name],
+        None => vec![DEFAULT_STORY_POINTS_FIELD, TEAM_MANAGED_STORY_POINTS_FIELD],
+    };
+    let field_id = find_field_id(&client, &config, &field_names)
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+    set_issue_field(&client, &config, &ticket_key, &field_id, serde_json::json!(points))
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+    tracing::info!("Set {} to {} points", ticket_key, points);
+    Ok(points)
+}
+
+/// Re-estimation campaign: queue a project's estimated but not yet started
+/// issues, keeping their old estimates to compare with the new ones.
+/// Returns how many were queued.
+#[tauri::command]
+async fn start_reestimation_campaign(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    project_key: String,
+    story_points_field: Option<String>,
+) -> Result<usize, String> {
+    if state.get_room(&room_id).is_none() {
+        return Err("Room not found".into());
+    }
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    let client = state.http_client()?;
+    let config = state.get_jira_config();
+    let field_name = story_points_field
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .unwrap_or(DEFAULT_STORY_POINTS_FIELD)
+        .to_string();
+    let field_id = find_field_id(&client, &config, &[&field_name])
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+    let issues = search_issues(&client, &config, &state.jira_user_names, &campaign::backlog_jql(&project_key, &field_name), &field_id)
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+
+    let mut previous = std::collections::HashMap::new();
+    let mut tickets = Vec::new();
+    for (ticket, estimate) in issues {
+        if let Some(estimate) = estimate {
+            previous.insert(ticket.key.clone(), estimate);
+            tickets.push(ticket);
+        }
+    }
+    if tickets.is_empty() {
+        return Err(format!("{} has no estimated issues waiting to be started", project_key));
+    }
+    let count = tickets.len();
+    for ticket in &tickets {
+        state.prefetched_tickets.insert(ticket.key.clone());
+    }
+    state.start_campaign(&room_id, campaign::Campaign::new(field_name, previous), tickets)?;
+    tracing::info!("Re-estimating {} issues from {}", count, project_key);
+
+    state.broadcast_room_update(&room_id).await;
+    if let (Some(relay_client), Some(room)) = (state.get_relay_client().await, state.get_room(&room_id)) {
+        if let Err(e) = relay_client.sync_room(room) {
+            tracing::warn!("Failed to sync room to relay: {}", e);
+        }
+    }
+    Ok(count)
+}
+
+/// Write the campaign's changed estimates back to Jira. Returns the keys that
+/// were updated; tickets that failed are reported in the error and can be retried.
+#[tauri::command]
+async fn write_back_campaign(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Vec<String>, String> {
+    let room = state.get_room(&room_id).ok_or("Room not found")?;
+    let campaign = room.campaign.ok_or("This room isn't running a re-estimation campaign")?;
+    let changes = campaign.unwritten_changes();
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    let client = state.http_client()?;
+    let config = state.get_jira_config();
+    let field_id = find_field_id(&client, &config, &[&campaign.field])
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+
+    let mut updated = Vec::new();
+    let mut failures = Vec::new();
+    for change in changes {
+        match set_issue_field(&client, &config, &change.ticket_key, &field_id, serde_json::json!(change.estimate)).await {
+            Ok(()) => {
+                state.mark_campaign_written(&room_id, &change.ticket_key);
+                updated.push(change.ticket_key);
+            }
+            Err(e) => failures.push(format!("{}: {}", change.ticket_key, jira_failure(&state, e, None))),
+        }
+    }
+    tracing::info!("Wrote {} re-estimated values back to Jira", updated.len());
+    state.broadcast_room_update(&room_id).await;
+    if !failures.is_empty() {
+        return Err(format!("Updated {}, but some failed: {}", updated.join(", "), failures.join("; ")));
+    }
+    Ok(updated)
+}
+
+/// Stop a room's re-estimation campaign, dropping its results
+#[tauri::command]
+async fn end_reestimation_campaign(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<(), String> {
+    state.end_campaign(&room_id)?;
+    state.broadcast_room_update(&room_id).await;
+    Ok(())
+}
+
+/// A ticket from a session plan that couldn't be fetched
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedTicket {
+    pub key: String,
+    pub error: String,
+}
+
+/// Outcome of setting up one planned session
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedSession {
+    pub room: room::Room,
+    /// Keys queued for estimation, in plan order
+    pub queued: Vec<String>,
+    pub unresolved: Vec<UnresolvedTicket>,
+}
+
+/// Create a room per session in a plan file (CSV or JSON) and queue its tickets,
+/// reporting any keys Jira couldn't resolve
+#[tauri::command]
+async fn import_session_plan(
+    state: tauri::State<'_, Arc<AppState>>,
+    path: String,
+) -> Result<Vec<ImportedSession>, String> {
+    if !state.has_jira_config() {
+        return Err("Jira is not configured. Please set up Jira credentials first.".into());
+    }
+    let plan = session_plan::load_session_plan(std::path::Path::new(&path))?;
+    // Validate every name up front so a bad row doesn't leave half the plan imported
+    for session in &plan {
+        moderation::validate_room_name(&session.name)?;
+    }
+
+    let config = state.get_jira_config();
+    let client = state.http_client()?;
+    let mut imported = Vec::with_capacity(plan.len());
+    for session in plan {
+        let room = state.create_room(session.name, None, None)?;
+
+        let mut tickets = Vec::new();
+        let mut unresolved = Vec::new();
+        for (key, result) in fetch_tickets(&client, &config, &state.jira_user_names, session.tickets).await {
+            match result {
+                Ok(ticket) => tickets.push(ticket),
+                Err(error) => {
+                    let error = jira_failure(&state, error, None);
+                    tracing::warn!("Couldn't fetch {} for session {}: {}", key, room.name, error);
+                    unresolved.push(UnresolvedTicket { key, error });
+                }
+            }
+        }
+        let queued: Vec<String> = tickets.iter().map(|t| t.key.clone()).collect();
+        for key in &queued {
+            state.prefetched_tickets.insert(key.clone());
+        }
+        state.enqueue_tickets(&room.id, tickets);
+        for (key, minutes) in &session.deadlines {
+            state.set_ticket_deadline(&room.id, key, Some(*minutes))?;
+        }
+        let room = state.get_room(&room.id).unwrap_or(room);
+
+        if let Some(relay_client) = state.get_relay_client().await {
+            if let Err(e) = relay_client.sync_room(room.clone()) {
+                tracing::warn!("Failed to sync room to relay: {}", e);
+            }
+        }
+
+        tracing::info!(
+            "Imported session {} with {} tickets ({} unresolved)",
+            room.name,
+            queued.len(),
+            unresolved.len()
+        );
+        imported.push(ImportedSession { room, queued, unresolved });
+    }
+
+    Ok(imported)
+}
+
+#[tauri::command]
+async fn clear_current_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<(), String> {
+    host_actions::set_ticket(state.inner(), &room_id, None).await
+}
+
+/// Move a room's current ticket (with the votes so far) to another room,
+/// e.g. when a squad escalates a topic to an all-hands estimation
+#[tauri::command]
+async fn transfer_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    from_room_id: String,
+    to_room_id: String,
+) -> Result<JiraTicket, String> {
+    let ticket = state.transfer_ticket(&from_room_id, &to_room_id)?;
+    tracing::info!("Moved {} from room {} to {}", ticket.key, from_room_id, to_room_id);
+
+    let relay_client = state.get_relay_client().await;
+    for room_id in [&from_room_id, &to_room_id] {
+        state.broadcast_room_update(room_id).await;
+        if let (Some(relay_client), Some(room)) = (&relay_client, state.get_room(room_id)) {
+            if let Err(e) = relay_client.sync_room(room) {
+                tracing::warn!("Failed to sync room to relay: {}", e);
+            }
+        }
+    }
+    Ok(ticket)
+}
+
+// ============ Jira Issue Creation ============
+
+/// A sub-task to create when splitting a ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubTicketDraft {
+    pub summary: String,
+    pub description: Option<String>,
+    /// Defaults to "Sub-task"; some Jira sites name it "Subtask"
+    pub issue_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraCreatedIssue {
+    key: String,
+}
+
+/// Wrap plain text into a minimal ADF document (one paragraph per line)
+fn text_to_adf(text: &str) -> serde_json::Value {
+    let paragraphs: Vec<serde_json::Value> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::json!({
+                "type": "paragraph",
+                "content": [{ "type": "text", "text": line }]
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "type": "doc",
+        "version": 1,
+        "content": paragraphs
+    })
+}
+
+/// Create an issue in Jira and return its key
+#[tracing::instrument(name = "jira", skip_all)]
+async fn create_issue(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    fields: serde_json::Value,
+) -> Result<String, String> {
+    let url = format!("{}/rest/api/3/issue", config.base_url);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+
+    let response = client
+        .post(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .json(&serde_json::json!({ "fields": fields }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to create issue: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+
+    let created: JiraCreatedIssue = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse created issue: {}", e))?;
+
+    Ok(created.key)
+}
+
+/// Add a label to a Jira issue. Labels can't contain spaces, so they become dashes.
+#[tracing::instrument(name = "jira", skip(client, config))]
+async fn add_issue_label(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    key: &str,
+    label: &str,
+) -> Result<(), String> {
+    let url = format!("{}/rest/api/3/issue/{}", config.base_url, key);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+    let label = label.split_whitespace().collect::<Vec<_>>().join("-");
+
+    let response = client
+        .put(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .json(&serde_json::json!({ "update": { "labels": [{ "add": label }] } }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to label issue: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+    Ok(())
+}
+
+/// ID of a Jira field by its display name, e.g. "Story Points" -> "customfield_10016".
+/// With several names, the first one the site has is used.
+#[tracing::instrument(name = "jira", skip(client, config))]
+async fn find_field_id(client: &reqwest::Client, config: &state::JiraConfig, names: &[&str]) -> Result<String, String> {
+    #[derive(Deserialize)]
+    struct Field {
+        id: String,
+        name: String,
+    }
+
+    let url = format!("{}/rest/api/3/field", config.base_url);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+    let response = client
+        .get(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to list Jira fields: {}", e))?;
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+    let fields: Vec<Field> = response.json().await.map_err(|e| format!("Failed to parse Jira fields: {}", e))?;
+    names
+        .iter()
+        .find_map(|name| fields.iter().find(|field| field.name.eq_ignore_ascii_case(name)))
+        .map(|field| field.id.clone())
+        .ok_or_else(|| format!("Jira has no field named \"{}\"", names.join("\" or \"")))
+}
+
+#[tracing::instrument(name = "jira", skip(client, config, value))]
+async fn set_issue_field(
+    client: &reqwest::Client,
+    config: &state::JiraConfig,
+    key: &str,
+    field_id: &str,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    let url = format!("{}/rest/api/3/issue/{}", config.base_url, key);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+
+    let response = client
+        .put(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .json(&serde_json::json!({ "fields": { field_id: value } }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update issue: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+    Ok(())
+}
+
+/// Flag a Jira issue. There's no flag field common to every site, so this uses
+/// the endpoint behind "Add flag" on Jira boards.
+#[tracing::instrument(name = "jira", skip(client, config))]
+async fn flag_issue(client: &reqwest::Client, config: &state::JiraConfig, key: &str) -> Result<(), String> {
+    let url = format!("{}/rest/greenhopper/1.0/xboard/issue/flag/flag.json", config.base_url);
+    let auth = format!("{}:{}", config.email, config.api_token);
+    let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
+
+    let response = client
+        .post(&url)
+        .header("Authorization", auth_header)
+        .header("Accept", "application/json")
+        .json(&serde_json::json!({ "issueKeys": [key], "flag": true }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to flag issue: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(jira_error(response).await);
+    }
+    Ok(())
+}
+
+/// Split a ticket into Jira sub-tasks and queue them for estimation. If some
+/// can't be created, the ones that were are still queued and recorded, and the
+/// error says which failed.
+#[tauri::command]
+async fn split_ticket(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    ticket_key: String,
+    parts: Vec<SubTicketDraft>,
+) -> Result<Vec<JiraTicket>, String> {
+    let config = state.get_jira_config();
+
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    if state.get_room(&room_id).is_none() {
+        return Err("Room not found".into());
+    }
+    if parts.is_empty() {
+        return Err("At least one sub-task is required to split a ticket".into());
+    }
+
+    let project_key = ticket_key
+        .split_once('-')
+        .map(|(project, _)| project.to_string())
+        .ok_or_else(|| format!("Invalid ticket key: {}", ticket_key))?;
+
+    let client = state.http_client()?;
+    let mut sub_tickets = Vec::with_capacity(parts.len());
+    let mut failed = Vec::new();
+    for part in parts {
+        // Once the credentials are rejected the rest would fail the same way
+        if !failed.is_empty() && !state.has_jira_config() {
+            failed.push(format!("\"{}\" (not attempted)", part.summary));
+            continue;
+        }
+        let issue_type = part.issue_type.unwrap_or_else(|| "Sub-task".to_string());
+        let mut fields = serde_json::json!({
+            "project": { "key": project_key },
+            "parent": { "key": ticket_key },
+            "summary": part.summary,
+            "issuetype": { "name": issue_type },
+        });
+        if let Some(description) = part.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            fields["description"] = text_to_adf(description);
+        }
+
+        let key = match create_issue(&client, &config, fields).await {
+            Ok(key) => key,
+            Err(e) => {
+                let message = jira_failure(&state, e, None);
+                tracing::warn!("Failed to create sub-task \"{}\" for {}: {}", part.summary, ticket_key, message);
+                failed.push(format!("\"{}\" ({})", part.summary, message));
+                continue;
+            }
+        };
+        tracing::info!("Created sub-task {} for {}", key, ticket_key);
+
+        sub_tickets.push(JiraTicket {
+            url: format!("{}/browse/{}", config.base_url, key),
+            key,
+            summary: part.summary,
+            complexity: Some(analysis::analyze_ticket(part.description.as_deref(), 0)),
+            description: part.description,
+            issue_type: Some(issue_type),
+            status: None,
+            diff_stats: None,
+        });
+    }
+
+    let created: Vec<String> = sub_tickets.iter().map(|t| t.key.clone()).collect();
+    if !sub_tickets.is_empty() {
+        state.enqueue_tickets(&room_id, sub_tickets.clone());
+        prefetch_queued_tickets(state.inner().clone(), room_id.clone());
+        state.record_history(&room_id, room::HistoryEvent::TicketSplit {
+            ticket_key,
+            sub_ticket_keys: created.clone(),
+        });
+        state.broadcast_room_update(&room_id).await;
+    }
+
+    if !failed.is_empty() {
+        let done = if created.is_empty() {
+            "No sub-tasks were created".to_string()
+        } else {
+            format!("Created and queued {}", created.join(", "))
+        };
+        return Err(format!("{}; failed to create {}", done, failed.join(", ")));
+    }
+    Ok(sub_tickets)
+}
+
+/// File a new issue in Jira, optionally queueing it for estimation in a room
+#[tauri::command]
+async fn create_jira_issue(
+    state: tauri::State<'_, Arc<AppState>>,
+    project_key: String,
+    issue_type: String,
+    summary: String,
+    description: Option<String>,
+    room_id: Option<String>,
+) -> Result<JiraTicket, String> {
+    let config = state.get_jira_config();
+
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
+    if summary.trim().is_empty() {
+        return Err("Summary is required".into());
+    }
+
+    let mut fields = serde_json::json!({
+        "project": { "key": project_key },
+        "summary": summary,
+        "issuetype": { "name": issue_type },
+    });
+    if let Some(description) = description.as_deref().filter(|d| !d.trim().is_empty()) {
+        fields["description"] = text_to_adf(description);
+    }
+
+    let client = state.http_client()?;
+    let key = create_issue(&client, &config, fields)
+        .await
+        .map_err(|e| jira_failure(&state, e, None))?;
+    tracing::info!("Created issue {} in {}", key, project_key);
+
+    let ticket = JiraTicket {
+        url: format!("{}/browse/{}", config.base_url, key),
+        key,
+        summary,
+        complexity: Some(analysis::analyze_ticket(description.as_deref(), 0)),
+        description,
+        issue_type: Some(issue_type),
+        status: None,
+        diff_stats: None,
+    };
+
+    if let Some(room_id) = room_id {
+        if state.enqueue_tickets(&room_id, vec![ticket.clone()]) {
+            prefetch_queued_tickets(state.inner().clone(), room_id.clone());
+            state.record_history(&room_id, room::HistoryEvent::IssueCreated {
+                ticket_key: ticket.key.clone(),
+            });
+            state.broadcast_room_update(&room_id).await;
+        } else {
+            tracing::warn!("Created {} but room {} no longer exists", ticket.key, room_id);
+        }
+    }
+
+    Ok(ticket)
+}
+
+/// Queue GitHub pull requests for estimating review effort: "owner/repo#123",
+/// a pull request URL, or "owner/repo" for the repository's open ones
+#[tauri::command]
+async fn load_github_pull_requests(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+    source: String,
+) -> Result<Vec<JiraTicket>, String> {
+    let source = github::PullRequestSource::parse(&source)?;
+    let token = state.get_settings().github_token;
+    let client = state.http_client()?;
+    let pull_requests = github::load(&client, &source, token.as_deref()).await?;
+    tracing::info!("Loaded {} pull requests from GitHub", pull_requests.len());
+
+    if !state.enqueue_tickets(&room_id, pull_requests.clone()) {
+        return Err("Room not found".into());
+    }
+    state.broadcast_room_update(&room_id).await;
+    Ok(pull_requests)
+}
+
+// ============ Jira Project/Board Browsing ============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraProject {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraProjectResponse {
+    id: String,
+    key: String,
+    name: String,
+}
+
+#[tauri::command]
+async fn list_jira_projects(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<JiraProject>, String> {
+    let config = state.get_jira_config();
+    
+    if !state.has_jira_config() {
+        return Err("Jira is not configured.".into());
+    }
 
     let url = format!("{}/rest/api/3/project", config.base_url);
     let auth = format!("{}:{}", config.email, config.api_token);
     let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
 
-    let client = reqwest::Client::new();
+    let client = state.http_client()?;
     let response = client
         .get(&url)
         .header("Authorization", &auth_header)
@@ -401,9 +2289,7 @@ async fn list_jira_projects(
         .map_err(|e| format!("Failed to fetch projects: {}", e))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Jira API error ({}): {}", status, body));
+        return Err(jira_failure(&state, jira_error(response).await, None));
     }
 
     let projects: Vec<JiraProjectResponse> = response
@@ -453,7 +2339,7 @@ async fn list_jira_boards(
     let auth = format!("{}:{}", config.email, config.api_token);
     let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
 
-    let client = reqwest::Client::new();
+    let client = state.http_client()?;
     let response = client
         .get(&url)
         .header("Authorization", &auth_header)
@@ -463,9 +2349,7 @@ async fn list_jira_boards(
         .map_err(|e| format!("Failed to fetch boards: {}", e))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Jira API error ({}): {}", status, body));
+        return Err(jira_failure(&state, jira_error(response).await, None));
     }
 
     let boards: JiraBoardsResponse = response
@@ -493,10 +2377,74 @@ struct JiraBoardIssuesResponse {
     issues: Vec<JiraIssueResponse>,
 }
 
+/// Jira field holding story points on company-managed projects
+const DEFAULT_STORY_POINTS_FIELD: &str = "Story Points";
+/// Jira field holding story points on team-managed projects
+const TEAM_MANAGED_STORY_POINTS_FIELD: &str = "Story point estimate";
+
+/// Narrows the board issue list; every filter is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct BoardIssueFilter {
+    /// Status categories to include, e.g. "To Do", "In Progress"
+    status_categories: Vec<String>,
+    /// Only issues carrying at least one of these labels
+    labels: Vec<String>,
+    /// Only issues with no story points yet
+    only_unestimated: bool,
+    /// Story points field name, if the site doesn't use "Story Points"
+    /// (team-managed projects call it "Story point estimate")
+    story_points_field: Option<String>,
+}
+
+impl BoardIssueFilter {
+    /// JQL for the filter, or None if nothing is filtered
+    fn to_jql(&self) -> Option<String> {
+        let list = |values: &[String]| {
+            values
+                .iter()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| jql_string(v.trim()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut clauses = Vec::new();
+        let categories = list(&self.status_categories);
+        if !categories.is_empty() {
+            clauses.push(format!("statusCategory in ({})", categories));
+        }
+        let labels = list(&self.labels);
+        if !labels.is_empty() {
+            clauses.push(format!("labels in ({})", labels));
+        }
+        if self.only_unestimated {
+            let field = self
+                .story_points_field
+                .as_deref()
+                .filter(|f| !f.trim().is_empty())
+                .unwrap_or(DEFAULT_STORY_POINTS_FIELD);
+            clauses.push(format!("{} is EMPTY", jql_string(field)));
+        }
+
+        if clauses.is_empty() {
+            None
+        } else {
+            Some(clauses.join(" AND "))
+        }
+    }
+}
+
+/// Quote a value for use in JQL
+fn jql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[tauri::command]
 async fn list_board_issues(
     state: tauri::State<'_, Arc<AppState>>,
     board_id: i64,
+    filter: Option<BoardIssueFilter>,
 ) -> Result<Vec<JiraIssueInfo>, String> {
     let config = state.get_jira_config();
     
@@ -504,78 +2452,393 @@ async fn list_board_issues(
         return Err("Jira is not configured.".into());
     }
 
+    let mut query = vec![("maxResults", "50".to_string())];
+    if let Some(jql) = filter.unwrap_or_default().to_jql() {
+        tracing::debug!("Filtering board {} issues with JQL: {}", board_id, jql);
+        query.push(("jql", jql));
+    }
+
     // Try backlog first, then fall back to board issues
-    let url = format!("{}/rest/agile/1.0/board/{}/backlog?maxResults=50", config.base_url, board_id);
+    let url = format!("{}/rest/agile/1.0/board/{}/backlog", config.base_url, board_id);
     let auth = format!("{}:{}", config.email, config.api_token);
     let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(auth));
 
-    let client = reqwest::Client::new();
+    let client = state.http_client()?;
     let response = client
         .get(&url)
+        .query(&query)
         .header("Authorization", &auth_header)
         .header("Accept", "application/json")
         .send()
         .await
         .map_err(|e| format!("Failed to fetch issues: {}", e))?;
 
-    if !response.status().is_success() {
-        // Try board issues instead
-        let url = format!("{}/rest/agile/1.0/board/{}/issue?maxResults=50", config.base_url, board_id);
-        let response = client
-            .get(&url)
-            .header("Authorization", &auth_header)
+    if !response.status().is_success() {
+        // Try board issues instead
+        let url = format!("{}/rest/agile/1.0/board/{}/issue", config.base_url, board_id);
+        let response = client
+            .get(&url)
+            .query(&query)
+            .header("Authorization", &auth_header)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch issues: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(jira_failure(&state, jira_error(response).await, None));
+        }
+
+        let issues: JiraBoardIssuesResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse issues: {}", e))?;
+
+        return Ok(issues.issues.into_iter().map(|i| JiraIssueInfo {
+            key: i.key,
+            summary: i.fields.summary,
+            issue_type: i.fields.issuetype.map(|t| t.name),
+            status: i.fields.status.map(|s| s.name),
+        }).collect());
+    }
+
+    let issues: JiraBoardIssuesResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse issues: {}", e))?;
+
+    Ok(issues.issues.into_iter().map(|i| JiraIssueInfo {
+        key: i.key,
+        summary: i.fields.summary,
+        issue_type: i.fields.issuetype.map(|t| t.name),
+        status: i.fields.status.map(|s| s.name),
+    }).collect())
+}
+
+// ============ Credential Management ============
+
+#[tauri::command]
+async fn has_stored_credentials() -> Result<bool, String> {
+    Ok(credentials::has_stored_credentials())
+}
+
+#[tauri::command]
+async fn unlock_credentials(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+) -> Result<bool, String> {
+    let creds = credentials::load_credentials(&password)?;
+    state.set_jira_config(creds.base_url, creds.email, creds.api_token);
+    if credentials::has_stored_llm_credentials() {
+        match credentials::load_llm_credentials(&password) {
+            Ok(llm) => state.set_llm_credentials(Some(llm)),
+            Err(e) => tracing::warn!("Failed to unlock language model settings: {}", e),
+        }
+    }
+    if let Err(e) = unlock_proxy_password(&state, &password) {
+        tracing::warn!("Failed to unlock the proxy password: {}", e);
+    }
+    if let Err(e) = unlock_github_token(&state, &password) {
+        tracing::warn!("Failed to unlock the GitHub token: {}", e);
+    }
+    if let Err(e) = unlock_sms_auth_token(&state, &password) {
+        tracing::warn!("Failed to unlock the SMS auth token: {}", e);
+    }
+    if let Err(e) = unlock_relay_token(&state, &password) {
+        tracing::warn!("Failed to unlock the relay token: {}", e);
+    }
+
+    // The same password unlocks encrypted room storage
+    if !state.is_room_storage_unlocked() {
+        if let Err(e) = restore_rooms(&state, Some(credentials::MasterKey::derive(&password)?)) {
+            tracing::error!("Failed to unlock room storage: {}", e);
+        }
+    }
+    retry_pending_jira_requests(state.inner().clone());
+    Ok(true)
+}
+
+/// Fill in the proxy password from the credentials store, first moving a plaintext
+/// one left in settings.json by an older version into the store
+fn unlock_proxy_password(state: &AppState, password: &str) -> Result<(), String> {
+    let mut settings = state.get_settings();
+    let Some(proxy) = settings.proxy.as_mut() else {
+        return Ok(());
+    };
+    if proxy.password_stored {
+        proxy.password = Some(credentials::load_proxy_password(password)?);
+    } else if let Some(proxy_password) = &proxy.password {
+        credentials::save_proxy_password(password, proxy_password)?;
+        proxy.password_stored = true;
+        settings::save_settings(&settings)?;
+        tracing::info!("Moved the proxy password into the credentials store");
+    } else {
+        return Ok(());
+    }
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Fill in the GitHub token from the credentials store, first moving a plaintext
+/// one left in settings.json by an older version into the store
+fn unlock_github_token(state: &AppState, password: &str) -> Result<(), String> {
+    let mut settings = state.get_settings();
+    if settings.github_token_stored {
+        settings.github_token = Some(credentials::load_github_token(password)?);
+    } else if let Some(token) = &settings.github_token {
+        credentials::save_github_token(password, token)?;
+        settings.github_token_stored = true;
+        settings::save_settings(&settings)?;
+        tracing::info!("Moved the GitHub token into the credentials store");
+    } else {
+        return Ok(());
+    }
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Fill in the SMS auth token from the credentials store, first moving a plaintext
+/// one left in settings.json by an older version into the store
+fn unlock_sms_auth_token(state: &AppState, password: &str) -> Result<(), String> {
+    let mut settings = state.get_settings();
+    if settings.sms_auth_token_stored {
+        settings.sms_auth_token = Some(credentials::load_sms_auth_token(password)?);
+    } else if let Some(token) = &settings.sms_auth_token {
+        credentials::save_sms_auth_token(password, token)?;
+        settings.sms_auth_token_stored = true;
+        settings::save_settings(&settings)?;
+        tracing::info!("Moved the SMS auth token into the credentials store");
+    } else {
+        return Ok(());
+    }
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Fill in the relay token from the credentials store, first moving a plaintext
+/// one left in settings.json by an older version into the store
+fn unlock_relay_token(state: &AppState, password: &str) -> Result<(), String> {
+    let mut settings = state.get_settings();
+    if settings.relay_token_stored {
+        settings.relay_token = Some(credentials::load_relay_token(password)?);
+    } else if let Some(token) = &settings.relay_token {
+        credentials::save_relay_token(password, token)?;
+        settings.relay_token_stored = true;
+        settings::save_settings(&settings)?;
+        tracing::info!("Moved the relay token into the credentials store");
+    } else {
+        return Ok(());
+    }
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Unlock encrypted room storage and restore the saved rooms
+#[tauri::command]
+async fn unlock_room_storage(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+) -> Result<(), String> {
+    if state.is_room_storage_unlocked() {
+        return Ok(());
+    }
+    restore_rooms(&state, Some(derive_master_key(&password)?))
+}
+
+/// The crash the last run ended in, if any, so the host can be offered recovery
+#[tauri::command]
+async fn get_recovery_info() -> Result<Option<recovery::RecoveryInfo>, String> {
+    recovery::info()
+}
+
+/// Restore the rooms as they were when the app crashed, participants and votes
+/// included, and reconnect to the relay if it was connected. Returns the number
+/// of rooms restored.
+#[tauri::command]
+async fn recover_last_session(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<usize, String> {
+    if !state.is_room_storage_unlocked() {
+        return Err("Unlock room storage first".into());
+    }
+    let dump = recovery::load(state.storage_key().as_ref())?.ok_or("There is no session to recover")?;
+    tracing::info!("Recovering {} rooms from a crash: {}", dump.rooms.len(), dump.reason);
+    let count = dump.rooms.len();
+    state.recover_session(dump.rooms, &dump.identities);
+    recovery::discard()?;
+
+    if dump.relay_connected {
+        connect_relay(app_handle, state).await?;
+    }
+    Ok(count)
+}
+
+/// Forget the crashed session and carry on with the rooms as last saved
+#[tauri::command]
+async fn discard_recovery() -> Result<(), String> {
+    recovery::discard()
+}
+
+// ============ Onboarding ============
+
+#[tauri::command]
+async fn get_onboarding_state() -> Result<onboarding::OnboardingState, String> {
+    onboarding::load()
+}
+
+/// Record a step's check and return the updated onboarding state. A failed
+/// check is recorded rather than returned as an error, so the UI can show why.
+fn record_onboarding_check(
+    state: &AppState,
+    step: onboarding::OnboardingStep,
+    result: Result<String, String>,
+) -> Result<onboarding::OnboardingState, String> {
+    if let Err(e) = &result {
+        tracing::info!("Onboarding check {:?} failed: {}", step, e);
+    }
+    let now = state.env.now_secs();
+    onboarding::update(|onboarding| {
+        onboarding.record(step, result, now);
+        Ok(())
+    })
+}
+
+/// Check the participant server answers on this machine
+#[tauri::command]
+async fn test_server_started(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<onboarding::OnboardingState, String> {
+    let port = server_port(&state);
+    let result = onboarding::probe_server(&format!("http://127.0.0.1:{}", port))
+        .await
+        .map(|()| format!("The server is listening on port {}", port));
+    record_onboarding_check(&state, onboarding::OnboardingStep::ServerStarted, result)
+}
+
+/// Check the server answers on the LAN address participants are given. This
+/// goes out through the network interface, so a firewall blocking the port
+/// usually shows up here too.
+#[tauri::command]
+async fn test_network_reachable(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<onboarding::OnboardingState, String> {
+    let info = build_network_info(&state, server_port(&state));
+    let result = match onboarding::probe_server(&info.local_url).await {
+        Ok(()) if info.firewall_open => Ok(format!(
+            "Participants on your network can join at {}, and the port is open for others",
+            info.local_url
+        )),
+        Ok(()) => Ok(format!(
+            "Participants on your network can join at {}. Others need the port opened or the relay.",
+            info.local_url
+        )),
+        Err(e) if info.local_ip == "127.0.0.1" => Err(format!("No network connection was found ({})", e)),
+        Err(e) => Err(format!("{}. Check the firewall allows port {}.", e, info.port)),
+    };
+    record_onboarding_check(&state, onboarding::OnboardingStep::NetworkReachable, result)
+}
+
+/// Check the Jira credentials by asking Jira who they belong to
+#[tauri::command]
+async fn test_jira_connection(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<onboarding::OnboardingState, String> {
+    let result = async {
+        if !state.has_jira_config() {
+            return Err("Jira is not configured.".to_string());
+        }
+        let config = state.get_jira_config();
+        let auth = format!("{}:{}", config.email, config.api_token);
+        let response = state
+            .http_client()?
+            .get(format!("{}/rest/api/3/myself", config.base_url))
+            .header("Authorization", format!("Basic {}", general_purpose::STANDARD.encode(auth)))
             .header("Accept", "application/json")
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch issues: {}", e))?;
-
+            .map_err(|e| format!("Failed to reach Jira: {}", e))?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(format!("Jira API error ({}): {}", status, body));
+            return Err(jira_failure(&state, jira_error(response).await, None));
         }
-
-        let issues: JiraBoardIssuesResponse = response
+        let myself: JiraUser = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse issues: {}", e))?;
-
-        return Ok(issues.issues.into_iter().map(|i| JiraIssueInfo {
-            key: i.key,
-            summary: i.fields.summary,
-            issue_type: i.fields.issuetype.map(|t| t.name),
-            status: i.fields.status.map(|s| s.name),
-        }).collect());
+            .map_err(|e| format!("Failed to parse Jira response: {}", e))?;
+        Ok(format!("Connected to {} as {}", config.base_url, myself.display_name))
     }
+    .await;
+    record_onboarding_check(&state, onboarding::OnboardingStep::JiraConnected, result)
+}
 
-    let issues: JiraBoardIssuesResponse = response
-        .json()
+/// Check the relay can be reached, through the configured proxy if there is one
+#[tauri::command]
+async fn test_relay_connection(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<onboarding::OnboardingState, String> {
+    let result = if let Some(client) = state.get_relay_client().await {
+        Ok(format!("Connected to {}", client.get_relay_url().await))
+    } else {
+        async {
+            let health_url = relay::health_url(None)?;
+            onboarding::probe_relay(&state.http_client()?, &health_url).await?;
+            Ok("The relay can be reached; connect to it when participants are outside your network".to_string())
+        }
         .await
-        .map_err(|e| format!("Failed to parse issues: {}", e))?;
-
-    Ok(issues.issues.into_iter().map(|i| JiraIssueInfo {
-        key: i.key,
-        summary: i.fields.summary,
-        issue_type: i.fields.issuetype.map(|t| t.name),
-        status: i.fields.status.map(|s| s.name),
-    }).collect())
+    };
+    record_onboarding_check(&state, onboarding::OnboardingStep::RelayTested, result)
 }
 
-// ============ Credential Management ============
-
+/// Skip an optional step (Jira or the relay)
 #[tauri::command]
-async fn has_stored_credentials() -> Result<bool, String> {
-    Ok(credentials::has_stored_credentials())
+async fn skip_onboarding_step(
+    state: tauri::State<'_, Arc<AppState>>,
+    step: onboarding::OnboardingStep,
+) -> Result<onboarding::OnboardingState, String> {
+    let now = state.env.now_secs();
+    onboarding::update(|onboarding| onboarding.skip(step, now))
 }
 
+/// Turn encryption of stored rooms on or off; everything on disk is rewritten
 #[tauri::command]
-async fn unlock_credentials(
+async fn set_room_encryption(
     state: tauri::State<'_, Arc<AppState>>,
+    enabled: bool,
     password: String,
-) -> Result<bool, String> {
-    let creds = credentials::load_credentials(&password)?;
-    state.set_jira_config(creds.base_url, creds.email, creds.api_token);
-    Ok(true)
+) -> Result<(), String> {
+    if !state.is_room_storage_unlocked() {
+        return Err("Unlock room storage first".into());
+    }
+
+    let key = if enabled { Some(derive_master_key(&password)?) } else { None };
+    state.set_storage_key(key);
+
+    let mut settings = state.get_settings();
+    settings.encrypt_rooms = enabled;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Move the stored rooms to another storage backend and keep them there from now on.
+/// The old backend's files are left in place.
+#[tauri::command]
+async fn set_storage_backend(
+    state: tauri::State<'_, Arc<AppState>>,
+    backend: storage::StorageBackend,
+) -> Result<(), String> {
+    if !state.is_room_storage_unlocked() {
+        return Err("Unlock room storage first".into());
+    }
+    let storage = backend.open(state.get_rooms(), state.storage_key())?;
+    state.set_storage(storage, state.storage_key());
+
+    let mut settings = state.get_settings();
+    settings.storage_backend = backend;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    tracing::info!("Rooms are now stored with the {:?} backend", backend);
+    Ok(())
 }
 
 #[tauri::command]
@@ -594,6 +2857,145 @@ async fn save_jira_credentials(
     
     credentials::save_credentials(&password, &creds)?;
     state.set_jira_config(base_url, email, api_token);
+    retry_pending_jira_requests(state.inner().clone());
+    Ok(())
+}
+
+/// Store the language model used for session summaries, encrypted with the
+/// credentials password
+#[tauri::command]
+async fn save_llm_credentials(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+    endpoint: String,
+    model: String,
+    api_key: String,
+) -> Result<(), String> {
+    let endpoint = endpoint.trim().trim_end_matches('/').to_string();
+    url::Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint: {}", e))?;
+    if model.trim().is_empty() {
+        return Err("Model is required".into());
+    }
+    let creds = credentials::LlmCredentials { endpoint, model: model.trim().to_string(), api_key };
+
+    // Same password as the Jira credentials, so one unlock covers both
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(&password)?;
+    }
+    credentials::save_llm_credentials(&password, &creds)?;
+    state.set_llm_credentials(Some(creds));
+    Ok(())
+}
+
+/// Store the password for the configured proxy, encrypted with the credentials
+/// password; `None` removes it
+#[tauri::command]
+async fn save_proxy_password(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+    proxy_password: Option<String>,
+) -> Result<(), String> {
+    let mut settings = state.get_settings();
+    let proxy = settings.proxy.as_mut().ok_or("Configure a proxy first")?;
+
+    // Same password as the Jira credentials, so one unlock covers both
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(&password)?;
+    }
+    match &proxy_password {
+        Some(proxy_password) => credentials::save_proxy_password(&password, proxy_password)?,
+        None => credentials::delete_proxy_password()?,
+    }
+    proxy.password_stored = proxy_password.is_some();
+    proxy.password = proxy_password;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Store the GitHub token, encrypted with the credentials password; `None` removes it
+#[tauri::command]
+async fn save_github_token(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+    token: Option<String>,
+) -> Result<(), String> {
+    // Same password as the Jira credentials, so one unlock covers both
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(&password)?;
+    }
+    let token = token.filter(|token| !token.is_empty());
+    match &token {
+        Some(token) => credentials::save_github_token(&password, token)?,
+        None => credentials::delete_github_token()?,
+    }
+    let mut settings = state.get_settings();
+    settings.github_token_stored = token.is_some();
+    settings.github_token = token;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Store the secret the SMS provider signs its webhooks with, encrypted with the
+/// credentials password; `None` removes it
+#[tauri::command]
+async fn save_sms_auth_token(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+    token: Option<String>,
+) -> Result<(), String> {
+    // Same password as the Jira credentials, so one unlock covers both
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(&password)?;
+    }
+    let token = token.filter(|token| !token.is_empty());
+    match &token {
+        Some(token) => credentials::save_sms_auth_token(&password, token)?,
+        None => credentials::delete_sms_auth_token()?,
+    }
+    let mut settings = state.get_settings();
+    settings.sms_auth_token_stored = token.is_some();
+    settings.sms_auth_token = token;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+/// Store the API key or signed token the relay authenticates this host with,
+/// encrypted with the credentials password; `None` removes it
+#[tauri::command]
+async fn save_relay_token(
+    state: tauri::State<'_, Arc<AppState>>,
+    password: String,
+    token: Option<String>,
+) -> Result<(), String> {
+    // Same password as the Jira credentials, so one unlock covers both
+    if credentials::has_stored_credentials() {
+        credentials::load_credentials(&password)?;
+    }
+    let token = token.filter(|token| !token.is_empty());
+    match &token {
+        Some(token) => credentials::save_relay_token(&password, token)?,
+        None => credentials::delete_relay_token()?,
+    }
+    let mut settings = state.get_settings();
+    settings.relay_token_stored = token.is_some();
+    settings.relay_token = token;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
+
+#[tauri::command]
+async fn has_llm_credentials(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.llm_credentials().is_some())
+}
+
+#[tauri::command]
+async fn delete_llm_credentials(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+    credentials::delete_llm_credentials()?;
+    state.set_llm_credentials(None);
     Ok(())
 }
 
@@ -615,12 +3017,19 @@ struct NetworkInfo {
     local_url: String,
     public_url: Option<String>,
     firewall_open: bool,
+    /// IPv6 address participants could use when port forwarding isn't set up
+    ipv6: Option<ipv6::Ipv6Route>,
+    ipv6_url: Option<String>,
+    /// The URL to hand out: public if the port is open, else a reachable IPv6 one, else local
+    share_url: String,
 }
 
 #[tauri::command]
-async fn get_public_ip() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    
+async fn get_public_ip(state: tauri::State<'_, Arc<AppState>>) -> Result<String, String> {
+    lookup_public_ip(&state.http_client()?).await
+}
+
+async fn lookup_public_ip(client: &reqwest::Client) -> Result<String, String> {
     // Try multiple services in case one is down
     let services = [
         "https://api.ipify.org",
@@ -646,38 +3055,55 @@ async fn get_public_ip() -> Result<String, String> {
 async fn get_network_info(
     state: tauri::State<'_, Arc<AppState>>,
 ) -> Result<NetworkInfo, String> {
-    let local_ip = local_ip_address::local_ip()
-        .map(|ip| ip.to_string())
-        .unwrap_or_else(|_| "127.0.0.1".to_string());
-    
-    let server_url = state.get_server_url();
-    let port = server_url.split(':').last()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or(3030);
-    
     // Try to get public IP
-    let public_ip = get_public_ip().await.ok();
+    let public_ip = match state.http_client() {
+        Ok(client) => lookup_public_ip(&client).await.ok(),
+        Err(_) => None,
+    };
     
     // Cache the public IP in state
     state.set_public_ip(public_ip.clone());
     
     // Check if firewall rule exists
+    let port = server_port(&state);
     let firewall_open = check_firewall_rule(port);
     
     // Update firewall state
     state.set_firewall_open(firewall_open);
+
+    // IPv6 (native, or Teredo/6to4 through the NAT) is the fallback when the port isn't forwarded
+    state.set_ipv6_route(ipv6::probe(port).await);
+
+    Ok(build_network_info(&state, port))
+}
+
+fn server_port(state: &AppState) -> u16 {
+    state.get_server_url().split(':').last()
+        .and_then(|p| p.parse::<u16>().ok())
+        .unwrap_or(3030)
+}
+
+fn build_network_info(state: &AppState, port: u16) -> NetworkInfo {
+    let local_ip = local_ip_address::local_ip()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|_| "127.0.0.1".to_string());
     
+    let public_ip = state.get_public_ip();
     let local_url = format!("http://{}:{}", local_ip, port);
     let public_url = public_ip.as_ref().map(|ip| format!("http://{}:{}", ip, port));
+    let ipv6 = state.get_ipv6_route();
     
-    Ok(NetworkInfo {
+    NetworkInfo {
         local_ip,
         public_ip,
         port,
         local_url,
         public_url,
-        firewall_open,
-    })
+        firewall_open: state.is_firewall_open(),
+        ipv6_url: ipv6.as_ref().map(|route| route.url(port)),
+        ipv6,
+        share_url: state.get_share_url(),
+    }
 }
 
 fn check_firewall_rule(port: u16) -> bool {
@@ -756,6 +3182,83 @@ async fn open_upnp_port(
     Ok(format!("UPnP port {} opened successfully! External IP: {}", port, external_ip))
 }
 
+/// Manual port-forwarding steps for when UPnP fails, tailored to the router when
+/// its maker can be told from its UPnP description or its MAC address
+#[tauri::command]
+async fn get_port_forward_instructions(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<port_forward::PortForwardInstructions, String> {
+    use port_forward::{DetectedFrom, RouterVendor};
+
+    let port = server_port(&state);
+    let internal_ip = local_ip_address::local_ip()
+        .map_err(|e| format!("Failed to get local IP: {}", e))?
+        .to_string();
+
+    // A router that answers UPnP search but refused the mapping still describes itself
+    let (mut detected, gateway_ip) = match igd_next::aio::tokio::search_gateway(Default::default()).await {
+        Ok(gateway) => {
+            let description = fetch_gateway_description(&format!("http://{}{}", gateway.addr, gateway.root_url)).await;
+            let detected = description
+                .as_deref()
+                .and_then(RouterVendor::from_description)
+                .map(|vendor| (vendor, DetectedFrom::Upnp));
+            (detected, Some(gateway.addr.ip().to_string()))
+        }
+        Err(e) => {
+            tracing::info!("No UPnP gateway found for port-forwarding instructions: {}", e);
+            (None, default_gateway())
+        }
+    };
+    if detected.is_none() {
+        detected = gateway_ip
+            .as_deref()
+            .and_then(gateway_mac)
+            .and_then(|mac| RouterVendor::from_mac(&mac))
+            .map(|vendor| (vendor, DetectedFrom::MacAddress));
+    }
+
+    Ok(port_forward::instructions(detected, gateway_ip, internal_ip, port))
+}
+
+/// Fetch the router's UPnP device description, straight over the LAN
+async fn fetch_gateway_description(url: &str) -> Option<String> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .ok()?;
+    client.get(url).send().await.ok()?.text().await.ok()
+}
+
+/// The default gateway's address, from the routing table
+fn default_gateway() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        return port_forward::gateway_from_proc_route(&std::fs::read_to_string("/proc/net/route").ok()?);
+    }
+    // Windows lists the default route as "0.0.0.0  0.0.0.0  <gateway>  <interface>  <metric>"
+    let output = std::process::Command::new("route")
+        .args(["print", "-4", "0.0.0.0"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.len() >= 3 && fields[0] == "0.0.0.0" && fields[1] == "0.0.0.0").then(|| fields[2].to_string())
+    })
+}
+
+/// MAC address of a machine on the local network, from the ARP cache
+fn gateway_mac(ip: &str) -> Option<String> {
+    if let Some(mac) = std::fs::read_to_string("/proc/net/arp")
+        .ok()
+        .and_then(|table| port_forward::mac_from_arp_output(&table, ip))
+    {
+        return Some(mac);
+    }
+    let output = std::process::Command::new("arp").args(["-a", ip]).output().ok()?;
+    port_forward::mac_from_arp_output(&String::from_utf8_lossy(&output.stdout), ip)
+}
+
 #[tauri::command]
 async fn open_firewall_port(
     state: tauri::State<'_, Arc<AppState>>,
@@ -807,7 +3310,7 @@ exit /b %ERRORLEVEL%
             state.set_firewall_open(true);
             
             // Also fetch and cache the public IP so share URL works
-            if let Ok(public_ip) = get_public_ip().await {
+            if let Ok(public_ip) = lookup_public_ip(&state.http_client()?).await {
                 state.set_public_ip(Some(public_ip));
             }
             
@@ -825,22 +3328,69 @@ exit /b %ERRORLEVEL%
 
 #[tauri::command]
 async fn connect_relay(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, Arc<AppState>>,
 ) -> Result<String, String> {
     // Check if already connected
     if state.is_relay_connected().await {
         return Ok("Already connected to relay".to_string());
     }
-    
-    let relay_client = relay::RelayClient::connect(None).await?;
+    start_relay(app_handle, state.inner().clone()).await
+}
+
+/// First wait before reconnecting to a relay that dropped, doubled after each failed attempt
+const RELAY_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_RELAY_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Connect to the relay and hand it this host's rooms
+async fn start_relay(app_handle: tauri::AppHandle, state: Arc<AppState>) -> Result<String, String> {
+    let settings = state.get_settings();
+    let room_ids = state.get_rooms().into_iter().map(|room| room.id).collect();
+    let relay_client = relay::RelayClient::connect(None, &settings, room_ids).await?;
     
     // Set up callback to sync relay room updates back to local state
-    let state_for_callback = state.inner().clone();
+    let state_for_callback = state.clone();
     relay_client.set_room_update_callback(move |room| {
         tracing::info!("Relay room update callback: {} ({} participants)", 
             room.name, room.participants.len());
+        let room_id = room.id.clone();
         state_for_callback.update_room_from_relay(room);
+
+        // Votes from relay participants count towards the quorum too
+        let state = state_for_callback.clone();
+        tokio::spawn(async move {
+            if state.auto_reveal_if_ready(&room_id).await {
+                state.broadcast_room_update(&room_id).await;
+            }
+        });
+    }).await;
+    
+    // Surface relay errors with a hint about what to do, rather than only logging them
+    let error_handle = app_handle.clone();
+    relay_client.set_error_callback(move |error| {
+        let _ = error_handle.emit("relay-error", error);
+    }).await;
+    
+    // Alert the host when the relay drops, and keep trying to get it back
+    let disconnect_handle = app_handle.clone();
+    let disconnect_state = state.clone();
+    let dropped = Arc::downgrade(&relay_client);
+    let reported = std::sync::atomic::AtomicBool::new(false);
+    relay_client.set_disconnect_callback(move |reason| {
+        if reported.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            return;
+        }
+        tracing::warn!("Relay disconnected: {}", reason);
+        let _ = disconnect_handle.emit("relay-disconnected", reason);
+        tokio::spawn(reconnect_relay(disconnect_handle.clone(), disconnect_state.clone(), dropped.clone()));
+    }).await;
+    
+    // Bridge participants connected to the relay into local room state, in order
+    let (bridge_tx, bridge_rx) = tokio::sync::mpsc::unbounded_channel();
+    relay_client.set_participant_callback(move |event| {
+        let _ = bridge_tx.send(event);
     }).await;
+    tokio::spawn(api::bridge_relay_participants(state.clone(), Arc::downgrade(&relay_client), bridge_rx));
     
     // Store the relay client in state
     state.set_relay_client(Some(relay_client.clone())).await;
@@ -848,6 +3398,28 @@ async fn connect_relay(
     // Wait a moment for registration
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     
+    // Let the relay serve the web client so remote participants never need to reach this machine
+    let files = api::web_client_files();
+    if !files.is_empty() {
+        if let Err(e) = relay_client.publish_client(files) {
+            tracing::warn!("Failed to publish web client to relay: {}", e);
+        }
+    }
+    
+    // Rooms the relay kept while we were away may have diverged from ours; merge
+    // them so joins and votes on either side survive, then tell the host
+    let mut merge_reports = Vec::new();
+    for relay_room in relay_client.get_rooms().await {
+        let room_id = relay_room.id.clone();
+        if let Some(report) = state.merge_room_from_relay(relay_room).filter(|r| !r.is_empty()) {
+            state.broadcast_room_update(&room_id).await;
+            merge_reports.push(report);
+        }
+    }
+    if !merge_reports.is_empty() {
+        let _ = app_handle.emit("relay-merged", merge_reports);
+    }
+
     // Sync all existing local rooms to the relay
     let rooms = state.get_rooms();
     for room in rooms {
@@ -862,6 +3434,38 @@ async fn connect_relay(
     Ok(format!("Connected to relay: {}", relay_url))
 }
 
+/// Reconnect to the relay after `dropped` lost its connection, backing off
+/// between attempts. Gives up once the host disconnects or connects again
+/// themselves, which replaces the dropped client. Boxed, as the new
+/// connection's disconnect callback leads back here.
+fn reconnect_relay(
+    app_handle: tauri::AppHandle,
+    state: Arc<AppState>,
+    dropped: std::sync::Weak<relay::RelayClient>,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let mut delay = RELAY_RECONNECT_DELAY;
+        loop {
+            tokio::time::sleep(delay).await;
+            let current = state.get_relay_client().await;
+            if !current.is_some_and(|client| std::ptr::eq(Arc::as_ptr(&client), dropped.as_ptr())) {
+                return;
+            }
+            match start_relay(app_handle.clone(), state.clone()).await {
+                Ok(_) => {
+                    tracing::info!("Reconnected to the relay");
+                    let _ = app_handle.emit("relay-reconnected", ());
+                    return;
+                }
+                Err(e) => {
+                    delay = (delay * 2).min(MAX_RELAY_RECONNECT_DELAY);
+                    tracing::warn!("Failed to reconnect to the relay, retrying in {:?}: {}", delay, e);
+                }
+            }
+        }
+    })
+}
+
 #[tauri::command]
 async fn disconnect_relay(
     state: tauri::State<'_, Arc<AppState>>,
@@ -887,3 +3491,214 @@ async fn get_relay_url(
         Ok(None)
     }
 }
+
+/// Direct link to one room through the relay, for sharing; None when not connected
+#[tauri::command]
+async fn get_relay_share_url(
+    state: tauri::State<'_, Arc<AppState>>,
+    room_id: String,
+) -> Result<Option<String>, String> {
+    if state.get_room(&room_id).is_none() {
+        return Err("Room not found".into());
+    }
+    match state.get_relay_client().await {
+        Some(client) => Ok(Some(client.get_room_url(&room_id).await)),
+        None => Ok(None),
+    }
+}
+
+// ============ Profiles ============
+
+#[tauri::command]
+async fn list_profiles() -> Result<Vec<String>, String> {
+    profiles::list_profiles()
+}
+
+#[tauri::command]
+async fn get_active_profile() -> Result<String, String> {
+    Ok(profiles::active_profile())
+}
+
+/// Switch to another profile (creating it if new), for the profile picker shown at startup.
+/// Refused while anyone is connected, since their rooms would disappear.
+#[tauri::command]
+async fn select_profile(
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<String, String> {
+    let name = name.trim().to_string();
+    profiles::validate_profile_name(&name)?;
+    if name == profiles::active_profile() {
+        return Ok(name);
+    }
+    if !state.connections.is_empty() {
+        return Err("Can't switch profiles while participants are connected".into());
+    }
+
+    state.clear_profile_data().await;
+    profiles::set_active_profile(&name)?;
+    load_profile(&state);
+    Ok(name)
+}
+
+// ============ Dashboard ============
+
+#[derive(Serialize)]
+struct RoomOverview {
+    room: room::Room,
+    vote_summary: room::VoteSummary,
+    /// Participants currently connected to this host (directly or bridged)
+    connected_count: usize,
+}
+
+#[derive(Serialize)]
+struct RelayStatus {
+    connected: bool,
+    url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Dashboard {
+    rooms: Vec<RoomOverview>,
+    relay: RelayStatus,
+    network: NetworkInfo,
+    share_url: String,
+    has_jira_config: bool,
+    has_stored_credentials: bool,
+}
+
+/// Everything the main screen shows, in one call. Network details use the cached
+/// public IP and firewall state; `get_network_info` refreshes them.
+#[tauri::command]
+async fn get_dashboard(state: tauri::State<'_, Arc<AppState>>) -> Result<Dashboard, String> {
+    let rooms = state
+        .get_rooms()
+        .into_iter()
+        .map(|room| RoomOverview {
+            vote_summary: room.get_vote_summary(),
+            connected_count: state.connection_count(&room.id),
+            room,
+        })
+        .collect();
+
+    let relay = match state.get_relay_client().await {
+        Some(client) => RelayStatus {
+            connected: client.is_connected().await,
+            url: Some(client.get_relay_url().await),
+        },
+        None => RelayStatus {
+            connected: false,
+            url: None,
+        },
+    };
+
+    Ok(Dashboard {
+        rooms,
+        relay,
+        network: build_network_info(&state, server_port(&state)),
+        share_url: state.get_share_url(),
+        has_jira_config: state.has_jira_config(),
+        has_stored_credentials: credentials::has_stored_credentials(),
+    })
+}
+
+/// URL of the localhost admin page, including its access token
+#[tauri::command]
+async fn get_admin_url(state: tauri::State<'_, Arc<AppState>>) -> Result<String, String> {
+    let port = *state.admin_port.read().unwrap();
+    if port == 0 {
+        return Err("Server is not running yet".into());
+    }
+    Ok(format!("http://127.0.0.1:{}/admin?token={}", port, state.admin_token))
+}
+
+/// Base URL an external controller (e.g. a Stream Deck "website" button) can
+/// call to run host actions on a room: append `/reveal`, `/hide`, `/reset` or `/next`
+#[tauri::command]
+async fn get_controller_url(state: tauri::State<'_, Arc<AppState>>, room_id: String) -> Result<String, String> {
+    let server_url = state.get_server_url();
+    if server_url.is_empty() {
+        return Err("Server is not running yet".into());
+    }
+    let token = state.controller_token(&room_id).ok_or("Room not found")?;
+    Ok(format!("{}/api/host/{}", server_url, token))
+}
+
+/// Check the persisted settings and room files: whether they parse, their
+/// schema versions, and whether a migration is pending
+#[tauri::command]
+async fn check_data_integrity(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<migrations::FileReport>, String> {
+    let mut reports = vec![settings::check_settings_file()?];
+    let key = state.storage_key();
+    reports.extend(state.get_settings().storage_backend.check_files(key.as_ref())?);
+    Ok(reports)
+}
+
+// ============ Settings ============
+
+/// Check a release channel (the one in the settings by default) for a newer
+/// version, with its changelog. Updates found while a session is under way are
+/// marked deferred unless the settings say to offer them anyway.
+#[tauri::command]
+async fn check_for_updates(
+    state: tauri::State<'_, Arc<AppState>>,
+    channel: Option<updates::ReleaseChannel>,
+) -> Result<updates::UpdateInfo, String> {
+    let settings = state.get_settings();
+    let manifest_url = settings
+        .update_manifest_url
+        .as_deref()
+        .filter(|url| !url.is_empty())
+        .unwrap_or(updates::DEFAULT_MANIFEST_URL);
+    let client = state.http_client()?;
+    let mut info = updates::check(&client, manifest_url, channel.unwrap_or(settings.update_channel)).await?;
+    info.deferred = info.available && !settings.prompt_updates_during_sessions && state.has_active_session();
+    Ok(info)
+}
+
+/// Seats taken per room and overall, against the seat policy's limits
+#[tauri::command]
+async fn get_usage(state: tauri::State<'_, Arc<AppState>>) -> Result<seats::Usage, String> {
+    Ok(state.get_usage())
+}
+
+#[tauri::command]
+async fn get_settings(state: tauri::State<'_, Arc<AppState>>) -> Result<settings::AppSettings, String> {
+    Ok(state.get_settings())
+}
+
+#[tauri::command]
+async fn save_settings(
+    state: tauri::State<'_, Arc<AppState>>,
+    mut settings: settings::AppSettings,
+) -> Result<(), String> {
+    // Encryption needs the master password, so it's only changed by set_room_encryption;
+    // the storage backend moves the rooms, so it's only changed by set_storage_backend
+    settings.encrypt_rooms = state.get_settings().encrypt_rooms;
+    settings.storage_backend = state.get_settings().storage_backend;
+    // The proxy password lives in the credentials store, so it's only changed by save_proxy_password
+    if let Some(proxy) = settings.proxy.as_mut() {
+        let current = state.get_settings().proxy;
+        proxy.password = current.as_ref().and_then(|current| current.password.clone());
+        proxy.password_stored = current.is_some_and(|current| current.password_stored);
+    }
+    // So do the GitHub, SMS and relay tokens, changed by their own save_*_token commands
+    let current = state.get_settings();
+    settings.github_token = current.github_token;
+    settings.github_token_stored = current.github_token_stored;
+    settings.sms_auth_token = current.sms_auth_token;
+    settings.sms_auth_token_stored = current.sms_auth_token_stored;
+    settings.relay_token = current.relay_token;
+    settings.relay_token_stored = current.relay_token_stored;
+
+    // Validate before persisting so a typo doesn't break every outbound request
+    settings.http_client()?;
+    settings.tls_connector()?;
+    settings.allowed_networks()?;
+    settings.issue_type_decks()?;
+    settings::save_settings(&settings)?;
+    state.set_settings(settings);
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use crate::room::{Participant, Room};
+use serde::Serialize;
+
+/// How the host's and the relay's copies of a room were reconciled, for
+/// telling the host what changed after the two ran apart
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MergeReport {
+    pub room_id: String,
+    /// Names of participants who joined through the relay
+    pub joined_via_relay: Vec<String>,
+    /// Names of participants connected here whom the relay never saw
+    pub kept_local: Vec<String>,
+    /// Names of participants who left the relay and aren't connected here
+    pub removed: Vec<String>,
+    /// Names of participants whose vote was taken from the relay, where it changed later
+    pub votes_from_relay: Vec<String>,
+    /// Names of participants whose vote here was kept over an older one on the relay
+    pub votes_kept_local: Vec<String>,
+}
+
+impl MergeReport {
+    /// Whether the two copies agreed on everyone
+    pub fn is_empty(&self) -> bool {
+        self.joined_via_relay.is_empty()
+            && self.kept_local.is_empty()
+            && self.removed.is_empty()
+            && self.votes_from_relay.is_empty()
+            && self.votes_kept_local.is_empty()
+    }
+}
+
+/// Merge the relay's participants into the host's copy of a room.
+///
+/// Someone on both sides keeps whichever vote changed last (`updated_at`);
+/// on a tie the relay's copy wins. Both copies must be on this machine's
+/// clock: `RelayClient` hands out the relay's rooms with their timestamps
+/// shifted by the clock offset it measured, and the relay shifts the host's
+/// onto its own clock when a room is synced, so skew between the two machines
+/// doesn't decide which vote wins. Someone only on the relay joined there.
+/// Someone only here is kept if `connected_here` says they're connected to
+/// this host, or is the host voting from the desktop app, and otherwise left
+/// the relay. Delegations made here stay; the
+/// relay's are added. Signals come from whichever side the participant is
+/// on. Everything else about the room is left alone.
+pub fn merge_participants(local: &mut Room, relay: Room, connected_here: impl Fn(&str) -> bool) -> MergeReport {
+    let mut report = MergeReport { room_id: local.id.clone(), ..Default::default() };
+    let mut ours = std::mem::take(&mut local.participants);
+
+    let mut participants = Vec::with_capacity(relay.participants.len());
+    for mut theirs in relay.participants {
+        match ours.iter().position(|p| p.id == theirs.id) {
+            Some(index) => {
+                let mine = ours.remove(index);
+                let same = (&mine.vote, &mine.risk_vote, mine.sitting_out)
+                    == (&theirs.vote, &theirs.risk_vote, theirs.sitting_out);
+                if !same && mine.updated_at > theirs.updated_at {
+                    theirs.vote = mine.vote;
+                    theirs.risk_vote = mine.risk_vote;
+                    theirs.sitting_out = mine.sitting_out;
+                    theirs.updated_at = mine.updated_at;
+                    report.votes_kept_local.push(theirs.name.clone());
+                } else if !same {
+                    report.votes_from_relay.push(theirs.name.clone());
+                }
+                // Identities never leave this host, so the relay's copy has none
+                theirs.identity = mine.identity;
+            }
+            None => report.joined_via_relay.push(theirs.name.clone()),
+        }
+        participants.push(theirs);
+    }
+    for participant in ours {
+        if participant.is_host || connected_here(&participant.id) {
+            report.kept_local.push(participant.name.clone());
+            participants.push(participant);
+        } else {
+            report.removed.push(participant.name);
+        }
+    }
+    local.participants = participants;
+
+    local.delegations.retain(|from, _| connected_here(from));
+    local.delegations.extend(relay.delegations);
+    // Signals are raised where the participant is connected
+    local.signals.retain(|id, _| connected_here(id));
+    local.signals.extend(relay.signals.into_iter().filter(|(id, _)| !connected_here(id)));
+    report
+}
+
+/// Merge a room this instance is saving to storage it shares with other
+/// instances (`ours`) with the copy stored there, which they may have changed
+/// since this instance last saved the room. `previous` is how the room was
+/// here then, and `written` what that save stored.
+///
+/// Someone on both sides keeps whichever copy's vote changed last
+/// (`updated_at`), as with the relay; on a tie ours wins. Someone only in the
+/// stored copy joined through another instance, unless they were in
+/// `previous` and so left here; someone only in ours joined here, unless they
+/// were `written` and so left through another instance. A participant's
+/// delegation, signals and first-vote time come from the same copy as their
+/// vote. The rest of the room is ours if this instance changed it since
+/// `previous`, and the stored copy's otherwise.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))] // Only the postgres backend is shared
+pub fn merge_into_stored(ours: &Room, stored: &Room, previous: Option<&Room>, written: Option<&Room>) -> Room {
+    let was_in = |room: Option<&Room>, id: &str| room.is_some_and(|room| room.participants.iter().any(|p| p.id == id));
+    let mut sources: Vec<(&Participant, &Room)> = Vec::new();
+    for theirs in &stored.participants {
+        match ours.participants.iter().find(|p| p.id == theirs.id) {
+            Some(mine) if mine.updated_at >= theirs.updated_at => sources.push((mine, ours)),
+            Some(_) => sources.push((theirs, stored)),
+            None if was_in(previous, &theirs.id) => {}
+            None => sources.push((theirs, stored)),
+        }
+    }
+    for mine in &ours.participants {
+        if !stored.participants.iter().any(|p| p.id == mine.id) && !was_in(written, &mine.id) {
+            sources.push((mine, ours));
+        }
+    }
+
+    let changed_here = previous.is_none_or(|previous| room_settings(previous) != room_settings(ours));
+    let mut merged = if changed_here { ours.clone() } else { stored.clone() };
+    merged.revision = ours.revision.max(stored.revision);
+    merged.round_first_votes.clear();
+    merged.delegations.clear();
+    merged.signals.clear();
+    for (participant, source) in &sources {
+        let id = &participant.id;
+        if let Some(&secs) = source.round_first_votes.get(id) {
+            merged.round_first_votes.insert(id.clone(), secs);
+        }
+        if let Some(delegate) = source.delegations.get(id) {
+            merged.delegations.insert(id.clone(), delegate.clone());
+        }
+        if let Some(signals) = source.signals.get(id) {
+            merged.signals.insert(id.clone(), signals.clone());
+        }
+    }
+    merged.participants = sources.into_iter().map(|(participant, _)| participant.clone()).collect();
+    merged.can_reveal = merged.quorum_met();
+    merged
+}
+
+/// Everything about a room but its participants and what's kept for each of
+/// them, to tell whether any of it changed
+fn room_settings(room: &Room) -> serde_json::Value {
+    let mut value = serde_json::to_value(room).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        for field in ["participants", "revision", "can_reveal", "round_first_votes", "delegations", "signals"] {
+            fields.remove(field);
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{participant, room_with, FakeEnvironment};
+
+    #[test]
+    fn the_later_vote_wins_and_both_sides_keep_their_joiners() {
+        let env = FakeEnvironment::new();
+        let mut local = room_with(&env, &["alice", "bob", "carol"]);
+        let mut relay = local.clone();
+
+        // While apart: bob votes on the relay and alice on both, here last;
+        // dave joins the relay, erin joins here and carol leaves the relay
+        relay.set_vote("bob", Some("5".into()));
+        env.advance(1);
+        relay.set_vote("alice", Some("8".into()));
+        relay.participants.retain(|p| p.id != "carol");
+        relay.add_participant(participant("dave", "dave"));
+        local.add_participant(participant("erin", "erin"));
+        env.advance(1);
+        local.set_vote("alice", Some("3".into()));
+
+        let report = merge_participants(&mut local, relay, |id| id == "erin");
+
+        let votes: Vec<_> = local.participants.iter().map(|p| (p.id.as_str(), p.vote.as_deref())).collect();
+        assert_eq!(votes, [("alice", Some("3")), ("bob", Some("5")), ("dave", None), ("erin", None)]);
+        assert_eq!(report.votes_kept_local, ["alice"]);
+        assert_eq!(report.votes_from_relay, ["bob"]);
+        assert_eq!(report.joined_via_relay, ["dave"]);
+        assert_eq!(report.kept_local, ["erin"]);
+        assert_eq!(report.removed, ["carol"]);
+    }
+
+    #[test]
+    fn instances_saving_a_room_at_once_keep_each_others_changes() {
+        let env = FakeEnvironment::new();
+        let saved = room_with(&env, &["alice", "bob", "carol"]);
+
+        // Both instances start from the saved room. There, bob votes, erin
+        // joins and the host locks the room, and that is stored first. Here,
+        // alice votes, dave joins and carol leaves.
+        let mut there = saved.clone();
+        there.set_vote("bob", Some("5".into()));
+        there.add_participant(participant("erin", "erin"));
+        there.locked = true;
+        let stored = merge_into_stored(&there, &saved, Some(&saved), Some(&saved));
+        env.advance(1);
+        let mut here = saved.clone();
+        here.set_vote("alice", Some("3".into()));
+        here.add_participant(participant("dave", "dave"));
+        here.participants.retain(|p| p.id != "carol");
+
+        let written = merge_into_stored(&here, &stored, Some(&saved), Some(&saved));
+
+        let votes: Vec<_> = written.participants.iter().map(|p| (p.id.as_str(), p.vote.as_deref())).collect();
+        assert_eq!(votes, [("alice", Some("3")), ("bob", Some("5")), ("erin", None), ("dave", None)]);
+        // Only participants changed here, so the room stays locked
+        assert!(written.locked);
+
+        // Here hears about erin, and she leaves there before here hears that
+        let (there_saved, there_written) = (there, stored);
+        let mut there = written.clone();
+        there.participants.retain(|p| p.id != "erin");
+        let stored = merge_into_stored(&there, &written, Some(&there_saved), Some(&there_written));
+        let here_saved = here.clone();
+        here.participants = written.participants.clone();
+        here.set_vote("dave", Some("8".into()));
+
+        let merged = merge_into_stored(&here, &stored, Some(&here_saved), Some(&written));
+
+        let votes: Vec<_> = merged.participants.iter().map(|p| (p.id.as_str(), p.vote.as_deref())).collect();
+        assert_eq!(votes, [("alice", Some("3")), ("bob", Some("5")), ("dave", Some("8"))]);
+    }
+}
@@ -0,0 +1,70 @@
+/// Largest message a participant may send. Votes, names and the like are a
+/// few hundred bytes at most.
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 16 * 1024;
+/// Largest message accepted from the relay, which sends whole rooms, ticket
+/// descriptions included
+pub const MAX_RELAY_MESSAGE_BYTES: usize = 8 * 1024 * 1024;
+/// Deepest nesting of arrays and objects accepted in an inbound message
+pub const MAX_JSON_DEPTH: usize = 32;
+
+/// Check an inbound text message against the size and nesting limits before
+/// it goes anywhere near serde_json. A message that fails means the sender
+/// is broken or hostile, and its connection should be closed.
+pub fn check_inbound(text: &str, max_bytes: usize) -> Result<(), String> {
+    if text.len() > max_bytes {
+        return Err(format!("message of {} bytes exceeds the {} byte limit", text.len(), max_bytes));
+    }
+    if exceeds_depth(text, MAX_JSON_DEPTH) {
+        return Err(format!("message nests deeper than {} levels", MAX_JSON_DEPTH));
+    }
+    Ok(())
+}
+
+/// Whether the JSON text nests arrays and objects deeper than `max_depth`.
+/// Brackets inside strings don't count; malformed JSON is left to the parser.
+fn exceeds_depth(text: &str, max_depth: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in text.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_and_deeply_nested_messages_are_refused() {
+        let vote = r#"{"type":"Vote","payload":{"vote":"5"}}"#;
+        assert!(check_inbound(vote, MAX_CLIENT_MESSAGE_BYTES).is_ok());
+        assert!(check_inbound(&"x".repeat(MAX_CLIENT_MESSAGE_BYTES + 1), MAX_CLIENT_MESSAGE_BYTES).is_err());
+
+        let nested = format!("{}{}", "[".repeat(MAX_JSON_DEPTH + 1), "]".repeat(MAX_JSON_DEPTH + 1));
+        assert!(check_inbound(&nested, MAX_CLIENT_MESSAGE_BYTES).is_err());
+        // Brackets in a name are just text
+        let name = format!(r#"{{"type":"Rename","payload":{{"name":"{}"}}}}"#, "[".repeat(100));
+        assert!(check_inbound(&name, MAX_CLIENT_MESSAGE_BYTES).is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A step upgrading data from one schema version to the next
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// Settings file migrations; `SETTINGS_MIGRATIONS[n]` upgrades version n to n + 1
+pub const SETTINGS_MIGRATIONS: &[Migration] = &[unversioned_to_v1];
+/// Persisted room migrations, applied to each room in the snapshot and journal
+pub const ROOM_MIGRATIONS: &[Migration] = &[unversioned_to_v1];
+
+/// Version 0 files predate versioning; the shape itself is unchanged in version 1
+fn unversioned_to_v1(value: Value) -> Result<Value, String> {
+    Ok(value)
+}
+
+/// Current version for a set of migrations
+pub fn current_version(migrations: &[Migration]) -> u32 {
+    migrations.len() as u32
+}
+
+/// Split a persisted value into its schema version and data.
+/// Unversioned data (written before versioning existed) is version 0.
+pub fn unwrap_versioned(value: Value) -> (u32, Value) {
+    if let Value::Object(map) = &value {
+        if let (Some(version), Some(data)) = (map.get("version").and_then(Value::as_u64), map.get("data")) {
+            return (version as u32, data.clone());
+        }
+    }
+    (0, value)
+}
+
+/// Wrap data with the current schema version
+pub fn wrap_versioned<T: Serialize>(data: &T, migrations: &[Migration]) -> Result<Value, String> {
+    let data = serde_json::to_value(data).map_err(|e| format!("Failed to serialize data: {}", e))?;
+    Ok(json!({ "version": current_version(migrations), "data": data }))
+}
+
+/// Upgrade data from `version` to the current version
+pub fn migrate(mut data: Value, version: u32, migrations: &[Migration]) -> Result<Value, String> {
+    let current = current_version(migrations);
+    if version > current {
+        return Err(format!(
+            "Data is from a newer version of the app (schema {}, this app understands up to {})",
+            version, current
+        ));
+    }
+
+    for (step, migration) in migrations.iter().enumerate().skip(version as usize) {
+        data = migration(data).map_err(|e| format!("Migration from schema {} failed: {}", step, e))?;
+    }
+    Ok(data)
+}
+
+/// Copy a file aside before it's rewritten in a newer format, so a bad
+/// migration can always be recovered from by hand
+pub fn backup_before_migration(path: &Path, version: u32) -> Result<PathBuf, String> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    let backup = PathBuf::from(backup);
+
+    if !backup.exists() {
+        fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+        tracing::info!("Backed up {} to {}", path.display(), backup.display());
+    }
+    Ok(backup)
+}
+
+/// Health of a single persisted file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// File doesn't exist yet (nothing saved)
+    Missing,
+    Ok,
+    /// Older schema; will be migrated on next load
+    NeedsMigration,
+    /// Written by a newer version of the app
+    NewerVersion,
+    /// Encrypted, and can't be checked without the master password
+    Locked,
+    Corrupt { error: String },
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub version: Option<u32>,
+    pub status: FileStatus,
+    /// Number of unreadable entries, for line-based files
+    pub bad_entries: usize,
+}
+
+/// Check a single-document file (settings, snapshot) against its migrations.
+/// `parse` turns the file contents into JSON (decrypting if needed).
+pub fn check_document<F>(path: &Path, migrations: &[Migration], parse: F) -> FileReport
+where
+    F: FnOnce(&str) -> Result<Value, String>,
+{
+    let report = |version, status| FileReport {
+        path: path.display().to_string(),
+        version,
+        status,
+        bad_entries: 0,
+    };
+
+    if !path.exists() {
+        return report(None, FileStatus::Missing);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(contents) => check_contents(&path.display().to_string(), &contents, migrations, parse),
+        Err(e) => report(None, FileStatus::Corrupt { error: e.to_string() }),
+    }
+}
+
+/// Check one stored document that isn't a file of its own, such as a database row;
+/// `label` says where it is
+pub fn check_contents<F>(label: &str, contents: &str, migrations: &[Migration], parse: F) -> FileReport
+where
+    F: FnOnce(&str) -> Result<Value, String>,
+{
+    let report = |version, status| FileReport {
+        path: label.to_string(),
+        version,
+        status,
+        bad_entries: 0,
+    };
+
+    let value = match parse(contents) {
+        Ok(value) => value,
+        Err(error) => return report(None, FileStatus::Corrupt { error }),
+    };
+
+    let (version, _) = unwrap_versioned(value);
+    let current = current_version(migrations);
+    let status = match version.cmp(&current) {
+        std::cmp::Ordering::Less => FileStatus::NeedsMigration,
+        std::cmp::Ordering::Equal => FileStatus::Ok,
+        std::cmp::Ordering::Greater => FileStatus::NewerVersion,
+    };
+    report(Some(version), status)
+}
@@ -0,0 +1,109 @@
+use crate::room::{ClientError, ErrorCode};
+use serde::Serialize;
+use std::fmt;
+
+/// Maximum length of a participant display name, in characters
+pub const MAX_NAME_LENGTH: usize = 40;
+/// Maximum length of a room name or branding title, in characters
+pub const MAX_ROOM_NAME_LENGTH: usize = 80;
+
+/// User input that was rejected, along with the field it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<ValidationError> for ClientError {
+    fn from(error: ValidationError) -> Self {
+        let code = if error.field == "name" { ErrorCode::InvalidName } else { ErrorCode::Other };
+        ClientError::new(code, error.message)
+    }
+}
+
+impl From<ValidationError> for String {
+    fn from(error: ValidationError) -> Self {
+        error.message
+    }
+}
+
+/// Hook for rejecting or rewriting participant names (e.g. profanity filtering).
+/// Receives a name that already passed the basic checks in `validate_participant_name`.
+pub trait NameFilter: Send + Sync {
+    fn filter(&self, name: &str) -> Result<String, String>;
+}
+
+/// Rejects names containing any of a list of blocked words (case-insensitive)
+pub struct BlockedWordsFilter {
+    words: Vec<String>,
+}
+
+impl BlockedWordsFilter {
+    pub fn new(words: &[String]) -> Self {
+        Self {
+            words: words
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+        }
+    }
+}
+
+impl NameFilter for BlockedWordsFilter {
+    fn filter(&self, name: &str) -> Result<String, String> {
+        let lowered = name.to_lowercase();
+        if self.words.iter().any(|word| lowered.contains(word.as_str())) {
+            return Err("That name is not allowed".to_string());
+        }
+        Ok(name.to_string())
+    }
+}
+
+/// Normalize a single-line text field and check its length and characters
+pub fn validate_text(field: &'static str, label: &str, text: &str, max_length: usize) -> Result<String, ValidationError> {
+    // Collapse runs of whitespace so "  Bob   Smith " becomes "Bob Smith"
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.is_empty() {
+        return Err(ValidationError::new(field, format!("{} cannot be empty", label)));
+    }
+    if text.chars().count() > max_length {
+        return Err(ValidationError::new(
+            field,
+            format!("{} cannot be longer than {} characters", label, max_length),
+        ));
+    }
+    // split_whitespace already removed tabs and newlines; this catches the rest
+    if text.chars().any(char::is_control) {
+        return Err(ValidationError::new(field, format!("{} contains invalid characters", label)));
+    }
+
+    Ok(text)
+}
+
+/// Normalize and validate a participant name, then run it through the filter
+pub fn validate_participant_name(name: &str, filter: &dyn NameFilter) -> Result<String, ValidationError> {
+    let name = validate_text("name", "Name", name, MAX_NAME_LENGTH)?;
+    filter.filter(&name).map_err(|message| ValidationError::new("name", message))
+}
+
+/// Normalize and validate a room name
+pub fn validate_room_name(name: &str) -> Result<String, ValidationError> {
+    validate_text("room_name", "Room name", name, MAX_ROOM_NAME_LENGTH)
+}
@@ -0,0 +1,213 @@
+use crate::credentials::get_data_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const ONBOARDING_FILE: &str = "onboarding.json";
+/// How long a check waits for a server to answer
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Guards load-modify-save of the onboarding file, as checks can run at once
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A setup step a new host is taken through, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    /// The participant server answers on this machine
+    ServerStarted,
+    /// The server answers on the LAN address participants are given
+    NetworkReachable,
+    /// The Jira credentials work
+    JiraConnected,
+    /// The relay answers, for participants outside the network
+    RelayTested,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 4] = [
+        OnboardingStep::ServerStarted,
+        OnboardingStep::NetworkReachable,
+        OnboardingStep::JiraConnected,
+        OnboardingStep::RelayTested,
+    ];
+
+    /// Jira and the relay are optional; the app is usable without either
+    pub fn skippable(self) -> bool {
+        matches!(self, OnboardingStep::JiraConnected | OnboardingStep::RelayTested)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepState {
+    pub step: OnboardingStep,
+    pub status: StepStatus,
+    /// What the last check found, or why it failed
+    pub detail: Option<String>,
+    /// Unix seconds of the last check or skip
+    pub checked_at: Option<u64>,
+}
+
+/// Progress through the setup steps, kept across restarts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnboardingState {
+    pub steps: Vec<StepState>,
+    /// The first step not yet passed or skipped
+    pub current: Option<OnboardingStep>,
+    pub complete: bool,
+}
+
+impl Default for OnboardingState {
+    fn default() -> Self {
+        let steps = OnboardingStep::ALL
+            .iter()
+            .map(|&step| StepState { step, status: StepStatus::Pending, detail: None, checked_at: None })
+            .collect();
+        let mut state = Self { steps, current: None, complete: false };
+        state.advance();
+        state
+    }
+}
+
+impl OnboardingState {
+    fn step_mut(&mut self, step: OnboardingStep) -> &mut StepState {
+        let index = self.steps.iter().position(|s| s.step == step).unwrap_or_else(|| {
+            self.steps.push(StepState { step, status: StepStatus::Pending, detail: None, checked_at: None });
+            self.steps.len() - 1
+        });
+        &mut self.steps[index]
+    }
+
+    /// Record a check's outcome. A step that passed before fails again if its
+    /// check does, since setups do break.
+    pub fn record(&mut self, step: OnboardingStep, result: Result<String, String>, now: u64) {
+        let entry = self.step_mut(step);
+        (entry.status, entry.detail) = match result {
+            Ok(detail) => (StepStatus::Passed, Some(detail)),
+            Err(error) => (StepStatus::Failed, Some(error)),
+        };
+        entry.checked_at = Some(now);
+        self.advance();
+    }
+
+    pub fn skip(&mut self, step: OnboardingStep, now: u64) -> Result<(), String> {
+        if !step.skippable() {
+            return Err("This step is needed for participants to join and can't be skipped".into());
+        }
+        let entry = self.step_mut(step);
+        if entry.status != StepStatus::Passed {
+            entry.status = StepStatus::Skipped;
+            entry.detail = None;
+            entry.checked_at = Some(now);
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn advance(&mut self) {
+        self.current = OnboardingStep::ALL.into_iter().find(|&step| {
+            !self
+                .steps
+                .iter()
+                .any(|s| s.step == step && matches!(s.status, StepStatus::Passed | StepStatus::Skipped))
+        });
+        self.complete = self.current.is_none();
+    }
+}
+
+pub fn load() -> Result<OnboardingState, String> {
+    let path = get_data_dir()?.join(ONBOARDING_FILE);
+    if !path.exists() {
+        return Ok(OnboardingState::default());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read onboarding state: {}", e))?;
+    let mut state: OnboardingState =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse onboarding state: {}", e))?;
+    state.advance();
+    Ok(state)
+}
+
+fn save(state: &OnboardingState) -> Result<(), String> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize onboarding state: {}", e))?;
+    fs::write(data_dir.join(ONBOARDING_FILE), json).map_err(|e| format!("Failed to write onboarding state: {}", e))
+}
+
+/// Load the onboarding state, change it and save it back
+pub fn update(change: impl FnOnce(&mut OnboardingState) -> Result<(), String>) -> Result<OnboardingState, String> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load()?;
+    change(&mut state)?;
+    save(&state)?;
+    Ok(state)
+}
+
+/// Check the participant server answers at `base_url`, bypassing any proxy
+/// as the participants on the network would
+pub async fn probe_server(base_url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(format!("{}/api/story-points", base_url))
+        .send()
+        .await
+        .map_err(|e| format!("No answer from {}: {}", base_url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} answered with {}", base_url, response.status()));
+    }
+    Ok(())
+}
+
+/// Check the relay's health endpoint answers
+pub async fn probe_relay(client: &reqwest::Client, health_url: &str) -> Result<(), String> {
+    let response = client
+        .get(health_url)
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach the relay: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("The relay answered with {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_steps_can_be_skipped_and_failures_hold_progress() {
+        let mut state = OnboardingState::default();
+        assert_eq!(state.current, Some(OnboardingStep::ServerStarted));
+
+        state.record(OnboardingStep::ServerStarted, Ok("Listening on port 3030".into()), 1);
+        state.record(OnboardingStep::NetworkReachable, Err("No answer".into()), 2);
+        assert_eq!(state.current, Some(OnboardingStep::NetworkReachable));
+        assert!(state.skip(OnboardingStep::NetworkReachable, 3).is_err());
+
+        state.record(OnboardingStep::NetworkReachable, Ok("Reachable".into()), 4);
+        state.skip(OnboardingStep::JiraConnected, 5).unwrap();
+        assert_eq!(state.current, Some(OnboardingStep::RelayTested));
+        state.record(OnboardingStep::RelayTested, Ok("Connected".into()), 6);
+        assert!(state.complete);
+
+        // A check that breaks later puts the step back in front of the host
+        state.record(OnboardingStep::ServerStarted, Err("No answer".into()), 7);
+        assert_eq!((state.current, state.complete), (Some(OnboardingStep::ServerStarted), false));
+    }
+}
@@ -0,0 +1,184 @@
+//! Per-client queues of outgoing WebSocket messages.
+//!
+//! Room updates are full snapshots, so a client that falls behind only needs
+//! the newest one: queuing a `RoomUpdate` replaces any older one still waiting.
+//! Everything else is kept in order up to a limit; a client that lets that many
+//! messages pile up is treated as stuck and its queue is closed.
+
+use crate::room::WsMessage;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Messages (besides the latest room update) a client may have waiting
+const MAX_PENDING_MESSAGES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+    /// The client is gone
+    Closed,
+    /// The client stopped reading and has been cut off
+    Overflow,
+}
+
+struct Queue {
+    messages: VecDeque<WsMessage>,
+    senders: usize,
+    /// Set on overflow or when the receiver is dropped
+    closed: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    notify: Notify,
+}
+
+/// Queue messages for one client; cheap to clone
+pub struct ClientSender {
+    shared: Arc<Shared>,
+}
+
+/// Receiving end, drained by the task writing to the client's socket
+pub struct ClientReceiver {
+    shared: Arc<Shared>,
+}
+
+pub fn channel() -> (ClientSender, ClientReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            messages: VecDeque::new(),
+            senders: 1,
+            closed: false,
+        }),
+        notify: Notify::new(),
+    });
+    (ClientSender { shared: shared.clone() }, ClientReceiver { shared })
+}
+
+impl ClientSender {
+    pub fn send(&self, message: WsMessage) -> Result<(), SendError> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.closed {
+            return Err(SendError::Closed);
+        }
+        if matches!(message, WsMessage::RoomUpdate { .. }) {
+            queue.messages.retain(|m| !matches!(m, WsMessage::RoomUpdate { .. }));
+        }
+        if queue.messages.len() >= MAX_PENDING_MESSAGES {
+            queue.closed = true;
+            queue.messages.clear();
+            drop(queue);
+            self.shared.notify.notify_one();
+            return Err(SendError::Overflow);
+        }
+        queue.messages.push_back(message);
+        drop(queue);
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// Cut the client off, e.g. because the participant reconnected elsewhere
+    pub fn close(&self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.closed = true;
+        queue.messages.clear();
+        drop(queue);
+        self.shared.notify.notify_one();
+    }
+
+    /// Whether both senders feed the same client
+    pub fn same_client(&self, other: &ClientSender) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+}
+
+impl Clone for ClientSender {
+    fn clone(&self) -> Self {
+        self.shared.queue.lock().unwrap().senders += 1;
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for ClientSender {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.senders -= 1;
+        if queue.senders == 0 {
+            drop(queue);
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+impl ClientReceiver {
+    /// Next message for the client. None once the queue was closed for
+    /// overflowing, or once every sender is gone and the queue is drained.
+    pub async fn recv(&mut self) -> Option<WsMessage> {
+        loop {
+            {
+                let mut queue = self.shared.queue.lock().unwrap();
+                if queue.closed {
+                    return None;
+                }
+                if let Some(message) = queue.messages.pop_front() {
+                    return Some(message);
+                }
+                if queue.senders == 0 {
+                    return None;
+                }
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Next message if one is already waiting
+    #[cfg(test)]
+    pub fn try_recv(&mut self) -> Option<WsMessage> {
+        self.shared.queue.lock().unwrap().messages.pop_front()
+    }
+}
+
+impl Drop for ClientReceiver {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.closed = true;
+        queue.messages.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::room_with;
+    use crate::test_support::FakeEnvironment;
+
+    fn update(revision: u64) -> WsMessage {
+        let mut room = room_with(&FakeEnvironment::new(), &[]);
+        room.revision = revision;
+        let checksum = room.checksum();
+        WsMessage::RoomUpdate { room, checksum, server_time: 0 }
+    }
+
+    #[test]
+    fn only_the_newest_room_update_is_kept() {
+        let (tx, mut rx) = channel();
+        tx.send(update(1)).unwrap();
+        tx.send(WsMessage::Pong).unwrap();
+        tx.send(update(2)).unwrap();
+
+        assert!(matches!(rx.try_recv(), Some(WsMessage::Pong)));
+        assert!(matches!(rx.try_recv(), Some(WsMessage::RoomUpdate { room, .. }) if room.revision == 2));
+        assert!(rx.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn clients_that_stop_reading_are_cut_off() {
+        let (tx, mut rx) = channel();
+        for _ in 0..MAX_PENDING_MESSAGES {
+            tx.send(WsMessage::Pong).unwrap();
+        }
+        assert_eq!(tx.send(WsMessage::Pong), Err(SendError::Overflow));
+        assert_eq!(tx.send(update(1)), Err(SendError::Closed));
+        assert!(rx.recv().await.is_none());
+    }
+}
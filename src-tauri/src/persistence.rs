@@ -0,0 +1,402 @@
+use crate::credentials::{get_data_dir, EncryptedData, MasterKey};
+use crate::migrations::{self, FileReport, FileStatus, ROOM_MIGRATIONS};
+use crate::room::Room;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+const PERSISTENCE_DIR: &str = "rooms";
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const JOURNAL_FILE: &str = "journal.jsonl";
+/// How often buffered journal entries are flushed and synced to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of journal entries after which the journal is folded into the snapshot
+const COMPACT_THRESHOLD: usize = 1000;
+
+/// A change to the persisted room state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// Full current state of a room (created or updated)
+    RoomSaved { room: Box<Room> },
+    RoomDeleted { room_id: String },
+}
+
+enum WriterCommand {
+    Record(JournalEvent),
+    /// Switch encryption on (with the key) or off, rewriting everything on disk
+    SetKey(Option<MasterKey>),
+}
+
+/// Write-behind room journal. Events are appended to a log by a background
+/// thread, so callers never wait on disk; the log is synced at most once per
+/// `FLUSH_INTERVAL` and periodically compacted into a snapshot.
+/// With a key, every snapshot and journal entry is encrypted.
+pub struct Journal {
+    tx: mpsc::Sender<WriterCommand>,
+}
+
+impl Journal {
+    /// Start the writer thread. `rooms` is the recovered state the journal continues from;
+    /// it is compacted straight away so the files on disk are in the current format.
+    pub fn start(dir: PathBuf, rooms: Vec<Room>, key: Option<MasterKey>) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create persistence dir: {}", e))?;
+        let rooms: HashMap<String, Room> = rooms.into_iter().map(|room| (room.id.clone(), room)).collect();
+        let journal = compact(&dir, &rooms, key.as_ref())?;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("room-journal".into())
+            .spawn(move || run_writer(dir, journal, rooms, key, rx))
+            .map_err(|e| format!("Failed to start journal writer: {}", e))?;
+
+        Ok(Self { tx })
+    }
+
+    pub fn record(&self, event: JournalEvent) {
+        self.send(WriterCommand::Record(event));
+    }
+
+    /// Encrypt (or stop encrypting) the stored rooms from now on
+    pub fn set_key(&self, key: Option<MasterKey>) {
+        self.send(WriterCommand::SetKey(key));
+    }
+
+    fn send(&self, command: WriterCommand) {
+        if self.tx.send(command).is_err() {
+            tracing::warn!("Room journal writer has stopped; change not persisted");
+        }
+    }
+}
+
+/// Serialize a value for disk, encrypting it if there is a key
+pub(crate) fn encode(value: &Value, key: Option<&MasterKey>) -> Result<String, String> {
+    let json = value.to_string();
+    match key {
+        Some(key) => serde_json::to_string(&key.encrypt(json.as_bytes())?)
+            .map_err(|e| format!("Failed to serialize encrypted data: {}", e)),
+        None => Ok(json),
+    }
+}
+
+/// Parse a value from disk, decrypting it if it was stored encrypted
+pub(crate) fn decode(text: &str, key: Option<&MasterKey>) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    match EncryptedData::from_value(&value) {
+        Some(encrypted) => {
+            let key = key.ok_or("Room storage is encrypted; unlock it with the master password")?;
+            let plain_text = key.decrypt(&encrypted)?;
+            serde_json::from_slice(&plain_text).map_err(|e| e.to_string())
+        }
+        None => Ok(value),
+    }
+}
+
+/// Whether the stored rooms are encrypted
+pub fn is_encrypted(dir: &Path) -> bool {
+    is_encrypted_file(&dir.join(SNAPSHOT_FILE))
+}
+
+/// Whether a stored document is encrypted
+pub fn is_encrypted_file(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|text| is_encrypted_text(&text))
+}
+
+/// Whether stored text, such as a database row, is encrypted
+pub(crate) fn is_encrypted_text(text: &str) -> bool {
+    serde_json::from_str::<Value>(text).is_ok_and(|value| EncryptedData::from_value(&value).is_some())
+}
+
+/// Directory that room state is persisted in
+pub fn persistence_dir() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(PERSISTENCE_DIR))
+}
+
+/// Rebuild room state from the snapshot plus any journaled events after it,
+/// migrating older formats (the originals are backed up first).
+/// A torn final journal line (from a crash mid-write) is skipped.
+pub fn recover(dir: &Path, key: Option<&MasterKey>) -> Result<Vec<Room>, String> {
+    let mut rooms: HashMap<String, Room> = HashMap::new();
+    let current = migrations::current_version(ROOM_MIGRATIONS);
+
+    let snapshot_path = dir.join(SNAPSHOT_FILE);
+    if snapshot_path.exists() {
+        let json = fs::read_to_string(&snapshot_path).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        let value = decode(&json, key).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        let (version, data) = migrations::unwrap_versioned(value);
+        if version < current {
+            migrations::backup_before_migration(&snapshot_path, version)?;
+        }
+
+        let saved: Vec<Value> = serde_json::from_value(data).map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+        for room in saved {
+            let room = parse_room(room, version)?;
+            rooms.insert(room.id.clone(), room);
+        }
+    }
+
+    let journal_path = dir.join(JOURNAL_FILE);
+    if journal_path.exists() {
+        let file = File::open(&journal_path).map_err(|e| format!("Failed to open journal: {}", e))?;
+        let mut backed_up = false;
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read journal: {}", e))?;
+            let entry = decode(&line, key).map(migrations::unwrap_versioned);
+            let (version, data) = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable journal entry {}: {}", index + 1, e);
+                    continue;
+                }
+            };
+            if version < current && !backed_up {
+                migrations::backup_before_migration(&journal_path, version)?;
+                backed_up = true;
+            }
+            apply(&mut rooms, parse_event(data, version)?);
+        }
+    }
+
+    Ok(rooms.into_values().collect())
+}
+
+/// A room as stored: as clients see it, plus each participant's identity
+/// token, so people are recognized when they rejoin after a restart
+pub(crate) fn stored_room(room: &Room) -> Result<Value, String> {
+    let mut value = serde_json::to_value(room).map_err(|e| format!("Failed to serialize room: {}", e))?;
+    if let Some(participants) = value.get_mut("participants").and_then(Value::as_array_mut) {
+        for (stored, participant) in participants.iter_mut().zip(&room.participants) {
+            if let (Some(stored), Some(identity)) = (stored.as_object_mut(), &participant.identity) {
+                stored.insert("identity".into(), Value::String(identity.clone()));
+            }
+        }
+    }
+    Ok(value)
+}
+
+fn stored_event(event: &JournalEvent) -> Result<Value, String> {
+    let mut value = serde_json::to_value(event).map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+    if let JournalEvent::RoomSaved { room } = event {
+        value["room"] = stored_room(room)?;
+    }
+    Ok(value)
+}
+
+pub(crate) fn parse_room(value: Value, version: u32) -> Result<Room, String> {
+    let value = migrations::migrate(value, version, ROOM_MIGRATIONS)?;
+    let identities: Vec<Option<String>> = value
+        .get("participants")
+        .and_then(Value::as_array)
+        .map(|participants| {
+            participants
+                .iter()
+                .map(|p| p.get("identity").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut room: Room = serde_json::from_value(value).map_err(|e| format!("Failed to parse saved room: {}", e))?;
+    for (participant, identity) in room.participants.iter_mut().zip(identities) {
+        participant.identity = identity;
+    }
+    Ok(room)
+}
+
+fn parse_event(mut value: Value, version: u32) -> Result<JournalEvent, String> {
+    if let Some(room) = value.get_mut("room") {
+        let room = parse_room(room.take(), version)?;
+        return Ok(JournalEvent::RoomSaved { room: Box::new(room) });
+    }
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse journal entry: {}", e))
+}
+
+/// Report on the persisted room files. Encrypted entries can only be checked with the key.
+pub fn check_files(dir: &Path, key: Option<&MasterKey>) -> Vec<FileReport> {
+    if key.is_none() && is_encrypted(dir) {
+        return [SNAPSHOT_FILE, JOURNAL_FILE]
+            .iter()
+            .map(|file| FileReport {
+                path: dir.join(file).display().to_string(),
+                version: None,
+                status: FileStatus::Locked,
+                bad_entries: 0,
+            })
+            .collect();
+    }
+
+    let mut reports = vec![migrations::check_document(&dir.join(SNAPSHOT_FILE), ROOM_MIGRATIONS, |text| {
+        decode(text, key)
+    })];
+
+    let journal_path = dir.join(JOURNAL_FILE);
+    let mut report = FileReport {
+        path: journal_path.display().to_string(),
+        version: None,
+        status: FileStatus::Missing,
+        bad_entries: 0,
+    };
+    if journal_path.exists() {
+        report.status = FileStatus::Ok;
+        match fs::read_to_string(&journal_path) {
+            Ok(contents) => {
+                let current = migrations::current_version(ROOM_MIGRATIONS);
+                for line in contents.lines() {
+                    match decode(line, key).map(migrations::unwrap_versioned) {
+                        Ok((version, data)) => {
+                            report.version = Some(report.version.map_or(version, |v| v.max(version)));
+                            if version > current {
+                                report.status = FileStatus::NewerVersion;
+                            } else if parse_event(data, version).is_err() {
+                                report.bad_entries += 1;
+                            }
+                        }
+                        Err(_) => report.bad_entries += 1,
+                    }
+                }
+                if matches!(report.status, FileStatus::Ok) && report.version.is_some_and(|v| v < current) {
+                    report.status = FileStatus::NeedsMigration;
+                }
+            }
+            Err(e) => report.status = FileStatus::Corrupt { error: e.to_string() },
+        }
+    }
+    reports.push(report);
+
+    reports
+}
+
+fn apply(rooms: &mut HashMap<String, Room>, event: JournalEvent) {
+    match event {
+        JournalEvent::RoomSaved { room } => {
+            rooms.insert(room.id.clone(), *room);
+        }
+        JournalEvent::RoomDeleted { room_id } => {
+            rooms.remove(&room_id);
+        }
+    }
+}
+
+/// Start an empty journal, replacing any existing one
+fn create_journal(dir: &Path) -> Result<BufWriter<File>, String> {
+    File::create(dir.join(JOURNAL_FILE))
+        .map(BufWriter::new)
+        .map_err(|e| format!("Failed to create journal: {}", e))
+}
+
+fn run_writer(
+    dir: PathBuf,
+    mut journal: BufWriter<File>,
+    mut rooms: HashMap<String, Room>,
+    mut key: Option<MasterKey>,
+    rx: mpsc::Receiver<WriterCommand>,
+) {
+    let mut dirty = false;
+    let mut entries = 0;
+    let mut last_flush = Instant::now();
+
+    loop {
+        let disconnected = match rx.recv_timeout(FLUSH_INTERVAL) {
+            Ok(WriterCommand::SetKey(new_key)) => {
+                key = new_key;
+                // Rewrite everything so no data is left behind in the old format
+                entries = COMPACT_THRESHOLD;
+                false
+            }
+            Ok(WriterCommand::Record(event)) => {
+                let line = stored_event(&event)
+                    .and_then(|value| migrations::wrap_versioned(&value, ROOM_MIGRATIONS))
+                    .and_then(|entry| encode(&entry, key.as_ref()));
+                match line {
+                    Ok(line) => {
+                        if let Err(e) = writeln!(journal, "{}", line) {
+                            tracing::error!("Failed to append to room journal: {}", e);
+                        }
+                        dirty = true;
+                        entries += 1;
+                    }
+                    Err(e) => tracing::error!("Failed to serialize journal entry: {}", e),
+                }
+                apply(&mut rooms, event);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        if dirty && (disconnected || last_flush.elapsed() >= FLUSH_INTERVAL) {
+            if let Err(e) = sync(&mut journal) {
+                tracing::error!("Failed to sync room journal: {}", e);
+            }
+            dirty = false;
+            last_flush = Instant::now();
+        }
+
+        if entries >= COMPACT_THRESHOLD {
+            // Empty the buffer first so nothing stale lands in the fresh journal
+            if let Err(e) = journal.flush() {
+                tracing::error!("Failed to flush room journal: {}", e);
+            }
+            match compact(&dir, &rooms, key.as_ref()) {
+                Ok(fresh) => {
+                    journal = fresh;
+                    entries = 0;
+                }
+                Err(e) => tracing::error!("Failed to compact room journal: {}", e),
+            }
+        }
+
+        if disconnected {
+            break;
+        }
+    }
+}
+
+fn sync(journal: &mut BufWriter<File>) -> std::io::Result<()> {
+    journal.flush()?;
+    journal.get_ref().sync_data()
+}
+
+/// Write the full state to a new snapshot and start an empty journal.
+/// The snapshot is replaced atomically; if we crash before the journal is
+/// truncated, replaying it over the new snapshot is harmless.
+fn compact(dir: &Path, rooms: &HashMap<String, Room>, key: Option<&MasterKey>) -> Result<BufWriter<File>, String> {
+    let snapshot = rooms.values().map(stored_room).collect::<Result<Vec<Value>, String>>()?;
+    let json = encode(&migrations::wrap_versioned(&snapshot, ROOM_MIGRATIONS)?, key)?;
+
+    let tmp_path = dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+    let mut file = File::create(&tmp_path).map_err(|e| format!("Failed to create snapshot: {}", e))?;
+    file.write_all(json.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    fs::rename(&tmp_path, dir.join(SNAPSHOT_FILE)).map_err(|e| format!("Failed to replace snapshot: {}", e))?;
+
+    create_journal(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{participant, room_with, FakeEnvironment};
+
+    #[test]
+    fn participants_are_saved_with_their_identities_and_votes() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &[]);
+        room.add_participant(participant("alice", "Alice").with_identity(Some("alice-token".into())));
+        room.set_vote("alice", Some("5".into()));
+        let dir = std::env::temp_dir().join(format!("scrum-poker-persistence-{}", room.id));
+
+        // Starting the journal writes the snapshot before returning
+        let journal = Journal::start(dir.clone(), vec![room], None).unwrap();
+        let recovered = recover(&dir, None);
+        drop(journal);
+        let _ = fs::remove_dir_all(&dir);
+
+        let alice = recovered.unwrap()[0].participants.iter().find(|p| p.id == "alice").cloned().unwrap();
+        assert_eq!((alice.identity.as_deref(), alice.vote.as_deref()), (Some("alice-token"), Some("5")));
+    }
+}
@@ -0,0 +1,202 @@
+use serde::Serialize;
+
+/// Router makers we have port-forwarding directions for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouterVendor {
+    Asus,
+    Avm,
+    DLink,
+    Linksys,
+    Netgear,
+    TpLink,
+    Ubiquiti,
+}
+
+/// Words in a UPnP device description's manufacturer/model fields, per vendor
+const DESCRIPTION_NAMES: &[(&str, RouterVendor)] = &[
+    ("asus", RouterVendor::Asus),
+    ("avm", RouterVendor::Avm),
+    ("fritz", RouterVendor::Avm),
+    ("d-link", RouterVendor::DLink),
+    ("linksys", RouterVendor::Linksys),
+    ("netgear", RouterVendor::Netgear),
+    ("tp-link", RouterVendor::TpLink),
+    ("ubiquiti", RouterVendor::Ubiquiti),
+];
+
+/// First three bytes of the MAC address (the OUI) of some common router makers
+const MAC_PREFIXES: &[(&str, RouterVendor)] = &[
+    ("04:d4:c4", RouterVendor::Asus),
+    ("2c:fd:a1", RouterVendor::Asus),
+    ("3c:a6:2f", RouterVendor::Avm),
+    ("c8:0e:14", RouterVendor::Avm),
+    ("1c:7e:e5", RouterVendor::DLink),
+    ("c4:a8:1d", RouterVendor::DLink),
+    ("20:aa:4b", RouterVendor::Linksys),
+    ("c0:56:27", RouterVendor::Linksys),
+    ("a0:40:a0", RouterVendor::Netgear),
+    ("9c:3d:cf", RouterVendor::Netgear),
+    ("50:c7:bf", RouterVendor::TpLink),
+    ("f4:f2:6d", RouterVendor::TpLink),
+    ("24:5a:4c", RouterVendor::Ubiquiti),
+    ("fc:ec:da", RouterVendor::Ubiquiti),
+];
+
+impl RouterVendor {
+    pub fn name(self) -> &'static str {
+        match self {
+            RouterVendor::Asus => "ASUS",
+            RouterVendor::Avm => "AVM FRITZ!Box",
+            RouterVendor::DLink => "D-Link",
+            RouterVendor::Linksys => "Linksys",
+            RouterVendor::Netgear => "NETGEAR",
+            RouterVendor::TpLink => "TP-Link",
+            RouterVendor::Ubiquiti => "Ubiquiti UniFi",
+        }
+    }
+
+    /// Where the port-forwarding page lives in the vendor's admin UI
+    fn menu_path(self) -> &'static str {
+        match self {
+            RouterVendor::Asus => "WAN > Virtual Server / Port Forwarding",
+            RouterVendor::Avm => "Internet > Permit Access > Port Sharing",
+            RouterVendor::DLink => "Advanced > Port Forwarding",
+            RouterVendor::Linksys => "Security > Apps and Gaming > Single Port Forwarding",
+            RouterVendor::Netgear => "Advanced > Advanced Setup > Port Forwarding / Port Triggering",
+            RouterVendor::TpLink => "Advanced > NAT Forwarding > Virtual Servers",
+            RouterVendor::Ubiquiti => "Settings > Routing > Port Forwarding",
+        }
+    }
+
+    /// Guess the vendor from the gateway's UPnP device description (XML)
+    pub fn from_description(description: &str) -> Option<Self> {
+        let description = description.to_lowercase();
+        DESCRIPTION_NAMES
+            .iter()
+            .find(|(name, _)| description.contains(name))
+            .map(|(_, vendor)| *vendor)
+    }
+
+    /// Guess the vendor from the gateway's MAC address, in any common notation
+    pub fn from_mac(mac: &str) -> Option<Self> {
+        let mac = mac.trim().to_lowercase().replace('-', ":");
+        MAC_PREFIXES
+            .iter()
+            .find(|(prefix, _)| mac.starts_with(prefix))
+            .map(|(_, vendor)| *vendor)
+    }
+}
+
+/// How the router vendor was worked out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectedFrom {
+    Upnp,
+    MacAddress,
+}
+
+/// Manual port-forwarding directions for when UPnP fails, with every value
+/// the router's admin page will ask for
+#[derive(Debug, Clone, Serialize)]
+pub struct PortForwardInstructions {
+    pub vendor: Option<RouterVendor>,
+    /// Display name of the vendor, e.g. "TP-Link"
+    pub vendor_name: Option<String>,
+    pub detected_from: Option<DetectedFrom>,
+    pub gateway_ip: Option<String>,
+    /// Router admin page, when the gateway address is known
+    pub admin_url: Option<String>,
+    /// This computer's address on the local network, where traffic should go
+    pub internal_ip: String,
+    pub port: u16,
+    pub protocol: &'static str,
+    pub steps: Vec<String>,
+}
+
+pub fn instructions(
+    detected: Option<(RouterVendor, DetectedFrom)>,
+    gateway_ip: Option<String>,
+    internal_ip: String,
+    port: u16,
+) -> PortForwardInstructions {
+    let vendor = detected.map(|(vendor, _)| vendor);
+    let admin_url = gateway_ip.as_ref().map(|ip| format!("http://{}", ip));
+
+    let mut steps = vec![match &admin_url {
+        Some(url) => format!("Open {} in a browser and sign in to your router", url),
+        None => "Open your router's admin page (often http://192.168.0.1 or http://192.168.1.1) and sign in".into(),
+    }];
+    steps.push(match vendor {
+        Some(vendor) => format!("Go to {}", vendor.menu_path()),
+        None => "Find the Port Forwarding page (sometimes called Virtual Server or NAT)".into(),
+    });
+    steps.push(format!(
+        "Add a rule: external port {port}, internal port {port}, protocol TCP, forwarded to {internal_ip}"
+    ));
+    steps.push("Name it \"Scrum Poker\", save, and apply the changes".into());
+    steps.push(format!(
+        "Give {internal_ip} a fixed address (DHCP reservation) on the router, so the rule keeps working after a restart"
+    ));
+
+    PortForwardInstructions {
+        vendor,
+        vendor_name: vendor.map(|vendor| vendor.name().to_string()),
+        detected_from: detected.map(|(_, from)| from),
+        gateway_ip,
+        admin_url,
+        internal_ip,
+        port,
+        protocol: "TCP",
+        steps,
+    }
+}
+
+/// MAC address listed for an IP in `arp -a` output (Windows, macOS and Linux formats)
+pub fn mac_from_arp_output(output: &str, ip: &str) -> Option<String> {
+    output
+        .lines()
+        .find(|line| {
+            line.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .any(|field| field == ip)
+        })?
+        .split_whitespace()
+        .find(|field| field.len() >= 11 && field.chars().filter(|c| *c == ':' || *c == '-').count() == 5)
+        .map(str::to_string)
+}
+
+/// Default gateway from the contents of /proc/net/route (Linux), where
+/// addresses are little-endian hex
+pub fn gateway_from_proc_route(table: &str) -> Option<String> {
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(1) != Some(&"00000000") {
+            return None;
+        }
+        let gateway = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+        (gateway != 0).then(|| std::net::Ipv4Addr::from(gateway.to_le_bytes()).to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vendors_are_recognized_from_upnp_or_mac() {
+        let description = "<device><manufacturer>TP-LINK</manufacturer><modelName>Archer C7</modelName></device>";
+        assert_eq!(RouterVendor::from_description(description), Some(RouterVendor::TpLink));
+        assert_eq!(RouterVendor::from_mac("A0-40-A0-12-34-56"), Some(RouterVendor::Netgear));
+        assert_eq!(RouterVendor::from_mac("00:11:22:33:44:55"), None);
+
+        let arp = "Interface: 192.168.1.20 --- 0x5\n  Internet Address      Physical Address      Type\n  192.168.1.1           a0-40-a0-12-34-56     dynamic\n";
+        assert_eq!(mac_from_arp_output(arp, "192.168.1.1").as_deref(), Some("a0-40-a0-12-34-56"));
+        let route = "Iface\tDestination\tGateway\tFlags\neth0\t00000000\t0101A8C0\t0003\n";
+        assert_eq!(gateway_from_proc_route(route).as_deref(), Some("192.168.1.1"));
+
+        let steps = instructions(Some((RouterVendor::TpLink, DetectedFrom::Upnp)), Some("192.168.1.1".into()), "192.168.1.20".into(), 3030);
+        assert_eq!(steps.admin_url.as_deref(), Some("http://192.168.1.1"));
+        assert!(steps.steps[1].contains("Virtual Servers"));
+        assert!(steps.steps[2].contains("3030") && steps.steps[2].contains("192.168.1.20"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Profile whose data lives directly in the app data directory, as before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+/// Subdirectory holding every other profile's data
+const PROFILES_DIR: &str = "profiles";
+const MAX_PROFILE_NAME_LENGTH: usize = 32;
+
+/// Profile this process is running as. Each profile has its own settings,
+/// credentials, rooms and relay connection, so two facilitators sharing a
+/// machine can each run their own instance without seeing each other's sessions.
+static ACTIVE_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Root app data directory, shared by all profiles
+pub fn base_data_dir() -> Result<PathBuf, String> {
+    directories::ProjectDirs::from("com", "scrumpoker", "ScrumPoker")
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .ok_or_else(|| "Could not determine data directory".to_string())
+}
+
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Switch the data directory to another profile, creating it if needed
+pub fn set_active_profile(name: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    let dir = profile_dir(name)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profile dir: {}", e))?;
+    *ACTIVE_PROFILE.write().unwrap() = Some(name.to_string());
+    tracing::info!("Using profile {} ({})", name, dir.display());
+    Ok(())
+}
+
+/// Data directory for a profile
+pub fn profile_dir(name: &str) -> Result<PathBuf, String> {
+    let base = base_data_dir()?;
+    if name == DEFAULT_PROFILE {
+        Ok(base)
+    } else {
+        Ok(base.join(PROFILES_DIR).join(name))
+    }
+}
+
+/// Profiles that exist on disk, default first
+pub fn list_profiles() -> Result<Vec<String>, String> {
+    let mut profiles: Vec<String> = match fs::read_dir(base_data_dir()?.join(PROFILES_DIR)) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| validate_profile_name(name).is_ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(profiles)
+}
+
+/// Profile names become directory names, so keep them to a safe character set
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME_LENGTH {
+        return Err(format!("Profile name must be 1-{} characters", MAX_PROFILE_NAME_LENGTH));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("Profile name may only contain letters, digits, '-' and '_'".into());
+    }
+    Ok(())
+}
+
+/// Profile requested on the command line (`--profile <name>`) or via `SCRUM_POKER_PROFILE`
+pub fn requested_profile() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    std::env::var("SCRUM_POKER_PROFILE").ok().filter(|name| !name.is_empty())
+}
@@ -0,0 +1,400 @@
+use crate::room::{now_secs, HistoryEvent, Room, TicketSignal};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Escape text for inclusion in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_number(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.1}", v)).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_seconds(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.0}s", v)).unwrap_or_else(|| "-".to_string())
+}
+
+/// e.g. "needs design (2), security review (1)"
+fn describe_signals(signals: &BTreeMap<TicketSignal, usize>) -> String {
+    signals
+        .iter()
+        .map(|(signal, count)| format!("{} ({})", signal.label(), count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// One-line description of a session event, as shown in the session log
+pub fn describe_event(event: &HistoryEvent) -> String {
+    match event {
+        HistoryEvent::TicketSplit { ticket_key, sub_ticket_keys } => format!(
+            "{} was split into {}",
+            ticket_key,
+            sub_ticket_keys.join(", ")
+        ),
+        HistoryEvent::IssueCreated { ticket_key } => format!("{} was created", ticket_key),
+        HistoryEvent::TicketTransferred { ticket_key, from_room, to_room } => {
+            format!("{} was moved from {} to {}", ticket_key, from_room, to_room)
+        }
+        HistoryEvent::TicketParked { ticket_key, minutes } => {
+            format!("{} was parked after running over its {} minute deadline", ticket_key, minutes)
+        }
+        HistoryEvent::DeckSwitched { ticket_key, issue_type, deck } => match issue_type {
+            Some(issue_type) => format!("Switched to the {} deck for {}: {}", issue_type, ticket_key, deck.join(" ")),
+            None => format!("Went back to the room's deck for {}: {}", ticket_key, deck.join(" ")),
+        },
+        HistoryEvent::VotesReset { ticket_key, voted_count } => format!(
+            "Votes reset for {} before being revealed ({} votes)",
+            ticket_key.as_deref().unwrap_or("untitled round"),
+            voted_count
+        ),
+        HistoryEvent::EstimateOverridden { ticket_key, value, reason, average } => format!(
+            "Host overrode the estimate for {} to {} (average was {}): {}",
+            ticket_key.as_deref().unwrap_or("untitled round"),
+            value,
+            format_number(*average),
+            reason
+        ),
+        HistoryEvent::VotesRevealed { ticket_key, voted_count, average, average_time_to_vote_secs, risk_average, tags, batch, signals, .. } => {
+            let mut line = format!(
+                "Votes revealed for {}: {} votes, average {}, time to vote {}",
+                ticket_key.as_deref().unwrap_or("untitled round"),
+                voted_count,
+                format_number(*average),
+                format_seconds(*average_time_to_vote_secs)
+            );
+            if risk_average.is_some() {
+                let _ = write!(line, ", risk {}", format_number(*risk_average));
+            }
+            if !tags.is_empty() {
+                let _ = write!(line, " [{}]", tags.join(", "));
+            }
+            if !batch.is_empty() {
+                let _ = write!(line, " (batch of {})", batch.len());
+            }
+            if !signals.is_empty() {
+                let _ = write!(line, ", flagged {}", describe_signals(signals));
+            }
+            line
+        }
+    }
+}
+
+/// Render an HTML summary of a room's session
+pub fn render_recap_html(room: &Room) -> String {
+    let mut html = String::new();
+    let duration_secs = match &room.session_summary {
+        Some(summary) => summary.duration_secs,
+        None => now_secs().saturating_sub(room.created_at),
+    };
+    let duration_minutes = duration_secs / 60;
+
+    let _ = write!(
+        html,
+        "<html><body style=\"font-family: sans-serif\">\
+         <h1>Session recap: {}</h1>\
+         <p>Started: {} &middot; Duration: {} min &middot; Participants: {}</p>",
+        escape_html(&room.name),
+        escape_html(&room.time_zone.format(room.created_at)),
+        duration_minutes,
+        room.participants.len()
+    );
+
+    if let Some(summary) = &room.session_summary {
+        let _ = write!(
+            html,
+            "<p>Tickets estimated: {} &middot; Total points: {:.1} &middot; Estimates overridden: {} &middot; Average time to vote: {}</p>",
+            summary.tickets_estimated.len(),
+            summary.total_points,
+            summary.overrides,
+            format_seconds(summary.average_time_to_vote_secs)
+        );
+    }
+
+    let stats = room.get_participant_stats();
+    if !stats.is_empty() {
+        html.push_str(
+            "<h2>Participants</h2><table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+             <tr><th>Name</th><th>Rounds voted</th><th>Average</th><th>Team average</th>\
+             <th>Highest</th><th>Lowest</th><th>Time to vote</th></tr>",
+        );
+        for s in &stats {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&s.name),
+                s.rounds_voted,
+                format_number(s.average_vote),
+                format_number(s.team_average),
+                s.times_highest,
+                s.times_lowest,
+                format_seconds(s.average_time_to_vote_secs)
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    if !room.history.is_empty() {
+        html.push_str("<h2>Session log</h2><ul>");
+        for entry in &room.history {
+            let line = describe_event(&entry.event);
+            let _ = write!(
+                html,
+                "<li>{}: {}</li>",
+                room.time_zone.format_time(entry.timestamp),
+                escape_html(&line)
+            );
+        }
+        html.push_str("</ul>");
+    }
+
+    if !room.ticket_queue.is_empty() {
+        html.push_str("<h2>Still to estimate</h2><ul>");
+        for ticket in &room.ticket_queue {
+            let _ = write!(
+                html,
+                "<li><a href=\"{}\">{}</a>: {}</li>",
+                escape_html(&ticket.url),
+                escape_html(&ticket.key),
+                escape_html(&ticket.summary)
+            );
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// Build an unsent email (.eml) containing the recap, addressed to the recipients.
+/// Mail clients open `X-Unsent` messages as drafts ready to send.
+pub fn build_recap_email(room: &Room, recipients: &[String]) -> String {
+    let subject = format!("Session recap: {}", room.name);
+    let body = general_purpose::STANDARD.encode(render_recap_html(room));
+
+    let mut email = String::new();
+    let _ = write!(
+        email,
+        "To: {}\r\n\
+         Subject: =?UTF-8?B?{}?=\r\n\
+         X-Unsent: 1\r\n\
+         MIME-Version: 1.0\r\n\
+         Content-Type: text/html; charset=utf-8\r\n\
+         Content-Transfer-Encoding: base64\r\n\r\n",
+        recipients.join(", "),
+        general_purpose::STANDARD.encode(subject)
+    );
+
+    // Base64 bodies are wrapped at 76 characters per RFC 2045
+    for chunk in body.as_bytes().chunks(76) {
+        email.push_str(&String::from_utf8_lossy(chunk));
+        email.push_str("\r\n");
+    }
+
+    email
+}
+
+/// Write the recap email into `dir`, returning the path of the .eml file
+pub fn write_recap_email(room: &Room, recipients: &[String], dir: &Path) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create recap dir: {}", e))?;
+
+    let safe_name: String = room
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("recap_{}_{}.eml", safe_name, now_secs()));
+
+    std::fs::write(&path, build_recap_email(room, recipients))
+        .map_err(|e| format!("Failed to write recap email: {}", e))?;
+
+    Ok(path)
+}
+
+/// Text format of a round summary, for pasting into tickets and chats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryFormat {
+    #[default]
+    Markdown,
+    /// Jira's wiki markup, as used in comments and descriptions
+    JiraWiki,
+    PlainText,
+}
+
+impl SummaryFormat {
+    /// Escape characters with a meaning in the format's tables and emphasis
+    fn escape(self, text: &str) -> String {
+        let special: &[char] = match self {
+            SummaryFormat::Markdown => &['\\', '|', '*', '_', '`'],
+            SummaryFormat::JiraWiki => &['|', '*', '_', '{', '['],
+            SummaryFormat::PlainText => &[],
+        };
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if special.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+/// Summary of the room's last reveal: who voted what, the numbers and the
+/// estimate the room's method arrives at
+pub fn format_round_summary(room: &Room, format: SummaryFormat) -> Result<String, String> {
+    let Some(HistoryEvent::VotesRevealed { ticket_key, voted_count, average, risk_average, votes, tags, batch, signals, .. }) = room
+        .history
+        .iter()
+        .rev()
+        .map(|entry| &entry.event)
+        .find(|event| matches!(event, HistoryEvent::VotesRevealed { .. }))
+    else {
+        return Err("No votes have been revealed in this room yet".into());
+    };
+
+    let title = if !batch.is_empty() {
+        format!("Estimate for {}", batch.join(", "))
+    } else {
+        match (ticket_key, &room.current_ticket) {
+            (Some(key), Some(ticket)) if ticket.key == *key => format!("Estimate for {}: {}", key, ticket.summary),
+            (Some(key), _) => format!("Estimate for {}", key),
+            (None, _) => "Estimate for an untitled round".to_string(),
+        }
+    };
+
+    let rows: Vec<(String, String)> = votes
+        .iter()
+        .map(|v| {
+            let vote = if v.on_behalf_of.is_empty() {
+                v.vote.clone()
+            } else {
+                format!("{} (also for {})", v.vote, v.on_behalf_of.join(", "))
+            };
+            (format.escape(&v.name), format.escape(&vote))
+        })
+        .collect();
+
+    let numeric: Vec<f64> = votes.iter().filter_map(|v| v.vote.parse::<f64>().ok()).collect();
+    // The host's override, if one came after the reveal, stands in for the method's estimate
+    let overridden = room
+        .history
+        .iter()
+        .rev()
+        .map(|entry| &entry.event)
+        .take_while(|event| !matches!(event, HistoryEvent::VotesRevealed { .. }))
+        .find_map(|event| match event {
+            HistoryEvent::EstimateOverridden { value, reason, .. } => Some((value, reason)),
+            _ => None,
+        });
+    let final_estimate = match overridden {
+        Some((value, reason)) => format.escape(&format!("{} (overridden by the host: {})", value, reason)),
+        None => room
+            .estimation_method
+            .estimator()
+            .suggest(&numeric, &room.deck)
+            .map(|card| format.escape(&card))
+            .unwrap_or_else(|| "-".to_string()),
+    };
+    let mut stats = vec![
+        ("Final estimate", final_estimate),
+        ("Votes", voted_count.to_string()),
+        ("Average", format_number(*average)),
+    ];
+    let lowest = numeric.iter().copied().reduce(f64::min);
+    let highest = numeric.iter().copied().reduce(f64::max);
+    if let (Some(lowest), Some(highest)) = (lowest, highest) {
+        stats.push(("Range", format!("{} to {}", lowest, highest)));
+    }
+    if risk_average.is_some() {
+        stats.push(("Risk", format_number(*risk_average)));
+    }
+    if !tags.is_empty() {
+        stats.push(("Tags", format.escape(&tags.join(", "))));
+    }
+    if !signals.is_empty() {
+        stats.push(("Flagged", describe_signals(signals)));
+    }
+
+    let mut text = String::new();
+    match format {
+        SummaryFormat::Markdown => {
+            let _ = writeln!(text, "**{}**\n", format.escape(&title));
+            text.push_str("| Name | Vote |\n| --- | --- |\n");
+            for (name, vote) in &rows {
+                let _ = writeln!(text, "| {} | {} |", name, vote);
+            }
+            text.push('\n');
+            for (label, value) in &stats {
+                let _ = writeln!(text, "- **{}:** {}", label, value);
+            }
+        }
+        SummaryFormat::JiraWiki => {
+            let _ = writeln!(text, "*{}*\n", format.escape(&title));
+            text.push_str("||Name||Vote||\n");
+            for (name, vote) in &rows {
+                let _ = writeln!(text, "|{}|{}|", name, vote);
+            }
+            text.push('\n');
+            for (label, value) in &stats {
+                let _ = writeln!(text, "* *{}:* {}", label, value);
+            }
+        }
+        SummaryFormat::PlainText => {
+            let _ = writeln!(text, "{}\n", title);
+            let width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+            for (name, vote) in &rows {
+                let _ = writeln!(text, "{:width$}  {}", name, vote, width = width);
+            }
+            text.push('\n');
+            for (label, value) in &stats {
+                let _ = writeln!(text, "{}: {}", label, value);
+            }
+        }
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{room_with, FakeEnvironment};
+
+    #[test]
+    fn round_summaries_escape_names_for_each_format() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob|ops", "carol"]);
+        assert!(format_round_summary(&room, SummaryFormat::Markdown).is_err());
+
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob|ops", Some("5".into()));
+        room.set_vote("carol", Some("8".into()));
+        room.complete_round();
+
+        let markdown = format_round_summary(&room, SummaryFormat::Markdown).unwrap();
+        assert!(markdown.contains("| bob\\|ops | 5 |"), "{}", markdown);
+        assert!(markdown.contains("- **Final estimate:** 5"), "{}", markdown);
+        assert!(markdown.contains("- **Range:** 3 to 8"), "{}", markdown);
+
+        let wiki = format_round_summary(&room, SummaryFormat::JiraWiki).unwrap();
+        assert!(wiki.contains("||Name||Vote||\n|alice|3|"), "{}", wiki);
+
+        let plain = format_round_summary(&room, SummaryFormat::PlainText).unwrap();
+        assert!(plain.contains("bob|ops  5\ncarol    8"), "{}", plain);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::credentials::MasterKey;
+use crate::persistence;
+use crate::room::Room;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+const RECOVERY_FILE: &str = "recovery.json";
+/// How long the panic hook waits for the room state. The panicking thread may
+/// be holding a room's lock, in which case the dump is given up on.
+const DUMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Everything needed to pick a session back up after a crash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionDump {
+    pub reason: String,
+    /// Rooms as they were, participants and votes included
+    pub rooms: Vec<Room>,
+    /// Identity token per participant ID. Rooms never serialize these, and
+    /// participants need them to get their places back.
+    pub identities: HashMap<String, String>,
+    pub relay_connected: bool,
+}
+
+/// The file on disk: a readable header, so the host can be offered recovery
+/// before the storage is unlocked, and the dump, encrypted like the rooms are
+#[derive(Debug, Serialize, Deserialize)]
+struct RecoveryFile {
+    crashed_at: u64,
+    rooms: usize,
+    session: String,
+}
+
+/// What the host is told about a crash on the next start
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryInfo {
+    /// Unix seconds
+    pub crashed_at: u64,
+    pub rooms: usize,
+}
+
+fn recovery_path() -> Result<PathBuf, String> {
+    Ok(persistence::persistence_dir()?.join(RECOVERY_FILE))
+}
+
+/// Record the app's sessions whenever a panic happens, after the usual panic
+/// output. A clean exit removes the file again.
+pub fn install_panic_hook(state: Arc<AppState>) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let reason = info.to_string();
+        let state = state.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(dump_session(&state, reason));
+        });
+        match rx.recv_timeout(DUMP_TIMEOUT) {
+            Ok(Ok(())) => tracing::error!("Saved the session state for recovery"),
+            Ok(Err(e)) => tracing::error!("Failed to save the session state for recovery: {}", e),
+            Err(_) => tracing::error!("Timed out saving the session state for recovery"),
+        }
+    }));
+}
+
+fn dump_session(state: &AppState, reason: String) -> Result<(), String> {
+    let key = state.storage_key();
+    if key.is_none() && state.get_settings().encrypt_rooms {
+        return Err("room storage is locked, so rooms can't be written unencrypted".into());
+    }
+    let rooms = state.get_rooms();
+    let identities = rooms
+        .iter()
+        .flat_map(|room| &room.participants)
+        .filter_map(|p| Some((p.id.clone(), p.identity.clone()?)))
+        .collect();
+    let relay_connected = state.relay_client.try_read().is_ok_and(|client| client.is_some());
+    let dump = SessionDump { reason, rooms, identities, relay_connected };
+    write(&dump, key.as_ref(), state.env.now_secs())
+}
+
+fn write(dump: &SessionDump, key: Option<&MasterKey>, now: u64) -> Result<(), String> {
+    let value = serde_json::to_value(dump).map_err(|e| e.to_string())?;
+    let file = RecoveryFile {
+        crashed_at: now,
+        rooms: dump.rooms.len(),
+        session: persistence::encode(&value, key)?,
+    };
+    let path = recovery_path()?;
+    let json = serde_json::to_string(&file).map_err(|e| e.to_string())?;
+    // Write then rename, so a second crash mid-write leaves the first dump intact
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, json).map_err(|e| format!("Failed to write recovery file: {}", e))?;
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to write recovery file: {}", e))
+}
+
+fn read_file() -> Result<Option<RecoveryFile>, String> {
+    let path = recovery_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read recovery file: {}", e))?;
+    serde_json::from_str(&json).map(Some).map_err(|e| format!("Invalid recovery file: {}", e))
+}
+
+/// The crash left behind by the last run, if there was one
+pub fn info() -> Result<Option<RecoveryInfo>, String> {
+    Ok(read_file()?.map(|file| RecoveryInfo { crashed_at: file.crashed_at, rooms: file.rooms }))
+}
+
+/// Read the last run's session dump, decrypting it with the storage key
+pub fn load(key: Option<&MasterKey>) -> Result<Option<SessionDump>, String> {
+    let Some(file) = read_file()? else {
+        return Ok(None);
+    };
+    let value = persistence::decode(&file.session, key)?;
+    serde_json::from_value(value).map(Some).map_err(|e| format!("Invalid recovery file: {}", e))
+}
+
+pub fn discard() -> Result<(), String> {
+    match fs::remove_file(recovery_path()?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove recovery file: {}", e)),
+    }
+}
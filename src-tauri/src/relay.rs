@@ -1,31 +1,174 @@
-use crate::room::{JiraTicket, Room};
+use crate::message_limits::{self, MAX_RELAY_MESSAGE_BYTES};
+use crate::room::{now_millis, JiraTicket, Room, WsMessage};
+use crate::settings::{AppSettings, ProxySettings};
+use base64::{Engine as _, engine::general_purpose};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tokio::sync::{mpsc, RwLock};
 use tokio_tungstenite::{
+    client_async_tls_with_config,
     connect_async_tls_with_config,
-    tungstenite::Message,
+    tungstenite::{protocol::WebSocketConfig, Message},
     Connector,
 };
+use tracing::Instrument;
 
 
 const DEFAULT_RELAY_URL: &str = "wss://scrum-poker-hydra.ngrok.dev";
 
+/// The relay's HTTP health check, for testing it can be reached without
+/// registering as a host
+pub fn health_url(relay_url: Option<&str>) -> Result<String, String> {
+    let mut url = url::Url::parse(relay_url.unwrap_or(DEFAULT_RELAY_URL))
+        .map_err(|e| format!("Invalid relay URL: {}", e))?;
+    let scheme = if url.scheme() == "ws" { "http" } else { "https" };
+    url.set_scheme(scheme).map_err(|_| "Invalid relay URL".to_string())?;
+    url.set_path("/api/health");
+    Ok(url.to_string())
+}
+
+/// Version of the host <-> relay protocol spoken by this client
+pub const RELAY_PROTOCOL_VERSION: u32 = 1;
+
+/// How often a Ping is sent to the relay
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Number of keepalive intervals without a Pong before the connection is considered dead
+const MAX_MISSED_PONGS: u32 = 3;
+/// Clock readings taken on connecting; the one with the shortest round trip is used
+const TIME_SYNC_SAMPLES: usize = 3;
+
+/// Callback invoked with a reason when the relay connection is lost
+type DisconnectCallback = Box<dyn Fn(String) + Send + Sync>;
+
+/// Callback invoked for participants bridged through the relay
+type ParticipantCallback = Box<dyn Fn(ParticipantEvent) + Send + Sync>;
+
+/// Callback invoked when the relay rejects something the host asked for
+type ErrorCallback = Box<dyn Fn(RelayError) + Send + Sync>;
+
+/// Why the relay rejected a host request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayErrorCode {
+    /// This connection already registered as a host
+    HostAlreadyRegistered,
+    /// The relay won't host any more rooms for this host
+    RoomLimitReached,
+    /// The relay speaks a different protocol version than this app
+    ProtocolVersionMismatch,
+    /// The request needs a registered host, or the room belongs to another host
+    Unauthorized,
+    /// Anything else, including errors from relays that predate error codes
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+impl RelayErrorCode {
+    /// What the user can do about it
+    pub fn remediation(self) -> &'static str {
+        match self {
+            RelayErrorCode::HostAlreadyRegistered => {
+                "This app is already registered with the relay. Disconnect from the relay and connect again."
+            }
+            RelayErrorCode::RoomLimitReached => {
+                "The relay is hosting as many rooms as it allows. Delete rooms you no longer need, then reconnect."
+            }
+            RelayErrorCode::ProtocolVersionMismatch => {
+                "This app and the relay server are different versions. Update the app, or ask whoever runs the relay to update it."
+            }
+            RelayErrorCode::Unauthorized => {
+                "The relay didn't accept this app as the host. Check that the relay token in your settings is current; if it is, another instance or account may be hosting the same room."
+            }
+            RelayErrorCode::Other => "Check the relay connection and try again.",
+        }
+    }
+}
+
+/// A relay error, ready to show to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayError {
+    pub code: RelayErrorCode,
+    pub message: String,
+    pub remediation: &'static str,
+}
+
+impl RelayError {
+    pub fn new(code: RelayErrorCode, message: String) -> Self {
+        Self {
+            code,
+            message,
+            remediation: code.remediation(),
+        }
+    }
+}
+
+/// Envelope carrying a participant's WebSocket message between the relay and the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticipantMessage {
+    /// Relay-assigned ID of the participant's connection
+    pub connection_id: String,
+    pub message: WsMessage,
+}
+
+/// A web client file published to the relay so it can serve the client itself
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishedFile {
+    pub path: String,
+    pub content_type: String,
+    /// Base64 encoded file contents
+    pub data: String,
+}
+
+/// Lifecycle of a participant connected to the relay and bridged to this host
+#[derive(Debug, Clone)]
+pub enum ParticipantEvent {
+    Connected(String),
+    Message(ParticipantMessage),
+    Disconnected(String),
+}
+
 /// Messages sent TO the relay server
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum OutgoingMessage {
-    HostRegister,
+    HostRegister {
+        protocol_version: u32,
+        /// Credential from the relay operator, if the relay requires one
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+        /// Rooms this host already has, whose relay copies it takes back on reconnecting
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        room_ids: Vec<String>,
+    },
     HostCreateRoom { name: String },
-    HostSyncRoom { room: Room },
+    /// `sent_at` is this app's clock (Unix milliseconds), so the relay can move
+    /// the room's timestamps onto its own clock before passing them on
+    HostSyncRoom { room: Room, sent_at: u64 },
     HostDeleteRoom { room_id: String },
     HostRevealVotes { room_id: String },
     HostHideVotes { room_id: String },
+    /// Show one more card of a one-by-one reveal
+    HostRevealVote { room_id: String, participant_id: String },
     HostResetVotes { room_id: String },
     HostKickParticipant { room_id: String, participant_id: String },
-    HostSetTicket { room_id: String, ticket: JiraTicket },
+    HostSetSittingOut { room_id: String, participant_id: String, sitting_out: bool },
+    /// Show a ticket, along with the deck it's voted on (which depends on its issue type)
+    HostSetTicket { room_id: String, ticket: JiraTicket, deck: Vec<String> },
     HostClearTicket { room_id: String },
+    /// Ask the relay to resend a room after a missed update or checksum mismatch
+    HostResyncRoom { room_id: String },
+    /// Reply to a bridged participant
+    HostParticipantMessage(ParticipantMessage),
+    /// Upload the web client so participants can load it from the relay
+    HostPublishClient { files: Vec<PublishedFile> },
+    /// Ask for the relay's clock, sending ours (Unix milliseconds)
+    TimeSync { client_time: u64 },
     Ping,
 }
 
@@ -33,15 +176,71 @@ pub enum OutgoingMessage {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum IncomingMessage {
-    HostRegistered { rooms: Vec<Room>, relay_url: String },
-    RoomCreated { room: Room },
-    RoomSynced { room: Room },
+    HostRegistered {
+        rooms: Vec<Room>,
+        relay_url: String,
+        /// Direct join link for each of the rooms, keyed by room ID
+        #[serde(default)]
+        join_urls: HashMap<String, String>,
+    },
+    RoomCreated {
+        room: Room,
+        #[serde(default)]
+        join_url: Option<String>,
+    },
+    RoomSynced {
+        room: Room,
+        /// Link participants can open to go straight into the room; older relays don't send one
+        #[serde(default)]
+        join_url: Option<String>,
+    },
     RoomDeleted { room_id: String },
-    RoomUpdate { room: Room },
-    Error { message: String },
+    RoomUpdate {
+        room: Room,
+        /// `Room::checksum` as computed by the relay
+        #[serde(default)]
+        checksum: Option<String>,
+    },
+    Error {
+        #[serde(default)]
+        code: RelayErrorCode,
+        message: String,
+    },
+    /// A participant connected to the relay for one of this host's rooms
+    ParticipantConnected { connection_id: String },
+    /// A bridged participant sent a message
+    ParticipantMessage(ParticipantMessage),
+    ParticipantDisconnected { connection_id: String },
+    TimeSyncReply { client_time: u64, server_time: u64 },
     Pong,
 }
 
+/// How far the relay's clock is ahead of this machine's, from one time sync
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ClockSample {
+    offset_ms: i64,
+    round_trip_ms: u64,
+}
+
+impl ClockSample {
+    /// Take the relay to have read its clock halfway through the round trip
+    fn measure(client_time: u64, server_time: u64, received_at: u64) -> Self {
+        let round_trip_ms = received_at.saturating_sub(client_time);
+        let offset_ms = server_time as i64 + (round_trip_ms / 2) as i64 - received_at as i64;
+        Self { offset_ms, round_trip_ms }
+    }
+}
+
+/// Put the relay's participant timestamps on this machine's clock, so which
+/// vote changed last is decided by when it happened rather than by whose
+/// clock is ahead
+fn to_host_clock(mut room: Room, offset_ms: i64) -> Room {
+    for participant in room.participants.iter_mut().filter(|p| p.updated_at > 0) {
+        participant.updated_at = (participant.updated_at as i64 - offset_ms).max(0) as u64;
+    }
+    room
+}
+
 /// Relay client state
 pub struct RelayClient {
     /// Channel to send messages to the relay
@@ -50,118 +249,222 @@ pub struct RelayClient {
     rooms: Arc<RwLock<Vec<Room>>>,
     /// Relay URL for sharing
     relay_url: Arc<RwLock<String>>,
+    /// Join links the relay handed out, keyed by room ID
+    join_urls: Arc<RwLock<HashMap<String, String>>>,
     /// Connection status
     connected: Arc<RwLock<bool>>,
     /// Callback for room updates
     room_update_callback: Arc<RwLock<Option<Box<dyn Fn(Room) + Send + Sync>>>>,
+    /// Callback for when the connection drops or stops responding
+    disconnect_callback: Arc<RwLock<Option<DisconnectCallback>>>,
+    /// Callback for participants bridged through the relay
+    participant_callback: Arc<RwLock<Option<ParticipantCallback>>>,
+    /// Callback for errors reported by the relay
+    error_callback: Arc<RwLock<Option<ErrorCallback>>>,
+    /// The best measurement of the relay's clock so far. Rooms are kept with
+    /// the relay's timestamps and handed out on this machine's clock.
+    clock: Arc<std::sync::Mutex<Option<ClockSample>>>,
 }
 
 impl RelayClient {
-    /// Create a new relay client and connect to the server
-    pub async fn connect(relay_url: Option<&str>) -> Result<Arc<Self>, String> {
+    /// Create a new relay client and connect to the server.
+    /// Without an explicit proxy, HTTPS_PROXY / ALL_PROXY / NO_PROXY from the environment are honored.
+    /// The relay sends back its copies of `room_ids`, if it still has them, for merging.
+    pub async fn connect(relay_url: Option<&str>, settings: &AppSettings, room_ids: Vec<String>) -> Result<Arc<Self>, String> {
         let url = relay_url.unwrap_or(DEFAULT_RELAY_URL);
         let ws_url = url::Url::parse(url)
             .map_err(|e| format!("Invalid relay URL: {}", e))?;
         
         tracing::info!("Connecting to relay server: {}", ws_url);
         
-        // Create TLS connector using native roots
-        let tls_connector = Connector::NativeTls(
-            native_tls::TlsConnector::new()
-                .map_err(|e| format!("Failed to create TLS connector: {}", e))?
-        );
+        // Create TLS connector using native roots plus any configured custom CA
+        let tls_connector = Connector::NativeTls(settings.tls_connector()?);
         
-        let (ws_stream, _) = connect_async_tls_with_config(
-            &ws_url,
-            None,
-            false,
-            Some(tls_connector),
-        )
-        .await
-        .map_err(|e| format!("Failed to connect to relay: {}", e))?;
+        // Oversized frames from the relay fail the connection rather than being buffered
+        let config = WebSocketConfig {
+            max_message_size: Some(MAX_RELAY_MESSAGE_BYTES),
+            max_frame_size: Some(MAX_RELAY_MESSAGE_BYTES),
+            ..Default::default()
+        };
+
+        let proxy = settings.proxy.clone().or_else(|| proxy_from_env(&ws_url));
+        let (ws_stream, _) = match proxy {
+            Some(proxy) => {
+                tracing::info!("Connecting to relay through proxy {}", proxy.url);
+                let stream = connect_via_proxy(&proxy, &ws_url).await?;
+                client_async_tls_with_config(ws_url.as_str(), stream, Some(config), Some(tls_connector))
+                    .await
+                    .map_err(|e| format!("Failed to connect to relay: {}", e))?
+            }
+            None => connect_async_tls_with_config(
+                &ws_url,
+                Some(config),
+                false,
+                Some(tls_connector),
+            )
+            .await
+            .map_err(|e| format!("Failed to connect to relay: {}", e))?,
+        };
         
         tracing::info!("Connected to relay server");
-        
+        // Messages to and from this relay connection are logged under one span
+        let span = tracing::info_span!("relay", url = %url);
+
         let (mut write, mut read) = ws_stream.split();
         let (tx, mut rx) = mpsc::unbounded_channel::<OutgoingMessage>();
         
         let rooms = Arc::new(RwLock::new(Vec::new()));
         let relay_url_storage = Arc::new(RwLock::new(url.to_string()));
+        let join_urls = Arc::new(RwLock::new(HashMap::new()));
         let connected = Arc::new(RwLock::new(true));
         let room_update_callback: Arc<RwLock<Option<Box<dyn Fn(Room) + Send + Sync>>>> = 
             Arc::new(RwLock::new(None));
+        let last_pong = Arc::new(RwLock::new(Instant::now()));
+        let disconnect_callback: Arc<RwLock<Option<DisconnectCallback>>> = Arc::new(RwLock::new(None));
+        let participant_callback: Arc<RwLock<Option<ParticipantCallback>>> = Arc::new(RwLock::new(None));
+        let error_callback: Arc<RwLock<Option<ErrorCallback>>> = Arc::new(RwLock::new(None));
+        let clock: Arc<std::sync::Mutex<Option<ClockSample>>> = Arc::new(std::sync::Mutex::new(None));
         
         let client = Arc::new(Self {
             tx,
             rooms: rooms.clone(),
             relay_url: relay_url_storage.clone(),
+            join_urls: join_urls.clone(),
             connected: connected.clone(),
             room_update_callback: room_update_callback.clone(),
+            disconnect_callback: disconnect_callback.clone(),
+            participant_callback: participant_callback.clone(),
+            error_callback: error_callback.clone(),
+            clock: clock.clone(),
         });
         
         // Spawn task to send messages
         tokio::spawn(async move {
             while let Some(msg) = rx.recv().await {
                 let json = serde_json::to_string(&msg).unwrap();
-                tracing::debug!("Sending to relay: {}", json);
+                // Registration carries the host token, which shouldn't end up in logs
+                if !matches!(msg, OutgoingMessage::HostRegister { .. }) {
+                    tracing::debug!("Sending to relay: {}", json);
+                }
                 if write.send(Message::Text(json)).await.is_err() {
                     tracing::error!("Failed to send message to relay");
                     break;
                 }
             }
-        });
+        }.instrument(span.clone()));
         
         // Spawn task to receive messages
         let rooms_clone = rooms.clone();
         let connected_clone = connected.clone();
         let relay_url_clone = relay_url_storage.clone();
+        let join_urls_clone = join_urls.clone();
         let callback_clone = room_update_callback.clone();
+        let last_pong_clone = last_pong.clone();
+        let disconnect_clone = disconnect_callback.clone();
+        let participant_clone = participant_callback.clone();
+        let error_clone = error_callback.clone();
+        let resync_tx = client.tx.clone();
+        let clock_clone = clock.clone();
+        let clock_offset = move || clock_clone.lock().unwrap().map_or(0, |sample| sample.offset_ms);
         
-        tokio::spawn(async move {
+        let reader = tokio::spawn(async move {
             while let Some(result) = read.next().await {
                 match result {
                     Ok(Message::Text(text)) => {
+                        if let Err(reason) = message_limits::check_inbound(&text, MAX_RELAY_MESSAGE_BYTES) {
+                            tracing::error!("Closing relay connection: {}", reason);
+                            *connected_clone.write().await = false;
+                            if let Some(cb) = disconnect_clone.read().await.as_ref() {
+                                cb(format!("Relay sent an invalid message: {}", reason));
+                            }
+                            break;
+                        }
                         tracing::info!("Received from relay: {}", text);
                         match serde_json::from_str::<IncomingMessage>(&text) {
                             Ok(msg) => match msg {
-                                IncomingMessage::HostRegistered { rooms: r, relay_url } => {
+                                IncomingMessage::HostRegistered { rooms: r, relay_url, join_urls } => {
                                     tracing::info!("Host registered with {} existing rooms", r.len());
                                     *rooms_clone.write().await = r;
                                     *relay_url_clone.write().await = relay_url;
+                                    *join_urls_clone.write().await = join_urls;
                                 }
-                                IncomingMessage::RoomCreated { room } => {
+                                IncomingMessage::RoomCreated { room, join_url } => {
                                     tracing::info!("Room created: {}", room.name);
+                                    if let Some(join_url) = join_url {
+                                        join_urls_clone.write().await.insert(room.id.clone(), join_url);
+                                    }
                                     rooms_clone.write().await.push(room.clone());
                                     if let Some(cb) = callback_clone.read().await.as_ref() {
-                                        cb(room);
+                                        cb(to_host_clock(room, clock_offset()));
                                     }
                                 }
-                                IncomingMessage::RoomSynced { room } => {
+                                IncomingMessage::RoomSynced { room, join_url } => {
                                     tracing::info!("Room synced: {}", room.name);
-                                    // Room was synced to relay, no action needed
+                                    if let Some(join_url) = join_url {
+                                        join_urls_clone.write().await.insert(room.id, join_url);
+                                    }
                                 }
                                 IncomingMessage::RoomDeleted { room_id } => {
                                     tracing::info!("Room deleted: {}", room_id);
                                     rooms_clone.write().await.retain(|r| r.id != room_id);
+                                    join_urls_clone.write().await.remove(&room_id);
                                 }
-                                IncomingMessage::RoomUpdate { room } => {
+                                IncomingMessage::RoomUpdate { room, checksum } => {
                                     tracing::info!("Room update: {} ({} participants)", 
                                         room.name, room.participants.len());
                                     // Update room in list
                                     let mut rooms = rooms_clone.write().await;
+                                    let previous_revision = rooms.iter().find(|r| r.id == room.id).map(|r| r.revision);
                                     if let Some(existing) = rooms.iter_mut().find(|r| r.id == room.id) {
                                         *existing = room.clone();
                                     }
                                     drop(rooms);
+
+                                    // Each update is a full snapshot, but a gap or a mismatched
+                                    // checksum means the two copies have drifted somewhere
+                                    let gap = previous_revision.is_some_and(|prev| prev > 0 && room.revision > prev + 1);
+                                    let mismatch = checksum.as_ref().is_some_and(|c| *c != room.checksum());
+                                    if gap || mismatch {
+                                        tracing::warn!(
+                                            "Relay copy of room {} out of sync (revision {:?} -> {}, checksum mismatch: {}); resyncing",
+                                            room.id, previous_revision, room.revision, mismatch
+                                        );
+                                        let _ = resync_tx.send(OutgoingMessage::HostResyncRoom { room_id: room.id.clone() });
+                                    }
                                     if let Some(cb) = callback_clone.read().await.as_ref() {
-                                        cb(room);
+                                        cb(to_host_clock(room, clock_offset()));
                                     }
                                 }
-                                IncomingMessage::Error { message } => {
-                                    tracing::error!("Relay error: {}", message);
+                                IncomingMessage::Error { code, message } => {
+                                    tracing::error!("Relay error ({:?}): {}", code, message);
+                                    if let Some(cb) = error_clone.read().await.as_ref() {
+                                        cb(RelayError::new(code, message));
+                                    }
+                                }
+                                IncomingMessage::ParticipantConnected { connection_id } => {
+                                    if let Some(cb) = participant_clone.read().await.as_ref() {
+                                        cb(ParticipantEvent::Connected(connection_id));
+                                    }
+                                }
+                                IncomingMessage::ParticipantMessage(envelope) => {
+                                    if let Some(cb) = participant_clone.read().await.as_ref() {
+                                        cb(ParticipantEvent::Message(envelope));
+                                    }
+                                }
+                                IncomingMessage::ParticipantDisconnected { connection_id } => {
+                                    if let Some(cb) = participant_clone.read().await.as_ref() {
+                                        cb(ParticipantEvent::Disconnected(connection_id));
+                                    }
+                                }
+                                IncomingMessage::TimeSyncReply { client_time, server_time } => {
+                                    let sample = ClockSample::measure(client_time, server_time, now_millis());
+                                    let mut best = clock.lock().unwrap();
+                                    if best.is_none_or(|best| sample.round_trip_ms <= best.round_trip_ms) {
+                                        *best = Some(sample);
+                                    }
                                 }
                                 IncomingMessage::Pong => {
-                                    // Keepalive response
+                                    *last_pong_clone.write().await = Instant::now();
                                 }
                             },
                             Err(e) => {
@@ -172,30 +475,62 @@ impl RelayClient {
                     Ok(Message::Close(_)) => {
                         tracing::info!("Relay connection closed");
                         *connected_clone.write().await = false;
+                        if let Some(cb) = disconnect_clone.read().await.as_ref() {
+                            cb("Relay closed the connection".to_string());
+                        }
                         break;
                     }
                     Err(e) => {
                         tracing::error!("Relay WebSocket error: {}", e);
                         *connected_clone.write().await = false;
+                        if let Some(cb) = disconnect_clone.read().await.as_ref() {
+                            cb(format!("Relay connection error: {}", e));
+                        }
                         break;
                     }
                     _ => {}
                 }
             }
-        });
+        }.instrument(span));
         
         // Register as host
-        client.send(OutgoingMessage::HostRegister)?;
+        client.send(OutgoingMessage::HostRegister {
+            protocol_version: RELAY_PROTOCOL_VERSION,
+            token: settings.relay_token.clone().filter(|t| !t.is_empty()),
+            room_ids,
+        })?;
+        for _ in 0..TIME_SYNC_SAMPLES {
+            client.send(OutgoingMessage::TimeSync { client_time: now_millis() })?;
+        }
         
-        // Start keepalive
+        // Start keepalive, treating the connection as dead if Pongs stop arriving.
+        // A half-open TCP connection never errors, so this is the only way to notice it.
         let tx_clone = client.tx.clone();
+        let connected_clone = connected.clone();
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+            let mut interval = tokio::time::interval(KEEPALIVE_INTERVAL);
             loop {
                 interval.tick().await;
+                if !*connected_clone.read().await {
+                    break;
+                }
+
+                let silence = last_pong.read().await.elapsed();
+                if silence > KEEPALIVE_INTERVAL * MAX_MISSED_PONGS {
+                    tracing::warn!("No Pong from relay for {:?}, marking connection as dead", silence);
+                    *connected_clone.write().await = false;
+                    reader.abort();
+                    if let Some(cb) = disconnect_callback.read().await.as_ref() {
+                        cb(format!("Relay stopped responding ({}s without a reply)", silence.as_secs()));
+                    }
+                    break;
+                }
+
                 if tx_clone.send(OutgoingMessage::Ping).is_err() {
                     break;
                 }
+                // Keep measuring the relay's clock, in case a quieter moment gives a better reading
+                let _ = tx_clone.send(OutgoingMessage::TimeSync { client_time: now_millis() });
             }
         });
         
@@ -216,24 +551,64 @@ impl RelayClient {
         *self.room_update_callback.write().await = Some(Box::new(callback));
     }
     
+    /// Set callback for when the relay connection is lost
+    pub async fn set_disconnect_callback<F>(&self, callback: F)
+    where
+        F: Fn(String) + Send + Sync + 'static
+    {
+        *self.disconnect_callback.write().await = Some(Box::new(callback));
+    }
+    
+    /// Set callback for errors the relay reports back to the host
+    pub async fn set_error_callback<F>(&self, callback: F)
+    where
+        F: Fn(RelayError) + Send + Sync + 'static
+    {
+        *self.error_callback.write().await = Some(Box::new(callback));
+    }
+    
+    /// Set callback for participants bridged through the relay
+    pub async fn set_participant_callback<F>(&self, callback: F)
+    where
+        F: Fn(ParticipantEvent) + Send + Sync + 'static
+    {
+        *self.participant_callback.write().await = Some(Box::new(callback));
+    }
+    
     /// Get relay URL for sharing
     pub async fn get_relay_url(&self) -> String {
         self.relay_url.read().await.clone()
     }
+
+    /// Link that takes participants straight into one room through the relay.
+    /// Relays that don't hand out join links get the web client's `/join/<room id>` path.
+    pub async fn get_room_url(&self, room_id: &str) -> String {
+        if let Some(join_url) = self.join_urls.read().await.get(room_id) {
+            return join_url.clone();
+        }
+        default_join_url(&self.relay_url.read().await, room_id)
+    }
     
     /// Check if connected
     pub async fn is_connected(&self) -> bool {
         *self.connected.read().await
     }
     
+    /// How far the relay's clock is ahead of this machine's, or 0 until measured
+    fn clock_offset(&self) -> i64 {
+        self.clock.lock().unwrap().map_or(0, |sample| sample.offset_ms)
+    }
+
     /// Get all rooms
     pub async fn get_rooms(&self) -> Vec<Room> {
-        self.rooms.read().await.clone()
+        let offset = self.clock_offset();
+        self.rooms.read().await.iter().map(|room| to_host_clock(room.clone(), offset)).collect()
     }
     
     /// Get a specific room
     pub async fn get_room(&self, room_id: &str) -> Option<Room> {
-        self.rooms.read().await.iter().find(|r| r.id == room_id).cloned()
+        let room = self.rooms.read().await.iter().find(|r| r.id == room_id).cloned()?;
+        Some(to_host_clock(room, self.clock_offset()))
     }
     
     /// Create a new room
@@ -251,6 +626,11 @@ impl RelayClient {
         self.send(OutgoingMessage::HostRevealVotes { room_id })
     }
     
+    /// Show one participant's vote during a one-by-one reveal
+    pub fn reveal_vote(&self, room_id: String, participant_id: String) -> Result<(), String> {
+        self.send(OutgoingMessage::HostRevealVote { room_id, participant_id })
+    }
+
     /// Hide votes in a room
     pub fn hide_votes(&self, room_id: String) -> Result<(), String> {
         self.send(OutgoingMessage::HostHideVotes { room_id })
@@ -266,9 +646,14 @@ impl RelayClient {
         self.send(OutgoingMessage::HostKickParticipant { room_id, participant_id })
     }
     
-    /// Set current ticket for a room
-    pub fn set_ticket(&self, room_id: String, ticket: JiraTicket) -> Result<(), String> {
-        self.send(OutgoingMessage::HostSetTicket { room_id, ticket })
+    /// Take a participant out of the voting, or bring them back
+    pub fn set_sitting_out(&self, room_id: String, participant_id: String, sitting_out: bool) -> Result<(), String> {
+        self.send(OutgoingMessage::HostSetSittingOut { room_id, participant_id, sitting_out })
+    }
+
+    /// Set current ticket for a room, and the deck to vote on it with
+    pub fn set_ticket(&self, room_id: String, ticket: JiraTicket, deck: Vec<String>) -> Result<(), String> {
+        self.send(OutgoingMessage::HostSetTicket { room_id, ticket, deck })
     }
     
     /// Clear current ticket for a room
@@ -276,8 +661,199 @@ impl RelayClient {
         self.send(OutgoingMessage::HostClearTicket { room_id })
     }
     
+    /// Send a message to a participant bridged through the relay
+    pub fn send_participant_message(&self, envelope: ParticipantMessage) -> Result<(), String> {
+        self.send(OutgoingMessage::HostParticipantMessage(envelope))
+    }
+    
+    /// Publish the web client so the relay can serve it to remote participants
+    pub fn publish_client(&self, files: Vec<PublishedFile>) -> Result<(), String> {
+        self.send(OutgoingMessage::HostPublishClient { files })
+    }
+    
     /// Sync a local room to the relay server
     pub fn sync_room(&self, room: Room) -> Result<(), String> {
-        self.send(OutgoingMessage::HostSyncRoom { room })
+        self.send(OutgoingMessage::HostSyncRoom { room, sent_at: now_millis() })
+    }
+}
+
+/// Proxy configured through the standard environment variables for reaching `target`,
+/// if any. Hosts listed in NO_PROXY are connected to directly.
+fn proxy_from_env(target: &url::Url) -> Option<ProxySettings> {
+    let no_proxy = ["NO_PROXY", "no_proxy"].iter().find_map(|var| std::env::var(var).ok());
+    if let (Some(no_proxy), Some(host)) = (no_proxy, target.host_str()) {
+        if bypasses_proxy(&no_proxy, host, target.port_or_known_default().unwrap_or(443)) {
+            return None;
+        }
+    }
+    ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .filter(|url| !url.is_empty())
+        .map(|url| ProxySettings {
+            url,
+            username: None,
+            password: None,
+            password_stored: false,
+        })
+}
+
+/// Whether a NO_PROXY list (comma separated; `*`, domain suffixes with or without a
+/// leading dot, IP addresses or CIDR ranges, each optionally with `:port`) covers a host
+fn bypasses_proxy(no_proxy: &str, host: &str, port: u16) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    let ip = host.parse::<std::net::IpAddr>().ok();
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            // Split off a port, leaving IPv6 addresses and ranges alone
+            let (pattern, entry_port) = match entry.rsplit_once(':') {
+                Some((pattern, entry_port)) if !pattern.contains(':') || pattern.ends_with(']') => {
+                    (pattern.to_string(), entry_port.parse::<u16>().ok())
+                }
+                _ => (entry.clone(), None),
+            };
+            if entry_port.is_some_and(|entry_port| entry_port != port) {
+                return false;
+            }
+            let pattern = pattern.trim_start_matches('[').trim_end_matches(']');
+            if let Some(ip) = ip {
+                if let Ok(network) = pattern.parse::<ipnet::IpNet>() {
+                    return network.contains(&ip);
+                }
+                return pattern.parse::<std::net::IpAddr>().is_ok_and(|pattern| pattern == ip);
+            }
+            let domain = pattern.trim_start_matches("*.").trim_start_matches('.');
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+}
+
+/// Open a TCP tunnel to the relay through an HTTP proxy using CONNECT
+async fn connect_via_proxy(proxy: &ProxySettings, target: &url::Url) -> Result<TcpStream, String> {
+    let proxy_url = url::Url::parse(&proxy.url)
+        .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    let proxy_host = proxy_url.host_str()
+        .ok_or_else(|| "Proxy URL has no host".to_string())?;
+    let proxy_port = proxy_url.port_or_known_default().unwrap_or(8080);
+
+    let target_host = target.host_str()
+        .ok_or_else(|| "Relay URL has no host".to_string())?;
+    let target_port = target.port_or_known_default().unwrap_or(443);
+    let authority = format!("{}:{}", target_host, target_port);
+
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .map_err(|e| format!("Failed to connect to proxy: {}", e))?;
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+
+    // Credentials from settings take precedence over ones embedded in the URL
+    let username = proxy.username.clone()
+        .or_else(|| Some(proxy_url.username().to_string()).filter(|u| !u.is_empty()));
+    if let Some(username) = username {
+        let password = proxy.password.clone()
+            .or_else(|| proxy_url.password().map(str::to_string))
+            .unwrap_or_default();
+        let credentials = general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+
+    stream.write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send CONNECT to proxy: {}", e))?;
+
+    // Read the proxy's response headers byte by byte so nothing past them is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        let byte = stream.read_u8()
+            .await
+            .map_err(|e| format!("Proxy closed the connection: {}", e))?;
+        response.push(byte);
+        if response.len() > 8192 {
+            return Err("Proxy response headers too large".to_string());
+        }
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status_ok = status_line
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|code| code == "200");
+    if !status_ok {
+        return Err(format!("Proxy refused the tunnel: {}", status_line));
+    }
+
+    Ok(stream)
+}
+
+/// The web client's join page for a room on the relay at `relay_url`
+fn default_join_url(relay_url: &str, room_id: &str) -> String {
+    let base = relay_url.replacen("wss://", "https://", 1).replacen("ws://", "http://", 1);
+    format!("{}/join/{}", base.trim_end_matches('/'), room_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{room_with, FakeEnvironment};
+
+    #[test]
+    fn no_proxy_matches_domains_addresses_and_ports() {
+        let no_proxy = "localhost, .corp.example, internal.test:8443, 10.0.0.0/8, ::1";
+        assert!(bypasses_proxy(no_proxy, "localhost", 443));
+        assert!(bypasses_proxy(no_proxy, "relay.corp.example", 443));
+        assert!(bypasses_proxy(no_proxy, "corp.example", 443));
+        assert!(!bypasses_proxy(no_proxy, "notcorp.example", 443));
+        assert!(bypasses_proxy(no_proxy, "internal.test", 8443));
+        assert!(!bypasses_proxy(no_proxy, "internal.test", 443));
+        assert!(bypasses_proxy(no_proxy, "10.1.2.3", 443));
+        assert!(!bypasses_proxy(no_proxy, "11.1.2.3", 443));
+        assert!(bypasses_proxy(no_proxy, "[::1]", 443));
+        assert!(!bypasses_proxy(no_proxy, "scrum-poker-hydra.ngrok.dev", 443));
+        assert!(bypasses_proxy("*", "scrum-poker-hydra.ngrok.dev", 443));
+        assert!(!bypasses_proxy("", "scrum-poker-hydra.ngrok.dev", 443));
+    }
+
+    fn parse_error(json: &str) -> RelayErrorCode {
+        match serde_json::from_str::<IncomingMessage>(json).unwrap() {
+            IncomingMessage::Error { code, .. } => code,
+            other => panic!("expected error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn relay_error_codes_fall_back_to_other() {
+        assert_eq!(
+            parse_error(r#"{"type":"error","code":"room_limit_reached","message":"Too many rooms"}"#),
+            RelayErrorCode::RoomLimitReached
+        );
+        // Older relays send no code; newer ones may send codes we don't know yet
+        assert_eq!(parse_error(r#"{"type":"error","message":"Oops"}"#), RelayErrorCode::Other);
+        assert_eq!(parse_error(r#"{"type":"error","code":"rate_limited","message":"Slow down"}"#), RelayErrorCode::Other);
+    }
+
+    #[test]
+    fn rooms_without_a_join_link_from_the_relay_use_the_join_page() {
+        assert_eq!(default_join_url("wss://relay.example.com/", "room-1"), "https://relay.example.com/join/room-1");
+        assert_eq!(default_join_url("http://localhost:8070", "room-1"), "http://localhost:8070/join/room-1");
+    }
+
+    #[test]
+    fn relay_timestamps_are_moved_onto_this_clock() {
+        // The relay runs 5s ahead, and the reply took 200ms to come back
+        let sample = ClockSample::measure(10_000, 15_100, 10_200);
+        assert_eq!(sample, ClockSample { offset_ms: 5_000, round_trip_ms: 200 });
+
+        let mut room = room_with(&FakeEnvironment::new(), &["alice", "bob"]);
+        room.participants[0].updated_at = 20_000;
+        let room = to_host_clock(room, sample.offset_ms);
+        assert_eq!((room.participants[0].updated_at, room.participants[1].updated_at), (15_000, 0));
     }
 }
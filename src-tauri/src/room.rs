@@ -1,9 +1,26 @@
+use crate::analysis::ComplexityHints;
+use crate::campaign::Campaign;
+use crate::capacity::SprintFill;
+use crate::estimate_rules::EstimateRule;
+use crate::estimation::EstimationMethod;
+use crate::moderation::validate_text;
+use crate::state::EnvHandle;
+use crate::timezone::RoomTimeZone;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 /// Story point values available for voting
 pub const STORY_POINTS: &[&str] = &["?", "☕", "0", "0.5", "1", "2", "3", "5", "8", "13", "20", "40", "100"];
 
+/// Card for sitting a ticket out on purpose, as opposed to not having voted yet
+/// or "?" (not sure). It counts as a vote for the quorum and shows up in the
+/// distribution, but is left out of averages and suggested estimates.
+pub const ABSTAIN: &str = "abstain";
+
+/// Values for the optional risk/uncertainty vote, from 1 (well understood) to 5 (lots of unknowns)
+pub const RISK_POINTS: &[&str] = &["?", "1", "2", "3", "4", "5"];
+
 /// Jira ticket information
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct JiraTicket {
@@ -13,6 +30,37 @@ pub struct JiraTicket {
     pub issue_type: Option<String>,
     pub status: Option<String>,
     pub url: String,
+    /// Hints about the ticket's size, shown before anyone votes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<ComplexityHints>,
+    /// Size of the change, for pull requests estimated for review effort
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_stats: Option<DiffStats>,
+}
+
+/// Lines and files a pull request touches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct DiffStats {
+    pub additions: u64,
+    pub deletions: u64,
+    pub changed_files: u64,
+    pub commits: u64,
+}
+
+impl JiraTicket {
+    /// Hash of the ticket's contents, so clients can tell when to fetch it again.
+    /// Computed the same way by the relay.
+    pub fn content_hash(&self) -> String {
+        let fields = [
+            self.key.as_str(),
+            &self.summary,
+            self.description.as_deref().unwrap_or(""),
+            self.issue_type.as_deref().unwrap_or(""),
+            self.status.as_deref().unwrap_or(""),
+            &self.url,
+        ];
+        format!("{:08x}", fnv1a(fields.join("\u{1}").as_bytes()))
+    }
 }
 
 /// Represents a participant in a room
@@ -21,7 +69,34 @@ pub struct Participant {
     pub id: String,
     pub name: String,
     pub vote: Option<String>,
+    /// Vote on the room's risk deck, when the room uses one
+    #[serde(default)]
+    pub risk_vote: Option<String>,
     pub is_host: bool,
+    /// Display color chosen by the server so every client shows the same person the same way
+    #[serde(default)]
+    pub color: String,
+    /// Client-generated token identifying the same person across rejoins. Never sent to clients.
+    #[serde(skip)]
+    pub identity: Option<String>,
+    /// Coarse client details, only present if the participant chose to share them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientInfo>,
+    /// When the participant's connection dropped; they're shown greyed out (keeping
+    /// their vote) until the room's grace period runs out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disconnected_at: Option<u64>,
+    /// Away or not estimating for now; left out of the quorum until they're back
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sitting_out: bool,
+    /// Joined to watch, like a product owner: never votes, and isn't counted
+    /// among the voters or waited on before a reveal
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_observer: bool,
+    /// When the vote, risk vote or sitting out last changed (Unix milliseconds),
+    /// so diverged host and relay copies can be merged participant by participant
+    #[serde(default)]
+    pub updated_at: u64,
 }
 
 impl Participant {
@@ -30,11 +105,112 @@ impl Participant {
             id: Uuid::new_v4().to_string(),
             name,
             vote: None,
+            risk_vote: None,
             is_host,
+            color: String::new(),
+            identity: None,
+            client: None,
+            disconnected_at: None,
+            sitting_out: false,
+            is_observer: false,
+            updated_at: 0,
+        }
+    }
+
+    pub fn observing(mut self, is_observer: bool) -> Self {
+        self.is_observer = is_observer;
+        self
+    }
+
+    /// Whether the room waits on this participant's vote
+    pub fn is_voter(&self) -> bool {
+        !self.is_observer && !self.sitting_out
+    }
+
+    pub fn with_identity(mut self, identity: Option<String>) -> Self {
+        // Ignore anything that doesn't look like a client-generated token
+        self.identity = identity.filter(|i| !i.is_empty() && i.len() <= MAX_IDENTITY_LENGTH);
+        self
+    }
+}
+
+/// Kind of app a participant joined from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientKind {
+    Browser,
+    Desktop,
+    Tui,
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+/// Browser families reported to the host; anything else is "Other"
+const USER_AGENT_FAMILIES: &[&str] = &["Edge", "Opera", "Firefox", "Chrome", "Safari", "Other"];
+
+/// Coarse, opt-in description of a participant's client, for troubleshooting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    #[serde(default)]
+    pub kind: ClientKind,
+    /// Browser family, e.g. "Firefox"
+    #[serde(default)]
+    pub user_agent_family: Option<String>,
+}
+
+impl ClientInfo {
+    /// Keep only values from the known set, so nothing free-form ends up in the room
+    pub fn sanitized(self, user_agent: Option<&str>) -> Self {
+        let family = self
+            .user_agent_family
+            .and_then(|family| USER_AGENT_FAMILIES.iter().find(|f| f.eq_ignore_ascii_case(&family)).copied())
+            .or_else(|| user_agent.map(user_agent_family));
+        Self {
+            kind: self.kind,
+            user_agent_family: family.map(str::to_string),
         }
     }
 }
 
+/// Browser family from a User-Agent header. Order matters: Edge and Opera
+/// also claim to be Chrome, and Chrome also claims to be Safari.
+pub fn user_agent_family(user_agent: &str) -> &'static str {
+    if user_agent.contains("Edg/") {
+        "Edge"
+    } else if user_agent.contains("OPR/") {
+        "Opera"
+    } else if user_agent.contains("Firefox/") {
+        "Firefox"
+    } else if user_agent.contains("Chrome/") {
+        "Chrome"
+    } else if user_agent.contains("Safari/") {
+        "Safari"
+    } else {
+        "Other"
+    }
+}
+
+/// Maximum length of a participant identity token
+const MAX_IDENTITY_LENGTH: usize = 64;
+
+/// Participant colors, handed out least-used first
+pub const PARTICIPANT_COLORS: &[&str] = &[
+    "#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#42d4f4",
+    "#f032e6", "#9a6324", "#469990", "#808000", "#000075", "#e6beff",
+];
+
+/// Seconds a dropped participant is kept before being removed
+pub const DEFAULT_DISCONNECT_GRACE_SECS: u64 = 60;
+
+fn default_disconnect_grace() -> u64 {
+    DEFAULT_DISCONNECT_GRACE_SECS
+}
+
+fn default_deck() -> Vec<String> {
+    STORY_POINTS.iter().chain([&ABSTAIN]).map(|s| s.to_string()).collect()
+}
+
 /// Represents a scrum poker room
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
@@ -42,96 +218,1691 @@ pub struct Room {
     pub name: String,
     pub participants: Vec<Participant>,
     pub votes_revealed: bool,
+    /// When clients should flip the cards over (Unix milliseconds), set a moment
+    /// after the reveal so everyone's animation starts together
+    #[serde(default)]
+    pub reveal_at: Option<u64>,
     #[serde(default)]
     pub created_at: u64,
+    /// When the host has said voting will start (Unix seconds), so people joining
+    /// early see a countdown; None if no start time was announced
+    #[serde(default)]
+    pub starts_at: Option<u64>,
+    /// Time zone times are shown in for this room (exports, history, scheduled start)
+    #[serde(default)]
+    pub time_zone: RoomTimeZone,
     pub invite_code: String,
     pub current_ticket: Option<JiraTicket>,
+    /// `JiraTicket::content_hash` of the current ticket. Room updates carry only
+    /// this; the ticket itself is sent when it changes.
+    #[serde(default)]
+    pub ticket_hash: Option<String>,
+    /// Revision of the room's state, bumped on every broadcast update so clients can spot gaps
+    #[serde(default)]
+    pub revision: u64,
+    /// Card values participants may vote with, including `ABSTAIN` when it's offered
+    #[serde(default = "default_deck")]
+    pub deck: Vec<String>,
+    /// Card values for the second (risk/uncertainty) vote; None when the room only votes on effort
+    #[serde(default)]
+    pub risk_deck: Option<Vec<String>>,
+    /// Decks keyed by Jira issue type (lowercase), switched to when a ticket of
+    /// that type comes up; copied from the settings
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub issue_type_decks: HashMap<String, Vec<String>>,
+    /// The room's own deck while an issue type's deck is in use, to go back to
+    /// for other tickets or when the host reverts the switch
+    #[serde(default)]
+    pub switched_from_deck: Option<Vec<String>>,
+    /// Formula used for the suggested estimate
+    #[serde(default)]
+    pub estimation_method: EstimationMethod,
+    /// What to do in Jira when a ticket's estimate comes in large; None uses
+    /// the rule in the settings, if any
+    #[serde(default)]
+    pub estimate_rule: Option<EstimateRule>,
+    /// Per-participant statistics for this session, keyed by participant ID
+    #[serde(default)]
+    pub participant_stats: HashMap<String, ParticipantStats>,
+    /// Team/company branding shown to everyone in the room
+    #[serde(default)]
+    pub branding: RoomBranding,
+    /// Only accept direct connections from private network addresses.
+    /// Participants bridged through the relay are turned away as well.
+    #[serde(default)]
+    pub lan_only: bool,
+    /// No new participants may join; people who were already here can still rejoin
+    #[serde(default)]
+    pub locked: bool,
+    /// The story is being read and discussed; nobody may vote until the host opens voting
+    #[serde(default)]
+    pub discussing: bool,
+    /// When voting opens after a discussion (Unix milliseconds), for the countdown
+    /// clients show; votes are turned away until then
+    #[serde(default)]
+    pub voting_opens_at: Option<u64>,
+    /// Keep participants from seeing who has voted until everyone has or the
+    /// votes are revealed, so early voters don't put pressure on the rest
+    #[serde(default)]
+    pub hide_voters: bool,
+    /// How long participants whose connection dropped are kept, so a page refresh
+    /// doesn't wipe their vote; 0 removes them straight away
+    #[serde(default = "default_disconnect_grace")]
+    pub disconnect_grace_secs: u64,
+    /// Reveal the votes by itself once the quorum rules are met
+    #[serde(default)]
+    pub auto_reveal: bool,
+    /// Whether revealing flips every card at once or shows them one by one
+    #[serde(default)]
+    pub reveal_mode: RevealMode,
+    /// Participants whose votes have been shown so far in a one-by-one reveal,
+    /// in order; None when no such reveal is under way
+    #[serde(default)]
+    pub reveal_progress: Option<Vec<String>>,
+    #[serde(default)]
+    pub quorum: QuorumRules,
+    /// Whether the quorum rules are currently met, as a hint for the reveal button
+    #[serde(default)]
+    pub can_reveal: bool,
+    /// Points the team can take on this sprint; None when capacity isn't being tracked
+    #[serde(default)]
+    pub sprint_capacity: Option<f64>,
+    /// How full the sprint is with what's been estimated so far, kept up to date
+    /// with `sprint_capacity`
+    #[serde(default)]
+    pub sprint_fill: Option<SprintFill>,
+    /// Tickets waiting to be estimated, in order
+    #[serde(default)]
+    pub ticket_queue: Vec<JiraTicket>,
+    /// Tickets voted on together in triage mode: one vote applies to all of them.
+    /// Empty outside a batch round.
+    #[serde(default)]
+    pub batch: Vec<JiraTicket>,
+    /// Re-estimation of previously estimated tickets, comparing each new
+    /// estimate with the old one; None outside a campaign
+    #[serde(default)]
+    pub campaign: Option<Campaign>,
+    /// Minutes allowed to estimate a ticket, keyed by ticket key; other tickets have no deadline
+    #[serde(default)]
+    pub ticket_deadlines: HashMap<String, u32>,
+    /// When the current ticket gets parked if its votes haven't been revealed by then
+    #[serde(default)]
+    pub ticket_deadline_at: Option<u64>,
+    /// Tickets skipped for running past their deadline, oldest first
+    #[serde(default)]
+    pub parked_tickets: Vec<JiraTicket>,
+    /// Notable events in this session, oldest first
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    /// Final summary, set once the host ends the session
+    #[serde(default)]
+    pub session_summary: Option<SessionSummary>,
+    /// When the current ticket was shown (or votes last reset)
+    #[serde(default)]
+    pub round_started_at: u64,
+    /// Seconds from the start of the round to each participant's first vote, keyed by participant ID
+    #[serde(default)]
+    pub round_first_votes: HashMap<String, u64>,
+    /// Votes handed to someone else for this round, for people who had to drop
+    /// off: delegator's participant ID -> delegate's participant ID
+    #[serde(default)]
+    pub delegations: HashMap<String, String>,
+    /// Concerns participants have flagged about the current ticket this round,
+    /// keyed by participant ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub signals: HashMap<String, Vec<TicketSignal>>,
+    /// Colors handed out per participant identity, so rejoining keeps the same color
+    #[serde(skip)]
+    identity_colors: HashMap<String, String>,
+    /// Whether the current round has already been counted in the statistics
+    #[serde(skip)]
+    round_recorded: bool,
+    /// Clock and ID source for this room
+    #[serde(skip)]
+    env: EnvHandle,
 }
 
 impl Room {
-    pub fn new(name: String) -> Self {
-        let id = Uuid::new_v4().to_string();
-        let invite_code = generate_invite_code();
+    /// A new room voting with `deck` (from `DeckDefinition::cards`), or with
+    /// the default story points deck
+    pub fn new(name: String, deck: Option<Vec<String>>, env: EnvHandle) -> Self {
+        let id = env.new_id();
+        let invite_code = generate_invite_code(&env.new_id());
+        let now = env.now_secs();
         
         Self {
             id,
             name,
             participants: Vec::new(),
             votes_revealed: false,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            reveal_at: None,
+            created_at: now,
+            starts_at: None,
+            time_zone: RoomTimeZone::default(),
             invite_code,
             current_ticket: None,
+            ticket_hash: None,
+            revision: 0,
+            deck: deck.unwrap_or_else(default_deck),
+            risk_deck: None,
+            issue_type_decks: HashMap::new(),
+            switched_from_deck: None,
+            estimation_method: EstimationMethod::default(),
+            estimate_rule: None,
+            participant_stats: HashMap::new(),
+            branding: RoomBranding::default(),
+            lan_only: false,
+            locked: false,
+            discussing: false,
+            voting_opens_at: None,
+            hide_voters: false,
+            disconnect_grace_secs: DEFAULT_DISCONNECT_GRACE_SECS,
+            auto_reveal: false,
+            reveal_mode: RevealMode::default(),
+            reveal_progress: None,
+            quorum: QuorumRules::default(),
+            can_reveal: false,
+            sprint_capacity: None,
+            sprint_fill: None,
+            ticket_queue: Vec::new(),
+            batch: Vec::new(),
+            campaign: None,
+            ticket_deadlines: HashMap::new(),
+            ticket_deadline_at: None,
+            parked_tickets: Vec::new(),
+            history: Vec::new(),
+            session_summary: None,
+            round_started_at: now,
+            round_first_votes: HashMap::new(),
+            delegations: HashMap::new(),
+            signals: HashMap::new(),
+            identity_colors: HashMap::new(),
+            round_recorded: false,
+            env,
+        }
+    }
+
+    /// Pick the room back up after loading it from disk. Participants are
+    /// shown as disconnected, so they get their places and votes back if they
+    /// rejoin within the grace period, and keep their colors if they do.
+    pub fn resume(&mut self, env: EnvHandle) {
+        self.attach(env);
+        let participant_ids: Vec<String> = self.participants.iter().filter(|p| !p.is_host).map(|p| p.id.clone()).collect();
+        for participant_id in &participant_ids {
+            self.participant_disconnected(participant_id);
         }
     }
 
-    pub fn add_participant(&mut self, participant: Participant) {
+    /// Pick up a room as it was stored, e.g. by another instance sharing the
+    /// storage. Its participants stay as they were there, connected or not.
+    pub fn attach(&mut self, env: EnvHandle) {
+        self.env = env;
+        // A revealed round was counted before the room was saved
+        self.round_recorded = self.votes_revealed;
+        for participant in &self.participants {
+            if let Some(identity) = &participant.identity {
+                self.identity_colors.insert(identity.clone(), participant.color.clone());
+            }
+        }
+    }
+
+    pub fn add_participant(&mut self, mut participant: Participant) {
+        if participant.color.is_empty() {
+            participant.color = self.assign_color(participant.identity.as_deref());
+        }
         self.participants.push(participant);
     }
 
+    /// The host's own participant, in rooms where the host votes too
+    pub fn host_participant(&self) -> Option<&Participant> {
+        self.participants.iter().find(|p| p.is_host)
+    }
+
+    /// Pick a color for a joining participant. A known identity gets its previous
+    /// color back; otherwise the least-used palette color is chosen, so the palette
+    /// is cycled through before any color repeats.
+    fn assign_color(&mut self, identity: Option<&str>) -> String {
+        if let Some(color) = identity.and_then(|i| self.identity_colors.get(i)) {
+            return color.clone();
+        }
+
+        let color = PARTICIPANT_COLORS
+            .iter()
+            .min_by_key(|color| self.participants.iter().filter(|p| p.color == **color).count())
+            .map(|color| color.to_string())
+            .unwrap_or_default();
+
+        if let Some(identity) = identity {
+            self.identity_colors.insert(identity.to_string(), color.clone());
+        }
+        color
+    }
+
+    /// Seconds until the announced start, or None if there's no start time or it has passed
+    pub fn starts_in_secs(&self) -> Option<u64> {
+        let now = self.env.now_secs();
+        self.starts_at.filter(|&at| at > now).map(|at| at - now)
+    }
+
+    /// Whether someone may join: anyone while unlocked, and only returning
+    /// participants (recognized by their identity token) while locked
+    pub fn admits(&self, identity: Option<&str>) -> bool {
+        !self.locked || identity.is_some_and(|i| self.identity_colors.contains_key(i))
+    }
+
     pub fn remove_participant(&mut self, participant_id: &str) {
         self.participants.retain(|p| p.id != participant_id);
+        self.delegations.retain(|from, to| from != participant_id && to != participant_id);
+        self.signals.remove(participant_id);
+    }
+
+    /// Let `to` vote on `from`'s behalf for the rest of the round, or take the
+    /// vote back with None. Delegating drops the delegator's own vote, and votes
+    /// can't be passed on a second time.
+    pub fn delegate_vote(&mut self, from: &str, to: Option<&str>) -> Result<(), String> {
+        let Some(to) = to else {
+            self.delegations.remove(from);
+            return Ok(());
+        };
+        if from == to {
+            return Err("You can't hand your vote to yourself".into());
+        }
+        let delegate = self.participants.iter().find(|p| p.id == to).ok_or("Participant not found")?;
+        if delegate.is_observer {
+            return Err(format!("{} is observing and doesn't vote", delegate.name));
+        }
+        if self.delegations.contains_key(to) {
+            return Err(format!("{} has handed their own vote on", delegate.name));
+        }
+        if self.delegations.values().any(|d| d == from) {
+            return Err("Someone has handed you their vote, so you can't pass it on".into());
+        }
+
+        self.delegations.insert(from.to_string(), to.to_string());
+        if let Some(participant) = self.participants.iter_mut().find(|p| p.id == from) {
+            participant.vote = None;
+            participant.risk_vote = None;
+            participant.updated_at = self.env.now_millis();
+        }
+        Ok(())
+    }
+
+    /// Raise or lower a participant's signal about the current ticket. Returns
+    /// false if there's no such participant.
+    pub fn set_signal(&mut self, participant_id: &str, signal: TicketSignal, raised: bool) -> bool {
+        if !self.participants.iter().any(|p| p.id == participant_id) {
+            return false;
+        }
+        let signals = self.signals.entry(participant_id.to_string()).or_default();
+        signals.retain(|s| *s != signal);
+        if raised {
+            signals.push(signal);
+        }
+        if signals.is_empty() {
+            self.signals.remove(participant_id);
+        }
+        true
+    }
+
+    /// How many participants raised each signal
+    pub fn signal_counts(&self) -> BTreeMap<TicketSignal, usize> {
+        let mut counts = BTreeMap::new();
+        for signal in self.signals.values().flatten() {
+            *counts.entry(*signal).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Take a participant out of the voting (or bring them back). Sitting out
+    /// drops their vote and any vote handed to or by them. Returns false if
+    /// there's no such participant.
+    pub fn set_sitting_out(&mut self, participant_id: &str, sitting_out: bool) -> bool {
+        let Some(participant) = self.participants.iter_mut().find(|p| p.id == participant_id) else {
+            return false;
+        };
+        participant.sitting_out = sitting_out;
+        participant.updated_at = self.env.now_millis();
+        if sitting_out {
+            participant.vote = None;
+            participant.risk_vote = None;
+            self.delegations.retain(|from, to| from != participant_id && to != participant_id);
+        }
+        true
+    }
+
+    /// Whether a participant has voted, themselves or through their delegate
+    fn has_voted(&self, participant: &Participant) -> bool {
+        participant.vote.is_some()
+            || self
+                .delegations
+                .get(&participant.id)
+                .is_some_and(|to| self.participants.iter().any(|p| p.id == *to && p.vote.is_some()))
+    }
+
+    /// Give someone rejoining with the same identity token (after a page refresh or
+    /// a dropped connection) their old place back, with their ID, vote and role.
+    /// Returns the reclaimed participant ID, or None if they're new here.
+    pub fn reclaim_participant(&mut self, participant: &Participant) -> Option<String> {
+        let identity = participant.identity.as_deref()?;
+        let existing = self.participants.iter_mut().find(|p| p.identity.as_deref() == Some(identity))?;
+        existing.name = participant.name.clone();
+        existing.client = participant.client.clone();
+        existing.disconnected_at = None;
+        Some(existing.id.clone())
+    }
+
+    /// Handle a participant's connection dropping: keep them for the grace
+    /// period, or remove them if there is none
+    pub fn participant_disconnected(&mut self, participant_id: &str) {
+        if self.disconnect_grace_secs == 0 {
+            self.remove_participant(participant_id);
+        } else if let Some(participant) = self.participants.iter_mut().find(|p| p.id == participant_id) {
+            participant.disconnected_at = Some(self.env.now_secs());
+        }
+    }
+
+    /// Remove participants whose grace period has run out, returning their IDs
+    pub fn expire_disconnected(&mut self) -> Vec<String> {
+        let now = self.env.now_secs();
+        let grace = self.disconnect_grace_secs;
+        let (expired, kept) = std::mem::take(&mut self.participants)
+            .into_iter()
+            .partition::<Vec<_>, _>(|p| p.disconnected_at.is_some_and(|at| now >= at + grace));
+        self.participants = kept;
+        expired.into_iter().map(|p| p.id).collect()
+    }
+
+    /// Change a participant's display name, keeping their vote
+    pub fn rename_participant(&mut self, participant_id: &str, name: String) -> bool {
+        if let Some(participant) = self.participants.iter_mut().find(|p| p.id == participant_id) {
+            participant.name = name;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether a participant joined only to watch
+    pub fn is_observer(&self, participant_id: &str) -> bool {
+        self.participants.iter().any(|p| p.id == participant_id && p.is_observer)
+    }
+
+    /// Whether a vote is one of the cards in this room's deck
+    pub fn is_valid_vote(&self, vote: &str) -> bool {
+        self.deck.iter().any(|card| card == vote)
     }
 
     pub fn set_vote(&mut self, participant_id: &str, vote: Option<String>) {
         if let Some(participant) = self.participants.iter_mut().find(|p| p.id == participant_id) {
+            // Only the first vote counts towards time-to-vote; changing your mind doesn't
+            if vote.is_some() && !self.round_first_votes.contains_key(participant_id) {
+                let elapsed = self.env.now_secs().saturating_sub(self.round_started_at);
+                self.round_first_votes.insert(participant_id.to_string(), elapsed);
+            }
+            // Voting yourself takes back a delegated vote, and means you're back in
+            if vote.is_some() {
+                self.delegations.remove(participant_id);
+                participant.sitting_out = false;
+            }
             participant.vote = vote;
+            participant.updated_at = self.env.now_millis();
+        }
+    }
+
+    /// Whether enough of the room has voted for the votes to be revealed
+    /// Participants sitting the round out or observing don't count either way.
+    pub fn quorum_met(&self) -> bool {
+        let voters: Vec<&Participant> = self.participants.iter().filter(|p| p.is_voter()).collect();
+        let voted = voters.iter().filter(|p| self.has_voted(p)).count();
+        if voted == 0 {
+            return false;
+        }
+        let percent = self.quorum.min_voted_percent.unwrap_or(100).min(100) as usize;
+        if voted * 100 < percent * voters.len() {
+            return false;
+        }
+        voters
+            .iter()
+            .filter(|p| self.quorum.requires(&p.name))
+            .all(|p| self.has_voted(p))
+    }
+
+    /// Reveal the votes if the room auto-reveals and the quorum is met.
+    /// Returns whether the votes were revealed.
+    pub fn auto_reveal_if_ready(&mut self) -> bool {
+        if !self.auto_reveal || self.votes_revealed || self.reveal_progress.is_some() || !self.quorum_met() {
+            return false;
+        }
+        if self.start_stepwise_reveal() {
+            // The cards are then shown one at a time
+            return false;
+        }
+        self.votes_revealed = true;
+        self.complete_round();
+        true
+    }
+
+    /// Offer the abstain card or take it out of the deck. Taking it out clears
+    /// the abstentions already cast this round.
+    pub fn set_abstain_offered(&mut self, offered: bool) {
+        let offering = self.deck.iter().any(|card| card == ABSTAIN);
+        if offered && !offering {
+            self.deck.push(ABSTAIN.to_string());
+        } else if !offered && offering {
+            self.deck.retain(|card| card != ABSTAIN);
+            for participant in &mut self.participants {
+                if participant.vote.as_deref() == Some(ABSTAIN) {
+                    participant.vote = None;
+                }
+            }
+        }
+        // Keep the room's own deck in step while an issue type's deck is in use
+        if let Some(own_deck) = &mut self.switched_from_deck {
+            own_deck.retain(|card| card != ABSTAIN);
+            if offered {
+                own_deck.push(ABSTAIN.to_string());
+            }
+        }
+    }
+
+    /// Whether a vote is one of the cards in this room's risk deck
+    pub fn is_valid_risk_vote(&self, vote: &str) -> bool {
+        self.risk_deck.as_ref().is_some_and(|deck| deck.iter().any(|card| card == vote))
+    }
+
+    pub fn set_risk_vote(&mut self, participant_id: &str, vote: Option<String>) {
+        if let Some(participant) = self.participants.iter_mut().find(|p| p.id == participant_id) {
+            participant.risk_vote = vote;
+            participant.updated_at = self.env.now_millis();
+        }
+    }
+
+    /// Turn the risk vote on (with the default deck) or off
+    pub fn set_risk_voting(&mut self, enabled: bool) {
+        if enabled {
+            if self.risk_deck.is_none() {
+                self.risk_deck = Some(RISK_POINTS.iter().map(|s| s.to_string()).collect());
+            }
+        } else {
+            self.risk_deck = None;
+            for participant in &mut self.participants {
+                participant.risk_vote = None;
+            }
         }
     }
 
     pub fn reset_votes(&mut self) {
+        let now = self.env.now_millis();
         for participant in &mut self.participants {
             participant.vote = None;
+            participant.risk_vote = None;
+            participant.updated_at = now;
         }
         self.votes_revealed = false;
+        self.reveal_at = None;
+        self.reveal_progress = None;
+        self.delegations.clear();
+        self.signals.clear();
+        self.round_recorded = false;
+        self.start_round_timer();
     }
 
-    pub fn get_vote_summary(&self) -> VoteSummary {
-        let votes: Vec<&str> = self
+    /// Start the round over at the host's request. Votes that were cast but
+    /// never revealed are noted in the history as a reset round.
+    pub fn restart_round(&mut self) {
+        let voted_count = self.participants.iter().filter(|p| p.vote.is_some()).count();
+        if !self.round_recorded && voted_count > 0 {
+            self.record_history(HistoryEvent::VotesReset {
+                ticket_key: self.current_ticket.as_ref().map(|t| t.key.clone()),
+                voted_count,
+            });
+        }
+        self.reset_votes();
+    }
+
+    /// Begin revealing the votes one by one, if the room's reveal mode says so.
+    /// Returns false for rooms that flip every card at once.
+    pub fn start_stepwise_reveal(&mut self) -> bool {
+        if self.reveal_mode == RevealMode::AllAtOnce || self.votes_revealed {
+            return false;
+        }
+        self.reveal_progress.get_or_insert_with(Vec::new);
+        true
+    }
+
+    /// Show the next vote of a one-by-one reveal, lowest first so the round
+    /// builds up to the highest card; votes that aren't numbers come last.
+    /// Showing the last vote reveals the round as usual. Returns the participant
+    /// whose vote was shown, or None if no such reveal is under way.
+    pub fn reveal_next_vote(&mut self) -> Option<String> {
+        let shown = self.reveal_progress.as_ref()?;
+        let next = self
             .participants
             .iter()
-            .filter_map(|p| p.vote.as_deref())
-            .collect();
+            .filter(|p| p.vote.is_some() && !shown.contains(&p.id))
+            .min_by(|a, b| {
+                let value = |p: &Participant| p.vote.as_deref().and_then(|v| v.parse::<f64>().ok()).unwrap_or(f64::INFINITY);
+                value(a).total_cmp(&value(b))
+            })
+            .map(|p| p.id.clone());
 
-        let total_voters = self.participants.len();
-        let voted_count = votes.len();
-        
-        let numeric_votes: Vec<f64> = votes
+        if let Some(id) = &next {
+            self.reveal_progress.as_mut()?.push(id.clone());
+        }
+        let voters = self.participants.iter().filter(|p| p.vote.is_some()).count();
+        if self.reveal_progress.as_ref().is_some_and(|shown| shown.len() >= voters) {
+            self.reveal_progress = None;
+            self.votes_revealed = true;
+            self.complete_round();
+        }
+        next
+    }
+
+    /// Show a new ticket, which starts a new round timer
+    pub fn set_current_ticket(&mut self, ticket: Option<JiraTicket>) {
+        self.ticket_deadline_at = ticket
+            .as_ref()
+            .and_then(|t| self.ticket_deadlines.get(&t.key))
+            .map(|minutes| self.env.now_secs() + *minutes as u64 * 60);
+        self.ticket_hash = ticket.as_ref().map(JiraTicket::content_hash);
+        // Showing a ticket ends any batch round
+        if ticket.is_some() {
+            self.batch.clear();
+        }
+        // Signals were about the ticket that's going away
+        if ticket.as_ref().map(|t| &t.key) != self.current_ticket.as_ref().map(|t| &t.key) {
+            self.signals.clear();
+        }
+        self.current_ticket = ticket;
+        self.start_round_timer();
+        self.switch_deck_for_issue_type();
+    }
+
+    /// Triage mode: take several queued tickets out of the queue to be voted on
+    /// together in one round, for obvious small ones. The current ticket, if any,
+    /// goes back to the front of the queue.
+    pub fn start_batch(&mut self, ticket_keys: &[String]) -> Result<(), String> {
+        if ticket_keys.len() < 2 {
+            return Err("Pick at least two queued tickets to estimate together".into());
+        }
+        if let Some(missing) = ticket_keys.iter().find(|key| !self.ticket_queue.iter().any(|t| &t.key == *key)) {
+            return Err(format!("{} isn't in the queue", missing));
+        }
+        let (batch, queue) = self.ticket_queue.drain(..).partition(|t| ticket_keys.contains(&t.key));
+        self.ticket_queue = queue;
+        if let Some(current) = self.current_ticket.take() {
+            self.ticket_queue.insert(0, current);
+        }
+        self.set_current_ticket(None);
+        self.batch = batch;
+        self.reset_votes();
+        Ok(())
+    }
+
+    /// Use the deck configured for the current ticket's issue type, or go back to
+    /// the room's own deck for types without one. Abstaining stays offered if the
+    /// room offers it.
+    fn switch_deck_for_issue_type(&mut self) {
+        let Some(ticket) = &self.current_ticket else {
+            return;
+        };
+        let own_deck = self.switched_from_deck.clone().unwrap_or_else(|| self.deck.clone());
+        let issue_type = ticket.issue_type.as_ref().map(|t| t.to_lowercase());
+        let issue_deck = issue_type.as_ref().and_then(|t| self.issue_type_decks.get(t));
+        let deck = match issue_deck {
+            Some(deck) => {
+                let mut deck = deck.clone();
+                if own_deck.iter().any(|card| card == ABSTAIN) && !deck.iter().any(|card| card == ABSTAIN) {
+                    deck.push(ABSTAIN.to_string());
+                }
+                deck
+            }
+            None => own_deck.clone(),
+        };
+        self.switched_from_deck = issue_deck.is_some().then_some(own_deck);
+        if deck != self.deck {
+            self.record_history(HistoryEvent::DeckSwitched {
+                ticket_key: ticket.key.clone(),
+                issue_type: issue_deck.and(ticket.issue_type.clone()),
+                deck: deck.clone(),
+            });
+            self.deck = deck;
+        }
+    }
+
+    /// Undo the automatic deck switch for the current ticket, going back to the
+    /// room's own deck. Votes that aren't in it are cleared.
+    pub fn revert_deck_switch(&mut self) -> Result<(), String> {
+        let deck = self.switched_from_deck.take().ok_or("The deck wasn't switched for this ticket")?;
+        if let Some(ticket) = &self.current_ticket {
+            self.record_history(HistoryEvent::DeckSwitched {
+                ticket_key: ticket.key.clone(),
+                issue_type: None,
+                deck: deck.clone(),
+            });
+        }
+        self.deck = deck;
+        for participant in &mut self.participants {
+            if participant.vote.as_ref().is_some_and(|vote| !self.deck.contains(vote)) {
+                participant.vote = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Give a ticket a time limit, or lift it. For the current ticket the clock
+    /// starts now, unless its votes were already revealed.
+    pub fn set_ticket_deadline(&mut self, ticket_key: &str, minutes: Option<u32>) {
+        match minutes {
+            Some(minutes) => self.ticket_deadlines.insert(ticket_key.to_string(), minutes),
+            None => self.ticket_deadlines.remove(ticket_key),
+        };
+        let is_current = self.current_ticket.as_ref().is_some_and(|t| t.key == ticket_key);
+        if is_current && !self.round_recorded {
+            self.ticket_deadline_at = minutes.map(|minutes| self.env.now_secs() + minutes as u64 * 60);
+        }
+    }
+
+    /// Park the current ticket if it ran past its deadline without its votes being
+    /// revealed, and move on to the next queued one. Returns the parked ticket.
+    pub fn park_overdue_ticket(&mut self) -> Option<JiraTicket> {
+        let deadline = self.ticket_deadline_at?;
+        if self.env.now_secs() < deadline || self.is_ended() {
+            return None;
+        }
+        let ticket = self.current_ticket.clone()?;
+        self.record_history(HistoryEvent::TicketParked {
+            ticket_key: ticket.key.clone(),
+            minutes: self.ticket_deadlines.get(&ticket.key).copied().unwrap_or_default(),
+        });
+        self.parked_tickets.push(ticket.clone());
+        self.reset_votes();
+        if self.advance_ticket().is_none() {
+            self.set_current_ticket(None);
+        }
+        Some(ticket)
+    }
+
+    /// Put a parked ticket back at the end of the queue
+    pub fn requeue_parked_ticket(&mut self, ticket_key: &str) -> bool {
+        let Some(position) = self.parked_tickets.iter().position(|t| t.key == ticket_key) else {
+            return false;
+        };
+        let ticket = self.parked_tickets.remove(position);
+        self.enqueue_tickets(vec![ticket]);
+        true
+    }
+
+    fn start_round_timer(&mut self) {
+        self.round_started_at = self.env.now_secs();
+        self.round_first_votes.clear();
+    }
+
+    /// Average seconds from the start of the round to each participant's first vote
+    pub fn average_time_to_vote(&self) -> Option<f64> {
+        if self.round_first_votes.is_empty() {
+            return None;
+        }
+        let total: u64 = self.round_first_votes.values().sum();
+        Some(total as f64 / self.round_first_votes.len() as f64)
+    }
+
+    /// Append tickets to the end of the estimation queue, skipping ones already queued
+    pub fn enqueue_tickets(&mut self, tickets: Vec<JiraTicket>) {
+        for ticket in tickets {
+            if !self.ticket_queue.iter().any(|t| t.key == ticket.key) {
+                self.ticket_queue.push(ticket);
+            }
+        }
+    }
+
+    /// Show the next queued ticket, removing it from the queue
+    pub fn advance_ticket(&mut self) -> Option<JiraTicket> {
+        if self.ticket_queue.is_empty() {
+            return None;
+        }
+        let ticket = self.ticket_queue.remove(0);
+        self.set_current_ticket(Some(ticket.clone()));
+        Some(ticket)
+    }
+
+    /// Replace a queued ticket with freshly fetched details
+    pub fn update_queued_ticket(&mut self, ticket: JiraTicket) -> bool {
+        match self.ticket_queue.iter_mut().find(|t| t.key == ticket.key) {
+            Some(queued) => {
+                *queued = ticket;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take the current ticket out of this room, along with the votes cast so far
+    /// and its history, so it can be carried on in another room
+    pub fn hand_off_ticket(&mut self) -> Option<TicketHandoff> {
+        let ticket = self.current_ticket.clone()?;
+        let votes = self
+            .participants
             .iter()
-            .filter_map(|v| v.parse::<f64>().ok())
+            .filter(|p| p.vote.is_some() || p.risk_vote.is_some())
+            .map(|p| HandoffVote {
+                identity: p.identity.clone(),
+                name: p.name.clone(),
+                vote: p.vote.clone(),
+                risk_vote: p.risk_vote.clone(),
+            })
             .collect();
+        let (history, kept) = std::mem::take(&mut self.history)
+            .into_iter()
+            .partition(|entry| entry.event.ticket_key() == Some(ticket.key.as_str()));
+        self.history = kept;
 
-        let average = if numeric_votes.is_empty() {
-            None
-        } else {
-            Some(numeric_votes.iter().sum::<f64>() / numeric_votes.len() as f64)
+        self.set_current_ticket(None);
+        self.reset_votes();
+        Some(TicketHandoff { ticket, votes, history })
+    }
+
+    /// Carry on estimating a ticket handed off from another room. Votes move over
+    /// to participants recognized by identity token or name; anything the current
+    /// ticket had going is put back at the front of the queue.
+    pub fn receive_ticket(&mut self, handoff: TicketHandoff) {
+        if let Some(current) = self.current_ticket.take() {
+            if current.key != handoff.ticket.key && !self.ticket_queue.iter().any(|t| t.key == current.key) {
+                self.ticket_queue.insert(0, current);
+            }
+        }
+        self.ticket_queue.retain(|t| t.key != handoff.ticket.key);
+        self.set_current_ticket(Some(handoff.ticket));
+        self.reset_votes();
+
+        for carried in handoff.votes {
+            let Some(participant) = self.participants.iter().find(|p| carried.matches(p)) else {
+                continue;
+            };
+            let participant_id = participant.id.clone();
+            // The rooms may use different decks, so only keep votes that make sense here
+            if let Some(vote) = carried.vote.filter(|v| self.is_valid_vote(v)) {
+                self.set_vote(&participant_id, Some(vote));
+            }
+            if let Some(risk_vote) = carried.risk_vote.filter(|v| self.is_valid_risk_vote(v)) {
+                self.set_risk_vote(&participant_id, Some(risk_vote));
+            }
+        }
+
+        self.history.extend(handoff.history);
+        self.history.sort_by_key(|entry| entry.timestamp);
+    }
+
+    pub fn record_history(&mut self, event: HistoryEvent) {
+        self.history.push(HistoryEntry {
+            timestamp: self.env.now_secs(),
+            event,
+        });
+    }
+
+    /// Tag the revealed round with the host's take on the outcome ("needs spike",
+    /// "split", "accepted", ...). Returns the round's ticket key, if it had one.
+    pub fn tag_round(&mut self, tag: &str) -> Result<Option<String>, String> {
+        let tag = tag.trim();
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(format!("Tags must be 1 to {} characters", MAX_TAG_LENGTH));
+        }
+        if !self.votes_revealed {
+            return Err("Reveal the votes before tagging the round".into());
+        }
+        let Some(HistoryEvent::VotesRevealed { ticket_key, tags, .. }) = self
+            .history
+            .iter_mut()
+            .rev()
+            .map(|entry| &mut entry.event)
+            .find(|event| matches!(event, HistoryEvent::VotesRevealed { .. }))
+        else {
+            return Err("No revealed round to tag".into());
         };
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+        Ok(ticket_key.clone())
+    }
 
-        VoteSummary {
-            total_voters,
-            voted_count,
-            average,
+    /// Settle the revealed round on `value` by the host's call rather than the
+    /// votes. A reason is required; it's kept in the history next to the
+    /// average that was overridden.
+    pub fn override_estimate(&mut self, value: &str, reason: &str) -> Result<(), String> {
+        let value = value.trim();
+        if !self.deck.iter().any(|card| card == value) || value == ABSTAIN {
+            return Err(format!("{} is not a card in this room's deck", value));
+        }
+        let reason = validate_text("reason", "The reason", reason, MAX_OVERRIDE_REASON_LENGTH)?;
+        if !self.votes_revealed {
+            return Err("Reveal the votes before overriding the estimate".into());
+        }
+        let Some(HistoryEvent::VotesRevealed { ticket_key, average, batch, .. }) = self
+            .history
+            .iter()
+            .rev()
+            .map(|entry| &entry.event)
+            .find(|event| matches!(event, HistoryEvent::VotesRevealed { .. }))
+        else {
+            return Err("No revealed round to override".into());
+        };
+        // A batch round's estimate is overridden for each of its tickets
+        let keys: Vec<Option<String>> = if batch.is_empty() {
+            vec![ticket_key.clone()]
+        } else {
+            batch.iter().cloned().map(Some).collect()
+        };
+        let average = *average;
+        for ticket_key in keys {
+            self.record_history(HistoryEvent::EstimateOverridden {
+                ticket_key,
+                value: value.to_string(),
+                reason: reason.clone(),
+                average,
+            });
         }
+        Ok(())
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VoteSummary {
-    pub total_voters: usize,
-    pub voted_count: usize,
-    pub average: Option<f64>,
-}
+    /// Fold the current round's votes into the per-participant statistics and the history.
+    /// Each round is only counted once, even if votes are hidden and revealed again.
+    pub fn complete_round(&mut self) {
+        if self.round_recorded {
+            return;
+        }
+        self.round_recorded = true;
+        // The ticket got its estimate, so it's no longer at risk of being parked
+        self.ticket_deadline_at = None;
 
-/// Generate a human-readable invite code (e.g., "51 58 87 72")
-fn generate_invite_code() -> String {
+        let summary = self.get_vote_summary();
+        let revealed = HistoryEvent::VotesRevealed {
+            ticket_key: self.current_ticket.as_ref().map(|t| t.key.clone()),
+            voted_count: summary.voted_count,
+            average: summary.average,
+            average_time_to_vote_secs: self.average_time_to_vote(),
+            risk_average: summary.risk_average,
+            votes: self
+                .participants
+                .iter()
+                .filter_map(|p| {
+                    p.vote.clone().map(|vote| RecordedVote {
+                        name: p.name.clone(),
+                        vote,
+                        on_behalf_of: self
+                            .delegations
+                            .iter()
+                            .filter(|(_, to)| **to == p.id)
+                            .filter_map(|(from, _)| self.participants.iter().find(|d| d.id == *from))
+                            .map(|d| d.name.clone())
+                            .collect(),
+                    })
+                })
+                .collect(),
+            tags: Vec::new(),
+            batch: Vec::new(),
+            signals: self.signal_counts(),
+        };
+        // A batch round records the same result for each of its tickets
+        if self.batch.is_empty() {
+            self.record_history(revealed);
+        } else {
+            let keys: Vec<String> = self.batch.iter().map(|t| t.key.clone()).collect();
+            for key in &keys {
+                let mut event = revealed.clone();
+                if let HistoryEvent::VotesRevealed { ticket_key, batch, .. } = &mut event {
+                    *ticket_key = Some(key.clone());
+                    *batch = keys.clone();
+                }
+                self.record_history(event);
+            }
+        }
+        if let (Some(campaign), Some(average)) = (self.campaign.as_mut(), summary.average.filter(|a| *a > 0.0)) {
+            let keys = if self.batch.is_empty() { self.current_ticket.iter().collect() } else { self.batch.iter().collect::<Vec<_>>() };
+            for ticket in keys {
+                campaign.record(&ticket.key, average);
+            }
+        }
+
+        let numeric_votes: Vec<(&str, f64)> = self
+            .participants
+            .iter()
+            .filter_map(|p| {
+                p.vote
+                    .as_deref()
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .map(|v| (p.id.as_str(), v))
+            })
+            .collect();
+
+        let team_average = if numeric_votes.is_empty() {
+            None
+        } else {
+            Some(numeric_votes.iter().map(|(_, v)| v).sum::<f64>() / numeric_votes.len() as f64)
+        };
+        let highest = numeric_votes.iter().map(|(_, v)| *v).fold(f64::MIN, f64::max);
+        let lowest = numeric_votes.iter().map(|(_, v)| *v).fold(f64::MAX, f64::min);
+        // Nobody stands out when fewer than two people voted or everyone agreed
+        let has_spread = numeric_votes.len() > 1 && highest > lowest;
+
+        for participant in &self.participants {
+            if participant.vote.is_none() {
+                continue;
+            }
+
+            let stats = self
+                .participant_stats
+                .entry(participant.id.clone())
+                .or_insert_with(|| ParticipantStats::new(&participant.id));
+            stats.name = participant.name.clone();
+            stats.rounds_voted += 1;
+            if let Some(seconds) = self.round_first_votes.get(&participant.id) {
+                stats.timed_rounds += 1;
+                stats.time_to_vote_total += seconds;
+            }
+
+            if let (Some((_, vote)), Some(team_average)) = (
+                numeric_votes.iter().find(|(id, _)| *id == participant.id),
+                team_average,
+            ) {
+                stats.numeric_rounds += 1;
+                stats.vote_total += vote;
+                stats.team_average_total += team_average;
+                if has_spread && *vote == highest {
+                    stats.times_highest += 1;
+                }
+                if has_spread && *vote == lowest {
+                    stats.times_lowest += 1;
+                }
+            }
+        }
+    }
+
+    /// Every round voted in this room, oldest first, with the estimate it came to
+    pub fn voting_rounds(&self) -> Vec<VotingRound> {
+        let mut rounds: Vec<VotingRound> = Vec::new();
+        for entry in &self.history {
+            match &entry.event {
+                HistoryEvent::VotesRevealed { ticket_key, voted_count, average, votes, .. } => {
+                    let numeric: Vec<f64> = votes.iter().filter_map(|v| v.vote.parse().ok()).collect();
+                    rounds.push(VotingRound {
+                        ticket_key: ticket_key.clone(),
+                        timestamp: entry.timestamp,
+                        revealed: true,
+                        voted_count: *voted_count,
+                        votes: votes.clone(),
+                        average: *average,
+                        final_estimate: self.estimation_method.estimator().suggest(&numeric, &self.deck),
+                        override_reason: None,
+                    });
+                }
+                HistoryEvent::VotesReset { ticket_key, voted_count } => rounds.push(VotingRound {
+                    ticket_key: ticket_key.clone(),
+                    timestamp: entry.timestamp,
+                    revealed: false,
+                    voted_count: *voted_count,
+                    votes: Vec::new(),
+                    average: None,
+                    final_estimate: None,
+                    override_reason: None,
+                }),
+                HistoryEvent::EstimateOverridden { ticket_key, value, reason, .. } => {
+                    if let Some(round) = rounds.iter_mut().rev().find(|r| r.revealed && r.ticket_key == *ticket_key) {
+                        round.final_estimate = Some(value.clone());
+                        round.override_reason = Some(reason.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        rounds
+    }
+
+    /// Close the session: no more joins or votes are accepted afterwards
+    pub fn end_session(&mut self) -> SessionSummary {
+        let ended_at = self.env.now_secs();
+
+        // Latest revealed average per ticket; re-votes replace earlier rounds
+        let mut estimates: Vec<(String, Option<f64>)> = Vec::new();
+        let mut rounds = 0;
+        let mut overrides = 0;
+        let mut round_times = Vec::new();
+        for entry in &self.history {
+            let (key, estimate) = match &entry.event {
+                HistoryEvent::VotesRevealed { ticket_key, average, average_time_to_vote_secs, .. } => {
+                    rounds += 1;
+                    round_times.extend(*average_time_to_vote_secs);
+                    (ticket_key, *average)
+                }
+                // The host's call replaces the round's average
+                HistoryEvent::EstimateOverridden { ticket_key, value, .. } => {
+                    overrides += 1;
+                    (ticket_key, value.parse().ok())
+                }
+                _ => continue,
+            };
+            if let Some(key) = key {
+                match estimates.iter_mut().find(|(k, _)| k == key) {
+                    Some(existing) => existing.1 = estimate,
+                    None => estimates.push((key.clone(), estimate)),
+                }
+            }
+        }
+
+        let mut attendance: Vec<String> = self
+            .participant_stats
+            .values()
+            .map(|s| s.name.clone())
+            .chain(self.participants.iter().map(|p| p.name.clone()))
+            .collect();
+        attendance.sort();
+        attendance.dedup();
+
+        let summary = SessionSummary {
+            room_name: self.name.clone(),
+            started_at: self.created_at,
+            ended_at,
+            duration_secs: ended_at.saturating_sub(self.created_at),
+            rounds,
+            overrides,
+            total_points: estimates.iter().filter_map(|(_, avg)| *avg).sum(),
+            tickets_estimated: estimates.into_iter().map(|(key, _)| key).collect(),
+            average_time_to_vote_secs: if round_times.is_empty() {
+                None
+            } else {
+                Some(round_times.iter().sum::<f64>() / round_times.len() as f64)
+            },
+            attendance,
+        };
+
+        self.session_summary = Some(summary.clone());
+        summary
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.session_summary.is_some()
+    }
+
+    /// Hold voting while the story is read and discussed, so nobody anchors
+    /// the others with an early vote
+    pub fn start_discussion(&mut self) {
+        self.discussing = true;
+        self.voting_opens_at = None;
+    }
+
+    /// End the discussion; voting opens once `countdown_secs` have passed
+    pub fn open_voting(&mut self, countdown_secs: u32) {
+        self.discussing = false;
+        self.voting_opens_at = (countdown_secs > 0).then(|| self.env.now_millis() + u64::from(countdown_secs) * 1000);
+    }
+
+    /// Whether votes are accepted: not during a discussion or the countdown after it
+    pub fn voting_open(&self) -> bool {
+        !self.discussing && self.voting_opens_at.is_none_or(|at| self.env.now_millis() >= at)
+    }
+
+    /// Summaries of the per-participant statistics, sorted by name
+    pub fn get_participant_stats(&self) -> Vec<ParticipantStatsSummary> {
+        let mut summaries: Vec<ParticipantStatsSummary> = self
+            .participant_stats
+            .values()
+            .map(ParticipantStats::summary)
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
+
+    /// Short hash of the state every copy of the room must agree on: participants,
+    /// votes, reveal state and ticket. The relay computes the same hash the same way,
+    /// so the two copies can be compared.
+    pub fn checksum(&self) -> String {
+        let mut participants: Vec<&Participant> = self.participants.iter().collect();
+        participants.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut canonical = String::new();
+        for p in participants {
+            canonical.push_str(&p.id);
+            canonical.push('\u{1}');
+            canonical.push_str(&p.name);
+            canonical.push('\u{1}');
+            canonical.push_str(p.vote.as_deref().unwrap_or(""));
+            canonical.push('\u{1}');
+            canonical.push_str(p.risk_vote.as_deref().unwrap_or(""));
+            canonical.push('\u{2}');
+        }
+        canonical.push(if self.votes_revealed { '1' } else { '0' });
+        canonical.push('\u{2}');
+        canonical.push_str(self.ticket_hash.as_deref().unwrap_or(""));
+
+        format!("{:08x}", fnv1a(canonical.as_bytes()))
+    }
+
+    /// Whether clients are currently kept from seeing who has voted
+    pub fn voters_hidden(&self) -> bool {
+        self.hide_voters
+            && !self.votes_revealed
+            && self.reveal_progress.is_none()
+            && !self.participants.iter().filter(|p| p.is_voter()).all(|p| self.has_voted(p))
+    }
+
+    /// The room as `viewer` may see it. While voters are hidden, everyone
+    /// else's votes are left out, not just their values. A campaign's old
+    /// estimates are always left out.
+    pub fn view_for(&self, viewer: &str) -> Room {
+        let mut room = self.clone();
+        // Old estimates stay with the host; participants only see them in the results
+        if let Some(campaign) = &mut room.campaign {
+            campaign.previous.clear();
+        }
+        if self.voters_hidden() {
+            for participant in room.participants.iter_mut().filter(|p| p.id != viewer) {
+                participant.vote = None;
+                participant.risk_vote = None;
+            }
+            room.round_first_votes.retain(|id, _| id == viewer);
+        }
+        // When someone's vote last changed is only for merging with the relay,
+        // and would show who is voting while that's hidden
+        for participant in room.participants.iter_mut().filter(|p| p.id != viewer) {
+            participant.updated_at = 0;
+        }
+        // Only the host sees who raised what; everyone else sees their own
+        room.signals.retain(|id, _| id == viewer);
+        room
+    }
+
+    /// The `RoomUpdate` sent to `viewer`, without the ticket details
+    pub fn update_message(&self, viewer: &str) -> WsMessage {
+        let mut room = self.view_for(viewer);
+        room.current_ticket = None;
+        let checksum = room.checksum();
+        WsMessage::RoomUpdate { room, checksum, server_time: self.env.now_millis() }
+    }
+
+    /// The `TicketChanged` message for the current ticket
+    pub fn ticket_changed_message(&self) -> WsMessage {
+        WsMessage::TicketChanged {
+            ticket: self.current_ticket.clone(),
+            ticket_hash: self.ticket_hash.clone(),
+        }
+    }
+
+    /// Snapshot for a participant joining mid-session
+    pub fn catch_up(&self, participant_id: &str) -> CatchUp {
+        let history_start = self.history.len().saturating_sub(CATCH_UP_HISTORY_LEN);
+        // Summarized from what the participant may see, so hidden votes stay hidden
+        let room = self.view_for(participant_id);
+
+        CatchUp {
+            participant_id: participant_id.to_string(),
+            voting_open: !self.votes_revealed && self.reveal_progress.is_none() && !self.is_ended(),
+            vote_summary: room.get_vote_summary(),
+            room,
+            recent_history: self.history[history_start..].to_vec(),
+        }
+    }
+
+    pub fn get_vote_summary(&self) -> VoteSummary {
+        let votes: Vec<&str> = self
+            .participants
+            .iter()
+            .filter_map(|p| p.vote.as_deref())
+            .collect();
+
+        let total_voters = self.participants.iter().filter(|p| !p.is_observer).count();
+        let voted_count = votes.len();
+        
+        let numeric_votes: Vec<f64> = votes
+            .iter()
+            .filter_map(|v| v.parse::<f64>().ok())
+            .collect();
+
+        let average = if numeric_votes.is_empty() {
+            None
+        } else {
+            Some(numeric_votes.iter().sum::<f64>() / numeric_votes.len() as f64)
+        };
+
+        let suggested = self.estimation_method.estimator().suggest(&numeric_votes, &self.deck);
+
+        let risk_votes: Vec<f64> = self
+            .participants
+            .iter()
+            .filter_map(|p| p.risk_vote.as_deref())
+            .filter_map(|v| v.parse::<f64>().ok())
+            .collect();
+        let risk_average = if risk_votes.is_empty() {
+            None
+        } else {
+            Some(risk_votes.iter().sum::<f64>() / risk_votes.len() as f64)
+        };
+
+        VoteSummary {
+            total_voters,
+            voted_count,
+            abstained_count: votes.iter().filter(|v| **v == ABSTAIN).count(),
+            average,
+            suggested,
+            risk_voted_count: self.participants.iter().filter(|p| p.risk_vote.is_some()).count(),
+            risk_average,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteSummary {
+    pub total_voters: usize,
+    pub voted_count: usize,
+    /// Votes that were `ABSTAIN`; included in `voted_count` but not in the average
+    #[serde(default)]
+    pub abstained_count: usize,
+    pub average: Option<f64>,
+    /// Card suggested by the room's estimation method
+    #[serde(default)]
+    pub suggested: Option<String>,
+    #[serde(default)]
+    pub risk_voted_count: usize,
+    /// Average of the numeric risk votes
+    #[serde(default)]
+    pub risk_average: Option<f64>,
+}
+
+/// Longest tag the host may put on a round
+const MAX_TAG_LENGTH: usize = 40;
+
+/// Longest reason the host may give for overriding an estimate
+const MAX_OVERRIDE_REASON_LENGTH: usize = 200;
+
+/// Number of history entries included in a catch-up
+const CATCH_UP_HISTORY_LEN: usize = 10;
+
+/// Everything a late joiner needs to take part straight away
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatchUp {
+    /// The joiner's own participant ID
+    pub participant_id: String,
+    pub room: Room,
+    /// Whether votes can still be cast for the current ticket
+    pub voting_open: bool,
+    pub vote_summary: VoteSummary,
+    /// Most recent session events, oldest first
+    pub recent_history: Vec<HistoryEntry>,
+}
+
+/// Room-level theme and branding
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RoomBranding {
+    /// Hex color such as "#1a2b3c"
+    pub accent_color: Option<String>,
+    /// Title shown instead of the room name
+    pub title: Option<String>,
+    /// URL path of the uploaded logo
+    pub logo_url: Option<String>,
+}
+
+/// How the cards are shown when the votes are revealed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealMode {
+    /// Every card flips at the same moment
+    #[default]
+    AllAtOnce,
+    /// One card at a time, on a timer
+    Timed,
+    /// One card at a time, each time the host moves on
+    HostPaced,
+}
+
+/// Preconditions for revealing the votes. Enforced when the room auto-reveals;
+/// otherwise they're only a hint for the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuorumRules {
+    /// Share of participants (1-100) who must have voted; None means everyone
+    #[serde(default)]
+    pub min_voted_percent: Option<u8>,
+    /// Participants, by name, whose vote is always needed (e.g. the tech lead or QA).
+    /// Only counts while they're in the room.
+    #[serde(default)]
+    pub required_voters: Vec<String>,
+}
+
+impl QuorumRules {
+    fn requires(&self, name: &str) -> bool {
+        self.required_voters.iter().any(|required| required.trim().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A ticket on its way from one room to another
+#[derive(Debug, Clone)]
+pub struct TicketHandoff {
+    pub ticket: JiraTicket,
+    votes: Vec<HandoffVote>,
+    history: Vec<HistoryEntry>,
+}
+
+/// A vote cast before a hand-off. Participant IDs differ between rooms, so the
+/// voter is remembered by identity token and name instead.
+#[derive(Debug, Clone)]
+struct HandoffVote {
+    identity: Option<String>,
+    name: String,
+    vote: Option<String>,
+    risk_vote: Option<String>,
+}
+
+impl HandoffVote {
+    fn matches(&self, participant: &Participant) -> bool {
+        match (&self.identity, &participant.identity) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.name.eq_ignore_ascii_case(&participant.name),
+        }
+    }
+}
+
+/// One participant's vote as revealed, kept in the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedVote {
+    pub name: String,
+    pub vote: String,
+    /// People who handed this voter their vote for the round; the vote counts once
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_behalf_of: Vec<String>,
+}
+
+/// One round of voting, for looking back over a session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VotingRound {
+    pub ticket_key: Option<String>,
+    /// When the votes were revealed or reset
+    pub timestamp: u64,
+    /// False for rounds the host reset before revealing; their votes aren't kept
+    pub revealed: bool,
+    pub voted_count: usize,
+    pub votes: Vec<RecordedVote>,
+    pub average: Option<f64>,
+    /// The host's override if there was one, else what the room's estimation method suggests
+    pub final_estimate: Option<String>,
+    pub override_reason: Option<String>,
+}
+
+/// A concern a participant can flag about the ticket being estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketSignal {
+    UnclearRequirements,
+    NeedsDesign,
+    SecurityReview,
+}
+
+impl TicketSignal {
+    pub fn label(self) -> &'static str {
+        match self {
+            TicketSignal::UnclearRequirements => "unclear requirements",
+            TicketSignal::NeedsDesign => "needs design",
+            TicketSignal::SecurityReview => "security review",
+        }
+    }
+}
+
+/// A timestamped entry in a room's session history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: HistoryEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// A ticket was broken down into sub-tasks in Jira
+    TicketSplit {
+        ticket_key: String,
+        sub_ticket_keys: Vec<String>,
+    },
+    /// A new issue was filed in Jira from the room
+    IssueCreated { ticket_key: String },
+    /// Votes were revealed, completing a round
+    VotesRevealed {
+        ticket_key: Option<String>,
+        voted_count: usize,
+        average: Option<f64>,
+        /// Average seconds from the ticket being shown to each first vote
+        #[serde(default)]
+        average_time_to_vote_secs: Option<f64>,
+        /// Average risk vote, for rooms that vote on risk too
+        #[serde(default)]
+        risk_average: Option<f64>,
+        /// What each voter put down
+        #[serde(default)]
+        votes: Vec<RecordedVote>,
+        /// The host's notes on the outcome, e.g. "needs spike" or "accepted"
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        /// Every ticket voted on in the round, for triage rounds estimating several at once
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        batch: Vec<String>,
+        /// How many participants raised each signal about the ticket
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        signals: BTreeMap<TicketSignal, usize>,
+    },
+    /// The ticket was moved to another room to finish estimating it
+    TicketTransferred {
+        ticket_key: String,
+        from_room: String,
+        to_room: String,
+    },
+    /// The ticket ran past its deadline without an estimate and was skipped
+    TicketParked { ticket_key: String, minutes: u32 },
+    /// The host cleared the votes without revealing them. The votes themselves
+    /// stay secret; only how many were cast is kept.
+    VotesReset { ticket_key: Option<String>, voted_count: usize },
+    /// The host set the estimate outside the team's consensus
+    EstimateOverridden {
+        ticket_key: Option<String>,
+        value: String,
+        reason: String,
+        /// The round's average at the time, for comparison
+        average: Option<f64>,
+    },
+    /// The deck changed for a ticket: to its issue type's deck, or back to the
+    /// room's own (`issue_type` is then None)
+    DeckSwitched {
+        ticket_key: String,
+        issue_type: Option<String>,
+        deck: Vec<String>,
+    },
+}
+
+impl HistoryEvent {
+    /// Ticket the event is about, if any
+    pub fn ticket_key(&self) -> Option<&str> {
+        match self {
+            HistoryEvent::TicketSplit { ticket_key, .. }
+            | HistoryEvent::IssueCreated { ticket_key }
+            | HistoryEvent::TicketTransferred { ticket_key, .. }
+            | HistoryEvent::TicketParked { ticket_key, .. }
+            | HistoryEvent::DeckSwitched { ticket_key, .. } => Some(ticket_key),
+            HistoryEvent::VotesRevealed { ticket_key, .. }
+            | HistoryEvent::VotesReset { ticket_key, .. }
+            | HistoryEvent::EstimateOverridden { ticket_key, .. } => ticket_key.as_deref(),
+        }
+    }
+}
+
+/// Final snapshot of a session, computed when the host ends it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub room_name: String,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_secs: u64,
+    /// Number of rounds revealed
+    pub rounds: usize,
+    /// Number of estimates the host overrode
+    #[serde(default)]
+    pub overrides: usize,
+    /// Keys of the tickets that had votes revealed
+    pub tickets_estimated: Vec<String>,
+    /// Sum of the final average estimate of each ticket
+    pub total_points: f64,
+    /// Mean over rounds of the average time it took people to vote
+    #[serde(default)]
+    pub average_time_to_vote_secs: Option<f64>,
+    /// Everyone who took part at any point
+    pub attendance: Vec<String>,
+}
+
+/// Running totals for one participant across the rounds of a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParticipantStats {
+    pub participant_id: String,
+    pub name: String,
+    /// Rounds in which the participant cast any vote
+    pub rounds_voted: u32,
+    /// Rounds in which the participant cast a numeric vote
+    pub numeric_rounds: u32,
+    /// Sum of the participant's numeric votes
+    pub vote_total: f64,
+    /// Sum of the team averages for the rounds counted in `numeric_rounds`
+    pub team_average_total: f64,
+    pub times_highest: u32,
+    pub times_lowest: u32,
+    /// Rounds in which the time to the participant's first vote was measured
+    #[serde(default)]
+    pub timed_rounds: u32,
+    /// Sum of the seconds to first vote over `timed_rounds`
+    #[serde(default)]
+    pub time_to_vote_total: u64,
+}
+
+impl ParticipantStats {
+    fn new(participant_id: &str) -> Self {
+        Self {
+            participant_id: participant_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn summary(&self) -> ParticipantStatsSummary {
+        let (average_vote, team_average) = if self.numeric_rounds == 0 {
+            (None, None)
+        } else {
+            let rounds = self.numeric_rounds as f64;
+            (Some(self.vote_total / rounds), Some(self.team_average_total / rounds))
+        };
+
+        ParticipantStatsSummary {
+            participant_id: self.participant_id.clone(),
+            name: self.name.clone(),
+            rounds_voted: self.rounds_voted,
+            average_vote,
+            team_average,
+            // Positive bias means the participant tends to estimate above the team
+            bias: average_vote.zip(team_average).map(|(own, team)| own - team),
+            times_highest: self.times_highest,
+            times_lowest: self.times_lowest,
+            average_time_to_vote_secs: if self.timed_rounds == 0 {
+                None
+            } else {
+                Some(self.time_to_vote_total as f64 / self.timed_rounds as f64)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticipantStatsSummary {
+    pub participant_id: String,
+    pub name: String,
+    pub rounds_voted: u32,
+    pub average_vote: Option<f64>,
+    pub team_average: Option<f64>,
+    pub bias: Option<f64>,
+    pub times_highest: u32,
+    pub times_lowest: u32,
+    pub average_time_to_vote_secs: Option<f64>,
+}
+
+/// Machine-readable reason for a `WsMessage::Error`, so clients can show an
+/// actionable message rather than the raw text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    RoomNotFound,
+    RoomFull,
+    InvalidVote,
+    InvalidName,
+    Banned,
+    PinRequired,
+    /// The room only accepts clients on the local network
+    LanOnly,
+    SessionEnded,
+    /// The host locked the room to new participants
+    RoomLocked,
+    /// Too many join attempts from this address
+    Throttled,
+    /// A vote couldn't be delegated
+    InvalidDelegation,
+    /// The room is in its discussion phase, or counting down to voting
+    VotingClosed,
+    /// Every seat the app is licensed for is taken
+    SeatLimitReached,
+    #[default]
+    Other,
+}
+
+/// An error to report back to a participant
+#[derive(Debug, Clone)]
+pub struct ClientError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ClientError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ClientError> for WsMessage {
+    fn from(error: ClientError) -> Self {
+        WsMessage::Error {
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
+/// 32-bit FNV-1a; simple enough to reproduce exactly in the relay and web client
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5u32, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x01000193))
+}
+
+/// Current Unix time in seconds
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Current Unix time in milliseconds
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Generate a human-readable invite code (e.g., "51 58 87 72") from a fresh ID
+fn generate_invite_code(seed: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
     
-    let uuid = Uuid::new_v4();
     let mut hasher = DefaultHasher::new();
-    uuid.hash(&mut hasher);
+    seed.hash(&mut hasher);
     let hash = hasher.finish();
     
     format!(
@@ -147,17 +1918,583 @@ fn generate_invite_code() -> String {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum WsMessage {
-    /// Client wants to join a room
-    Join { room_id: String, name: String },
+    /// Client wants to join a room. On direct connections the room comes from
+    /// the `/ws/:room_id` URL and `room_id` here is ignored.
+    Join {
+        room_id: String,
+        name: String,
+        /// Stable client-generated token, used to recognize rejoins
+        #[serde(default)]
+        identity: Option<String>,
+        /// Client details the participant opted to share with the host
+        #[serde(default)]
+        client: Option<ClientInfo>,
+        /// Watch the room without voting
+        #[serde(default)]
+        observer: bool,
+    },
     /// Client submits a vote
     Vote { vote: Option<String> },
-    /// Server sends room state update
-    RoomUpdate { room: Room },
+    /// Vote on the room's risk deck
+    RiskVote { vote: Option<String> },
+    /// Client hands their vote to another participant for this round, or takes it back
+    Delegate { to: Option<String> },
+    /// Sit out of the voting for a while, or come back
+    SitOut { sitting_out: bool },
+    /// Client raises or lowers a signal about the current ticket
+    Signal { signal: TicketSignal, raised: bool },
+    /// Client changes their display name
+    Rename { name: String },
+    /// Server sends room state update. The room's `current_ticket` is left out;
+    /// clients keep the one from `TicketChanged` (or fetch `/api/room/:id/ticket`)
+    /// as long as `ticket_hash` is unchanged.
+    RoomUpdate {
+        room: Room,
+        /// `Room::checksum` of the room, for desync detection
+        #[serde(default)]
+        checksum: String,
+        /// The server's clock when it sent the update (Unix milliseconds). The
+        /// room's timestamps are on this clock; clients that haven't measured
+        /// their offset with `TimeSync` yet can estimate it from this.
+        #[serde(default)]
+        server_time: u64,
+    },
+    /// Server sends the current ticket, once whenever it changes
+    TicketChanged {
+        ticket: Option<JiraTicket>,
+        ticket_hash: Option<String>,
+    },
+    /// One more vote was shown in a one-by-one reveal
+    VoteRevealed {
+        participant_id: String,
+        vote: Option<String>,
+    },
+    /// Client noticed a gap or mismatch and wants the full room state again
+    Resync,
     /// Server sends error
-    Error { message: String },
+    Error {
+        #[serde(default)]
+        code: ErrorCode,
+        message: String,
+    },
     /// Participant was kicked
     Kicked,
+    /// One-shot state snapshot sent to a participant right after they join
+    CatchUp(CatchUp),
+    /// Host ended the session; clients should show the closing screen
+    SessionEnded { summary: SessionSummary },
+    /// Client asks for the server's clock, sending its own (Unix milliseconds)
+    TimeSync { client_time: u64 },
+    /// Server answers a `TimeSync`, echoing `client_time` so the client can
+    /// work out the round trip and so its clock offset: countdowns to
+    /// `voting_opens_at` or `reveal_at` then end at the same moment everywhere
+    TimeSyncReply { client_time: u64, server_time: u64 },
     /// Ping/Pong for keepalive
     Ping,
     Pong,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{participant, room_with, FakeEnvironment, START_TIME};
+
+    fn ticket(key: &str) -> JiraTicket {
+        JiraTicket {
+            key: key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn campaign_rounds_record_drift_from_the_old_estimate() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        let previous = HashMap::from([("POKER-1".to_string(), 3.0)]);
+        room.campaign = Some(Campaign::new("Story Points".into(), previous));
+
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("5".into()));
+        room.set_vote("bob", Some("8".into()));
+        room.votes_revealed = true;
+        room.complete_round();
+
+        let results = &room.campaign.as_ref().unwrap().results;
+        assert_eq!((results[0].estimate, results[0].drift), (6.5, 3.5));
+        assert!(room.view_for("alice").campaign.unwrap().previous.is_empty());
+    }
+
+    #[test]
+    fn signals_are_counted_with_the_round_and_cleared_for_the_next_ticket() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        assert!(room.set_signal("alice", TicketSignal::NeedsDesign, true));
+        assert!(room.set_signal("bob", TicketSignal::NeedsDesign, true));
+        assert!(room.set_signal("bob", TicketSignal::SecurityReview, true));
+        assert!(room.set_signal("bob", TicketSignal::SecurityReview, false));
+        assert!(!room.set_signal("nobody", TicketSignal::NeedsDesign, true));
+
+        // Participants only see their own
+        assert_eq!(room.view_for("alice").signals.keys().collect::<Vec<_>>(), ["alice"]);
+
+        room.votes_revealed = true;
+        room.complete_round();
+        let Some(HistoryEvent::VotesRevealed { signals, .. }) = room.history.last().map(|e| &e.event) else {
+            panic!("round wasn't recorded");
+        };
+        assert_eq!(signals, &BTreeMap::from([(TicketSignal::NeedsDesign, 2)]));
+
+        room.set_current_ticket(Some(ticket("POKER-2")));
+        assert!(room.signals.is_empty());
+    }
+
+    #[test]
+    fn hidden_voters_show_up_once_everyone_has_voted() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.hide_voters = true;
+        room.set_vote("alice", Some("5".into()));
+
+        let votes = |room: &Room| room.participants.iter().map(|p| p.vote.clone()).collect::<Vec<_>>();
+        assert_eq!(votes(&room.view_for("alice")), [Some("5".to_string()), None]);
+        assert_eq!(votes(&room.view_for("bob")), [None, None]);
+
+        room.set_vote("bob", Some("8".into()));
+        assert!(!room.voters_hidden());
+        assert_eq!(votes(&room.view_for("bob")), [Some("5".to_string()), Some("8".to_string())]);
+    }
+
+    #[test]
+    fn late_joiners_get_no_summary_of_hidden_votes() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.hide_voters = true;
+        room.set_vote("alice", Some("5".into()));
+        room.add_participant(participant("carol", "carol"));
+
+        let summary = room.catch_up("carol").vote_summary;
+        assert_eq!((summary.voted_count, summary.average, summary.suggested), (0, None, None));
+        assert_eq!(summary.total_voters, 3);
+    }
+
+    #[test]
+    fn new_room_uses_injected_clock_and_ids() {
+        let env = FakeEnvironment::new();
+        let first = Room::new("One".to_string(), None, env.handle());
+        let second = Room::new("Two".to_string(), None, env.handle());
+
+        assert_eq!(first.id, "id-1");
+        assert_eq!(second.id, "id-3");
+        assert_eq!(first.created_at, START_TIME);
+        // Invite codes derive from the injected IDs, so they're reproducible
+        assert_eq!(first.invite_code, Room::new("One".to_string(), None, FakeEnvironment::new().handle()).invite_code);
+        assert_ne!(first.invite_code, second.invite_code);
+    }
+
+    #[test]
+    fn time_to_vote_counts_only_first_vote() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+
+        env.advance(10);
+        room.set_vote("alice", Some("3".into()));
+        env.advance(20);
+        room.set_vote("bob", Some("5".into()));
+        env.advance(30);
+        room.set_vote("alice", Some("5".into()));
+
+        assert_eq!(room.round_first_votes.get("alice"), Some(&10));
+        assert_eq!(room.round_first_votes.get("bob"), Some(&30));
+        assert_eq!(room.average_time_to_vote(), Some(20.0));
+    }
+
+    #[test]
+    fn completing_a_round_records_history_once() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob", Some("5".into()));
+
+        env.advance(45);
+        room.complete_round();
+        room.complete_round();
+
+        assert_eq!(room.history.len(), 1);
+        assert_eq!(room.history[0].timestamp, START_TIME + 45);
+        match &room.history[0].event {
+            HistoryEvent::VotesRevealed { ticket_key, voted_count, average, .. } => {
+                assert_eq!(ticket_key.as_deref(), Some("POKER-1"));
+                assert_eq!(*voted_count, 2);
+                assert_eq!(*average, Some(4.0));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn end_session_summarizes_latest_estimate_per_ticket() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("8".into()));
+        room.set_vote("bob", Some("8".into()));
+        room.complete_round();
+
+        // Re-vote on the same ticket replaces the first estimate
+        room.reset_votes();
+        room.set_vote("alice", Some("5".into()));
+        room.set_vote("bob", Some("5".into()));
+        room.complete_round();
+
+        env.advance(600);
+        let summary = room.end_session();
+
+        assert!(room.is_ended());
+        assert_eq!(summary.duration_secs, 600);
+        assert_eq!(summary.rounds, 2);
+        assert_eq!(summary.tickets_estimated, vec!["POKER-1".to_string()]);
+        assert_eq!(summary.total_points, 5.0);
+        assert_eq!(summary.attendance, vec!["alice".to_string(), "bob".to_string()]);
+    }
+
+    #[test]
+    fn client_info_is_limited_to_known_families() {
+        let chrome = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+        let edge = "Mozilla/5.0 (Windows NT 10.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36 Edg/120.0";
+        assert_eq!(user_agent_family(chrome), "Chrome");
+        assert_eq!(user_agent_family(edge), "Edge");
+
+        let claimed = ClientInfo { kind: ClientKind::Browser, user_agent_family: Some("<script>".into()) };
+        assert_eq!(claimed.sanitized(Some(chrome)).user_agent_family.as_deref(), Some("Chrome"));
+        let declared = ClientInfo { kind: ClientKind::Tui, user_agent_family: Some("firefox".into()) };
+        assert_eq!(declared.sanitized(None).user_agent_family.as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn checksum_ignores_participant_order() {
+        let env = FakeEnvironment::new();
+        let room = room_with(&env, &["alice", "bob"]);
+        let mut reordered = room.clone();
+        reordered.participants.reverse();
+
+        assert_eq!(room.checksum(), reordered.checksum());
+    }
+
+    #[test]
+    fn handed_off_ticket_keeps_votes_of_people_in_the_new_room() {
+        let env = FakeEnvironment::new();
+        let mut squad = room_with(&env, &["alice", "bob"]);
+        squad.set_current_ticket(Some(ticket("POKER-1")));
+        squad.set_vote("alice", Some("5".into()));
+        squad.set_vote("bob", Some("8".into()));
+        squad.votes_revealed = true;
+        squad.complete_round();
+
+        let mut all_hands = room_with(&env, &["alice", "carol"]);
+        all_hands.set_current_ticket(Some(ticket("POKER-2")));
+        all_hands.receive_ticket(squad.hand_off_ticket().unwrap());
+
+        assert!(squad.current_ticket.is_none());
+        assert!(squad.history.is_empty());
+        assert_eq!(all_hands.current_ticket.as_ref().map(|t| t.key.as_str()), Some("POKER-1"));
+        assert_eq!(all_hands.ticket_queue[0].key, "POKER-2");
+        assert_eq!(all_hands.history.len(), 1);
+        let votes: Vec<_> = all_hands.participants.iter().map(|p| p.vote.as_deref()).collect();
+        assert_eq!(votes, [Some("5"), None]);
+    }
+
+    #[test]
+    fn auto_reveal_waits_for_quorum_and_required_voters() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob", "carol", "dave"]);
+        room.auto_reveal = true;
+        room.quorum = QuorumRules {
+            min_voted_percent: Some(75),
+            required_voters: vec!["Dave".into()],
+        };
+
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob", Some("5".into()));
+        room.set_vote("carol", Some("5".into()));
+        assert!(!room.auto_reveal_if_ready(), "75% voted but dave is required");
+
+        room.set_vote("bob", None);
+        room.set_vote("carol", None);
+        room.set_vote("dave", Some("8".into()));
+        assert!(!room.auto_reveal_if_ready(), "only half the room voted");
+
+        room.set_vote("carol", Some("5".into()));
+        assert!(room.auto_reveal_if_ready());
+        assert!(room.votes_revealed);
+    }
+
+    #[test]
+    fn dropped_participants_keep_their_vote_for_the_grace_period() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_vote("alice", Some("5".into()));
+
+        room.participant_disconnected("alice");
+        env.advance(DEFAULT_DISCONNECT_GRACE_SECS - 1);
+        assert!(room.expire_disconnected().is_empty());
+        assert_eq!(room.participants[0].vote.as_deref(), Some("5"));
+
+        env.advance(1);
+        assert_eq!(room.expire_disconnected(), ["alice"]);
+
+        room.disconnect_grace_secs = 0;
+        room.participant_disconnected("bob");
+        assert!(room.participants.is_empty());
+    }
+
+    #[test]
+    fn stepwise_reveal_shows_lowest_votes_first_then_completes_the_round() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob", "carol", "dave"]);
+        room.reveal_mode = RevealMode::HostPaced;
+        room.set_vote("alice", Some("8".into()));
+        room.set_vote("bob", Some("?".into()));
+        room.set_vote("carol", Some("3".into()));
+
+        assert!(room.start_stepwise_reveal());
+        assert_eq!(room.reveal_next_vote().as_deref(), Some("carol"));
+        assert_eq!(room.reveal_next_vote().as_deref(), Some("alice"));
+        assert!(!room.votes_revealed);
+        assert_eq!(room.reveal_next_vote().as_deref(), Some("bob"));
+        assert!(room.votes_revealed);
+        assert_eq!(room.reveal_progress, None);
+        assert_eq!(room.history.len(), 1);
+        assert_eq!(room.reveal_next_vote(), None);
+    }
+
+    #[test]
+    fn abstaining_counts_toward_the_quorum_but_not_the_average() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob", "carol"]);
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob", Some("5".into()));
+        assert!(!room.quorum_met());
+        assert!(room.is_valid_vote(ABSTAIN));
+        room.set_vote("carol", Some(ABSTAIN.into()));
+        assert!(room.quorum_met());
+
+        let summary = room.get_vote_summary();
+        assert_eq!(summary.voted_count, 3);
+        assert_eq!(summary.abstained_count, 1);
+        assert_eq!(summary.average, Some(4.0));
+
+        room.set_abstain_offered(false);
+        assert!(!room.is_valid_vote(ABSTAIN));
+        assert_eq!(room.participants[2].vote, None);
+    }
+
+    #[test]
+    fn tags_go_on_the_latest_revealed_round() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("8".into()));
+        assert!(room.tag_round("needs spike").is_err(), "votes aren't revealed yet");
+
+        room.votes_revealed = true;
+        room.complete_round();
+        assert_eq!(room.tag_round(" needs spike ").unwrap().as_deref(), Some("POKER-1"));
+        room.tag_round("Needs Spike").unwrap();
+        room.tag_round("split").unwrap();
+
+        let HistoryEvent::VotesRevealed { tags, .. } = &room.history[0].event else {
+            panic!("unexpected event {:?}", room.history[0].event);
+        };
+        assert_eq!(tags, &["needs spike", "split"]);
+    }
+
+    #[test]
+    fn overrides_need_a_reason_and_replace_the_estimate() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob", Some("5".into()));
+        room.votes_revealed = true;
+        room.complete_round();
+
+        assert!(room.override_estimate("8", "  ").is_err(), "a reason is required");
+        assert!(room.override_estimate("7", "Known unknowns").is_err(), "not a card");
+        room.override_estimate("8", "Needs a migration nobody mentioned").unwrap();
+
+        let HistoryEvent::EstimateOverridden { ticket_key, value, average, .. } = &room.history[1].event else {
+            panic!("unexpected event {:?}", room.history[1].event);
+        };
+        assert_eq!((ticket_key.as_deref(), value.as_str(), *average), (Some("POKER-1"), "8", Some(4.0)));
+        let summary = room.end_session();
+        assert_eq!((summary.overrides, summary.total_points), (1, 8.0));
+    }
+
+    #[test]
+    fn voting_rounds_include_resets_and_overrides() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_current_ticket(Some(ticket("POKER-1")));
+        room.set_vote("alice", Some("13".into()));
+        room.restart_round();
+        room.restart_round();
+
+        room.set_vote("alice", Some("3".into()));
+        room.set_vote("bob", Some("5".into()));
+        room.votes_revealed = true;
+        room.complete_round();
+        room.override_estimate("8", "Needs a migration").unwrap();
+
+        let rounds = room.voting_rounds();
+        assert_eq!(rounds.len(), 2, "a reset with no votes isn't a round");
+        assert_eq!((rounds[0].revealed, rounds[0].voted_count, rounds[0].votes.len()), (false, 1, 0));
+        assert_eq!(rounds[1].votes.len(), 2);
+        assert_eq!(rounds[1].final_estimate.as_deref(), Some("8"));
+        assert_eq!(rounds[1].override_reason.as_deref(), Some("Needs a migration"));
+    }
+
+    #[test]
+    fn issue_types_switch_the_deck_until_reverted() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice"]);
+        let own_deck = room.deck.clone();
+        room.issue_type_decks.insert("bug".into(), vec!["S".into(), "M".into(), "L".into()]);
+        let bug = JiraTicket { issue_type: Some("Bug".into()), ..ticket("POKER-1") };
+
+        room.set_current_ticket(Some(bug.clone()));
+        assert_eq!(room.deck, ["S", "M", "L", ABSTAIN]);
+        room.set_vote("alice", Some("M".into()));
+        room.revert_deck_switch().unwrap();
+        assert_eq!(room.deck, own_deck);
+        assert_eq!(room.participants[0].vote, None, "M isn't in the room's deck");
+        assert!(room.revert_deck_switch().is_err());
+
+        room.set_current_ticket(Some(bug));
+        room.set_current_ticket(Some(ticket("POKER-2")));
+        assert_eq!(room.deck, own_deck);
+        let switches = room
+            .history
+            .iter()
+            .filter(|entry| matches!(entry.event, HistoryEvent::DeckSwitched { .. }))
+            .count();
+        assert_eq!(switches, 4);
+    }
+
+    #[test]
+    fn a_batch_round_estimates_each_of_its_tickets() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice"]);
+        room.enqueue_tickets(vec![ticket("POKER-1"), ticket("POKER-2"), ticket("POKER-3")]);
+        room.set_current_ticket(Some(ticket("POKER-9")));
+        assert!(room.start_batch(&["POKER-1".into()]).is_err(), "a batch needs two tickets");
+        assert!(room.start_batch(&["POKER-1".into(), "POKER-7".into()]).is_err());
+
+        room.start_batch(&["POKER-1".into(), "POKER-3".into()]).unwrap();
+        let queued: Vec<&str> = room.ticket_queue.iter().map(|t| t.key.as_str()).collect();
+        assert_eq!(queued, ["POKER-9", "POKER-2"]);
+        assert!(room.current_ticket.is_none());
+
+        room.set_vote("alice", Some("1".into()));
+        room.votes_revealed = true;
+        room.complete_round();
+        let estimated: Vec<(Option<&str>, usize)> = room
+            .history
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                HistoryEvent::VotesRevealed { ticket_key, batch, .. } => Some((ticket_key.as_deref(), batch.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(estimated, [(Some("POKER-1"), 2), (Some("POKER-3"), 2)]);
+
+        room.advance_ticket();
+        assert!(room.batch.is_empty());
+    }
+
+    #[test]
+    fn delegated_votes_count_once_and_are_credited_to_both() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob", "carol"]);
+        room.set_vote("alice", Some("3".into()));
+        room.delegate_vote("alice", Some("bob")).unwrap();
+        assert_eq!(room.participants[0].vote, None);
+        assert!(room.delegate_vote("carol", Some("alice")).is_err(), "can't delegate to a delegator");
+        assert!(room.delegate_vote("bob", Some("carol")).is_err(), "can't pass a delegated vote on");
+
+        room.set_vote("bob", Some("5".into()));
+        room.set_vote("carol", Some("8".into()));
+        assert!(room.quorum_met());
+        room.complete_round();
+
+        let HistoryEvent::VotesRevealed { voted_count, votes, .. } = &room.history[0].event else {
+            panic!("expected a reveal");
+        };
+        assert_eq!(*voted_count, 2);
+        assert_eq!(votes[0].on_behalf_of, ["alice"]);
+        room.reset_votes();
+        assert!(room.delegations.is_empty());
+    }
+
+    #[test]
+    fn participants_sitting_out_are_left_out_of_the_quorum() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice", "bob"]);
+        room.set_vote("alice", Some("3".into()));
+        assert!(!room.quorum_met());
+
+        assert!(room.set_sitting_out("bob", true));
+        assert!(room.quorum_met());
+        assert_eq!(room.participants.len(), 2);
+
+        room.set_vote("bob", Some("5".into()));
+        assert!(!room.participants[1].sitting_out, "voting brings them back in");
+    }
+
+    #[test]
+    fn rejoining_with_the_same_identity_reclaims_the_old_place() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &[]);
+        room.add_participant(participant("alice", "alice").with_identity(Some("token".into())));
+        room.set_vote("alice", Some("8".into()));
+        room.participant_disconnected("alice");
+
+        let returning = participant("other-id", "Alice").with_identity(Some("token".into()));
+        assert_eq!(room.reclaim_participant(&returning).as_deref(), Some("alice"));
+        assert_eq!(room.participants.len(), 1);
+        assert_eq!(room.participants[0].name, "Alice");
+        assert_eq!(room.participants[0].vote.as_deref(), Some("8"));
+        assert_eq!(room.participants[0].disconnected_at, None);
+
+        assert_eq!(room.reclaim_participant(&participant("bob", "bob")), None);
+    }
+
+    #[test]
+    fn overdue_tickets_are_parked_unless_revealed_in_time() {
+        let env = FakeEnvironment::new();
+        let mut room = room_with(&env, &["alice"]);
+        room.enqueue_tickets(vec![ticket("POKER-1"), ticket("POKER-2"), ticket("POKER-3")]);
+        room.set_ticket_deadline("POKER-1", Some(10));
+        room.set_ticket_deadline("POKER-2", Some(10));
+        room.advance_ticket();
+        room.set_vote("alice", Some("5".into()));
+
+        env.advance(9 * 60);
+        assert!(room.park_overdue_ticket().is_none());
+        env.advance(60);
+        assert_eq!(room.park_overdue_ticket().map(|t| t.key), Some("POKER-1".into()));
+        assert_eq!(room.current_ticket.as_ref().map(|t| t.key.as_str()), Some("POKER-2"));
+        assert!(room.participants[0].vote.is_none());
+        assert!(matches!(room.history.last().unwrap().event, HistoryEvent::TicketParked { minutes: 10, .. }));
+
+        // Revealing in time records the estimate and stops the clock
+        room.votes_revealed = true;
+        room.complete_round();
+        env.advance(60 * 60);
+        assert!(room.park_overdue_ticket().is_none());
+        assert_eq!(room.parked_tickets.len(), 1);
+    }
+}
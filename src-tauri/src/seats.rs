@@ -0,0 +1,135 @@
+use crate::room::{ClientError, ErrorCode, Room};
+use serde::Serialize;
+use std::fmt;
+
+/// Which limit a join ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeatScope {
+    /// The room's own participant limit
+    Room,
+    /// The limit across every room this app hosts
+    Global,
+}
+
+/// A join turned away by the seat policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeatDenied {
+    pub scope: SeatScope,
+    /// The limit that was reached, if the policy has a fixed one
+    pub limit: Option<usize>,
+    pub message: String,
+}
+
+impl SeatDenied {
+    pub fn new(scope: SeatScope, limit: Option<usize>, message: impl Into<String>) -> Self {
+        Self { scope, limit, message: message.into() }
+    }
+}
+
+impl fmt::Display for SeatDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<SeatDenied> for ClientError {
+    fn from(denied: SeatDenied) -> Self {
+        let code = match denied.scope {
+            SeatScope::Room => ErrorCode::RoomFull,
+            SeatScope::Global => ErrorCode::SeatLimitReached,
+        };
+        ClientError::new(code, denied.message)
+    }
+}
+
+/// Someone asking for a seat, with the seats already taken. The built-in
+/// limits only count seats; the rest is for policies plugged in by embedding apps.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct SeatRequest<'a> {
+    pub room_id: &'a str,
+    /// Seats taken in the room being joined
+    pub room_seats: usize,
+    /// Seats taken across every room
+    pub total_seats: usize,
+    /// The joiner's identity token, if their client sent one
+    pub identity: Option<&'a str>,
+}
+
+/// Hook for deciding who gets a seat, so organizations embedding the app can
+/// plug in their own licensing. Only called for new participants: hosts and
+/// people reclaiming their place don't take another seat.
+pub trait SeatPolicy: Send + Sync {
+    fn admit(&self, request: &SeatRequest) -> Result<(), SeatDenied>;
+
+    /// Limits to show in the usage report; None where the policy has none
+    fn limits(&self) -> SeatLimits {
+        SeatLimits::default()
+    }
+}
+
+/// Fixed participant limits, per room and across all rooms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SeatLimits {
+    pub per_room: Option<usize>,
+    pub global: Option<usize>,
+}
+
+impl SeatPolicy for SeatLimits {
+    fn admit(&self, request: &SeatRequest) -> Result<(), SeatDenied> {
+        if let Some(limit) = self.per_room.filter(|limit| request.room_seats >= *limit) {
+            return Err(SeatDenied::new(SeatScope::Room, Some(limit), format!("This room is full ({} participants)", limit)));
+        }
+        if let Some(limit) = self.global.filter(|limit| request.total_seats >= *limit) {
+            return Err(SeatDenied::new(
+                SeatScope::Global,
+                Some(limit),
+                format!("All {} seats are in use; try again once someone leaves", limit),
+            ));
+        }
+        Ok(())
+    }
+
+    fn limits(&self) -> SeatLimits {
+        *self
+    }
+}
+
+/// Seats taken in one room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomUsage {
+    pub room_id: String,
+    pub room_name: String,
+    pub seats: usize,
+}
+
+/// Seats taken across the app, for `get_usage`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub rooms: Vec<RoomUsage>,
+    pub total_seats: usize,
+    pub limits: SeatLimits,
+}
+
+/// Participants in a room who take a seat: everyone but the host
+pub fn seats_taken(room: &Room) -> usize {
+    room.participants.iter().filter(|p| !p.is_host).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_room_limit_is_checked_before_the_global_one() {
+        let limits = SeatLimits { per_room: Some(2), global: Some(10) };
+        let request = |room_seats, total_seats| SeatRequest { room_id: "room", room_seats, total_seats, identity: None };
+
+        assert!(limits.admit(&request(1, 9)).is_ok());
+        assert_eq!(limits.admit(&request(2, 10)).unwrap_err().scope, SeatScope::Room);
+        let denied = limits.admit(&request(1, 10)).unwrap_err();
+        assert_eq!((denied.scope, denied.limit), (SeatScope::Global, Some(10)));
+        assert!(SeatLimits::default().admit(&request(100, 1000)).is_ok());
+    }
+}
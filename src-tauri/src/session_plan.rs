@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A refinement session to set up: a room name and the tickets to queue in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedSession {
+    pub name: String,
+    pub tickets: Vec<String>,
+    /// Minutes allowed per ticket key, for tickets that should be parked if they run long
+    #[serde(default)]
+    pub deadlines: HashMap<String, u32>,
+}
+
+/// JSON plans may list several sessions or just one
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonPlan {
+    Many(Vec<PlannedSession>),
+    One(PlannedSession),
+}
+
+/// Read a session plan from a `.json` or `.csv` file.
+///
+/// JSON is `{"name": ..., "tickets": [...], "deadlines": {"KEY": minutes}}` (deadlines
+/// optional) or a list of those. CSV has one `session name,ticket key[,minutes]` row
+/// per ticket (an optional `session,ticket` header row is skipped); rows are grouped
+/// by session name in order of appearance.
+pub fn load_session_plan(path: &Path) -> Result<Vec<PlannedSession>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let is_json = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    let sessions = if is_json { parse_json(&contents)? } else { parse_csv(&contents)? };
+
+    if sessions.is_empty() {
+        return Err("The plan doesn't list any sessions".into());
+    }
+    Ok(sessions)
+}
+
+fn parse_json(contents: &str) -> Result<Vec<PlannedSession>, String> {
+    let plan: JsonPlan = serde_json::from_str(contents).map_err(|e| format!("Invalid session plan: {}", e))?;
+    let sessions = match plan {
+        JsonPlan::Many(sessions) => sessions,
+        JsonPlan::One(session) => vec![session],
+    };
+
+    sessions
+        .into_iter()
+        .map(|session| {
+            if session.deadlines.values().any(|minutes| *minutes == 0) {
+                return Err(format!("Session {}: deadlines must be at least one minute", session.name));
+            }
+            Ok(PlannedSession {
+                tickets: session.tickets.iter().map(|key| normalize_key(key)).filter(|k| !k.is_empty()).collect(),
+                deadlines: session.deadlines.iter().map(|(key, minutes)| (normalize_key(key), *minutes)).collect(),
+                name: session.name,
+            })
+        })
+        .collect()
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<PlannedSession>, String> {
+    let mut sessions: Vec<PlannedSession> = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        let (name, key, minutes) = match fields.as_slice() {
+            [name, key, rest @ ..] => (name.trim(), normalize_key(key), rest.first().map(|m| m.trim())),
+            _ => return Err(format!("Line {}: expected \"session name,ticket key\"", index + 1)),
+        };
+        if index == 0 && name.eq_ignore_ascii_case("session") {
+            continue;
+        }
+        if name.is_empty() {
+            return Err(format!("Line {}: session name is missing", index + 1));
+        }
+
+        let session = match sessions.iter_mut().position(|s| s.name == name) {
+            Some(position) => &mut sessions[position],
+            None => {
+                sessions.push(PlannedSession {
+                    name: name.to_string(),
+                    tickets: Vec::new(),
+                    deadlines: HashMap::new(),
+                });
+                sessions.last_mut().unwrap()
+            }
+        };
+        if key.is_empty() {
+            continue;
+        }
+        if let Some(minutes) = minutes.filter(|m| !m.is_empty()) {
+            match minutes.parse::<u32>() {
+                Ok(minutes) if minutes > 0 => {
+                    session.deadlines.insert(key.clone(), minutes);
+                }
+                _ => return Err(format!("Line {}: \"{}\" isn't a number of minutes", index + 1, minutes)),
+            }
+        }
+        session.tickets.push(key);
+    }
+
+    Ok(sessions)
+}
+
+/// Split a CSV line on commas, honouring double-quoted fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn normalize_key(key: &str) -> String {
+    key.trim().to_uppercase()
+}
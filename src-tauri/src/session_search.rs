@@ -0,0 +1,103 @@
+use crate::room::{HistoryEvent, RecordedVote, Room};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Window to search, in Unix seconds; either end may be left open
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct DateRange {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// A revealed round in a session that matched
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundResult {
+    pub ticket_key: Option<String>,
+    pub revealed_at: u64,
+    pub average: Option<f64>,
+    pub votes: Vec<RecordedVote>,
+    /// The host's tags on the round
+    pub tags: Vec<String>,
+}
+
+/// A session that matched a search
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionResult {
+    pub room_id: String,
+    pub room_name: String,
+    pub started_at: u64,
+    /// None while the session is still running
+    pub ended_at: Option<u64>,
+    pub participants: Vec<String>,
+    /// Rounds for the ticket or tag searched for, or every round when the room
+    /// name or a participant matched
+    pub rounds: Vec<RoundResult>,
+}
+
+/// Find sessions in the date range whose room name, participants, ticket keys or
+/// round tags contain the query (ignoring case), newest first. An empty query matches every
+/// session in the range.
+pub fn search_sessions<'a>(rooms: impl IntoIterator<Item = &'a Room>, query: &str, range: DateRange) -> Vec<SessionResult> {
+    let query = query.trim().to_lowercase();
+    let mut results: Vec<SessionResult> = rooms
+        .into_iter()
+        .filter(|room| in_range(room, range))
+        .filter_map(|room| match_session(room, &query))
+        .collect();
+    results.sort_by_key(|result| std::cmp::Reverse(result.started_at));
+    results
+}
+
+/// Whether the session overlaps the range; running sessions count as ongoing
+fn in_range(room: &Room, range: DateRange) -> bool {
+    let ended_at = room.session_summary.as_ref().map_or(u64::MAX, |summary| summary.ended_at);
+    range.from.is_none_or(|from| ended_at >= from) && range.to.is_none_or(|to| room.created_at <= to)
+}
+
+fn match_session(room: &Room, query: &str) -> Option<SessionResult> {
+    let contains = |text: &str| text.to_lowercase().contains(query);
+    let participants = participant_names(room);
+    let rounds = room.history.iter().filter_map(|entry| match &entry.event {
+        HistoryEvent::VotesRevealed { ticket_key, average, votes, tags, .. } => Some(RoundResult {
+            ticket_key: ticket_key.clone(),
+            revealed_at: entry.timestamp,
+            average: *average,
+            votes: votes.clone(),
+            tags: tags.clone(),
+        }),
+        _ => None,
+    });
+
+    let session_matches = query.is_empty() || contains(&room.name) || participants.iter().any(|name| contains(name));
+    let rounds: Vec<RoundResult> = if session_matches {
+        rounds.collect()
+    } else {
+        let tickets_match = room.history.iter().any(|entry| entry.event.ticket_key().is_some_and(contains));
+        let rounds: Vec<RoundResult> = rounds
+            .filter(|round| round.ticket_key.as_deref().is_some_and(contains) || round.tags.iter().any(|tag| contains(tag)))
+            .collect();
+        if rounds.is_empty() && !tickets_match {
+            return None;
+        }
+        rounds
+    };
+
+    Some(SessionResult {
+        room_id: room.id.clone(),
+        room_name: room.name.clone(),
+        started_at: room.created_at,
+        ended_at: room.session_summary.as_ref().map(|summary| summary.ended_at),
+        participants,
+        rounds,
+    })
+}
+
+/// Everyone known to have taken part, including people who have since left
+fn participant_names(room: &Room) -> Vec<String> {
+    let mut names: BTreeSet<String> = room.participants.iter().map(|p| p.name.clone()).collect();
+    names.extend(room.participant_stats.values().map(|stats| stats.name.clone()));
+    if let Some(summary) = &room.session_summary {
+        names.extend(summary.attendance.iter().cloned());
+    }
+    names.into_iter().collect()
+}
@@ -0,0 +1,291 @@
+use crate::credentials::get_data_dir;
+use crate::estimate_rules::EstimateRule;
+use crate::migrations::{self, FileReport, SETTINGS_MIGRATIONS};
+use crate::seats::SeatLimits;
+use crate::storage::StorageBackend;
+use crate::updates::ReleaseChannel;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Explicit proxy used for outbound connections (Jira, public IP lookups, relay)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// Proxy URL, e.g. "http://proxy.corp.local:8080"
+    pub url: String,
+    pub username: Option<String>,
+    /// Only held in memory: the password is kept in the encrypted credentials store and
+    /// filled in once the credentials are unlocked. A plaintext password in settings
+    /// written by older versions is still read, and moved into the store on unlock.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// Whether the credentials store holds a password for this proxy
+    #[serde(default)]
+    pub password_stored: bool,
+}
+
+/// User-configurable application settings, persisted as JSON in the data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// When unset, the system proxy (environment / OS settings) is used
+    #[serde(default)]
+    pub proxy: Option<ProxySettings>,
+    /// Path to an extra root CA certificate (PEM or DER) to trust, for TLS-intercepting
+    /// corporate proxies or self-hosted Jira with a private CA
+    #[serde(default)]
+    pub ca_certificate_path: Option<String>,
+    /// Client IP ranges (CIDR, e.g. "10.0.0.0/8") allowed to reach the HTTP API.
+    /// Empty allows everyone; loopback is always allowed so the host can reach its own rooms.
+    #[serde(default)]
+    pub allowed_networks: Vec<String>,
+    /// Browser origins (e.g. "https://intranet.example.com") allowed to open WebSockets,
+    /// in addition to pages served by this app itself
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Words that may not appear in participant names
+    #[serde(default)]
+    pub blocked_name_words: Vec<String>,
+    /// Dev mode: proxy the web client to a Vite dev server (e.g. "http://localhost:5174")
+    /// instead of serving the embedded build, so web client changes hot-reload
+    #[serde(default)]
+    pub web_client_dev_url: Option<String>,
+    /// Distribution list that session recap emails are addressed to
+    #[serde(default)]
+    pub recap_recipients: Vec<String>,
+    /// Encrypt the stored rooms and session history with the master password.
+    /// Rooms are then only restored once the password is entered.
+    #[serde(default)]
+    pub encrypt_rooms: bool,
+    /// API key or signed token from the relay operator, for relays that require hosts to authenticate.
+    /// Kept in the encrypted credentials store and only filled in once the credentials are unlocked.
+    #[serde(default, skip_serializing)]
+    pub relay_token: Option<String>,
+    /// Whether the credentials store holds a relay token
+    #[serde(default)]
+    pub relay_token_stored: bool,
+    /// Secret the SMS provider signs its webhooks with (the Twilio auth token).
+    /// Texted votes are only accepted once this is set. Kept in the encrypted
+    /// credentials store and only filled in once the credentials are unlocked.
+    #[serde(default, skip_serializing)]
+    pub sms_auth_token: Option<String>,
+    /// Whether the credentials store holds an SMS auth token
+    #[serde(default)]
+    pub sms_auth_token_stored: bool,
+    /// What to do in Jira with tickets estimated at or above a threshold, for
+    /// rooms without a rule of their own
+    #[serde(default)]
+    pub estimate_rule: Option<EstimateRule>,
+    /// GitHub token for loading pull requests from private repositories
+    /// (public ones work without, within GitHub's anonymous rate limit).
+    /// Only held in memory, like the proxy password: it's kept in the encrypted
+    /// credentials store and filled in once the credentials are unlocked.
+    #[serde(default, skip_serializing)]
+    pub github_token: Option<String>,
+    /// Whether the credentials store holds a GitHub token
+    #[serde(default)]
+    pub github_token_stored: bool,
+    /// Deck to switch rooms to for tickets of a Jira issue type, e.g.
+    /// "Bug" -> ["S", "M", "L"]; other types use the room's own deck
+    #[serde(default)]
+    pub issue_type_decks: HashMap<String, Vec<String>>,
+    /// Most participants (not counting the host) allowed in one room
+    #[serde(default)]
+    pub max_participants_per_room: Option<usize>,
+    /// Most participants allowed across all open rooms
+    #[serde(default)]
+    pub max_participants_total: Option<usize>,
+    /// Release channel checked for updates
+    #[serde(default)]
+    pub update_channel: ReleaseChannel,
+    /// Release manifest to check instead of the default, with `{channel}` in
+    /// place of the channel name, e.g. for an internal mirror
+    #[serde(default)]
+    pub update_manifest_url: Option<String>,
+    /// Offer updates even while participants are in a session; by default
+    /// they wait until the session is over
+    #[serde(default)]
+    pub prompt_updates_during_sessions: bool,
+    /// Where rooms and their history are stored
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+}
+
+impl AppSettings {
+    /// Participant limits to enforce unless the embedding app plugs in its own seat policy
+    pub fn seat_limits(&self) -> SeatLimits {
+        SeatLimits { per_room: self.max_participants_per_room, global: self.max_participants_total }
+    }
+
+    /// Build an HTTP client honoring the configured proxy and custom CA
+    pub fn http_client(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(bytes) = self.read_ca_certificate()? {
+            let certificate = reqwest::Certificate::from_pem(&bytes)
+                .or_else(|_| reqwest::Certificate::from_der(&bytes))
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        // Without an explicit proxy reqwest falls back to the system proxy settings
+        if let Some(proxy) = &self.proxy {
+            let mut reqwest_proxy = reqwest::Proxy::all(&proxy.url)
+                .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            if let Some(username) = &proxy.username {
+                reqwest_proxy = reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
+            }
+            builder = builder.proxy(reqwest_proxy);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    /// Build a TLS connector for the relay WebSocket, trusting the custom CA if configured
+    pub fn tls_connector(&self) -> Result<native_tls::TlsConnector, String> {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        if let Some(bytes) = self.read_ca_certificate()? {
+            let certificate = native_tls::Certificate::from_pem(&bytes)
+                .or_else(|_| native_tls::Certificate::from_der(&bytes))
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+            builder.add_root_certificate(certificate);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to create TLS connector: {}", e))
+    }
+
+    /// Parse the configured allowlist
+    pub fn allowed_networks(&self) -> Result<Vec<IpNet>, String> {
+        self.allowed_networks
+            .iter()
+            .map(|cidr| {
+                cidr.trim()
+                    .parse::<IpNet>()
+                    .map_err(|e| format!("Invalid network \"{}\": {}", cidr, e))
+            })
+            .collect()
+    }
+
+    /// The configured issue type decks, keyed by lowercase issue type
+    pub fn issue_type_decks(&self) -> Result<HashMap<String, Vec<String>>, String> {
+        self.issue_type_decks
+            .iter()
+            .map(|(issue_type, deck)| {
+                let deck: Vec<String> = deck.iter().map(|card| card.trim().to_string()).filter(|card| !card.is_empty()).collect();
+                if deck.is_empty() {
+                    return Err(format!("The deck for {} has no cards", issue_type));
+                }
+                Ok((issue_type.trim().to_lowercase(), deck))
+            })
+            .collect()
+    }
+
+    /// Whether a client address may use the HTTP API
+    pub fn is_client_allowed(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        if self.allowed_networks.is_empty() || ip.is_loopback() {
+            return true;
+        }
+
+        // Invalid entries are rejected on save; skip any that slipped through
+        self.allowed_networks
+            .iter()
+            .filter_map(|cidr| cidr.trim().parse::<IpNet>().ok())
+            .any(|net| net.contains(&ip))
+    }
+
+    /// Whether a WebSocket upgrade with this Origin header may proceed.
+    /// Requests without an Origin come from non-browser clients and are allowed.
+    pub fn is_origin_allowed(&self, origin: Option<&str>, host: Option<&str>) -> bool {
+        let Some(origin) = origin else { return true };
+        let origin = origin.trim_end_matches('/');
+
+        // Same origin: the page was served by this app
+        let origin_host = origin.split_once("://").map(|(_, rest)| rest);
+        if origin_host.is_some() && origin_host == host {
+            return true;
+        }
+
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim().trim_end_matches('/').eq_ignore_ascii_case(origin))
+    }
+
+    fn read_ca_certificate(&self) -> Result<Option<Vec<u8>>, String> {
+        match self.ca_certificate_path.as_deref().filter(|p| !p.trim().is_empty()) {
+            Some(path) => fs::read(path)
+                .map(Some)
+                .map_err(|e| format!("Failed to read CA certificate {}: {}", path, e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Load settings from disk, falling back to defaults if none are saved.
+/// Settings from older versions are migrated and saved back, keeping a backup.
+/// With a database storage backend the file only says which backend is in use,
+/// and the settings themselves come from the database.
+pub fn load_settings() -> Result<AppSettings, String> {
+    let path = get_data_dir()?.join(SETTINGS_FILE);
+
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    let value = serde_json::from_str(&json).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    let (mut settings, mut version) = parse_settings(value)?;
+
+    if version < migrations::current_version(SETTINGS_MIGRATIONS) {
+        migrations::backup_before_migration(&path, version)?;
+    }
+    let backend = settings.storage_backend;
+    if let Some(value) = backend.load_settings()? {
+        (settings, version) = parse_settings(value)?;
+        settings.storage_backend = backend;
+    }
+    if version < migrations::current_version(SETTINGS_MIGRATIONS) {
+        save_settings(&settings)?;
+    }
+
+    Ok(settings)
+}
+
+/// Migrate a saved settings document, returning the version it was saved as
+fn parse_settings(value: serde_json::Value) -> Result<(AppSettings, u32), String> {
+    let (version, data) = migrations::unwrap_versioned(value);
+    let data = migrations::migrate(data, version, SETTINGS_MIGRATIONS)?;
+    let settings = serde_json::from_value(data).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    Ok((settings, version))
+}
+
+/// Save settings to disk, or to the storage backend's database if it keeps them
+pub fn save_settings(settings: &AppSettings) -> Result<(), String> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    let mut versioned = migrations::wrap_versioned(settings, SETTINGS_MIGRATIONS)?;
+    if settings.storage_backend.save_settings(&versioned)? {
+        let pointer = AppSettings { storage_backend: settings.storage_backend, ..AppSettings::default() };
+        versioned = migrations::wrap_versioned(&pointer, SETTINGS_MIGRATIONS)?;
+    }
+    let json = serde_json::to_string_pretty(&versioned)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(data_dir.join(SETTINGS_FILE), json).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Report on the saved settings file
+pub fn check_settings_file() -> Result<FileReport, String> {
+    Ok(migrations::check_document(&get_data_dir()?.join(SETTINGS_FILE), SETTINGS_MIGRATIONS, |text| {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }))
+}
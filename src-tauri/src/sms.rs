@@ -0,0 +1,332 @@
+use crate::api::{can_access_room, LAN_ONLY_MESSAGE, ROOM_LOCKED_MESSAGE, SESSION_ENDED_MESSAGE};
+use crate::room::Participant;
+use crate::settings::AppSettings;
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{OriginalUri, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+const HELP_TEXT: &str = "Text JOIN <invite code> <your name> to join a room, then VOTE <card> to vote, \
+VOTE to take your vote back, or LEAVE when you're done.";
+
+/// A text message as delivered by an SMS provider's webhook
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundMessage {
+    /// Sender's phone number
+    pub from: String,
+    pub body: String,
+}
+
+/// The parts of a webhook request a provider needs to check and read it
+pub struct InboundRequest<'a> {
+    /// Full URL the provider called, which some providers sign
+    pub url: &'a str,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+/// An SMS gateway that forwards inbound texts to this app and accepts replies in
+/// its webhook response. Add a provider by implementing this and listing it in
+/// [`provider`].
+pub trait SmsProvider: Send + Sync {
+    /// Check the webhook really comes from the provider and pull out the message
+    fn parse_inbound(&self, request: &InboundRequest) -> Result<InboundMessage, String>;
+
+    /// Webhook response that texts `text` back to the sender
+    fn reply(&self, text: &str) -> Response;
+}
+
+/// What a participant can text in
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmsCommand {
+    Join { invite_code: String, name: String },
+    /// A card to vote with, or None to take the vote back
+    Vote(Option<String>),
+    Leave,
+    Help,
+}
+
+/// Read a command from a text, e.g. "VOTE 5" or "join 12 34 56 78 Alice".
+/// Keywords are case-insensitive; invite codes may use spaces or dashes.
+pub fn parse_command(text: &str) -> SmsCommand {
+    let mut words = text.split_whitespace();
+    let keyword = words.next().unwrap_or("").to_uppercase();
+    let rest: Vec<&str> = words.collect();
+    match keyword.as_str() {
+        "VOTE" => SmsCommand::Vote(rest.first().map(|card| card.to_string())),
+        "LEAVE" | "STOP" => SmsCommand::Leave,
+        "JOIN" => {
+            // The invite code is four pairs of digits, however they were separated
+            let code_words = rest
+                .iter()
+                .take_while(|word| word.chars().all(|c| c.is_ascii_digit() || c == '-'))
+                .count();
+            let digits: String = rest[..code_words]
+                .iter()
+                .flat_map(|word| word.chars().filter(char::is_ascii_digit))
+                .collect();
+            if digits.len() != 8 || code_words == rest.len() {
+                return SmsCommand::Help;
+            }
+            let invite_code = digits
+                .as_bytes()
+                .chunks(2)
+                .map(|pair| String::from_utf8_lossy(pair).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ");
+            SmsCommand::Join { invite_code, name: rest[code_words..].join(" ") }
+        }
+        _ => SmsCommand::Help,
+    }
+}
+
+/// Someone taking part by text message, keyed by their phone number
+#[derive(Debug, Clone)]
+pub struct SmsSession {
+    pub room_id: String,
+    pub participant_id: String,
+}
+
+/// The provider called `name`, if it's built in and configured
+#[cfg_attr(not(feature = "twilio"), allow(unused_variables))]
+fn provider(name: &str, settings: &AppSettings) -> Option<Box<dyn SmsProvider>> {
+    match name {
+        #[cfg(feature = "twilio")]
+        "twilio" => settings
+            .sms_auth_token
+            .clone()
+            .map(|token| Box::new(twilio::Twilio::new(token)) as Box<dyn SmsProvider>),
+        _ => None,
+    }
+}
+
+/// Webhook for inbound texts: `/api/inbound/:provider`
+pub async fn inbound(
+    State(state): State<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(provider) = provider(&provider_name, &state.get_settings()) else {
+        return (StatusCode::NOT_FOUND, "Unknown or unconfigured SMS provider").into_response();
+    };
+    let url = webhook_url(&headers, &uri.to_string());
+    let message = match provider.parse_inbound(&InboundRequest { url: &url, headers: &headers, body: &body }) {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::warn!("Rejected inbound {} message: {}", provider_name, e);
+            return (StatusCode::FORBIDDEN, "Invalid webhook request").into_response();
+        }
+    };
+    let reply = handle_message(&state, &message).await;
+    provider.reply(&reply)
+}
+
+/// The URL the provider called, as it saw it (behind a proxy, from the forwarded headers)
+fn webhook_url(headers: &HeaderMap, path: &str) -> String {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let scheme = header("x-forwarded-proto").unwrap_or("http");
+    let host = header("x-forwarded-host").or_else(|| header("host")).unwrap_or("localhost");
+    format!("{}://{}{}", scheme, host, path)
+}
+
+/// Act on a text and return the reply to send back
+pub async fn handle_message(state: &AppState, message: &InboundMessage) -> String {
+    match parse_command(&message.body) {
+        SmsCommand::Join { invite_code, name } => join(state, &message.from, &invite_code, &name).await,
+        SmsCommand::Vote(vote) => {
+            let Some(session) = state.sms_sessions.get(&message.from).map(|s| s.clone()) else {
+                return format!("You're not in a room. {}", HELP_TEXT);
+            };
+            if let Err(error) = state.set_vote(&session.room_id, &session.participant_id, vote.clone()) {
+                return error.message;
+            }
+            state.auto_reveal_if_ready(&session.room_id).await;
+            state.broadcast_room_update(&session.room_id).await;
+            match vote {
+                Some(vote) => format!("Voted {}", vote),
+                None => "Vote cleared".to_string(),
+            }
+        }
+        SmsCommand::Leave => {
+            if let Some((_, session)) = state.sms_sessions.remove(&message.from) {
+                state.remove_participant(&session.room_id, &session.participant_id);
+                state.broadcast_room_update(&session.room_id).await;
+            }
+            "You've left the room".to_string()
+        }
+        SmsCommand::Help => HELP_TEXT.to_string(),
+    }
+}
+
+async fn join(state: &AppState, from: &str, invite_code: &str, name: &str) -> String {
+    let Some(room) = state.get_room_by_invite(invite_code) else {
+        return "No room has that invite code".to_string();
+    };
+    // Texts arrive through the provider, so like relay participants they're never on the LAN
+    if !can_access_room(&room, None) {
+        return LAN_ONLY_MESSAGE.to_string();
+    }
+    if room.is_ended() {
+        return SESSION_ENDED_MESSAGE.to_string();
+    }
+    let identity = format!("sms:{}", from);
+    if !room.admits(Some(&identity)) {
+        return ROOM_LOCKED_MESSAGE.to_string();
+    }
+    if let Err(denied) = state.check_seat(&room.id, Some(&identity)) {
+        return denied.message;
+    }
+    let name = match state.validate_participant_name(name) {
+        Ok(name) => name,
+        Err(error) => return error.message,
+    };
+
+    // Joining another room from the same phone leaves the previous one
+    if let Some((_, previous)) = state.sms_sessions.remove(from) {
+        if previous.room_id != room.id {
+            state.remove_participant(&previous.room_id, &previous.participant_id);
+            state.broadcast_room_update(&previous.room_id).await;
+        }
+    }
+
+    let participant = Participant::new(name, false).with_identity(Some(identity));
+    let Some(participant_id) = state.add_participant(&room.id, participant) else {
+        return "Couldn't join the room".to_string();
+    };
+    state.broadcast_room_update(&room.id).await;
+    state.sms_sessions.insert(from.to_string(), SmsSession { room_id: room.id.clone(), participant_id });
+    format!("Joined {}. Text VOTE <card> to vote ({}).", room.name, room.deck.join(" "))
+}
+
+/// Twilio Programmable Messaging: form-encoded webhooks signed with the
+/// account's auth token, replies in TwiML
+#[cfg(feature = "twilio")]
+mod twilio {
+    use super::{InboundMessage, InboundRequest, SmsProvider};
+    use crate::recap::escape_html;
+    use axum::{
+        http::header,
+        response::{IntoResponse, Response},
+    };
+    use base64::{engine::general_purpose, Engine as _};
+    use sha1::{Digest, Sha1};
+
+    pub struct Twilio {
+        auth_token: String,
+    }
+
+    impl Twilio {
+        pub fn new(auth_token: String) -> Self {
+            Self { auth_token }
+        }
+
+        /// `X-Twilio-Signature` for a request: HMAC-SHA1 of the URL followed by
+        /// every form parameter's name and value, sorted by name
+        fn signature(&self, url: &str, params: &[(String, String)]) -> String {
+            let mut sorted: Vec<&(String, String)> = params.iter().collect();
+            sorted.sort();
+            let mut data = url.to_string();
+            for (name, value) in sorted {
+                data.push_str(name);
+                data.push_str(value);
+            }
+            general_purpose::STANDARD.encode(hmac_sha1(self.auth_token.as_bytes(), data.as_bytes()))
+        }
+    }
+
+    impl SmsProvider for Twilio {
+        fn parse_inbound(&self, request: &InboundRequest) -> Result<InboundMessage, String> {
+            let params: Vec<(String, String)> = url::form_urlencoded::parse(request.body).into_owned().collect();
+            let signature = request
+                .headers
+                .get("x-twilio-signature")
+                .and_then(|value| value.to_str().ok())
+                .ok_or("Missing signature")?;
+            if !constant_time_eq(signature.as_bytes(), self.signature(request.url, &params).as_bytes()) {
+                return Err("Bad signature".into());
+            }
+
+            let param = |name: &str| params.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+            Ok(InboundMessage {
+                from: param("From").ok_or("Missing sender")?,
+                body: param("Body").unwrap_or_default(),
+            })
+        }
+
+        fn reply(&self, text: &str) -> Response {
+            let twiml = format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response><Message>{}</Message></Response>",
+                escape_html(text)
+            );
+            ([(header::CONTENT_TYPE, "text/xml")], twiml).into_response()
+        }
+    }
+
+    fn hmac_sha1(key: &[u8], data: &[u8]) -> [u8; 20] {
+        const BLOCK_SIZE: usize = 64;
+        let mut block = [0u8; BLOCK_SIZE];
+        if key.len() > BLOCK_SIZE {
+            block[..20].copy_from_slice(&Sha1::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = Sha1::new();
+        inner.update(block.map(|b| b ^ 0x36));
+        inner.update(data);
+        let mut outer = Sha1::new();
+        outer.update(block.map(|b| b ^ 0x5c));
+        outer.update(inner.finalize());
+        outer.finalize().into()
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use axum::http::HeaderMap;
+
+        #[test]
+        fn only_correctly_signed_webhooks_are_accepted() {
+            let twilio = Twilio::new("secret".into());
+            let url = "https://poker.example.com/api/inbound/twilio";
+            let body = b"From=%2B15551234567&Body=VOTE+5";
+            let params: Vec<(String, String)> = url::form_urlencoded::parse(body).into_owned().collect();
+
+            let mut headers = HeaderMap::new();
+            headers.insert("x-twilio-signature", twilio.signature(url, &params).parse().unwrap());
+            let message = twilio.parse_inbound(&InboundRequest { url, headers: &headers, body }).unwrap();
+            assert_eq!(message.from, "+15551234567");
+            assert_eq!(message.body, "VOTE 5");
+
+            let forged = Twilio::new("other".into()).signature(url, &params);
+            headers.insert("x-twilio-signature", forged.parse().unwrap());
+            assert!(twilio.parse_inbound(&InboundRequest { url, headers: &headers, body }).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_read_from_loosely_typed_texts() {
+        assert_eq!(parse_command("vote 5"), SmsCommand::Vote(Some("5".into())));
+        assert_eq!(parse_command("VOTE"), SmsCommand::Vote(None));
+        assert_eq!(
+            parse_command("Join 12-34-56-78 Ada Lovelace"),
+            SmsCommand::Join { invite_code: "12 34 56 78".into(), name: "Ada Lovelace".into() }
+        );
+        assert_eq!(parse_command("JOIN 12 34 56 78"), SmsCommand::Help, "a name is needed");
+        assert_eq!(parse_command("hello?"), SmsCommand::Help);
+    }
+}
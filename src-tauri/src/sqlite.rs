@@ -0,0 +1,144 @@
+//! The `sqlite` storage backend: rooms and settings in a SQLite database, through
+//! rusqlite with SQLite bundled in, so no system library is needed.
+
+use crate::storage::{Database, RoomStore};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait for another connection's write lock before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS rooms (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS settings (name TEXT PRIMARY KEY, data TEXT NOT NULL);
+";
+/// Row of the settings table holding the app settings
+const APP_SETTINGS: &str = "app";
+
+/// Rooms, each stored as the same versioned (and possibly encrypted) JSON as a
+/// room file, and the settings document
+pub struct SqliteDatabase {
+    connection: Connection,
+    path: PathBuf,
+}
+
+impl SqliteDatabase {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let connection = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        connection.busy_timeout(BUSY_TIMEOUT).map_err(sql_error)?;
+        connection.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(Self { connection, path: path.to_path_buf() })
+    }
+}
+
+impl RoomStore for SqliteDatabase {
+    fn put_room(&mut self, room_id: &str, text: &str) -> Result<(), String> {
+        self.connection
+            .execute(
+                "INSERT INTO rooms (id, data) VALUES (?1, ?2) ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+                params![room_id, text],
+            )
+            .map(|_| ())
+            .map_err(sql_error)
+    }
+
+    fn delete_room(&mut self, room_id: &str) -> Result<(), String> {
+        self.connection
+            .execute("DELETE FROM rooms WHERE id = ?1", params![room_id])
+            .map(|_| ())
+            .map_err(sql_error)
+    }
+
+    fn replace_rooms(&mut self, rooms: &[(String, String)]) -> Result<(), String> {
+        // Rolled back when dropped without committing
+        let transaction = self.connection.transaction().map_err(sql_error)?;
+        transaction.execute("DELETE FROM rooms", []).map_err(sql_error)?;
+        for (room_id, text) in rooms {
+            transaction
+                .execute("INSERT INTO rooms (id, data) VALUES (?1, ?2)", params![room_id, text])
+                .map_err(sql_error)?;
+        }
+        transaction.commit().map_err(sql_error)
+    }
+}
+
+impl Database for SqliteDatabase {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn rooms(&mut self) -> Result<Vec<(String, String)>, String> {
+        let mut statement = self.connection.prepare("SELECT id, data FROM rooms ORDER BY id").map_err(sql_error)?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(sql_error)?;
+        rows.collect::<Result<_, _>>().map_err(sql_error)
+    }
+
+    fn settings(&mut self) -> Result<Option<String>, String> {
+        self.connection
+            .query_row("SELECT data FROM settings WHERE name = ?1", params![APP_SETTINGS], |row| row.get(0))
+            .optional()
+            .map_err(sql_error)
+    }
+
+    fn put_settings(&mut self, text: &str) -> Result<(), String> {
+        self.connection
+            .execute(
+                "INSERT INTO settings (name, data) VALUES (?1, ?2) ON CONFLICT (name) DO UPDATE SET data = excluded.data",
+                params![APP_SETTINGS, text],
+            )
+            .map(|_| ())
+            .map_err(sql_error)
+    }
+}
+
+fn sql_error(error: rusqlite::Error) -> String {
+    format!("SQLite error: {}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("scrum-poker-sqlite-{}.db", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn rooms_and_settings_are_kept_in_the_database() {
+        let path = temp_path();
+        let mut database = SqliteDatabase::open(&path).unwrap();
+        database.put_room("gone", "{}").unwrap();
+        database.replace_rooms(&[("a".into(), "{\"v\":1}".into()), ("b".into(), "{}".into())]).unwrap();
+        database.put_room("a", "{\"v\":2}").unwrap();
+        database.delete_room("b").unwrap();
+        database.put_settings("{\"version\":1}").unwrap();
+        drop(database);
+
+        // A fresh connection, as on the next start
+        let mut database = SqliteDatabase::open(&path).unwrap();
+        let rooms = database.rooms().unwrap();
+        let settings = database.settings().unwrap();
+        drop(database);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(rooms, vec![("a".to_string(), "{\"v\":2}".to_string())]);
+        assert_eq!(settings.as_deref(), Some("{\"version\":1}"));
+    }
+
+    #[test]
+    fn a_failed_replacement_keeps_the_rooms_there_were() {
+        let path = temp_path();
+        let mut database = SqliteDatabase::open(&path).unwrap();
+        database.put_room("a", "{}").unwrap();
+        // The same room twice breaks the primary key halfway through
+        let duplicated = [("b".to_string(), "{}".to_string()), ("b".to_string(), "{}".to_string())];
+        let failed = database.replace_rooms(&duplicated);
+        let rooms = database.rooms().unwrap();
+        drop(database);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(failed.is_err());
+        assert_eq!(rooms, vec![("a".to_string(), "{}".to_string())]);
+    }
+}
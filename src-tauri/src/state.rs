@@ -1,9 +1,87 @@
+use crate::accuracy::{self, IssueTypeAccuracy, TicketActual};
+use crate::ballots::{self, AsyncTicketResult, AsyncVoter, Ballot, BallotView};
+use crate::basic_client::BasicSession;
+use crate::campaign::Campaign;
+use crate::capacity;
+use crate::credentials::{LlmCredentials, MasterKey};
+use crate::decks::DeckDefinition;
+use crate::diagnostics::ErrorLog;
+use crate::estimate_rules::{EstimateRule, RuleHit};
+use crate::estimation::EstimationMethod;
+use crate::github;
+use crate::ipv6::Ipv6Route;
+use crate::merge::{self, MergeReport};
+use crate::moderation::{self, BlockedWordsFilter, NameFilter, ValidationError};
+use crate::outbox::ClientSender;
+use crate::persistence::JournalEvent;
 use crate::relay::RelayClient;
-use crate::room::{JiraTicket, Participant, Room, WsMessage};
-use dashmap::DashMap;
+use crate::seats::{self, RoomUsage, SeatDenied, SeatPolicy, SeatRequest, Usage};
+use crate::session_search::{self, DateRange, SessionResult};
+use crate::settings::AppSettings;
+use crate::sms::SmsSession;
+use crate::storage::{self, SharedChange, Storage};
+use crate::throttle::JoinThrottle;
+use crate::timezone::RoomTimeZone;
+use crate::room::{ClientError, ErrorCode, HistoryEvent, JiraTicket, Participant, ParticipantStatsSummary, QuorumRules, RevealMode, Room, RoomBranding, SessionSummary, TicketSignal, VotingRound, WsMessage};
+use dashmap::{DashMap, DashSet};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::RwLock;
-use tokio::sync::mpsc;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// Where rooms get the current time and fresh IDs from.
+/// The app uses the system clock and random UUIDs; tests substitute a deterministic source.
+pub trait Environment: Send + Sync {
+    /// Current Unix time in seconds
+    fn now_secs(&self) -> u64;
+    /// Current Unix time in milliseconds
+    fn now_millis(&self) -> u64 {
+        self.now_secs() * 1000
+    }
+    /// A new unique ID
+    fn new_id(&self) -> String;
+}
+
+/// The real clock and random IDs
+pub struct SystemEnvironment;
+
+impl Environment for SystemEnvironment {
+    fn now_secs(&self) -> u64 {
+        crate::room::now_secs()
+    }
+
+    fn now_millis(&self) -> u64 {
+        crate::room::now_millis()
+    }
+
+    fn new_id(&self) -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+}
+
+/// Shared handle to an `Environment`, defaulting to the system one
+#[derive(Clone)]
+pub struct EnvHandle(pub Arc<dyn Environment>);
+
+impl Default for EnvHandle {
+    fn default() -> Self {
+        Self(Arc::new(SystemEnvironment))
+    }
+}
+
+impl std::ops::Deref for EnvHandle {
+    type Target = dyn Environment;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl std::fmt::Debug for EnvHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EnvHandle")
+    }
+}
 
 /// Jira configuration for API access
 #[derive(Debug, Clone, Default)]
@@ -11,83 +89,625 @@ pub struct JiraConfig {
     pub base_url: String,
     pub email: String,
     pub api_token: String,
+    /// Set when Jira answered 401/403 with these credentials; cleared when they're replaced
+    pub rejected: bool,
 }
 
-/// Connection info for a WebSocket client
-pub struct Connection {
-    pub participant_id: String,
-    pub room_id: String,
-    pub sender: mpsc::UnboundedSender<WsMessage>,
+/// A Jira request that failed because the credentials were rejected, tried
+/// again once they've been re-entered
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingJiraRequest {
+    /// Load a ticket as a room's current ticket
+    FetchTicket { room_id: String, ticket_key: String },
+    /// Refresh a room's next queued tickets
+    Prefetch { room_id: String },
+    /// Carry out a triggered estimate rule
+    EstimateRule(RuleHit),
 }
 
+/// Longest a host may keep dropped participants around
+const MAX_DISCONNECT_GRACE_SECS: u64 = 30 * 60;
+
+/// Error shown to anyone voting during the discussion phase
+const VOTING_CLOSED_MESSAGE: &str = "Voting hasn't opened yet; the story is still being discussed";
+/// Error shown to an observer trying to vote
+const OBSERVER_VOTE_MESSAGE: &str = "You joined as an observer, so you can't vote";
+
+/// Longest countdown from the end of a discussion to voting opening
+pub const MAX_VOTING_COUNTDOWN_SECS: u32 = 60;
+
+/// How far ahead of the reveal broadcast clients are told to flip the cards
+const REVEAL_DELAY_MS: u64 = 500;
+
+/// Room events kept for watchers that haven't caught up yet
+const ROOM_EVENT_BUFFER: usize = 64;
+
+/// A room's state as just sent to its participants, or its removal
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "grpc"), allow(dead_code))] // Only watched by the gRPC API
+pub enum RoomEvent {
+    Updated(Box<Room>),
+    Deleted(String),
+}
+
+/// A room's connected participants, keyed by participant ID
+pub type RoomConnections = HashMap<String, ClientSender>;
+
 /// Application state shared across the app
 pub struct AppState {
     /// All rooms, keyed by room ID
     pub rooms: DashMap<String, Room>,
     /// Room ID to invite code mapping (for quick lookup)
     pub invite_codes: DashMap<String, String>,
-    /// Active WebSocket connections, keyed by participant ID
-    pub connections: DashMap<String, Connection>,
+    /// Active WebSocket connections, keyed by room ID. Rooms without
+    /// connections have no entry.
+    pub connections: DashMap<String, RoomConnections>,
+    /// Ticket hash last sent to each room's connections, so ticket details are
+    /// only sent again when they change
+    pub sent_ticket_hashes: DashMap<String, Option<String>>,
     /// Server port (set after server starts)
     pub server_port: RwLock<u16>,
     /// Server IP address
     pub server_ip: RwLock<String>,
+    /// Port of the loopback-only admin listener (set after it starts)
+    pub admin_port: RwLock<u16>,
     /// Jira configuration
     pub jira_config: RwLock<JiraConfig>,
+    /// Jira requests to retry once rejected credentials have been re-entered
+    pending_jira_requests: Mutex<Vec<PendingJiraRequest>>,
+    /// Called when Jira rejects the credentials, so the UI can ask for them again
+    jira_auth_callback: RwLock<Option<Box<dyn Fn() + Send + Sync>>>,
+    /// Language model used to draft session summaries, once unlocked
+    pub llm_credentials: RwLock<Option<LlmCredentials>>,
     /// Whether firewall port is open
     pub firewall_open: RwLock<bool>,
     /// Cached public IP address
     pub public_ip: RwLock<Option<String>>,
+    /// IPv6 address participants could use instead, from the last network check
+    pub ipv6_route: RwLock<Option<Ipv6Route>>,
     /// Relay client (when connected)
     pub relay_client: tokio::sync::RwLock<Option<Arc<RelayClient>>>,
+    /// User settings (proxy, ...)
+    pub settings: RwLock<AppSettings>,
+    /// Filter applied to participant names on join and rename
+    pub name_filter: RwLock<Arc<dyn NameFilter>>,
+    /// Seat policy plugged in by an embedding app; None uses the limits in the settings
+    seat_policy: RwLock<Option<Arc<dyn SeatPolicy>>>,
+    /// Where room changes are persisted (set once recovery has run)
+    pub storage: RwLock<Option<Box<dyn Storage>>>,
+    /// Key the stored rooms are encrypted with, if encryption is on
+    pub storage_key: RwLock<Option<MasterKey>>,
+    /// Rooms other instances sharing the storage change, until taken by `take_shared_changes`
+    shared_changes: tokio::sync::mpsc::UnboundedSender<SharedChange>,
+    shared_changes_rx: Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<SharedChange>>>,
+    /// Every room update sent out, for watchers such as gRPC event streams
+    room_events: tokio::sync::broadcast::Sender<RoomEvent>,
+    /// Token guarding the localhost admin page, regenerated on every start
+    pub admin_token: String,
+    /// Tokens for external controllers (Stream Deck buttons and the like), mapped
+    /// to the room they control; like the admin token, they last until restart
+    pub controller_tokens: DashMap<String, String>,
+    /// Recent warnings and errors, shown on the admin page
+    pub error_log: Arc<ErrorLog>,
+    /// Per-address limit on join attempts
+    pub join_throttle: JoinThrottle,
+    /// Keys of queued tickets whose full details have already been fetched
+    pub prefetched_tickets: DashSet<String>,
+    /// Display names of Jira users mentioned in descriptions, keyed by account ID
+    pub jira_user_names: DashMap<String, String>,
+    /// Participants whose connections are logged message by message, for debugging
+    traced_participants: DashSet<String>,
+    /// Participants using the plain HTML client, keyed by session token
+    pub basic_sessions: DashMap<String, BasicSession>,
+    /// Personal ballots for estimating without a live session, keyed by token
+    pub async_ballots: DashMap<String, Ballot>,
+    /// Participants voting by text message, keyed by phone number
+    pub sms_sessions: DashMap<String, SmsSession>,
+    /// Actual effort of completed tickets, fetched from Jira, keyed by ticket key
+    pub ticket_actuals: DashMap<String, TicketActual>,
+    /// Estimate rules triggered by revealed rounds, waiting to be carried out in Jira
+    pub estimate_rule_hits: Mutex<Vec<RuleHit>>,
+    /// Clock and ID source handed to rooms
+    pub env: EnvHandle,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        Self::with_env(EnvHandle::default())
+    }
+
+    pub fn with_env(env: EnvHandle) -> Self {
+        let (shared_changes, shared_changes_rx) = tokio::sync::mpsc::unbounded_channel();
         Self {
             rooms: DashMap::new(),
             invite_codes: DashMap::new(),
             connections: DashMap::new(),
+            sent_ticket_hashes: DashMap::new(),
             server_port: RwLock::new(0),
             server_ip: RwLock::new(String::new()),
+            admin_port: RwLock::new(0),
             jira_config: RwLock::new(JiraConfig::default()),
+            pending_jira_requests: Mutex::new(Vec::new()),
+            jira_auth_callback: RwLock::new(None),
+            llm_credentials: RwLock::new(None),
             firewall_open: RwLock::new(false),
             public_ip: RwLock::new(None),
+            ipv6_route: RwLock::new(None),
             relay_client: tokio::sync::RwLock::new(None),
+            settings: RwLock::new(AppSettings::default()),
+            name_filter: RwLock::new(Arc::new(BlockedWordsFilter::new(&[]))),
+            seat_policy: RwLock::new(None),
+            storage: RwLock::new(None),
+            storage_key: RwLock::new(None),
+            shared_changes,
+            shared_changes_rx: Mutex::new(Some(shared_changes_rx)),
+            room_events: tokio::sync::broadcast::channel(ROOM_EVENT_BUFFER).0,
+            admin_token: uuid::Uuid::new_v4().simple().to_string(),
+            controller_tokens: DashMap::new(),
+            error_log: Arc::new(ErrorLog::new()),
+            join_throttle: JoinThrottle::new(),
+            prefetched_tickets: DashSet::new(),
+            jira_user_names: DashMap::new(),
+            traced_participants: DashSet::new(),
+            ticket_actuals: DashMap::new(),
+            estimate_rule_hits: Mutex::new(Vec::new()),
+            basic_sessions: DashMap::new(),
+            sms_sessions: DashMap::new(),
+            async_ballots: DashMap::new(),
+            env,
+        }
+    }
+
+    /// Forget everything belonging to the current profile: rooms, connections,
+    /// controller tokens, Jira and model credentials, held Jira requests and rule hits, cached Jira
+    /// user names, the relay connection and room persistence
+    pub async fn clear_profile_data(&self) {
+        self.set_relay_client(None).await;
+        for room in self.connections.iter() {
+            for sender in room.values() {
+                let _ = sender.send(WsMessage::Kicked);
+            }
+        }
+        self.connections.clear();
+        self.rooms.clear();
+        self.invite_codes.clear();
+        self.controller_tokens.clear();
+        self.prefetched_tickets.clear();
+        self.basic_sessions.clear();
+        self.sms_sessions.clear();
+        self.async_ballots.clear();
+        self.ticket_actuals.clear();
+        *self.jira_config.write().unwrap() = JiraConfig::default();
+        self.jira_user_names.clear();
+        self.pending_jira_requests.lock().unwrap().clear();
+        *self.llm_credentials.write().unwrap() = None;
+        self.estimate_rule_hits.lock().unwrap().clear();
+        *self.storage.write().unwrap() = None;
+        *self.storage_key.write().unwrap() = None;
+        *self.settings.write().unwrap() = AppSettings::default();
+    }
+
+    /// Load rooms recovered from disk. Nobody is connected yet, so participants
+    /// are kept as disconnected along with their votes, and get their places
+    /// back if they rejoin within the room's grace period. In `shared` storage
+    /// they may be connected to another instance, so are left as they are.
+    pub fn restore_rooms(&self, rooms: Vec<Room>, shared: bool) {
+        for mut room in rooms {
+            if shared {
+                room.attach(self.env.clone());
+            } else {
+                room.resume(self.env.clone());
+            }
+            room.issue_type_decks = self.issue_type_decks();
+            self.invite_codes.insert(room.invite_code.clone(), room.id.clone());
+            self.rooms.insert(room.id.clone(), room);
+        }
+    }
+
+    /// Put back rooms saved when the app crashed, participants and votes
+    /// included. Participants are shown as disconnected, so they get their
+    /// places back if they rejoin within the room's grace period.
+    pub fn recover_session(&self, rooms: Vec<Room>, identities: &HashMap<String, String>) {
+        for mut room in rooms {
+            for participant in &mut room.participants {
+                participant.identity = identities.get(&participant.id).cloned();
+            }
+            room.resume(self.env.clone());
+            room.issue_type_decks = self.issue_type_decks();
+            let room_id = room.id.clone();
+            self.invite_codes.insert(room.invite_code.clone(), room_id.clone());
+            self.rooms.insert(room_id.clone(), room);
+            self.journal_room(&room_id);
+        }
+    }
+
+    pub fn set_storage(&self, storage: Box<dyn Storage>, key: Option<MasterKey>) {
+        storage.subscribe(self.shared_changes.clone());
+        *self.storage.write().unwrap() = Some(storage);
+        *self.storage_key.write().unwrap() = key;
+    }
+
+    /// Whether stored rooms have been restored and changes are being persisted
+    pub fn is_room_storage_unlocked(&self) -> bool {
+        self.storage.read().unwrap().is_some()
+    }
+
+    pub fn storage_key(&self) -> Option<MasterKey> {
+        self.storage_key.read().unwrap().clone()
+    }
+
+    /// Switch the key the stored rooms are encrypted with (None stores them in plain text)
+    pub fn set_storage_key(&self, key: Option<MasterKey>) {
+        if let Some(storage) = self.storage.read().unwrap().as_ref() {
+            storage.set_key(key.clone());
+        }
+        *self.storage_key.write().unwrap() = key;
+    }
+
+    /// Changes other instances make to the shared storage, for `apply_shared_change`.
+    /// There is one stream for the life of the app, so it can only be taken once.
+    pub fn take_shared_changes(&self) -> Option<tokio::sync::mpsc::UnboundedReceiver<SharedChange>> {
+        self.shared_changes_rx.lock().unwrap().take()
+    }
+
+    /// Take on a change another instance made to a room in the shared storage
+    /// and pass it on to the participants connected here. It's already stored,
+    /// so it isn't journaled again. Joins and votes here may not be stored yet,
+    /// so participants are merged as with the relay, keeping those connected
+    /// here; the rest of the room is as stored.
+    pub fn apply_shared_change(&self, change: SharedChange) {
+        match change {
+            SharedChange::Saved { room_id, text } => {
+                let mut stored = match storage::decode_room(&text, self.storage_key().as_ref()) {
+                    Ok((_, room)) => room,
+                    Err(e) => {
+                        tracing::warn!("Failed to read room {} changed by another instance: {}", room_id, e);
+                        return;
+                    }
+                };
+                stored.attach(self.env.clone());
+                stored.issue_type_decks = self.issue_type_decks();
+                let room = match self.rooms.get_mut(&room_id) {
+                    Some(mut local) => {
+                        self.invite_codes.remove(&local.invite_code);
+                        let mut room = stored.clone();
+                        room.participants = std::mem::take(&mut local.participants);
+                        room.delegations = std::mem::take(&mut local.delegations);
+                        room.signals = std::mem::take(&mut local.signals);
+                        merge::merge_participants(&mut room, stored, |id| self.is_connected(&room_id, id));
+                        *local = room.clone();
+                        room
+                    }
+                    None => {
+                        self.rooms.insert(room_id.clone(), stored.clone());
+                        stored
+                    }
+                };
+                self.invite_codes.insert(room.invite_code.clone(), room_id.clone());
+                self.send_room_update(&room);
+            }
+            SharedChange::Deleted { room_id } => {
+                self.forget_room(&room_id);
+            }
+        }
+    }
+
+    fn journal(&self, event: JournalEvent) {
+        if let Some(storage) = self.storage.read().unwrap().as_ref() {
+            storage.record(event);
+        }
+    }
+
+    /// Token letting an external controller run host actions on a room, created
+    /// the first time it's asked for
+    pub fn controller_token(&self, room_id: &str) -> Option<String> {
+        self.rooms.get(room_id)?;
+        if let Some(entry) = self.controller_tokens.iter().find(|entry| entry.value() == room_id) {
+            return Some(entry.key().clone());
+        }
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.controller_tokens.insert(token.clone(), room_id.to_string());
+        Some(token)
+    }
+
+    /// Room an external controller's token is for, if it still exists
+    pub fn room_for_controller(&self, token: &str) -> Option<String> {
+        let room_id = self.controller_tokens.get(token)?.clone();
+        self.rooms.contains_key(&room_id).then_some(room_id)
+    }
+
+    /// Persist a room's current state
+    fn journal_room(&self, room_id: &str) {
+        if let Some(room) = self.get_room(room_id) {
+            self.journal(JournalEvent::RoomSaved { room: Box::new(room) });
+        }
+    }
+
+    pub fn get_settings(&self) -> AppSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: AppSettings) {
+        *self.name_filter.write().unwrap() = Arc::new(BlockedWordsFilter::new(&settings.blocked_name_words));
+        *self.settings.write().unwrap() = settings;
+        // Rooms pick up the new issue type decks from their next ticket on
+        let decks = self.issue_type_decks();
+        for mut room in self.rooms.iter_mut() {
+            room.issue_type_decks = decks.clone();
         }
     }
 
+    /// Issue type decks from the settings; invalid ones are rejected on save
+    fn issue_type_decks(&self) -> HashMap<String, Vec<String>> {
+        self.settings.read().unwrap().issue_type_decks().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring issue type decks: {}", e);
+            HashMap::new()
+        })
+    }
+
+    /// Go back to the room's own deck after it was switched for the current ticket's issue type
+    pub fn revert_deck_switch(&self, room_id: &str) -> Result<(), String> {
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        room.revert_deck_switch()
+    }
+
+    /// Normalize a participant name and run it through the name filter
+    pub fn validate_participant_name(&self, name: &str) -> Result<String, ValidationError> {
+        let filter = self.name_filter.read().unwrap().clone();
+        moderation::validate_participant_name(name, filter.as_ref())
+    }
+
+    /// HTTP client for outbound requests, honoring the proxy settings
+    pub fn http_client(&self) -> Result<reqwest::Client, String> {
+        self.settings.read().unwrap().http_client()
+    }
+
     pub fn set_jira_config(&self, base_url: String, email: String, api_token: String) {
         let mut config = self.jira_config.write().unwrap();
         config.base_url = base_url.trim_end_matches('/').to_string();
         config.email = email;
         config.api_token = api_token;
+        config.rejected = false;
+    }
+
+    pub fn set_jira_auth_callback(&self, callback: impl Fn() + Send + Sync + 'static) {
+        *self.jira_auth_callback.write().unwrap() = Some(Box::new(callback));
+    }
+
+    /// Mark the Jira credentials as rejected, keeping `retry` to run once
+    /// they've been re-entered. The callback only fires on the first rejection,
+    /// so a burst of failing requests prompts the host once.
+    pub fn reject_jira_config(&self, retry: Option<PendingJiraRequest>) {
+        if let Some(request) = retry {
+            let mut pending = self.pending_jira_requests.lock().unwrap();
+            if !pending.contains(&request) {
+                pending.push(request);
+            }
+        }
+        let already_rejected = std::mem::replace(&mut self.jira_config.write().unwrap().rejected, true);
+        if !already_rejected {
+            tracing::warn!("Jira rejected the credentials; waiting for them to be re-entered");
+            if let Some(callback) = self.jira_auth_callback.read().unwrap().as_ref() {
+                callback();
+            }
+        }
+    }
+
+    /// Requests held back by rejected credentials, once they've been replaced
+    pub fn take_pending_jira_requests(&self) -> Vec<PendingJiraRequest> {
+        if self.jira_config.read().unwrap().rejected {
+            return Vec::new();
+        }
+        std::mem::take(&mut *self.pending_jira_requests.lock().unwrap())
     }
 
     pub fn get_jira_config(&self) -> JiraConfig {
         self.jira_config.read().unwrap().clone()
     }
 
+    pub fn llm_credentials(&self) -> Option<LlmCredentials> {
+        self.llm_credentials.read().unwrap().clone()
+    }
+
+    pub fn set_llm_credentials(&self, credentials: Option<LlmCredentials>) {
+        *self.llm_credentials.write().unwrap() = credentials;
+    }
+
     pub fn has_jira_config(&self) -> bool {
         let config = self.jira_config.read().unwrap();
-        !config.base_url.is_empty() && !config.email.is_empty() && !config.api_token.is_empty()
+        !config.base_url.is_empty() && !config.email.is_empty() && !config.api_token.is_empty() && !config.rejected
     }
 
     pub fn set_current_ticket(&self, room_id: &str, ticket: Option<JiraTicket>) {
         if let Some(mut room) = self.rooms.get_mut(room_id) {
-            room.current_ticket = ticket;
+            room.set_current_ticket(ticket);
+        }
+    }
+
+    /// Move a room's current ticket, with its votes so far and history, to another room
+    pub fn transfer_ticket(&self, from_room_id: &str, to_room_id: &str) -> Result<JiraTicket, String> {
+        if from_room_id == to_room_id {
+            return Err("The ticket is already in that room".into());
+        }
+        let to_room_name = match self.rooms.get(to_room_id) {
+            Some(room) if room.is_ended() => return Err("That room's session has ended".into()),
+            Some(room) => room.name.clone(),
+            None => return Err("Target room not found".into()),
+        };
+
+        // Only hold one room at a time; both may live in the same shard
+        let (handoff, from_room_name) = {
+            let mut from = self.rooms.get_mut(from_room_id).ok_or("Room not found")?;
+            let handoff = from.hand_off_ticket().ok_or("The room has no current ticket")?;
+            (handoff, from.name.clone())
+        };
+        let ticket = handoff.ticket.clone();
+        let event = HistoryEvent::TicketTransferred {
+            ticket_key: ticket.key.clone(),
+            from_room: from_room_name,
+            to_room: to_room_name,
+        };
+
+        match self.rooms.get_mut(to_room_id) {
+            Some(mut to) => {
+                to.receive_ticket(handoff);
+                to.record_history(event.clone());
+            }
+            None => {
+                // Deleted in the meantime; put the ticket back where it was
+                if let Some(mut from) = self.rooms.get_mut(from_room_id) {
+                    from.receive_ticket(handoff);
+                }
+                return Err("Target room not found".into());
+            }
+        }
+        if let Some(mut from) = self.rooms.get_mut(from_room_id) {
+            from.record_history(event);
+        }
+
+        self.journal_room(from_room_id);
+        self.journal_room(to_room_id);
+        Ok(ticket)
+    }
+
+    pub fn enqueue_tickets(&self, room_id: &str, tickets: Vec<JiraTicket>) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.enqueue_tickets(tickets);
+            drop(room);
+            self.journal_room(room_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn advance_ticket(&self, room_id: &str) -> Option<JiraTicket> {
+        let ticket = self.rooms.get_mut(room_id)?.advance_ticket();
+        if let Some(ticket) = &ticket {
+            self.prefetched_tickets.remove(&ticket.key);
+            self.journal_room(room_id);
+        }
+        ticket
+    }
+
+    /// Keys of the next `count` queued tickets whose details haven't been prefetched yet
+    pub fn tickets_to_prefetch(&self, room_id: &str, count: usize) -> Vec<String> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.ticket_queue
+                    .iter()
+                    .take(count)
+                    .filter(|t| !self.prefetched_tickets.contains(&t.key) && !github::is_pull_request_key(&t.key))
+                    .map(|t| t.key.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Store prefetched details for a queued ticket
+    pub fn update_queued_ticket(&self, room_id: &str, ticket: JiraTicket) {
+        let key = ticket.key.clone();
+        let updated = self.rooms.get_mut(room_id).is_some_and(|mut room| room.update_queued_ticket(ticket));
+        if updated {
+            self.prefetched_tickets.insert(key);
+            self.journal_room(room_id);
+        }
+    }
+
+    pub fn set_ticket_deadline(&self, room_id: &str, ticket_key: &str, minutes: Option<u32>) -> Result<(), String> {
+        if minutes == Some(0) {
+            return Err("A deadline must be at least one minute".into());
+        }
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.set_ticket_deadline(ticket_key, minutes);
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    pub fn requeue_parked_ticket(&self, room_id: &str, ticket_key: &str) -> Result<(), String> {
+        let requeued = self.rooms.get_mut(room_id).ok_or("Room not found")?.requeue_parked_ticket(ticket_key);
+        if !requeued {
+            return Err(format!("{} isn't parked", ticket_key));
+        }
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    pub fn start_batch(&self, room_id: &str, ticket_keys: &[String]) -> Result<(), String> {
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.start_batch(ticket_keys)?;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    /// Queue a campaign's tickets and start comparing their new estimates with the old ones
+    pub fn start_campaign(&self, room_id: &str, campaign: Campaign, tickets: Vec<JiraTicket>) -> Result<(), String> {
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        room.enqueue_tickets(tickets);
+        room.campaign = Some(campaign);
+        drop(room);
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    pub fn end_campaign(&self, room_id: &str) -> Result<(), String> {
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.campaign = None;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    /// Note that a campaign ticket's new estimate is now in Jira
+    pub fn mark_campaign_written(&self, room_id: &str, ticket_key: &str) {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            if let Some(campaign) = room.campaign.as_mut() {
+                campaign.mark_written(ticket_key);
+            }
         }
     }
 
-    pub fn create_room(&self, name: String) -> Room {
-        let room = Room::new(name);
+    /// Park every current ticket that ran past its deadline, returning the room IDs
+    /// and the tickets that were parked
+    pub fn park_overdue_tickets(&self) -> Vec<(String, JiraTicket)> {
+        let room_ids: Vec<String> = self.rooms.iter().map(|room| room.id.clone()).collect();
+        let mut parked = Vec::new();
+        for room_id in room_ids {
+            let ticket = self.rooms.get_mut(&room_id).and_then(|mut room| room.park_overdue_ticket());
+            if let Some(ticket) = ticket {
+                self.journal_room(&room_id);
+                parked.push((room_id, ticket));
+            }
+        }
+        parked
+    }
+
+    pub fn record_history(&self, room_id: &str, event: HistoryEvent) {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.record_history(event);
+            drop(room);
+            self.journal_room(room_id);
+        }
+    }
+
+    /// Create a room voting with `deck`, or with the default story points.
+    /// With `host_name`, the host takes part in the votes as a participant too.
+    pub fn create_room(&self, name: String, deck: Option<DeckDefinition>, host_name: Option<String>) -> Result<Room, ValidationError> {
+        let name = moderation::validate_room_name(&name)?;
+        let cards = deck.map(|deck| deck.cards()).transpose()?;
+        let host_name = host_name
+            .map(|host_name| moderation::validate_text("host_name", "Your name", &host_name, moderation::MAX_NAME_LENGTH))
+            .transpose()?;
+        let mut room = Room::new(name, cards, self.env.clone());
+        room.issue_type_decks = self.issue_type_decks();
+        if let Some(host_name) = host_name {
+            room.add_participant(Participant::new(host_name, true));
+        }
         let room_id = room.id.clone();
         let invite_code = room.invite_code.clone();
         
         self.rooms.insert(room_id.clone(), room.clone());
         self.invite_codes.insert(invite_code, room_id);
+        self.journal(JournalEvent::RoomSaved { room: Box::new(room.clone()) });
         
-        room
+        Ok(room)
     }
 
     pub fn get_room(&self, room_id: &str) -> Option<Room> {
@@ -105,20 +725,25 @@ impl AppState {
     }
 
     pub fn delete_room(&self, room_id: &str) -> bool {
+        let deleted = self.forget_room(room_id);
+        if deleted {
+            self.journal(JournalEvent::RoomDeleted { room_id: room_id.to_string() });
+        }
+        deleted
+    }
+
+    /// Drop a room and disconnect everyone in it, without touching the storage
+    fn forget_room(&self, room_id: &str) -> bool {
         if let Some((_, room)) = self.rooms.remove(room_id) {
+            let _ = self.room_events.send(RoomEvent::Deleted(room_id.to_string()));
             self.invite_codes.remove(&room.invite_code);
+            self.sent_ticket_hashes.remove(room_id);
+            self.controller_tokens.retain(|_, controlled| controlled != room_id);
             
             // Disconnect all participants in this room
-            let to_remove: Vec<String> = self
-                .connections
-                .iter()
-                .filter(|c| c.room_id == room_id)
-                .map(|c| c.participant_id.clone())
-                .collect();
-            
-            for participant_id in to_remove {
-                if let Some((_, conn)) = self.connections.remove(&participant_id) {
-                    let _ = conn.sender.send(WsMessage::Kicked);
+            if let Some((_, connections)) = self.connections.remove(room_id) {
+                for sender in connections.values() {
+                    let _ = sender.send(WsMessage::Kicked);
                 }
             }
             
@@ -132,10 +757,16 @@ impl AppState {
     /// This is called when the relay server sends a room_update message
     pub fn update_room_from_relay(&self, relay_room: Room) {
         if let Some(mut local_room) = self.rooms.get_mut(&relay_room.id) {
-            // Sync participants from relay (relay is authoritative for participant list)
-            local_room.participants = relay_room.participants;
+            let revealed = relay_room.votes_revealed;
+            // Relay participants come from the relay, keeping anyone connected to this
+            // host directly or bridged through the relay; the later change to a vote wins
+            let room_id = relay_room.id.clone();
+            merge::merge_participants(&mut local_room, relay_room, |id| self.is_connected(&room_id, id));
             // Sync votes_revealed state
-            local_room.votes_revealed = relay_room.votes_revealed;
+            local_room.votes_revealed = revealed;
+            if !revealed {
+                local_room.reveal_at = None;
+            }
             // Note: We don't sync current_ticket from relay as it's set locally
             tracing::debug!(
                 "Updated local room {} from relay: {} participants",
@@ -150,10 +781,103 @@ impl AppState {
         }
     }
 
+    /// Reconcile a room with the relay's copy after the connection to the relay
+    /// was lost for a while, so neither side's joins and votes are thrown away.
+    /// Only this host can reveal or change the ticket, so those stay as they are
+    /// here. Returns None for rooms the relay doesn't have.
+    pub fn merge_room_from_relay(&self, relay_room: Room) -> Option<MergeReport> {
+        let mut local_room = self.rooms.get_mut(&relay_room.id)?;
+        let room_id = relay_room.id.clone();
+        let report = merge::merge_participants(&mut local_room, relay_room, |id| self.is_connected(&room_id, id));
+        if !report.is_empty() {
+            tracing::info!(
+                room_id = %room_id,
+                joined_via_relay = report.joined_via_relay.len(),
+                kept_local = report.kept_local.len(),
+                removed = report.removed.len(),
+                votes_from_relay = report.votes_from_relay.len(),
+                votes_kept_local = report.votes_kept_local.len(),
+                "Merged room with the relay's copy"
+            );
+        }
+        Some(report)
+    }
+
+    /// Replace the participant limits from the settings with the embedding
+    /// app's own seat policy
+    #[allow(dead_code)] // Only called by embedding apps
+    pub fn set_seat_policy(&self, policy: Arc<dyn SeatPolicy>) {
+        *self.seat_policy.write().unwrap() = Some(policy);
+    }
+
+    fn seat_policy(&self) -> Arc<dyn SeatPolicy> {
+        match self.seat_policy.read().unwrap().as_ref() {
+            Some(policy) => policy.clone(),
+            None => Arc::new(self.settings.read().unwrap().seat_limits()),
+        }
+    }
+
+    /// Check with the seat policy whether someone may join a room. Someone
+    /// reclaiming their place already has a seat. Unknown rooms pass, since
+    /// joining them fails anyway.
+    pub fn check_seat(&self, room_id: &str, identity: Option<&str>) -> Result<(), SeatDenied> {
+        let Some(room) = self.get_room(room_id) else {
+            return Ok(());
+        };
+        if identity.is_some() && room.participants.iter().any(|p| p.identity.as_deref() == identity) {
+            return Ok(());
+        }
+        let total_seats = self.rooms.iter().filter(|r| !r.is_ended()).map(|r| seats::seats_taken(&r)).sum();
+        self.seat_policy().admit(&SeatRequest {
+            room_id,
+            room_seats: seats::seats_taken(&room),
+            total_seats,
+            identity,
+        })
+    }
+
+    /// Whether anyone is connected to an open room, i.e. a session is under way
+    pub fn has_active_session(&self) -> bool {
+        self.rooms
+            .iter()
+            .any(|room| !room.is_ended() && self.connections.get(&room.id).is_some_and(|c| !c.is_empty()))
+    }
+
+    /// Seats taken in each open room and overall
+    pub fn get_usage(&self) -> Usage {
+        let mut rooms: Vec<RoomUsage> = self
+            .rooms
+            .iter()
+            .filter(|room| !room.is_ended())
+            .map(|room| RoomUsage {
+                room_id: room.id.clone(),
+                room_name: room.name.clone(),
+                seats: seats::seats_taken(&room),
+            })
+            .collect();
+        rooms.sort_by(|a, b| a.room_name.cmp(&b.room_name));
+        Usage {
+            total_seats: rooms.iter().map(|r| r.seats).sum(),
+            rooms,
+            limits: self.seat_policy().limits(),
+        }
+    }
+
     pub fn add_participant(&self, room_id: &str, participant: Participant) -> Option<String> {
         let participant_id = participant.id.clone();
+        if let Err(denied) = self.check_seat(room_id, participant.identity.as_deref()) {
+            tracing::info!(room_id, "Join refused by the seat policy: {}", denied);
+            return None;
+        }
         
         if let Some(mut room) = self.rooms.get_mut(room_id) {
+            if room.is_ended() || !room.admits(participant.identity.as_deref()) {
+                return None;
+            }
+            if let Some(reclaimed) = room.reclaim_participant(&participant) {
+                tracing::info!("Participant {} rejoined room {}", reclaimed, room_id);
+                return Some(reclaimed);
+            }
             room.add_participant(participant);
             Some(participant_id)
         } else {
@@ -166,65 +890,672 @@ impl AppState {
             room.remove_participant(participant_id);
         }
         
-        // Also remove connection and notify
-        if let Some((_, conn)) = self.connections.remove(participant_id) {
-            let _ = conn.sender.send(WsMessage::Kicked);
+        // Also notify and remove the connection
+        self.send_to_participant(room_id, participant_id, WsMessage::Kicked);
+        self.unregister_connection(room_id, participant_id);
+    }
+
+    /// A participant's connection dropped; they stay greyed out for the room's grace period.
+    /// Ignored if they've already reconnected on another connection.
+    pub fn participant_disconnected(&self, room_id: &str, participant_id: &str, connection: &ClientSender) {
+        let current = self
+            .connections
+            .get(room_id)
+            .and_then(|connections| connections.get(participant_id).map(|sender| sender.same_client(connection)));
+        if current == Some(false) {
+            return;
+        }
+        self.unregister_connection(room_id, participant_id);
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.participant_disconnected(participant_id);
+        }
+    }
+
+    /// Announce when voting starts in a room, or clear the start time with None
+    pub fn set_scheduled_start(&self, room_id: &str, starts_at: Option<u64>) -> Result<(), String> {
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        if starts_at.is_some_and(|at| at <= self.env.now_secs()) {
+            return Err("The start time must be in the future".into());
+        }
+        room.starts_at = starts_at;
+        drop(room);
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    pub fn set_room_time_zone(&self, room_id: &str, time_zone: RoomTimeZone) -> Result<(), String> {
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.time_zone = time_zone;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    /// Tag a room's revealed round, returning the round's ticket key
+    pub fn tag_round(&self, room_id: &str, tag: &str) -> Result<Option<String>, String> {
+        let ticket_key = self.rooms.get_mut(room_id).ok_or("Room not found")?.tag_round(tag)?;
+        self.journal_room(room_id);
+        Ok(ticket_key)
+    }
+
+    /// Override a room's revealed estimate, recording the host's reason
+    pub fn override_estimate(&self, room_id: &str, value: &str, reason: &str) -> Result<(), String> {
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.override_estimate(value, reason)?;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    pub fn set_disconnect_grace(&self, room_id: &str, secs: u64) -> Result<(), String> {
+        if secs > MAX_DISCONNECT_GRACE_SECS {
+            return Err(format!("The grace period can be at most {} minutes", MAX_DISCONNECT_GRACE_SECS / 60));
         }
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.disconnect_grace_secs = secs;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    /// Remove participants whose grace period ran out, returning the rooms that changed
+    pub fn expire_disconnected_participants(&self) -> Vec<String> {
+        let room_ids: Vec<String> = self.rooms.iter().map(|room| room.id.clone()).collect();
+        room_ids
+            .into_iter()
+            .filter(|room_id| {
+                let expired = self.rooms.get_mut(room_id).map(|mut room| room.expire_disconnected()).unwrap_or_default();
+                for participant_id in &expired {
+                    tracing::info!("Participant {} didn't come back to room {}; removing", participant_id, room_id);
+                }
+                !expired.is_empty()
+            })
+            .collect()
     }
 
-    pub fn set_vote(&self, room_id: &str, participant_id: &str, vote: Option<String>) {
+    pub fn rename_participant(&self, room_id: &str, participant_id: &str, name: String) -> bool {
         if let Some(mut room) = self.rooms.get_mut(room_id) {
-            room.set_vote(participant_id, vote);
+            room.rename_participant(participant_id, name)
+        } else {
+            false
         }
     }
 
+    /// Record a participant's vote, rejecting cards that aren't in the room's deck
+    pub fn set_vote(&self, room_id: &str, participant_id: &str, vote: Option<String>) -> Result<(), ClientError> {
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?;
+        if room.is_ended() {
+            return Err(ClientError::new(ErrorCode::SessionEnded, "This session has ended"));
+        }
+        if !room.voting_open() {
+            return Err(ClientError::new(ErrorCode::VotingClosed, VOTING_CLOSED_MESSAGE));
+        }
+        if room.is_observer(participant_id) {
+            return Err(ClientError::new(ErrorCode::InvalidVote, OBSERVER_VOTE_MESSAGE));
+        }
+        if let Some(vote) = &vote {
+            if !room.is_valid_vote(vote) {
+                return Err(ClientError::new(ErrorCode::InvalidVote, "Invalid vote"));
+            }
+        }
+        room.set_vote(participant_id, vote);
+        Ok(())
+    }
+
+    /// Hand a participant's vote to someone else for this round, or take it back
+    pub fn delegate_vote(&self, room_id: &str, participant_id: &str, to: Option<&str>) -> Result<(), ClientError> {
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?;
+        if room.is_ended() {
+            return Err(ClientError::new(ErrorCode::SessionEnded, "This session has ended"));
+        }
+        if !room.voting_open() {
+            return Err(ClientError::new(ErrorCode::VotingClosed, VOTING_CLOSED_MESSAGE));
+        }
+        if room.is_observer(participant_id) {
+            return Err(ClientError::new(ErrorCode::InvalidDelegation, OBSERVER_VOTE_MESSAGE));
+        }
+        room.delegate_vote(participant_id, to)
+            .map_err(|message| ClientError::new(ErrorCode::InvalidDelegation, message))
+    }
+
+    /// Raise or lower a participant's signal about the current ticket
+    pub fn set_signal(&self, room_id: &str, participant_id: &str, signal: TicketSignal, raised: bool) -> Result<(), ClientError> {
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?;
+        if room.is_ended() {
+            return Err(ClientError::new(ErrorCode::SessionEnded, "This session has ended"));
+        }
+        if !room.set_signal(participant_id, signal, raised) {
+            return Err(ClientError::new(ErrorCode::Other, "Participant not found"));
+        }
+        Ok(())
+    }
+
+    /// Take a participant out of the voting or bring them back, by their own
+    /// choice or the host's
+    pub fn set_sitting_out(&self, room_id: &str, participant_id: &str, sitting_out: bool) -> Result<(), ClientError> {
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?;
+        if room.is_ended() {
+            return Err(ClientError::new(ErrorCode::SessionEnded, "This session has ended"));
+        }
+        if !room.set_sitting_out(participant_id, sitting_out) {
+            return Err(ClientError::new(ErrorCode::Other, "Participant not found"));
+        }
+        Ok(())
+    }
+
+    /// Record a participant's risk vote, for rooms that vote on risk as well as effort
+    pub fn set_risk_vote(&self, room_id: &str, participant_id: &str, vote: Option<String>) -> Result<(), ClientError> {
+        let mut room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?;
+        if room.is_ended() {
+            return Err(ClientError::new(ErrorCode::SessionEnded, "This session has ended"));
+        }
+        if !room.voting_open() {
+            return Err(ClientError::new(ErrorCode::VotingClosed, VOTING_CLOSED_MESSAGE));
+        }
+        if room.is_observer(participant_id) {
+            return Err(ClientError::new(ErrorCode::InvalidVote, OBSERVER_VOTE_MESSAGE));
+        }
+        if let Some(vote) = &vote {
+            if !room.is_valid_risk_vote(vote) {
+                return Err(ClientError::new(ErrorCode::InvalidVote, "Invalid risk vote"));
+            }
+        }
+        room.set_risk_vote(participant_id, vote);
+        Ok(())
+    }
+
+    /// Reveal a room's votes if it auto-reveals and its quorum is now met,
+    /// telling the relay too. Call after a vote, before broadcasting.
+    pub async fn auto_reveal_if_ready(&self, room_id: &str) -> bool {
+        let revealed = self
+            .rooms
+            .get_mut(room_id)
+            .is_some_and(|mut room| {
+                let revealed = room.auto_reveal_if_ready();
+                if revealed {
+                    self.schedule_reveal(&mut room);
+                }
+                revealed
+            });
+        if revealed {
+            tracing::info!("Quorum met in room {}; revealing votes", room_id);
+            if let Some(relay_client) = self.get_relay_client().await {
+                let _ = relay_client.reveal_votes(room_id.to_string());
+            }
+        }
+        revealed
+    }
+
+    pub fn set_reveal_rules(&self, room_id: &str, auto_reveal: bool, quorum: QuorumRules) -> Result<(), String> {
+        if quorum.min_voted_percent.is_some_and(|p| p == 0 || p > 100) {
+            return Err("Minimum share of voters must be between 1 and 100%".into());
+        }
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        room.auto_reveal = auto_reveal;
+        room.quorum = quorum;
+        Ok(())
+    }
+
     pub fn set_votes_revealed(&self, room_id: &str, revealed: bool) {
         if let Some(mut room) = self.rooms.get_mut(room_id) {
             room.votes_revealed = revealed;
+            // Revealing completes the round, so fold it into the session stats
+            if revealed {
+                room.complete_round();
+                self.schedule_reveal(&mut room);
+            } else {
+                room.reveal_at = None;
+                room.reveal_progress = None;
+            }
+        }
+    }
+
+    pub fn set_reveal_mode(&self, room_id: &str, mode: RevealMode) -> Result<(), String> {
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        room.reveal_mode = mode;
+        Ok(())
+    }
+
+    /// Start a one-by-one reveal if the room uses one; false means the votes
+    /// should be revealed all at once instead
+    pub fn start_stepwise_reveal(&self, room_id: &str) -> bool {
+        self.rooms.get_mut(room_id).is_some_and(|mut room| room.start_stepwise_reveal())
+    }
+
+    /// Show the next card of a one-by-one reveal to everyone. The last card
+    /// reveals the round as usual. Returns false if no such reveal is under way.
+    pub async fn reveal_next_vote(&self, room_id: &str) -> bool {
+        let (shown, finished) = {
+            let Some(mut room) = self.rooms.get_mut(room_id) else {
+                return false;
+            };
+            if room.reveal_progress.is_none() {
+                return false;
+            }
+            let shown = room
+                .reveal_next_vote()
+                .and_then(|id| room.participants.iter().find(|p| p.id == id).map(|p| (id, p.vote.clone())));
+            let finished = room.votes_revealed;
+            if finished {
+                self.schedule_reveal(&mut room);
+            }
+            (shown, finished)
+        };
+
+        let relay_client = self.get_relay_client().await;
+        if let Some((participant_id, vote)) = shown {
+            if let Some(connections) = self.connections.get(room_id) {
+                for sender in connections.values() {
+                    let _ = sender.send(WsMessage::VoteRevealed { participant_id: participant_id.clone(), vote: vote.clone() });
+                }
+            }
+            if let Some(relay_client) = &relay_client {
+                let _ = relay_client.reveal_vote(room_id.to_string(), participant_id);
+            }
+        }
+        if finished {
+            self.broadcast_room_update(room_id).await;
+            if let Some(relay_client) = &relay_client {
+                let _ = relay_client.reveal_votes(room_id.to_string());
+            }
+        }
+        true
+    }
+
+    /// Rooms in the middle of a timed one-by-one reveal
+    fn timed_reveals(&self) -> Vec<String> {
+        self.rooms
+            .iter()
+            .filter(|room| room.reveal_mode == RevealMode::Timed && room.reveal_progress.is_some())
+            .map(|room| room.id.clone())
+            .collect()
+    }
+
+    /// Pick the moment clients flip the cards, far enough ahead that the
+    /// update reaches everyone first
+    fn schedule_reveal(&self, room: &mut Room) {
+        room.reveal_at = Some(self.env.now_millis() + REVEAL_DELAY_MS);
+        // Every way of revealing ends up here, so it's where estimate rules are checked
+        self.queue_estimate_rule(room);
+    }
+
+    /// Queue the room's estimate rule (or the one in the settings) if the
+    /// revealed round's estimate triggers it. The suggested card counts as the
+    /// estimate, or the average when there's no suggestion.
+    fn queue_estimate_rule(&self, room: &Room) {
+        let Some(ticket) = &room.current_ticket else {
+            return;
+        };
+        let Some(rule) = room.estimate_rule.clone().or_else(|| self.settings.read().unwrap().estimate_rule.clone()) else {
+            return;
+        };
+        let summary = room.get_vote_summary();
+        let estimate = summary.suggested.as_deref().and_then(|card| card.parse::<f64>().ok()).or(summary.average);
+        if let Some(hit) = estimate.and_then(|estimate| rule.check(&room.id, &ticket.key, estimate)) {
+            self.estimate_rule_hits.lock().unwrap().push(hit);
+        }
+    }
+
+    /// Estimate rules triggered since the last call
+    pub fn take_estimate_rule_hits(&self) -> Vec<RuleHit> {
+        std::mem::take(&mut *self.estimate_rule_hits.lock().unwrap())
+    }
+
+    pub fn set_room_estimate_rule(&self, room_id: &str, rule: Option<EstimateRule>) -> Result<(), String> {
+        if let Some(rule) = &rule {
+            rule.validate()?;
+        }
+        self.rooms.get_mut(room_id).ok_or("Room not found")?.estimate_rule = rule;
+        self.journal_room(room_id);
+        Ok(())
+    }
+
+    /// Apply a change to a room's branding
+    pub fn update_room_branding<F>(&self, room_id: &str, update: F) -> bool
+    where
+        F: FnOnce(&mut RoomBranding),
+    {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            update(&mut room.branding);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_room_lan_only(&self, room_id: &str, lan_only: bool) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.lan_only = lan_only;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_room_locked(&self, room_id: &str, locked: bool) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.locked = locked;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Start or end a room's discussion phase. Ending it opens voting after
+    /// `countdown_secs`.
+    pub fn set_discussion_phase(&self, room_id: &str, discussing: bool, countdown_secs: u32) -> Result<(), String> {
+        if countdown_secs > MAX_VOTING_COUNTDOWN_SECS {
+            return Err(format!("The countdown can be at most {} seconds", MAX_VOTING_COUNTDOWN_SECS));
+        }
+        let mut room = self.rooms.get_mut(room_id).ok_or("Room not found")?;
+        if discussing {
+            room.start_discussion();
+        } else {
+            room.open_voting(countdown_secs);
+        }
+        Ok(())
+    }
+
+    pub fn set_room_hide_voters(&self, room_id: &str, hidden: bool) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.hide_voters = hidden;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_room_risk_voting(&self, room_id: &str, enabled: bool) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.set_risk_voting(enabled);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_room_abstain_offered(&self, room_id: &str, offered: bool) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.set_abstain_offered(offered);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_room_sprint_capacity(&self, room_id: &str, capacity: Option<f64>) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.sprint_capacity = capacity;
+            true
+        } else {
+            false
         }
     }
 
+    pub fn set_room_estimation_method(&self, room_id: &str, method: EstimationMethod) -> bool {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.estimation_method = method;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// End a room's session and let every connected client know
+    pub fn end_session(&self, room_id: &str) -> Option<SessionSummary> {
+        let summary = self.rooms.get_mut(room_id).map(|mut room| room.end_session())?;
+        self.journal_room(room_id);
+
+        if let Some(connections) = self.connections.get(room_id) {
+            for sender in connections.values() {
+                let _ = sender.send(WsMessage::SessionEnded { summary: summary.clone() });
+            }
+        }
+
+        Some(summary)
+    }
+
+    /// Estimated tickets whose actual effort hasn't been fetched yet
+    pub fn tickets_missing_actuals(&self) -> Vec<String> {
+        let rooms: Vec<_> = self.rooms.iter().collect();
+        let mut keys: Vec<String> = accuracy::final_estimates(rooms.iter().map(|room| room.value()))
+            .into_keys()
+            .filter(|key| !self.ticket_actuals.contains_key(key) && !github::is_pull_request_key(key))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Store newly fetched actuals and save them all
+    pub fn record_ticket_actuals(&self, actuals: Vec<TicketActual>) -> Result<(), String> {
+        for actual in actuals {
+            self.ticket_actuals.insert(actual.ticket_key.clone(), actual);
+        }
+        let all: Vec<TicketActual> = self.ticket_actuals.iter().map(|actual| actual.clone()).collect();
+        accuracy::save_actuals(&all)
+    }
+
+    /// Issue a personal ballot per voter for the room's current and queued tickets
+    pub fn issue_async_ballots(&self, room_id: &str, voters: &[AsyncVoter]) -> Result<Vec<Ballot>, String> {
+        let room = self.get_room(room_id).ok_or("Room not found")?;
+        let issued = ballots::issue_ballots(&room, voters, self)?;
+        for ballot in &issued {
+            self.async_ballots.insert(ballot.token.clone(), ballot.clone());
+        }
+        Ok(issued)
+    }
+
+    pub fn ballot_view(&self, token: &str) -> Option<BallotView> {
+        let ballot = self.async_ballots.get(token)?;
+        let room = self.rooms.get(&ballot.room_id)?;
+        Some(BallotView {
+            room_name: room.name.clone(),
+            name: ballot.name.clone(),
+            deck: room.deck.clone(),
+            tickets: ballot.tickets.clone(),
+            votes: ballot.votes.clone(),
+        })
+    }
+
+    /// Record (or with None, take back) an async vote on one of the ballot's tickets
+    pub fn cast_ballot_vote(&self, token: &str, ticket_key: &str, vote: Option<String>) -> Result<BallotView, String> {
+        {
+            let mut ballot = self.async_ballots.get_mut(token).ok_or("Ballot not found")?;
+            let room = self.rooms.get(&ballot.room_id).ok_or("Room not found")?;
+            if room.is_ended() {
+                return Err("This session has ended".into());
+            }
+            if !ballot.tickets.iter().any(|ticket| ticket.key == ticket_key) {
+                return Err("That ticket isn't on your ballot".into());
+            }
+            match vote {
+                Some(vote) if !room.is_valid_vote(&vote) => return Err("Invalid vote".into()),
+                Some(vote) => ballot.votes.insert(ticket_key.to_string(), vote),
+                None => ballot.votes.remove(ticket_key),
+            };
+        }
+        self.ballot_view(token).ok_or_else(|| "Ballot not found".into())
+    }
+
+    /// Async votes so far on each ticket of the room's ballots
+    pub fn async_results(&self, room_id: &str) -> Vec<AsyncTicketResult> {
+        let mut room_ballots: Vec<Ballot> =
+            self.async_ballots.iter().filter(|b| b.room_id == room_id).map(|b| b.clone()).collect();
+        room_ballots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.name.cmp(&b.name)));
+        ballots::tally(room_ballots.iter())
+    }
+
+    pub fn all_ballots(&self) -> Vec<Ballot> {
+        self.async_ballots.iter().map(|ballot| ballot.clone()).collect()
+    }
+
+    /// How estimates compared with the actual effort, per issue type
+    pub fn estimation_accuracy(&self) -> Vec<IssueTypeAccuracy> {
+        let rooms: Vec<_> = self.rooms.iter().collect();
+        let estimates = accuracy::final_estimates(rooms.iter().map(|room| room.value()));
+        drop(rooms);
+        let actuals: Vec<TicketActual> = self.ticket_actuals.iter().map(|actual| actual.clone()).collect();
+        accuracy::accuracy_report(&estimates, &actuals)
+    }
+
+    /// Past and running sessions matching a ticket key, room name or participant
+    pub fn search_sessions(&self, query: &str, range: DateRange) -> Vec<SessionResult> {
+        let rooms: Vec<_> = self.rooms.iter().collect();
+        session_search::search_sessions(rooms.iter().map(|room| room.value()), query, range)
+    }
+
+    pub fn get_participant_stats(&self, room_id: &str) -> Option<Vec<ParticipantStatsSummary>> {
+        self.rooms.get(room_id).map(|room| room.get_participant_stats())
+    }
+
     pub fn reset_votes(&self, room_id: &str) {
         if let Some(mut room) = self.rooms.get_mut(room_id) {
-            room.reset_votes();
+            room.restart_round();
         }
     }
 
+    /// Cast (or with None, take back) the host's own vote, for rooms the host votes in
+    pub fn set_host_vote(&self, room_id: &str, vote: Option<String>) -> Result<(), ClientError> {
+        let host_id = self
+            .get_room(room_id)
+            .ok_or_else(|| ClientError::new(ErrorCode::RoomNotFound, "Room not found"))?
+            .host_participant()
+            .map(|host| host.id.clone())
+            .ok_or_else(|| ClientError::new(ErrorCode::Other, "The host doesn't vote in this room"))?;
+        self.set_vote(room_id, &host_id, vote)
+    }
+
+    pub fn get_voting_rounds(&self, room_id: &str) -> Option<Vec<VotingRound>> {
+        self.rooms.get(room_id).map(|room| room.voting_rounds())
+    }
+
+    /// Send a message to one participant, if they're connected to the given room.
+    /// Returns false if the participant isn't connected there.
+    pub fn send_to_participant(&self, room_id: &str, participant_id: &str, message: WsMessage) -> bool {
+        self.connections
+            .get(room_id)
+            .and_then(|connections| connections.get(participant_id).map(|sender| sender.send(message).is_ok()))
+            .unwrap_or(false)
+    }
+
     pub fn register_connection(
         &self,
         participant_id: String,
         room_id: String,
-        sender: mpsc::UnboundedSender<WsMessage>,
+        sender: ClientSender,
     ) {
-        self.connections.insert(
-            participant_id.clone(),
-            Connection {
-                participant_id,
-                room_id,
-                sender,
-            },
-        );
+        let previous = self.connections.entry(room_id).or_default().insert(participant_id, sender);
+        // A participant lives on one connection; one they've rejoined from replaces the old
+        if let Some(previous) = previous {
+            previous.close();
+        }
+    }
+
+    pub fn unregister_connection(&self, room_id: &str, participant_id: &str) {
+        if let Some(mut connections) = self.connections.get_mut(room_id) {
+            connections.remove(participant_id);
+        }
+        self.connections.remove_if(room_id, |_, connections| connections.is_empty());
+    }
+
+    /// Whether a participant is connected to this host in a room
+    pub fn is_connected(&self, room_id: &str, participant_id: &str) -> bool {
+        self.connections
+            .get(room_id)
+            .is_some_and(|connections| connections.contains_key(participant_id))
+    }
+
+    /// Log everything sent to and received from a participant, or stop doing so
+    pub fn trace_participant(&self, participant_id: &str, enabled: bool) {
+        if enabled {
+            self.traced_participants.insert(participant_id.to_string());
+        } else {
+            self.traced_participants.remove(participant_id);
+        }
+    }
+
+    pub fn is_participant_traced(&self, participant_id: &str) -> bool {
+        self.traced_participants.contains(participant_id)
     }
 
-    pub fn unregister_connection(&self, participant_id: &str) {
-        self.connections.remove(participant_id);
+    /// Number of participants connected to this host in a room
+    pub fn connection_count(&self, room_id: &str) -> usize {
+        self.connections.get(room_id).map_or(0, |connections| connections.len())
     }
 
-    /// Broadcast a room update to all connected clients in that room
+    /// Send the room's state to everyone in it. Every broadcast change is also journaled.
     pub async fn broadcast_room_update(&self, room_id: &str) {
+        if let Some(mut room) = self.rooms.get_mut(room_id) {
+            room.revision += 1;
+            room.can_reveal = room.quorum_met();
+            let was_overcommitted = room.sprint_fill.as_ref().is_some_and(|fill| fill.overcommitted);
+            room.sprint_fill = room.sprint_capacity.map(|points| capacity::sprint_fill(points, &room));
+            if let Some(fill) = room.sprint_fill.as_ref().filter(|fill| fill.overcommitted && !was_overcommitted) {
+                tracing::warn!(
+                    "Room {} is overcommitted: {} of {} points estimated",
+                    room_id,
+                    fill.committed,
+                    fill.capacity
+                );
+            }
+        }
         if let Some(room) = self.get_room(room_id) {
-            tracing::info!(
-                "Broadcasting room update for room_id={}, has_ticket={}, connections={}",
-                room_id,
-                room.current_ticket.is_some(),
-                self.connections.iter().filter(|c| c.room_id == room_id).count()
-            );
-            let message = WsMessage::RoomUpdate { room };
-            
-            for conn in self.connections.iter() {
-                if conn.room_id == room_id {
-                    let _ = conn.sender.send(message.clone());
+            self.journal(JournalEvent::RoomSaved { room: Box::new(room.clone()) });
+            self.send_room_update(&room);
+        }
+    }
+
+    /// Hear about every room update and deletion from now on. A watcher that
+    /// falls behind misses updates, and can catch up from the next one.
+    #[cfg(feature = "grpc")]
+    pub fn watch_rooms(&self) -> tokio::sync::broadcast::Receiver<RoomEvent> {
+        self.room_events.subscribe()
+    }
+
+    /// Send a room's state to the participants connected to this host
+    fn send_room_update(&self, room: &Room) {
+        let room_id = room.id.as_str();
+        if self.room_events.receiver_count() > 0 {
+            let _ = self.room_events.send(RoomEvent::Updated(Box::new(room.clone())));
+        }
+        tracing::info!(
+            room_id,
+            revision = room.revision,
+            has_ticket = room.current_ticket.is_some(),
+            connections = self.connection_count(room_id),
+            "Broadcasting room update"
+        );
+        let previous_hash = self.sent_ticket_hashes.insert(room_id.to_string(), room.ticket_hash.clone());
+        let ticket_changed = previous_hash.flatten() != room.ticket_hash;
+        // Everyone gets the same update unless who has voted is hidden, or
+        // someone has raised a signal that only they (and the host) may see
+        let per_viewer = room.voters_hidden() || !room.signals.is_empty();
+        let shared = (!per_viewer).then(|| room.update_message(""));
+
+        if let Some(connections) = self.connections.get(room_id) {
+            for (participant_id, sender) in connections.iter() {
+                if self.is_participant_traced(participant_id) {
+                    tracing::info!(
+                        participant = %participant_id,
+                        revision = room.revision,
+                        ticket_changed,
+                        "Sending room update to traced participant"
+                    );
+                }
+                if ticket_changed {
+                    let _ = sender.send(room.ticket_changed_message());
                 }
+                let message = shared.clone().unwrap_or_else(|| room.update_message(participant_id));
+                let _ = sender.send(message);
             }
         }
     }
@@ -261,6 +1592,14 @@ impl AppState {
         self.public_ip.read().unwrap().clone()
     }
 
+    pub fn set_ipv6_route(&self, route: Option<Ipv6Route>) {
+        *self.ipv6_route.write().unwrap() = route;
+    }
+
+    pub fn get_ipv6_route(&self) -> Option<Ipv6Route> {
+        self.ipv6_route.read().unwrap().clone()
+    }
+
     pub fn get_share_url(&self) -> String {
         let port = *self.server_port.read().unwrap();
         
@@ -270,6 +1609,11 @@ impl AppState {
                 return format!("http://{}:{}", public_ip, port);
             }
         }
+
+        // Next best is an IPv6 address (native or tunnelled) that answered the reachability test
+        if let Some(route) = self.get_ipv6_route().filter(|route| route.reachable) {
+            return route.url(port);
+        }
         
         // Otherwise use local IP
         self.get_server_url()
@@ -292,3 +1636,407 @@ impl AppState {
         }
     }
 }
+
+/// How often rooms are checked for participants whose grace period ran out
+const DISCONNECT_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Periodically remove participants who didn't reconnect in time
+pub async fn expire_disconnected_participants(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(DISCONNECT_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        for room_id in state.expire_disconnected_participants() {
+            state.broadcast_room_update(&room_id).await;
+        }
+    }
+}
+
+/// Time between cards in a timed one-by-one reveal
+const TIMED_REVEAL_STEP: Duration = Duration::from_millis(1500);
+
+/// Drive timed reveals, showing one more card in each every step
+pub async fn advance_timed_reveals(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(TIMED_REVEAL_STEP);
+    loop {
+        interval.tick().await;
+        for room_id in state.timed_reveals() {
+            state.reveal_next_vote(&room_id).await;
+        }
+    }
+}
+
+/// How often rooms are checked for tickets past their deadline
+const DEADLINE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Periodically park tickets that ran past their deadline and show the next one
+pub async fn enforce_ticket_deadlines(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(DEADLINE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        for (room_id, ticket) in state.park_overdue_tickets() {
+            tracing::info!("Parked {} in room {} after it ran past its deadline", ticket.key, room_id);
+            state.broadcast_room_update(&room_id).await;
+            if let Some(relay_client) = state.get_relay_client().await {
+                let next = state.get_room(&room_id).and_then(|room| Some((room.current_ticket?, room.deck)));
+                let _ = match next {
+                    Some((next, deck)) => relay_client.set_ticket(room_id, next, deck),
+                    None => relay_client.clear_ticket(room_id),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::REVEAL_DELAY_MS;
+    use crate::estimate_rules::{EstimateRule, RuleAction};
+    use crate::room::{ErrorCode, JiraTicket, RecordedVote, TicketSignal, WsMessage};
+    use crate::seats::SeatScope;
+    use crate::session_search::DateRange;
+    use crate::settings::AppSettings;
+    use crate::test_support::{participant, test_state, START_TIME};
+    use std::collections::HashMap;
+
+    #[test]
+    fn created_rooms_are_deterministic() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+
+        assert_eq!(room.id, "id-1");
+        assert_eq!(room.created_at, START_TIME);
+        assert_eq!(state.get_room_by_invite(&room.invite_code).map(|r| r.id), Some(room.id));
+    }
+
+    #[test]
+    fn rejected_jira_credentials_hold_requests_until_replaced() {
+        use super::PendingJiraRequest;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let (state, _env) = test_state();
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let counter = prompts.clone();
+        state.set_jira_auth_callback(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        state.set_jira_config("https://acme.atlassian.net".into(), "host@acme.test".into(), "token".into());
+
+        let fetch = PendingJiraRequest::FetchTicket { room_id: "room".into(), ticket_key: "POKER-1".into() };
+        state.reject_jira_config(Some(fetch.clone()));
+        state.reject_jira_config(Some(fetch.clone()));
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        assert!(!state.has_jira_config());
+        assert!(state.take_pending_jira_requests().is_empty());
+
+        state.set_jira_config("https://acme.atlassian.net".into(), "host@acme.test".into(), "new-token".into());
+        assert!(state.has_jira_config());
+        assert_eq!(state.take_pending_jira_requests(), vec![fetch]);
+    }
+
+    #[tokio::test]
+    async fn switching_profiles_forgets_the_previous_profile() {
+        use super::PendingJiraRequest;
+        use crate::credentials::LlmCredentials;
+
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        let controller = state.controller_token(&room.id).unwrap();
+        state.set_jira_config("https://acme.atlassian.net".into(), "host@acme.test".into(), "token".into());
+        let fetch = PendingJiraRequest::FetchTicket { room_id: "room".into(), ticket_key: "POKER-1".into() };
+        state.reject_jira_config(Some(fetch));
+        state.set_llm_credentials(Some(LlmCredentials {
+            endpoint: "https://api.openai.com/v1".into(),
+            model: "gpt-4o-mini".into(),
+            api_key: "sk-old".into(),
+        }));
+        state.jira_user_names.insert("5b10a2844c20165700ede21g".into(), "Alice Example".into());
+        let rule = EstimateRule { threshold: 13.0, action: RuleAction::Flag };
+        state.estimate_rule_hits.lock().unwrap().extend(rule.check("room", "POKER-1", 21.0));
+
+        state.clear_profile_data().await;
+
+        state.set_jira_config("https://other.atlassian.net".into(), "host@other.test".into(), "token".into());
+        assert!(state.take_pending_jira_requests().is_empty());
+        assert!(state.llm_credentials().is_none());
+        assert!(state.take_estimate_rule_hits().is_empty());
+        assert!(state.jira_user_names.is_empty());
+        assert!(state.controller_tokens.get(&controller).is_none());
+    }
+
+    #[test]
+    fn nobody_votes_until_the_discussion_and_countdown_are_over() {
+        let (state, env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+
+        state.set_discussion_phase(&room.id, true, 0).unwrap();
+        let error = state.set_vote(&room.id, "alice", Some("5".into())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::VotingClosed);
+
+        state.set_discussion_phase(&room.id, false, 10).unwrap();
+        assert!(state.set_vote(&room.id, "alice", Some("5".into())).is_err());
+        env.advance(10);
+        assert!(state.set_vote(&room.id, "alice", Some("5".into())).is_ok());
+        assert!(state.set_discussion_phase(&room.id, false, 600).is_err());
+    }
+
+    #[test]
+    fn votes_must_come_from_the_deck() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+
+        assert!(state.set_vote(&room.id, "alice", Some("5".into())).is_ok());
+        let error = state.set_vote(&room.id, "alice", Some("7".into())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidVote);
+    }
+
+    #[test]
+    fn targeted_messages_only_reach_the_participant_in_that_room() {
+        let (state, _env) = test_state();
+        let (tx, mut rx) = crate::outbox::channel();
+        state.register_connection("alice".into(), "room-1".into(), tx);
+
+        assert!(state.send_to_participant("room-1", "alice", WsMessage::Pong));
+        assert!(!state.send_to_participant("room-2", "alice", WsMessage::Pong));
+        assert!(!state.send_to_participant("room-1", "bob", WsMessage::Pong));
+
+        assert!(matches!(rx.try_recv(), Some(WsMessage::Pong)));
+        assert!(rx.try_recv().is_none());
+    }
+
+    #[test]
+    fn raised_signals_reach_whoever_raised_them() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+        state.add_participant(&room.id, participant("bob", "Bob"));
+        let (alice_tx, mut alice_rx) = crate::outbox::channel();
+        let (bob_tx, mut bob_rx) = crate::outbox::channel();
+        state.register_connection("alice".into(), room.id.clone(), alice_tx);
+        state.register_connection("bob".into(), room.id.clone(), bob_tx);
+
+        state.set_signal(&room.id, "alice", TicketSignal::NeedsDesign, true).unwrap();
+        state.send_room_update(&state.get_room(&room.id).unwrap());
+
+        let signals = |rx: &mut crate::outbox::ClientReceiver| loop {
+            match rx.try_recv() {
+                Some(WsMessage::RoomUpdate { room, .. }) => return room.signals,
+                Some(_) => continue,
+                None => panic!("no room update"),
+            }
+        };
+        assert_eq!(signals(&mut alice_rx).get("alice"), Some(&vec![TicketSignal::NeedsDesign]));
+        assert!(signals(&mut bob_rx).is_empty());
+    }
+
+    #[test]
+    fn ended_sessions_reject_joins_and_votes() {
+        let (state, env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+
+        env.advance(90);
+        let summary = state.end_session(&room.id).unwrap();
+        assert_eq!(summary.duration_secs, 90);
+
+        assert!(state.add_participant(&room.id, participant("bob", "Bob")).is_none());
+        let error = state.set_vote(&room.id, "alice", Some("5".into())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::SessionEnded);
+    }
+
+    #[test]
+    fn locked_rooms_only_admit_returning_participants() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        let alice = participant("alice", "Alice").with_identity(Some("alice-token".into()));
+        state.add_participant(&room.id, alice.clone());
+        state.remove_participant(&room.id, "alice");
+
+        assert!(state.set_room_locked(&room.id, true));
+        assert!(state.add_participant(&room.id, participant("bob", "Bob")).is_none());
+        assert!(state.add_participant(&room.id, alice).is_some());
+
+        state.set_room_locked(&room.id, false);
+        assert!(state.add_participant(&room.id, participant("bob", "Bob")).is_some());
+    }
+
+    #[test]
+    fn seats_are_counted_across_rooms_and_kept_by_returning_participants() {
+        let (state, _env) = test_state();
+        state.set_settings(AppSettings { max_participants_total: Some(2), ..Default::default() });
+        let squad = state.create_room("Squad".to_string(), None, None).unwrap();
+        let other = state.create_room("Other".to_string(), None, None).unwrap();
+        let alice = participant("alice", "Alice").with_identity(Some("alice-token".into()));
+        state.add_participant(&squad.id, alice.clone());
+        state.add_participant(&squad.id, participant("bob", "Bob"));
+
+        let denied = state.check_seat(&other.id, None).unwrap_err();
+        assert_eq!((denied.scope, denied.limit), (SeatScope::Global, Some(2)));
+        assert!(state.add_participant(&other.id, participant("carol", "Carol")).is_none());
+        assert!(state.add_participant(&squad.id, alice).is_some());
+
+        let usage = state.get_usage();
+        assert_eq!(usage.total_seats, 2);
+        assert_eq!(usage.rooms.iter().map(|r| r.seats).collect::<Vec<_>>(), [0, 2]);
+    }
+
+    #[test]
+    fn hosts_can_vote_in_rooms_they_take_part_in() {
+        let (state, _env) = test_state();
+        let watched = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        assert!(state.set_host_vote(&watched.id, Some("5".into())).is_err());
+
+        let room = state.create_room("Sprint 43".to_string(), None, Some(" Dana ".into())).unwrap();
+        state.set_host_vote(&room.id, Some("5".into())).unwrap();
+        let host = state.get_room(&room.id).unwrap().host_participant().cloned().unwrap();
+        assert_eq!((host.name.as_str(), host.vote.as_deref()), ("Dana", Some("5")));
+
+        state.set_host_vote(&room.id, None).unwrap();
+        assert!(state.get_room(&room.id).unwrap().host_participant().unwrap().vote.is_none());
+    }
+
+    #[test]
+    fn observers_watch_without_being_waited_on() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+        state.add_participant(&room.id, participant("po", "Pat").observing(true));
+
+        let error = state.set_vote(&room.id, "po", Some("5".into())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidVote);
+        assert!(state.delegate_vote(&room.id, "alice", Some("po")).is_err());
+
+        state.set_vote(&room.id, "alice", Some("5".into())).unwrap();
+        let room = state.get_room(&room.id).unwrap();
+        assert!(room.quorum_met());
+        assert_eq!(room.get_vote_summary().total_voters, 1);
+    }
+
+    #[test]
+    fn recovered_participants_keep_their_votes_when_they_rejoin() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice").with_identity(Some("alice-token".into())));
+        state.set_vote(&room.id, "alice", Some("8".into())).unwrap();
+        let saved = state.get_rooms();
+        let identities = HashMap::from([("alice".to_string(), "alice-token".to_string())]);
+
+        let (state, _env) = test_state();
+        state.recover_session(saved, &identities);
+        let recovered = state.get_room(&room.id).unwrap();
+        assert!(recovered.participants[0].disconnected_at.is_some());
+
+        let rejoining = participant("someone-new", "Alice").with_identity(Some("alice-token".into()));
+        assert_eq!(state.add_participant(&room.id, rejoining).as_deref(), Some("alice"));
+        assert_eq!(state.get_room(&room.id).unwrap().participants[0].vote.as_deref(), Some("8"));
+    }
+
+    #[test]
+    fn sessions_can_be_found_by_ticket_with_what_people_voted() {
+        let (state, env) = test_state();
+        let squad = state.create_room("Squad refinement".to_string(), None, None).unwrap();
+        state.add_participant(&squad.id, participant("alice", "Alice"));
+        state.set_current_ticket(&squad.id, Some(JiraTicket { key: "PROJ-123".into(), ..Default::default() }));
+        state.set_vote(&squad.id, "alice", Some("5".into())).unwrap();
+        state.set_votes_revealed(&squad.id, true);
+        state.end_session(&squad.id);
+
+        env.advance(7 * 24 * 60 * 60);
+        state.create_room("Next sprint".to_string(), None, None).unwrap();
+
+        let found = state.search_sessions("proj-123", DateRange::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].room_name, "Squad refinement");
+        assert_eq!(found[0].rounds[0].votes, [RecordedVote { name: "Alice".into(), vote: "5".into(), on_behalf_of: vec![] }]);
+
+        assert_eq!(state.search_sessions("", DateRange::default())[0].room_name, "Next sprint");
+        let last_week = DateRange { from: None, to: Some(START_TIME + 60) };
+        assert_eq!(state.search_sessions("", last_week).len(), 1);
+    }
+
+    #[test]
+    fn reveals_are_scheduled_just_ahead_of_the_broadcast() {
+        let (state, env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+
+        env.advance(30);
+        state.set_votes_revealed(&room.id, true);
+        let reveal_at = state.get_room(&room.id).unwrap().reveal_at;
+        assert_eq!(reveal_at, Some((START_TIME + 30) * 1000 + REVEAL_DELAY_MS));
+
+        state.reset_votes(&room.id);
+        assert_eq!(state.get_room(&room.id).unwrap().reveal_at, None);
+    }
+
+    #[test]
+    fn large_estimates_queue_the_rooms_rule() {
+        let (state, _env) = test_state();
+        let room = state.create_room("Sprint 42".to_string(), None, None).unwrap();
+        let rule = EstimateRule { threshold: 13.0, action: RuleAction::Flag };
+        state.set_room_estimate_rule(&room.id, Some(rule)).unwrap();
+        state.add_participant(&room.id, participant("alice", "Alice"));
+        state.set_current_ticket(&room.id, Some(JiraTicket { key: "PROJ-7".into(), ..Default::default() }));
+
+        state.set_vote(&room.id, "alice", Some("8".into())).unwrap();
+        state.set_votes_revealed(&room.id, true);
+        assert!(state.take_estimate_rule_hits().is_empty());
+
+        state.reset_votes(&room.id);
+        state.set_vote(&room.id, "alice", Some("20".into())).unwrap();
+        state.set_votes_revealed(&room.id, true);
+        let hits = state.take_estimate_rule_hits();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].ticket_key.as_str(), hits[0].estimate), ("PROJ-7", 20.0));
+    }
+
+    #[test]
+    fn rooms_changed_by_another_instance_update_the_copy_here() {
+        use crate::storage::{encode_room, SharedChange};
+
+        let (here, _env) = test_state();
+        let (there, _env) = test_state();
+        let room = there.create_room("Sprint 42".to_string(), None, None).unwrap();
+        there.add_participant(&room.id, participant("alice", "Alice"));
+        there.set_vote(&room.id, "alice", Some("5".into())).unwrap();
+
+        let text = encode_room(&there.get_room(&room.id).unwrap(), None).unwrap();
+        here.apply_shared_change(SharedChange::Saved { room_id: room.id.clone(), text });
+        let copy = here.get_room_by_invite(&room.invite_code).unwrap();
+        // Alice is connected to the other instance, so stays connected
+        assert_eq!(copy.participants.len(), 1);
+        assert_eq!(copy.participants[0].disconnected_at, None);
+        assert_eq!(copy.participants[0].vote.as_deref(), Some("5"));
+
+        here.apply_shared_change(SharedChange::Deleted { room_id: room.id.clone() });
+        assert!(here.get_room(&room.id).is_none());
+        assert!(here.get_room_by_invite(&room.invite_code).is_none());
+    }
+
+    #[test]
+    fn shared_changes_keep_votes_here_that_are_not_stored_yet() {
+        use crate::storage::{encode_room, SharedChange};
+
+        let (here, env) = test_state();
+        let room = here.create_room("Sprint 42".to_string(), None, None).unwrap();
+        here.add_participant(&room.id, participant("alice", "Alice"));
+        here.add_participant(&room.id, participant("bob", "Bob"));
+        let (tx, _rx) = crate::outbox::channel();
+        here.register_connection("alice".into(), room.id.clone(), tx);
+        let mut there = here.get_room(&room.id).unwrap();
+
+        // Alice votes here while another instance stores bob's vote and a lock
+        env.advance(1);
+        here.set_vote(&room.id, "alice", Some("3".into())).unwrap();
+        there.set_vote("bob", Some("5".into()));
+        there.locked = true;
+        let text = encode_room(&there, None).unwrap();
+        here.apply_shared_change(SharedChange::Saved { room_id: room.id.clone(), text });
+
+        let copy = here.get_room(&room.id).unwrap();
+        let votes: Vec<_> = copy.participants.iter().map(|p| (p.id.as_str(), p.vote.as_deref())).collect();
+        assert_eq!(votes, [("alice", Some("3")), ("bob", Some("5"))]);
+        assert!(copy.locked);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::credentials::{get_data_dir, MasterKey};
+use crate::migrations::{self, FileReport, FileStatus, ROOM_MIGRATIONS};
+use crate::persistence::{self, Journal, JournalEvent};
+use crate::room::Room;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+const ROOM_FILES_DIR: &str = "room-files";
+#[cfg(feature = "sqlite")]
+const SQLITE_FILE: &str = "rooms.sqlite3";
+
+/// Where rooms, with their session history, are kept between runs. The file
+/// backends leave the settings in settings.json; the database backends keep
+/// them too, and settings.json then only records which backend is in use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// A snapshot plus an append-only journal of changes
+    #[default]
+    Journal,
+    /// One JSON file per room, rewritten on each change. Easy to back up or
+    /// to keep in a folder shared with other machines.
+    RoomFiles,
+    /// Rooms and settings in a SQLite database in the data directory.
+    /// Needs a build with the `sqlite` feature.
+    Sqlite,
+    /// Rooms and settings in a PostgreSQL database shared by several instances,
+    /// found through `SCRUM_POKER_DATABASE_URL`. Needs a build with the `postgres` feature.
+    Postgres,
+}
+
+/// A room another instance sharing the storage changed
+#[derive(Debug)]
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))] // Only the postgres backend is shared
+pub enum SharedChange {
+    /// Saved, as encoded text
+    Saved { room_id: String, text: String },
+    Deleted { room_id: String },
+}
+
+/// Somewhere room changes are persisted to. Callers never wait on disk.
+pub trait Storage: Send + Sync {
+    fn record(&self, event: JournalEvent);
+    /// Encrypt (or stop encrypting) the stored rooms from now on
+    fn set_key(&self, key: Option<MasterKey>);
+    /// Send the changes other instances sharing this storage make to `changes`.
+    /// Only shared backends have any.
+    fn subscribe(&self, _changes: tokio::sync::mpsc::UnboundedSender<SharedChange>) {}
+}
+
+impl Storage for Journal {
+    fn record(&self, event: JournalEvent) {
+        Journal::record(self, event);
+    }
+
+    fn set_key(&self, key: Option<MasterKey>) {
+        Journal::set_key(self, key);
+    }
+}
+
+/// Where a backend's writer thread puts each room, as encoded text
+pub(crate) trait RoomStore: Send {
+    fn put_room(&mut self, room_id: &str, text: &str) -> Result<(), String>;
+    /// Store a changed room; `previous` is how it was when this writer last
+    /// stored it. Storage shared with other instances merges it with what they
+    /// stored since; otherwise the room just replaces what's there.
+    fn save_room(&mut self, room: &Room, previous: Option<&Room>, key: Option<&MasterKey>) -> Result<(), String> {
+        let _ = previous;
+        self.put_room(&room.id, &encode_room(room, key)?)
+    }
+    fn delete_room(&mut self, room_id: &str) -> Result<(), String>;
+    /// Store exactly these rooms, dropping any others
+    fn replace_rooms(&mut self, rooms: &[(String, String)]) -> Result<(), String>;
+}
+
+/// A database backend, holding a row per room plus the settings
+pub(crate) trait Database: RoomStore {
+    /// Where the database is, for integrity reports
+    fn location(&self) -> String;
+    /// Every stored room as (room ID, encoded text)
+    fn rooms(&mut self) -> Result<Vec<(String, String)>, String>;
+    /// The stored settings document, if settings have been saved here
+    fn settings(&mut self) -> Result<Option<String>, String>;
+    fn put_settings(&mut self, text: &str) -> Result<(), String>;
+}
+
+impl StorageBackend {
+    /// Connect to the backend's database
+    fn database(self) -> Result<Box<dyn Database>, String> {
+        match self {
+            StorageBackend::Journal | StorageBackend::RoomFiles => {
+                Err(format!("The {:?} backend has no database", self))
+            }
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite => Ok(Box::new(crate::sqlite::SqliteDatabase::open(&get_data_dir()?.join(SQLITE_FILE))?)),
+            #[cfg(not(feature = "sqlite"))]
+            StorageBackend::Sqlite => Err("This build doesn't include SQLite storage (the `sqlite` feature)".into()),
+            #[cfg(feature = "postgres")]
+            StorageBackend::Postgres => {
+                Ok(Box::new(crate::postgres::PostgresDatabase::open(crate::postgres::Config::from_env()?)?))
+            }
+            #[cfg(not(feature = "postgres"))]
+            StorageBackend::Postgres => Err("This build doesn't include PostgreSQL storage (the `postgres` feature)".into()),
+        }
+    }
+
+    /// Whether other instances use the same storage at the same time
+    pub fn is_shared(self) -> bool {
+        self == StorageBackend::Postgres
+    }
+
+    /// Read back the rooms stored by a previous run
+    pub fn load(self, key: Option<&MasterKey>) -> Result<Vec<Room>, String> {
+        match self {
+            StorageBackend::Journal => persistence::recover(&persistence::persistence_dir()?, key),
+            StorageBackend::RoomFiles => load_room_files(&room_files_dir()?, key),
+            StorageBackend::Sqlite | StorageBackend::Postgres => self
+                .database()?
+                .rooms()?
+                .into_iter()
+                .map(|(room_id, text)| decode_room(&text, key).map(|(_, room)| room).map_err(|e| format!("Failed to read room {}: {}", room_id, e)))
+                .collect(),
+        }
+    }
+
+    /// Start persisting changes, beginning from `rooms`, which are written out
+    /// straight away. This is also how rooms move over to another backend.
+    pub fn open(self, rooms: Vec<Room>, key: Option<MasterKey>) -> Result<Box<dyn Storage>, String> {
+        Ok(match self {
+            StorageBackend::Journal => Box::new(Journal::start(persistence::persistence_dir()?, rooms, key)?),
+            StorageBackend::RoomFiles => {
+                let dir = room_files_dir()?;
+                fs::create_dir_all(&dir).map_err(|e| format!("Failed to create room files dir: {}", e))?;
+                Box::new(RoomWriter::start("room-files", Box::new(RoomFiles { dir }), rooms, key)?)
+            }
+            StorageBackend::Sqlite => Box::new(RoomWriter::start("room-database", self.database()?, rooms, key)?),
+            #[cfg(feature = "postgres")]
+            StorageBackend::Postgres => {
+                Box::new(crate::postgres::SharedRooms::start(crate::postgres::Config::from_env()?, rooms, key)?)
+            }
+            // Fails, saying the build doesn't include the backend
+            #[cfg(not(feature = "postgres"))]
+            StorageBackend::Postgres => Box::new(RoomWriter::start("room-database", self.database()?, rooms, key)?),
+        })
+    }
+
+    /// Report on the stored room files, as for `check_data_integrity`
+    pub fn check_files(self, key: Option<&MasterKey>) -> Result<Vec<FileReport>, String> {
+        Ok(match self {
+            StorageBackend::Journal => persistence::check_files(&persistence::persistence_dir()?, key),
+            StorageBackend::RoomFiles => room_file_paths(&room_files_dir()?)
+                .into_iter()
+                .map(|path| match key {
+                    None if persistence::is_encrypted_file(&path) => locked_report(path.display().to_string()),
+                    _ => migrations::check_document(&path, ROOM_MIGRATIONS, |text| persistence::decode(text, key)),
+                })
+                .collect(),
+            StorageBackend::Sqlite | StorageBackend::Postgres => {
+                let mut database = self.database()?;
+                let location = database.location();
+                database
+                    .rooms()?
+                    .into_iter()
+                    .map(|(room_id, text)| {
+                        let label = format!("{} (room {})", location, room_id);
+                        match key {
+                            None if persistence::is_encrypted_text(&text) => locked_report(label),
+                            _ => migrations::check_contents(&label, &text, ROOM_MIGRATIONS, |text| persistence::decode(text, key)),
+                        }
+                    })
+                    .collect()
+            }
+        })
+    }
+
+    /// The settings document kept by a database backend, if it has one
+    pub fn load_settings(self) -> Result<Option<Value>, String> {
+        match self {
+            StorageBackend::Journal | StorageBackend::RoomFiles => Ok(None),
+            StorageBackend::Sqlite | StorageBackend::Postgres => self
+                .database()?
+                .settings()?
+                .map(|text| serde_json::from_str(&text).map_err(|e| format!("Failed to parse stored settings: {}", e)))
+                .transpose(),
+        }
+    }
+
+    /// Keep the settings document in the backend's database. Returns false for
+    /// the file backends, which leave the settings to settings.json.
+    pub fn save_settings(self, settings: &Value) -> Result<bool, String> {
+        match self {
+            StorageBackend::Journal | StorageBackend::RoomFiles => Ok(false),
+            StorageBackend::Sqlite | StorageBackend::Postgres => {
+                self.database()?.put_settings(&settings.to_string()).map(|_| true)
+            }
+        }
+    }
+}
+
+fn locked_report(path: String) -> FileReport {
+    FileReport { path, version: None, status: FileStatus::Locked, bad_entries: 0 }
+}
+
+/// A room as stored by the file and database backends: versioned, and encrypted if there is a key
+pub(crate) fn encode_room(room: &Room, key: Option<&MasterKey>) -> Result<String, String> {
+    let value = migrations::wrap_versioned(&persistence::stored_room(room)?, ROOM_MIGRATIONS)?;
+    persistence::encode(&value, key)
+}
+
+/// Read a room written by `encode_room`, along with the schema version it was stored in
+pub(crate) fn decode_room(text: &str, key: Option<&MasterKey>) -> Result<(u32, Room), String> {
+    let (version, data) = migrations::unwrap_versioned(persistence::decode(text, key)?);
+    Ok((version, persistence::parse_room(data, version)?))
+}
+
+fn room_files_dir() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(ROOM_FILES_DIR))
+}
+
+fn room_file_paths(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn load_room_files(dir: &Path, key: Option<&MasterKey>) -> Result<Vec<Room>, String> {
+    let current = migrations::current_version(ROOM_MIGRATIONS);
+    room_file_paths(dir)
+        .into_iter()
+        .map(|path| {
+            let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let (version, room) = decode_room(&text, key).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if version < current {
+                migrations::backup_before_migration(&path, version)?;
+            }
+            Ok(room)
+        })
+        .collect()
+}
+
+enum WriterCommand {
+    Record(JournalEvent),
+    SetKey(Option<MasterKey>),
+}
+
+/// Writes each changed room to a `RoomStore` from a background thread
+pub(crate) struct RoomWriter {
+    tx: mpsc::Sender<WriterCommand>,
+}
+
+impl RoomWriter {
+    pub(crate) fn start(name: &str, mut store: Box<dyn RoomStore>, rooms: Vec<Room>, key: Option<MasterKey>) -> Result<Self, String> {
+        let mut rooms: HashMap<String, Room> = rooms.into_iter().map(|room| (room.id.clone(), room)).collect();
+        // Rooms that are gone would otherwise come back on the next start
+        store.replace_rooms(&encode_rooms(&rooms, key.as_ref())?)?;
+
+        let (tx, rx) = mpsc::channel();
+        let thread_name = name.to_string();
+        std::thread::Builder::new()
+            .name(thread_name.clone())
+            .spawn(move || {
+                let mut key = key;
+                for command in rx {
+                    let result = match command {
+                        WriterCommand::Record(JournalEvent::RoomSaved { room }) => {
+                            let result = store.save_room(&room, rooms.get(&room.id), key.as_ref());
+                            rooms.insert(room.id.clone(), *room);
+                            result
+                        }
+                        WriterCommand::Record(JournalEvent::RoomDeleted { room_id }) => {
+                            rooms.remove(&room_id);
+                            store.delete_room(&room_id)
+                        }
+                        WriterCommand::SetKey(new_key) => {
+                            key = new_key;
+                            encode_rooms(&rooms, key.as_ref()).and_then(|encoded| store.replace_rooms(&encoded))
+                        }
+                    };
+                    if let Err(e) = result {
+                        tracing::error!("Failed to update {}: {}", thread_name, e);
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start {} writer: {}", name, e))?;
+
+        Ok(Self { tx })
+    }
+
+    fn send(&self, command: WriterCommand) {
+        if self.tx.send(command).is_err() {
+            tracing::warn!("Room writer has stopped; change not persisted");
+        }
+    }
+}
+
+impl Storage for RoomWriter {
+    fn record(&self, event: JournalEvent) {
+        self.send(WriterCommand::Record(event));
+    }
+
+    fn set_key(&self, key: Option<MasterKey>) {
+        self.send(WriterCommand::SetKey(key));
+    }
+}
+
+fn encode_rooms(rooms: &HashMap<String, Room>, key: Option<&MasterKey>) -> Result<Vec<(String, String)>, String> {
+    rooms
+        .values()
+        .map(|room| Ok((room.id.clone(), encode_room(room, key)?)))
+        .collect()
+}
+
+/// One file per room
+struct RoomFiles {
+    dir: PathBuf,
+}
+
+impl RoomStore for RoomFiles {
+    /// Replace the room's file atomically, so a crash mid-write keeps the old one
+    fn put_room(&mut self, room_id: &str, text: &str) -> Result<(), String> {
+        let path = room_file_path(&self.dir, room_id);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, text).map_err(|e| format!("Failed to write room file: {}", e))?;
+        fs::rename(&partial, &path).map_err(|e| format!("Failed to write room file: {}", e))
+    }
+
+    fn delete_room(&mut self, room_id: &str) -> Result<(), String> {
+        fs::remove_file(room_file_path(&self.dir, room_id))
+            .or_else(|e| if e.kind() == std::io::ErrorKind::NotFound { Ok(()) } else { Err(e) })
+            .map_err(|e| format!("Failed to remove room file: {}", e))
+    }
+
+    fn replace_rooms(&mut self, rooms: &[(String, String)]) -> Result<(), String> {
+        for path in room_file_paths(&self.dir) {
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            if !rooms.iter().any(|(room_id, _)| room_id == stem) {
+                fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+        }
+        rooms.iter().try_for_each(|(room_id, text)| self.put_room(room_id, text))
+    }
+}
+
+fn room_file_path(dir: &Path, room_id: &str) -> PathBuf {
+    dir.join(format!("{}.json", room_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{room_with, FakeEnvironment};
+
+    #[test]
+    fn room_files_round_trip_and_drop_deleted_rooms() {
+        let env = FakeEnvironment::new();
+        let room = room_with(&env, &["alice"]);
+        let dir = std::env::temp_dir().join(format!("scrum-poker-room-files-{}", room.id));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(room_file_path(&dir, "deleted-room"), "{}").unwrap();
+
+        // Starting from the live rooms clears out the files of deleted ones
+        let files = RoomWriter::start("room-files", Box::new(RoomFiles { dir: dir.clone() }), vec![room.clone()], None);
+        let loaded = load_room_files(&dir, None);
+        drop(files);
+        let _ = fs::remove_dir_all(&dir);
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!((loaded[0].id.as_str(), loaded[0].participants.len()), (room.id.as_str(), 1));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn database_rows_round_trip_and_drop_deleted_rooms() {
+        let env = FakeEnvironment::new();
+        let room = room_with(&env, &["alice", "bob"]);
+        let path = std::env::temp_dir().join(format!("scrum-poker-rooms-{}.sqlite3", room.id));
+        let mut database = crate::sqlite::SqliteDatabase::open(&path).unwrap();
+        database.put_room("deleted-room", "{}").unwrap();
+
+        let writer = RoomWriter::start("room-database", Box::new(database), vec![room.clone()], None);
+        let rows = crate::sqlite::SqliteDatabase::open(&path).and_then(|mut database| database.rooms());
+        drop(writer);
+        let _ = fs::remove_file(&path);
+
+        let rows = rows.unwrap();
+        assert_eq!(rows.len(), 1);
+        let (_, loaded) = decode_room(&rows[0].1, None).unwrap();
+        assert_eq!((loaded.id.as_str(), loaded.participants.len()), (room.id.as_str(), 2));
+    }
+}
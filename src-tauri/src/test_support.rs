@@ -0,0 +1,66 @@
+//! Shared fixtures for unit tests: a controllable clock, predictable IDs and
+//! helpers for setting up rooms and state.
+
+use crate::room::{Participant, Room};
+use crate::state::{AppState, EnvHandle, Environment};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Arbitrary fixed start time (2024-01-01T00:00:00Z)
+pub const START_TIME: u64 = 1_704_067_200;
+
+/// Clock that only moves when told to, and IDs that count up ("id-1", "id-2", ...)
+pub struct FakeEnvironment {
+    now: AtomicU64,
+    next_id: AtomicU64,
+}
+
+impl FakeEnvironment {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            now: AtomicU64::new(START_TIME),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, secs: u64) {
+        self.now.fetch_add(secs, Ordering::SeqCst);
+    }
+
+    pub fn handle(self: &Arc<Self>) -> EnvHandle {
+        EnvHandle(self.clone())
+    }
+}
+
+impl Environment for FakeEnvironment {
+    fn now_secs(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    fn new_id(&self) -> String {
+        format!("id-{}", self.next_id.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+/// App state using a fake environment
+pub fn test_state() -> (AppState, Arc<FakeEnvironment>) {
+    let env = FakeEnvironment::new();
+    (AppState::with_env(env.handle()), env)
+}
+
+/// A participant with a fixed ID, so tests can refer to them by name
+pub fn participant(id: &str, name: &str) -> Participant {
+    let mut participant = Participant::new(name.to_string(), false);
+    participant.id = id.to_string();
+    participant
+}
+
+/// A room with one participant per name; each participant's ID is their name
+pub fn room_with(env: &Arc<FakeEnvironment>, names: &[&str]) -> Room {
+    let mut room = Room::new("Refinement".to_string(), None, env.handle());
+    for name in names {
+        room.add_participant(participant(name, name));
+    }
+    room
+}
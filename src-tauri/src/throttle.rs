@@ -0,0 +1,40 @@
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Join attempts allowed per client address within `JOIN_WINDOW`
+const MAX_JOINS_PER_WINDOW: usize = 10;
+const JOIN_WINDOW: Duration = Duration::from_secs(60);
+
+/// Sliding-window limit on join attempts per client address
+#[derive(Default)]
+pub struct JoinThrottle {
+    attempts: DashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl JoinThrottle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a join attempt, returning false if the address is over its limit
+    pub fn try_join(&self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut attempts = self.attempts.entry(ip.to_canonical()).or_default();
+        while attempts.front().is_some_and(|t| now.duration_since(*t) > JOIN_WINDOW) {
+            attempts.pop_front();
+        }
+
+        if attempts.len() >= MAX_JOINS_PER_WINDOW {
+            return false;
+        }
+        attempts.push_back(now);
+        drop(attempts);
+
+        // Forget addresses that have gone quiet so the map doesn't grow forever
+        self.attempts
+            .retain(|_, attempts| attempts.back().is_some_and(|t| now.duration_since(*t) <= JOIN_WINDOW));
+        true
+    }
+}
@@ -0,0 +1,449 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Offset, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Longest a time zone name may be
+const MAX_NAME_LENGTH: usize = 64;
+/// Where the system keeps its time zone database (tzdata), unless `TZDIR` says otherwise
+const ZONEINFO_DIRS: [&str; 3] = ["/usr/share/zoneinfo", "/usr/lib/zoneinfo", "/usr/share/lib/zoneinfo"];
+/// Current rules of every zone, for machines without a time zone database (Windows)
+const BUNDLED_RULES: &str = include_str!("tz_rules.txt");
+/// POSIX TZ rules change clocks at 02:00 local time unless they say otherwise
+const DEFAULT_TRANSITION_SECS: i64 = 2 * 60 * 60;
+
+/// Time zone a room's times are shown in, so a distributed team reads the
+/// facilitator's local times instead of raw UTC seconds. Only the IANA name is
+/// stored; each timestamp is looked up in the system's time zone database (or
+/// the bundled rules where there is none), so times on either side of a DST
+/// change get the offset in force at the time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomTimeZone {
+    /// IANA name, e.g. "Europe/Berlin"
+    pub name: String,
+}
+
+impl Default for RoomTimeZone {
+    /// The host machine's time zone
+    fn default() -> Self {
+        let name = iana_time_zone::get_timezone()
+            .ok()
+            .filter(|name| ZoneRules::load(name).is_ok())
+            .unwrap_or_else(|| "UTC".to_string());
+        Self { name }
+    }
+}
+
+impl RoomTimeZone {
+    /// A zone from its IANA name; names the time zone database doesn't know are rejected
+    pub fn new(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+            return Err("Pick a time zone".into());
+        }
+        ZoneRules::load(name)?;
+        Ok(Self { name: name.to_string() })
+    }
+
+    /// A Unix timestamp as local date and time, e.g. "2024-03-05 14:30 Europe/Berlin"
+    pub fn format(&self, unix_secs: u64) -> String {
+        format!("{} {}", self.local(unix_secs).format("%Y-%m-%d %H:%M"), self.name)
+    }
+
+    /// Just the local time of day, e.g. "14:30"
+    pub fn format_time(&self, unix_secs: u64) -> String {
+        self.local(unix_secs).format("%H:%M").to_string()
+    }
+
+    /// The timestamp with the zone's offset at that moment. A zone missing from
+    /// this machine's database (a room restored elsewhere) reads as UTC.
+    fn local(&self, unix_secs: u64) -> DateTime<FixedOffset> {
+        let unix_secs = i64::try_from(unix_secs).unwrap_or(i64::MAX);
+        let offset_secs = match ZoneRules::load(&self.name) {
+            Ok(rules) => rules.utc_offset_secs(unix_secs),
+            Err(e) => {
+                tracing::warn!("Showing times in UTC: {}", e);
+                0
+            }
+        };
+        let offset = FixedOffset::east_opt(offset_secs).unwrap_or(Utc.fix());
+        DateTime::from_timestamp(unix_secs, 0)
+            .unwrap_or_default()
+            .with_timezone(&offset)
+    }
+}
+
+/// Offsets of one zone over time, read from its TZif file (RFC 8536)
+#[derive(Debug)]
+struct ZoneRules {
+    /// When the offset changed (Unix seconds) and the offset from then on, oldest first
+    transitions: Vec<(i64, i32)>,
+    /// Offset before the first transition
+    initial_offset: i32,
+    /// Rule for times after the last transition, from the file's POSIX TZ footer
+    rule: Option<PosixRule>,
+}
+
+impl ZoneRules {
+    fn load(name: &str) -> Result<Self, String> {
+        // Names are paths into the database; keep them there
+        let valid = !name.starts_with('/')
+            && name.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || "/_+-".contains(c));
+        let unknown = || format!("Unknown time zone: {}", name);
+        if !valid {
+            return Err(unknown());
+        }
+        let dirs: Vec<PathBuf> = match std::env::var_os("TZDIR") {
+            Some(dir) => vec![PathBuf::from(dir)],
+            None => ZONEINFO_DIRS.iter().map(PathBuf::from).collect(),
+        };
+        match dirs.iter().find_map(|dir| std::fs::read(dir.join(name)).ok()) {
+            Some(data) => Self::parse(&data).ok_or_else(unknown),
+            None => Self::bundled(name).ok_or_else(unknown),
+        }
+    }
+
+    /// The zone's current rule from the bundled table, without its history
+    fn bundled(name: &str) -> Option<Self> {
+        let rule = BUNDLED_RULES
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))?;
+        let rule = PosixRule::parse(rule)?;
+        Some(Self { transitions: Vec::new(), initial_offset: rule.std_offset, rule: Some(rule) })
+    }
+
+    fn parse(data: &[u8]) -> Option<Self> {
+        let header = TzifHeader::parse(data)?;
+        // Version 2+ files repeat the data with 64-bit times, followed by the footer
+        let (header, body, time_size) = if header.version >= b'2' {
+            let rest = data.get(header.v1_data_len(4)..)?;
+            (TzifHeader::parse(rest)?, rest, 8)
+        } else {
+            (header, data, 4)
+        };
+        let mut at = TzifHeader::LEN;
+        let mut next = |len: usize| {
+            let slice = body.get(at..at + len);
+            at += len;
+            slice
+        };
+        let times = next(header.time_count * time_size)?;
+        let indices = next(header.time_count)?;
+        let types = next(header.type_count * 6)?;
+        let type_offset = |index: usize| -> Option<i32> {
+            let ttinfo = types.get(index * 6..index * 6 + 4)?;
+            Some(i32::from_be_bytes(ttinfo.try_into().ok()?))
+        };
+
+        let mut transitions = Vec::with_capacity(header.time_count);
+        for (i, &index) in indices.iter().enumerate() {
+            let raw = &times[i * time_size..(i + 1) * time_size];
+            let time = match time_size {
+                8 => i64::from_be_bytes(raw.try_into().ok()?),
+                _ => i64::from(i32::from_be_bytes(raw.try_into().ok()?)),
+            };
+            transitions.push((time, type_offset(usize::from(index))?));
+        }
+        let initial_offset = type_offset(0)?;
+
+        let rule = if time_size == 8 {
+            let footer_start = TzifHeader::LEN + header.data_len(8);
+            body.get(footer_start..)
+                .and_then(|footer| std::str::from_utf8(footer).ok())
+                .map(|footer| footer.trim_matches('\n'))
+                .filter(|footer| !footer.is_empty())
+                .and_then(PosixRule::parse)
+        } else {
+            None
+        };
+        Some(Self { transitions, initial_offset, rule })
+    }
+
+    /// Seconds east of UTC in force at a Unix timestamp
+    fn utc_offset_secs(&self, unix_secs: i64) -> i32 {
+        let passed = self.transitions.partition_point(|&(time, _)| time <= unix_secs);
+        // Past the last transition the footer rule applies, if there is one
+        if passed == self.transitions.len() {
+            if let Some(rule) = &self.rule {
+                return rule.utc_offset_secs(unix_secs);
+            }
+        }
+        match passed {
+            0 => self.initial_offset,
+            passed => self.transitions[passed - 1].1,
+        }
+    }
+}
+
+/// Counts from a TZif header
+struct TzifHeader {
+    version: u8,
+    ut_count: usize,
+    std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl TzifHeader {
+    const LEN: usize = 44;
+
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let count = |field: usize| -> Option<usize> {
+            let start = 20 + field * 4;
+            let bytes: [u8; 4] = data.get(start..start + 4)?.try_into().ok()?;
+            usize::try_from(u32::from_be_bytes(bytes)).ok()
+        };
+        Some(Self {
+            version: *data.get(4)?,
+            ut_count: count(0)?,
+            std_count: count(1)?,
+            leap_count: count(2)?,
+            time_count: count(3)?,
+            type_count: count(4)?,
+            char_count: count(5)?,
+        })
+    }
+
+    /// Length of the data block after the header, for times of `time_size` bytes
+    fn data_len(&self, time_size: usize) -> usize {
+        self.time_count * (time_size + 1)
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_size + 4)
+            + self.std_count
+            + self.ut_count
+    }
+
+    /// Header plus version 1 data block, where the version 2 header starts
+    fn v1_data_len(&self, time_size: usize) -> usize {
+        Self::LEN + self.data_len(time_size)
+    }
+}
+
+/// A POSIX TZ rule such as "CET-1CEST,M3.5.0,M10.5.0/3"
+#[derive(Debug)]
+struct PosixRule {
+    /// Seconds east of UTC outside daylight saving time
+    std_offset: i32,
+    /// Daylight saving time offset and when it starts and ends, if the zone has it
+    dst: Option<(i32, PosixDate, i64, PosixDate, i64)>,
+}
+
+/// Day of the year a POSIX rule switches on
+#[derive(Debug, Clone, Copy)]
+enum PosixDate {
+    /// `Jn`: day 1-365, never counting February 29
+    Julian(u32),
+    /// `n`: day 0-365, counting February 29
+    ZeroBased(u32),
+    /// `Mm.w.d`: weekday d (0 = Sunday) of week w (5 = last) of month m
+    MonthWeekDay(u32, u32, u32),
+}
+
+impl PosixRule {
+    fn parse(tz: &str) -> Option<Self> {
+        let mut rest = tz;
+        skip_name(&mut rest)?;
+        // POSIX offsets count hours west of UTC
+        let std_offset = -i32::try_from(parse_duration(&mut rest)?).ok()?;
+        if rest.is_empty() {
+            return Some(Self { std_offset, dst: None });
+        }
+        skip_name(&mut rest)?;
+        let dst_offset = if rest.starts_with(',') {
+            std_offset + 3600
+        } else {
+            -i32::try_from(parse_duration(&mut rest)?).ok()?
+        };
+        let switch = |rest: &mut &str| -> Option<(PosixDate, i64)> {
+            *rest = rest.strip_prefix(',')?;
+            let date = parse_date(rest)?;
+            let time = match rest.strip_prefix('/') {
+                Some(after) => {
+                    *rest = after;
+                    parse_duration(rest)?
+                }
+                None => DEFAULT_TRANSITION_SECS,
+            };
+            Some((date, time))
+        };
+        let (start, start_time) = switch(&mut rest)?;
+        let (end, end_time) = switch(&mut rest)?;
+        Some(Self { std_offset, dst: Some((dst_offset, start, start_time, end, end_time)) })
+    }
+
+    fn utc_offset_secs(&self, unix_secs: i64) -> i32 {
+        let Some((dst_offset, start, start_time, end, end_time)) = self.dst else {
+            return self.std_offset;
+        };
+        let Some(year) = DateTime::from_timestamp(unix_secs + i64::from(self.std_offset), 0).map(|t| t.year()) else {
+            return self.std_offset;
+        };
+        // Daylight saving time starts by standard time and ends by daylight time
+        let (Some(starts), Some(ends)) = (
+            start.unix_secs(year).map(|day| day + start_time - i64::from(self.std_offset)),
+            end.unix_secs(year).map(|day| day + end_time - i64::from(dst_offset)),
+        ) else {
+            return self.std_offset;
+        };
+        let in_dst = if starts < ends {
+            starts <= unix_secs && unix_secs < ends
+        } else {
+            // Southern hemisphere: daylight time runs over the new year
+            !(ends <= unix_secs && unix_secs < starts)
+        };
+        if in_dst { dst_offset } else { self.std_offset }
+    }
+}
+
+impl PosixDate {
+    /// Midnight (as if UTC) of this day in a year
+    fn unix_secs(self, year: i32) -> Option<i64> {
+        let jan_1 = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let date = match self {
+            PosixDate::Julian(day) => {
+                let leap_day = NaiveDate::from_ymd_opt(year, 2, 29).is_some() && day >= 60;
+                jan_1 + chrono::Days::new(u64::from(day - 1 + u32::from(leap_day)))
+            }
+            PosixDate::ZeroBased(day) => jan_1 + chrono::Days::new(u64::from(day)),
+            PosixDate::MonthWeekDay(month, week, weekday) => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let first_weekday = first.weekday().num_days_from_sunday();
+                let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+                    day -= 7;
+                }
+                NaiveDate::from_ymd_opt(year, month, day)?
+            }
+        };
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+    }
+}
+
+/// Skip a zone abbreviation: letters, or anything in angle brackets
+fn skip_name(rest: &mut &str) -> Option<()> {
+    let len = if let Some(quoted) = rest.strip_prefix('<') {
+        quoted.find('>')? + 2
+    } else {
+        rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len())
+    };
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// `[+-]hh[:mm[:ss]]` as seconds
+fn parse_duration(rest: &mut &str) -> Option<i64> {
+    let sign = match rest.chars().next()? {
+        '-' => -1,
+        _ => 1,
+    };
+    *rest = rest.trim_start_matches(['+', '-']);
+    let mut secs = 0;
+    for (i, unit) in [3600, 60, 1].into_iter().enumerate() {
+        if i > 0 {
+            match rest.strip_prefix(':') {
+                Some(after) => *rest = after,
+                None => break,
+            }
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        secs += rest[..digits].parse::<i64>().ok()? * unit;
+        *rest = &rest[digits..];
+    }
+    Some(sign * secs)
+}
+
+/// `Jn`, `n` or `Mm.w.d`
+fn parse_date(rest: &mut &str) -> Option<PosixDate> {
+    let number = |rest: &mut &str| -> Option<u32> {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let value = rest[..digits].parse().ok()?;
+        *rest = &rest[digits..];
+        Some(value)
+    };
+    if let Some(after) = rest.strip_prefix('J') {
+        *rest = after;
+        let day = number(rest)?;
+        return (1..=365).contains(&day).then_some(PosixDate::Julian(day));
+    }
+    if let Some(after) = rest.strip_prefix('M') {
+        *rest = after;
+        let month = number(rest)?;
+        *rest = rest.strip_prefix('.')?;
+        let week = number(rest)?;
+        *rest = rest.strip_prefix('.')?;
+        let weekday = number(rest)?;
+        let valid = (1..=12).contains(&month) && (1..=5).contains(&week) && weekday <= 6;
+        return valid.then_some(PosixDate::MonthWeekDay(month, week, weekday));
+    }
+    let day = number(rest)?;
+    (day <= 365).then_some(PosixDate::ZeroBased(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_shown_in_the_rooms_zone() {
+        let berlin = RoomTimeZone::new("Europe/Berlin").unwrap();
+        // 2024-01-01 12:00:00 UTC
+        assert_eq!(berlin.format(1_704_110_400), "2024-01-01 13:00 Europe/Berlin");
+
+        let honolulu = RoomTimeZone::new("Pacific/Honolulu").unwrap();
+        assert_eq!(honolulu.format_time(1_704_110_400), "02:00");
+
+        assert!(RoomTimeZone::new("Mars/Olympus").is_err());
+        assert!(RoomTimeZone::new("../../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn offsets_follow_daylight_saving_changes() {
+        let berlin = RoomTimeZone::new("Europe/Berlin").unwrap();
+        // 2024-07-01 12:00:00 UTC, in summer time
+        assert_eq!(berlin.format_time(1_719_835_200), "14:00");
+        // Either side of the 2024-10-27 01:00 UTC change back
+        assert_eq!(berlin.format_time(1_729_990_740), "02:59");
+        assert_eq!(berlin.format_time(1_729_990_800), "02:00");
+
+        let sydney = RoomTimeZone::new("Australia/Sydney").unwrap();
+        assert_eq!(sydney.format_time(1_704_110_400), "23:00");
+        assert_eq!(sydney.format_time(1_719_835_200), "22:00");
+    }
+
+    #[test]
+    fn bundled_rules_cover_every_zone() {
+        for line in BUNDLED_RULES.lines().filter(|line| !line.starts_with('#')) {
+            let (name, _) = line.split_once(' ').unwrap();
+            assert!(ZoneRules::bundled(name).is_some(), "can't read the rule for {}", name);
+        }
+        let berlin = ZoneRules::bundled("Europe/Berlin").unwrap();
+        // 2024-07-01 and 2024-01-01 12:00:00 UTC
+        assert_eq!(berlin.utc_offset_secs(1_719_835_200), 7200);
+        assert_eq!(berlin.utc_offset_secs(1_704_110_400), 3600);
+        assert!(ZoneRules::bundled("Europe/Berl").is_none());
+    }
+
+    #[test]
+    fn posix_rules_cover_times_past_the_last_transition() {
+        let rule = PosixRule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        // 2100-07-01 and 2100-01-01, 00:00 UTC
+        assert_eq!(rule.utc_offset_secs(4_118_083_200), 7200);
+        assert_eq!(rule.utc_offset_secs(4_102_444_800), 3600);
+
+        let southern = PosixRule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(southern.utc_offset_secs(4_102_444_800), 11 * 3600);
+        assert_eq!(southern.utc_offset_secs(4_118_083_200), 10 * 3600);
+
+        assert_eq!(PosixRule::parse("<+0530>-5:30").unwrap().utc_offset_secs(0), 5 * 3600 + 1800);
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Where release manifests are published, one per channel, in the Tauri
+/// updater's static JSON format
+pub const DEFAULT_MANIFEST_URL: &str =
+    "https://github.com/Bakobiibizo/scrum-poker/releases/download/{channel}/latest.json";
+
+/// Which releases the host wants to be offered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases
+    Beta,
+}
+
+impl ReleaseChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+        }
+    }
+}
+
+/// A release manifest, as read by the Tauri updater
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    version: String,
+    /// Changelog for the release
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+    #[serde(default)]
+    platforms: HashMap<String, PlatformRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlatformRelease {
+    url: String,
+}
+
+/// Result of checking a channel for a newer release
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpdateInfo {
+    pub channel: ReleaseChannel,
+    pub current_version: String,
+    pub latest_version: String,
+    pub available: bool,
+    /// Changelog of the latest release
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    /// Download for this platform, if the release has one
+    pub download_url: Option<String>,
+    /// An update is available but a session is under way, so the host
+    /// shouldn't be prompted until it's over
+    pub deferred: bool,
+}
+
+/// Platform key used in release manifests, e.g. "windows-x86_64" or "darwin-aarch64"
+fn platform_key() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+/// Fetch a channel's manifest and compare it with the running version
+pub async fn check(client: &reqwest::Client, manifest_url: &str, channel: ReleaseChannel) -> Result<UpdateInfo, String> {
+    let url = manifest_url.replace("{channel}", channel.as_str());
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Update check failed ({})", response.status()));
+    }
+    let manifest: Manifest = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid release manifest: {}", e))?;
+
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+    let latest_version = manifest.version.trim_start_matches('v').to_string();
+    Ok(UpdateInfo {
+        channel,
+        available: compare_versions(&latest_version, &current_version) == Some(Ordering::Greater),
+        download_url: manifest.platforms.get(&platform_key()).map(|p| p.url.clone()),
+        current_version,
+        latest_version,
+        notes: manifest.notes,
+        pub_date: manifest.pub_date,
+        deferred: false,
+    })
+}
+
+/// Compare two "major.minor.patch[-pre]" versions. A pre-release sorts before
+/// its release; pre-release tags compare by their dot-separated parts,
+/// numerically where both are numbers. None if either isn't a version.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    fn parse(version: &str) -> Option<([u64; 3], Option<&str>)> {
+        let version = version.split('+').next()?;
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+        let mut numbers = core.split('.').map(|n| n.parse::<u64>().ok());
+        let core = [numbers.next()??, numbers.next()??, numbers.next()??];
+        numbers.next().is_none().then_some((core, pre))
+    }
+    let (a_core, a_pre) = parse(a)?;
+    let (b_core, b_pre) = parse(b)?;
+    Some(a_core.cmp(&b_core).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let mut a_parts = a.split('.');
+            let mut b_parts = b.split('.');
+            loop {
+                match (a_parts.next(), b_parts.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(a), Some(b)) => {
+                        let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                            (Ok(a), Ok(b)) => a.cmp(&b),
+                            _ => a.cmp(b),
+                        };
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                    }
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_releases_sort_before_their_release() {
+        assert_eq!(compare_versions("0.2.0", "0.1.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.10.0", "0.9.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.2.0-beta.1", "0.2.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("0.2.0-beta.10", "0.2.0-beta.2"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.2.0-beta.1", "0.1.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("latest", "0.1.0"), None);
+    }
+}
@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { 
   PlusCircle, 
   Users, 
@@ -23,9 +24,16 @@ import {
   Shield,
   ShieldCheck,
   Copy,
-  CheckCircle
+  CheckCircle,
+  Lock,
+  Unlock,
+  Clock,
+  History,
+  Search,
+  FileText
 } from "lucide-react";
-import type { Room } from "./types";
+import type { AsyncTicketResult, ComplexityHints, DeckDefinition, DiffStats, EstimateRule, MergeReport, OnboardingState, OnboardingStep, PortForwardInstructions, QuorumRules, RecoveryInfo, RelayError, RevealMode, Room, SessionResult, SummaryFormat, TicketSignal, UpdateInfo } from "./types";
+import { ABSTAIN, ROUND_TAGS, TICKET_SIGNAL_LABELS } from "./types";
 
 interface JiraProject {
   id: string;
@@ -39,6 +47,13 @@ interface JiraBoard {
   board_type: string;
 }
 
+interface BoardIssueFilter {
+  status_categories: string[];
+  labels: string[];
+  only_unestimated: boolean;
+  story_points_field?: string;
+}
+
 interface JiraIssueInfo {
   key: string;
   summary: string;
@@ -46,13 +61,103 @@ interface JiraIssueInfo {
   status: string | null;
 }
 
+/**
+ * Whether to show the votes face up. Waits for the room's `reveal_at` so the
+ * host flips the cards together with everyone else.
+ */
+function useCardsFlipped(room: Room | null): boolean {
+  const [flipped, setFlipped] = useState(false);
+  const revealed = room?.votes_revealed ?? false;
+  const revealAt = room?.reveal_at ?? null;
+
+  useEffect(() => {
+    if (!revealed) {
+      setFlipped(false);
+      return;
+    }
+    const wait = revealAt === null ? 0 : Math.max(revealAt - Date.now(), 0);
+    if (wait === 0) {
+      setFlipped(true);
+      return;
+    }
+    const timer = setTimeout(() => setFlipped(true), wait);
+    return () => clearTimeout(timer);
+  }, [revealed, revealAt]);
+
+  return flipped;
+}
+
+/** Short description of a ticket's complexity hints, e.g. "Medium complexity · 3 acceptance criteria" */
+function describeComplexity(hints: ComplexityHints): string {
+  const parts = [`${hints.level[0].toUpperCase()}${hints.level.slice(1)} complexity`];
+  if (hints.acceptance_criteria > 0) parts.push(`${hints.acceptance_criteria} acceptance criteria`);
+  if (hints.linked_issues > 0) parts.push(`${hints.linked_issues} linked issue${hints.linked_issues === 1 ? "" : "s"}`);
+  if (hints.has_code_blocks) parts.push("code in description");
+  parts.push(`${hints.word_count} words`);
+  return parts.join(" · ");
+}
+
+/** Short description of a pull request's size, e.g. "+120 −30 · 4 files" */
+function describeDiffStats(stats: DiffStats): string {
+  return `+${stats.additions} −${stats.deletions} · ${stats.changed_files} file${stats.changed_files === 1 ? "" : "s"}`;
+}
+
+/** Ticket description with its links made clickable */
+function LinkedText({ text }: { text: string }) {
+  // Splitting on a capturing group puts the URLs at the odd indexes
+  const parts = text.split(/(https?:\/\/[^\s()]+)/);
+  return (
+    <>
+      {parts.map((part, i) =>
+        i % 2 === 1 ? (
+          <a key={i} href={part} target="_blank" rel="noreferrer" className="text-blue-400 underline break-all">
+            {part}
+          </a>
+        ) : (
+          part
+        ),
+      )}
+    </>
+  );
+}
+
+/** How often to look for a new release, and to offer one held back during a session */
+const UPDATE_CHECK_INTERVAL_MS = 30 * 60 * 1000;
+
+const DECK_CHOICES: { type: DeckDefinition["type"]; label: string }[] = [
+  { type: "fibonacci", label: "Fibonacci" },
+  { type: "t_shirt", label: "T-shirt sizes" },
+  { type: "powers_of_two", label: "Powers of 2" },
+  { type: "custom", label: "Custom..." },
+];
+
+const SUMMARY_FORMATS: { format: SummaryFormat; label: string }[] = [
+  { format: "markdown", label: "Markdown" },
+  { format: "jira_wiki", label: "Jira" },
+  { format: "plain_text", label: "Text" },
+];
+
+/** Onboarding steps as shown to the host, with the command that checks each */
+const ONBOARDING_STEPS: Record<OnboardingStep, { label: string; command: string; optional: boolean }> = {
+  server_started: { label: "Server started", command: "test_server_started", optional: false },
+  network_reachable: { label: "Reachable on your network", command: "test_network_reachable", optional: false },
+  jira_connected: { label: "Jira connected", command: "test_jira_connection", optional: true },
+  relay_tested: { label: "Relay reachable", command: "test_relay_connection", optional: true },
+};
+
 function App() {
   const [rooms, setRooms] = useState<Room[]>([]);
   const [selectedRoom, setSelectedRoom] = useState<Room | null>(null);
   const [newRoomName, setNewRoomName] = useState("");
+  const [newRoomDeck, setNewRoomDeck] = useState<DeckDefinition["type"]>("fibonacci");
+  const [customCards, setCustomCards] = useState("");
+  const [createRoomError, setCreateRoomError] = useState<string | null>(null);
+  const [hostName, setHostName] = useState("");
   const [serverUrl, setServerUrl] = useState("");
   const [isCreating, setIsCreating] = useState(false);
   const [isOpeningUpnp, setIsOpeningUpnp] = useState(false);
+  const [portForward, setPortForward] = useState<PortForwardInstructions | null>(null);
+  const [isLoadingPortForward, setIsLoadingPortForward] = useState(false);
   
   // Jira state
   const [showJiraConfig, setShowJiraConfig] = useState(false);
@@ -64,7 +169,35 @@ function App() {
   const [jiraToken, setJiraToken] = useState("");
   const [jiraPassword, setJiraPassword] = useState("");
   const [jiraUnlockError, setJiraUnlockError] = useState("");
+  // Whether round tags are also added to the ticket in Jira as labels
+  const [labelTagsInJira, setLabelTagsInJira] = useState(false);
+  const [overrideValue, setOverrideValue] = useState("");
+  const [overrideReason, setOverrideReason] = useState("");
+  const [overrideError, setOverrideError] = useState<string | null>(null);
+  const [jiraPushResult, setJiraPushResult] = useState("");
+  const [hasLlm, setHasLlm] = useState(false);
+  const [llmEndpoint, setLlmEndpoint] = useState("");
+  const [llmModel, setLlmModel] = useState("");
+  const [llmKey, setLlmKey] = useState("");
+  const [summaryDraft, setSummaryDraft] = useState<string | null>(null);
+  const [isDraftingSummary, setIsDraftingSummary] = useState(false);
+  // Async ballots: one voter per line, "Name <email>"
+  const [showBallots, setShowBallots] = useState(false);
+  const [ballotVoters, setBallotVoters] = useState("");
+  const [ballotDigest, setBallotDigest] = useState("");
+  const [asyncResults, setAsyncResults] = useState<AsyncTicketResult[]>([]);
   const [ticketKey, setTicketKey] = useState("");
+  // "owner/repo" or "owner/repo#123", for estimating pull request review effort
+  const [pullRequestSource, setPullRequestSource] = useState("");
+  // Triage mode: queued tickets picked to be voted on together
+  const [batchKeys, setBatchKeys] = useState<string[]>([]);
+  const [tracedParticipants, setTracedParticipants] = useState<string[]>([]);
+  const [batchPoints, setBatchPoints] = useState("");
+  const [batchPushResult, setBatchPushResult] = useState("");
+  // Re-estimation campaign: the project whose estimated backlog is re-voted
+  const [campaignProject, setCampaignProject] = useState("");
+  const [campaignField, setCampaignField] = useState("");
+  const [campaignResult, setCampaignResult] = useState("");
   const [isLoadingTicket, setIsLoadingTicket] = useState(false);
   const [ticketError, setTicketError] = useState("");
   
@@ -75,11 +208,23 @@ function App() {
   const [jiraBoards, setJiraBoards] = useState<JiraBoard[]>([]);
   const [selectedBoard, setSelectedBoard] = useState<JiraBoard | null>(null);
   const [boardIssues, setBoardIssues] = useState<JiraIssueInfo[]>([]);
+  const [profiles, setProfiles] = useState<string[]>([]);
+  const [activeProfile, setActiveProfile] = useState("default");
+  const [issueFilter, setIssueFilter] = useState<BoardIssueFilter>({
+    status_categories: [],
+    labels: [],
+    only_unestimated: false,
+  });
   const [isLoadingJira, setIsLoadingJira] = useState(false);
   const [jiraBrowserError, setJiraBrowserError] = useState("");
   
   // Network state
   const [showNetworkInfo, setShowNetworkInfo] = useState(false);
+  const [showSessionSearch, setShowSessionSearch] = useState(false);
+  const [sessionQuery, setSessionQuery] = useState("");
+  const [sessionFrom, setSessionFrom] = useState("");
+  const [sessionTo, setSessionTo] = useState("");
+  const [sessionResults, setSessionResults] = useState<SessionResult[]>([]);
   const [networkInfo, setNetworkInfo] = useState<{
     local_ip: string;
     public_ip: string | null;
@@ -87,18 +232,155 @@ function App() {
     local_url: string;
     public_url: string | null;
     firewall_open: boolean;
+    ipv6: { address: string; kind: "native" | "six_to_four" | "teredo"; reachable: boolean } | null;
+    ipv6_url: string | null;
+    /** Best URL to hand out: public if the port is open, else reachable IPv6, else local */
+    share_url: string;
   } | null>(null);
   const [isLoadingNetwork, setIsLoadingNetwork] = useState(false);
   
   // Relay state
   const [isRelayConnected, setIsRelayConnected] = useState(false);
   const [relayUrl, setRelayUrl] = useState<string | null>(null);
+  // Relay link straight into the selected room
+  const [relayShareUrl, setRelayShareUrl] = useState<string | null>(null);
   const [isConnectingRelay, setIsConnectingRelay] = useState(false);
+  const [relayError, setRelayError] = useState<RelayError | null>(null);
+  // Why the relay connection dropped, while it's being re-established
+  const [relayDropped, setRelayDropped] = useState<string | null>(null);
+  // Rooms whose relay copy had drifted while the relay was unreachable
+  const [mergeReports, setMergeReports] = useState<MergeReport[]>([]);
+  const [update, setUpdate] = useState<UpdateInfo | null>(null);
+  const [updateDismissed, setUpdateDismissed] = useState(false);
+  const [recovery, setRecovery] = useState<RecoveryInfo | null>(null);
+  const [recoveryError, setRecoveryError] = useState("");
+  const [onboarding, setOnboarding] = useState<OnboardingState | null>(null);
+  const [checkingStep, setCheckingStep] = useState<OnboardingStep | null>(null);
   
   // Copy link feedback
   const [linkCopied, setLinkCopied] = useState(false);
+  const [summaryCopied, setSummaryCopied] = useState<SummaryFormat | null>(null);
 
   // Fetch rooms and server URL on mount
+  useEffect(() => {
+    loadProfiles();
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<RelayError>("relay-error", (event) => setRelayError(event.payload));
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  // The relay dropped: tell the host, and clear the alert once it's back
+  useEffect(() => {
+    const unlistenDropped = listen<string>("relay-disconnected", (event) => {
+      setRelayDropped(event.payload);
+      setIsRelayConnected(false);
+    });
+    const unlistenReconnected = listen("relay-reconnected", () => {
+      setRelayDropped(null);
+      checkRelayStatus();
+    });
+    return () => {
+      unlistenDropped.then((stop) => stop());
+      unlistenReconnected.then((stop) => stop());
+    };
+  }, []);
+
+  // The last run crashed: offer to pick its session back up
+  useEffect(() => {
+    invoke<RecoveryInfo | null>("get_recovery_info")
+      .then(setRecovery)
+      .catch((error) => console.warn("Failed to check for a crashed session:", error));
+  }, []);
+
+  const recoverLastSession = async () => {
+    try {
+      await invoke<number>("recover_last_session");
+      setRecovery(null);
+      setRecoveryError("");
+      loadRooms();
+    } catch (error) {
+      setRecoveryError(String(error));
+    }
+  };
+
+  const discardRecovery = async () => {
+    try {
+      await invoke("discard_recovery");
+      setRecovery(null);
+    } catch (error) {
+      setRecoveryError(String(error));
+    }
+  };
+
+  // New hosts get a checklist of the setup steps until each has passed or been skipped
+  useEffect(() => {
+    invoke<OnboardingState>("get_onboarding_state")
+      .then(setOnboarding)
+      .catch((error) => console.warn("Failed to load onboarding state:", error));
+  }, []);
+
+  const checkOnboardingStep = async (step: OnboardingStep) => {
+    setCheckingStep(step);
+    try {
+      setOnboarding(await invoke<OnboardingState>(ONBOARDING_STEPS[step].command));
+    } catch (error) {
+      console.error("Onboarding check failed:", error);
+    } finally {
+      setCheckingStep(null);
+    }
+  };
+
+  const skipOnboardingStep = async (step: OnboardingStep) => {
+    try {
+      setOnboarding(await invoke<OnboardingState>("skip_onboarding_step", { step }));
+    } catch (error) {
+      console.error("Failed to skip onboarding step:", error);
+    }
+  };
+
+  // Check for updates now and then; one found mid-session is held back until the session is over
+  useEffect(() => {
+    const check = () =>
+      invoke<UpdateInfo>("check_for_updates")
+        .then(setUpdate)
+        .catch((error) => console.warn("Update check failed:", error));
+    check();
+    const interval = setInterval(check, UPDATE_CHECK_INTERVAL_MS);
+    return () => clearInterval(interval);
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<MergeReport[]>("relay-merged", (event) => {
+      setMergeReports(event.payload);
+      if (selectedRoom && event.payload.some((r) => r.room_id === selectedRoom.id)) loadRoom(selectedRoom.id);
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [selectedRoom?.id]);
+
+  // Jira rejected the credentials: ask for them again; failed requests are retried afterwards
+  useEffect(() => {
+    const unlisten = listen("jira-auth-failed", async () => {
+      setHasJiraConfig(false);
+      setJiraUnlockError("Jira rejected the credentials. Unlock or re-enter them to continue.");
+      const hasStored = await invoke<boolean>("has_stored_credentials");
+      setHasStoredCreds(hasStored);
+      if (hasStored) {
+        setShowJiraUnlock(true);
+      } else {
+        setShowJiraConfig(true);
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   useEffect(() => {
     loadRooms();
     loadServerUrl();
@@ -117,10 +399,60 @@ function App() {
     return () => clearInterval(interval);
   }, [selectedRoom?.id]);
 
+  // Keyboard shortcuts for the selected room: R reveal, H hide, C clear votes, N next ticket
+  useEffect(() => {
+    if (!selectedRoom) return;
+    const actions: Record<string, string> = { r: "reveal", h: "hide", c: "reset", n: "next_ticket" };
+    const onKeyDown = (event: KeyboardEvent) => {
+      const target = event.target as HTMLElement;
+      if (["INPUT", "TEXTAREA", "SELECT"].includes(target.tagName) || event.ctrlKey || event.metaKey || event.altKey) {
+        return;
+      }
+      const action = actions[event.key.toLowerCase()];
+      if (!action) return;
+      event.preventDefault();
+      invoke("host_action", { roomId: selectedRoom.id, action })
+        .then(() => loadRoom(selectedRoom.id))
+        .catch((error) => console.error(`Host action ${action} failed:`, error));
+    };
+    window.addEventListener("keydown", onKeyDown);
+    return () => window.removeEventListener("keydown", onKeyDown);
+  }, [selectedRoom?.id]);
+
+  const loadProfiles = async () => {
+    try {
+      setProfiles(await invoke<string[]>("list_profiles"));
+      setActiveProfile(await invoke<string>("get_active_profile"));
+    } catch (error) {
+      console.error("Failed to load profiles:", error);
+    }
+  };
+
+  const switchProfile = async (name: string) => {
+    if (name === "__new__") {
+      const newName = window.prompt("New profile name (letters, digits, - and _):");
+      if (!newName) return;
+      name = newName;
+    }
+    try {
+      const selected = await invoke<string>("select_profile", { name });
+      setActiveProfile(selected);
+      setSelectedRoom(null);
+      await loadProfiles();
+      loadRooms();
+      checkJiraConfig();
+      checkStoredCredentials();
+      checkRelayStatus();
+    } catch (error) {
+      alert(String(error));
+    }
+  };
+
   const checkJiraConfig = async () => {
     try {
       const configured = await invoke<boolean>("has_jira_config");
       setHasJiraConfig(configured);
+      setHasLlm(await invoke<boolean>("has_llm_credentials"));
     } catch (error) {
       console.error("Failed to check Jira config:", error);
     }
@@ -140,6 +472,7 @@ function App() {
     setJiraUnlockError("");
     try {
       await invoke("unlock_credentials", { password: jiraPassword });
+      setHasLlm(await invoke<boolean>("has_llm_credentials"));
       setHasJiraConfig(true);
       setShowJiraUnlock(false);
       setJiraPassword("");
@@ -160,6 +493,18 @@ function App() {
         email: jiraEmail,
         apiToken: jiraToken,
       });
+      if (llmEndpoint.trim()) {
+        await invoke("save_llm_credentials", {
+          password: jiraPassword,
+          endpoint: llmEndpoint,
+          model: llmModel,
+          apiKey: llmKey,
+        });
+        setHasLlm(true);
+        setLlmEndpoint("");
+        setLlmModel("");
+        setLlmKey("");
+      }
       setHasJiraConfig(true);
       setHasStoredCreds(true);
       setShowJiraConfig(false);
@@ -211,6 +556,20 @@ function App() {
     setIsLoadingTicket(false);
   };
 
+  const loadPullRequests = async () => {
+    if (!selectedRoom || !pullRequestSource.trim()) return;
+    setIsLoadingTicket(true);
+    setTicketError("");
+    try {
+      await invoke("load_github_pull_requests", { roomId: selectedRoom.id, source: pullRequestSource.trim() });
+      setPullRequestSource("");
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setTicketError(String(error));
+    }
+    setIsLoadingTicket(false);
+  };
+
   const clearTicket = async () => {
     if (!selectedRoom) return;
     try {
@@ -221,6 +580,52 @@ function App() {
     }
   };
 
+  const searchSessions = async () => {
+    // Date inputs are local days; cover the whole of the last one
+    const toUnix = (day: string, endOfDay: boolean) =>
+      day ? Math.floor(new Date(`${day}T${endOfDay ? "23:59:59" : "00:00:00"}`).getTime() / 1000) : null;
+    try {
+      const results = await invoke<SessionResult[]>("search_sessions", {
+        query: sessionQuery,
+        dateRange: { from: toUnix(sessionFrom, false), to: toUnix(sessionTo, true) },
+      });
+      setSessionResults(results);
+    } catch (error) {
+      console.error("Failed to search sessions:", error);
+    }
+  };
+
+  const setTicketDeadline = async (ticketKey: string, minutes: number | null) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_ticket_deadline", { roomId: selectedRoom.id, ticketKey, minutes });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set ticket deadline:", error);
+    }
+  };
+
+  const requeueParkedTicket = async (ticketKey: string) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("requeue_parked_ticket", { roomId: selectedRoom.id, ticketKey });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to requeue ticket:", error);
+    }
+  };
+
+  const transferTicket = async (toRoomId: string) => {
+    if (!selectedRoom || !toRoomId) return;
+    try {
+      await invoke("transfer_ticket", { fromRoomId: selectedRoom.id, toRoomId });
+      loadRooms();
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setTicketError(String(error));
+    }
+  };
+
   // Jira browser functions
   const openJiraBrowser = async () => {
     setShowJiraBrowser(true);
@@ -258,12 +663,12 @@ function App() {
     setIsLoadingJira(false);
   };
 
-  const selectBoard = async (board: JiraBoard) => {
+  const selectBoard = async (board: JiraBoard, filter: BoardIssueFilter = issueFilter) => {
     setSelectedBoard(board);
     setIsLoadingJira(true);
     setJiraBrowserError("");
     try {
-      const issues = await invoke<JiraIssueInfo[]>("list_board_issues", { boardId: board.id });
+      const issues = await invoke<JiraIssueInfo[]>("list_board_issues", { boardId: board.id, filter });
       setBoardIssues(issues);
     } catch (error) {
       setJiraBrowserError(String(error));
@@ -271,6 +676,12 @@ function App() {
     setIsLoadingJira(false);
   };
 
+  const updateIssueFilter = (changes: Partial<BoardIssueFilter>) => {
+    const filter = { ...issueFilter, ...changes };
+    setIssueFilter(filter);
+    if (selectedBoard) selectBoard(selectedBoard, filter);
+  };
+
   const selectIssue = async (issue: JiraIssueInfo) => {
     if (!selectedRoom) return;
     setIsLoadingJira(true);
@@ -338,6 +749,7 @@ function App() {
     try {
       await invoke<string>("connect_relay");
       setIsRelayConnected(true);
+      setRelayDropped(null);
       const url = await invoke<string | null>("get_relay_url");
       setRelayUrl(url);
     } catch (error) {
@@ -351,6 +763,7 @@ function App() {
     try {
       await invoke("disconnect_relay");
       setIsRelayConnected(false);
+      setRelayDropped(null);
       setRelayUrl(null);
     } catch (error) {
       console.error("Failed to disconnect from relay:", error);
@@ -402,16 +815,32 @@ function App() {
     setIsOpeningUpnp(false);
   };
 
+  const loadPortForwardInstructions = async () => {
+    setIsLoadingPortForward(true);
+    try {
+      setPortForward(await invoke<PortForwardInstructions>("get_port_forward_instructions"));
+    } catch (error) {
+      console.error("Failed to get port-forwarding instructions:", error);
+      alert(String(error));
+    }
+    setIsLoadingPortForward(false);
+  };
+
   const createRoom = async () => {
     if (!newRoomName.trim()) return;
     setIsCreating(true);
+    setCreateRoomError(null);
+    const deck: DeckDefinition = newRoomDeck === "custom"
+      ? { type: "custom", cards: customCards.split(",").map(card => card.trim()).filter(Boolean) }
+      : { type: newRoomDeck };
     try {
-      const room = await invoke<Room>("create_room", { name: newRoomName });
+      const room = await invoke<Room>("create_room", { name: newRoomName, deck, hostName: hostName.trim() || null });
       setRooms([...rooms, room]);
       setSelectedRoom(room);
       setNewRoomName("");
     } catch (error) {
       console.error("Failed to create room:", error);
+      setCreateRoomError(String(error));
     }
     setIsCreating(false);
   };
@@ -448,6 +877,48 @@ function App() {
     }
   };
 
+  const draftSessionSummary = async () => {
+    if (!selectedRoom) return;
+    setIsDraftingSummary(true);
+    try {
+      setSummaryDraft(await invoke<string>("draft_session_summary", { roomId: selectedRoom.id }));
+    } catch (error) {
+      alert(String(error));
+    } finally {
+      setIsDraftingSummary(false);
+    }
+  };
+
+  const openBallots = async () => {
+    if (!selectedRoom) return;
+    setShowBallots(true);
+    setBallotDigest("");
+    try {
+      setAsyncResults(await invoke<AsyncTicketResult[]>("get_async_results", { roomId: selectedRoom.id }));
+    } catch (error) {
+      console.error("Failed to load async results:", error);
+    }
+  };
+
+  const issueBallots = async () => {
+    if (!selectedRoom) return;
+    const voters = ballotVoters
+      .split("\n")
+      .map((line) => line.trim())
+      .filter(Boolean)
+      .map((line) => {
+        const match = line.match(/^(.*?)\s*<([^>]+)>$/);
+        return match ? { name: match[1], email: match[2] } : { name: line, email: null };
+      });
+    try {
+      const path = await invoke<string>("issue_async_ballots", { roomId: selectedRoom.id, voters, directory: null });
+      setBallotDigest(`Voting links written to ${path}`);
+      setAsyncResults(await invoke<AsyncTicketResult[]>("get_async_results", { roomId: selectedRoom.id }));
+    } catch (error) {
+      setBallotDigest(String(error));
+    }
+  };
+
   const resetVotes = async () => {
     if (!selectedRoom) return;
     try {
@@ -458,6 +929,272 @@ function App() {
     }
   };
 
+  const toggleRoomLock = async () => {
+    if (!selectedRoom) return;
+    try {
+      await invoke(selectedRoom.locked ? "unlock_room" : "lock_room", { roomId: selectedRoom.id });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to toggle room lock:", error);
+    }
+  };
+
+  // Countdown participants see between the end of a discussion and voting opening
+  const VOTING_COUNTDOWN_SECS = 5;
+
+  const toggleDiscussion = async () => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_discussion_phase", {
+        roomId: selectedRoom.id,
+        discussing: !selectedRoom.discussing,
+        countdownSecs: selectedRoom.discussing ? VOTING_COUNTDOWN_SECS : null,
+      });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to change the discussion phase:", error);
+    }
+  };
+
+  const updateRevealRules = async (autoReveal: boolean, quorum: QuorumRules) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_reveal_rules", { roomId: selectedRoom.id, autoReveal, quorum });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to update reveal rules:", error);
+    }
+  };
+
+  const tagRound = async (tag: string) => {
+    if (!selectedRoom || !tag.trim()) return;
+    try {
+      await invoke("tag_round", { roomId: selectedRoom.id, tag, labelInJira: labelTagsInJira });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to tag the round:", error);
+    }
+  };
+
+  const overrideEstimate = async () => {
+    if (!selectedRoom || !overrideValue || !overrideReason.trim()) return;
+    setOverrideError(null);
+    try {
+      await invoke("override_estimate", { roomId: selectedRoom.id, value: overrideValue, reason: overrideReason });
+      setOverrideValue("");
+      setOverrideReason("");
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to override the estimate:", error);
+      setOverrideError(String(error));
+    }
+  };
+
+  const castHostVote = async (vote: string) => {
+    if (!selectedRoom) return;
+    const current = selectedRoom.participants.find((p) => p.is_host)?.vote;
+    try {
+      if (vote === current) {
+        await invoke("host_clear_vote", { roomId: selectedRoom.id });
+      } else {
+        await invoke("host_vote", { roomId: selectedRoom.id, vote });
+      }
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to vote:", error);
+    }
+  };
+
+  const pushEstimateToJira = async () => {
+    if (!selectedRoom) return;
+    try {
+      const points = await invoke<number>("push_estimate_to_jira", { roomId: selectedRoom.id });
+      setJiraPushResult(`Set ${points} points in Jira`);
+    } catch (error) {
+      setJiraPushResult(String(error));
+    }
+  };
+
+  const copyRoundSummary = async (format: SummaryFormat) => {
+    if (!selectedRoom) return;
+    try {
+      const summary = await invoke<string>("format_round_summary", { roomId: selectedRoom.id, format });
+      await navigator.clipboard.writeText(summary);
+      setSummaryCopied(format);
+      setTimeout(() => setSummaryCopied(null), 2000);
+    } catch (error) {
+      console.error("Failed to copy the round summary:", error);
+    }
+  };
+
+  const setEstimateRule = async (rule: EstimateRule | null) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_estimate_rule", { roomId: selectedRoom.id, rule });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set the estimate rule:", error);
+    }
+  };
+
+  const setHideVoters = async (hidden: boolean) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_hide_voters", { roomId: selectedRoom.id, hidden });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to change who-has-voted visibility:", error);
+    }
+  };
+
+  const setAbstainOffered = async (offered: boolean) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_abstain_offered", { roomId: selectedRoom.id, offered });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to change the deck:", error);
+    }
+  };
+
+  const startBatch = async () => {
+    if (!selectedRoom || batchKeys.length < 2) return;
+    try {
+      await invoke("start_batch", { roomId: selectedRoom.id, ticketKeys: batchKeys });
+      setBatchKeys([]);
+      setBatchPushResult("");
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setBatchPushResult(String(error));
+    }
+  };
+
+  const pushBatchEstimate = async (points: number) => {
+    if (!selectedRoom) return;
+    try {
+      const updated = await invoke<string[]>("push_batch_estimate", { roomId: selectedRoom.id, points });
+      setBatchPushResult(`Set ${points} points on ${updated.join(", ")}`);
+    } catch (error) {
+      setBatchPushResult(String(error));
+    }
+  };
+
+  const startCampaign = async () => {
+    if (!selectedRoom || !campaignProject.trim()) return;
+    try {
+      const count = await invoke<number>("start_reestimation_campaign", {
+        roomId: selectedRoom.id,
+        projectKey: campaignProject.trim(),
+        storyPointsField: campaignField.trim() || null,
+      });
+      setCampaignResult(`Queued ${count} previously estimated tickets`);
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setCampaignResult(String(error));
+    }
+  };
+
+  const writeBackCampaign = async () => {
+    if (!selectedRoom) return;
+    try {
+      const updated = await invoke<string[]>("write_back_campaign", { roomId: selectedRoom.id });
+      setCampaignResult(updated.length > 0 ? `Updated ${updated.join(", ")}` : "Nothing changed");
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setCampaignResult(String(error));
+    }
+  };
+
+  const endCampaign = async () => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("end_reestimation_campaign", { roomId: selectedRoom.id });
+      setCampaignResult("");
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      setCampaignResult(String(error));
+    }
+  };
+
+  const revertDeckSwitch = async () => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("revert_deck_switch", { roomId: selectedRoom.id });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to revert the deck:", error);
+    }
+  };
+
+  const setSprintCapacity = async (points: number | null) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_sprint_capacity", { roomId: selectedRoom.id, points });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set the sprint capacity:", error);
+    }
+  };
+
+  const setRevealMode = async (mode: RevealMode) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_reveal_mode", { roomId: selectedRoom.id, mode });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set reveal mode:", error);
+    }
+  };
+
+  const revealNextVote = async () => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("reveal_next_vote", { roomId: selectedRoom.id });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to reveal the next vote:", error);
+    }
+  };
+
+  const setDisconnectGrace = async (secs: number) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_disconnect_grace", { roomId: selectedRoom.id, secs });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set grace period:", error);
+    }
+  };
+
+  /** Announce the start of voting at a time of day ("HH:MM"), the next one to come; "" clears it */
+  const setScheduledStart = async (time: string) => {
+    if (!selectedRoom) return;
+    let startsAt: number | null = null;
+    if (time) {
+      const [hours, minutes] = time.split(":").map(Number);
+      const start = new Date();
+      start.setHours(hours, minutes, 0, 0);
+      if (start.getTime() <= Date.now()) start.setDate(start.getDate() + 1);
+      startsAt = Math.floor(start.getTime() / 1000);
+    }
+    try {
+      await invoke("set_scheduled_start", { roomId: selectedRoom.id, startsAt });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set the start time:", error);
+    }
+  };
+
+  const setRoomTimeZone = async (name: string) => {
+    if (!selectedRoom || !name) return;
+    try {
+      await invoke("set_room_time_zone", { roomId: selectedRoom.id, name });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to set the time zone:", error);
+    }
+  };
+
   const kickParticipant = async (participantId: string) => {
     if (!selectedRoom) return;
     try {
@@ -471,16 +1208,58 @@ function App() {
     }
   };
 
-  const getInviteLink = (): { url: string; type: 'relay' | 'public' | 'local' } | null => {
+  const toggleTracing = async (participantId: string) => {
+    const enabled = !tracedParticipants.includes(participantId);
+    try {
+      await invoke("trace_participant", { participantId, enabled });
+      setTracedParticipants(enabled
+        ? [...tracedParticipants, participantId]
+        : tracedParticipants.filter(id => id !== participantId));
+    } catch (error) {
+      console.error("Failed to toggle participant tracing:", error);
+    }
+  };
+
+  const setSittingOut = async (participantId: string, sittingOut: boolean) => {
+    if (!selectedRoom) return;
+    try {
+      await invoke("set_participant_sitting_out", {
+        roomId: selectedRoom.id,
+        participantId,
+        sittingOut
+      });
+      loadRoom(selectedRoom.id);
+    } catch (error) {
+      console.error("Failed to update participant:", error);
+    }
+  };
+
+  useEffect(() => {
+    setRelayShareUrl(null);
+    if (!isRelayConnected || !selectedRoom) return;
+    invoke<string | null>("get_relay_share_url", { roomId: selectedRoom.id })
+      .then(setRelayShareUrl)
+      .catch((error) => console.error("Failed to get the relay link:", error));
+  }, [isRelayConnected, relayUrl, selectedRoom?.id]);
+
+  const getInviteLink = (): { url: string; type: 'relay' | 'public' | 'ipv6' | 'local' } | null => {
     if (!selectedRoom) return null;
     
     // Priority: 1. Relay (most accessible), 2. Public URL (UPnP), 3. Local
+    if (isRelayConnected && relayShareUrl) {
+      return { url: relayShareUrl, type: 'relay' };
+    }
     if (isRelayConnected && relayUrl) {
       // Relay URL format: wss://... -> https://... for the web client
       const httpRelayUrl = relayUrl.replace('wss://', 'https://').replace('ws://', 'http://');
       return { url: `${httpRelayUrl}/join/${selectedRoom.id}`, type: 'relay' };
     }
     
+    // Without a forwarded port, a reachable IPv6 address gets past the NAT instead
+    if (networkInfo?.ipv6_url && networkInfo.share_url === networkInfo.ipv6_url) {
+      return { url: `${networkInfo.ipv6_url}/join/${selectedRoom.id}`, type: 'ipv6' };
+    }
+
     if (networkInfo?.public_url) {
       return { url: `${networkInfo.public_url}/join/${selectedRoom.id}`, type: 'public' };
     }
@@ -501,6 +1280,17 @@ function App() {
     setTimeout(() => setLinkCopied(false), 2000);
   };
 
+  /** Copy the base URL for Stream Deck-style controllers ("…/reveal", "…/next", …) */
+  const copyControllerUrl = async () => {
+    if (!selectedRoom) return;
+    try {
+      const url = await invoke<string>("get_controller_url", { roomId: selectedRoom.id });
+      navigator.clipboard.writeText(url);
+    } catch (error) {
+      console.error("Failed to get the controller URL:", error);
+    }
+  };
+
   const getVoteStats = () => {
     if (!selectedRoom) return null;
     
@@ -517,11 +1307,27 @@ function App() {
     const avg = numericVotes.reduce((a, b) => a + b, 0) / numericVotes.length;
     const min = Math.min(...numericVotes);
     const max = Math.max(...numericVotes);
+    const abstained = votes.filter((v) => v === ABSTAIN).length;
 
-    return { avg: avg.toFixed(1), min, max };
+    return { avg: avg.toFixed(1), min, max, abstained };
   };
 
-  const stats = selectedRoom?.votes_revealed ? getVoteStats() : null;
+  const cardsFlipped = useCardsFlipped(selectedRoom);
+  const hostParticipant = selectedRoom?.participants.find((p) => p.is_host);
+  const currentRoundTags =
+    [...(selectedRoom?.history ?? [])].reverse().find((entry) => entry.type === "votes_revealed")?.tags ?? [];
+  // The host's override of the last revealed round, if there is one
+  const recentHistory = [...(selectedRoom?.history ?? [])].reverse();
+  const currentOverride = recentHistory
+    .slice(0, Math.max(0, recentHistory.findIndex((entry) => entry.type === "votes_revealed")))
+    .find((entry) => entry.type === "estimate_overridden");
+  const stats = cardsFlipped ? getVoteStats() : null;
+  // How many participants raised each signal about the current ticket, most first
+  const signalCounts = Object.entries(
+    Object.values(selectedRoom?.signals ?? {})
+      .flat()
+      .reduce<Partial<Record<TicketSignal, number>>>((counts, signal) => ({ ...counts, [signal]: (counts[signal] ?? 0) + 1 }), {}),
+  ).sort(([, a], [, b]) => (b ?? 0) - (a ?? 0)) as [TicketSignal, number][];
 
   return (
     <div className="min-h-screen bg-gradient-to-br from-gray-900 via-gray-800 to-gray-900">
@@ -539,6 +1345,17 @@ function App() {
           </div>
           
           <div className="flex items-center gap-4">
+            <select
+              value={activeProfile}
+              onChange={(e) => switchProfile(e.target.value)}
+              title="Profile: each has its own rooms, credentials and relay connection"
+              className="px-2 py-1.5 bg-gray-700 border border-gray-600 rounded-md text-sm text-gray-200"
+            >
+              {profiles.map((profile) => (
+                <option key={profile} value={profile}>{profile}</option>
+              ))}
+              <option value="__new__">New profile...</option>
+            </select>
             <button
               onClick={handleJiraButtonClick}
               className={`flex items-center gap-2 px-3 py-1.5 rounded-md text-sm transition-colors
@@ -569,6 +1386,16 @@ function App() {
               <Globe className="w-4 h-4" />
               Network
             </button>
+            <button
+              onClick={() => {
+                setShowSessionSearch(true);
+                searchSessions();
+              }}
+              className="flex items-center gap-2 px-3 py-1.5 rounded-md text-sm bg-gray-700 text-gray-300 hover:bg-gray-600 transition-colors"
+            >
+              <History className="w-4 h-4" />
+              Past Sessions
+            </button>
             <div className="flex items-center gap-2 text-sm">
               <Server className="w-4 h-4 text-gray-400" />
               <span className="text-gray-400">
@@ -579,6 +1406,155 @@ function App() {
         </div>
       </header>
 
+      {relayDropped && (
+        <div role="alert" className="bg-amber-900/40 border-b border-amber-700 px-6 py-3 flex items-start gap-3">
+          <div className="flex-1">
+            <p className="text-sm font-medium text-amber-200">Lost the relay connection: {relayDropped}</p>
+            <p className="text-sm text-amber-300">
+              Remote participants can't reach the room until it's back. Reconnecting automatically…
+            </p>
+          </div>
+          <button
+            onClick={() => setRelayDropped(null)}
+            className="p-1 text-amber-300 hover:text-white rounded-md"
+            title="Dismiss"
+          >
+            <X className="w-4 h-4" />
+          </button>
+        </div>
+      )}
+
+      {relayError && (
+        <div role="alert" className="bg-red-900/40 border-b border-red-700 px-6 py-3 flex items-start gap-3">
+          <div className="flex-1">
+            <p className="text-sm font-medium text-red-200">Relay error: {relayError.message}</p>
+            <p className="text-sm text-red-300">{relayError.remediation}</p>
+          </div>
+          <button
+            onClick={() => setRelayError(null)}
+            className="p-1 text-red-300 hover:text-white rounded-md"
+            title="Dismiss"
+          >
+            <X className="w-4 h-4" />
+          </button>
+        </div>
+      )}
+
+      {recovery && (
+        <div role="alert" className="bg-amber-900/40 border-b border-amber-700 px-6 py-3 flex items-center gap-3">
+          <div className="flex-1 text-sm">
+            <p className="font-medium text-amber-200">
+              Scrum Poker closed unexpectedly at {new Date(recovery.crashed_at * 1000).toLocaleString()} with{" "}
+              {recovery.rooms} {recovery.rooms === 1 ? "room" : "rooms"} open
+            </p>
+            <p className="text-amber-300">
+              Recover the session to get participants, votes and the relay connection back.
+            </p>
+            {recoveryError && <p className="text-red-300">{recoveryError}</p>}
+          </div>
+          <button
+            onClick={recoverLastSession}
+            className="px-3 py-1 text-sm bg-amber-600 hover:bg-amber-500 rounded-md text-white transition-colors"
+          >
+            Recover
+          </button>
+          <button
+            onClick={discardRecovery}
+            className="px-3 py-1 text-sm bg-gray-700 hover:bg-gray-600 rounded-md text-white transition-colors"
+          >
+            Discard
+          </button>
+        </div>
+      )}
+
+      {onboarding && !onboarding.complete && (
+        <div role="status" className="bg-gray-800 border-b border-gray-700 px-6 py-3 text-sm">
+          <p className="font-medium text-gray-200 mb-2">Get set up for your first session</p>
+          <ul className="space-y-1">
+            {onboarding.steps.map(({ step, status, detail }) => (
+              <li key={step} className="flex items-center gap-3">
+                {status === "passed" ? (
+                  <CheckCircle className="w-4 h-4 text-green-400" />
+                ) : status === "failed" ? (
+                  <X className="w-4 h-4 text-red-400" />
+                ) : (
+                  <Clock className={`w-4 h-4 ${step === onboarding.current ? "text-amber-400" : "text-gray-500"}`} />
+                )}
+                <span className={status === "skipped" ? "text-gray-500 line-through" : "text-gray-200"}>
+                  {ONBOARDING_STEPS[step].label}
+                </span>
+                {detail && <span className={status === "failed" ? "text-red-300" : "text-gray-400"}>{detail}</span>}
+                <span className="flex-1" />
+                <button
+                  onClick={() => checkOnboardingStep(step)}
+                  disabled={checkingStep !== null}
+                  className="px-2 py-0.5 bg-gray-700 hover:bg-gray-600 disabled:opacity-50 rounded-md text-white transition-colors"
+                >
+                  {checkingStep === step ? <Loader2 className="w-4 h-4 animate-spin" /> : status === "pending" ? "Check" : "Check again"}
+                </button>
+                {ONBOARDING_STEPS[step].optional && status !== "passed" && status !== "skipped" && (
+                  <button
+                    onClick={() => skipOnboardingStep(step)}
+                    className="px-2 py-0.5 text-gray-400 hover:text-white rounded-md"
+                  >
+                    Skip
+                  </button>
+                )}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
+      {update?.available && !update.deferred && !updateDismissed && (
+        <div role="status" className="bg-green-900/40 border-b border-green-700 px-6 py-3 flex items-start gap-3">
+          <div className="flex-1 text-sm">
+            <p className="font-medium text-green-200">
+              Scrum Poker {update.latest_version} is available{update.channel === "beta" ? " (beta)" : ""}; you have{" "}
+              {update.current_version}
+            </p>
+            {update.notes && <p className="text-green-300 whitespace-pre-line mt-1">{update.notes}</p>}
+            {update.download_url && (
+              <a href={update.download_url} target="_blank" rel="noreferrer" className="text-green-200 underline mt-1 inline-block">
+                Download
+              </a>
+            )}
+          </div>
+          <button
+            onClick={() => setUpdateDismissed(true)}
+            className="p-1 text-green-300 hover:text-white rounded-md"
+            title="Dismiss"
+          >
+            <X className="w-4 h-4" />
+          </button>
+        </div>
+      )}
+
+      {mergeReports.length > 0 && (
+        <div role="status" className="bg-blue-900/40 border-b border-blue-700 px-6 py-3 flex items-start gap-3">
+          <div className="flex-1 text-sm text-blue-200">
+            <p className="font-medium">Reconnected to the relay and merged what happened while it was unreachable</p>
+            {mergeReports.map((report) => (
+              <ul key={report.room_id} className="text-blue-300">
+                <li className="font-medium">{rooms.find((r) => r.id === report.room_id)?.name ?? report.room_id}</li>
+                {report.joined_via_relay.length > 0 && <li>Joined via the relay: {report.joined_via_relay.join(", ")}</li>}
+                {report.kept_local.length > 0 && <li>Joined here: {report.kept_local.join(", ")}</li>}
+                {report.removed.length > 0 && <li>Left: {report.removed.join(", ")}</li>}
+                {report.votes_from_relay.length > 0 && <li>Votes taken from the relay: {report.votes_from_relay.join(", ")}</li>}
+                {report.votes_kept_local.length > 0 && <li>Newer votes kept here: {report.votes_kept_local.join(", ")}</li>}
+              </ul>
+            ))}
+          </div>
+          <button
+            onClick={() => setMergeReports([])}
+            className="p-1 text-blue-300 hover:text-white rounded-md"
+            title="Dismiss"
+          >
+            <X className="w-4 h-4" />
+          </button>
+        </div>
+      )}
+
       <div className="flex h-[calc(100vh-73px)]">
         {/* Sidebar - Room List */}
         <aside className="w-80 bg-gray-800/30 border-r border-gray-700 flex flex-col">
@@ -605,6 +1581,37 @@ function App() {
                 <PlusCircle className="w-4 h-4" />
               </button>
             </div>
+            <div className="flex gap-2 mt-2">
+              <select
+                value={newRoomDeck}
+                onChange={(e) => setNewRoomDeck(e.target.value as DeckDefinition["type"])}
+                className="px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-sm text-white"
+                title="Voting deck"
+              >
+                {DECK_CHOICES.map(({ type, label }) => (
+                  <option key={type} value={type}>{label}</option>
+                ))}
+              </select>
+              {newRoomDeck === "custom" && (
+                <input
+                  type="text"
+                  value={customCards}
+                  onChange={(e) => setCustomCards(e.target.value)}
+                  placeholder="1, 2, 3, 5, 8"
+                  className="flex-1 min-w-0 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md
+                           text-sm text-white placeholder-gray-400"
+                />
+              )}
+            </div>
+            <input
+              type="text"
+              value={hostName}
+              onChange={(e) => setHostName(e.target.value)}
+              placeholder="Your name, to vote too (optional)"
+              className="w-full mt-2 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-sm
+                       text-white placeholder-gray-400"
+            />
+            {createRoomError && <p className="mt-2 text-xs text-red-400">{createRoomError}</p>}
           </div>
 
           {/* Room List */}
@@ -661,32 +1668,53 @@ function App() {
                     Room Code: <span className="font-mono">{selectedRoom.invite_code}</span>
                   </p>
                 </div>
-                <button
-                  onClick={copyInviteLink}
-                  className={`px-4 py-2 rounded-md text-white font-medium transition-all flex items-center gap-2
-                            ${linkCopied 
-                              ? 'bg-green-600 scale-105' 
-                              : 'bg-blue-600 hover:bg-blue-500'}`}
-                  title={getInviteLink()?.url || 'No link available'}
-                >
-                  {linkCopied ? (
-                    <>
-                      <CheckCircle className="w-4 h-4" />
-                      Copied!
-                    </>
-                  ) : (
-                    <>
-                      {isRelayConnected ? (
-                        <Globe className="w-4 h-4" />
-                      ) : networkInfo?.public_url ? (
-                        <ExternalLink className="w-4 h-4" />
-                      ) : (
-                        <Link className="w-4 h-4" />
-                      )}
-                      Copy {isRelayConnected ? 'Relay' : networkInfo?.public_url ? 'Public' : 'Local'} Link
-                    </>
-                  )}
-                </button>
+                <div className="flex items-center gap-2">
+                  <button
+                    onClick={toggleDiscussion}
+                    className={`px-4 py-2 rounded-md text-white font-medium
+                              ${selectedRoom.discussing ? 'bg-purple-600 hover:bg-purple-500' : 'bg-gray-700 hover:bg-gray-600'}`}
+                    title={selectedRoom.discussing
+                      ? `Open voting after a ${VOTING_COUNTDOWN_SECS}s countdown`
+                      : 'Hold voting while the story is read and discussed'}
+                  >
+                    {selectedRoom.discussing ? 'Open Voting' : 'Discuss'}
+                  </button>
+                  <button
+                    onClick={toggleRoomLock}
+                    className={`px-4 py-2 rounded-md text-white font-medium flex items-center gap-2
+                              ${selectedRoom.locked ? 'bg-amber-600 hover:bg-amber-500' : 'bg-gray-700 hover:bg-gray-600'}`}
+                    title={selectedRoom.locked ? 'New participants cannot join' : 'Stop new participants joining'}
+                  >
+                    {selectedRoom.locked ? <Lock className="w-4 h-4" /> : <Unlock className="w-4 h-4" />}
+                    {selectedRoom.locked ? 'Locked' : 'Lock Room'}
+                  </button>
+                  <button
+                    onClick={copyInviteLink}
+                    className={`px-4 py-2 rounded-md text-white font-medium transition-all flex items-center gap-2
+                              ${linkCopied 
+                                ? 'bg-green-600 scale-105' 
+                                : 'bg-blue-600 hover:bg-blue-500'}`}
+                    title={getInviteLink()?.url || 'No link available'}
+                  >
+                    {linkCopied ? (
+                      <>
+                        <CheckCircle className="w-4 h-4" />
+                        Copied!
+                      </>
+                    ) : (
+                      <>
+                        {isRelayConnected ? (
+                          <Globe className="w-4 h-4" />
+                        ) : networkInfo?.public_url ? (
+                          <ExternalLink className="w-4 h-4" />
+                        ) : (
+                          <Link className="w-4 h-4" />
+                        )}
+                        Copy {{ relay: 'Relay', public: 'Public', ipv6: 'IPv6', local: 'Local' }[getInviteLink()?.type ?? 'local']} Link
+                      </>
+                    )}
+                  </button>
+                </div>
               </div>
 
               {/* Current Ticket */}
@@ -715,16 +1743,73 @@ function App() {
                             {selectedRoom.current_ticket.status}
                           </span>
                         )}
+                        {!selectedRoom.votes_revealed && selectedRoom.current_ticket.complexity && (
+                          <span className="px-2 py-0.5 bg-amber-600/30 rounded text-xs text-amber-300" title="Estimated from the description and linked issues">
+                            {describeComplexity(selectedRoom.current_ticket.complexity)}
+                          </span>
+                        )}
+                        {selectedRoom.current_ticket.diff_stats && (
+                          <span className="px-2 py-0.5 bg-green-600/30 rounded text-xs text-green-300" title={`${selectedRoom.current_ticket.diff_stats.commits} commits`}>
+                            {describeDiffStats(selectedRoom.current_ticket.diff_stats)}
+                          </span>
+                        )}
                       </div>
                       <h3 className="text-lg font-semibold text-white mb-2">
                         {selectedRoom.current_ticket.summary}
                       </h3>
                       {selectedRoom.current_ticket.description && (
                         <div className="text-gray-300 text-sm whitespace-pre-wrap bg-gray-800/50 rounded p-3 max-h-64 overflow-y-auto">
-                          {selectedRoom.current_ticket.description}
+                          <LinkedText text={selectedRoom.current_ticket.description} />
+                        </div>
+                      )}
+                      {signalCounts.length > 0 && (
+                        <div className="flex flex-wrap items-center gap-2 mt-3 text-sm">
+                          <span className="text-gray-400">Flagged by participants:</span>
+                          {signalCounts.map(([signal, count]) => (
+                            <span key={signal} className="px-2 py-0.5 rounded-md bg-amber-600/30 text-amber-200">
+                              {TICKET_SIGNAL_LABELS[signal]} ({count})
+                            </span>
+                          ))}
                         </div>
                       )}
+                      <div className="flex items-center gap-2 mt-3 text-sm text-gray-400">
+                        <Clock className="w-4 h-4" />
+                        <span>Time box</span>
+                        <input
+                          type="number"
+                          min={1}
+                          key={selectedRoom.current_ticket.key}
+                          defaultValue={selectedRoom.ticket_deadlines[selectedRoom.current_ticket.key] ?? ""}
+                          onBlur={(e) => {
+                            const minutes = parseInt(e.target.value, 10);
+                            setTicketDeadline(selectedRoom.current_ticket!.key, minutes > 0 ? minutes : null);
+                          }}
+                          placeholder="none"
+                          className="w-16 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-white"
+                        />
+                        <span>min</span>
+                        {selectedRoom.ticket_deadline_at && (
+                          <span className="text-amber-400">
+                            Parked at {new Date(selectedRoom.ticket_deadline_at * 1000).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })} unless revealed
+                          </span>
+                        )}
+                      </div>
                     </div>
+                    {rooms.length > 1 && (
+                      <select
+                        value=""
+                        onChange={(e) => transferTicket(e.target.value)}
+                        className="bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-gray-300"
+                        title="Move this ticket and its votes to another room"
+                      >
+                        <option value="">Move to room…</option>
+                        {rooms
+                          .filter((room) => room.id !== selectedRoom.id)
+                          .map((room) => (
+                            <option key={room.id} value={room.id}>{room.name}</option>
+                          ))}
+                      </select>
+                    )}
                     <button
                       onClick={clearTicket}
                       className="p-2 text-gray-400 hover:text-red-400 hover:bg-red-500/10 rounded-md transition-colors"
@@ -770,7 +1855,220 @@ function App() {
                     <p className="mt-2 text-sm text-red-400">{ticketError}</p>
                   )}
                 </div>
-              ) : null}
+              ) : null}
+
+              {/* GitHub pull requests, queued for estimating review effort */}
+              <div className="flex items-center gap-3 mb-6">
+                <input
+                  type="text"
+                  value={pullRequestSource}
+                  onChange={(e) => setPullRequestSource(e.target.value)}
+                  onKeyDown={(e) => e.key === "Enter" && loadPullRequests()}
+                  placeholder="GitHub owner/repo or owner/repo#123"
+                  className="flex-1 px-3 py-2 bg-gray-700 border border-gray-600 rounded-md text-sm
+                           text-white placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                />
+                <button
+                  onClick={loadPullRequests}
+                  disabled={!pullRequestSource.trim() || isLoadingTicket}
+                  className="px-4 py-2 bg-gray-600 hover:bg-gray-500 disabled:bg-gray-700 disabled:cursor-not-allowed
+                           rounded-md text-sm text-white font-medium transition-colors"
+                >
+                  Queue PRs
+                </button>
+              </div>
+              {!hasJiraConfig && ticketError && <p className="-mt-4 mb-6 text-sm text-red-400">{ticketError}</p>}
+
+              {/* Deck picked by the ticket's issue type */}
+              {selectedRoom.switched_from_deck && (
+                <div className="flex items-center gap-3 mb-6 text-sm text-gray-300">
+                  <span>
+                    Voting with the {selectedRoom.current_ticket?.issue_type ?? "issue type"} deck: {selectedRoom.deck.join(" ")}
+                  </span>
+                  <button
+                    onClick={revertDeckSwitch}
+                    className="px-2 py-1 text-xs bg-gray-700 hover:bg-gray-600 rounded-md text-white transition-colors"
+                  >
+                    Use room deck
+                  </button>
+                </div>
+              )}
+
+              {/* Sprint fill meter */}
+              {selectedRoom.sprint_fill && (
+                <div className="mb-6">
+                  <div className="flex justify-between text-sm mb-1">
+                    <span className="text-gray-300">Sprint fill</span>
+                    <span className={selectedRoom.sprint_fill.overcommitted ? "text-red-400 font-medium" : "text-gray-400"}>
+                      {selectedRoom.sprint_fill.committed} / {selectedRoom.sprint_fill.capacity} points
+                      {selectedRoom.sprint_fill.overcommitted && " · overcommitted"}
+                    </span>
+                  </div>
+                  <div className="h-2 bg-gray-700 rounded-full overflow-hidden">
+                    <div
+                      className={`h-full ${selectedRoom.sprint_fill.overcommitted ? "bg-red-500" : selectedRoom.sprint_fill.fraction > 0.85 ? "bg-amber-500" : "bg-green-500"}`}
+                      style={{ width: `${Math.min(selectedRoom.sprint_fill.fraction, 1) * 100}%` }}
+                    />
+                  </div>
+                </div>
+              )}
+
+              {/* Triage mode: several small queued tickets voted on in one round */}
+              {(selectedRoom.batch?.length ?? 0) > 0 ? (
+                <div className="bg-blue-900/20 rounded-lg p-4 mb-6 border border-blue-700/50">
+                  <h3 className="text-sm font-medium text-blue-300 mb-2">
+                    Triage round: one vote for {selectedRoom.batch!.length} tickets
+                  </h3>
+                  <ul className="space-y-1 mb-3">
+                    {selectedRoom.batch!.map((ticket) => (
+                      <li key={ticket.key} className="text-sm text-gray-300">
+                        <span className="font-mono text-blue-400">{ticket.key}</span> {ticket.summary}
+                      </li>
+                    ))}
+                  </ul>
+                  {selectedRoom.votes_revealed && hasJiraConfig && (
+                    <div className="flex items-center gap-2">
+                      <input
+                        type="number"
+                        min={0}
+                        step="any"
+                        value={batchPoints}
+                        onChange={(e) => setBatchPoints(e.target.value)}
+                        placeholder={stats?.avg ?? "Points"}
+                        className="w-20 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-sm text-white"
+                      />
+                      <button
+                        onClick={() => pushBatchEstimate(Number(batchPoints || stats?.avg))}
+                        disabled={!batchPoints && !stats}
+                        className="px-3 py-1 text-sm bg-blue-600 hover:bg-blue-500 disabled:bg-gray-600 rounded-md text-white transition-colors"
+                      >
+                        Set on all in Jira
+                      </button>
+                    </div>
+                  )}
+                  {batchPushResult && <p className="mt-2 text-sm text-gray-400">{batchPushResult}</p>}
+                </div>
+              ) : selectedRoom.ticket_queue.length >= 2 && (
+                <div className="bg-gray-800/50 rounded-lg p-4 mb-6 border border-gray-700">
+                  <h3 className="text-sm font-medium text-gray-300 mb-2">Queue · tick obvious small ones to triage together</h3>
+                  <ul className="space-y-1 mb-3">
+                    {selectedRoom.ticket_queue.map((ticket) => (
+                      <li key={ticket.key}>
+                        <label className="flex items-center gap-2 text-sm text-gray-300">
+                          <input
+                            type="checkbox"
+                            checked={batchKeys.includes(ticket.key)}
+                            onChange={(e) =>
+                              setBatchKeys((keys) => (e.target.checked ? [...keys, ticket.key] : keys.filter((k) => k !== ticket.key)))
+                            }
+                          />
+                          <span className="font-mono text-blue-400">{ticket.key}</span> {ticket.summary}
+                        </label>
+                      </li>
+                    ))}
+                  </ul>
+                  <button
+                    onClick={startBatch}
+                    disabled={batchKeys.length < 2}
+                    className="px-3 py-1 text-sm bg-gray-700 hover:bg-gray-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md text-white transition-colors"
+                  >
+                    Estimate {batchKeys.length || ""} together
+                  </button>
+                  {batchPushResult && <p className="mt-2 text-sm text-red-400">{batchPushResult}</p>}
+                </div>
+              )}
+
+              {/* Re-estimation campaign over a project's estimated backlog */}
+              {hasJiraConfig && (
+                <div className="bg-gray-800/50 rounded-lg p-4 mb-6 border border-gray-700">
+                  {selectedRoom.campaign ? (
+                    <>
+                      <h3 className="text-sm font-medium text-gray-300 mb-2">
+                        Re-estimating · {selectedRoom.campaign.results.length} of{" "}
+                        {Object.keys(selectedRoom.campaign.previous).length} done
+                      </h3>
+                      <ul className="space-y-1 mb-3">
+                        {selectedRoom.campaign.results.map((d) => (
+                          <li key={d.ticket_key} className="text-sm text-gray-300">
+                            <span className="font-mono text-blue-400">{d.ticket_key}</span> {d.previous} → {d.estimate}
+                            {d.drift !== 0 && (
+                              <span className={d.drift > 0 ? "text-orange-400" : "text-blue-400"}>
+                                {" "}({d.drift > 0 ? "+" : ""}{d.drift})
+                              </span>
+                            )}
+                            {d.written && <span className="text-green-400"> ✓</span>}
+                          </li>
+                        ))}
+                      </ul>
+                      <div className="flex gap-2">
+                        <button
+                          onClick={writeBackCampaign}
+                          disabled={!selectedRoom.campaign.results.some((d) => !d.written && d.drift !== 0)}
+                          className="px-3 py-1 text-sm bg-blue-600 hover:bg-blue-500 disabled:bg-gray-600 rounded-md text-white transition-colors"
+                        >
+                          Write back {selectedRoom.campaign.results.filter((d) => !d.written && d.drift !== 0).length} changes
+                        </button>
+                        <button
+                          onClick={endCampaign}
+                          className="px-3 py-1 text-sm bg-gray-700 hover:bg-gray-600 rounded-md text-white transition-colors"
+                        >
+                          End campaign
+                        </button>
+                      </div>
+                    </>
+                  ) : (
+                    <>
+                      <h3 className="text-sm font-medium text-gray-300 mb-2">Re-estimate a backlog</h3>
+                      <div className="flex items-center gap-2">
+                        <input
+                          type="text"
+                          value={campaignProject}
+                          onChange={(e) => setCampaignProject(e.target.value)}
+                          placeholder="Project key"
+                          className="w-28 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-sm text-white"
+                        />
+                        <input
+                          type="text"
+                          value={campaignField}
+                          onChange={(e) => setCampaignField(e.target.value)}
+                          placeholder="Story Points"
+                          className="w-32 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-sm text-white"
+                        />
+                        <button
+                          onClick={startCampaign}
+                          disabled={!campaignProject.trim()}
+                          className="px-3 py-1 text-sm bg-gray-700 hover:bg-gray-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-md text-white transition-colors"
+                        >
+                          Start
+                        </button>
+                      </div>
+                    </>
+                  )}
+                  {campaignResult && <p className="mt-2 text-sm text-gray-400">{campaignResult}</p>}
+                </div>
+              )}
+
+              {/* Parked Tickets */}
+              {selectedRoom.parked_tickets.length > 0 && (
+                <div className="bg-amber-900/20 rounded-lg p-4 mb-6 border border-amber-700/50">
+                  <h3 className="text-sm font-medium text-amber-300 mb-2">Parked (ran past their time box)</h3>
+                  <ul className="space-y-1">
+                    {selectedRoom.parked_tickets.map((ticket) => (
+                      <li key={ticket.key} className="flex items-center justify-between text-sm text-gray-300">
+                        <span>
+                          <span className="font-mono text-amber-400">{ticket.key}</span> {ticket.summary}
+                        </span>
+                        <button
+                          onClick={() => requeueParkedTicket(ticket.key)}
+                          className="px-2 py-1 text-xs bg-gray-700 hover:bg-gray-600 rounded-md text-white transition-colors"
+                        >
+                          Requeue
+                        </button>
+                      </li>
+                    ))}
+                  </ul>
+                </div>
+              )}
 
               {/* Controls */}
               <div className="flex items-center gap-3 mb-6">
@@ -782,6 +2080,39 @@ function App() {
                   <RefreshCw className="w-4 h-4" />
                   Reset Votes
                 </button>
+
+                <button
+                  onClick={openBallots}
+                  className="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded-md text-white 
+                           font-medium transition-colors flex items-center gap-2"
+                  title="Collect estimates without a live session, through personal voting links"
+                >
+                  <FileText className="w-4 h-4" />
+                  Async Ballots
+                </button>
+
+                <button
+                  onClick={copyControllerUrl}
+                  className="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded-md text-white 
+                           font-medium transition-colors flex items-center gap-2"
+                  title="Copy the URL for Stream Deck or other controller buttons: add /reveal, /hide, /reset or /next. Keys: R reveal, H hide, C clear, N next ticket"
+                >
+                  <Copy className="w-4 h-4" />
+                  Controller URL
+                </button>
+
+                {hasLlm && (
+                  <button
+                    onClick={draftSessionSummary}
+                    disabled={isDraftingSummary}
+                    className="px-4 py-2 bg-gray-700 hover:bg-gray-600 disabled:opacity-50 rounded-md text-white 
+                             font-medium transition-colors flex items-center gap-2"
+                    title="Draft a summary of the session for the sprint doc"
+                  >
+                    {isDraftingSummary ? <Loader2 className="w-4 h-4 animate-spin" /> : <FileText className="w-4 h-4" />}
+                    Draft Summary
+                  </button>
+                )}
                 
                 {selectedRoom.votes_revealed ? (
                   <button
@@ -792,16 +2123,196 @@ function App() {
                     <EyeOff className="w-4 h-4" />
                     Hide Votes
                   </button>
-                ) : (
+                ) : selectedRoom.reveal_progress ? (
                   <button
-                    onClick={revealVotes}
+                    onClick={revealNextVote}
                     className="px-4 py-2 bg-green-600 hover:bg-green-500 rounded-md text-white 
                              font-medium transition-colors flex items-center gap-2"
+                  >
+                    <Eye className="w-4 h-4" />
+                    Next Card ({selectedRoom.reveal_progress.length}/{selectedRoom.participants.filter((p) => p.vote !== null).length})
+                  </button>
+                ) : (
+                  <button
+                    onClick={revealVotes}
+                    className={`px-4 py-2 rounded-md text-white font-medium transition-colors flex items-center gap-2
+                             ${selectedRoom.can_reveal ? 'bg-green-600 hover:bg-green-500' : 'bg-green-800 hover:bg-green-700'}`}
+                    title={selectedRoom.can_reveal ? 'Quorum met' : 'Quorum not met yet'}
                   >
                     <Eye className="w-4 h-4" />
                     Reveal Votes
                   </button>
                 )}
+
+                <select
+                  value={selectedRoom.reveal_mode}
+                  onChange={(e) => setRevealMode(e.target.value as RevealMode)}
+                  className="bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="How the cards are shown when votes are revealed"
+                >
+                  <option value="all_at_once">All at once</option>
+                  <option value="timed">One by one (timed)</option>
+                  <option value="host_paced">One by one (click through)</option>
+                </select>
+
+                <label className="flex items-center gap-2 text-sm text-gray-300" title="Let people sit a ticket out; abstentions count toward the quorum but not the average">
+                  <input
+                    type="checkbox"
+                    checked={selectedRoom.deck?.includes(ABSTAIN) ?? false}
+                    onChange={(e) => setAbstainOffered(e.target.checked)}
+                  />
+                  Offer abstain
+                </label>
+
+                <label className="flex items-center gap-2 text-sm text-gray-300" title="Participants don't see who has voted until everyone has or the votes are revealed">
+                  <input
+                    type="checkbox"
+                    checked={selectedRoom.hide_voters}
+                    onChange={(e) => setHideVoters(e.target.checked)}
+                  />
+                  Hide who voted
+                </label>
+
+                <label className="flex items-center gap-2 text-sm text-gray-300" title="Points the team can take on this sprint; leave empty to stop tracking">
+                  Capacity
+                  <input
+                    type="number"
+                    min={1}
+                    defaultValue={selectedRoom.sprint_capacity ?? ""}
+                    key={`${selectedRoom.id}-${selectedRoom.sprint_capacity}`}
+                    onBlur={(e) => {
+                      const points = e.target.value ? Number(e.target.value) : null;
+                      if (points !== selectedRoom.sprint_capacity) setSprintCapacity(points);
+                    }}
+                    className="w-16 px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-white"
+                  />
+                </label>
+
+                <label className="flex items-center gap-2 text-sm text-gray-300 ml-auto">
+                  <input
+                    type="checkbox"
+                    checked={selectedRoom.auto_reveal}
+                    onChange={(e) => updateRevealRules(e.target.checked, selectedRoom.quorum)}
+                  />
+                  Auto-reveal when
+                </label>
+                <input
+                  type="number"
+                  min={1}
+                  max={100}
+                  value={selectedRoom.quorum.min_voted_percent ?? 100}
+                  onChange={(e) => updateRevealRules(selectedRoom.auto_reveal, {
+                    ...selectedRoom.quorum,
+                    min_voted_percent: Number(e.target.value) || null,
+                  })}
+                  className="w-16 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="Share of participants who must have voted"
+                />
+                <span className="text-sm text-gray-300">% voted, including</span>
+                <input
+                  type="text"
+                  placeholder="names, comma separated"
+                  defaultValue={selectedRoom.quorum.required_voters.join(", ")}
+                  onBlur={(e) => updateRevealRules(selectedRoom.auto_reveal, {
+                    ...selectedRoom.quorum,
+                    required_voters: e.target.value.split(",").map((name) => name.trim()).filter(Boolean),
+                  })}
+                  className="w-48 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="People whose vote is always needed"
+                />
+                <span className="text-sm text-gray-300">Keep dropped people</span>
+                <input
+                  type="number"
+                  min={0}
+                  max={1800}
+                  key={`grace-${selectedRoom.id}`}
+                  defaultValue={selectedRoom.disconnect_grace_secs}
+                  onBlur={(e) => setDisconnectGrace(Math.max(0, Number(e.target.value) || 0))}
+                  className="w-16 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="Seconds a participant whose connection dropped keeps their place and vote"
+                />
+                <span className="text-sm text-gray-300">s</span>
+                <span className="text-sm text-gray-300">Voting starts at</span>
+                <input
+                  type="time"
+                  key={`start-${selectedRoom.id}-${selectedRoom.starts_at ?? ""}`}
+                  defaultValue={
+                    selectedRoom.starts_at
+                      ? new Date(selectedRoom.starts_at * 1000).toTimeString().slice(0, 5)
+                      : ""
+                  }
+                  onBlur={(e) => setScheduledStart(e.target.value)}
+                  className="bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="People joining early see a countdown to this time"
+                />
+                <span className="text-sm text-gray-300">Time zone</span>
+                <input
+                  list="time-zones"
+                  key={`tz-${selectedRoom.id}-${selectedRoom.time_zone?.name ?? ""}`}
+                  defaultValue={selectedRoom.time_zone?.name ?? ""}
+                  onBlur={(e) => setRoomTimeZone(e.target.value.trim())}
+                  className="w-40 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                  title="Times in the recap, copilot notes and join page are shown in this zone"
+                />
+                <datalist id="time-zones">
+                  {Intl.supportedValuesOf("timeZone").map((zone) => (
+                    <option key={zone} value={zone} />
+                  ))}
+                </datalist>
+                {hasJiraConfig && (
+                  <>
+                    <span className="text-sm text-gray-300">Estimates of</span>
+                    <input
+                      type="number"
+                      min={1}
+                      placeholder="13"
+                      key={`rule-${selectedRoom.id}-${selectedRoom.estimate_rule?.threshold ?? ""}`}
+                      defaultValue={selectedRoom.estimate_rule?.threshold ?? ""}
+                      onBlur={(e) => {
+                        const threshold = Number(e.target.value);
+                        if (!e.target.value) {
+                          setEstimateRule(null);
+                        } else if (threshold > 0) {
+                          setEstimateRule({
+                            threshold,
+                            action: selectedRoom.estimate_rule?.action ?? { type: "label", label: "needs-breakdown" },
+                          });
+                        }
+                      }}
+                      className="w-16 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                      title="Leave empty to use the rule from the settings, if any"
+                    />
+                    <span className="text-sm text-gray-300">or more</span>
+                    <select
+                      value={selectedRoom.estimate_rule?.action.type ?? "label"}
+                      disabled={!selectedRoom.estimate_rule}
+                      onChange={(e) =>
+                        selectedRoom.estimate_rule &&
+                        setEstimateRule({
+                          threshold: selectedRoom.estimate_rule.threshold,
+                          action: e.target.value === "flag" ? { type: "flag" } : { type: "label", label: "needs-breakdown" },
+                        })
+                      }
+                      className="bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                    >
+                      <option value="label">get a Jira label</option>
+                      <option value="flag">get flagged in Jira</option>
+                    </select>
+                    {selectedRoom.estimate_rule?.action.type === "label" && (
+                      <input
+                        key={`label-${selectedRoom.id}-${selectedRoom.estimate_rule.action.label}`}
+                        defaultValue={selectedRoom.estimate_rule.action.label}
+                        onBlur={(e) => {
+                          const label = e.target.value.trim().replace(/\s+/g, "-");
+                          if (label && selectedRoom.estimate_rule) {
+                            setEstimateRule({ threshold: selectedRoom.estimate_rule.threshold, action: { type: "label", label } });
+                          }
+                        }}
+                        className="w-36 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-sm text-white"
+                      />
+                    )}
+                  </>
+                )}
               </div>
 
               {/* Stats */}
@@ -822,6 +2333,129 @@ function App() {
                       <p className="text-sm text-gray-400">Maximum</p>
                     </div>
                   </div>
+                  {stats.abstained > 0 && (
+                    <p className="text-sm text-gray-400 text-center mt-3">
+                      {stats.abstained} abstained
+                    </p>
+                  )}
+                  {selectedRoom.votes_revealed && (
+                    <div className="flex flex-wrap items-center gap-2 mt-4 text-sm">
+                      <span className="text-gray-400">Tag round:</span>
+                      {ROUND_TAGS.map((tag) => (
+                        <button
+                          key={tag}
+                          onClick={() => tagRound(tag)}
+                          className={`px-2 py-1 rounded-md ${currentRoundTags.includes(tag) ? "bg-blue-600 text-white" : "bg-gray-700 hover:bg-gray-600 text-gray-200"}`}
+                        >
+                          {tag}
+                        </button>
+                      ))}
+                      {currentRoundTags.filter((tag) => !ROUND_TAGS.includes(tag)).map((tag) => (
+                        <span key={tag} className="px-2 py-1 rounded-md bg-blue-600 text-white">{tag}</span>
+                      ))}
+                      <input
+                        placeholder="Other tag"
+                        onKeyDown={(e) => {
+                          if (e.key === "Enter") {
+                            tagRound(e.currentTarget.value);
+                            e.currentTarget.value = "";
+                          }
+                        }}
+                        className="w-28 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-white"
+                      />
+                      {hasJiraConfig && selectedRoom.current_ticket && (
+                        <label className="flex items-center gap-1 text-gray-400 ml-auto">
+                          <input
+                            type="checkbox"
+                            checked={labelTagsInJira}
+                            onChange={(e) => setLabelTagsInJira(e.target.checked)}
+                          />
+                          Add as Jira label
+                        </label>
+                      )}
+                    </div>
+                  )}
+                  {selectedRoom.votes_revealed && (
+                    <div className="mt-2 text-sm">
+                      {currentOverride && (
+                        <p className="text-yellow-400 mb-1">
+                          Overridden to {currentOverride.value}: {currentOverride.reason}
+                        </p>
+                      )}
+                      <div className="flex flex-wrap items-center gap-2">
+                        <span className="text-gray-400">Override estimate:</span>
+                        <select
+                          value={overrideValue}
+                          onChange={(e) => setOverrideValue(e.target.value)}
+                          className="bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-white"
+                        >
+                          <option value="">Card...</option>
+                          {selectedRoom.deck.filter((card) => card !== ABSTAIN).map((card) => (
+                            <option key={card} value={card}>{card}</option>
+                          ))}
+                        </select>
+                        <input
+                          value={overrideReason}
+                          onChange={(e) => setOverrideReason(e.target.value)}
+                          onKeyDown={(e) => e.key === "Enter" && overrideEstimate()}
+                          placeholder="Reason (required)"
+                          className="flex-1 min-w-0 bg-gray-800 border border-gray-600 rounded-md px-2 py-1 text-white"
+                        />
+                        <button
+                          onClick={overrideEstimate}
+                          disabled={!overrideValue || !overrideReason.trim()}
+                          className="px-2 py-1 rounded-md bg-yellow-700 hover:bg-yellow-600 disabled:bg-gray-700 disabled:text-gray-500 text-white"
+                        >
+                          Override
+                        </button>
+                      </div>
+                      {overrideError && <p className="text-xs text-red-400 mt-1">{overrideError}</p>}
+                      {hasJiraConfig && selectedRoom.current_ticket && !selectedRoom.batch?.length && (
+                        <div className="flex items-center gap-2 mt-2">
+                          <button
+                            onClick={pushEstimateToJira}
+                            className="px-2 py-1 rounded-md bg-blue-700 hover:bg-blue-600 text-white"
+                          >
+                            Write estimate to Jira
+                          </button>
+                          {jiraPushResult && <span className="text-gray-400">{jiraPushResult}</span>}
+                        </div>
+                      )}
+                    </div>
+                  )}
+                  {selectedRoom.votes_revealed && (
+                    <div className="flex flex-wrap items-center gap-2 mt-2 text-sm">
+                      <span className="text-gray-400">Copy summary as:</span>
+                      {SUMMARY_FORMATS.map(({ format, label }) => (
+                        <button
+                          key={format}
+                          onClick={() => copyRoundSummary(format)}
+                          className="flex items-center gap-1 px-2 py-1 rounded-md bg-gray-700 hover:bg-gray-600 text-gray-200"
+                        >
+                          {summaryCopied === format ? <CheckCircle className="w-3 h-3 text-green-400" /> : <Copy className="w-3 h-3" />}
+                          {label}
+                        </button>
+                      ))}
+                    </div>
+                  )}
+                </div>
+              )}
+
+              {/* The host's own vote, in rooms the host takes part in */}
+              {hostParticipant && !selectedRoom.votes_revealed && (
+                <div className="bg-gray-800/50 rounded-lg p-4 mb-6 border border-gray-700">
+                  <h3 className="text-sm font-semibold text-white mb-2">Your vote, {hostParticipant.name}</h3>
+                  <div className="flex flex-wrap gap-2">
+                    {selectedRoom.deck.map((card) => (
+                      <button
+                        key={card}
+                        onClick={() => castHostVote(card)}
+                        className={`min-w-10 px-2 py-2 rounded-md text-sm font-medium ${hostParticipant.vote === card ? "bg-green-600 text-white" : "bg-gray-700 hover:bg-gray-600 text-gray-200"}`}
+                      >
+                        {card}
+                      </button>
+                    ))}
+                  </div>
                 </div>
               )}
 
@@ -850,13 +2484,29 @@ function App() {
                     </thead>
                     <tbody className="divide-y divide-gray-700">
                       {selectedRoom.participants.map((participant) => (
-                        <tr key={participant.id} className="hover:bg-gray-700/20">
+                        <tr
+                          key={participant.id}
+                          className={`hover:bg-gray-700/20 ${participant.disconnected_at != null ? "opacity-40" : ""}`}
+                          title={participant.disconnected_at != null ? "Disconnected; kept for the grace period" : undefined}
+                        >
                           <td className="px-4 py-3">
                             <span className="text-white font-medium">{participant.name}</span>
+                            {participant.client && (
+                              <span className="ml-2 text-xs text-gray-500">
+                                {participant.client.kind}
+                                {participant.client.user_agent_family && ` · ${participant.client.user_agent_family}`}
+                              </span>
+                            )}
+                            {selectedRoom.delegations?.[participant.id] && (
+                              <span className="ml-2 text-xs text-gray-500">
+                                vote handed to{" "}
+                                {selectedRoom.participants.find((p) => p.id === selectedRoom.delegations?.[participant.id])?.name}
+                              </span>
+                            )}
                           </td>
                           <td className="px-4 py-3 text-center">
                             {participant.vote !== null ? (
-                              selectedRoom.votes_revealed ? (
+                              cardsFlipped || selectedRoom.reveal_progress?.includes(participant.id) ? (
                                 <span className="inline-flex items-center justify-center w-10 h-10 
                                                rounded-full bg-green-600 text-white font-bold">
                                   {participant.vote}
@@ -867,11 +2517,30 @@ function App() {
                                   ✓
                                 </span>
                               )
+                            ) : participant.is_observer ? (
+                              <span className="text-xs text-gray-500">observing</span>
+                            ) : participant.sitting_out ? (
+                              <span className="text-xs text-gray-500">sitting out</span>
                             ) : (
                               <span className="text-gray-500">—</span>
                             )}
                           </td>
                           <td className="px-4 py-3 text-right">
+                            <button
+                              onClick={() => setSittingOut(participant.id, !participant.sitting_out)}
+                              className="px-3 py-1 text-sm text-gray-400 hover:text-white
+                                       hover:bg-gray-700/50 rounded transition-colors"
+                            >
+                              {participant.sitting_out ? "Count In" : "Sit Out"}
+                            </button>
+                            <button
+                              onClick={() => toggleTracing(participant.id)}
+                              title="Log every message to and from this participant"
+                              className="px-3 py-1 text-sm text-gray-400 hover:text-white
+                                       hover:bg-gray-700/50 rounded transition-colors"
+                            >
+                              {tracedParticipants.includes(participant.id) ? "Untrace" : "Trace"}
+                            </button>
                             <button
                               onClick={() => kickParticipant(participant.id)}
                               className="px-3 py-1 text-sm text-red-400 hover:text-red-300 
@@ -965,6 +2634,44 @@ function App() {
                 />
               </div>
 
+              <div className="border-t border-gray-600 pt-4 space-y-2">
+                <label className="block text-sm font-medium text-gray-300">
+                  Session Summaries (optional)
+                </label>
+                <p className="text-xs text-gray-500">
+                  An OpenAI-compatible endpoint used to draft session summaries{hasLlm ? "; one is already set up" : ""}
+                </p>
+                <input
+                  type="text"
+                  value={llmEndpoint}
+                  onChange={(e) => setLlmEndpoint(e.target.value)}
+                  placeholder="https://api.openai.com/v1"
+                  className="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded-md 
+                           text-white placeholder-gray-400 focus:outline-none focus:ring-2 
+                           focus:ring-blue-500"
+                />
+                <div className="flex gap-2">
+                  <input
+                    type="text"
+                    value={llmModel}
+                    onChange={(e) => setLlmModel(e.target.value)}
+                    placeholder="Model"
+                    className="w-1/2 px-3 py-2 bg-gray-700 border border-gray-600 rounded-md 
+                             text-white placeholder-gray-400 focus:outline-none focus:ring-2 
+                             focus:ring-blue-500"
+                  />
+                  <input
+                    type="password"
+                    value={llmKey}
+                    onChange={(e) => setLlmKey(e.target.value)}
+                    placeholder="API key"
+                    className="w-1/2 px-3 py-2 bg-gray-700 border border-gray-600 rounded-md 
+                             text-white placeholder-gray-400 focus:outline-none focus:ring-2 
+                             focus:ring-blue-500"
+                  />
+                </div>
+              </div>
+
               <div className="border-t border-gray-600 pt-4">
                 <label className="block text-sm font-medium text-gray-300 mb-1">
                   Encryption Password
@@ -1010,6 +2717,94 @@ function App() {
         </div>
       )}
 
+      {/* Session Summary Draft Modal */}
+      {summaryDraft !== null && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-gray-800 rounded-lg p-6 w-full max-w-2xl border border-gray-700 shadow-2xl">
+            <div className="flex items-center justify-between mb-4">
+              <h2 className="text-xl font-bold text-white">Session Summary</h2>
+              <button
+                onClick={() => setSummaryDraft(null)}
+                className="p-2 text-gray-400 hover:text-white hover:bg-gray-700 rounded-md"
+              >
+                <X className="w-5 h-5" />
+              </button>
+            </div>
+            <p className="text-gray-400 text-sm mb-2">Drafted from the session log; check it before sharing.</p>
+            <textarea
+              value={summaryDraft}
+              onChange={(e) => setSummaryDraft(e.target.value)}
+              rows={14}
+              className="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded-md text-white 
+                       text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+            <div className="flex justify-end mt-3">
+              <button
+                onClick={() => navigator.clipboard.writeText(summaryDraft)}
+                className="px-4 py-2 bg-blue-600 hover:bg-blue-500 rounded-md text-white font-medium 
+                         transition-colors flex items-center gap-2"
+              >
+                <Copy className="w-4 h-4" />
+                Copy
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {showBallots && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-gray-800 rounded-lg p-6 w-full max-w-2xl border border-gray-700 shadow-2xl">
+            <div className="flex items-center justify-between mb-4">
+              <h2 className="text-xl font-bold text-white">Async Ballots</h2>
+              <button
+                onClick={() => setShowBallots(false)}
+                className="p-2 text-gray-400 hover:text-white hover:bg-gray-700 rounded-md"
+              >
+                <X className="w-5 h-5" />
+              </button>
+            </div>
+            <p className="text-gray-400 text-sm mb-2">
+              One voter per line, as <code>Name &lt;email&gt;</code>. Each gets a personal link to estimate the current and queued tickets.
+            </p>
+            <textarea
+              value={ballotVoters}
+              onChange={(e) => setBallotVoters(e.target.value)}
+              rows={5}
+              className="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded-md text-white 
+                       text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+            <div className="flex items-center justify-between mt-3">
+              <span className="text-sm text-gray-400">{ballotDigest}</span>
+              <button
+                onClick={issueBallots}
+                disabled={!ballotVoters.trim()}
+                className="px-4 py-2 bg-blue-600 hover:bg-blue-500 disabled:opacity-50 rounded-md text-white font-medium"
+              >
+                Issue Ballots
+              </button>
+            </div>
+            {asyncResults.length > 0 && (
+              <table className="w-full mt-4 text-sm">
+                <tbody className="divide-y divide-gray-700">
+                  {asyncResults.map((result) => (
+                    <tr key={result.ticket_key}>
+                      <td className="py-2 pr-2 text-white font-medium">{result.ticket_key}</td>
+                      <td className="py-2 pr-2 text-gray-300">
+                        {result.votes.map(([name, vote]) => `${name}: ${vote}`).join(", ") || "—"}
+                      </td>
+                      <td className="py-2 text-gray-500">
+                        {result.waiting_for.length > 0 && `waiting for ${result.waiting_for.join(", ")}`}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            )}
+          </div>
+        </div>
+      )}
+
       {/* Jira Unlock Modal */}
       {showJiraUnlock && (
         <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
@@ -1080,6 +2875,90 @@ function App() {
         </div>
       )}
 
+      {/* Past Sessions Modal */}
+      {showSessionSearch && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-gray-800 rounded-lg p-6 w-full max-w-2xl max-h-[80vh] border border-gray-700 shadow-2xl flex flex-col">
+            <div className="flex items-center justify-between mb-4">
+              <h2 className="text-xl font-bold text-white flex items-center gap-2">
+                <History className="w-5 h-5" />
+                Past Sessions
+              </h2>
+              <button
+                onClick={() => setShowSessionSearch(false)}
+                className="p-2 text-gray-400 hover:text-white hover:bg-gray-700 rounded-md"
+              >
+                <X className="w-5 h-5" />
+              </button>
+            </div>
+
+            <div className="flex flex-wrap items-center gap-2 mb-4 text-sm">
+              <input
+                type="text"
+                value={sessionQuery}
+                onChange={(e) => setSessionQuery(e.target.value)}
+                onKeyDown={(e) => e.key === "Enter" && searchSessions()}
+                placeholder="Ticket key, room or participant"
+                className="flex-1 px-3 py-2 bg-gray-700 border border-gray-600 rounded-md text-white placeholder-gray-400"
+              />
+              <input
+                type="date"
+                value={sessionFrom}
+                onChange={(e) => setSessionFrom(e.target.value)}
+                className="px-2 py-2 bg-gray-700 border border-gray-600 rounded-md text-white"
+              />
+              <span className="text-gray-400">to</span>
+              <input
+                type="date"
+                value={sessionTo}
+                onChange={(e) => setSessionTo(e.target.value)}
+                className="px-2 py-2 bg-gray-700 border border-gray-600 rounded-md text-white"
+              />
+              <button
+                onClick={searchSessions}
+                className="px-3 py-2 bg-blue-600 hover:bg-blue-500 rounded-md text-white flex items-center gap-1"
+              >
+                <Search className="w-4 h-4" />
+                Search
+              </button>
+            </div>
+
+            <div className="overflow-y-auto space-y-3">
+              {sessionResults.length === 0 ? (
+                <p className="text-gray-400 text-center py-8">No matching sessions</p>
+              ) : (
+                sessionResults.map((session) => (
+                  <div key={session.room_id} className="bg-gray-900/50 rounded-md p-3 border border-gray-700">
+                    <div className="flex items-baseline justify-between">
+                      <h3 className="font-semibold text-white">{session.room_name}</h3>
+                      <span className="text-xs text-gray-400">
+                        {new Date(session.started_at * 1000).toLocaleDateString()}
+                        {session.ended_at === null && " (running)"}
+                      </span>
+                    </div>
+                    <p className="text-xs text-gray-400 mb-2">{session.participants.join(", ")}</p>
+                    {session.rounds.map((round, index) => (
+                      <div key={index} className="text-sm text-gray-300">
+                        <span className="font-mono text-blue-400">{round.ticket_key ?? "Untitled round"}</span>
+                        {round.average !== null && <span> · avg {round.average.toFixed(1)}</span>}
+                        {round.votes.length > 0 && (
+                          <span className="text-gray-400">
+                            {" "}· {round.votes.map((v) => `${v.name}: ${v.vote}`).join(", ")}
+                          </span>
+                        )}
+                        {round.tags?.length > 0 && (
+                          <span className="text-amber-300"> · {round.tags.join(", ")}</span>
+                        )}
+                      </div>
+                    ))}
+                  </div>
+                ))
+              )}
+            </div>
+          </div>
+        </div>
+      )}
+
       {/* Network Info Modal */}
       {showNetworkInfo && (
         <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
@@ -1233,6 +3112,28 @@ function App() {
                           <span className="font-mono mx-1 text-yellow-400">TCP port {networkInfo.port}</span> 
                           to <span className="font-mono mx-1 text-yellow-400">{networkInfo.local_ip}</span>
                         </p>
+                        {portForward ? (
+                          <div className="mt-2">
+                            <p className="text-gray-300">
+                              {portForward.vendor_name
+                                ? `Steps for your ${portForward.vendor_name} router:`
+                                : "Router not recognized; general steps:"}
+                            </p>
+                            <ol className="list-decimal list-inside mt-1 space-y-0.5">
+                              {portForward.steps.map((step, index) => (
+                                <li key={index}>{step}</li>
+                              ))}
+                            </ol>
+                          </div>
+                        ) : (
+                          <button
+                            onClick={loadPortForwardInstructions}
+                            disabled={isLoadingPortForward}
+                            className="mt-2 text-blue-400 hover:text-blue-300 disabled:text-gray-500"
+                          >
+                            {isLoadingPortForward ? "Looking for your router..." : "Show steps for my router"}
+                          </button>
+                        )}
                       </div>
                     </>
                   ) : (
@@ -1240,6 +3141,40 @@ function App() {
                   )}
                 </div>
 
+                {/* IPv6, including Teredo/6to4 tunnels through the NAT */}
+                <div className="bg-gray-900/50 rounded-md p-4 border border-gray-700">
+                  <h3 className="text-sm font-medium text-gray-400 mb-2">IPv6 (Alternative Path)</h3>
+                  {networkInfo.ipv6 && networkInfo.ipv6_url ? (
+                    <>
+                      <div className="flex items-center gap-2">
+                        <code className="flex-1 bg-gray-900 px-3 py-2 rounded text-purple-400 font-mono text-sm break-all">
+                          {networkInfo.ipv6_url}
+                        </code>
+                        <button
+                          onClick={() => navigator.clipboard.writeText(networkInfo.ipv6_url!)}
+                          className="p-2 text-gray-400 hover:text-white hover:bg-gray-700 rounded-md"
+                          title="Copy"
+                        >
+                          <Copy className="w-4 h-4" />
+                        </button>
+                      </div>
+                      <p className="text-xs text-gray-500 mt-1">
+                        {networkInfo.ipv6.kind === "teredo"
+                          ? "Teredo tunnel"
+                          : networkInfo.ipv6.kind === "six_to_four"
+                            ? "6to4 tunnel"
+                            : "Native IPv6"}
+                        {" · "}
+                        {networkInfo.ipv6.reachable
+                          ? "the server answers on this address; participants need IPv6 too"
+                          : "the server didn't answer on this address"}
+                      </p>
+                    </>
+                  ) : (
+                    <p className="text-gray-500 text-sm">No routable IPv6 address</p>
+                  )}
+                </div>
+
                 {/* Firewall Status */}
                 <div className="bg-gray-900/50 rounded-md p-4 border border-gray-700">
                   <div className="flex items-center justify-between">
@@ -1326,6 +3261,40 @@ function App() {
               </div>
             )}
 
+            {selectedBoard && (
+              <div className="mb-4 flex flex-wrap items-center gap-3 text-sm">
+                <select
+                  value={issueFilter.status_categories.join(",")}
+                  onChange={(e) => updateIssueFilter({
+                    status_categories: e.target.value ? e.target.value.split(",") : [],
+                  })}
+                  className="px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-white"
+                >
+                  <option value="">All statuses</option>
+                  <option value="To Do">To Do</option>
+                  <option value="To Do,In Progress">Not done</option>
+                  <option value="In Progress">In Progress</option>
+                </select>
+                <input
+                  type="text"
+                  placeholder="Labels (comma separated)"
+                  defaultValue={issueFilter.labels.join(", ")}
+                  onBlur={(e) => updateIssueFilter({
+                    labels: e.target.value.split(",").map((l) => l.trim()).filter(Boolean),
+                  })}
+                  className="px-2 py-1 bg-gray-700 border border-gray-600 rounded-md text-white placeholder-gray-400"
+                />
+                <label className="flex items-center gap-2 text-gray-300">
+                  <input
+                    type="checkbox"
+                    checked={issueFilter.only_unestimated}
+                    onChange={(e) => updateIssueFilter({ only_unestimated: e.target.checked })}
+                  />
+                  Only unestimated
+                </label>
+              </div>
+            )}
+
             <div className="flex-1 overflow-y-auto">
               {isLoadingJira ? (
                 <div className="flex items-center justify-center py-12">
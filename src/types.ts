@@ -6,6 +6,57 @@ export interface JiraTicket {
   issue_type: string | null;
   status: string | null;
   url: string;
+  /** Size hints from the description, shown before voting */
+  complexity?: ComplexityHints | null;
+  /** Size of the change, for GitHub pull requests */
+  diff_stats?: DiffStats | null;
+}
+
+/** Lines and files a pull request touches */
+export interface DiffStats {
+  additions: number;
+  deletions: number;
+  changed_files: number;
+  commits: number;
+}
+
+/** Heuristic signals about a ticket's size */
+export interface ComplexityHints {
+  level: "low" | "medium" | "high";
+  word_count: number;
+  acceptance_criteria: number;
+  linked_issues: number;
+  has_code_blocks: boolean;
+}
+
+/** How far a re-estimated ticket moved from its old estimate */
+export interface Drift {
+  ticket_key: string;
+  previous: number;
+  estimate: number;
+  /** estimate - previous */
+  drift: number;
+  /** Whether the new estimate has been written back to Jira */
+  written: boolean;
+}
+
+/** Re-estimation of previously estimated tickets */
+export interface Campaign {
+  /** Story points field the old estimates came from */
+  field: string;
+  /** Old estimates by ticket key; only the host gets these */
+  previous: Record<string, number>;
+  results: Drift[];
+}
+
+/** How much of the sprint the session's estimates take up */
+export interface SprintFill {
+  capacity: number;
+  committed: number;
+  tickets: number;
+  /** committed / capacity */
+  fraction: number;
+  overcommitted: boolean;
 }
 
 /** Participant in a room */
@@ -13,7 +64,23 @@ export interface Participant {
   id: string;
   name: string;
   vote: string | null;
+  /** Vote on the room's risk deck, if it has one */
+  risk_vote: string | null;
   is_host: boolean;
+  /** Client details, present only if the participant opted in */
+  client?: ClientInfo;
+  /** Unix seconds when their connection dropped; they're kept for a grace period */
+  disconnected_at?: number;
+  /** Sitting out of the voting for now; not counted towards the quorum */
+  sitting_out?: boolean;
+  /** Joined only to watch; never votes and isn't counted among the voters */
+  is_observer?: boolean;
+}
+
+/** Coarse client details shared with the host for troubleshooting */
+export interface ClientInfo {
+  kind: "browser" | "desktop" | "tui" | "other";
+  user_agent_family: string | null;
 }
 
 /** Scrum poker room */
@@ -22,9 +89,75 @@ export interface Room {
   name: string;
   participants: Participant[];
   votes_revealed: boolean;
+  /** When to flip the cards over (Unix ms), so every client does it together */
+  reveal_at?: number | null;
+  /** Participants whose votes have been shown so far in a one-by-one reveal */
+  reveal_progress?: string[] | null;
+  /** Votes handed to someone else this round: delegator ID -> delegate ID */
+  delegations?: Record<string, string>;
+  /** Concerns participants flagged about the current ticket, keyed by participant ID */
+  signals?: Record<string, TicketSignal[]>;
   created_at: number;
   invite_code: string;
   current_ticket: JiraTicket | null;
+  /** Tickets voted on together in a triage round, with one vote for all; empty otherwise */
+  batch?: JiraTicket[];
+  /** Re-estimation campaign; null outside one */
+  campaign: Campaign | null;
+  /** Hash of the current ticket; updates leave the ticket out and only send this */
+  ticket_hash?: string | null;
+  /** Notable events in the session, oldest first */
+  history: HistoryEntry[];
+  /** Cards participants may vote with, including ABSTAIN when it's offered */
+  deck: string[];
+  /** The room's own deck while the current ticket's issue type deck is in use */
+  switched_from_deck: string[] | null;
+  /** Cards for the second (risk/uncertainty) vote; null when the room only votes on effort */
+  risk_deck: string[] | null;
+  /** No new participants may join while set */
+  locked: boolean;
+  /** Seconds a dropped participant is kept before being removed; 0 removes them at once */
+  disconnect_grace_secs: number;
+  /** The story is being discussed; nobody can vote until the host opens voting */
+  discussing: boolean;
+  /** When voting opens after a discussion (Unix ms), counted down on the clients */
+  voting_opens_at: number | null;
+  /** Participants don't see who has voted until everyone has or the votes are revealed */
+  hide_voters: boolean;
+  /** Announced start of voting (Unix seconds); early joiners see a countdown */
+  starts_at?: number | null;
+  /** What happens in Jira to tickets estimated at or above a threshold; null uses the settings' rule */
+  estimate_rule?: EstimateRule | null;
+  /** Zone the room's times are shown in */
+  time_zone: RoomTimeZone;
+  /** Reveal the votes automatically once the quorum rules are met */
+  auto_reveal: boolean;
+  reveal_mode: RevealMode;
+  quorum: QuorumRules;
+  /** Whether the quorum rules are currently met */
+  can_reveal: boolean;
+  /** Points the team can take on this sprint; null when not tracked */
+  sprint_capacity: number | null;
+  sprint_fill: SprintFill | null;
+  /** Minutes allowed per ticket key before an unestimated ticket is parked */
+  ticket_deadlines: Record<string, number>;
+  /** When the current ticket gets parked (Unix seconds), if it has a deadline */
+  ticket_deadline_at: number | null;
+  /** Tickets skipped for running past their deadline */
+  parked_tickets: JiraTicket[];
+  /** Tickets waiting to be estimated, in order */
+  ticket_queue: JiraTicket[];
+}
+
+/** Preconditions for revealing the votes */
+/** Whether revealing flips every card at once or shows them one by one */
+export type RevealMode = "all_at_once" | "timed" | "host_paced";
+
+export interface QuorumRules {
+  /** Share of participants (1-100) who must have voted; null means everyone */
+  min_voted_percent: number | null;
+  /** Participants, by name, whose vote is always needed */
+  required_voters: string[];
 }
 
 /** Vote summary statistics */
@@ -32,6 +165,120 @@ export interface VoteSummary {
   total_voters: number;
   voted_count: number;
   average: number | null;
+  /** Card suggested by the room's estimation method */
+  suggested: string | null;
+  risk_voted_count: number;
+  risk_average: number | null;
+}
+
+/** Formula used for a room's suggested estimate */
+export type EstimationMethod = "mean_snap" | "median" | "trimmed_mean" | "delphi";
+
+/** Why the relay rejected a host request */
+export type RelayErrorCode =
+  | "host_already_registered"
+  | "room_limit_reached"
+  | "protocol_version_mismatch"
+  | "unauthorized"
+  | "other";
+
+/** Payload of the `relay-error` event */
+export interface RelayError {
+  code: RelayErrorCode;
+  message: string;
+  /** What the user can do about it */
+  remediation: string;
+}
+
+/** Session the app crashed in last time, offered for recovery */
+export interface RecoveryInfo {
+  /** Unix seconds */
+  crashed_at: number;
+  rooms: number;
+}
+
+/** The scale a room votes with, chosen when it's created */
+export type DeckDefinition =
+  | { type: "fibonacci" }
+  | { type: "t_shirt" }
+  | { type: "powers_of_two" }
+  | { type: "custom"; cards: string[] };
+
+/** Text formats the last reveal can be summarized in */
+export type SummaryFormat = "markdown" | "jira_wiki" | "plain_text";
+
+export type OnboardingStep = "server_started" | "network_reachable" | "jira_connected" | "relay_tested";
+
+export interface OnboardingStepState {
+  step: OnboardingStep;
+  status: "pending" | "passed" | "failed" | "skipped";
+  /** What the last check found, or why it failed */
+  detail: string | null;
+  /** Unix seconds */
+  checked_at: number | null;
+}
+
+/** First-run setup progress */
+export interface OnboardingState {
+  steps: OnboardingStepState[];
+  /** The first step not yet passed or skipped */
+  current: OnboardingStep | null;
+  complete: boolean;
+}
+
+/** Result of checking a release channel for a newer version */
+export interface UpdateInfo {
+  channel: "stable" | "beta";
+  current_version: string;
+  latest_version: string;
+  available: boolean;
+  /** Changelog of the latest release */
+  notes: string | null;
+  pub_date: string | null;
+  download_url: string | null;
+  /** Held back because a session is under way */
+  deferred: boolean;
+}
+
+/** How a room was reconciled with the relay's copy after reconnecting; values are participant names */
+export interface MergeReport {
+  room_id: string;
+  joined_via_relay: string[];
+  kept_local: string[];
+  removed: string[];
+  votes_from_relay: string[];
+  votes_kept_local: string[];
+}
+
+/** A revealed round found by a session search */
+export interface RoundResult {
+  ticket_key: string | null;
+  /** Unix seconds */
+  revealed_at: number;
+  average: number | null;
+  votes: { name: string; vote: string }[];
+  /** The host's tags on the round, e.g. "needs spike" */
+  tags: string[];
+}
+
+/** Async (emailed ballot) votes on one ticket */
+export interface AsyncTicketResult {
+  ticket_key: string;
+  summary: string;
+  /** [voter name, card] pairs */
+  votes: [string, string][];
+  waiting_for: string[];
+}
+
+/** A past (or running) session matching a search */
+export interface SessionResult {
+  room_id: string;
+  room_name: string;
+  /** Unix seconds */
+  started_at: number;
+  ended_at: number | null;
+  participants: string[];
+  rounds: RoundResult[];
 }
 
 /** Story point values */
@@ -41,8 +288,66 @@ export const STORY_POINTS = ["?", "☕", "0", "0.5", "1", "2", "3", "5", "8", "1
 export type WsMessage =
   | { type: "Join"; payload: { room_id: string; name: string } }
   | { type: "Vote"; payload: { vote: string | null } }
+  | { type: "RiskVote"; payload: { vote: string | null } }
   | { type: "RoomUpdate"; payload: { room: Room } }
+  | { type: "VoteRevealed"; payload: { participant_id: string; vote: string | null } }
+  | { type: "TicketChanged"; payload: { ticket: JiraTicket | null; ticket_hash: string | null } }
   | { type: "Error"; payload: { message: string } }
   | { type: "Kicked" }
   | { type: "Ping" }
   | { type: "Pong" };
+
+/** IANA time zone a room's times are shown in */
+export interface RoomTimeZone {
+  name: string;
+}
+
+/** Card for sitting a ticket out on purpose; counts toward the quorum but not the average */
+export const ABSTAIN = "abstain";
+
+/** A session history event; only the fields the app reads are typed */
+export interface HistoryEntry {
+  /** Unix seconds */
+  timestamp: number;
+  type: string;
+  ticket_key?: string | null;
+  /** Host tags, on "votes_revealed" entries */
+  tags?: string[];
+  /** How many participants raised each signal, on "votes_revealed" entries */
+  signals?: Partial<Record<TicketSignal, number>>;
+  /** The host's card and reason, on "estimate_overridden" entries */
+  value?: string;
+  reason?: string;
+}
+
+/** A concern a participant can flag about the ticket being estimated */
+export type TicketSignal = "unclear_requirements" | "needs_design" | "security_review";
+
+export const TICKET_SIGNAL_LABELS: Record<TicketSignal, string> = {
+  unclear_requirements: "Unclear requirements",
+  needs_design: "Needs design",
+  security_review: "Security review",
+};
+
+/** Tags offered for a revealed round; any other text works too */
+export const ROUND_TAGS = ["accepted", "needs spike", "split"];
+
+/** Label or flag a Jira ticket once its estimate reaches the threshold */
+export interface EstimateRule {
+  threshold: number;
+  action: { type: "label"; label: string } | { type: "flag" };
+}
+
+/** Manual port-forwarding steps for when UPnP fails */
+export interface PortForwardInstructions {
+  vendor: string | null;
+  /** Display name of the router maker, e.g. "TP-Link" */
+  vendor_name: string | null;
+  detected_from: "upnp" | "mac_address" | null;
+  gateway_ip: string | null;
+  admin_url: string | null;
+  internal_ip: string;
+  port: number;
+  protocol: string;
+  steps: string[];
+}
@@ -1,12 +1,23 @@
 import { useEffect, useState, useRef, useCallback } from "react";
-import { Users, LogOut, Check, Ticket, ExternalLink, Spade } from "lucide-react";
-import type { Room } from "./types";
-import { STORY_POINTS } from "./types";
+import { Users, LogOut, Check, Copy, Ticket, ExternalLink, Spade } from "lucide-react";
+import type { ComplexityHints, DiffStats, JiraTicket, Room, TicketSignal } from "./types";
+import { ABSTAIN, STORY_POINTS, TICKET_SIGNALS } from "./types";
+
+/** Token identifying this browser in a room, so a refresh or dropped connection rejoins as the same participant */
+function sessionIdentity(roomId: string): string {
+  const key = `scrum-poker-identity:${roomId}`;
+  let identity = localStorage.getItem(key);
+  if (!identity) {
+    identity = crypto.randomUUID();
+    localStorage.setItem(key, identity);
+  }
+  return identity;
+}
 
 /** Get chip color based on value */
 function getChipColor(value: string): string {
   const numValue = parseFloat(value);
-  if (value === "?" || value === "☕") return "chip-white";
+  if (value === "?" || value === "☕" || value === ABSTAIN) return "chip-white";
   if (numValue === 0) return "chip-black";
   if (numValue <= 1) return "chip-blue";
   if (numValue <= 3) return "chip-green";
@@ -49,36 +60,206 @@ function PokerChip({ value, selected, onClick, revealed, mini }: PokerChipProps)
   );
 }
 
+/** Short description of a ticket's complexity hints, e.g. "Medium complexity · 3 acceptance criteria" */
+function describeComplexity(hints: ComplexityHints): string {
+  const parts = [`${hints.level[0].toUpperCase()}${hints.level.slice(1)} complexity`];
+  if (hints.acceptance_criteria > 0) parts.push(`${hints.acceptance_criteria} acceptance criteria`);
+  if (hints.linked_issues > 0) parts.push(`${hints.linked_issues} linked issue${hints.linked_issues === 1 ? "" : "s"}`);
+  if (hints.has_code_blocks) parts.push("code in description");
+  parts.push(`${hints.word_count} words`);
+  return parts.join(" · ");
+}
+
+/** Short description of a pull request's size, e.g. "+120 −30 · 4 files" */
+function describeDiffStats(stats: DiffStats): string {
+  return `+${stats.additions} −${stats.deletions} · ${stats.changed_files} file${stats.changed_files === 1 ? "" : "s"}`;
+}
+
+/** Ticket description with its links made clickable */
+function LinkedText({ text }: { text: string }) {
+  // Splitting on a capturing group puts the URLs at the odd indexes
+  const parts = text.split(/(https?:\/\/[^\s()]+)/);
+  return (
+    <>
+      {parts.map((part, i) =>
+        i % 2 === 1 ? (
+          <a key={i} href={part} target="_blank" rel="noreferrer" className="text-blue-400 underline break-all">
+            {part}
+          </a>
+        ) : (
+          part
+        ),
+      )}
+    </>
+  );
+}
+
 type AppState = "join" | "lobby";
 
+/** "starts in 4 minutes", from a number of seconds */
+function describeCountdown(secs: number): string {
+  if (secs < 60) return "starts in less than a minute";
+  const minutes = Math.round(secs / 60);
+  if (minutes < 60) return `starts in ${minutes} minute${minutes === 1 ? "" : "s"}`;
+  const hours = Math.floor(minutes / 60);
+  const rest = minutes % 60;
+  return `starts in ${hours}h${rest > 0 ? ` ${rest}m` : ""}`;
+}
+
+/**
+ * Seconds until the room's announced start, counting down locally from what the
+ * server said (it works the countdown out on its own clock), and the start time
+ * in the room's time zone. Null when voting has no announced start or has
+ * already started.
+ */
+function useStartCountdown(roomId: string): { secs: number; local: string | null } | null {
+  const [startsAt, setStartsAt] = useState<number | null>(null);
+  const [local, setLocal] = useState<string | null>(null);
+  const [now, setNow] = useState(Date.now());
+
+  useEffect(() => {
+    if (!roomId) return;
+    fetch(`/api/room/${encodeURIComponent(roomId)}`)
+      .then((response) => (response.ok ? response.json() : null))
+      .then((room) => {
+        if (room?.starts_in_secs != null) {
+          setStartsAt(Date.now() + room.starts_in_secs * 1000);
+          setLocal(room.starts_at_local ?? null);
+        }
+      })
+      .catch((err) => console.error("Failed to fetch room:", err));
+  }, [roomId]);
+
+  useEffect(() => {
+    if (startsAt === null) return;
+    const timer = setInterval(() => setNow(Date.now()), 1000);
+    return () => clearInterval(timer);
+  }, [startsAt]);
+
+  if (startsAt === null || startsAt <= now) return null;
+  return { secs: Math.ceil((startsAt - now) / 1000), local };
+}
+
+/** Clock readings taken on connecting; the one with the shortest round trip is used */
+const TIME_SYNC_SAMPLES = 5;
+const TIME_SYNC_SPACING_MS = 200;
+
+/** How far the server's clock is ahead of this device's, as measured by one time_sync */
+interface ClockSample {
+  offset: number;
+  roundTrip: number;
+}
+
+/**
+ * Work out the clock offset from a time_sync reply, taking the server to have
+ * read its clock halfway through the round trip.
+ */
+function clockSample(clientTime: number, serverTime: number, receivedAt: number): ClockSample {
+  const roundTrip = Math.max(receivedAt - clientTime, 0);
+  return { offset: serverTime + roundTrip / 2 - receivedAt, roundTrip };
+}
+
+/** Seconds until voting opens after a discussion, or null once it's open */
+function useVotingCountdown(room: Room | null, clockOffset: number): number | null {
+  const opensAt = room?.voting_opens_at ?? null;
+  const [now, setNow] = useState(Date.now() + clockOffset);
+
+  useEffect(() => {
+    if (opensAt === null) return;
+    setNow(Date.now() + clockOffset);
+    const timer = setInterval(() => setNow(Date.now() + clockOffset), 250);
+    return () => clearInterval(timer);
+  }, [opensAt, clockOffset]);
+
+  if (opensAt === null || opensAt <= now) return null;
+  return Math.ceil((opensAt - now) / 1000);
+}
+
+/** Longest we'll hold back a reveal, in case this device's clock is off */
+const MAX_REVEAL_WAIT_MS = 1000;
+
+/**
+ * Whether to show the votes face up. Waits for the room's `reveal_at` so every
+ * client flips the cards at the same moment, whatever its latency.
+ */
+function useCardsFlipped(room: Room | null, clockOffset: number): boolean {
+  const [flipped, setFlipped] = useState(false);
+  const revealed = room?.votes_revealed ?? false;
+  const revealAt = room?.reveal_at ?? null;
+
+  useEffect(() => {
+    if (!revealed) {
+      setFlipped(false);
+      return;
+    }
+    const wait = revealAt === null ? 0 : Math.min(Math.max(revealAt - (Date.now() + clockOffset), 0), MAX_REVEAL_WAIT_MS);
+    if (wait === 0) {
+      setFlipped(true);
+      return;
+    }
+    const timer = setTimeout(() => setFlipped(true), wait);
+    return () => clearTimeout(timer);
+  }, [revealed, revealAt, clockOffset]);
+
+  return flipped;
+}
+
 function App() {
   const [appState, setAppState] = useState<AppState>("join");
   const [roomId, setRoomId] = useState<string>("");
   const [room, setRoom] = useState<Room | null>(null);
   const [participantId, setParticipantId] = useState<string>("");
   const [userName, setUserName] = useState("");
+  const [shareClientInfo, setShareClientInfo] = useState(false);
+  const [observing, setObserving] = useState(false);
   const [selectedVote, setSelectedVote] = useState<string | null>(null);
+  const [selectedRisk, setSelectedRisk] = useState<string | null>(null);
   const [error, setError] = useState<string>("");
   const [isConnecting, setIsConnecting] = useState(false);
+  const [summaryCopied, setSummaryCopied] = useState(false);
   const wsRef = useRef<WebSocket | null>(null);
   const reconnectTimeoutRef = useRef<number | null>(null);
+  const lastRevisionRef = useRef<number | null>(null);
+  // Room timestamps are on the server's clock; this is how far ahead of ours it is
+  const clockSampleRef = useRef<ClockSample | null>(null);
+  const [clockOffset, setClockOffset] = useState(0);
+  // Updates leave the ticket out; we keep the last one we got, by hash
+  const ticketRef = useRef<{ hash: string | null; ticket: JiraTicket | null }>({ hash: null, ticket: null });
+  const startsIn = useStartCountdown(roomId);
 
   // Extract room ID from URL path
   useEffect(() => {
     const path = window.location.pathname;
-    const match = path.match(/\/join\/([^/]+)/);
+    const match = path.match(/\/(?:join|r)\/([^/]+)/);
     if (match) {
       setRoomId(match[1]);
     }
   }, []);
 
+  // Fetch the room's ticket after an update with a hash we haven't seen
+  const fetchTicket = useCallback(async (id: string, hash: string | null) => {
+    ticketRef.current = { hash, ticket: null };
+    if (hash === null) return;
+    try {
+      const response = await fetch(`/api/room/${encodeURIComponent(id)}/ticket`);
+      if (!response.ok) return;
+      const { ticket, ticket_hash } = await response.json();
+      // Ignore the answer if the ticket changed again meanwhile
+      if (ticketRef.current.hash !== hash) return;
+      ticketRef.current = { hash: ticket_hash, ticket };
+      setRoom(prev => (prev ? { ...prev, current_ticket: ticket, ticket_hash } : prev));
+    } catch (err) {
+      console.error("Failed to fetch ticket:", err);
+    }
+  }, []);
+
   // WebSocket connection management
   const connectWebSocket = useCallback(() => {
     if (wsRef.current?.readyState === WebSocket.OPEN) return;
     
-    // Use wss:// for https, ws:// for http
+    // Use wss:// for https, ws:// for http; the connection is bound to the room in the path
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    const wsUrl = `${protocol}//${window.location.host}`;
+    const wsUrl = `${protocol}//${window.location.host}/ws/${encodeURIComponent(roomId)}`;
     const ws = new WebSocket(wsUrl);
     
     ws.onopen = () => {
@@ -89,8 +270,30 @@ function App() {
       ws.send(JSON.stringify({
         type: "join",
         room_id: roomId,
-        name: userName
+        name: userName,
+        identity: sessionIdentity(roomId),
+        // Only sent if the participant agreed; the server works out the browser family
+        ...(shareClientInfo ? { client: { kind: "browser", user_agent_family: null } } : {}),
+        ...(observing ? { observer: true } : {}),
       }));
+
+      // A new connection may be to another server; measure its clock afresh
+      clockSampleRef.current = null;
+      for (let i = 0; i < TIME_SYNC_SAMPLES; i++) {
+        window.setTimeout(() => {
+          if (ws.readyState === WebSocket.OPEN) {
+            ws.send(JSON.stringify({ type: "time_sync", client_time: Date.now() }));
+          }
+        }, i * TIME_SYNC_SPACING_MS);
+      }
+    };
+
+    // Until a time_sync reply comes back, take the server's clock as it was
+    // when it sent a room, ignoring the transit time
+    const estimateClock = (serverTime: unknown) => {
+      if (clockSampleRef.current === null && typeof serverTime === "number" && serverTime > 0) {
+        setClockOffset(serverTime - Date.now());
+      }
     };
 
     ws.onmessage = (event) => {
@@ -103,11 +306,33 @@ function App() {
             // Initial join confirmation
             console.log("Joined room:", message.room);
             setParticipantId(message.participant_id);
+            ticketRef.current = { hash: message.room.ticket_hash ?? null, ticket: message.room.current_ticket ?? null };
             setRoom(message.room);
+            estimateClock(message.server_time);
+            break;
+          case "ticket_changed":
+            ticketRef.current = { hash: message.ticket_hash, ticket: message.ticket };
+            setRoom(prev => (prev ? { ...prev, current_ticket: message.ticket, ticket_hash: message.ticket_hash } : prev));
             break;
-          case "room_update":
+          case "room_update": {
             console.log("Room update received:", message.room);
-            setRoom(message.room);
+            // Updates are full snapshots, but a skipped revision means we missed something
+            const revision = message.room.revision;
+            const last = lastRevisionRef.current;
+            if (typeof revision === "number") {
+              if (last !== null && revision > last + 1) {
+                console.warn(`Missed room updates (${last} -> ${revision}), requesting resync`);
+                ws.send(JSON.stringify({ type: "resync" }));
+              }
+              lastRevisionRef.current = revision;
+            }
+            const ticketHash: string | null = message.room.ticket_hash ?? null;
+            if (ticketHash !== ticketRef.current.hash) {
+              // Missed the ticket_changed message; fetch the ticket instead
+              fetchTicket(message.room.id, ticketHash);
+            }
+            setRoom({ ...message.room, current_ticket: ticketHash === ticketRef.current.hash ? ticketRef.current.ticket : null });
+            estimateClock(message.server_time);
             // Find our vote
             const me = message.room.participants.find(
               (p: any) => p.id === participantId || p.name === userName
@@ -115,19 +340,37 @@ function App() {
             if (me) {
               if (!participantId) setParticipantId(me.id);
               setSelectedVote(me.vote);
+              setSelectedRisk(me.risk_vote ?? null);
             }
             break;
+          }
+          case "vote_revealed":
+            // One more card of a one-by-one reveal
+            setRoom(prev => (prev ? { ...prev, reveal_progress: [...(prev.reveal_progress ?? []), message.participant_id] } : prev));
+            break;
           case "error":
-            console.error("Server error:", message.message);
+            console.error("Server error:", message.code, message.message);
             setError(message.message);
-            setAppState("join");
-            wsRef.current?.close();
+            // A rejected vote or hand-off doesn't end the session; everything else sends us back to the join form
+            if (!["invalid_vote", "invalid_delegation", "voting_closed"].includes(message.code)) {
+              setAppState("join");
+              wsRef.current?.close();
+            }
             break;
           case "kicked":
             setError("You have been removed from the room");
             setAppState("join");
             wsRef.current?.close();
             break;
+          case "time_sync_reply": {
+            const sample = clockSample(message.client_time, message.server_time, Date.now());
+            const best = clockSampleRef.current;
+            if (best === null || sample.roundTrip <= best.roundTrip) {
+              clockSampleRef.current = sample;
+              setClockOffset(Math.round(sample.offset));
+            }
+            break;
+          }
           case "pong":
             // Keepalive response
             break;
@@ -153,7 +396,7 @@ function App() {
     };
 
     wsRef.current = ws;
-  }, [roomId, userName, appState]);
+  }, [roomId, userName, appState, shareClientInfo, observing, fetchTicket]);
 
   // Cleanup on unmount
   useEffect(() => {
@@ -199,22 +442,91 @@ function App() {
     }
   };
 
+  const handleRiskVote = (value: string) => {
+    const newVote = selectedRisk === value ? null : value;
+    setSelectedRisk(newVote);
+
+    if (wsRef.current?.readyState === WebSocket.OPEN) {
+      wsRef.current.send(JSON.stringify({
+        type: "risk_vote",
+        vote: newVote
+      }));
+    }
+  };
+
+  const handleDelegate = (to: string | null) => {
+    if (to) setSelectedVote(null);
+
+    if (wsRef.current?.readyState === WebSocket.OPEN) {
+      wsRef.current.send(JSON.stringify({
+        type: "delegate",
+        to
+      }));
+    }
+  };
+
+  const handleSignal = (signal: TicketSignal, raised: boolean) => {
+    if (wsRef.current?.readyState === WebSocket.OPEN) {
+      wsRef.current.send(JSON.stringify({
+        type: "signal",
+        signal,
+        raised
+      }));
+    }
+  };
+
+  const handleSitOut = (sittingOut: boolean) => {
+    if (sittingOut) setSelectedVote(null);
+
+    if (wsRef.current?.readyState === WebSocket.OPEN) {
+      wsRef.current.send(JSON.stringify({
+        type: "sit_out",
+        sitting_out: sittingOut
+      }));
+    }
+  };
+
   const handleLeave = () => {
     wsRef.current?.close();
     setAppState("join");
     setRoom(null);
     setSelectedVote(null);
+    setSelectedRisk(null);
     setParticipantId("");
   };
 
+  const cardsFlipped = useCardsFlipped(room, clockOffset);
+  const votingCountdown = useVotingCountdown(room, clockOffset);
+  // Re-estimation campaigns show the old estimate once the new one is in
+  const currentDrift = room?.campaign?.results.find((d) => d.ticket_key === room.current_ticket?.key);
+  const votingClosed = (room?.discussing ?? false) || votingCountdown !== null;
   const currentParticipant = room?.participants.find((p) => p.id === participantId);
+  const isObserver = currentParticipant?.is_observer ?? false;
   const otherParticipants = room?.participants.filter((p) => p.id !== participantId) ?? [];
-  const votedCount = room?.participants.filter((p) => p.vote !== null).length ?? 0;
-  const totalParticipants = room?.participants.length ?? 0;
+  const otherVoters = otherParticipants.filter((p) => !p.is_observer);
+  const delegations = room?.delegations ?? {};
+  const nameOf = (id: string) => room?.participants.find((p) => p.id === id)?.name ?? "someone";
+  const myDelegate = participantId ? delegations[participantId] : undefined;
+  const votedCount = room?.participants.filter((p) => p.vote !== null || delegations[p.id]).length ?? 0;
+  const totalParticipants = room?.participants.filter((p) => !p.sitting_out && !p.is_observer).length ?? 0;
+
+  // The host formats the summary so it reads the same whichever client copies it
+  const copyRoundSummary = async () => {
+    if (!roomId) return;
+    try {
+      const response = await fetch(`/api/room/${encodeURIComponent(roomId)}/summary?format=markdown`);
+      if (!response.ok) throw new Error(await response.text());
+      await navigator.clipboard.writeText(await response.text());
+      setSummaryCopied(true);
+      setTimeout(() => setSummaryCopied(false), 2000);
+    } catch (err) {
+      console.error("Failed to copy the round summary:", err);
+    }
+  };
 
   // Calculate stats when revealed
   const getStats = () => {
-    if (!room?.votes_revealed) return null;
+    if (!room || !cardsFlipped) return null;
     
     const numericVotes = room.participants
       .map((p) => p.vote)
@@ -224,10 +536,18 @@ function App() {
 
     if (numericVotes.length === 0) return null;
 
+    const riskVotes = room.participants
+      .map((p) => parseFloat(p.risk_vote ?? ""))
+      .filter((v) => !isNaN(v));
+
     return {
       avg: (numericVotes.reduce((a, b) => a + b, 0) / numericVotes.length).toFixed(1),
       min: Math.min(...numericVotes),
       max: Math.max(...numericVotes),
+      abstained: room.participants.filter((p) => p.vote === ABSTAIN).length,
+      risk: riskVotes.length > 0
+        ? (riskVotes.reduce((a, b) => a + b, 0) / riskVotes.length).toFixed(1)
+        : null,
     };
   };
 
@@ -244,6 +564,12 @@ function App() {
             </div>
             <h1 className="text-3xl font-bold text-white mb-2">Scrum Poker</h1>
             <p className="text-gray-400">Place your bets on story points</p>
+            {startsIn !== null && (
+              <p className="mt-3 text-amber-300 text-sm font-medium">
+                Voting {describeCountdown(startsIn.secs)}
+                {startsIn.local && <span className="block text-xs text-gray-400">{startsIn.local}</span>}
+              </p>
+            )}
           </div>
 
           {error && (
@@ -270,6 +596,24 @@ function App() {
               />
             </div>
 
+            <label className="flex items-center gap-2 text-sm text-gray-400">
+              <input
+                type="checkbox"
+                checked={shareClientInfo}
+                onChange={(e) => setShareClientInfo(e.target.checked)}
+              />
+              Share my browser type with the host (helps them troubleshoot)
+            </label>
+
+            <label className="flex items-center gap-2 text-sm text-gray-400">
+              <input
+                type="checkbox"
+                checked={observing}
+                onChange={(e) => setObserving(e.target.checked)}
+              />
+              Just watching: join as an observer, without voting
+            </label>
+
             <button
               onClick={handleJoin}
               disabled={!userName.trim() || !roomId || isConnecting}
@@ -303,6 +647,20 @@ function App() {
           </div>
 
           <div className="flex items-center gap-4">
+            {room?.sprint_fill && (
+              <div
+                className={`text-sm ${room.sprint_fill.overcommitted ? "text-red-400" : "text-gray-300"}`}
+                title={room.sprint_fill.overcommitted ? "The estimates so far add up to more than the sprint's capacity" : "Points estimated so far against the sprint's capacity"}
+              >
+                Sprint {room.sprint_fill.committed}/{room.sprint_fill.capacity} pts
+                <div className="w-24 h-1.5 mt-1 bg-gray-700 rounded-full overflow-hidden">
+                  <div
+                    className={`h-full ${room.sprint_fill.overcommitted ? "bg-red-500" : "bg-green-500"}`}
+                    style={{ width: `${Math.min(room.sprint_fill.fraction, 1) * 100}%` }}
+                  />
+                </div>
+              </div>
+            )}
             <div className="flex items-center gap-2 text-sm text-gray-300">
               <Users className="w-4 h-4" />
               <span>{votedCount}/{totalParticipants} voted</span>
@@ -345,16 +703,55 @@ function App() {
                     {room.current_ticket.status}
                   </span>
                 )}
+                {!room.votes_revealed && room.current_ticket.complexity && (
+                  <span className="px-2 py-0.5 bg-amber-600/30 border border-amber-600/50 rounded text-xs text-amber-300" title="Estimated from the description and linked issues">
+                    {describeComplexity(room.current_ticket.complexity)}
+                  </span>
+                )}
+                {room.current_ticket.diff_stats && (
+                  <span className="px-2 py-0.5 bg-green-600/30 border border-green-600/50 rounded text-xs text-green-300" title={`${room.current_ticket.diff_stats.commits} commits`}>
+                    {describeDiffStats(room.current_ticket.diff_stats)}
+                  </span>
+                )}
               </div>
               <h3 className="text-lg font-bold text-white mb-3">
                 {room.current_ticket.summary}
               </h3>
               {room.current_ticket.description && (
                 <div className="text-gray-300 text-sm whitespace-pre-wrap bg-gray-900/60 rounded-md p-4 max-h-[50vh] overflow-y-auto border border-gray-700/50">
-                  {room.current_ticket.description}
+                  <LinkedText text={room.current_ticket.description} />
+                </div>
+              )}
+              {!room.votes_revealed && (
+                <div className="flex flex-wrap items-center gap-2 mt-3 text-xs">
+                  <span className="text-gray-400">Flag for the host:</span>
+                  {TICKET_SIGNALS.map(({ signal, label }) => {
+                    const raised = !!room.signals?.[participantId]?.includes(signal);
+                    return (
+                      <button
+                        key={signal}
+                        onClick={() => handleSignal(signal, !raised)}
+                        className={`px-2 py-1 rounded-md border ${raised ? "bg-amber-600/30 border-amber-500 text-amber-200" : "border-gray-600 text-gray-300 hover:bg-gray-700"}`}
+                      >
+                        {label}
+                      </button>
+                    );
+                  })}
                 </div>
               )}
             </div>
+          ) : room?.batch && room.batch.length > 0 ? (
+            <div className="bg-blue-900/30 backdrop-blur rounded-md p-4 border border-blue-700 flex-1">
+              <h3 className="text-lg font-bold text-white mb-1">Triage: {room.batch.length} tickets</h3>
+              <p className="text-sm text-gray-400 mb-3">One vote applies to all of these</p>
+              <ul className="space-y-1">
+                {room.batch.map((ticket) => (
+                  <li key={ticket.key} className="text-sm text-gray-300">
+                    <span className="font-mono text-blue-400">{ticket.key}</span> {ticket.summary}
+                  </li>
+                ))}
+              </ul>
+            </div>
           ) : (
             <div className="bg-gray-900/40 backdrop-blur rounded-md p-8 border border-gray-700 flex-1 flex items-center justify-center">
               <div className="text-center text-gray-500">
@@ -421,7 +818,7 @@ function App() {
           {/* Chip Selection */}
           <div className="bg-gray-900/40 backdrop-blur rounded-md p-4 border border-gray-700 flex-1">
             <div className="text-center mb-4">
-              {room?.votes_revealed ? (
+              {cardsFlipped ? (
                 <div className="space-y-1">
                   <h2 className="text-xl font-bold text-white">Votes Revealed!</h2>
                   {stats && (
@@ -432,35 +829,123 @@ function App() {
                       <span className="text-gray-300">
                         Range: <span className="text-blue-400 font-bold">{stats.min}</span>-<span className="text-orange-400 font-bold">{stats.max}</span>
                       </span>
+                      {stats.abstained > 0 && (
+                        <span className="text-gray-300">{stats.abstained} abstained</span>
+                      )}
+                      {stats.risk !== null && (
+                        <span className="text-gray-300">
+                          Risk: <span className="text-red-400 font-bold">{stats.risk}</span>
+                        </span>
+                      )}
                     </div>
                   )}
+                  {currentDrift && (
+                    <p className="text-sm text-gray-300">
+                      Previously estimated at <span className="font-bold">{currentDrift.previous}</span>
+                      {currentDrift.drift !== 0 && (
+                        <span className={currentDrift.drift > 0 ? "text-orange-400" : "text-blue-400"}>
+                          {" "}({currentDrift.drift > 0 ? "+" : ""}{currentDrift.drift})
+                        </span>
+                      )}
+                    </p>
+                  )}
+                  <button
+                    onClick={copyRoundSummary}
+                    className="inline-flex items-center gap-1 text-sm text-gray-400 hover:text-white"
+                  >
+                    {summaryCopied ? <Check className="w-3 h-3" /> : <Copy className="w-3 h-3" />}
+                    {summaryCopied ? "Copied" : "Copy summary"}
+                  </button>
+                </div>
+              ) : room?.reveal_progress ? (
+                <div className="space-y-1">
+                  <h2 className="text-xl font-bold text-white">Revealing...</h2>
+                  <p className="text-gray-400 text-sm">Cards are turned over one at a time</p>
+                </div>
+              ) : votingClosed ? (
+                <div className="space-y-1">
+                  <h2 className="text-xl font-bold text-white">
+                    {votingCountdown !== null ? `Voting opens in ${votingCountdown}s` : "Discussion"}
+                  </h2>
+                  <p className="text-gray-400 text-sm">Read the story and talk it through; voting opens when the host says so</p>
                 </div>
               ) : (
                 <div className="space-y-1">
-                  <h2 className="text-xl font-bold text-white">Place Your Bet</h2>
+                  <h2 className="text-xl font-bold text-white">{isObserver ? "Watching" : "Place Your Bet"}</h2>
                   <p className="text-gray-400 text-sm">
-                    {votedCount === totalParticipants && totalParticipants > 0
+                    {isObserver
+                      ? "You're observing; the votes show up here when the host reveals them"
+                      : votedCount === totalParticipants && totalParticipants > 0
                       ? "Everyone voted! Waiting for reveal..." 
-                      : "Select a chip to vote"}
+                      : room?.hide_voters
+                        ? "Select a chip to vote. Who has voted stays hidden until everyone has."
+                        : "Select a chip to vote"}
                   </p>
                 </div>
               )}
             </div>
 
             {/* Poker chips */}
-            {!room?.votes_revealed && (
+            {!isObserver && !room?.votes_revealed && !room?.reveal_progress && (
               <div className="flex flex-wrap justify-center gap-3">
-                {STORY_POINTS.map((value) => (
+                {(room?.deck ?? STORY_POINTS).map((value) => (
                   <PokerChip
                     key={value}
                     value={value}
                     selected={selectedVote === value}
-                    onClick={() => handleVote(value)}
+                    onClick={votingClosed ? undefined : () => handleVote(value)}
                   />
                 ))}
               </div>
             )}
 
+            {/* Risk chips, for rooms estimating uncertainty as well as effort */}
+            {!isObserver && room?.risk_deck && !room.votes_revealed && (
+              <div className="mt-6">
+                <p className="text-center text-gray-400 text-sm mb-3">How risky or uncertain is it? (1 = well understood, 5 = lots of unknowns)</p>
+                <div className="flex flex-wrap justify-center gap-3">
+                  {room.risk_deck.map((value) => (
+                    <PokerChip
+                      key={value}
+                      value={value}
+                      selected={selectedRisk === value}
+                      onClick={votingClosed ? undefined : () => handleRiskVote(value)}
+                    />
+                  ))}
+                </div>
+              </div>
+            )}
+
+            {/* Hand your vote to someone else for this round */}
+            {!isObserver && !room?.votes_revealed && !room?.reveal_progress && otherVoters.length > 0 && (
+              <div className="flex items-center justify-center gap-2 mt-4 text-sm text-gray-400">
+                <label htmlFor="delegate">Hand my vote to</label>
+                <select
+                  id="delegate"
+                  value={myDelegate ?? ""}
+                  onChange={(e) => handleDelegate(e.target.value || null)}
+                  disabled={votingClosed}
+                  className="bg-gray-800 border border-gray-700 rounded px-2 py-1 text-white"
+                >
+                  <option value="">Nobody, I'll vote</option>
+                  {otherVoters.map((p) => (
+                    <option key={p.id} value={p.id}>{p.name}</option>
+                  ))}
+                </select>
+              </div>
+            )}
+
+            {!isObserver && !room?.votes_revealed && !room?.reveal_progress && (
+              <div className="flex justify-center mt-3">
+                <button
+                  onClick={() => handleSitOut(!currentParticipant?.sitting_out)}
+                  className="text-sm text-gray-400 hover:text-white underline"
+                >
+                  {currentParticipant?.sitting_out ? "I'm back, count me in" : "Sit this one out"}
+                </button>
+              </div>
+            )}
+
             {/* Your vote indicator */}
             {currentParticipant?.vote && !room?.votes_revealed && (
               <div className="flex items-center justify-center gap-2 mt-4 text-green-400 text-sm">
@@ -477,7 +962,7 @@ function App() {
         <div className="max-w-7xl mx-auto">
           <div className="flex items-center gap-2 mb-3">
             <Users className="w-4 h-4 text-gray-400" />
-            <span className="text-sm font-medium text-gray-300">Participants ({totalParticipants})</span>
+            <span className="text-sm font-medium text-gray-300">Participants ({room?.participants.length ?? 0})</span>
           </div>
           <div className="flex flex-wrap gap-3">
             {/* Current user first */}
@@ -485,8 +970,11 @@ function App() {
               <ParticipantCard
                 name={currentParticipant.name}
                 vote={currentParticipant.vote}
-                revealed={room?.votes_revealed ?? false}
+                revealed={cardsFlipped || !!room?.reveal_progress?.includes(currentParticipant.id)}
                 isYou={true}
+                sittingOut={currentParticipant.sitting_out}
+                observer={currentParticipant.is_observer}
+                votingFor={delegations[currentParticipant.id] ? nameOf(delegations[currentParticipant.id]) : undefined}
               />
             )}
             
@@ -496,8 +984,12 @@ function App() {
                 key={participant.id}
                 name={participant.name}
                 vote={participant.vote}
-                revealed={room?.votes_revealed ?? false}
+                revealed={cardsFlipped || !!room?.reveal_progress?.includes(participant.id)}
                 isYou={false}
+                sittingOut={participant.sitting_out}
+                observer={participant.is_observer}
+                votingFor={delegations[participant.id] ? nameOf(delegations[participant.id]) : undefined}
+                disconnected={participant.disconnected_at != null}
               />
             ))}
           </div>
@@ -512,19 +1004,33 @@ interface ParticipantCardProps {
   vote: string | null;
   revealed: boolean;
   isYou: boolean;
+  /** Connection dropped; shown faded until they're back or removed */
+  disconnected?: boolean;
+  /** Name of whoever this participant handed their vote to */
+  votingFor?: string;
+  /** Not voting for now */
+  sittingOut?: boolean;
+  /** Only watching; never votes */
+  observer?: boolean;
 }
 
-function ParticipantCard({ name, vote, revealed, isYou }: ParticipantCardProps) {
+function ParticipantCard({ name, vote, revealed, isYou, disconnected, votingFor, sittingOut, observer }: ParticipantCardProps) {
   return (
-    <div className={`flex flex-col items-center gap-2 p-3 rounded-md ${isYou ? "bg-green-500/10 border border-green-500/30" : "bg-gray-800/50"}`}>
+    <div
+      className={`flex flex-col items-center gap-2 p-3 rounded-md ${isYou ? "bg-green-500/10 border border-green-500/30" : "bg-gray-800/50"} ${disconnected ? "opacity-40" : ""}`}
+      title={disconnected ? "Reconnecting…" : undefined}
+    >
       <div className="w-12 h-12 rounded-full bg-gray-700 flex items-center justify-center text-lg font-bold text-white">
         {name.charAt(0).toUpperCase()}
       </div>
       <span className={`text-sm font-medium truncate max-w-full ${isYou ? "text-green-300" : "text-gray-300"}`}>
         {name} {isYou && "(You)"}
       </span>
+      {votingFor && <span className="text-xs text-gray-500">→ {votingFor}</span>}
+      {sittingOut && <span className="text-xs text-gray-500">sitting out</span>}
+      {observer && <span className="text-xs text-gray-500">observing</span>}
       <div className="h-12 flex items-center justify-center">
-        {vote !== null ? (
+        {observer ? null : vote !== null ? (
           revealed ? (
             <PokerChip value={vote} mini revealed />
           ) : (
@@ -6,6 +6,27 @@ export interface JiraTicket {
   issue_type: string | null;
   status: string | null;
   url: string;
+  /** Size hints from the description, shown before voting */
+  complexity?: ComplexityHints | null;
+  /** Size of the change, for GitHub pull requests */
+  diff_stats?: DiffStats | null;
+}
+
+/** Lines and files a pull request touches */
+export interface DiffStats {
+  additions: number;
+  deletions: number;
+  changed_files: number;
+  commits: number;
+}
+
+/** Heuristic signals about a ticket's size */
+export interface ComplexityHints {
+  level: "low" | "medium" | "high";
+  word_count: number;
+  acceptance_criteria: number;
+  linked_issues: number;
+  has_code_blocks: boolean;
 }
 
 /** Participant in a room */
@@ -13,7 +34,34 @@ export interface Participant {
   id: string;
   name: string;
   vote: string | null;
+  /** Vote on the room's risk deck, if it has one */
+  risk_vote?: string | null;
   is_host: boolean;
+  /** Server-assigned display color */
+  color?: string;
+  /** Client details, present only if the participant opted in */
+  client?: ClientInfo;
+  /** Unix seconds when their connection dropped; they're kept for a grace period */
+  disconnected_at?: number;
+  /** Sitting out of the voting for now; not counted towards the quorum */
+  sitting_out?: boolean;
+  /** Joined only to watch; never votes and isn't counted among the voters */
+  is_observer?: boolean;
+}
+
+/** A concern a participant can flag about the ticket being estimated */
+export type TicketSignal = "unclear_requirements" | "needs_design" | "security_review";
+
+export const TICKET_SIGNALS: { signal: TicketSignal; label: string }[] = [
+  { signal: "unclear_requirements", label: "Unclear requirements" },
+  { signal: "needs_design", label: "Needs design" },
+  { signal: "security_review", label: "Security review" },
+];
+
+/** Coarse client details shared with the host for troubleshooting */
+export interface ClientInfo {
+  kind: "browser" | "desktop" | "tui" | "other";
+  user_agent_family: string | null;
 }
 
 /** Scrum poker room */
@@ -22,20 +70,135 @@ export interface Room {
   name: string;
   participants: Participant[];
   votes_revealed: boolean;
+  /** When to flip the cards over (Unix ms), so every client does it together */
+  reveal_at?: number | null;
+  /** Participants whose votes have been shown so far in a one-by-one reveal */
+  reveal_progress?: string[] | null;
+  /** Votes handed to someone else this round: delegator ID -> delegate ID */
+  delegations?: Record<string, string>;
+  /** Your own signals about the current ticket, keyed by your participant ID */
+  signals?: Record<string, TicketSignal[]>;
   created_at: number;
   invite_code: string;
   current_ticket: JiraTicket | null;
+  /** Tickets voted on together in a triage round, with one vote for all; empty otherwise */
+  batch?: JiraTicket[];
+  /** Re-estimation campaign results; null outside a campaign */
+  campaign?: Campaign | null;
+  /** Hash of the current ticket; updates leave the ticket out and only send this */
+  ticket_hash?: string | null;
+  deck: string[];
+  /** Cards for the second (risk/uncertainty) vote; null when the room only votes on effort */
+  risk_deck: string[] | null;
+  /** Bumped on every update; a gap means an update was missed */
+  revision: number;
+  /** Whether enough people have voted for the host's reveal rules */
+  can_reveal?: boolean;
+  /** Sprint fill meter; null when the host isn't tracking capacity */
+  sprint_fill?: SprintFill | null;
+  /** The story is being discussed; voting is closed until the host opens it */
+  discussing?: boolean;
+  /** When voting opens after a discussion (Unix ms), for the countdown */
+  voting_opens_at?: number | null;
+  /** Other people's votes are left out until everyone has voted or the votes are revealed */
+  hide_voters?: boolean;
+}
+
+/** How far a re-estimated ticket moved from its old estimate */
+export interface Drift {
+  ticket_key: string;
+  previous: number;
+  estimate: number;
+  /** estimate - previous */
+  drift: number;
+  /** Whether the new estimate has been written back to Jira */
+  written: boolean;
+}
+
+/** Re-estimation of previously estimated tickets */
+export interface Campaign {
+  /** Story points field the old estimates came from */
+  field: string;
+  /** Old estimates by ticket key; only the host gets these */
+  previous: Record<string, number>;
+  results: Drift[];
+}
+
+/** How much of the sprint the session's estimates take up */
+export interface SprintFill {
+  capacity: number;
+  committed: number;
+  tickets: number;
+  /** committed / capacity */
+  fraction: number;
+  overcommitted: boolean;
+}
+
+/** Final snapshot of a session, sent when the host ends it */
+export interface SessionSummary {
+  room_name: string;
+  started_at: number;
+  ended_at: number;
+  duration_secs: number;
+  rounds: number;
+  /** Estimates the host overrode */
+  overrides: number;
+  tickets_estimated: string[];
+  total_points: number;
+  average_time_to_vote_secs: number | null;
+  attendance: string[];
+}
+
+/** Snapshot sent to a participant right after they join */
+export interface CatchUp {
+  participant_id: string;
+  room: Room;
+  voting_open: boolean;
+  vote_summary: { total_voters: number; voted_count: number; abstained_count?: number; average: number | null; suggested: string | null; risk_voted_count: number; risk_average: number | null };
+  recent_history: Array<{ timestamp: number; type: string } & Record<string, unknown>>;
 }
 
 /** Story point values */
 export const STORY_POINTS = ["?", "☕", "0", "0.5", "1", "2", "3", "5", "8", "13", "20", "40", "100"];
 
+/** Card for sitting a ticket out on purpose; counts toward the quorum but not the average */
+export const ABSTAIN = "abstain";
+
+/** Machine-readable reason attached to errors */
+export type ErrorCode =
+  | "RoomNotFound"
+  | "RoomFull"
+  | "InvalidVote"
+  | "InvalidDelegation"
+  | "InvalidName"
+  | "Banned"
+  | "PinRequired"
+  | "LanOnly"
+  | "SessionEnded"
+  | "RoomLocked"
+  | "Throttled"
+  | "VotingClosed"
+  | "SeatLimitReached"
+  | "Other";
+
 /** WebSocket message types */
 export type WsMessage =
-  | { type: "Join"; payload: { room_id: string; name: string } }
+  | { type: "Join"; payload: { room_id: string; name: string; identity?: string; client?: ClientInfo; observer?: boolean } }
   | { type: "Vote"; payload: { vote: string | null } }
-  | { type: "RoomUpdate"; payload: { room: Room } }
-  | { type: "Error"; payload: { message: string } }
+  | { type: "RiskVote"; payload: { vote: string | null } }
+  | { type: "Delegate"; payload: { to: string | null } }
+  | { type: "SitOut"; payload: { sitting_out: boolean } }
+  | { type: "Signal"; payload: { signal: TicketSignal; raised: boolean } }
+  | { type: "Rename"; payload: { name: string } }
+  | { type: "RoomUpdate"; payload: { room: Room; checksum: string; server_time: number } }
+  | { type: "VoteRevealed"; payload: { participant_id: string; vote: string | null } }
+  | { type: "TicketChanged"; payload: { ticket: JiraTicket | null; ticket_hash: string | null } }
+  | { type: "Resync" }
+  | { type: "Error"; payload: { code: ErrorCode; message: string } }
+  | { type: "CatchUp"; payload: CatchUp }
   | { type: "Kicked" }
+  | { type: "SessionEnded"; payload: { summary: SessionSummary } }
+  | { type: "TimeSync"; payload: { client_time: number } }
+  | { type: "TimeSyncReply"; payload: { client_time: number; server_time: number } }
   | { type: "Ping" }
   | { type: "Pong" };
@@ -6,6 +6,10 @@ export default defineConfig({
   server: {
     port: 5174,
     cors: true,
+    // When the desktop app proxies the dev server, the HMR socket still connects to Vite directly
+    hmr: {
+      clientPort: 5174,
+    },
   },
   build: {
     outDir: "dist",